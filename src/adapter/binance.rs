@@ -1,9 +1,14 @@
 use reqwest::Client;
 use serde::Deserialize;
 
-use super::types::{OrderRequest, OrderResponse, OrderType, Side};
-use super::unified::UnifiedAdapter;
+use super::throttle::{self, Throttle, ThrottleStats};
+use super::types::{
+    FillEvent, OrderRequest, OrderResponse, OrderStatus, OrderType, Side, POST_ONLY_REJECTED,
+};
+use super::unified::{OrderFuture, UnifiedAdapter};
 use crate::exchange::signing::sign_binance;
+use crate::exchange::symbol::normalize;
+use crate::exchange::ExchangeKind;
 
 pub struct BinanceAdapter {
     client: Client,
@@ -11,17 +16,18 @@ pub struct BinanceAdapter {
     api_key: String,
     api_secret: String,
     runtime: tokio::runtime::Handle,
+    throttle: &'static Throttle,
 }
 
 impl BinanceAdapter {
-    pub fn new(api_key: String, api_secret: String) -> Self {
+    pub fn new(base: String, api_key: String, api_secret: String) -> Self {
         Self {
             client: Client::new(),
-            base: std::env::var("BINANCE_BASE")
-                .unwrap_or_else(|_| "https://api.binance.com".to_string()),
+            base,
             api_key,
             api_secret,
             runtime: tokio::runtime::Handle::current(),
+            throttle: throttle::binance(),
         }
     }
 
@@ -29,28 +35,16 @@ impl BinanceAdapter {
         chrono::Utc::now().timestamp_millis() as u64
     }
 
-    async fn place_order_async(&self, req: OrderRequest) -> Result<OrderResponse, String> {
-        let timestamp = Self::timestamp_ms();
-        let recv_window = 5000u64;
-
-        let side = match req.side {
-            Side::Buy => "BUY",
-            Side::Sell => "SELL",
-        };
-
-        let order_type = match req.order_type {
-            OrderType::Market => "MARKET",
-            OrderType::Limit => "LIMIT",
-        };
-
-        let mut query = format!(
-            "symbol={}&side={}&type={}&quantity={:.8}&newClientOrderId={}&timestamp={}&recvWindow={}",
-            req.symbol, side, order_type, req.qty, req.client_id, timestamp, recv_window
-        );
+    /// `SYMBOL` in Binance spelling, for calls that don't carry a symbol.
+    fn configured_symbol() -> String {
+        let symbol = std::env::var("SYMBOL").unwrap_or_else(|_| "BTCUSDT".to_string());
+        normalize(&symbol, ExchangeKind::Binance)
+    }
 
-        if let (OrderType::Limit, Some(price)) = (req.order_type, req.price) {
-            query.push_str(&format!("&price={:.8}&timeInForce=GTC", price));
-        }
+    async fn place_order_async(&self, req: OrderRequest) -> Result<OrderResponse, String> {
+        // Wait before stamping, so a queued request can't outlive recvWindow
+        self.throttle.acquire(1.0).await;
+        let query = order_query(&req, Self::timestamp_ms(), 5000)?;
 
         let signature =
             sign_binance(&query, &self.api_secret).map_err(|e| format!("signing failed: {}", e))?;
@@ -81,7 +75,7 @@ impl BinanceAdapter {
                 code: -1,
                 msg: body.clone(),
             });
-            return Err(format!("Binance error {}: {}", err.code, err.msg));
+            return Err(reject_message(err.code, &err.msg, req.post_only));
         }
 
         #[derive(Deserialize)]
@@ -100,9 +94,109 @@ impl BinanceAdapter {
         })
     }
 
+    async fn signed_get(&self, path: &str, query: String) -> Result<String, String> {
+        let signature =
+            sign_binance(&query, &self.api_secret).map_err(|e| format!("signing failed: {}", e))?;
+        let url = format!("{}{}?{}&signature={}", self.base, path, query, signature);
+        let resp = self
+            .client
+            .get(&url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| format!("request failed: {}", e))?;
+        let status = resp.status();
+        let body = resp
+            .text()
+            .await
+            .map_err(|e| format!("read body failed: {}", e))?;
+        if !status.is_success() {
+            return Err(format!("Binance error: {}", body));
+        }
+        Ok(body)
+    }
+
+    async fn fetch_order_status_async(
+        &self,
+        symbol: &str,
+        client_id: &str,
+    ) -> Result<OrderStatus, String> {
+        let symbol = normalize(symbol, ExchangeKind::Binance);
+        self.throttle.acquire(1.0).await;
+        let query = format!(
+            "symbol={}&origClientOrderId={}&timestamp={}&recvWindow=5000",
+            symbol,
+            client_id,
+            Self::timestamp_ms()
+        );
+        let body = self.signed_get("/api/v3/order", query).await?;
+        parse_order_status(&body)
+    }
+
+    async fn fetch_order_fills_async(
+        &self,
+        symbol: &str,
+        client_id: &str,
+    ) -> Result<Vec<FillEvent>, String> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct BinanceOrder {
+            order_id: u64,
+        }
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct BinanceTrade {
+            id: u64,
+            order_id: u64,
+            price: String,
+            qty: String,
+            commission: String,
+            time: u64,
+            is_buyer: bool,
+        }
+
+        let symbol = normalize(symbol, ExchangeKind::Binance);
+        self.throttle.acquire(1.0).await;
+        let query = format!(
+            "symbol={}&origClientOrderId={}&timestamp={}&recvWindow=5000",
+            symbol,
+            client_id,
+            Self::timestamp_ms()
+        );
+        let body = self.signed_get("/api/v3/order", query).await?;
+        let order: BinanceOrder =
+            serde_json::from_str(&body).map_err(|e| format!("parse error: {}", e))?;
+
+        self.throttle.acquire(1.0).await;
+        let query = format!(
+            "symbol={}&orderId={}&timestamp={}&recvWindow=5000",
+            symbol,
+            order.order_id,
+            Self::timestamp_ms()
+        );
+        let body = self.signed_get("/api/v3/myTrades", query).await?;
+        let trades: Vec<BinanceTrade> =
+            serde_json::from_str(&body).map_err(|e| format!("parse error: {}", e))?;
+        Ok(trades
+            .into_iter()
+            .map(|t| {
+                let qty: f64 = t.qty.parse().unwrap_or(0.0);
+                FillEvent {
+                    order_id: t.order_id.to_string(),
+                    fill_id: t.id.to_string(),
+                    qty: if t.is_buyer { qty } else { -qty },
+                    price: t.price.parse().unwrap_or(0.0),
+                    fee: t.commission.parse().unwrap_or(0.0),
+                    ts: t.time / 1000,
+                }
+            })
+            .collect())
+    }
+
     async fn cancel_order_async(&self, order_id: &str) -> Result<(), String> {
+        self.throttle.acquire(1.0).await;
         let timestamp = Self::timestamp_ms();
-        let symbol = std::env::var("SYMBOL").unwrap_or_else(|_| "BTCUSDT".to_string());
+        let symbol = Self::configured_symbol();
 
         let query = format!(
             "symbol={}&orderId={}&timestamp={}&recvWindow=5000",
@@ -131,8 +225,9 @@ impl BinanceAdapter {
     }
 
     async fn cancel_all_async(&self) -> Result<(), String> {
+        self.throttle.acquire(1.0).await;
         let timestamp = Self::timestamp_ms();
-        let symbol = std::env::var("SYMBOL").unwrap_or_else(|_| "BTCUSDT".to_string());
+        let symbol = Self::configured_symbol();
 
         let query = format!("symbol={}&timestamp={}&recvWindow=5000", symbol, timestamp);
 
@@ -158,11 +253,75 @@ impl BinanceAdapter {
     }
 }
 
+/// Query string for a spot order, with the symbol in Binance spelling so a
+/// canonical config symbol (`BTC/USDT`) is accepted. Post-only maps to `LIMIT_MAKER`. Spot has
+/// no reduce-only flag and cannot go short, so a reduce-only sell can never
+/// flip the position; the flag is only enforced by sizing (`Action::Close`).
+fn order_query(req: &OrderRequest, timestamp: u64, recv_window: u64) -> Result<String, String> {
+    let side = match req.side {
+        Side::Buy => "BUY",
+        Side::Sell => "SELL",
+    };
+
+    let order_type = match (req.order_type, req.post_only) {
+        (OrderType::Limit, true) => "LIMIT_MAKER",
+        (OrderType::Limit, false) => "LIMIT",
+        (OrderType::Market, true) => return Err("post-only order needs a limit price".to_string()),
+        (OrderType::Market, false) => "MARKET",
+    };
+
+    let mut query = format!(
+        "symbol={}&side={}&type={}&quantity={:.8}&newClientOrderId={}&timestamp={}&recvWindow={}",
+        normalize(&req.symbol, ExchangeKind::Binance),
+        side,
+        order_type,
+        req.qty,
+        req.client_id,
+        timestamp,
+        recv_window
+    );
+
+    if let (OrderType::Limit, Some(price)) = (req.order_type, req.price) {
+        query.push_str(&format!("&price={:.8}", price));
+        // LIMIT_MAKER takes no time in force
+        if !req.post_only {
+            query.push_str("&timeInForce=GTC");
+        }
+    }
+    Ok(query)
+}
+
+/// Status field of a `GET /api/v3/order` response.
+fn parse_order_status(body: &str) -> Result<OrderStatus, String> {
+    #[derive(Deserialize)]
+    struct BinanceOrderStatus {
+        status: String,
+    }
+    let order: BinanceOrderStatus =
+        serde_json::from_str(body).map_err(|e| format!("parse error: {}", e))?;
+    Ok(OrderStatus::parse(&order.status))
+}
+
+/// Error string for a rejected order; would-cross post-only rejects carry
+/// [`POST_ONLY_REJECTED`] so callers can tell them apart.
+fn reject_message(code: i64, msg: &str, post_only: bool) -> String {
+    let would_cross = code == -2010 && msg.contains("immediately match");
+    if post_only && would_cross {
+        format!("{}: Binance error {}: {}", POST_ONLY_REJECTED, code, msg)
+    } else {
+        format!("Binance error {}: {}", code, msg)
+    }
+}
+
 impl UnifiedAdapter for BinanceAdapter {
     fn place_order(&mut self, req: OrderRequest) -> Result<OrderResponse, String> {
         self.runtime.block_on(self.place_order_async(req))
     }
 
+    fn submit_order(&self, req: &OrderRequest) -> Option<OrderFuture<'_>> {
+        Some(Box::pin(self.place_order_async(req.clone())))
+    }
+
     fn cancel_order(&mut self, order_id: &str) -> Result<(), String> {
         self.runtime.block_on(self.cancel_order_async(order_id))
     }
@@ -170,15 +329,121 @@ impl UnifiedAdapter for BinanceAdapter {
     fn cancel_all(&mut self) -> Result<(), String> {
         self.runtime.block_on(self.cancel_all_async())
     }
+
+    fn fetch_order_fills(
+        &mut self,
+        symbol: &str,
+        client_id: &str,
+    ) -> Result<Vec<FillEvent>, String> {
+        self.runtime
+            .block_on(self.fetch_order_fills_async(symbol, client_id))
+    }
+
+    fn fetch_order_status(&mut self, symbol: &str, client_id: &str) -> Result<OrderStatus, String> {
+        self.runtime
+            .block_on(self.fetch_order_status_async(symbol, client_id))
+    }
+
+    fn throttle_stats(&self) -> Option<ThrottleStats> {
+        Some(self.throttle.stats())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::adapter::types::{classify_reject, RejectKind};
 
     #[test]
     fn test_timestamp() {
         let ts = BinanceAdapter::timestamp_ms();
         assert!(ts > 1700000000000); // sanity check
     }
+
+    fn req(order_type: OrderType, post_only: bool) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: Side::Sell,
+            order_type,
+            price: Some(50_000.0),
+            qty: 0.01,
+            client_id: "CID-1".to_string(),
+            reduce_only: false,
+            post_only,
+        }
+    }
+
+    #[test]
+    fn post_only_limit_becomes_limit_maker() {
+        let q = order_query(&req(OrderType::Limit, true), 1, 5000).unwrap();
+        assert!(q.contains("type=LIMIT_MAKER"));
+        assert!(!q.contains("timeInForce"));
+        let q = order_query(&req(OrderType::Limit, false), 1, 5000).unwrap();
+        assert!(q.contains("type=LIMIT&") && q.contains("timeInForce=GTC"));
+        assert!(order_query(&req(OrderType::Market, true), 1, 5000).is_err());
+    }
+
+    #[tokio::test]
+    async fn market_data_draws_from_the_order_bucket() {
+        let adapter = BinanceAdapter::new(String::new(), String::new(), String::new());
+        let before = adapter.throttle_stats().unwrap().requests;
+        // What `exchange::binance` does before a kline or book fetch
+        throttle::binance().acquire(0.0).await;
+        assert!(adapter.throttle_stats().unwrap().requests > before);
+    }
+
+    #[test]
+    fn order_query_uses_binance_symbol_spelling() {
+        let mut canonical = req(OrderType::Market, false);
+        canonical.symbol = "btc/usdt".to_string();
+        let q = order_query(&canonical, 1, 5000).unwrap();
+        assert!(q.starts_with("symbol=BTCUSDT&"), "{}", q);
+        let q = order_query(&req(OrderType::Market, false), 1, 5000).unwrap();
+        assert!(q.starts_with("symbol=BTCUSDT&"), "{}", q);
+    }
+
+    #[test]
+    fn order_status_parsed_from_query_response() {
+        let body = |status: &str| {
+            format!(
+                r#"{{"symbol":"BTCUSDT","orderId":28,"clientOrderId":"CID-1","status":"{}"}}"#,
+                status
+            )
+        };
+        assert_eq!(parse_order_status(&body("NEW")), Ok(OrderStatus::Open));
+        assert_eq!(
+            parse_order_status(&body("PARTIALLY_FILLED")),
+            Ok(OrderStatus::PartiallyFilled)
+        );
+        assert_eq!(parse_order_status(&body("FILLED")), Ok(OrderStatus::Filled));
+        assert_eq!(
+            parse_order_status(&body("CANCELED")),
+            Ok(OrderStatus::Canceled)
+        );
+        assert_eq!(
+            parse_order_status(&body("EXPIRED_IN_MATCH")),
+            Ok(OrderStatus::Expired)
+        );
+        assert_eq!(
+            parse_order_status(&body("SOMETHING_NEW")),
+            Ok(OrderStatus::Unknown)
+        );
+        assert!(parse_order_status("{}").is_err());
+    }
+
+    #[test]
+    fn post_only_reject_is_classified_separately() {
+        let cross = reject_message(-2010, "Order would immediately match and take.", true);
+        assert_eq!(classify_reject(&cross), RejectKind::PostOnlyWouldCross);
+
+        // Same venue code for a non-post-only order, and other rejects, are ordinary
+        let plain = reject_message(-2010, "Order would immediately match and take.", false);
+        assert_eq!(classify_reject(&plain), RejectKind::Other);
+        let funds = reject_message(-2010, "Account has insufficient balance.", true);
+        assert_eq!(classify_reject(&funds), RejectKind::Other);
+        assert_eq!(
+            classify_reject("request failed: timeout"),
+            RejectKind::Other
+        );
+    }
 }
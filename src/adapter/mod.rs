@@ -1,3 +1,4 @@
 pub mod binance;
+pub mod throttle;
 pub mod types;
 pub mod unified;
@@ -0,0 +1,176 @@
+//! Client-side token bucket for venue request-rate limits.
+//!
+//! Binance answers 429 (and eventually an IP ban) once a window's request
+//! budget is spent, and retrying after the fact only adds to the pile. The
+//! bucket holds up to `capacity` tokens, refilled continuously at
+//! `capacity / window`; every request reserves its cost before sending and
+//! sleeps until the reservation is covered. The balance may go negative, so
+//! concurrent callers queue behind each other instead of racing for the
+//! next token.
+//!
+//! Binance counts requests per IP, so every Binance call in the process
+//! (market data, aux data, orders) draws from the one bucket in [`binance`].
+
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Cumulative throttle activity, exported as metrics.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ThrottleStats {
+    /// Requests that passed through the bucket.
+    pub requests: u64,
+    /// Requests that had to wait for capacity.
+    pub waits: u64,
+    /// Total time spent waiting, in seconds.
+    pub waited_secs: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    tokens: f64,
+    last: Instant,
+    stats: ThrottleStats,
+}
+
+impl TokenBucket {
+    /// Allow `requests` per `window`, starting full so a cold start can burst.
+    pub fn new(requests: u32, window: Duration) -> Self {
+        let capacity = requests.max(1) as f64;
+        let window = window.as_secs_f64().max(1e-3);
+        Self {
+            capacity,
+            refill_per_sec: capacity / window,
+            tokens: capacity,
+            last: Instant::now(),
+            stats: ThrottleStats::default(),
+        }
+    }
+
+    /// `API_RATE_LIMIT` requests per `API_RATE_WINDOW_MS` (default 10 per second).
+    pub fn from_env() -> Self {
+        let requests = std::env::var("API_RATE_LIMIT")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(10);
+        let window_ms = std::env::var("API_RATE_WINDOW_MS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(1000);
+        Self::new(requests, Duration::from_millis(window_ms))
+    }
+
+    /// Take `cost` tokens at `now` and return how long the caller must wait
+    /// before sending. Deterministic in `now`, so tests drive the clock.
+    pub fn reserve_at(&mut self, now: Instant, cost: f64) -> Duration {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        if now > self.last {
+            self.last = now;
+        }
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.tokens -= cost;
+        self.stats.requests += 1;
+        if self.tokens >= 0.0 {
+            return Duration::ZERO;
+        }
+        let wait = -self.tokens / self.refill_per_sec;
+        self.stats.waits += 1;
+        self.stats.waited_secs += wait;
+        Duration::from_secs_f64(wait)
+    }
+
+    pub fn stats(&self) -> ThrottleStats {
+        self.stats
+    }
+}
+
+/// A bucket shared by an adapter's `&self` request paths.
+#[derive(Debug)]
+pub struct Throttle(Mutex<TokenBucket>);
+
+impl Throttle {
+    pub fn new(bucket: TokenBucket) -> Self {
+        Self(Mutex::new(bucket))
+    }
+
+    /// Wait until `cost` tokens are available. The lock is only held for
+    /// the reservation, never across the sleep.
+    pub async fn acquire(&self, cost: f64) {
+        let wait = {
+            let mut bucket = self.0.lock().unwrap_or_else(|e| e.into_inner());
+            bucket.reserve_at(Instant::now(), cost)
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    pub fn stats(&self) -> ThrottleStats {
+        self.0.lock().unwrap_or_else(|e| e.into_inner()).stats()
+    }
+}
+
+static BINANCE: OnceLock<Throttle> = OnceLock::new();
+
+/// The process-wide Binance bucket, built from the environment on first use.
+pub fn binance() -> &'static Throttle {
+    BINANCE.get_or_init(|| Throttle::new(TokenBucket::from_env()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(d: Duration) -> u128 {
+        d.as_millis()
+    }
+
+    #[test]
+    fn test_burst_is_smoothed_to_configured_rate() {
+        let mut bucket = TokenBucket::new(5, Duration::from_secs(1));
+        let t0 = Instant::now();
+        let waits: Vec<u128> = (0..10).map(|_| ms(bucket.reserve_at(t0, 1.0))).collect();
+        // Full bucket absorbs the first 5, then one request every 200ms
+        assert_eq!(waits, vec![0, 0, 0, 0, 0, 200, 400, 600, 800, 1000]);
+
+        let stats = bucket.stats();
+        assert_eq!(stats.requests, 10);
+        assert_eq!(stats.waits, 5);
+        assert!((stats.waited_secs - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let mut bucket = TokenBucket::new(5, Duration::from_secs(1));
+        let t0 = Instant::now();
+        for _ in 0..5 {
+            bucket.reserve_at(t0, 1.0);
+        }
+        // 600ms later three tokens have come back
+        let t1 = t0 + Duration::from_millis(600);
+        for _ in 0..3 {
+            assert_eq!(bucket.reserve_at(t1, 1.0), Duration::ZERO);
+        }
+        assert_eq!(ms(bucket.reserve_at(t1, 1.0)), 200);
+
+        // A long idle refills only up to capacity
+        let t2 = t1 + Duration::from_secs(60);
+        for _ in 0..5 {
+            assert_eq!(bucket.reserve_at(t2, 1.0), Duration::ZERO);
+        }
+        assert!(bucket.reserve_at(t2, 1.0) > Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_capacity() {
+        let throttle = Throttle::new(TokenBucket::new(100, Duration::from_secs(1)));
+        let start = Instant::now();
+        for _ in 0..105 {
+            throttle.acquire(1.0).await;
+        }
+        // Five requests past a full bucket at 100/s need ~50ms
+        assert!(start.elapsed() >= Duration::from_millis(45));
+        assert_eq!(throttle.stats().waits, 5);
+    }
+}
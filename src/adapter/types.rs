@@ -24,6 +24,28 @@ pub struct OrderRequest {
     pub price: Option<f64>,
     pub qty: f64,
     pub client_id: String,
+    /// Never increase or flip the position; set for every `Action::Close`.
+    pub reduce_only: bool,
+    /// Maker-only: the venue rejects the order rather than let it cross.
+    pub post_only: bool,
+}
+
+/// Prefix adapters put on a post-only order the venue refused because it
+/// would have crossed the book. Unlike other rejects it is safe to retry.
+pub const POST_ONLY_REJECTED: &str = "post_only_rejected";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectKind {
+    PostOnlyWouldCross,
+    Other,
+}
+
+pub fn classify_reject(err: &str) -> RejectKind {
+    if err.starts_with(POST_ONLY_REJECTED) {
+        RejectKind::PostOnlyWouldCross
+    } else {
+        RejectKind::Other
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -32,6 +54,51 @@ pub struct OrderResponse {
     pub status: String,
 }
 
+/// Where an order stands on the venue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    Open,
+    PartiallyFilled,
+    Filled,
+    Canceled,
+    Rejected,
+    Expired,
+    /// The venue can't say, or the adapter can't ask.
+    Unknown,
+}
+
+impl OrderStatus {
+    /// Map a venue status string (Binance spelling) to a status.
+    pub fn parse(status: &str) -> Self {
+        match status {
+            "NEW" | "PENDING_NEW" => OrderStatus::Open,
+            "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+            "FILLED" => OrderStatus::Filled,
+            "CANCELED" | "PENDING_CANCEL" => OrderStatus::Canceled,
+            "REJECTED" => OrderStatus::Rejected,
+            "EXPIRED" | "EXPIRED_IN_MATCH" => OrderStatus::Expired,
+            _ => OrderStatus::Unknown,
+        }
+    }
+
+    /// Still working on the book, so it can still be cancelled.
+    pub fn is_open(self) -> bool {
+        matches!(self, OrderStatus::Open | OrderStatus::PartiallyFilled)
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            OrderStatus::Open => "open",
+            OrderStatus::PartiallyFilled => "partially_filled",
+            OrderStatus::Filled => "filled",
+            OrderStatus::Canceled => "canceled",
+            OrderStatus::Rejected => "rejected",
+            OrderStatus::Expired => "expired",
+            OrderStatus::Unknown => "unknown",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct FillEvent {
     pub order_id: String,
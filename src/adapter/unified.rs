@@ -1,9 +1,49 @@
-use super::types::{OrderRequest, OrderResponse};
+use std::future::Future;
+use std::pin::Pin;
+
+use super::throttle::ThrottleStats;
+use super::types::{FillEvent, OrderRequest, OrderResponse, OrderStatus};
+
+/// An order submission in flight.
+pub type OrderFuture<'a> = Pin<Box<dyn Future<Output = Result<OrderResponse, String>> + Send + 'a>>;
 
 pub trait UnifiedAdapter {
     fn place_order(&mut self, req: OrderRequest) -> Result<OrderResponse, String>;
+
+    /// Submit through a shared reference, so several orders can be in
+    /// flight at once. Adapters whose transport can't do that return None
+    /// and their orders go out one at a time via `place_order`.
+    fn submit_order(&self, _req: &OrderRequest) -> Option<OrderFuture<'_>> {
+        None
+    }
+
     fn cancel_order(&mut self, order_id: &str) -> Result<(), String>;
     fn cancel_all(&mut self) -> Result<(), String>;
+
+    /// Executions of a previously placed order, oldest first, with signed qty
+    /// (buy > 0). Venues that cannot report fills return none.
+    fn fetch_order_fills(
+        &mut self,
+        _symbol: &str,
+        _client_id: &str,
+    ) -> Result<Vec<FillEvent>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Current venue status of a previously placed order. Venues that cannot
+    /// be queried report `Unknown`.
+    fn fetch_order_status(
+        &mut self,
+        _symbol: &str,
+        _client_id: &str,
+    ) -> Result<OrderStatus, String> {
+        Ok(OrderStatus::Unknown)
+    }
+
+    /// Client-side rate-limit activity, for adapters that throttle requests.
+    fn throttle_stats(&self) -> Option<ThrottleStats> {
+        None
+    }
 }
 
 // Stub implementation to make integration explicit.
@@ -24,4 +64,13 @@ impl UnifiedAdapter for NullAdapter {
     fn cancel_all(&mut self) -> Result<(), String> {
         Ok(())
     }
+
+    /// The stub accepts every order and never fills one.
+    fn fetch_order_status(
+        &mut self,
+        _symbol: &str,
+        _client_id: &str,
+    ) -> Result<OrderStatus, String> {
+        Ok(OrderStatus::Open)
+    }
 }
@@ -0,0 +1,309 @@
+//! Side-by-side comparison of two configs over the same dataset.
+//!
+//! Both runs are bucketed onto the same UTC-day grid: each day's realized
+//! PnL (round trips by exit time, all strategies) under A is paired with the
+//! same day under B. Every day from the first to the last close on either
+//! side is a pair, so a day one config sat out counts as zero there and the
+//! paired differences sum to the realized PnL delta. Significance is a
+//! paired t-test on those differences with a normal approximation.
+
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use serde::Serialize;
+
+use super::{run_backtest_full, BacktestResult, CsvRow};
+use crate::state::Config;
+
+/// Two-sided significance level for declaring a winner.
+pub const COMPARE_ALPHA: f64 = 0.05;
+
+/// Headline numbers for one side of the comparison.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub pnl: f64,
+    /// Per-trade Sharpe: mean / sample std of round-trip PnL (not annualized).
+    pub sharpe: f64,
+    pub max_drawdown: f64,
+    pub trades: usize,
+}
+
+impl RunSummary {
+    fn from_result(result: &BacktestResult) -> Self {
+        let pnls: Vec<f64> = result.trades.iter().map(|t| t.pnl).collect();
+        let (mean, sd) = mean_sd(&pnls);
+        Self {
+            pnl: result.total_pnl,
+            sharpe: if sd > 0.0 { mean / sd } else { 0.0 },
+            max_drawdown: result.max_drawdown,
+            trades: pnls.len(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Verdict {
+    A,
+    B,
+    Inconclusive,
+}
+
+/// A vs B; every delta is `a - b`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompareReport {
+    pub a: RunSummary,
+    pub b: RunSummary,
+    pub pnl_delta: f64,
+    pub sharpe_delta: f64,
+    /// Positive means A drew down more.
+    pub max_drawdown_delta: f64,
+    pub trade_delta: i64,
+    /// Days on the shared grid the t-test ran over.
+    pub paired_days: usize,
+    pub t_stat: f64,
+    pub p_value: f64,
+    pub verdict: Verdict,
+}
+
+impl CompareReport {
+    pub fn from_results(a: &BacktestResult, b: &BacktestResult) -> Self {
+        let diffs = paired_diffs(a, b);
+        let (t_stat, p_value) = paired_t(&diffs);
+        let verdict = if p_value < COMPARE_ALPHA && t_stat > 0.0 {
+            Verdict::A
+        } else if p_value < COMPARE_ALPHA && t_stat < 0.0 {
+            Verdict::B
+        } else {
+            Verdict::Inconclusive
+        };
+        let sa = RunSummary::from_result(a);
+        let sb = RunSummary::from_result(b);
+        Self {
+            pnl_delta: sa.pnl - sb.pnl,
+            sharpe_delta: sa.sharpe - sb.sharpe,
+            max_drawdown_delta: sa.max_drawdown - sb.max_drawdown,
+            trade_delta: sa.trades as i64 - sb.trades as i64,
+            paired_days: diffs.len(),
+            t_stat,
+            p_value,
+            verdict,
+            a: sa,
+            b: sb,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Run both configs over `rows` and compare them.
+pub fn compare(cfg_a: Config, cfg_b: Config, rows: &[CsvRow]) -> Result<CompareReport> {
+    let a = run_backtest_full(cfg_a, rows)?;
+    let b = run_backtest_full(cfg_b, rows)?;
+    Ok(CompareReport::from_results(&a, &b))
+}
+
+const DAY_SECS: u64 = 86_400;
+
+/// Per-day `a - b` realized PnL over every day from the first close on
+/// either side to the last.
+fn paired_diffs(a: &BacktestResult, b: &BacktestResult) -> Vec<f64> {
+    let mut days: BTreeMap<u64, f64> = BTreeMap::new();
+    for t in &a.trades {
+        *days.entry(t.exit_ts / DAY_SECS).or_default() += t.pnl;
+    }
+    for t in &b.trades {
+        *days.entry(t.exit_ts / DAY_SECS).or_default() -= t.pnl;
+    }
+    let (Some(&first), Some(&last)) = (days.keys().next(), days.keys().next_back()) else {
+        return Vec::new();
+    };
+    (first..=last)
+        .map(|d| days.get(&d).copied().unwrap_or(0.0))
+        .collect()
+}
+
+fn mean_sd(xs: &[f64]) -> (f64, f64) {
+    if xs.is_empty() {
+        return (0.0, 0.0);
+    }
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    if xs.len() < 2 {
+        return (mean, 0.0);
+    }
+    let var = xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, var.sqrt())
+}
+
+/// (t statistic, two-sided p-value) for mean(diffs) == 0.
+fn paired_t(diffs: &[f64]) -> (f64, f64) {
+    let (mean, sd) = mean_sd(diffs);
+    if diffs.len() < 2 || mean == 0.0 {
+        return (0.0, 1.0);
+    }
+    if sd == 0.0 {
+        return (mean.signum() * f64::INFINITY, 0.0);
+    }
+    let t = mean / (sd / (diffs.len() as f64).sqrt());
+    (t, 2.0 * (1.0 - normal_cdf(t.abs())))
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz & Stegun 7.1.26; absolute error below 1.5e-7.
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.3275911 * x);
+    let poly = t
+        * (0.254829592
+            + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::TradeRecord;
+    use crate::strategy::ExitReason;
+
+    /// Drifting series with bounded xorshift noise; momentum churns on it.
+    fn rows(n: usize, drift: f64) -> Vec<CsvRow> {
+        let mut px = 100.0;
+        let mut s: u64 = 7;
+        (0..n)
+            .map(|i| {
+                s ^= s << 13;
+                s ^= s >> 7;
+                s ^= s << 17;
+                let noise = ((s % 1000) as f64 / 1000.0 - 0.5) * 0.004;
+                px *= 1.0 + drift + noise;
+                CsvRow {
+                    ts: 1_000 + i as u64 * 300,
+                    o: px,
+                    h: px * 1.001,
+                    l: px * 0.999,
+                    c: px,
+                    v: 1000.0,
+                    funding: 0.0,
+                    borrow: 0.0,
+                    liq: 0.0,
+                    depeg: 0.0,
+                    oi: 0.0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_compare_prefers_strictly_better_config() {
+        // The fixture is pinned, not tuned: on a driftless series a round
+        // trip earns nothing on average and always pays fee and slippage, so
+        // a config that takes no entries (a zero daily cap) beats the same
+        // config left to churn. Keep drift at zero and A at zero entries;
+        // moving either turns this into a test of the strategies.
+        let data = rows(3000, 0.0);
+        let mut loose = Config::from_env();
+        loose.max_trades_per_day = 1_000;
+        // Only the priced-in costs hold entries back, so the churn happens
+        loose.edge_hurdle = 0.0;
+        let mut idle = loose.clone();
+        idle.max_trades_per_day = 0;
+
+        let report = compare(idle.clone(), loose.clone(), &data).unwrap();
+        assert_eq!(report.a.trades, 0, "{}", report.to_json());
+        assert!(report.b.trades > 0, "{}", report.to_json());
+        assert!(report.pnl_delta > 0.0, "{}", report.to_json());
+        // A handful of round trips over ~10 days is too little to call
+        assert!(report.paired_days >= 2, "{}", report.to_json());
+        assert!(report.t_stat > 0.0);
+
+        // Swapping sides flips every delta
+        let flipped = compare(loose, idle, &data).unwrap();
+        assert!((flipped.pnl_delta + report.pnl_delta).abs() < 1e-9);
+        assert!((flipped.t_stat + report.t_stat).abs() < 1e-9);
+        assert_eq!(flipped.paired_days, report.paired_days);
+    }
+
+    fn result(trades: &[(u64, f64)]) -> BacktestResult {
+        BacktestResult {
+            total_pnl: trades.iter().map(|t| t.1).sum(),
+            max_drawdown: 0.0,
+            buy_hold_pnl: 0.0,
+            strategies: Vec::new(),
+            config_hash: String::new(),
+            candle_count: 0,
+            trades: trades
+                .iter()
+                .map(|&(exit_ts, pnl)| TradeRecord {
+                    strategy: "churn-0".to_string(),
+                    entry_ts: exit_ts.saturating_sub(600),
+                    exit_ts,
+                    side: 1.0,
+                    entry_px: 100.0,
+                    exit_px: 100.0,
+                    qty: 1.0,
+                    pnl,
+                    regime: None,
+                    exit_reason: ExitReason::Signal,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_days_pair_on_a_shared_grid() {
+        const DAY: u64 = 86_400;
+        // A trades twice a day, B once at a different hour; A nets 1.05
+        // more every day and both sit out day 3
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        for d in [0u64, 1, 2, 4, 5, 6] {
+            let wobble = (d % 3) as f64 * 0.1;
+            a.push((d * DAY + 3_600, 0.5 + wobble));
+            a.push((d * DAY + 7_200, 0.5));
+            b.push((d * DAY + 50_000, wobble - 0.05));
+        }
+        let diffs = paired_diffs(&result(&a), &result(&b));
+        assert_eq!(diffs.len(), 7);
+        assert_eq!(diffs[3], 0.0);
+        assert!(diffs
+            .iter()
+            .enumerate()
+            .all(|(d, x)| d == 3 || (x - 1.05).abs() < 1e-9));
+
+        let report = CompareReport::from_results(&result(&a), &result(&b));
+        assert_eq!(report.paired_days, 7);
+        assert_eq!(report.verdict, Verdict::A, "{}", report.to_json());
+        assert!((report.pnl_delta - diffs.iter().sum::<f64>()).abs() < 1e-9);
+
+        // Ordinal pairing would line A's second trade up against B's next
+        // day; by day, A's two trades net out against B's flat day 0
+        let a = result(&[(3_600, 1.0), (7_200, -1.0)]);
+        let b = result(&[(80_000, 0.0), (DAY + 3_600, 2.0)]);
+        assert_eq!(paired_diffs(&a, &b), vec![0.0, -2.0]);
+    }
+
+    #[test]
+    fn test_identical_configs_are_inconclusive() {
+        let data = rows(600, 0.0);
+        let report = compare(Config::from_env(), Config::from_env(), &data).unwrap();
+        assert_eq!(report.verdict, Verdict::Inconclusive);
+        assert_eq!(report.pnl_delta, 0.0);
+        assert_eq!(report.trade_delta, 0);
+        assert_eq!(report.p_value, 1.0);
+    }
+
+    #[test]
+    fn test_paired_t_on_known_differences() {
+        let (t, p) = paired_t(&[1.0, 2.0, 3.0, 4.0]);
+        // mean 2.5, sd 1.29 -> t = 3.87
+        assert!((t - 3.873).abs() < 1e-3);
+        assert!(p < 0.001);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-4);
+    }
+}
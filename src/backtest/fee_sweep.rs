@@ -0,0 +1,150 @@
+//! What-if fee sweep: rerun the same config over the same data at several
+//! fee rates and find where the edge disappears.
+//!
+//! Everything except `fee_rate` comes from one base `ExecConfig` (env by
+//! default), so the runs differ only in fees. The strategies' edge filter is
+//! priced once at the base model, so every run takes the same trades and
+//! only what they cost changes. PnL here is net of fees (equity change), since
+//! `total_pnl` sums realized trade PnL, which excludes fees.
+
+use anyhow::Result;
+
+use super::{run_chunk, BacktestResult, CsvRow, ExecConfig};
+use crate::state::Config;
+
+/// Run `cfg` over `rows` once per fee rate; results are in `fees` order.
+pub fn fee_sweep(cfg: Config, rows: &[CsvRow], fees: &[f64]) -> Result<Vec<(f64, BacktestResult)>> {
+    fee_sweep_with(cfg, rows, &ExecConfig::from_env(), fees)
+}
+
+/// `fee_sweep` over an explicit base execution model.
+pub fn fee_sweep_with(
+    cfg: Config,
+    rows: &[CsvRow],
+    base: &ExecConfig,
+    fees: &[f64],
+) -> Result<Vec<(f64, BacktestResult)>> {
+    let cfg = base.with_edge_costs(cfg);
+    fees.iter()
+        .map(|&fee| {
+            let exec_cfg = ExecConfig {
+                fee_rate: fee,
+                ..base.clone()
+            };
+            let (result, _) = run_chunk(cfg.clone(), rows, &exec_cfg, &[], &[], None, true)?;
+            Ok((fee, result))
+        })
+        .collect()
+}
+
+/// PnL after fees and slippage, summed over strategies.
+pub fn net_pnl(result: &BacktestResult) -> f64 {
+    result.strategies.iter().map(|s| s.equity_pnl).sum()
+}
+
+/// Fee rate at which net PnL crosses zero, linearly interpolated between
+/// the bracketing sweep points. `None` if the sweep never crosses, i.e. the
+/// run is profitable at every fee tried or at none of them.
+pub fn breakeven_fee(sweep: &[(f64, BacktestResult)]) -> Option<f64> {
+    zero_crossing(sweep.iter().map(|(f, r)| (*f, net_pnl(r))).collect())
+}
+
+/// `breakeven_fee` for a single strategy id.
+pub fn strategy_breakeven_fee(sweep: &[(f64, BacktestResult)], id: &str) -> Option<f64> {
+    zero_crossing(
+        sweep
+            .iter()
+            .filter_map(|(f, r)| {
+                let s = r.strategies.iter().find(|s| s.id == id)?;
+                Some((*f, s.equity_pnl))
+            })
+            .collect(),
+    )
+}
+
+fn zero_crossing(mut points: Vec<(f64, f64)>) -> Option<f64> {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    points.windows(2).find_map(|w| {
+        let ((f0, p0), (f1, p1)) = (w[0], w[1]);
+        if p0 > 0.0 && p1 <= 0.0 {
+            Some(f0 + (f1 - f0) * p0 / (p0 - p1))
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steady uptrend with small xorshift noise: trend-following wins gross,
+    /// but each round trip only clears a few basis points.
+    fn rows(n: usize, drift: f64) -> Vec<CsvRow> {
+        let mut px = 100.0;
+        let mut s: u64 = 11;
+        (0..n)
+            .map(|i| {
+                s ^= s << 13;
+                s ^= s >> 7;
+                s ^= s << 17;
+                let noise = ((s % 1000) as f64 / 1000.0 - 0.5) * 0.004;
+                px *= 1.0 + drift + noise;
+                CsvRow {
+                    ts: 1_000 + i as u64 * 300,
+                    o: px,
+                    h: px * 1.001,
+                    l: px * 0.999,
+                    c: px,
+                    v: 1000.0,
+                    funding: 0.0,
+                    borrow: 0.0,
+                    liq: 0.0,
+                    depeg: 0.0,
+                    oi: 0.0,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_thin_edge_breakeven_fee_is_found() {
+        // Driftless noise: every strategy clears only a few bps per round trip
+        let data = rows(2000, 0.0);
+        let exec = ExecConfig::instant();
+        let fees = [0.0, 0.0001, 0.0003, 0.001, 0.003];
+        let sweep = fee_sweep_with(Config::from_env(), &data, &exec, &fees).unwrap();
+        assert_eq!(sweep.len(), fees.len());
+
+        let pnls: Vec<f64> = sweep.iter().map(|(_, r)| net_pnl(r)).collect();
+        assert!(pnls[0] > 0.0 && pnls[4] < 0.0, "{:?}", pnls);
+        assert!(pnls.windows(2).all(|w| w[1] < w[0]), "{:?}", pnls);
+
+        // Sign flips between 0.0003 and 0.001
+        let be = breakeven_fee(&sweep).unwrap();
+        assert!(be > 0.0003 && be < 0.001, "breakeven {}", be);
+
+        // Rerunning at the interpolated fee lands on zero
+        let at = fee_sweep_with(Config::from_env(), &data, &exec, &[be]).unwrap();
+        assert!(
+            net_pnl(&at[0].1).abs() < 1e-6 * pnls[0],
+            "{}",
+            net_pnl(&at[0].1)
+        );
+
+        // Same for a single strategy
+        let be1 = strategy_breakeven_fee(&sweep, "churn-1").unwrap();
+        let at1 = fee_sweep_with(Config::from_env(), &data, &exec, &[be1]).unwrap();
+        let churn1 = at1[0].1.strategies.iter().find(|s| s.id == "churn-1");
+        assert!(churn1.unwrap().equity_pnl.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_no_crossing_has_no_breakeven() {
+        assert_eq!(zero_crossing(vec![(0.0, 1.0), (0.001, 0.5)]), None);
+        assert_eq!(zero_crossing(vec![(0.0, -1.0), (0.001, -2.0)]), None);
+        // Unsorted input is ordered by fee first
+        let be = zero_crossing(vec![(0.002, -1.0), (0.0, 1.0)]).unwrap();
+        assert!((be - 0.001).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,2101 @@
+pub mod compare;
+pub mod fee_sweep;
+pub mod monte_carlo;
+pub mod preregister;
+
+pub use compare::{compare, CompareReport, Verdict};
+pub use fee_sweep::{breakeven_fee, fee_sweep, fee_sweep_with, net_pnl, strategy_breakeven_fee};
+pub use monte_carlo::{monte_carlo, DrawdownDistribution};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::data::Gap;
+use crate::events::{detect_phase1, EventConfig};
+use crate::features::FeaturePipeline;
+use crate::feed::aux_data::LiqBaseline;
+use crate::logging::{json_log_at, obj, v_num, v_str, Level};
+use crate::metrics::MetricsEngine;
+use crate::narrative_detector::NarrativeRegime;
+use crate::reliability::wal::{Wal, WalEntry};
+use crate::risk::RiskEngine;
+use crate::state::{candle_feed_stale, Config, Fill, MarketState, StrategyInstance};
+use crate::strategy::{
+    funding_settlements, next_funding_ts, Action, ExitReason, MarketAux, PortfolioState,
+    StrategyState,
+};
+
+/// Execution mode for backtesting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExecMode {
+    /// Instant fill at close price (unrealistic but fast)
+    Instant,
+    /// Market order with slippage
+    Market,
+    /// Limit order with fill probability
+    Limit,
+    /// Realistic: limit with adverse selection and partial fills
+    Realistic,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecConfig {
+    pub slippage_k: f64,
+    pub fee_rate: f64,
+    pub latency_min: u64,
+    pub latency_max: u64,
+    pub max_fill_ratio: f64,
+    /// Execution mode
+    pub mode: ExecMode,
+    /// Limit order fill probability base (0.0-1.0)
+    pub limit_fill_prob: f64,
+    /// Adverse selection factor (0.0-1.0) - how much fills are biased against us
+    pub adverse_selection: f64,
+    /// Skip passive fills that the *next* bar would have rewarded, with
+    /// probability `adverse_selection`. Peeks one bar ahead, so it only exists
+    /// in the backtest; a stress test, not a forecast.
+    #[serde(default)]
+    pub adverse_lookahead: bool,
+    /// Volatility multiplier for slippage
+    pub vol_slip_mult: f64,
+}
+
+impl ExecConfig {
+    pub fn from_env() -> Self {
+        let mode = match std::env::var("EXEC_MODE").unwrap_or_default().as_str() {
+            "instant" => ExecMode::Instant,
+            "market" => ExecMode::Market,
+            "limit" => ExecMode::Limit,
+            "realistic" => ExecMode::Realistic,
+            _ => ExecMode::Market,
+        };
+        Self {
+            slippage_k: std::env::var("SLIP_K")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.0008),
+            fee_rate: std::env::var("FEE_RATE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.001),
+            latency_min: std::env::var("LAT_MIN")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2),
+            latency_max: std::env::var("LAT_MAX")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(8),
+            max_fill_ratio: std::env::var("FILL_RATIO")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.5),
+            mode,
+            limit_fill_prob: std::env::var("LIMIT_FILL_PROB")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.7),
+            adverse_selection: std::env::var("ADVERSE_SEL")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0.3),
+            adverse_lookahead: std::env::var("ADVERSE_LOOKAHEAD")
+                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+                .unwrap_or(false),
+            vol_slip_mult: std::env::var("VOL_SLIP_MULT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(2.0),
+        }
+    }
+
+    /// Price the strategies' edge filter with this execution model's fee and
+    /// volatility slippage, so the backtest filters on the costs it charges.
+    pub fn with_edge_costs(&self, cfg: Config) -> Config {
+        Config {
+            edge_fee_bps: self.fee_rate * 10_000.0,
+            edge_slip_vol_mult: self.vol_slip_mult,
+            ..cfg
+        }
+    }
+
+    /// Orders rest on the book (limit/realistic) rather than cross the spread.
+    pub fn is_passive(&self) -> bool {
+        matches!(self.mode, ExecMode::Limit | ExecMode::Realistic)
+    }
+
+    /// Instant execution config (for fast testing)
+    pub fn instant() -> Self {
+        Self {
+            mode: ExecMode::Instant,
+            slippage_k: 0.0,
+            fee_rate: 0.0,
+            latency_min: 0,
+            latency_max: 0,
+            max_fill_ratio: 1.0,
+            limit_fill_prob: 1.0,
+            adverse_selection: 0.0,
+            adverse_lookahead: false,
+            vol_slip_mult: 0.0,
+        }
+    }
+
+    /// Maker order config (limit orders, low fees)
+    pub fn maker() -> Self {
+        Self {
+            mode: ExecMode::Limit,
+            slippage_k: 0.0001,
+            fee_rate: 0.0002, // Maker fee
+            latency_min: 5,
+            latency_max: 20,
+            max_fill_ratio: 0.8,
+            limit_fill_prob: 0.7,
+            adverse_selection: 0.25,
+            adverse_lookahead: false,
+            vol_slip_mult: 1.0,
+        }
+    }
+
+    /// Taker order config (market orders, higher fees)
+    pub fn taker() -> Self {
+        Self {
+            mode: ExecMode::Market,
+            slippage_k: 0.0008,
+            fee_rate: 0.001, // Taker fee
+            latency_min: 2,
+            latency_max: 8,
+            max_fill_ratio: 1.0,
+            limit_fill_prob: 1.0,
+            adverse_selection: 0.0,
+            adverse_lookahead: false,
+            vol_slip_mult: 2.0,
+        }
+    }
+
+    /// Realistic config (mixed limit/market with adverse selection)
+    pub fn realistic() -> Self {
+        Self {
+            mode: ExecMode::Realistic,
+            slippage_k: 0.0005,
+            fee_rate: 0.0004, // Blended rate
+            latency_min: 3,
+            latency_max: 15,
+            max_fill_ratio: 0.6,
+            limit_fill_prob: 0.65,
+            adverse_selection: 0.3,
+            adverse_lookahead: false,
+            vol_slip_mult: 1.5,
+        }
+    }
+}
+
+/// Calculate fill probability for a limit order
+pub fn calc_fill_probability(
+    is_buy: bool,
+    limit_price: f64,
+    current_price: f64,
+    volatility: f64,
+    base_prob: f64,
+    adverse_sel: f64,
+) -> f64 {
+    if volatility <= 0.0 {
+        return base_prob;
+    }
+
+    // Distance from current price (negative = favorable, positive = unfavorable)
+    let distance = if is_buy {
+        (current_price - limit_price) / current_price
+    } else {
+        (limit_price - current_price) / current_price
+    };
+
+    // Favorable limit (below market for buy, above for sell) = higher fill prob
+    // Unfavorable limit = lower fill prob
+    let distance_factor = if distance > 0.0 {
+        // Limit is favorable - high fill probability
+        (1.0 + distance / volatility).min(1.5)
+    } else {
+        // Limit is unfavorable - probability decays
+        (-distance.abs() / volatility).exp()
+    };
+
+    // Adverse selection: fills that happen tend to be losing trades
+    // If we get filled, price was likely moving against us
+    let adverse_factor = 1.0 - adverse_sel;
+
+    (base_prob * distance_factor * adverse_factor).clamp(0.0, 1.0)
+}
+
+/// Look-ahead adverse selection: whether a passive order of signed `qty`
+/// resting at `price` fills this bar, given the next bar's close. Fills the
+/// next move punishes always go through; fills it would reward are dropped
+/// with probability `adverse_sel`, so the fills you get skew toward losers.
+/// The draw is deterministic in `key`.
+pub fn adverse_fill_allowed(
+    qty: f64,
+    price: f64,
+    next_close: f64,
+    adverse_sel: f64,
+    key: u64,
+) -> bool {
+    let favorable = (next_close - price) * qty.signum() > 0.0;
+    if !favorable {
+        return true;
+    }
+    // splitmix64 finalizer: adjacent keys (consecutive bars) map far apart
+    let mut x = key.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^= x >> 31;
+    let u = (x >> 11) as f64 / (1u64 << 53) as f64;
+    u >= adverse_sel
+}
+
+/// Calculate slippage for an order
+pub fn calc_slippage(
+    qty: f64,
+    _price: f64,
+    volume: f64,
+    volatility: f64,
+    config: &ExecConfig,
+) -> f64 {
+    // Base slippage from size vs volume
+    let size_slip = config.slippage_k * qty.abs() / volume.max(1.0);
+
+    // Volatility-adjusted slippage
+    let vol_slip = crate::state::vol_slippage(volatility, config.vol_slip_mult);
+
+    // Total slippage
+    (size_slip + vol_slip).min(0.05) // Cap at 5%
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PendingOrder {
+    pub qty: f64,
+    pub submit_ts: u64,
+    /// Strategy index that owns this order (FIXED: per-strategy attribution)
+    pub strategy_idx: usize,
+    /// Remainder of a partially filled order; its fills add no tranche.
+    #[serde(default)]
+    pub continued: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct CsvRow {
+    pub ts: u64,
+    pub o: f64,
+    pub h: f64,
+    pub l: f64,
+    pub c: f64,
+    pub v: f64,
+    pub funding: f64,
+    pub borrow: f64,
+    pub liq: f64,
+    pub depeg: f64,
+    pub oi: f64,
+}
+
+pub fn parse_csv_line(line: &str) -> Result<CsvRow> {
+    let parts: Vec<&str> = line.split(',').collect();
+    if parts.len() < 10 {
+        return Err(anyhow!("expected 10+ columns, got {}", parts.len()));
+    }
+    let oi = if parts.len() >= 11 {
+        parts[10].trim().parse().unwrap_or(0.0)
+    } else {
+        0.0
+    };
+    Ok(CsvRow {
+        ts: parts[0].trim().parse()?,
+        o: parts[1].trim().parse()?,
+        h: parts[2].trim().parse()?,
+        l: parts[3].trim().parse()?,
+        c: parts[4].trim().parse()?,
+        v: parts[5].trim().parse()?,
+        funding: parts[6].trim().parse()?,
+        borrow: parts[7].trim().parse()?,
+        liq: parts[8].trim().parse()?,
+        depeg: parts[9].trim().parse()?,
+        oi,
+    })
+}
+
+fn slippage_price(price: f64, qty: f64, liquidity: f64, k: f64, vol: f64) -> f64 {
+    let liq = liquidity.max(1.0);
+    let slip = k * (qty.abs() / liq) * (1.0 + vol * 2.0);
+    if qty >= 0.0 {
+        price * (1.0 + slip)
+    } else {
+        price * (1.0 - slip)
+    }
+}
+
+/// Qty that flattens strategy `idx`, net of its orders still in flight, so
+/// repeated Close signals under partial fills don't stack and overshoot.
+/// None when the in-flight orders already cover the position.
+fn close_qty(position: f64, pending: &[PendingOrder], idx: usize) -> Option<f64> {
+    let in_flight: f64 = pending
+        .iter()
+        .filter(|o| o.strategy_idx == idx)
+        .map(|o| o.qty)
+        .sum();
+    let qty = -(position + in_flight);
+    (qty.abs() > 1e-9).then_some(qty)
+}
+
+/// Slice of a pending `qty` filled this bar. The tail is filled outright
+/// rather than stranding sub-1e-9 dust that would keep the position from
+/// ever reading flat.
+fn partial_fill_qty(qty: f64, max_fill_ratio: f64) -> f64 {
+    let fill = qty * max_fill_ratio;
+    if (qty - fill).abs() <= 1e-9 {
+        qty
+    } else {
+        fill
+    }
+}
+
+/// Deterministic latency model with bounded jitter.
+/// Uses a simple xorshift to avoid RNG dependencies and keep replay stable;
+/// `seed` (Config.seed) selects the jitter sequence.
+fn latency_delay(submit_ts: u64, strategy_idx: usize, seed: u64, min: u64, max: u64) -> u64 {
+    if max <= min {
+        return min;
+    }
+    let mut x = submit_ts ^ ((strategy_idx as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)) ^ seed;
+    x ^= x << 13;
+    x ^= x >> 7;
+    x ^= x << 17;
+    let span = max - min + 1;
+    min + (x % span)
+}
+
+/// Per-strategy result from a backtest run.
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyResult {
+    pub id: String,
+    pub pnl: f64,
+    pub equity_pnl: f64,
+    pub equity: f64,
+    pub friction: f64,
+    /// Net funding received (negative when paid), included in `equity_pnl`.
+    pub funding: f64,
+    pub max_drawdown: f64,
+    /// Per-bar Sharpe and Sortino of equity returns (not annualized).
+    pub sharpe: f64,
+    pub sortino: f64,
+    pub trades: u64,
+    pub wins: u64,
+    pub losses: u64,
+    pub fills: u64,
+    /// Trading was halted by the end of the run (close-only).
+    pub halted: bool,
+    /// Orders refused because the venue was down (see `Downtime`).
+    pub rejected: u64,
+}
+
+/// A venue maintenance window, `[start_ts, end_ts)`: every order submitted
+/// inside it is rejected and nothing already working fills until it ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Downtime {
+    pub start_ts: u64,
+    pub end_ts: u64,
+}
+
+impl Downtime {
+    pub fn contains(&self, ts: u64) -> bool {
+        ts >= self.start_ts && ts < self.end_ts
+    }
+}
+
+/// One round trip, from the fill that opened a position to the fill that flattened it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeRecord {
+    pub strategy: String,
+    pub entry_ts: u64,
+    pub exit_ts: u64,
+    /// +1.0 long, -1.0 short
+    pub side: f64,
+    /// Average entry price (after any adds)
+    pub entry_px: f64,
+    pub exit_px: f64,
+    /// Largest absolute position held during the trade
+    pub qty: f64,
+    /// Realized PnL over all closing fills (fees excluded, as in `StrategyResult::pnl`)
+    pub pnl: f64,
+    /// Narrative regime at the entry candle; `None` inside the classifier warmup
+    pub regime: Option<NarrativeRegime>,
+    pub exit_reason: ExitReason,
+}
+
+/// A position opened but not yet flattened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OpenTrade {
+    entry_ts: u64,
+    side: f64,
+    entry_px: f64,
+    qty: f64,
+    pnl: f64,
+    regime: Option<NarrativeRegime>,
+    exit_reason: Option<ExitReason>,
+}
+
+impl OpenTrade {
+    fn close(self, strategy: &str, exit_ts: u64, exit_px: f64) -> TradeRecord {
+        TradeRecord {
+            strategy: strategy.to_string(),
+            entry_ts: self.entry_ts,
+            exit_ts,
+            side: self.side,
+            entry_px: self.entry_px,
+            exit_px,
+            qty: self.qty,
+            pnl: self.pnl,
+            regime: self.regime,
+            exit_reason: self.exit_reason.unwrap_or(ExitReason::Signal),
+        }
+    }
+}
+
+/// Per-strategy open trades and the round trips closed so far.
+#[derive(Debug, Default)]
+struct TradeTracker {
+    open: Vec<Option<OpenTrade>>,
+    closed: Vec<TradeRecord>,
+}
+
+impl TradeTracker {
+    fn new(strategies: usize) -> Self {
+        Self {
+            open: vec![None; strategies],
+            closed: Vec::new(),
+        }
+    }
+
+    /// Record why strategy `idx` is exiting; the first reason given for a trade sticks.
+    fn mark_exit(&mut self, idx: usize, reason: ExitReason) {
+        if let Some(t) = self.open[idx].as_mut() {
+            t.exit_reason.get_or_insert(reason);
+        }
+    }
+
+    /// Fold a fill already applied to `portfolio` into strategy `idx`'s open
+    /// trade, emitting a record when the position flattens or flips.
+    fn on_fill(
+        &mut self,
+        idx: usize,
+        strategy: &str,
+        fill: &Fill,
+        realized: f64,
+        portfolio: &PortfolioState,
+        regime: Option<NarrativeRegime>,
+    ) {
+        let new_pos = portfolio.position;
+        let prev_pos = new_pos - fill.qty;
+        let open = &mut self.open[idx];
+        if let Some(t) = open.as_mut() {
+            t.pnl += realized;
+            if new_pos.abs() > t.qty && new_pos.signum() == t.side {
+                t.qty = new_pos.abs();
+                t.entry_px = portfolio.entry_price;
+            }
+        } else if realized != 0.0 {
+            // Closing sub-1e-9 dust left when the previous trade was recorded
+            // as flat; its PnL belongs to that trade.
+            if let Some(t) = self
+                .closed
+                .iter_mut()
+                .rev()
+                .find(|t| t.strategy == strategy)
+            {
+                t.pnl += realized;
+            }
+        }
+        let flat = new_pos.abs() <= 1e-9;
+        let flipped = !flat && prev_pos.abs() > 1e-9 && prev_pos.signum() != new_pos.signum();
+        if flat || flipped {
+            if let Some(t) = open.take() {
+                self.closed.push(t.close(strategy, fill.ts, fill.price));
+            }
+        }
+        if open.is_none() && !flat {
+            *open = Some(OpenTrade {
+                entry_ts: fill.ts,
+                side: new_pos.signum(),
+                entry_px: portfolio.entry_price,
+                qty: new_pos.abs(),
+                pnl: 0.0,
+                regime,
+                exit_reason: None,
+            });
+        }
+    }
+}
+
+/// Aggregate backtest result with per-strategy breakdown.
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestResult {
+    pub total_pnl: f64,
+    pub max_drawdown: f64,
+    pub buy_hold_pnl: f64,
+    pub strategies: Vec<StrategyResult>,
+    pub config_hash: String,
+    pub candle_count: usize,
+    /// Closed round trips across all strategies, in fill order.
+    pub trades: Vec<TradeRecord>,
+}
+
+impl BacktestResult {
+    /// Serialize to JSON string.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Credit `settlements` funding payments at the row's rate to every open
+/// position, accumulating each strategy's carry in `funding`.
+fn settle_funding(
+    strategies: &mut [StrategyInstance],
+    funding: &mut [f64],
+    row: &CsvRow,
+    settlements: u64,
+) {
+    if settlements == 0 || row.funding == 0.0 {
+        return;
+    }
+    for (idx, inst) in strategies.iter_mut().enumerate() {
+        funding[idx] += inst
+            .state
+            .portfolio
+            .apply_funding(row.funding * settlements as f64, row.c);
+    }
+}
+
+pub fn run_backtest(cfg: Config, rows: &[CsvRow]) -> Result<(f64, f64)> {
+    let exec_cfg = ExecConfig::from_env();
+    let cfg = exec_cfg.with_edge_costs(cfg);
+    let event_cfg = EventConfig::from_env();
+    let mut market = MarketState::new(cfg.clone());
+    let mut strategies = StrategyInstance::build_churn_set(cfg.clone());
+    let mut risk = RiskEngine::new(cfg.clone());
+    let mut metrics = MetricsEngine::new();
+    let mut pending: Vec<PendingOrder> = Vec::new();
+    let mut pipeline = FeaturePipeline::new(200, 200, 30, 200);
+    let mut friction: Vec<f64> = vec![0.0; strategies.len()];
+    let mut holds: Vec<u64> = vec![0; strategies.len()];
+    let mut guarded_blocks: Vec<u64> = vec![0; strategies.len()];
+    let mut submits: Vec<u64> = vec![0; strategies.len()];
+    let mut fills: Vec<u64> = vec![0; strategies.len()];
+    let mut forced_closes: Vec<u64> = vec![0; strategies.len()];
+    let initial_cash = cfg.starting_capital;
+    let mut buy_hold_entry = None;
+    let mut buy_hold_exit = None;
+    let mut last_row: Option<CsvRow> = None;
+    let mut funding: Vec<f64> = vec![0.0; strategies.len()];
+    let mut prev_ts: Option<u64> = None;
+    let mut liq_baseline = LiqBaseline::new(cfg.liq_baseline_bars);
+
+    for (row_idx, row) in rows.iter().enumerate() {
+        last_row = Some(row.clone());
+        if buy_hold_entry.is_none() {
+            buy_hold_entry = Some(row.c);
+        }
+        buy_hold_exit = Some(row.c);
+        let candle = crate::exchange::Candle {
+            ts: row.ts,
+            o: row.o,
+            h: row.h,
+            l: row.l,
+            c: row.c,
+            v: row.v,
+        };
+        market.on_candle(candle);
+        market.update_aux(
+            &cfg.symbol,
+            MarketAux {
+                funding_rate: row.funding,
+                borrow_rate: row.borrow,
+                liquidation_score: liq_baseline.score(cfg.liq_score_source, row.liq),
+                stable_depeg: row.depeg,
+                fetch_ts: row.ts,
+                has_funding: row.funding != 0.0,
+                has_borrow: row.borrow != 0.0,
+                has_liquidations: row.liq != 0.0,
+                has_depeg: row.depeg != 0.0,
+                next_funding_ts: next_funding_ts(row.ts, cfg.funding_interval_secs),
+            },
+        );
+        let features = pipeline.update(row.c, row.funding, row.oi, row.liq, row.depeg);
+        let _events = detect_phase1(row.ts, &features, &event_cfg);
+        if let Some(prev) = prev_ts {
+            let settlements = funding_settlements(prev, row.ts, cfg.funding_interval_secs);
+            settle_funding(&mut strategies, &mut funding, row, settlements);
+        }
+        prev_ts = Some(row.ts);
+
+        for (idx, inst) in strategies.iter_mut().enumerate() {
+            let view = market.view(&cfg.symbol);
+            let action = inst.strategy.update(view, &mut inst.state);
+            // Operator-disabled: exits still run, entries are dropped
+            let action = if cfg.strategy_enabled(&inst.id) {
+                action
+            } else {
+                action.exits_only(inst.state.portfolio.position)
+            };
+            risk.enforce_drawdown_kill(&mut inst.state, &inst.id);
+            risk.enforce_profit_factor(&mut inst.state, &inst.id);
+            // FIXED: Use current price for MTM risk calculations
+            let guarded = risk.apply_with_price(&inst.state, action, row.ts, row.c);
+            if matches!(action, Action::Hold) {
+                holds[idx] += 1;
+            }
+            if !matches!(action, Action::Hold) && matches!(guarded, Action::Hold) {
+                guarded_blocks[idx] += 1;
+            }
+
+            let desired = match guarded {
+                Action::Hold => None,
+                Action::Close => {
+                    close_qty(inst.state.portfolio.position, &pending, idx).map(|q| (q, row.c))
+                }
+                Action::Buy { qty } => Some((qty, row.c)),
+                Action::Sell { qty } => Some((-qty.abs(), row.c)),
+            };
+            if let Some((qty, _price)) = desired {
+                // FIXED: Tag order with strategy index
+                pending.push(PendingOrder {
+                    qty,
+                    submit_ts: row.ts,
+                    strategy_idx: idx,
+                    continued: false,
+                });
+                submits[idx] += 1;
+            }
+
+            // FIXED: Only process orders belonging to THIS strategy
+            let mut still_pending = Vec::new();
+            for order in pending.drain(..) {
+                // Skip orders from other strategies
+                if order.strategy_idx != idx {
+                    still_pending.push(order);
+                    continue;
+                }
+                let delay = latency_delay(
+                    order.submit_ts,
+                    order.strategy_idx,
+                    cfg.seed,
+                    exec_cfg.latency_min,
+                    exec_cfg.latency_max,
+                );
+                if row.ts < order.submit_ts + delay {
+                    still_pending.push(order);
+                    continue;
+                }
+                // Backtest-only: peek at the next close (see `adverse_lookahead`)
+                if exec_cfg.adverse_lookahead && exec_cfg.is_passive() {
+                    if let Some(next) = rows.get(row_idx + 1) {
+                        let key =
+                            row.ts ^ (idx as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ cfg.seed;
+                        if !adverse_fill_allowed(
+                            order.qty,
+                            row.c,
+                            next.c,
+                            exec_cfg.adverse_selection,
+                            key,
+                        ) {
+                            still_pending.push(order);
+                            continue;
+                        }
+                    }
+                }
+                let fill_qty = partial_fill_qty(order.qty, exec_cfg.max_fill_ratio);
+                let fill_price = slippage_price(
+                    row.c,
+                    fill_qty,
+                    row.v,
+                    exec_cfg.slippage_k,
+                    view.indicators.vol,
+                );
+                let fee = fill_price * fill_qty.abs() * exec_cfg.fee_rate;
+                let slip_cost = (fill_price - row.c).abs() * fill_qty.abs();
+                friction[idx] += fee + slip_cost;
+                let fill = Fill {
+                    price: fill_price,
+                    qty: fill_qty,
+                    fee,
+                    ts: row.ts,
+                };
+                let position_before = inst.state.portfolio.position;
+                let realized = inst.state.portfolio.apply_fill(fill);
+                inst.state.count_tranche(position_before, !order.continued);
+                fills[idx] += 1;
+                inst.state
+                    .metrics
+                    .record_fill(realized, &fill, inst.state.portfolio.position);
+                if realized < 0.0 {
+                    inst.state.record_loss(row.ts, realized);
+                }
+                inst.state.last_trade_ts = row.ts;
+                let day = row.ts / 86_400;
+                if inst.state.trade_day != day {
+                    inst.state.trade_day = day;
+                    inst.state.trades_today = 0;
+                }
+                inst.state.trades_today += 1;
+
+                let remainder = order.qty - fill_qty;
+                if remainder.abs() > 1e-9 {
+                    still_pending.push(PendingOrder {
+                        qty: remainder,
+                        submit_ts: row.ts,
+                        strategy_idx: idx,
+                        continued: true,
+                    });
+                }
+            }
+            pending = still_pending;
+            metrics.update(&mut inst.state);
+        }
+    }
+
+    if let Some(last) = last_row {
+        let view = market.view(&cfg.symbol);
+        for (idx, inst) in strategies.iter_mut().enumerate() {
+            if inst.state.portfolio.position.abs() > 1e-9 {
+                let qty = -inst.state.portfolio.position;
+                let fill_price = slippage_price(
+                    last.c,
+                    qty,
+                    last.v,
+                    exec_cfg.slippage_k,
+                    view.indicators.vol,
+                );
+                let fee = fill_price * qty.abs() * exec_cfg.fee_rate;
+                friction[idx] += fee;
+                let fill = Fill {
+                    price: fill_price,
+                    qty,
+                    fee,
+                    ts: last.ts,
+                };
+                let realized = inst.state.portfolio.apply_fill(fill);
+                inst.state
+                    .metrics
+                    .record_fill(realized, &fill, inst.state.portfolio.position);
+                fills[idx] += 1;
+                forced_closes[idx] += 1;
+            }
+        }
+    }
+
+    let pnl = strategies.iter().map(|s| s.state.metrics.pnl).sum::<f64>();
+    let max_dd = strategies
+        .iter()
+        .map(|s| s.state.metrics.max_drawdown.abs())
+        .fold(0.0, f64::max);
+    let buy_hold = if let (Some(entry), Some(exit)) = (buy_hold_entry, buy_hold_exit) {
+        exit - entry
+    } else {
+        0.0
+    };
+    println!("baseline=buy_hold pnl={:.4}", buy_hold);
+    println!("baseline=no_trade pnl=0.0000");
+    for (idx, inst) in strategies.iter().enumerate() {
+        let total_trades = inst.state.metrics.wins + inst.state.metrics.losses;
+        let equity_pnl = inst.state.portfolio.equity - initial_cash;
+        println!(
+            "strategy={} pnl={:.4} equity_pnl={:.4} equity={:.4} pos={:.6} entry={:.2} friction={:.4} friction_only_pnl={:.4} dd={:.4} trades={} wins={} losses={} holds={} guarded={} submits={} fills={} forced_closes={}",
+            inst.id,
+            inst.state.metrics.pnl,
+            equity_pnl,
+            inst.state.portfolio.equity,
+            inst.state.portfolio.position,
+            inst.state.portfolio.entry_price,
+            friction[idx],
+            -friction[idx],
+            inst.state.metrics.max_drawdown.abs(),
+            total_trades,
+            inst.state.metrics.wins,
+            inst.state.metrics.losses,
+            holds[idx],
+            guarded_blocks[idx],
+            submits[idx],
+            fills[idx],
+            forced_closes[idx]
+        );
+    }
+    Ok((pnl, max_dd))
+}
+
+/// Run backtest returning structured per-strategy results.
+pub fn run_backtest_full(cfg: Config, rows: &[CsvRow]) -> Result<BacktestResult> {
+    run_backtest_with_exec(cfg, rows, &ExecConfig::from_env())
+}
+
+/// `run_backtest_full` with explicit execution assumptions instead of env.
+pub fn run_backtest_with_exec(
+    cfg: Config,
+    rows: &[CsvRow],
+    exec_cfg: &ExecConfig,
+) -> Result<BacktestResult> {
+    run_backtest_with_gaps(cfg, rows, exec_cfg, &[])
+}
+
+/// Whether the feed going silent across `gap` would trip the live
+/// staleness switch: the bot's last tick without a candle is the bar
+/// before data resumes, measured from the last candle it saw.
+pub fn gap_trips_staleness(gap: &Gap, cfg: &Config) -> bool {
+    let bar = gap.end_ts.saturating_sub(gap.start_ts) / (gap.missing_bars + 1);
+    candle_feed_stale(gap.start_ts, gap.end_ts.saturating_sub(bar), cfg)
+}
+
+/// `run_backtest_with_exec` treating `gaps` (from the dataset manifest) as
+/// feed outages rather than contiguous data. When the first candle after a
+/// gap arrives, every strategy is halted if the outage would have tripped
+/// the live staleness guard, so that bar is close-only; the halt lifts on
+/// the next contiguous bar. Halts from risk checks are left in place.
+pub fn run_backtest_with_gaps(
+    cfg: Config,
+    rows: &[CsvRow],
+    exec_cfg: &ExecConfig,
+    gaps: &[Gap],
+) -> Result<BacktestResult> {
+    run_backtest_with_downtime(cfg, rows, exec_cfg, gaps, &[])
+}
+
+/// `run_backtest_with_gaps` with scheduled venue outages. A rejected order is
+/// dropped, not queued, so the strategy re-decides once the venue is back
+/// instead of firing a backlog of stale signals on recovery.
+pub fn run_backtest_with_downtime(
+    cfg: Config,
+    rows: &[CsvRow],
+    exec_cfg: &ExecConfig,
+    gaps: &[Gap],
+    downtime: &[Downtime],
+) -> Result<BacktestResult> {
+    run_chunk(
+        exec_cfg.with_edge_costs(cfg),
+        rows,
+        exec_cfg,
+        gaps,
+        downtime,
+        None,
+        true,
+    )
+    .map(|(result, _)| result)
+}
+
+/// Everything a backtest carries from one bar to the next: market buffers
+/// and indicators, each strategy's state, orders in flight, running totals
+/// and open trades. Saved at the end of a chunk so the next chunk picks up
+/// exactly where it stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestCheckpoint {
+    /// Timestamp of the last bar the checkpoint has seen.
+    pub ts: u64,
+    /// Candles processed so far, across every chunk.
+    pub candles: usize,
+    market: serde_json::Value,
+    ids: Vec<String>,
+    strategies: Vec<StrategyState>,
+    pending: Vec<PendingOrder>,
+    friction: Vec<f64>,
+    funding: Vec<f64>,
+    fills: Vec<u64>,
+    rejected: Vec<u64>,
+    buy_hold_entry: Option<f64>,
+    liq_baseline: LiqBaseline,
+    open_trades: Vec<Option<OpenTrade>>,
+    closed_trades: Vec<TradeRecord>,
+}
+
+impl BacktestCheckpoint {
+    /// Append as a `backtest_checkpoint` WAL entry.
+    pub fn write_to(&self, wal: &mut Wal) -> Result<()> {
+        wal.append_entry(&WalEntry::BacktestCheckpoint {
+            ts: self.ts,
+            state: serde_json::to_value(self)?,
+        })?;
+        Ok(())
+    }
+
+    /// Newest checkpoint in the WAL at `path`; None when it holds none.
+    pub fn load(path: &str) -> Result<Option<Self>> {
+        match Wal::last_backtest_checkpoint(path)? {
+            Some(state) => Ok(Some(serde_json::from_value(state)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Run `rows` as one chunk of a longer backtest without force-closing at
+/// the end, starting from `warm` (or fresh), and return the state to resume
+/// from. Regime tags on trades only see the current chunk's bars, and with
+/// `adverse_lookahead` a chunk's last bar can't peek into the next.
+pub fn run_backtest_chunk(
+    cfg: Config,
+    rows: &[CsvRow],
+    exec_cfg: &ExecConfig,
+    warm: Option<&BacktestCheckpoint>,
+) -> Result<BacktestCheckpoint> {
+    run_chunk(
+        exec_cfg.with_edge_costs(cfg),
+        rows,
+        exec_cfg,
+        &[],
+        &[],
+        warm,
+        false,
+    )
+    .map(|(_, checkpoint)| checkpoint)
+}
+
+/// Final chunk of a chunked backtest: resume from `warm`, run `rows` and
+/// close out as `run_backtest_with_exec` would. Results cover every chunk.
+pub fn run_backtest_warm(
+    cfg: Config,
+    rows: &[CsvRow],
+    exec_cfg: &ExecConfig,
+    warm: Option<&BacktestCheckpoint>,
+) -> Result<BacktestResult> {
+    run_chunk(
+        exec_cfg.with_edge_costs(cfg),
+        rows,
+        exec_cfg,
+        &[],
+        &[],
+        warm,
+        true,
+    )
+    .map(|(result, _)| result)
+}
+
+/// Core bar loop. `cfg` is used as given: callers price the edge filter
+/// (`ExecConfig::with_edge_costs`) first.
+fn run_chunk(
+    cfg: Config,
+    rows: &[CsvRow],
+    exec_cfg: &ExecConfig,
+    gaps: &[Gap],
+    downtime: &[Downtime],
+    warm: Option<&BacktestCheckpoint>,
+    end_of_data: bool,
+) -> Result<(BacktestResult, BacktestCheckpoint)> {
+    let event_cfg = EventConfig::from_env();
+    let mut market = MarketState::new(cfg.clone());
+    let mut strategies = StrategyInstance::build_churn_set(cfg.clone());
+    let mut risk = RiskEngine::new(cfg.clone());
+    let mut metrics = MetricsEngine::new();
+    let mut pending: Vec<PendingOrder> = Vec::new();
+    let mut pipeline = FeaturePipeline::new(200, 200, 30, 200);
+    let mut friction: Vec<f64> = vec![0.0; strategies.len()];
+    let mut fills_count: Vec<u64> = vec![0; strategies.len()];
+    let mut rejected: Vec<u64> = vec![0; strategies.len()];
+    let mut stale_halted: Vec<bool> = vec![false; strategies.len()];
+    let initial_cash = cfg.starting_capital;
+    let mut buy_hold_entry = None;
+    let mut buy_hold_exit = None;
+    let mut last_row: Option<CsvRow> = None;
+    let mut funding: Vec<f64> = vec![0.0; strategies.len()];
+    let mut prev_ts: Option<u64> = None;
+    let mut liq_baseline = LiqBaseline::new(cfg.liq_baseline_bars);
+
+    let regimes = crate::regime::bar_regimes(rows);
+    let mut tracker = TradeTracker::new(strategies.len());
+    let mut candles = 0;
+    if let Some(warm) = warm {
+        let ids: Vec<String> = strategies.iter().map(|s| s.id.clone()).collect();
+        if warm.ids != ids {
+            return Err(anyhow!(
+                "checkpoint strategies {:?} don't match {:?}",
+                warm.ids,
+                ids
+            ));
+        }
+        market
+            .import_symbol(&cfg.symbol, &warm.market)
+            .map_err(|e| anyhow!("checkpoint market state: {}", e))?;
+        for (inst, state) in strategies.iter_mut().zip(&warm.strategies) {
+            inst.state = state.clone();
+        }
+        pending = warm.pending.clone();
+        friction = warm.friction.clone();
+        funding = warm.funding.clone();
+        fills_count = warm.fills.clone();
+        rejected = warm.rejected.clone();
+        buy_hold_entry = warm.buy_hold_entry;
+        prev_ts = Some(warm.ts);
+        liq_baseline = warm.liq_baseline.clone();
+        tracker.open = warm.open_trades.clone();
+        tracker.closed = warm.closed_trades.clone();
+        candles = warm.candles;
+    }
+
+    for (row_idx, row) in rows.iter().enumerate() {
+        last_row = Some(row.clone());
+        if buy_hold_entry.is_none() {
+            buy_hold_entry = Some(row.c);
+        }
+        buy_hold_exit = Some(row.c);
+        let candle = crate::exchange::Candle {
+            ts: row.ts,
+            o: row.o,
+            h: row.h,
+            l: row.l,
+            c: row.c,
+            v: row.v,
+        };
+        market.on_candle(candle);
+        market.update_aux(
+            &cfg.symbol,
+            MarketAux {
+                funding_rate: row.funding,
+                borrow_rate: row.borrow,
+                liquidation_score: liq_baseline.score(cfg.liq_score_source, row.liq),
+                stable_depeg: row.depeg,
+                fetch_ts: row.ts,
+                has_funding: row.funding != 0.0,
+                has_borrow: row.borrow != 0.0,
+                has_liquidations: row.liq != 0.0,
+                has_depeg: row.depeg != 0.0,
+                next_funding_ts: next_funding_ts(row.ts, cfg.funding_interval_secs),
+            },
+        );
+        let features = pipeline.update(row.c, row.funding, row.oi, row.liq, row.depeg);
+        let _events = detect_phase1(row.ts, &features, &event_cfg);
+        if let Some(prev) = prev_ts {
+            let settlements = funding_settlements(prev, row.ts, cfg.funding_interval_secs);
+            settle_funding(&mut strategies, &mut funding, row, settlements);
+        }
+        prev_ts = Some(row.ts);
+        if let Some(gap) = gaps.iter().find(|g| g.end_ts == row.ts) {
+            if gap_trips_staleness(gap, &cfg) {
+                json_log_at(
+                    Level::Warn,
+                    "risk_guard",
+                    obj(&[
+                        ("check", v_str("candle_staleness")),
+                        ("result", v_str("fail")),
+                        ("symbol", v_str(&cfg.symbol)),
+                        ("candle_ts", v_num(gap.start_ts as f64)),
+                        ("stale_secs", v_num((row.ts - gap.start_ts) as f64)),
+                    ]),
+                );
+                for (idx, inst) in strategies.iter_mut().enumerate() {
+                    if !inst.state.trading_halted {
+                        inst.state.trading_halted = true;
+                        stale_halted[idx] = true;
+                    }
+                }
+            }
+        } else {
+            // Fresh bars again: lift only the halts the outage set
+            for (idx, inst) in strategies.iter_mut().enumerate() {
+                if std::mem::take(&mut stale_halted[idx]) {
+                    inst.state.trading_halted = false;
+                }
+            }
+        }
+        let venue_down = downtime.iter().any(|d| d.contains(row.ts));
+
+        for (idx, inst) in strategies.iter_mut().enumerate() {
+            let view = market.view(&cfg.symbol);
+            let action = inst.strategy.update(view, &mut inst.state);
+            // Operator-disabled: exits still run, entries are dropped
+            let action = if cfg.strategy_enabled(&inst.id) {
+                action
+            } else {
+                action.exits_only(inst.state.portfolio.position)
+            };
+            risk.enforce_drawdown_kill(&mut inst.state, &inst.id);
+            risk.enforce_profit_factor(&mut inst.state, &inst.id);
+            let guarded = risk.apply_with_price(&inst.state, action, row.ts, row.c);
+            if let Action::Close = guarded {
+                let reason = match action {
+                    Action::Close => inst.strategy.exit_reason(view, &inst.state),
+                    _ => ExitReason::Signal,
+                };
+                tracker.mark_exit(idx, reason);
+            }
+
+            let desired = match guarded {
+                Action::Hold => None,
+                Action::Close => {
+                    close_qty(inst.state.portfolio.position, &pending, idx).map(|q| (q, row.c))
+                }
+                Action::Buy { qty } => Some((qty, row.c)),
+                Action::Sell { qty } => Some((-qty.abs(), row.c)),
+            };
+            if let Some((qty, _price)) = desired {
+                if venue_down {
+                    rejected[idx] += 1;
+                } else {
+                    pending.push(PendingOrder {
+                        qty,
+                        submit_ts: row.ts,
+                        strategy_idx: idx,
+                        continued: false,
+                    });
+                }
+            }
+
+            let mut still_pending = Vec::new();
+            for order in pending.drain(..) {
+                if order.strategy_idx != idx || venue_down {
+                    still_pending.push(order);
+                    continue;
+                }
+                let delay = latency_delay(
+                    order.submit_ts,
+                    order.strategy_idx,
+                    cfg.seed,
+                    exec_cfg.latency_min,
+                    exec_cfg.latency_max,
+                );
+                if row.ts < order.submit_ts + delay {
+                    still_pending.push(order);
+                    continue;
+                }
+                // Backtest-only: peek at the next close (see `adverse_lookahead`)
+                if exec_cfg.adverse_lookahead && exec_cfg.is_passive() {
+                    if let Some(next) = rows.get(row_idx + 1) {
+                        let key =
+                            row.ts ^ (idx as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ cfg.seed;
+                        if !adverse_fill_allowed(
+                            order.qty,
+                            row.c,
+                            next.c,
+                            exec_cfg.adverse_selection,
+                            key,
+                        ) {
+                            still_pending.push(order);
+                            continue;
+                        }
+                    }
+                }
+                let fill_qty = partial_fill_qty(order.qty, exec_cfg.max_fill_ratio);
+                let fill_price = slippage_price(
+                    row.c,
+                    fill_qty,
+                    row.v,
+                    exec_cfg.slippage_k,
+                    view.indicators.vol,
+                );
+                let fee = fill_price * fill_qty.abs() * exec_cfg.fee_rate;
+                let slip_cost = (fill_price - row.c).abs() * fill_qty.abs();
+                friction[idx] += fee + slip_cost;
+                let fill = Fill {
+                    price: fill_price,
+                    qty: fill_qty,
+                    fee,
+                    ts: row.ts,
+                };
+                let position_before = inst.state.portfolio.position;
+                let realized = inst.state.portfolio.apply_fill(fill);
+                inst.state.count_tranche(position_before, !order.continued);
+                tracker.on_fill(
+                    idx,
+                    &inst.id,
+                    &fill,
+                    realized,
+                    &inst.state.portfolio,
+                    regimes[row_idx],
+                );
+                fills_count[idx] += 1;
+                inst.state
+                    .metrics
+                    .record_fill(realized, &fill, inst.state.portfolio.position);
+                if realized < 0.0 {
+                    inst.state.record_loss(row.ts, realized);
+                }
+                inst.state.last_trade_ts = row.ts;
+                let day = row.ts / 86_400;
+                if inst.state.trade_day != day {
+                    inst.state.trade_day = day;
+                    inst.state.trades_today = 0;
+                }
+                inst.state.trades_today += 1;
+                let remainder = order.qty - fill_qty;
+                if remainder.abs() > 1e-9 {
+                    still_pending.push(PendingOrder {
+                        qty: remainder,
+                        submit_ts: row.ts,
+                        strategy_idx: idx,
+                        continued: true,
+                    });
+                }
+            }
+            pending = still_pending;
+            metrics.update(&mut inst.state);
+        }
+    }
+
+    let checkpoint = BacktestCheckpoint {
+        ts: prev_ts.unwrap_or(0),
+        candles: candles + rows.len(),
+        market: market.export_symbol(&cfg.symbol).unwrap_or_default(),
+        ids: strategies.iter().map(|s| s.id.clone()).collect(),
+        strategies: strategies.iter().map(|s| s.state.clone()).collect(),
+        pending: pending.clone(),
+        friction: friction.clone(),
+        funding: funding.clone(),
+        fills: fills_count.clone(),
+        rejected: rejected.clone(),
+        buy_hold_entry,
+        liq_baseline: liq_baseline.clone(),
+        open_trades: tracker.open.clone(),
+        closed_trades: tracker.closed.clone(),
+    };
+
+    // Force-close open positions
+    if let Some(last) = last_row.filter(|_| end_of_data) {
+        let view = market.view(&cfg.symbol);
+        for (idx, inst) in strategies.iter_mut().enumerate() {
+            if inst.state.portfolio.position.abs() > 1e-9 {
+                let qty = -inst.state.portfolio.position;
+                let fill_price = slippage_price(
+                    last.c,
+                    qty,
+                    last.v,
+                    exec_cfg.slippage_k,
+                    view.indicators.vol,
+                );
+                let fee = fill_price * qty.abs() * exec_cfg.fee_rate;
+                friction[idx] += fee;
+                let fill = Fill {
+                    price: fill_price,
+                    qty,
+                    fee,
+                    ts: last.ts,
+                };
+                tracker.mark_exit(idx, ExitReason::EndOfData);
+                let realized = inst.state.portfolio.apply_fill(fill);
+                tracker.on_fill(idx, &inst.id, &fill, realized, &inst.state.portfolio, None);
+                inst.state
+                    .metrics
+                    .record_fill(realized, &fill, inst.state.portfolio.position);
+                fills_count[idx] += 1;
+            }
+        }
+    }
+
+    let total_pnl = strategies.iter().map(|s| s.state.metrics.pnl).sum::<f64>();
+    let max_dd = strategies
+        .iter()
+        .map(|s| s.state.metrics.max_drawdown.abs())
+        .fold(0.0, f64::max);
+    let buy_hold_pnl = match (buy_hold_entry, buy_hold_exit) {
+        (Some(e), Some(x)) => x - e,
+        _ => 0.0,
+    };
+
+    let strat_results: Vec<StrategyResult> = strategies
+        .iter()
+        .enumerate()
+        .map(|(idx, inst)| StrategyResult {
+            id: inst.id.clone(),
+            pnl: inst.state.metrics.pnl,
+            equity_pnl: inst.state.portfolio.equity - initial_cash,
+            equity: inst.state.portfolio.equity,
+            friction: friction[idx],
+            funding: funding[idx],
+            max_drawdown: inst.state.metrics.max_drawdown.abs(),
+            sharpe: inst.state.metrics.sharpe(),
+            sortino: inst.state.metrics.sortino(),
+            trades: inst.state.metrics.wins + inst.state.metrics.losses,
+            wins: inst.state.metrics.wins,
+            losses: inst.state.metrics.losses,
+            fills: fills_count[idx],
+            halted: inst.state.trading_halted,
+            rejected: rejected[idx],
+        })
+        .collect();
+
+    let result = BacktestResult {
+        total_pnl,
+        max_drawdown: max_dd,
+        buy_hold_pnl,
+        strategies: strat_results,
+        config_hash: cfg.config_hash(),
+        candle_count: candles + rows.len(),
+        trades: tracker.closed,
+    };
+    Ok((result, checkpoint))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_cfg() -> Config {
+        let mut cfg = Config::from_env();
+        cfg.symbol = "BTCUSDT".to_string();
+        cfg.candle_granularity = 300;
+        cfg.window = 50;
+        cfg
+    }
+
+    #[test]
+    fn test_parse_csv_line_valid() {
+        let line = "1000,1,1,1,1,10,0.0001,0.0,0.0,0.0,0.0";
+        let row = parse_csv_line(line).unwrap();
+        assert_eq!(row.ts, 1000);
+        assert_eq!(row.c, 1.0);
+        assert_eq!(row.funding, 0.0001);
+    }
+
+    #[test]
+    fn test_slippage_price_monotonic() {
+        let base = 100.0;
+        let p1 = slippage_price(base, 0.1, 1000.0, 0.001, 0.01);
+        let p2 = slippage_price(base, 1.0, 1000.0, 0.001, 0.01);
+        assert!(p2 > p1);
+    }
+
+    #[test]
+    fn test_slippage_rises_with_volatility() {
+        let base = 100.0;
+        let low_vol = slippage_price(base, 1.0, 1000.0, 0.001, 0.005);
+        let high_vol = slippage_price(base, 1.0, 1000.0, 0.001, 0.05);
+        assert!(high_vol > low_vol);
+    }
+
+    #[test]
+    fn test_partial_fills_accumulate_to_full() {
+        let mut remaining: f64 = 10.0;
+        let max_fill_ratio = 0.5;
+        let mut filled = 0.0;
+        for _ in 0..5 {
+            if remaining.abs() <= 1e-9 {
+                break;
+            }
+            let fill = remaining * max_fill_ratio;
+            filled += fill;
+            remaining -= fill;
+        }
+        let total = filled + remaining;
+        assert!((total - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_close_nets_orders_in_flight() {
+        let order = |qty, strategy_idx| PendingOrder {
+            qty,
+            submit_ts: 0,
+            strategy_idx,
+            continued: false,
+        };
+        // Nothing in flight: close the whole position
+        assert_eq!(close_qty(2.0, &[], 0), Some(-2.0));
+        // Half a close still working: only the rest goes out, and another
+        // strategy's orders don't count
+        let pending = [order(-1.0, 0), order(5.0, 1)];
+        assert_eq!(close_qty(2.0, &pending, 0), Some(-1.0));
+        // Fully covered: a repeated Close adds nothing
+        assert_eq!(close_qty(2.0, &[order(-2.0, 0)], 0), None);
+        assert_eq!(close_qty(-0.5, &[order(0.5, 0)], 0), None);
+    }
+
+    #[test]
+    fn test_partial_fill_leaves_no_dust() {
+        assert_eq!(partial_fill_qty(10.0, 0.5), 5.0);
+        assert_eq!(partial_fill_qty(-4.0, 0.25), -1.0);
+        // A remainder within 1e-9 of the slice is filled outright
+        assert_eq!(partial_fill_qty(1e-9, 0.5), 1e-9);
+
+        // Halving a remainder stops at flat instead of creeping toward it
+        let mut remaining = 1.0;
+        let mut rounds = 0;
+        while remaining != 0.0 {
+            remaining -= partial_fill_qty(remaining, 0.5);
+            rounds += 1;
+            assert!(rounds < 64, "stuck at {}", remaining);
+        }
+    }
+
+    #[test]
+    fn test_dust_pnl_credits_the_closed_trade() {
+        let mut tracker = TradeTracker::new(1);
+        let mut portfolio = crate::strategy::StrategyState::with_capital(
+            1_000.0,
+            crate::strategy::ContractKind::Linear,
+        )
+        .portfolio;
+        let mut fill = |tracker: &mut TradeTracker, price, qty, ts| {
+            let f = Fill {
+                price,
+                qty,
+                fee: 0.0,
+                ts,
+            };
+            let realized = portfolio.apply_fill(f);
+            tracker.on_fill(0, "churn-0", &f, realized, &portfolio, None);
+        };
+        fill(&mut tracker, 100.0, 1.0, 0);
+        // Closed to within dust: the trade is recorded as flat
+        fill(&mut tracker, 110.0, -(1.0 - 5e-10), 300);
+        assert_eq!(tracker.closed.len(), 1);
+        let before = tracker.closed[0].pnl;
+        // Closing the dust later books its PnL to that trade, not a new one
+        fill(&mut tracker, 120.0, -5e-10, 600);
+        assert_eq!(tracker.closed.len(), 1);
+        assert!((tracker.closed[0].pnl - before - 5e-10 * 20.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_latency_delay_deterministic_and_bounded() {
+        let min = 2;
+        let max = 8;
+        let d1 = latency_delay(1700, 3, 0, min, max);
+        let d2 = latency_delay(1700, 3, 0, min, max);
+        let d3 = latency_delay(1701, 3, 0, min, max);
+        assert_eq!(d1, d2);
+        assert!(d1 >= min && d1 <= max);
+        assert!(d3 >= min && d3 <= max);
+    }
+
+    #[test]
+    fn test_run_backtest_smoke() {
+        let rows = vec![
+            CsvRow {
+                ts: 1000,
+                o: 1.0,
+                h: 1.0,
+                l: 1.0,
+                c: 1.0,
+                v: 1000.0,
+                funding: 0.0,
+                borrow: 0.0,
+                liq: 0.0,
+                depeg: 0.0,
+                oi: 0.0,
+            },
+            CsvRow {
+                ts: 1300,
+                o: 1.0,
+                h: 1.0,
+                l: 1.0,
+                c: 1.1,
+                v: 1100.0,
+                funding: 0.0,
+                borrow: 0.0,
+                liq: 0.0,
+                depeg: 0.0,
+                oi: 0.0,
+            },
+            CsvRow {
+                ts: 1600,
+                o: 1.0,
+                h: 1.0,
+                l: 1.0,
+                c: 0.9,
+                v: 900.0,
+                funding: 0.0,
+                borrow: 0.0,
+                liq: 0.0,
+                depeg: 0.0,
+                oi: 0.0,
+            },
+        ];
+        let cfg = test_cfg();
+        let result = run_backtest(cfg, &rows);
+        assert!(result.is_ok());
+    }
+
+    /// Open a 1.0 long at 100, then step `churn-0` (stop 0.4%, take 0.6%)
+    /// through one bar per close until it exits, recording the round trip.
+    fn exit_path(closes: &[f64], z_momentum: f64) -> TradeRecord {
+        use crate::strategy::{Candle, IndicatorSnapshot, MarketView, StopMode};
+
+        let mut cfg = test_cfg();
+        cfg.edge_hurdle = 0.0;
+        cfg.edge_fee_bps = 0.0;
+        cfg.edge_slip_vol_mult = 0.0;
+        cfg.exit_threshold = 0.4;
+        cfg.min_hold_candles = 0;
+        cfg.time_stop = 3;
+        cfg.stop_loss_mode = StopMode::Fixed;
+        cfg.take_profit_mode = StopMode::Fixed;
+        let mut inst = StrategyInstance::build_churn_set(cfg.clone()).remove(0);
+        let mut tracker = TradeTracker::new(1);
+
+        let open = Fill {
+            price: 100.0,
+            qty: 1.0,
+            fee: 0.0,
+            ts: 0,
+        };
+        let realized = inst.state.portfolio.apply_fill(open);
+        tracker.on_fill(0, &inst.id, &open, realized, &inst.state.portfolio, None);
+
+        for (i, &c) in closes.iter().enumerate() {
+            let ts = (i as u64 + 1) * cfg.candle_granularity;
+            let view = MarketView {
+                symbol: "BTCUSDT",
+                last: Candle {
+                    ts,
+                    o: c,
+                    h: c,
+                    l: c,
+                    c,
+                    v: 1000.0,
+                },
+                indicators: IndicatorSnapshot {
+                    z_momentum,
+                    ..Default::default()
+                },
+                aux: MarketAux::default(),
+            };
+            if let Action::Close = inst.strategy.update(view, &mut inst.state) {
+                tracker.mark_exit(0, inst.strategy.exit_reason(view, &inst.state));
+                let fill = Fill {
+                    price: c,
+                    qty: -inst.state.portfolio.position,
+                    fee: 0.0,
+                    ts,
+                };
+                let realized = inst.state.portfolio.apply_fill(fill);
+                tracker.on_fill(0, &inst.id, &fill, realized, &inst.state.portfolio, None);
+                break;
+            }
+        }
+        tracker.closed.pop().expect("position was closed")
+    }
+
+    #[test]
+    fn test_trade_record_exit_reason_follows_exit_path() {
+        // Drop through the stop on the second bar
+        let stop = exit_path(&[100.1, 99.5], 2.0);
+        assert_eq!(stop.exit_reason, ExitReason::Stop);
+        assert_eq!(stop.exit_ts, 600);
+        assert!(stop.pnl < 0.0);
+
+        // Rally through the take-profit
+        let take = exit_path(&[100.2, 100.8], 2.0);
+        assert_eq!(take.exit_reason, ExitReason::Take);
+        assert!(take.pnl > 0.0);
+
+        // Strong score keeps it open until the 3-bar time stop
+        let time = exit_path(&[100.1, 100.0, 100.1, 100.0], 2.0);
+        assert_eq!(time.exit_reason, ExitReason::Time);
+        assert_eq!(time.exit_ts, 900);
+
+        // Score decays below the exit threshold on the first bar
+        let signal = exit_path(&[100.1], 0.1);
+        assert_eq!(signal.exit_reason, ExitReason::Signal);
+        assert_eq!((signal.entry_ts, signal.exit_ts), (0, 300));
+        assert_eq!(signal.side, 1.0);
+        assert!((signal.exit_px - 100.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_trade_records_account_for_all_realized_pnl() {
+        let rows: Vec<CsvRow> = (0..400)
+            .map(|i| {
+                let c = 100.0 + 3.0 * (i as f64 / 9.0).sin() + 0.01 * i as f64;
+                CsvRow {
+                    ts: 1_000 + i * 300,
+                    o: c,
+                    h: c * 1.002,
+                    l: c * 0.998,
+                    c,
+                    v: 1_000.0 + 50.0 * (i % 7) as f64,
+                    funding: 0.0,
+                    borrow: 0.0,
+                    liq: 0.0,
+                    depeg: 0.0,
+                    oi: 0.0,
+                }
+            })
+            .collect();
+        let result = run_backtest_full(test_cfg(), &rows).unwrap();
+        let trade_pnl: f64 = result.trades.iter().map(|t| t.pnl).sum();
+        assert!(
+            (trade_pnl - result.total_pnl).abs() < 1e-9,
+            "trades={} total={}",
+            trade_pnl,
+            result.total_pnl
+        );
+        for t in &result.trades {
+            assert!(t.exit_ts >= t.entry_ts);
+            assert!(t.qty > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_disabled_strategy_places_no_orders() {
+        let rows: Vec<CsvRow> = (0..400)
+            .map(|i| {
+                let c = 100.0 + 3.0 * (i as f64 / 9.0).sin() + 0.01 * i as f64;
+                CsvRow {
+                    ts: 1_000 + i * 300,
+                    o: c,
+                    h: c * 1.002,
+                    l: c * 0.998,
+                    c,
+                    v: 1_000.0,
+                    funding: 0.0,
+                    borrow: 0.0,
+                    liq: 0.0,
+                    depeg: 0.0,
+                    oi: 0.0,
+                }
+            })
+            .collect();
+        let fills =
+            |r: &BacktestResult, id: &str| r.strategies.iter().find(|s| s.id == id).unwrap().fills;
+        let exec = ExecConfig::instant();
+        let enabled = run_backtest_with_exec(test_cfg(), &rows, &exec).unwrap();
+        assert!(fills(&enabled, "churn-0") > 0);
+
+        let mut cfg = test_cfg();
+        cfg.disabled_strategies = vec!["churn-0".to_string()];
+        let result = run_backtest_with_exec(cfg, &rows, &exec).unwrap();
+        let churn0 = result
+            .strategies
+            .iter()
+            .find(|s| s.id == "churn-0")
+            .unwrap();
+        assert_eq!(churn0.fills, 0);
+        assert_eq!(churn0.equity_pnl, 0.0);
+        assert!(result.trades.iter().all(|t| t.strategy != "churn-0"));
+        // The rest of the set is unaffected
+        assert_eq!(fills(&result, "churn-1"), fills(&enabled, "churn-1"));
+    }
+
+    #[test]
+    fn test_disabled_strategy_still_exits() {
+        let rows: Vec<CsvRow> = (0..400u64)
+            .map(|i| {
+                let c = 100.0 + (i as f64 * 0.7).sin() * 2.0 + i as f64 * 0.01;
+                parse_csv_line(&format!(
+                    "{},{},{},{},{},1000,0,0,0,0,0",
+                    i * 300,
+                    c,
+                    c * 1.002,
+                    c * 0.998,
+                    c
+                ))
+                .unwrap()
+            })
+            .collect();
+        let cfg = test_cfg();
+        let exec = ExecConfig::instant();
+        let first = run_backtest_chunk(cfg.clone(), &rows[..170], &exec, None).unwrap();
+        let (idx, _) = first
+            .strategies
+            .iter()
+            .enumerate()
+            .find(|(_, s)| s.portfolio.position != 0.0)
+            .expect("a position open at the cut");
+        let id = first.ids[idx].clone();
+
+        // Disabled with the position open: it is closed, nothing new opens
+        let mut disabled = cfg.clone();
+        disabled.disabled_strategies = vec![id.clone()];
+        let rest = run_backtest_chunk(disabled, &rows[170..], &exec, Some(&first)).unwrap();
+        assert_eq!(rest.strategies[idx].portfolio.position, 0.0);
+        let mine: Vec<_> = rest
+            .closed_trades
+            .iter()
+            .filter(|t| t.strategy == id && t.exit_ts > first.ts)
+            .collect();
+        assert_eq!(mine.len(), 1, "{:?}", mine);
+        assert!(mine[0].entry_ts <= first.ts);
+    }
+
+    #[test]
+    fn test_same_seed_reproduces_full_backtest() {
+        let rows: Vec<CsvRow> = (0..400)
+            .map(|i| {
+                let c = 100.0 + 3.0 * (i as f64 / 7.0).sin() + 0.02 * i as f64;
+                CsvRow {
+                    ts: 1_000 + i * 300,
+                    o: c,
+                    h: c * 1.002,
+                    l: c * 0.998,
+                    c,
+                    v: 1_000.0 + 50.0 * (i % 5) as f64,
+                    funding: 0.0,
+                    borrow: 0.0,
+                    liq: 0.0,
+                    depeg: 0.0,
+                    oi: 0.0,
+                }
+            })
+            .collect();
+        let mut cfg = test_cfg();
+        cfg.seed = 7;
+        let a = run_backtest_full(cfg.clone(), &rows).unwrap();
+        let b = run_backtest_full(cfg.clone(), &rows).unwrap();
+        assert_eq!(a.to_json(), b.to_json());
+        assert_eq!(a.trades.len(), b.trades.len());
+
+        // The seed is part of the run identity
+        let mut other = cfg;
+        other.seed = 8;
+        assert_ne!(
+            a.config_hash,
+            run_backtest_full(other, &rows).unwrap().config_hash
+        );
+
+        // Jitter follows the seed
+        let draws = |seed| -> Vec<u64> {
+            (0..32)
+                .map(|t| latency_delay(t, 0, seed, 0, 1000))
+                .collect()
+        };
+        assert_eq!(draws(7), draws(7));
+        assert_ne!(draws(7), draws(8));
+    }
+
+    #[test]
+    fn test_edge_filter_prices_the_fills_it_is_charged() {
+        let priced = ExecConfig::taker().with_edge_costs(test_cfg());
+        assert!((priced.edge_fee_bps - 10.0).abs() < 1e-9);
+        assert_eq!(priced.edge_slip_vol_mult, 2.0);
+        let free = ExecConfig::instant().with_edge_costs(test_cfg());
+        assert_eq!((free.edge_fee_bps, free.edge_slip_vol_mult), (0.0, 0.0));
+
+        // Same data, same strategies: costlier fills leave fewer entries
+        let rows: Vec<CsvRow> = (0..600)
+            .map(|i| {
+                let c = 100.0 + (i as f64 * 0.05).sin() * 2.0;
+                CsvRow {
+                    ts: 1_000 + i as u64 * 300,
+                    o: c,
+                    h: c * 1.002,
+                    l: c * 0.998,
+                    c,
+                    v: 1_000.0,
+                    funding: 0.0,
+                    borrow: 0.0,
+                    liq: 0.0,
+                    depeg: 0.0,
+                    oi: 0.0,
+                }
+            })
+            .collect();
+        let mut cfg = test_cfg();
+        cfg.edge_hurdle = 0.0;
+        let cheap = run_backtest_with_exec(cfg.clone(), &rows, &ExecConfig::instant()).unwrap();
+        let dear = ExecConfig {
+            fee_rate: 0.005,
+            ..ExecConfig::instant()
+        };
+        let dear = run_backtest_with_exec(cfg, &rows, &dear).unwrap();
+        assert!(
+            dear.trades.len() < cheap.trades.len(),
+            "{} vs {}",
+            dear.trades.len(),
+            cheap.trades.len()
+        );
+    }
+
+    #[test]
+    fn test_adverse_fill_skews_toward_losers() {
+        // Next move against the order: always fills
+        assert!(adverse_fill_allowed(1.0, 100.0, 99.0, 1.0, 1));
+        assert!(adverse_fill_allowed(-1.0, 100.0, 101.0, 1.0, 1));
+        // Next move in our favor: never at 1.0, always at 0.0
+        assert!(!adverse_fill_allowed(1.0, 100.0, 101.0, 1.0, 1));
+        assert!(adverse_fill_allowed(1.0, 100.0, 101.0, 0.0, 1));
+        // In between, roughly `1 - adverse_sel` of favorable fills survive
+        let kept = (0..10_000u64)
+            .filter(|&k| adverse_fill_allowed(1.0, 100.0, 101.0, 0.3, k))
+            .count();
+        assert!((6_500..7_500).contains(&kept), "kept {}", kept);
+    }
+
+    #[test]
+    fn test_adverse_lookahead_reduces_reversion_edge() {
+        // Noise around a fixed level: every move reverts, so fading pays
+        let mut x: u64 = 11;
+        let rows: Vec<CsvRow> = (0..2000)
+            .map(|i| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                let c = 100.0 + ((x % 1000) as f64 / 1000.0 - 0.5);
+                CsvRow {
+                    ts: 1_000 + i * 300,
+                    o: c,
+                    h: c * 1.001,
+                    l: c * 0.999,
+                    c,
+                    v: 1_000.0,
+                    funding: 0.0,
+                    borrow: 0.0,
+                    liq: 0.0,
+                    depeg: 0.0,
+                    oi: 0.0,
+                }
+            })
+            .collect();
+        // Frictionless maker fills, so the only difference is which fills happen
+        let base = ExecConfig {
+            adverse_selection: 0.8,
+            fee_rate: 0.0,
+            slippage_k: 0.0,
+            vol_slip_mult: 0.0,
+            ..ExecConfig::maker()
+        };
+        let honest = run_backtest_with_exec(Config::from_env(), &rows, &base).unwrap();
+        let adverse = run_backtest_with_exec(
+            Config::from_env(),
+            &rows,
+            &ExecConfig {
+                adverse_lookahead: true,
+                ..base.clone()
+            },
+        )
+        .unwrap();
+        assert!(net_pnl(&honest) > 0.0, "honest={}", net_pnl(&honest));
+        assert!(
+            net_pnl(&adverse) < 0.0,
+            "adverse={} honest={}",
+            net_pnl(&adverse),
+            net_pnl(&honest)
+        );
+
+        // Taker fills cross the spread, so the look-ahead gate leaves them alone
+        let taker = ExecConfig {
+            adverse_selection: 0.8,
+            ..ExecConfig::taker()
+        };
+        let gated = ExecConfig {
+            adverse_lookahead: true,
+            ..taker.clone()
+        };
+        assert_eq!(
+            net_pnl(&run_backtest_with_exec(Config::from_env(), &rows, &taker).unwrap()),
+            net_pnl(&run_backtest_with_exec(Config::from_env(), &rows, &gated).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_funding_accrues_only_at_settlement_boundaries() {
+        let mut cfg = Config::from_env();
+        cfg.funding_interval_secs = 28_800;
+        let mut strategies = StrategyInstance::build_churn_set(cfg.clone());
+        strategies[0].state.portfolio.position = 1.0;
+        strategies[1].state.portfolio.position = -2.0;
+        let mut funding = vec![0.0; strategies.len()];
+        // 5m candles straddling the 08:00 UTC settlement
+        let rows: Vec<CsvRow> = (0..12)
+            .map(|i| CsvRow {
+                ts: 27_600 + i * 300,
+                o: 100.0,
+                h: 100.0,
+                l: 100.0,
+                c: 100.0,
+                v: 1_000.0,
+                funding: 0.0001,
+                borrow: 0.0,
+                liq: 0.0,
+                depeg: 0.0,
+                oi: 0.0,
+            })
+            .collect();
+        let mut prev_ts: Option<u64> = None;
+        for row in &rows {
+            let before = funding.clone();
+            if let Some(prev) = prev_ts {
+                let settlements = funding_settlements(prev, row.ts, cfg.funding_interval_secs);
+                settle_funding(&mut strategies, &mut funding, row, settlements);
+            }
+            prev_ts = Some(row.ts);
+            assert_eq!(funding != before, row.ts == 28_800, "ts {}", row.ts);
+        }
+        // Long pays 1 x 100 x 1bp, the short receives twice that
+        assert!((funding[0] + 0.01).abs() < 1e-12, "{}", funding[0]);
+        assert!((funding[1] - 0.02).abs() < 1e-12, "{}", funding[1]);
+        assert_eq!(funding[2], 0.0);
+        assert!((strategies[0].state.portfolio.cash - (cfg.starting_capital - 0.01)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_warm_started_chunks_match_continuous_run() {
+        let rows: Vec<CsvRow> = (0..400u64)
+            .map(|i| {
+                let c = 100.0 + (i as f64 * 0.7).sin() * 2.0 + i as f64 * 0.01;
+                parse_csv_line(&format!(
+                    "{},{},{},{},{},1000,0.0001,0,0,0,0",
+                    i * 300,
+                    c,
+                    c * 1.002,
+                    c * 0.998,
+                    c
+                ))
+                .unwrap()
+            })
+            .collect();
+        let mut cfg = Config::from_env();
+        cfg.candle_granularity = 300;
+        cfg.candle_granularity_ms = 300_000;
+        cfg.funding_interval_secs = 3600;
+        let exec = ExecConfig::instant();
+        let whole = run_backtest_with_exec(cfg.clone(), &rows, &exec).unwrap();
+
+        // First chunk ends with positions open; its state goes through a WAL
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("backtest.wal");
+        let path = path.to_str().unwrap();
+        let first = run_backtest_chunk(cfg.clone(), &rows[..170], &exec, None).unwrap();
+        assert!(first.strategies.iter().any(|s| s.portfolio.position != 0.0));
+        first.write_to(&mut Wal::open(path).unwrap()).unwrap();
+        let warm = BacktestCheckpoint::load(path).unwrap().unwrap();
+        assert_eq!(warm.ts, rows[169].ts);
+        let chunked = run_backtest_warm(cfg.clone(), &rows[170..], &exec, Some(&warm)).unwrap();
+
+        let close = |a: f64, b: f64| (a - b).abs() <= 1e-9 * a.abs().max(1.0);
+        assert!(!whole.trades.is_empty());
+        assert_eq!(chunked.candle_count, whole.candle_count);
+        assert!(close(chunked.total_pnl, whole.total_pnl));
+        assert!(close(chunked.buy_hold_pnl, whole.buy_hold_pnl));
+        for (a, b) in chunked.strategies.iter().zip(&whole.strategies) {
+            assert_eq!(
+                (&a.id, a.fills, a.trades, a.wins),
+                (&b.id, b.fills, b.trades, b.wins)
+            );
+            for (x, y) in [
+                (a.pnl, b.pnl),
+                (a.equity, b.equity),
+                (a.friction, b.friction),
+                (a.funding, b.funding),
+                (a.max_drawdown, b.max_drawdown),
+                (a.sharpe, b.sharpe),
+            ] {
+                assert!(close(x, y), "{}: {} vs {}", a.id, x, y);
+            }
+        }
+        let trips = |r: &BacktestResult| -> Vec<(String, u64, u64)> {
+            r.trades
+                .iter()
+                .map(|t| (t.strategy.clone(), t.entry_ts, t.exit_ts))
+                .collect()
+        };
+        assert_eq!(trips(&chunked), trips(&whole));
+
+        // A cold start on the second chunk alone diverges
+        let cold = run_backtest_warm(cfg, &rows[170..], &exec, None).unwrap();
+        assert_ne!(trips(&cold), trips(&whole));
+    }
+
+    #[test]
+    fn test_downtime_rejects_orders_and_resumes_cleanly() {
+        let rows: Vec<CsvRow> = (0..300u64)
+            .map(|i| {
+                let c = 100.0 + (i as f64 * 0.7).sin() * 2.0;
+                parse_csv_line(&format!(
+                    "{},{},{},{},{},1000,0,0,0,0,0",
+                    i * 300,
+                    c,
+                    c * 1.002,
+                    c * 0.998,
+                    c
+                ))
+                .unwrap()
+            })
+            .collect();
+        let mut cfg = Config::from_env();
+        cfg.candle_granularity = 300;
+        cfg.candle_granularity_ms = 300_000;
+        let exec = ExecConfig::instant();
+        let window = Downtime {
+            start_ts: 150 * 300,
+            end_ts: 180 * 300,
+        };
+
+        let base = run_backtest_with_exec(cfg.clone(), &rows, &exec).unwrap();
+        let down = run_backtest_with_downtime(cfg, &rows, &exec, &[], &[window]).unwrap();
+
+        // Orders inside the window fail and nothing fills there
+        assert!(base.strategies.iter().all(|s| s.rejected == 0));
+        assert!(down.strategies.iter().map(|s| s.rejected).sum::<u64>() > 0);
+        assert!(down
+            .trades
+            .iter()
+            .all(|t| !window.contains(t.entry_ts) && !window.contains(t.exit_ts)));
+
+        // Identical history up to the outage
+        let before = |r: &BacktestResult| -> Vec<(String, u64, u64)> {
+            r.trades
+                .iter()
+                .filter(|t| t.exit_ts < window.start_ts)
+                .map(|t| (t.strategy.clone(), t.entry_ts, t.exit_ts))
+                .collect()
+        };
+        assert!(!before(&base).is_empty());
+        assert_eq!(before(&base), before(&down));
+
+        // Trading resumes, without a backlog of rejected orders stacking up
+        assert!(down.trades.iter().any(|t| t.entry_ts >= window.end_ts));
+        let max_qty = base.trades.iter().map(|t| t.qty).fold(0.0, f64::max);
+        for s in &down.strategies {
+            let mut trades: Vec<_> = down.trades.iter().filter(|t| t.strategy == s.id).collect();
+            trades.sort_by_key(|t| t.entry_ts);
+            assert!(trades.windows(2).all(|w| w[1].entry_ts >= w[0].exit_ts));
+            assert!(trades.iter().all(|t| t.qty <= max_qty + 1e-9), "{}", s.id);
+        }
+    }
+
+    #[test]
+    fn test_manifest_gap_halts_until_bars_resume() {
+        // 5m bars with a two-hour outage in the middle
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("gapped.csv");
+        let mut csv = crate::data::EXPECTED_COLUMNS.join(",");
+        let ts_of = |i: u64| if i < 150 { i * 300 } else { i * 300 + 7_200 };
+        for i in 0..300u64 {
+            let c = 100.0 + (i as f64 * 0.7).sin() * 2.0;
+            csv.push_str(&format!(
+                "\n{},{},{},{},{},1000,0,0,0,0,0",
+                ts_of(i),
+                c,
+                c * 1.002,
+                c * 0.998,
+                c
+            ));
+        }
+        std::fs::write(&path, csv).unwrap();
+        let (manifest, _) = crate::data::analyze_csv(&path, 300, 0, 0).unwrap();
+        assert_eq!(manifest.gaps.len(), 1);
+        let gap = manifest.gaps[0].clone();
+        assert_eq!(gap.end_ts, ts_of(150));
+
+        let rows: Vec<CsvRow> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .skip(1)
+            .map(|l| parse_csv_line(l).unwrap())
+            .collect();
+        let mut cfg = Config::from_env();
+        cfg.candle_granularity = 300;
+        cfg.candle_granularity_ms = 300_000;
+        cfg.max_latency_ms = 60_000;
+        assert!(gap_trips_staleness(&gap, &cfg));
+
+        let exec = ExecConfig::instant();
+        let contiguous = run_backtest_with_exec(cfg.clone(), &rows, &exec).unwrap();
+        assert!(contiguous.strategies.iter().all(|s| !s.halted));
+        assert!(contiguous.trades.iter().any(|t| t.entry_ts >= gap.end_ts));
+
+        // Halted on the bar the outage ends...
+        let cut =
+            run_backtest_with_gaps(cfg.clone(), &rows[..=150], &exec, &manifest.gaps).unwrap();
+        assert!(cut.strategies.iter().all(|s| s.halted));
+        // ...and trading again once contiguous bars resume
+        let outage = run_backtest_with_gaps(cfg.clone(), &rows, &exec, &manifest.gaps).unwrap();
+        assert!(outage.strategies.iter().all(|s| !s.halted));
+        assert!(outage.trades.iter().any(|t| t.entry_ts > gap.end_ts));
+
+        // A one-bar hole stays inside the latency budget
+        let short = Gap {
+            start_ts: 0,
+            end_ts: 600,
+            missing_bars: 1,
+        };
+        assert!(!gap_trips_staleness(&short, &cfg));
+    }
+}
@@ -0,0 +1,160 @@
+//! Trade-order Monte Carlo: how much of a backtest's drawdown was luck.
+//!
+//! The realized round-trip PnLs are reshuffled `n` times and each ordering
+//! is replayed as a cumulative PnL path. Final PnL is order-invariant, so
+//! it comes out the same every run; max drawdown is not, and where the
+//! observed path lands in the shuffled distribution shows whether the
+//! backtest drew a kind or a harsh ordering. Drawdowns here are positive
+//! amounts in quote currency (peak-to-trough of cumulative PnL), not
+//! fractions of equity.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::Serialize;
+
+use super::TradeRecord;
+
+/// Seed `monte_carlo` uses, so repeated runs over one backtest agree.
+pub const MONTE_CARLO_SEED: u64 = 0x5eed_cafe;
+
+/// Outcome of `n` reshuffled trade orderings.
+#[derive(Debug, Clone, Serialize)]
+pub struct DrawdownDistribution {
+    pub runs: usize,
+    pub trades: usize,
+    /// Max drawdown of the trades in their actual order.
+    pub observed_max_drawdown: f64,
+    /// Fraction of shuffles that drew down no more than the observed path;
+    /// near 1.0 means the backtest got an unusually harsh ordering, near
+    /// 0.0 an unusually kind one.
+    pub observed_percentile: f64,
+    /// Per-run max drawdown, sorted ascending.
+    pub max_drawdowns: Vec<f64>,
+    /// Per-run final PnL, in run order.
+    pub final_pnls: Vec<f64>,
+}
+
+impl DrawdownDistribution {
+    pub fn mean_final_pnl(&self) -> f64 {
+        mean(&self.final_pnls)
+    }
+
+    pub fn mean_max_drawdown(&self) -> f64 {
+        mean(&self.max_drawdowns)
+    }
+
+    /// Nearest-rank `p` quantile of max drawdown (0.95 = the bad tail).
+    pub fn drawdown_quantile(&self, p: f64) -> f64 {
+        if self.max_drawdowns.is_empty() {
+            return 0.0;
+        }
+        let idx = (p.clamp(0.0, 1.0) * (self.max_drawdowns.len() - 1) as f64).round() as usize;
+        self.max_drawdowns[idx]
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+}
+
+/// Reshuffle `trades` `n` times with the default seed.
+pub fn monte_carlo(trades: &[TradeRecord], n: usize) -> DrawdownDistribution {
+    monte_carlo_seeded(trades, n, MONTE_CARLO_SEED)
+}
+
+pub fn monte_carlo_seeded(trades: &[TradeRecord], n: usize, seed: u64) -> DrawdownDistribution {
+    let mut pnls: Vec<f64> = trades.iter().map(|t| t.pnl).collect();
+    let observed = path_stats(&pnls).1;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut final_pnls = Vec::with_capacity(n);
+    let mut max_drawdowns = Vec::with_capacity(n);
+    for _ in 0..n {
+        pnls.shuffle(&mut rng);
+        let (final_pnl, dd) = path_stats(&pnls);
+        final_pnls.push(final_pnl);
+        max_drawdowns.push(dd);
+    }
+    max_drawdowns.sort_by(|a, b| a.total_cmp(b));
+    let at_or_below = max_drawdowns.iter().filter(|&&dd| dd <= observed).count();
+    DrawdownDistribution {
+        runs: n,
+        trades: pnls.len(),
+        observed_max_drawdown: observed,
+        observed_percentile: if n > 0 {
+            at_or_below as f64 / n as f64
+        } else {
+            0.0
+        },
+        max_drawdowns,
+        final_pnls,
+    }
+}
+
+/// (final PnL, max drawdown) of the cumulative path starting at zero.
+fn path_stats(pnls: &[f64]) -> (f64, f64) {
+    let (mut cum, mut peak, mut dd) = (0.0f64, 0.0f64, 0.0f64);
+    for pnl in pnls {
+        cum += pnl;
+        peak = peak.max(cum);
+        dd = dd.max(peak - cum);
+    }
+    (cum, dd)
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    if xs.is_empty() {
+        0.0
+    } else {
+        xs.iter().sum::<f64>() / xs.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::ExitReason;
+
+    fn trade(pnl: f64) -> TradeRecord {
+        TradeRecord {
+            strategy: "churn-0".to_string(),
+            entry_ts: 0,
+            exit_ts: 0,
+            side: 1.0,
+            entry_px: 100.0,
+            exit_px: 100.0,
+            qty: 1.0,
+            pnl,
+            regime: None,
+            exit_reason: ExitReason::Signal,
+        }
+    }
+
+    #[test]
+    fn test_final_pnl_is_order_invariant_while_drawdown_varies() {
+        // All the losses up front: the worst possible ordering
+        let pnls = [-4.0, -3.0, -2.5, -1.0, 2.0, 3.5, 4.0, 5.0, 1.5, 2.5];
+        let trades: Vec<TradeRecord> = pnls.iter().map(|&p| trade(p)).collect();
+        let total: f64 = pnls.iter().sum();
+
+        let dist = monte_carlo(&trades, 500);
+        assert_eq!(dist.runs, 500);
+        assert!((dist.mean_final_pnl() - total).abs() < 1e-9);
+        assert!(dist.final_pnls.iter().all(|p| (p - total).abs() < 1e-9));
+
+        assert_eq!(dist.observed_max_drawdown, 10.5);
+        let (lo, hi) = (dist.max_drawdowns[0], dist.drawdown_quantile(1.0));
+        assert!(lo < hi, "drawdown should vary: {} .. {}", lo, hi);
+        assert!(hi <= 10.5 + 1e-9);
+        assert!(dist.observed_percentile > 0.95);
+    }
+
+    #[test]
+    fn test_seeded_runs_repeat() {
+        let trades: Vec<TradeRecord> = (0..20).map(|i| trade(i as f64 - 9.5)).collect();
+        let a = monte_carlo_seeded(&trades, 50, 7);
+        let b = monte_carlo_seeded(&trades, 50, 7);
+        assert_eq!(a.max_drawdowns, b.max_drawdowns);
+        assert_eq!(monte_carlo(&[], 10).observed_max_drawdown, 0.0);
+    }
+}
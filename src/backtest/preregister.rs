@@ -0,0 +1,173 @@
+//! Dataset pre-registration (trap #11: selection bias).
+//!
+//! Test sets are declared up front in `preregistration.json`, keyed by the
+//! SHA256 of each file. Results on datasets outside the manifest (or whose
+//! bytes changed after registration) are only reportable as exploratory.
+
+use crate::data::file_sha256;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const DEFAULT_PREREG_PATH: &str = "preregistration.json";
+
+/// A single pre-registered dataset.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RegisteredDataset {
+    pub path: String,
+    pub hash_sha256: String,
+}
+
+/// The committed list of datasets results may be reported on.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Preregistration {
+    pub datasets: Vec<RegisteredDataset>,
+    pub generated_at_epoch: u64,
+}
+
+/// Outcome of checking a dataset against the pre-registration.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RegistrationCheck {
+    /// Path and content hash match a registered entry.
+    Registered,
+    /// Path is not in the manifest.
+    Unregistered,
+    /// Path is registered but the file bytes changed since registration.
+    HashMismatch { expected: String, actual: String },
+}
+
+impl RegistrationCheck {
+    pub fn is_registered(&self) -> bool {
+        matches!(self, RegistrationCheck::Registered)
+    }
+
+    /// Results may be reported if registered, or if the run is explicitly exploratory.
+    pub fn allows_report(&self, exploratory: bool) -> bool {
+        self.is_registered() || exploratory
+    }
+
+    pub fn reason(&self) -> String {
+        match self {
+            RegistrationCheck::Registered => "registered".to_string(),
+            RegistrationCheck::Unregistered => "dataset not in preregistration".to_string(),
+            RegistrationCheck::HashMismatch { expected, actual } => format!(
+                "dataset changed since registration: expected {} got {}",
+                expected, actual
+            ),
+        }
+    }
+}
+
+impl Preregistration {
+    /// Hash each dataset path into a new pre-registration.
+    pub fn register<P: AsRef<Path>>(paths: &[P], now_ts: u64) -> Result<Self, String> {
+        let mut datasets = Vec::with_capacity(paths.len());
+        for p in paths {
+            let path = p.as_ref();
+            datasets.push(RegisteredDataset {
+                path: normalize_path(path),
+                hash_sha256: file_sha256(path)?,
+            });
+        }
+        Ok(Self {
+            datasets,
+            generated_at_epoch: now_ts,
+        })
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let raw = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        serde_json::from_str(&raw).map_err(|e| e.to_string())
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    /// Check a dataset path against the registered entries.
+    pub fn check(&self, dataset_path: &Path) -> Result<RegistrationCheck, String> {
+        let key = normalize_path(dataset_path);
+        let entry = match self.datasets.iter().find(|d| d.path == key) {
+            Some(e) => e,
+            None => return Ok(RegistrationCheck::Unregistered),
+        };
+        let actual = file_sha256(dataset_path)?;
+        if actual == entry.hash_sha256 {
+            Ok(RegistrationCheck::Registered)
+        } else {
+            Ok(RegistrationCheck::HashMismatch {
+                expected: entry.hash_sha256.clone(),
+                actual,
+            })
+        }
+    }
+}
+
+/// Strip a leading `./` so `data/x.csv` and `./data/x.csv` share one key.
+fn normalize_path(path: &Path) -> String {
+    let s = path.display().to_string();
+    s.strip_prefix("./").map(|v| v.to_string()).unwrap_or(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(path: &Path, body: &str) {
+        fs::write(path, body).unwrap();
+    }
+
+    #[test]
+    fn test_registered_dataset_passes() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.csv");
+        write(&path, "ts,open\n1000,1\n");
+        let prereg = Preregistration::register(&[&path], 0).unwrap();
+        let check = prereg.check(&path).unwrap();
+        assert_eq!(check, RegistrationCheck::Registered);
+        assert!(check.allows_report(false));
+    }
+
+    #[test]
+    fn test_unregistered_dataset_flagged() {
+        let dir = TempDir::new().unwrap();
+        let registered = dir.path().join("a.csv");
+        let other = dir.path().join("b.csv");
+        write(&registered, "ts,open\n1000,1\n");
+        write(&other, "ts,open\n2000,2\n");
+        let prereg = Preregistration::register(&[&registered], 0).unwrap();
+        let check = prereg.check(&other).unwrap();
+        assert_eq!(check, RegistrationCheck::Unregistered);
+        assert!(!check.allows_report(false));
+        assert!(check.allows_report(true));
+    }
+
+    #[test]
+    fn test_changed_bytes_invalidate_registration() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.csv");
+        write(&path, "ts,open\n1000,1\n");
+        let prereg = Preregistration::register(&[&path], 0).unwrap();
+        write(&path, "ts,open\n1000,2\n");
+        let check = prereg.check(&path).unwrap();
+        assert!(matches!(check, RegistrationCheck::HashMismatch { .. }));
+        assert!(!check.allows_report(false));
+    }
+
+    #[test]
+    fn test_roundtrip_through_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("a.csv");
+        write(&path, "ts,open\n1000,1\n");
+        let manifest = dir.path().join("preregistration.json");
+        Preregistration::register(&[&path], 42)
+            .unwrap()
+            .write(&manifest)
+            .unwrap();
+        let loaded = Preregistration::load(&manifest).unwrap();
+        assert_eq!(loaded.generated_at_epoch, 42);
+        assert!(loaded.check(&path).unwrap().is_registered());
+    }
+}
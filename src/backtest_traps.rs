@@ -421,7 +421,7 @@ pub mod trap_10_staleness_zero {
 ///
 /// **The Lie:** Picking winning periods/symbols
 ///
-/// **Guard:** Pre-register test sets; always report all runs
+/// **Guard:** Pre-register test sets (`backtest::preregister`); always report all runs
 pub mod trap_11_selection_bias {
     use super::*;
 
@@ -549,6 +549,8 @@ pub mod trap_15_clean_room {
         pub duplicate_rate: f64,
         /// Probability of missing candle
         pub missing_candle_rate: f64,
+        /// Seed for every chaos draw (Config.seed)
+        pub seed: u64,
     }
 
     impl Default for ChaosConfig {
@@ -558,9 +560,70 @@ pub mod trap_15_clean_room {
                 delay_range_ms: (0, 1000),
                 duplicate_rate: 0.005,
                 missing_candle_rate: 0.01,
+                seed: 0,
             }
         }
     }
+
+    impl ChaosConfig {
+        /// Default knobs drawing from the run's `Config.seed`.
+        pub fn from_config(cfg: &crate::state::Config) -> Self {
+            Self {
+                seed: cfg.seed,
+                ..Self::default()
+            }
+        }
+
+        /// Uniform draw in [0, 1) for the `n`th decision of `stream`.
+        /// Pure function of (seed, stream, n) so a run replays exactly.
+        fn draw(&self, stream: u64, n: u64) -> f64 {
+            let mut x = self.seed
+                ^ stream.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+                ^ n.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            x ^= x >> 30;
+            x = x.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            x ^= x >> 27;
+            x = x.wrapping_mul(0x94d0_49bb_1331_11eb);
+            x ^= x >> 31;
+            (x >> 11) as f64 / (1u64 << 53) as f64
+        }
+
+        /// Whether the `attempt`th fetch fails.
+        pub fn fetch_fails(&self, attempt: u64) -> bool {
+            self.draw(1, attempt) < self.fetch_failure_rate
+        }
+
+        /// Feed `events` through the chaos knobs: drop missing candles,
+        /// duplicate events and delay arrival within `delay_range_ms`.
+        /// Returns `(arrival_ts, event)` sorted by arrival.
+        pub fn apply<T: Clone>(&self, events: &[(u64, T)]) -> Vec<(u64, T)> {
+            let (lo, hi) = self.delay_range_ms;
+            let span = hi.saturating_sub(lo) + 1;
+            let mut out = Vec::with_capacity(events.len());
+            for (i, (ts, event)) in events.iter().enumerate() {
+                let i = i as u64;
+                if self.draw(2, i) < self.missing_candle_rate {
+                    continue;
+                }
+                let copies = if self.draw(3, i) < self.duplicate_rate {
+                    2
+                } else {
+                    1
+                };
+                for c in 0..copies {
+                    let delay = lo + ((self.draw(4, i * 2 + c) * span as f64) as u64).min(span - 1);
+                    out.push((ts + delay, event.clone()));
+                }
+            }
+            out.sort_by_key(|(ts, _)| *ts);
+            out
+        }
+
+        /// `trap_07_event_order::scramble_events` keyed by this seed.
+        pub fn scramble<T: Clone>(&self, events: &[(u64, T)], window_ms: u64) -> Vec<(u64, T)> {
+            super::trap_07_event_order::scramble_events(events, window_ms, self.seed)
+        }
+    }
 }
 
 /// Trap #16: WAL not used for determinism
@@ -641,41 +704,8 @@ pub mod trap_18_rounding {
         guard: "Apply tick_size, step_size, min_notional in backtest same as live",
     };
 
-    /// Exchange filters
-    #[derive(Debug, Clone)]
-    pub struct ExchangeFilters {
-        /// Price tick size (e.g., 0.01)
-        pub tick_size: f64,
-        /// Quantity step size (e.g., 0.001)
-        pub step_size: f64,
-        /// Minimum notional value (e.g., 10.0)
-        pub min_notional: f64,
-    }
-
-    impl ExchangeFilters {
-        pub fn binance_btcusdt() -> Self {
-            Self {
-                tick_size: 0.01,
-                step_size: 0.00001,
-                min_notional: 10.0,
-            }
-        }
-
-        /// Round quantity to step size
-        pub fn round_qty(&self, qty: f64) -> f64 {
-            (qty / self.step_size).floor() * self.step_size
-        }
-
-        /// Round price to tick size
-        pub fn round_price(&self, price: f64) -> f64 {
-            (price / self.tick_size).round() * self.tick_size
-        }
-
-        /// Check if order meets minimum notional
-        pub fn meets_min_notional(&self, qty: f64, price: f64) -> bool {
-            qty * price >= self.min_notional
-        }
-    }
+    /// Lives with the live order path so backtest and live round alike.
+    pub use crate::exchange::contract_spec::ExchangeFilters;
 }
 
 /// Trap definition
@@ -756,7 +786,7 @@ pub fn trap_status() -> Vec<TrapStatus> {
             name: "Warmup pollution",
             severity: Severity::High,
             guard: GuardStatus::Guarded,
-            evidence: "FeaturePipeline pre-fills 200 candles before trading",
+            evidence: "FeaturePipeline pre-fills 200 candles; live loop holds until samples >= max(ema_slow, vol_window)",
         },
         TrapStatus {
             id: 3,
@@ -818,8 +848,8 @@ pub fn trap_status() -> Vec<TrapStatus> {
             id: 11,
             name: "Selection bias",
             severity: Severity::High,
-            guard: GuardStatus::Unguarded,
-            evidence: "No pre-registration; user controls dataset choice",
+            guard: GuardStatus::Guarded,
+            evidence: "preregistration.json hashes test sets; backtest refuses others without --exploratory",
         },
         TrapStatus {
             id: 12,
@@ -883,6 +913,39 @@ pub fn integrity_score() -> (usize, usize) {
     (guarded, traps.len())
 }
 
+/// Machine-readable trap status for CI gating.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TrapReport {
+    pub guarded: usize,
+    pub total: usize,
+    /// Ids of Critical traps with no guard at all; CI fails when non-empty.
+    pub critical_unguarded: Vec<u8>,
+    pub traps: Vec<TrapStatus>,
+}
+
+impl TrapReport {
+    pub fn passes(&self) -> bool {
+        self.critical_unguarded.is_empty()
+    }
+}
+
+/// Current trap status plus integrity score.
+pub fn trap_report() -> TrapReport {
+    let traps = trap_status();
+    let (guarded, total) = integrity_score();
+    let critical_unguarded = traps
+        .iter()
+        .filter(|t| t.severity == Severity::Critical && t.guard == GuardStatus::Unguarded)
+        .map(|t| t.id)
+        .collect();
+    TrapReport {
+        guarded,
+        total,
+        critical_unguarded,
+        traps,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -924,6 +987,37 @@ mod tests {
         // Events across windows maintain window order
     }
 
+    #[test]
+    fn test_chaos_replays_from_seed() {
+        let events: Vec<(u64, u64)> = (0..500).map(|i| (i * 100, i)).collect();
+        let chaos = |seed| trap_15_clean_room::ChaosConfig {
+            fetch_failure_rate: 0.2,
+            duplicate_rate: 0.1,
+            missing_candle_rate: 0.1,
+            seed,
+            ..Default::default()
+        };
+        let run = |c: &trap_15_clean_room::ChaosConfig| {
+            let fed = c.scramble(&c.apply(&events), 250);
+            let fails: Vec<bool> = (0..64).map(|n| c.fetch_fails(n)).collect();
+            (fed, fails)
+        };
+
+        let a = chaos(7);
+        assert_eq!(run(&a), run(&chaos(7)));
+        assert_ne!(run(&a), run(&chaos(8)));
+
+        // Knobs actually bite
+        let (fed, fails) = run(&a);
+        assert_ne!(fed.len(), events.len());
+        assert!(fails.iter().any(|f| *f));
+        assert!(fails.iter().any(|f| !*f));
+
+        let mut cfg = crate::state::Config::from_env();
+        cfg.seed = 7;
+        assert_eq!(trap_15_clean_room::ChaosConfig::from_config(&cfg).seed, 7);
+    }
+
     #[test]
     fn test_exchange_filters() {
         let filters = trap_18_rounding::ExchangeFilters::binance_btcusdt();
@@ -942,4 +1036,19 @@ mod tests {
         // Different hash = violation
         assert!(trap_16_wal_determinism::verify_replay_determinism(12345, 12346).is_some());
     }
+
+    #[test]
+    fn test_trap_report_json() {
+        let report = trap_report();
+        let json = serde_json::to_value(&report).unwrap();
+        let traps = json["traps"].as_array().unwrap();
+        assert_eq!(traps.len(), 18);
+        let ids: Vec<u64> = traps.iter().map(|t| t["id"].as_u64().unwrap()).collect();
+        assert_eq!(ids, (1..=18).collect::<Vec<u64>>());
+
+        let guarded = traps.iter().filter(|t| t["guard"] == "Guarded").count();
+        assert_eq!(json["guarded"].as_u64().unwrap() as usize, guarded);
+        assert_eq!(json["total"], 18);
+        assert!(report.passes());
+    }
 }
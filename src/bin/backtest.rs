@@ -1,14 +1,20 @@
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
-use arbitragefx::backtest::{parse_csv_line, run_backtest};
+use arbitragefx::backtest::preregister::{Preregistration, DEFAULT_PREREG_PATH};
+use arbitragefx::backtest::{parse_csv_line, run_backtest, run_backtest_with_gaps, ExecConfig};
 use arbitragefx::data::analyze_csv;
 use arbitragefx::regime::classify_dataset;
-use arbitragefx::state::Config;
+use arbitragefx::state::{check_granularity, detect_interval, Config};
 
 fn main() {
-    let path = std::env::args()
-        .nth(1)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let exploratory = args.iter().any(|a| a == "--exploratory");
+    let strict_interval = args.iter().any(|a| a == "--strict-interval");
+    let path = args
+        .iter()
+        .find(|a| !a.starts_with("--"))
+        .cloned()
         .unwrap_or_else(|| "data.csv".to_string());
     let file = match File::open(&path) {
         Ok(f) => f,
@@ -62,6 +68,24 @@ fn main() {
         eprintln!("no rows parsed");
         return;
     }
+    let cfg = Config::from_env();
+    // Time stops, cooldowns and min-hold count configured bars, not data rows
+    let timestamps: Vec<u64> = rows.iter().map(|r| r.ts).collect();
+    let detected_interval = detect_interval(&timestamps);
+    if let Err(err) = check_granularity(cfg.candle_granularity, detected_interval) {
+        if strict_interval {
+            eprintln!(
+                "refusing to run {}: {}; set CANDLE_SECS to the dataset interval",
+                path, err
+            );
+            std::process::exit(5);
+        }
+        eprintln!(
+            "WARNING {}: {}; bar-counted rules (TIME_STOP, cooldowns, min hold) are rescaled. \
+             Set CANDLE_SECS to the dataset interval or pass --strict-interval to refuse.",
+            path, err
+        );
+    }
     // Regime classification
     let regime = classify_dataset(&rows);
     println!(
@@ -79,9 +103,74 @@ fn main() {
         regime.reflexive_frac * 100.0
     );
 
-    let cfg = Config::from_env();
-    match run_backtest(cfg, &rows) {
-        Ok((pnl, dd)) => println!("pnl_total={:.4} max_drawdown={:.4}", pnl, dd),
+    // Trap #11: only report on pre-registered datasets unless explicitly exploratory
+    let prereg_path =
+        std::env::var("PREREG_PATH").unwrap_or_else(|_| DEFAULT_PREREG_PATH.to_string());
+    let prereg = Preregistration::load(prereg_path.as_ref()).unwrap_or_default();
+    let check = match prereg.check(path.as_ref()) {
+        Ok(c) => c,
+        Err(err) => {
+            eprintln!("preregistration check failed: {}", err);
+            std::process::exit(5);
+        }
+    };
+    if !check.allows_report(exploratory) {
+        eprintln!(
+            "refusing to report {}: {} ({}); rerun with --exploratory",
+            path,
+            check.reason(),
+            prereg_path
+        );
+        std::process::exit(5);
+    }
+    if !check.is_registered() {
+        println!("exploratory=true reason=\"{}\"", check.reason());
+    }
+
+    // Holes in the data are feed outages the live staleness guard would see
+    let interval_secs = detected_interval.unwrap_or(cfg.candle_granularity);
+    let gaps = match analyze_csv(path.as_ref(), interval_secs, u64::MAX, 0) {
+        Ok((manifest, _)) => manifest.gaps,
+        Err(err) => {
+            eprintln!("gap scan failed, treating data as contiguous: {}", err);
+            Vec::new()
+        }
+    };
+    if gaps.is_empty() {
+        match run_backtest(cfg, &rows) {
+            Ok((pnl, dd)) => println!("pnl_total={:.4} max_drawdown={:.4}", pnl, dd),
+            Err(err) => eprintln!("backtest failed: {}", err),
+        }
+        return;
+    }
+    println!("gaps={}", gaps.len());
+    match run_backtest_with_gaps(cfg, &rows, &ExecConfig::from_env(), &gaps) {
+        Ok(result) => {
+            println!("baseline=buy_hold pnl={:.4}", result.buy_hold_pnl);
+            println!("baseline=no_trade pnl=0.0000");
+            for s in &result.strategies {
+                println!(
+                    "strategy={} pnl={:.4} equity_pnl={:.4} equity={:.4} friction={:.4} funding={:.4} dd={:.4} trades={} wins={} losses={} fills={} rejected={} halted={}",
+                    s.id,
+                    s.pnl,
+                    s.equity_pnl,
+                    s.equity,
+                    s.friction,
+                    s.funding,
+                    s.max_drawdown,
+                    s.trades,
+                    s.wins,
+                    s.losses,
+                    s.fills,
+                    s.rejected,
+                    s.halted
+                );
+            }
+            println!(
+                "pnl_total={:.4} max_drawdown={:.4}",
+                result.total_pnl, result.max_drawdown
+            );
+        }
         Err(err) => eprintln!("backtest failed: {}", err),
     }
 }
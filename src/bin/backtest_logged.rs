@@ -120,6 +120,7 @@ fn main() {
                 has_borrow: row.borrow != 0.0,
                 has_liquidations: row.liq != 0.0,
                 has_depeg: row.depeg != 0.0,
+                next_funding_ts: 0,
             },
         );
 
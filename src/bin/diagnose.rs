@@ -61,6 +61,7 @@ fn main() {
                 has_borrow: row.borrow != 0.0,
                 has_liquidations: row.liq != 0.0,
                 has_depeg: row.depeg != 0.0,
+                next_funding_ts: 0,
             },
         );
 
@@ -101,12 +102,13 @@ fn main() {
 
         if let Some((qty, price)) = fill {
             let fee = price * qty.abs() * 0.001;
-            let realized = inst.state.portfolio.apply_fill(Fill {
+            let fill = Fill {
                 price,
                 qty,
                 fee,
                 ts: row.ts,
-            });
+            };
+            let realized = inst.state.portfolio.apply_fill(fill);
 
             // Log trades
             if bar_idx < 50 || realized.abs() > 0.0 {
@@ -116,12 +118,13 @@ fn main() {
                 );
             }
 
-            inst.state.metrics.record_trade(realized);
+            inst.state
+                .metrics
+                .record_fill(realized, &fill, inst.state.portfolio.position);
         }
 
         // Mark to market equity and track drawdown
-        inst.state.portfolio.equity =
-            inst.state.portfolio.cash + inst.state.portfolio.position * row.c;
+        inst.state.portfolio.equity = inst.state.portfolio.mark_equity(row.c);
         if inst.state.portfolio.equity > inst.state.metrics.equity_peak {
             inst.state.metrics.equity_peak = inst.state.portfolio.equity;
         }
@@ -10,10 +10,7 @@ fn pick(seed: &mut u64, n: u64) -> u64 {
 }
 
 fn main() {
-    let mut seed = env::var("SEED")
-        .ok()
-        .and_then(|v| v.parse().ok())
-        .unwrap_or(42u64);
+    let mut seed = arbitragefx::state::Config::from_env().seed;
     let events = env::var("EVENTS")
         .ok()
         .and_then(|v| v.parse().ok())
@@ -12,7 +12,7 @@ use arbitragefx::strategy::Action;
 async fn main() -> Result<()> {
     let cfg = Config::from_env();
     let exchange = ExchangeKind::from_env().build(cfg.clone())?;
-    let aux_fetcher = AuxDataFetcher::new();
+    let aux_fetcher = AuxDataFetcher::new().with_venue(ExchangeKind::from_env());
     let mut market = MarketState::new(cfg.clone());
     let mut strategies = StrategyInstance::build_carry_event_set(cfg.clone());
     let mut risk = RiskEngine::new(cfg.clone());
@@ -59,7 +59,7 @@ async fn main() -> Result<()> {
             metrics.update(&mut inst.state);
         }
 
-        let sleep_for = cfg.sleep_until_next_candle(candle.ts);
-        sleep(Duration::from_secs(sleep_for)).await;
+        let sleep_for = cfg.sleep_until_next_candle_ms(candle.ts.saturating_mul(1000));
+        sleep(Duration::from_millis(sleep_for)).await;
     }
 }
@@ -0,0 +1,116 @@
+//! Dataset manifest writer.
+//!
+//! Usage:
+//!   cargo run --bin manifest -- <csv> [interval_secs] [ttl_secs] [--max-bad-rows N] [--max-gaps N]
+//!
+//! Writes `<csv>.manifest.json` ({manifest, report}) next to the dataset and
+//! prints the quality report. Exits 5 if bad rows or gaps exceed the thresholds
+//! (defaults 0; env DATA_MAX_BAD_ROWS / DATA_MAX_GAPS).
+
+use arbitragefx::data::{analyze_csv, default_manifest_path, validate_schema, EXPECTED_COLUMNS};
+use serde_json::json;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn flag_value(args: &[String], name: &str) -> Option<u64> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse().ok())
+}
+
+fn env_u64(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let mut positional = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        if args[i].starts_with("--") {
+            i += 2;
+            continue;
+        }
+        positional.push(args[i].clone());
+        i += 1;
+    }
+    let path = positional
+        .first()
+        .cloned()
+        .unwrap_or_else(|| "data/sample.csv".to_string());
+    let interval_secs = positional
+        .get(1)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| env_u64("DATA_INTERVAL_SECS", 60));
+    let ttl_secs = positional
+        .get(2)
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| env_u64("DATA_TTL_SECS", 3600));
+    let max_bad_rows =
+        flag_value(&args, "--max-bad-rows").unwrap_or_else(|| env_u64("DATA_MAX_BAD_ROWS", 0));
+    let max_gaps = flag_value(&args, "--max-gaps").unwrap_or_else(|| env_u64("DATA_MAX_GAPS", 0));
+
+    let now_ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let schema = match validate_schema(PathBuf::from(&path).as_path()) {
+        Ok(s) => s,
+        Err(err) => {
+            eprintln!("schema check failed: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    if !schema.ok {
+        eprintln!("schema mismatch: {:?}", schema.message);
+        eprintln!("expected columns: {:?}", EXPECTED_COLUMNS);
+        std::process::exit(2);
+    }
+
+    let (manifest, report) = match analyze_csv(
+        PathBuf::from(&path).as_path(),
+        interval_secs,
+        ttl_secs,
+        now_ts,
+    ) {
+        Ok(m) => m,
+        Err(err) => {
+            eprintln!("analysis failed: {}", err);
+            std::process::exit(3);
+        }
+    };
+
+    let out_path = default_manifest_path(PathBuf::from(&path).as_path());
+    let payload = json!({
+        "manifest": manifest,
+        "report": report
+    });
+    if let Err(err) = fs::write(&out_path, serde_json::to_string_pretty(&payload).unwrap()) {
+        eprintln!("failed to write {}: {}", out_path.display(), err);
+        std::process::exit(4);
+    }
+    println!("wrote manifest {}", out_path.display());
+    println!(
+        "rows={} bad_rows={} gaps={} stale={}",
+        report.rows, report.bad_rows, report.gaps, report.stale
+    );
+    for w in &report.warnings {
+        println!("  warning: {}", w);
+    }
+
+    if report.bad_rows > max_bad_rows || report.gaps > max_gaps {
+        eprintln!(
+            "quality threshold exceeded: bad_rows {} (max {}), gaps {} (max {})",
+            report.bad_rows, max_bad_rows, report.gaps, max_gaps
+        );
+        std::process::exit(5);
+    }
+}
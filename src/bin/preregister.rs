@@ -0,0 +1,31 @@
+use arbitragefx::backtest::preregister::{Preregistration, DEFAULT_PREREG_PATH};
+use std::env;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let paths: Vec<PathBuf> = env::args().skip(1).map(PathBuf::from).collect();
+    if paths.is_empty() {
+        eprintln!("usage: preregister <dataset.csv>...");
+        std::process::exit(1);
+    }
+    let out_path = env::var("PREREG_PATH").unwrap_or_else(|_| DEFAULT_PREREG_PATH.to_string());
+
+    let now_ts = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    let prereg = match Preregistration::register(&paths, now_ts) {
+        Ok(p) => p,
+        Err(err) => {
+            eprintln!("registration failed: {}", err);
+            std::process::exit(2);
+        }
+    };
+    if let Err(err) = prereg.write(out_path.as_ref()) {
+        eprintln!("failed to write {}: {}", out_path, err);
+        std::process::exit(3);
+    }
+    println!("wrote {} ({} datasets)", out_path, prereg.datasets.len());
+}
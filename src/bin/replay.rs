@@ -1,8 +1,27 @@
-use std::collections::HashSet;
+//! Replay tool.
+//!
+//! Usage:
+//!   cargo run --bin replay -- <wal_path> [--db <sqlite_path>]
+//!   cargo run --bin replay < events.jsonl
+//!
+//! With a WAL path, rebuilds every strategy the way the live loop does on
+//! startup (`Wal::recover` + `RecoveryState::apply_to`) and prints one line of
+//! reconstructed state per strategy. `--db` compares the result against the
+//! latest SQLite snapshot and exits 3 on any equity/pnl mismatch. Strategy ids
+//! come from the current env config (SYMBOLS etc.), so run it with the same
+//! environment as the incident.
+//!
+//! Without arguments, reads order events from stdin and replays them through
+//! the order state machine.
+
+use std::collections::{HashMap, HashSet};
+use std::env;
 use std::io::{self, BufRead};
+use std::process;
 
-use arbitragefx::state::Fill;
-use arbitragefx::strategy::PortfolioState;
+use arbitragefx::reliability::wal::Wal;
+use arbitragefx::state::{Config, Fill, StrategyInstance};
+use arbitragefx::strategy::{ContractKind, PortfolioState};
 use arbitragefx::verify::invariants::{
     apply_fill_idempotent, assert_equity_consistency, assert_portfolio_invariants,
 };
@@ -30,6 +49,102 @@ enum InputEvent {
 }
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first() {
+        Some(path) if !path.starts_with("--") => {
+            let db = args
+                .iter()
+                .position(|a| a == "--db")
+                .and_then(|i| args.get(i + 1));
+            process::exit(replay_wal(path, db.map(String::as_str)));
+        }
+        _ => replay_events(),
+    }
+}
+
+/// Latest persisted (equity, pnl) per strategy id.
+fn latest_snapshot(db: &str) -> rusqlite::Result<HashMap<String, (f64, f64)>> {
+    let conn = rusqlite::Connection::open(db)?;
+    let mut stmt = conn.prepare(
+        "SELECT m.strategy_id, m.equity, m.pnl FROM metrics m
+         WHERE m.ts = (SELECT MAX(ts) FROM metrics WHERE strategy_id = m.strategy_id)",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+    })?;
+    rows.collect()
+}
+
+fn close(a: f64, b: f64) -> bool {
+    (a - b).abs() <= 1e-6 * a.abs().max(b.abs()).max(1.0)
+}
+
+fn replay_wal(path: &str, db: Option<&str>) -> i32 {
+    let recovery = match Wal::recover(path) {
+        Ok(r) => r,
+        Err(err) => {
+            eprintln!("failed to read wal {}: {}", path, err);
+            return 2;
+        }
+    };
+    let cfg = Config::from_env();
+    let mut strategies = StrategyInstance::build_default_set_per_symbol(cfg.clone());
+    recovery.apply_to(&mut strategies, &HashMap::new(), cfg.persist_hwm);
+
+    for id in recovery.snapshots_by_strategy.keys() {
+        if !strategies.iter().any(|inst| &inst.id == id) {
+            eprintln!("wal snapshot for unknown strategy {} (check SYMBOLS)", id);
+        }
+    }
+    for inst in &strategies {
+        let p = &inst.state.portfolio;
+        println!(
+            "strategy={} cash={} position={} entry_price={} equity={} pnl={}",
+            inst.id, p.cash, p.position, p.entry_price, p.equity, inst.state.metrics.pnl
+        );
+    }
+    println!(
+        "pending_orders={} fills_since_snapshot={} corrupt_entries={}",
+        recovery.pending_orders.len(),
+        recovery.fills_since_snapshot.len(),
+        recovery.corrupt_entries
+    );
+
+    let Some(db) = db else {
+        return 0;
+    };
+    let stored = match latest_snapshot(db) {
+        Ok(s) => s,
+        Err(err) => {
+            eprintln!("failed to read snapshot db {}: {}", db, err);
+            return 2;
+        }
+    };
+    let mut mismatches = 0;
+    for inst in &strategies {
+        let Some(&(equity, pnl)) = stored.get(&inst.id) else {
+            println!("compare strategy={} status=missing_in_db", inst.id);
+            continue;
+        };
+        let replayed = (inst.state.portfolio.equity, inst.state.metrics.pnl);
+        if close(replayed.0, equity) && close(replayed.1, pnl) {
+            println!("compare strategy={} status=match", inst.id);
+        } else {
+            mismatches += 1;
+            println!(
+                "compare strategy={} status=mismatch equity={}/{} pnl={}/{}",
+                inst.id, replayed.0, equity, replayed.1, pnl
+            );
+        }
+    }
+    if mismatches > 0 {
+        3
+    } else {
+        0
+    }
+}
+
+fn replay_events() {
     let stdin = io::stdin();
     let mut order = Order::new("client-1".to_string(), 1.0);
     let mut portfolio = PortfolioState {
@@ -37,6 +152,7 @@ fn main() {
         position: 0.0,
         entry_price: 0.0,
         equity: 1000.0,
+        contract: ContractKind::Linear,
     };
     let mut seen = HashSet::new();
 
@@ -82,6 +82,7 @@ fn run_backtest(rows: &[CsvRow], cfg: &Config) -> (f64, f64, u64, u64) {
                 has_borrow: row.borrow != 0.0,
                 has_liquidations: row.liq != 0.0,
                 has_depeg: row.depeg != 0.0,
+                next_funding_ts: 0,
             },
         );
 
@@ -106,13 +107,16 @@ fn run_backtest(rows: &[CsvRow], cfg: &Config) -> (f64, f64, u64, u64) {
 
             if let Some((qty, price)) = fill {
                 let fee = price * qty.abs() * 0.001;
-                let realized = inst.state.portfolio.apply_fill(Fill {
+                let fill = Fill {
                     price,
                     qty,
                     fee,
                     ts: row.ts,
-                });
-                inst.state.metrics.record_trade(realized);
+                };
+                let realized = inst.state.portfolio.apply_fill(fill);
+                inst.state
+                    .metrics
+                    .record_fill(realized, &fill, inst.state.portfolio.position);
                 total_trades += 1;
             }
 
@@ -67,6 +67,7 @@ fn run_backtest(rows: &[CsvRow], cfg: &Config) -> u64 {
                 has_borrow: true,
                 has_liquidations: true,
                 has_depeg: false,
+                next_funding_ts: 0,
             },
         );
 
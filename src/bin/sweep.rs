@@ -330,7 +330,7 @@ fn run_hypothesis(h: &Hypothesis, rows: &[CsvRow]) -> TrialResult {
     let mut risk = RiskEngine::new(cfg.clone());
     let mut metrics = MetricsEngine::new();
 
-    let initial_cash = 1000.0;
+    let initial_cash = cfg.starting_capital;
     let mut friction = 0.0;
 
     for row in rows {
@@ -355,6 +355,7 @@ fn run_hypothesis(h: &Hypothesis, rows: &[CsvRow]) -> TrialResult {
                 has_borrow: row.borrow != 0.0,
                 has_liquidations: row.liq != 0.0,
                 has_depeg: row.depeg != 0.0,
+                next_funding_ts: 0,
             },
         );
 
@@ -420,7 +421,7 @@ fn run_hypothesis(h: &Hypothesis, rows: &[CsvRow]) -> TrialResult {
                     inst.state.metrics.wins += 1;
                 } else if realized < 0.0 {
                     inst.state.metrics.losses += 1;
-                    inst.state.last_loss_ts = row.ts;
+                    inst.state.record_loss(row.ts, realized);
                 }
                 inst.state.last_trade_ts = row.ts;
             }
@@ -0,0 +1,30 @@
+//! Backtest trap status as JSON, for CI gating.
+//!
+//! Usage:
+//!   cargo run --bin traps -- [out.json]
+//!
+//! Prints `{guarded, total, critical_unguarded, traps}` (and writes it to
+//! `out.json` when given). Exits 1 if any Critical trap is Unguarded.
+
+use arbitragefx::backtest_traps::trap_report;
+
+fn main() {
+    let report = trap_report();
+    let json = serde_json::to_string_pretty(&report).expect("trap report serializes");
+    println!("{}", json);
+
+    if let Some(path) = std::env::args().nth(1) {
+        if let Err(e) = std::fs::write(&path, &json) {
+            eprintln!("failed to write {}: {}", path, e);
+            std::process::exit(1);
+        }
+    }
+
+    if !report.passes() {
+        eprintln!(
+            "critical traps unguarded: {:?} (integrity {}/{})",
+            report.critical_unguarded, report.guarded, report.total
+        );
+        std::process::exit(1);
+    }
+}
@@ -2,13 +2,21 @@
 //!
 //! Reads a BacktestResult JSON or WalkForwardResult JSON, computes Bayesian
 //! updates for each hypothesis, and writes an updated hypothesis_ledger.edn.
+//! Each hypothesis's status (Supported/Refuted/Inconclusive) is reported with
+//! the new evidence counted as one more regime test; walk-forward evidence
+//! carries the best strategy's p-value.
 //!
 //! Usage:
 //!   cargo run --bin update_ledger -- out/walk_forward/report.json [--dataset-id LABEL] [--regime REGIME]
 
+use arbitragefx::hypothesis::edn::read_ledger;
+use arbitragefx::hypothesis::{Evidence, HypothesisStatus, Supports};
 use serde::Deserialize;
 use std::fs;
 
+/// Significance level for ledger status when the input carries none.
+const DEFAULT_ALPHA: f64 = 0.05;
+
 #[derive(Debug, Deserialize)]
 struct StrategyResult {
     id: String,
@@ -83,6 +91,8 @@ struct HypothesisUpdate {
     observation_strength: f64,
     evidence_weight: f64,
     supports: String,
+    /// p-value behind the observation, when it came from a test.
+    p_value: Option<f64>,
 }
 
 fn evaluate_backtest(result: &BacktestResult, _dataset_id: &str) -> Vec<HypothesisUpdate> {
@@ -122,6 +132,7 @@ fn evaluate_backtest(result: &BacktestResult, _dataset_id: &str) -> Vec<Hypothes
         ),
         observation_strength: alpha_frac,
         evidence_weight: 0.15,
+        p_value: None,
         supports: if positive_count > total / 2 {
             ":partial"
         } else if positive_count == 0 {
@@ -142,6 +153,7 @@ fn evaluate_backtest(result: &BacktestResult, _dataset_id: &str) -> Vec<Hypothes
         ),
         observation_strength: if friction_dominates { 0.85 } else { 0.4 },
         evidence_weight: 0.15,
+        p_value: None,
         supports: if friction_dominates {
             "true"
         } else {
@@ -157,6 +169,7 @@ fn evaluate_backtest(result: &BacktestResult, _dataset_id: &str) -> Vec<Hypothes
         observation: format!("Max DD: {:.2}% across all strategies", max_dd * 100.0),
         observation_strength: if dd_under_2pct { 0.95 } else { 0.5 },
         evidence_weight: 0.15,
+        p_value: None,
         supports: if dd_under_2pct { "true" } else { "false" }.into(),
     });
 
@@ -167,6 +180,7 @@ fn evaluate_backtest(result: &BacktestResult, _dataset_id: &str) -> Vec<Hypothes
         observation: format!("{}/{} strategies beat no-trade", net_positive, total),
         observation_strength: if beats_notrade { 0.5 } else { 0.9 },
         evidence_weight: 0.15,
+        p_value: None,
         supports: if beats_notrade { ":partial" } else { "true" }.into(),
     });
 
@@ -182,6 +196,7 @@ fn evaluate_backtest(result: &BacktestResult, _dataset_id: &str) -> Vec<Hypothes
                 lo.id, lo.trades, lo.friction, hi.id, hi.trades, hi.friction, trade_ratio, friction_ratio),
             observation_strength: if friction_ratio > trade_ratio { 0.95 } else { 0.7 },
             evidence_weight: 0.15,
+            p_value: None,
             supports: "true".into(),
         });
     }
@@ -199,6 +214,7 @@ fn evaluate_backtest(result: &BacktestResult, _dataset_id: &str) -> Vec<Hypothes
             ),
             observation_strength: if preservation > 0.97 { 0.9 } else { 0.6 },
             evidence_weight: 0.15,
+            p_value: None,
             supports: "true".into(),
         });
     }
@@ -229,7 +245,13 @@ fn evaluate_walk_forward(wf: &WalkForwardResult) -> Vec<HypothesisUpdate> {
         ),
         observation_strength: test_positive as f64 / total as f64,
         evidence_weight: 0.20, // Walk-forward evidence is stronger
-        supports: if survivors > 0 { ":partial" } else { "false" }.into(),
+        // The best strategy's test p-value is what survives (or not) correction
+        p_value: wf
+            .summaries
+            .iter()
+            .map(|s| s.p_value)
+            .min_by(|a, b| a.total_cmp(b)),
+        supports: if survivors > 0 { "true" } else { "false" }.into(),
     });
 
     // H007: No consistent alpha — walk-forward verdict
@@ -241,6 +263,7 @@ fn evaluate_walk_forward(wf: &WalkForwardResult) -> Vec<HypothesisUpdate> {
         ),
         observation_strength: if survivors == 0 { 0.95 } else { 0.3 },
         evidence_weight: 0.25,
+        p_value: None,
         supports: if survivors == 0 { "true" } else { ":partial" }.into(),
     });
 
@@ -254,6 +277,7 @@ fn evaluate_walk_forward(wf: &WalkForwardResult) -> Vec<HypothesisUpdate> {
         ),
         observation_strength: if mean_overfit < 0.5 { 0.9 } else { 0.5 },
         evidence_weight: 0.20,
+        p_value: None,
         supports: if mean_overfit < 0.5 {
             "true"
         } else {
@@ -281,19 +305,19 @@ fn main() {
     let content = fs::read_to_string(json_path).expect("failed to read JSON");
 
     // Try to parse as WalkForwardResult first, then BacktestResult
-    let updates = if let Ok(wf) = serde_json::from_str::<WalkForwardResult>(&content) {
+    let (updates, alpha) = if let Ok(wf) = serde_json::from_str::<WalkForwardResult>(&content) {
         println!(
             "Parsed walk-forward result: {} strategies, {} windows",
             wf.num_strategies, wf.num_windows
         );
-        evaluate_walk_forward(&wf)
+        (evaluate_walk_forward(&wf), wf.alpha)
     } else if let Ok(bt) = serde_json::from_str::<BacktestResult>(&content) {
         println!(
             "Parsed backtest result: {} strategies, {} candles",
             bt.strategies.len(),
             bt.candle_count.unwrap_or(0)
         );
-        evaluate_backtest(&bt, dataset_id)
+        (evaluate_backtest(&bt, dataset_id), DEFAULT_ALPHA)
     } else {
         eprintln!(
             "Could not parse {} as BacktestResult or WalkForwardResult",
@@ -313,6 +337,9 @@ fn main() {
         );
         println!("  observation: {}", u.observation);
         println!("  observation_strength: {:.2}", u.observation_strength);
+        if let Some(p) = u.p_value {
+            println!("  p_value: {:.4}", p);
+        }
     }
 
     // Read current ledger and apply updates
@@ -382,8 +409,32 @@ fn main() {
             }
         }
 
+        // Status with this run's evidence counted as one more regime test
+        let mut with_updates = read_ledger(&ledger);
+        for u in &updates {
+            if let Some(h) = with_updates.hypotheses.iter_mut().find(|h| h.id == u.id) {
+                h.evidence.push(Evidence {
+                    dataset: dataset_id.to_string(),
+                    observation: u.observation.clone(),
+                    supports: match u.supports.as_str() {
+                        "true" => Supports::Yes,
+                        "false" => Supports::No,
+                        _ => Supports::Partial,
+                    },
+                    weight: u.evidence_weight,
+                    p_value: u.p_value,
+                });
+            }
+        }
+        let statuses = with_updates.update_hypothesis_status(alpha);
+        println!();
+        println!("Status (Bonferroni across regimes, alpha={}):", alpha);
+        for (id, status) in &statuses {
+            println!("  {:<8} {:?}", id, status);
+        }
+
         // Append to JSONL history for temporal tracking (R1)
-        append_history(&updates, &current_stvs, json_path, dataset_id);
+        append_history(&updates, &current_stvs, &statuses, json_path, dataset_id);
 
         println!();
         println!("To apply these updates, re-run with --apply flag (not yet implemented).");
@@ -395,6 +446,7 @@ fn main() {
 fn append_history(
     updates: &[HypothesisUpdate],
     current_stvs: &[(String, Stv)],
+    statuses: &[(&str, HypothesisStatus)],
     source_path: &str,
     dataset_id: &str,
 ) {
@@ -440,6 +492,10 @@ fn append_history(
                 confidence: 0.0,
             })
             .update(u.observation_strength, u.evidence_weight);
+        let status = statuses
+            .iter()
+            .find(|(id, _)| *id == u.id)
+            .map(|(_, s)| format!("{:?}", s));
 
         let entry = serde_json::json!({
             "ts": ts,
@@ -452,6 +508,8 @@ fn append_history(
             "observation": u.observation,
             "supports": u.supports,
             "weight": u.evidence_weight,
+            "p_value": u.p_value,
+            "status": status,
         });
 
         if let Err(e) = writeln!(file, "{}", entry) {
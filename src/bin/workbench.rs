@@ -6,6 +6,11 @@
 use std::fs;
 use std::path::Path;
 
+use arbitragefx::feed::aux_data::{parse_funding_history, FundingPoint, FUNDING_HISTORY_PATH};
+use arbitragefx::hypothesis::edn::{extract_quoted, extract_stv};
+use arbitragefx::metrics::{correlation_matrix, CorrelationMatrix, CORRELATION_FLAG};
+use arbitragefx::storage::StateStore;
+
 use serde::Serialize;
 
 /// Parsed hypothesis from the ledger.
@@ -63,6 +68,9 @@ struct WorkbenchData {
     test_count: usize,
     dataset_count: usize,
     stv_history: Vec<StvHistoryEntry>,
+    funding_history: Vec<FundingPoint>,
+    ensemble_correlation: Option<CorrelationMatrix>,
+    correlation_flag: f64,
     trap_status: Vec<TrapStatusEntry>,
     uncertainty_map: UncertaintyMap,
     integrity_score: String,
@@ -189,29 +197,6 @@ fn classify_hypothesis(strength: f64, confidence: f64) -> String {
     }
 }
 
-fn extract_quoted(line: &str, key: &str) -> Option<String> {
-    let idx = line.find(key)?;
-    let rest = &line[idx + key.len()..];
-    let start = rest.find('"')? + 1;
-    let end = rest[start..].find('"')? + start;
-    Some(rest[start..end].to_string())
-}
-
-fn extract_stv(line: &str) -> Option<(f64, f64)> {
-    let idx = line.find("(stv")?;
-    let rest = &line[idx + 4..];
-    let end = rest.find(')')?;
-    let nums: Vec<f64> = rest[..end]
-        .split_whitespace()
-        .filter_map(|s| s.parse().ok())
-        .collect();
-    if nums.len() >= 2 {
-        Some((nums[0], nums[1]))
-    } else {
-        None
-    }
-}
-
 /// Load bench history from out/bench/*.json files.
 fn load_bench_history() -> Vec<BenchHistoryEntry> {
     let dir = Path::new("out/bench");
@@ -263,6 +248,24 @@ fn load_stv_history() -> Vec<StvHistoryEntry> {
         .collect()
 }
 
+/// Load funding history written by the live loop.
+fn load_funding_history() -> Vec<FundingPoint> {
+    fs::read_to_string(FUNDING_HISTORY_PATH)
+        .map(|c| parse_funding_history(&c))
+        .unwrap_or_default()
+}
+
+/// Correlate the equity curves the live loop persisted to SQLite
+/// (`SQLITE_PATH`, default ./bot.sqlite).
+fn load_ensemble_correlation() -> Option<CorrelationMatrix> {
+    let path = std::env::var("SQLITE_PATH").unwrap_or_else(|_| "./bot.sqlite".to_string());
+    if !Path::new(&path).exists() {
+        return None;
+    }
+    let curves = StateStore::new(&path).ok()?.equity_curves().ok()?;
+    (curves.len() > 1).then(|| correlation_matrix(&curves))
+}
+
 /// Load trap status from backtest_traps module.
 fn load_trap_status() -> Vec<TrapStatusEntry> {
     use arbitragefx::backtest_traps::trap_status;
@@ -373,6 +376,8 @@ fn main() {
     let bench_history = load_bench_history();
     let run_history = load_run_history();
     let stv_history = load_stv_history();
+    let funding_history = load_funding_history();
+    let ensemble_correlation = load_ensemble_correlation();
     let trap_status = load_trap_status();
     let uncertainty_map = parse_uncertainty_map();
     let (guarded, total) = arbitragefx::backtest_traps::integrity_score();
@@ -396,6 +401,15 @@ fn main() {
     println!("  bench_history: {} entries", bench_history.len());
     println!("  run_history: {} entries", run_history.len());
     println!("  stv_history: {} entries", stv_history.len());
+    println!("  funding_history: {} points", funding_history.len());
+    match &ensemble_correlation {
+        Some(m) => println!(
+            "  ensemble_correlation: {} strategies, {} flagged pairs",
+            m.ids.len(),
+            m.correlated_pairs(CORRELATION_FLAG).len()
+        ),
+        None => println!("  ensemble_correlation: missing"),
+    }
     println!("  trap_status: {} traps", trap_status.len());
     println!(
         "  uncertainty_map: {} categories",
@@ -425,6 +439,9 @@ fn main() {
         test_count,
         dataset_count,
         stv_history,
+        funding_history,
+        ensemble_correlation,
+        correlation_flag: CORRELATION_FLAG,
         trap_status,
         uncertainty_map,
         integrity_score,
@@ -757,6 +774,24 @@ const TEMPLATE: &str = r##"<!DOCTYPE html>
       <div class="section-body" id="timeline"><p class="empty">No evidence history. Run <code>cargo run --bin update_ledger</code></p></div>
     </div>
 
+    <div class="section" id="sec-funding" data-nav="Funding">
+      <div class="section-header" onclick="toggleSection(this)">
+        <h2>Funding History</h2>
+        <span class="section-desc">Perp funding observed by the live loop, per symbol</span>
+        <span class="section-toggle">&#9662;</span>
+      </div>
+      <div class="section-body" id="funding"><p class="empty">No funding history. The live loop writes <code>out/funding/history.jsonl</code></p></div>
+    </div>
+
+    <div class="section" id="sec-correlation" data-nav="Correlation">
+      <div class="section-header" onclick="toggleSection(this)">
+        <h2>Ensemble Correlation</h2>
+        <span class="section-desc">Pairwise correlation of persisted equity returns; red pairs are one bet</span>
+        <span class="section-toggle">&#9662;</span>
+      </div>
+      <div class="section-body" id="correlation"><p class="empty">No persisted equity curves. The live loop writes them to <code>bot.sqlite</code></p></div>
+    </div>
+
     <div class="section" id="sec-uncertainty" data-nav="Uncertainty">
       <div class="section-header" onclick="toggleSection(this)">
         <h2>Uncertainty Map</h2>
@@ -1025,7 +1060,7 @@ const TEMPLATE: &str = r##"<!DOCTYPE html>
       <span class="badge badge-gray">${wf.correction_method}</span>
       <span class="badge badge-gray">&alpha;=${wf.alpha}</span>
     </div>`;
-    html += '<div class="table-wrap"><table><thead><tr><th>Strategy</th><th class="text-right">Train PnL</th><th class="text-right">Test PnL</th><th class="text-right">Overfit</th><th class="text-right">P-value</th><th class="text-center">Positive</th><th class="text-center">Survives</th></tr></thead><tbody>';
+    html += '<div class="table-wrap"><table><thead><tr><th>Strategy</th><th class="text-right">Train PnL</th><th class="text-right">Test PnL</th><th class="text-right">Overfit</th><th class="text-right">Sharpe</th><th class="text-right">Sortino</th><th class="text-right">P-value</th><th class="text-center">Positive</th><th class="text-center">Survives</th></tr></thead><tbody>';
     for (const s of wf.summaries) {
       const orColor = s.overfit_ratio > 0.5 ? 'var(--green)' : s.overfit_ratio > 0 ? 'var(--yellow)' : 'var(--red)';
       html += `<tr>
@@ -1033,6 +1068,8 @@ const TEMPLATE: &str = r##"<!DOCTYPE html>
         <td class="mono text-right" style="color:${pnlColor(s.train_mean_pnl)}">${pnlSign(s.train_mean_pnl,4)}</td>
         <td class="mono text-right" style="color:${pnlColor(s.test_mean_pnl)}">${pnlSign(s.test_mean_pnl,4)}</td>
         <td class="mono text-right" style="color:${orColor}">${s.overfit_ratio.toFixed(3)}</td>
+        <td class="mono text-right">${(s.test_mean_sharpe ?? 0).toFixed(3)}</td>
+        <td class="mono text-right">${(s.test_mean_sortino ?? 0).toFixed(3)}</td>
         <td class="mono text-right">${s.p_value.toFixed(3)}</td>
         <td class="mono text-center">${s.test_positive_windows}/${s.total_windows}</td>
         <td class="text-center">${s.survives_correction ? '<span class="badge badge-green">YES</span>' : '<span class="badge badge-red">no</span>'}</td>
@@ -1087,6 +1124,51 @@ const TEMPLATE: &str = r##"<!DOCTYPE html>
     el.innerHTML = html;
   })();
 
+  // ── Funding History ──
+  (() => {
+    if (!D.funding_history.length) return;
+    const el = document.getElementById('funding');
+    const bySym = {};
+    for (const p of D.funding_history) { (bySym[p.symbol] = bySym[p.symbol] || []).push(p); }
+    let html = '<div class="table-wrap"><table><thead><tr><th>Symbol</th><th>Funding (bps)</th><th class="text-right">Min</th><th class="text-right">Max</th><th class="text-right">Points</th><th>Since</th></tr></thead><tbody>';
+    for (const sym of Object.keys(bySym).sort()) {
+      const pts = bySym[sym];
+      const bps = pts.map(p => p.rate * 1e4);
+      const last = bps[bps.length - 1];
+      html += `<tr>
+        <td class="mono">${sym}</td>
+        <td><div class="spark-wrap">${sparkSvg(bps, '#d29922')} <span class="spark-val" style="color:${pnlColor(last)}">${pnlSign(last, 2)}</span></div></td>
+        <td class="mono text-right">${Math.min(...bps).toFixed(2)}</td>
+        <td class="mono text-right">${Math.max(...bps).toFixed(2)}</td>
+        <td class="mono text-right">${pts.length}</td>
+        <td class="mono" style="font-size:0.7rem;color:var(--fg-muted);">${new Date(pts[0].ts * 1000).toISOString().slice(0, 16).replace('T', ' ')}</td>
+      </tr>`;
+    }
+    html += '</tbody></table></div>';
+    el.innerHTML = html;
+  })();
+
+  // ── Ensemble Correlation ──
+  (() => {
+    const m = D.ensemble_correlation;
+    if (!m) return;
+    const el = document.getElementById('correlation');
+    let html = '<div class="table-wrap"><table><thead><tr><th></th>';
+    for (const id of m.ids) html += `<th class="text-right mono">${id}</th>`;
+    html += '</tr></thead><tbody>';
+    m.ids.forEach((id, i) => {
+      html += `<tr><td class="mono">${id}</td>`;
+      m.values[i].forEach((r, j) => {
+        const flagged = i !== j && r !== null && r >= D.correlation_flag;
+        const color = flagged ? 'var(--red)' : (i === j ? 'var(--fg-muted)' : 'inherit');
+        html += `<td class="mono text-right" style="color:${color}">${r === null ? '&mdash;' : r.toFixed(2)}</td>`;
+      });
+      html += '</tr>';
+    });
+    html += '</tbody></table></div>';
+    el.innerHTML = html;
+  })();
+
   // ── Uncertainty Map ──
   (() => {
     const um = D.uncertainty_map;
@@ -1264,3 +1346,35 @@ const TEMPLATE: &str = r##"<!DOCTYPE html>
   </script>
 </body>
 </html>"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_funding_history_jsonl() {
+        let jsonl = r#"{"ts":1700000600,"symbol":"BTCUSDT","rate":0.0001}
+{"ts":1700000300,"symbol":"ETHUSDT","rate":-0.00005}
+not json
+{"ts":1700000000,"symbol":"BTCUSDT","rate":0.00012}
+
+{"ts":1700000900,"symbol":"BTCUSDT"}
+"#;
+        let points = parse_funding_history(jsonl);
+        assert_eq!(points.len(), 3);
+        assert_eq!(
+            points.iter().map(|p| p.ts).collect::<Vec<_>>(),
+            vec![1700000000, 1700000300, 1700000600]
+        );
+        assert_eq!(points[1].symbol, "ETHUSDT");
+        assert_eq!(points[1].rate, -0.00005);
+
+        // A settlement fetched twice renders once, at its latest rate
+        let jsonl = r#"{"ts":100,"symbol":"BTCUSDT","rate":0.0001,"funding_ts":28800}
+{"ts":400,"symbol":"BTCUSDT","rate":0.0002,"funding_ts":28800}
+"#;
+        let points = parse_funding_history(jsonl);
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].rate, 0.0002);
+    }
+}
@@ -1,9 +1,16 @@
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Read};
 use std::path::{Path, PathBuf};
 
+use crate::exchange::Candle;
+use crate::state::{check_granularity, detect_interval};
+
+/// Share of intervals off the modal spacing (and not whole missing bars)
+/// above which `analyze_csv` warns that the timestamps are irregular.
+pub const IRREGULAR_WARN_FRAC: f64 = 0.05;
+
 pub const EXPECTED_COLUMNS: [&str; 11] = [
     "ts", "open", "high", "low", "close", "volume", "funding", "borrow", "liq", "depeg", "oi",
 ];
@@ -24,6 +31,12 @@ pub struct DatasetManifest {
     pub ts_min: Option<u64>,
     pub ts_max: Option<u64>,
     pub interval_secs: u64,
+    /// Most common spacing between consecutive timestamps
+    #[serde(default)]
+    pub detected_interval_secs: Option<u64>,
+    /// Intervals that are neither the modal spacing nor a whole multiple of it
+    #[serde(default)]
+    pub irregular_intervals: u64,
     pub columns: Vec<String>,
     pub gaps: Vec<Gap>,
     pub warnings: Vec<String>,
@@ -40,6 +53,14 @@ pub struct SchemaReport {
     pub message: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// False when the file already had the current schema (nothing written)
+    pub migrated: bool,
+    pub rows: u64,
+    pub warnings: Vec<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataQualityReport {
     pub rows: u64,
@@ -66,6 +87,7 @@ pub fn analyze_csv(
     let mut ts_min: Option<u64> = None;
     let mut ts_max: Option<u64> = None;
     let mut prev_ts: Option<u64> = None;
+    let mut timestamps: Vec<u64> = Vec::new();
     let mut gaps: Vec<Gap> = Vec::new();
     let mut header: Vec<String> = Vec::new();
 
@@ -84,7 +106,9 @@ pub fn analyze_csv(
                 ts_min = Some(ts_min.map(|v| v.min(ts)).unwrap_or(ts));
                 ts_max = Some(ts_max.map(|v| v.max(ts)).unwrap_or(ts));
                 if let Some(prev) = prev_ts {
-                    if ts > prev && ts - prev > interval_secs {
+                    // Only spacing that drops a whole bar is a gap; jitter
+                    // (300s, 305s, 298s) is counted as irregularity below
+                    if ts > prev && ts - prev >= 2 * interval_secs {
                         let missing = (ts - prev) / interval_secs - 1;
                         gaps.push(Gap {
                             start_ts: prev,
@@ -96,6 +120,7 @@ pub fn analyze_csv(
                     }
                 }
                 prev_ts = Some(ts);
+                timestamps.push(ts);
             }
             Err(err) => {
                 bad_rows += 1;
@@ -107,6 +132,20 @@ pub fn analyze_csv(
     if header.is_empty() {
         warnings.push("missing_header".to_string());
     }
+    let detected_interval_secs = detect_interval(&timestamps);
+    if let Err(err) = check_granularity(interval_secs, detected_interval_secs) {
+        warnings.push(err);
+    }
+    let (irregular_intervals, intervals) =
+        count_irregular_intervals(&timestamps, detected_interval_secs);
+    if intervals > 0 && irregular_intervals as f64 / intervals as f64 > IRREGULAR_WARN_FRAC {
+        warnings.push(format!(
+            "irregular_intervals: {}/{} intervals off the modal {}s",
+            irregular_intervals,
+            intervals,
+            detected_interval_secs.unwrap_or(0)
+        ));
+    }
 
     let stale = ts_max
         .map(|ts| now_ts.saturating_sub(ts) > ttl_secs)
@@ -120,6 +159,8 @@ pub fn analyze_csv(
         ts_min,
         ts_max,
         interval_secs,
+        detected_interval_secs,
+        irregular_intervals,
         columns: header.clone(),
         gaps: gaps.clone(),
         warnings: warnings.clone(),
@@ -139,6 +180,80 @@ pub fn analyze_csv(
     Ok((manifest, report))
 }
 
+/// (irregular, total) over increasing consecutive intervals. An interval is
+/// irregular unless it is the modal spacing or a whole multiple of it (a gap).
+fn count_irregular_intervals(timestamps: &[u64], modal: Option<u64>) -> (u64, u64) {
+    let Some(modal) = modal.filter(|&m| m > 0) else {
+        return (0, 0);
+    };
+    timestamps
+        .windows(2)
+        .filter(|w| w[1] > w[0])
+        .fold((0, 0), |(irregular, total), w| {
+            let off = (w[1] - w[0]) % modal != 0;
+            (irregular + off as u64, total + 1)
+        })
+}
+
+/// Aggregate `from_secs` candles into epoch-aligned `to_secs` buckets: first
+/// open, highest high, lowest low, last close, summed volume. `to_secs` must
+/// be a whole multiple of `from_secs`; finer bars can't be invented, so
+/// upsampling is an error, as is input whose detected spacing (the same check
+/// `analyze_csv` runs) isn't `from_secs`. Buckets missing any input bar, at
+/// the edges or across a gap, are dropped rather than passed off as complete.
+pub fn resample(rows: &[Candle], from_secs: u64, to_secs: u64) -> Result<Vec<Candle>, String> {
+    if from_secs == 0 || to_secs == 0 {
+        return Err("resample: intervals must be positive".to_string());
+    }
+    if to_secs < from_secs {
+        return Err(format!(
+            "resample: cannot upsample {}s bars to {}s",
+            from_secs, to_secs
+        ));
+    }
+    if !to_secs.is_multiple_of(from_secs) {
+        return Err(format!(
+            "resample: {}s is not a whole multiple of {}s",
+            to_secs, from_secs
+        ));
+    }
+    let timestamps: Vec<u64> = rows.iter().map(|r| r.ts).collect();
+    check_granularity(from_secs, detect_interval(&timestamps))?;
+    if let Some(w) = timestamps.windows(2).find(|w| w[1] <= w[0]) {
+        return Err(format!("non_monotonic_ts: prev={} current={}", w[0], w[1]));
+    }
+
+    let per_bucket = (to_secs / from_secs) as usize;
+    let mut out = Vec::new();
+    let mut bucket: Option<(Candle, usize)> = None;
+    for row in rows {
+        let start = row.ts - row.ts % to_secs;
+        match &mut bucket {
+            Some((agg, n)) if agg.ts == start => {
+                agg.h = agg.h.max(row.h);
+                agg.l = agg.l.min(row.l);
+                agg.c = row.c;
+                agg.v += row.v;
+                *n += 1;
+            }
+            _ => {
+                if let Some((agg, n)) = bucket.take() {
+                    if n == per_bucket {
+                        out.push(agg);
+                    }
+                }
+                bucket = Some((Candle { ts: start, ..*row }, 1));
+            }
+        }
+    }
+    if let Some((agg, n)) = bucket {
+        if n == per_bucket {
+            out.push(agg);
+        }
+    }
+    Ok(out)
+}
+
 pub fn validate_schema(path: &Path) -> Result<SchemaReport, String> {
     let header = read_header(path)?;
     let expected = EXPECTED_COLUMNS
@@ -159,6 +274,72 @@ pub fn validate_schema(path: &Path) -> Result<SchemaReport, String> {
     })
 }
 
+/// Value written into the `oi` column of migrated legacy rows.
+pub const OI_BACKFILL: &str = "0.0";
+
+/// Upgrade a legacy 10-column dataset (no `oi`) to `EXPECTED_COLUMNS` in
+/// place, backfilling `oi` with `OI_BACKFILL`. Files that already have the
+/// current header are left untouched; any other header is an error.
+pub fn migrate_csv(path: &Path) -> Result<MigrationReport, String> {
+    let header = read_header(path)?;
+    if header == EXPECTED_COLUMNS {
+        return Ok(MigrationReport {
+            migrated: false,
+            rows: 0,
+            warnings: Vec::new(),
+        });
+    }
+    let legacy = &EXPECTED_COLUMNS[..EXPECTED_COLUMNS.len() - 1];
+    if header != legacy {
+        return Err(format!(
+            "cannot migrate: header {:?} is neither legacy {:?} nor current",
+            header, legacy
+        ));
+    }
+
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let mut out = String::new();
+    let mut rows = 0u64;
+    let mut warnings = Vec::new();
+    let mut header_done = false;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            out.push_str(&line);
+        } else if !header_done {
+            out.push_str(&EXPECTED_COLUMNS.join(","));
+            header_done = true;
+        } else {
+            let cols = trimmed.split(',').count();
+            if cols == legacy.len() {
+                rows += 1;
+                out.push_str(trimmed);
+                out.push(',');
+                out.push_str(OI_BACKFILL);
+            } else {
+                warnings.push(format!("row_left_as_is: {} columns: {}", cols, trimmed));
+                out.push_str(&line);
+            }
+        }
+        out.push('\n');
+    }
+    warnings.push(format!(
+        "oi_backfilled: {} rows set to {}",
+        rows, OI_BACKFILL
+    ));
+
+    // Write beside the original and rename so a crash never leaves half a file
+    let tmp = path.with_extension("csv.migrating");
+    fs::write(&tmp, out).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())?;
+    Ok(MigrationReport {
+        migrated: true,
+        rows,
+        warnings,
+    })
+}
+
 pub fn read_header(path: &Path) -> Result<Vec<String>, String> {
     let file = File::open(path).map_err(|e| e.to_string())?;
     let reader = BufReader::new(file);
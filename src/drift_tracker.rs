@@ -20,6 +20,8 @@ pub enum DriftSeverity {
     Low,
     /// Moderate drift - reduce position sizes, widen no-trade zone
     Moderate,
+    /// Elevated drift - halve position sizes, widen no-trade zone
+    Elevated,
     /// Severe drift - halt new positions
     Severe,
     /// Critical drift - close existing positions
@@ -33,11 +35,30 @@ impl DriftSeverity {
             DriftSeverity::None => 1.0,
             DriftSeverity::Low => 0.9,
             DriftSeverity::Moderate => 0.5,
+            DriftSeverity::Elevated => 0.5,
             DriftSeverity::Severe => 0.0,
             DriftSeverity::Critical => 0.0,
         }
     }
 
+    /// Ordering from calmest to worst
+    pub fn rank(&self) -> u8 {
+        match self {
+            DriftSeverity::None => 0,
+            DriftSeverity::Low => 1,
+            DriftSeverity::Moderate => 2,
+            DriftSeverity::Elevated => 3,
+            DriftSeverity::Severe => 4,
+            DriftSeverity::Critical => 5,
+        }
+    }
+
+    /// Sizes are scaled down but new positions are still allowed. Low drift
+    /// is log-only.
+    pub fn should_throttle(&self) -> bool {
+        matches!(self, DriftSeverity::Moderate | DriftSeverity::Elevated)
+    }
+
     /// Should we halt new positions?
     pub fn should_halt(&self) -> bool {
         matches!(self, DriftSeverity::Severe | DriftSeverity::Critical)
@@ -190,6 +211,8 @@ pub struct DriftThresholds {
     pub low_z: f64,
     /// Z-score threshold for Moderate severity
     pub moderate_z: f64,
+    /// Z-score threshold for Elevated severity
+    pub elevated_z: f64,
     /// Z-score threshold for Severe severity
     pub severe_z: f64,
     /// Z-score threshold for Critical severity
@@ -201,6 +224,7 @@ impl Default for DriftThresholds {
         Self {
             low_z: 1.0,
             moderate_z: 2.0,
+            elevated_z: 2.5,
             severe_z: 3.0,
             critical_z: 4.0,
         }
@@ -253,8 +277,10 @@ impl FeatureTracker {
             DriftSeverity::None
         } else if score < self.thresholds.moderate_z {
             DriftSeverity::Low
-        } else if score < self.thresholds.severe_z {
+        } else if score < self.thresholds.elevated_z {
             DriftSeverity::Moderate
+        } else if score < self.thresholds.severe_z {
+            DriftSeverity::Elevated
         } else if score < self.thresholds.critical_z {
             DriftSeverity::Severe
         } else {
@@ -307,10 +333,15 @@ impl FeatureTracker {
 pub struct DriftTracker {
     /// Individual feature trackers
     features: Vec<FeatureTracker>,
-    /// Overall severity (worst of all features)
+    /// Overall severity (worst of all features), held at Elevated until a
+    /// halt is confirmed
     pub overall_severity: DriftSeverity,
     /// Last update timestamp
     pub last_update_ts: u64,
+    /// Consecutive bars a halt must persist before it is honored (1 = at once)
+    pub halt_confirm_bars: u32,
+    /// Consecutive bars the raw severity has been at halt level
+    pub halt_streak: u32,
 }
 
 impl DriftTracker {
@@ -326,9 +357,18 @@ impl DriftTracker {
             ],
             overall_severity: DriftSeverity::None,
             last_update_ts: 0,
+            halt_confirm_bars: 1,
+            halt_streak: 0,
         }
     }
 
+    /// Require `bars` consecutive halt-level readings before halting, so a
+    /// single glitched bar can't stop trading.
+    pub fn with_halt_confirmation(mut self, bars: u32) -> Self {
+        self.halt_confirm_bars = bars.max(1);
+        self
+    }
+
     /// Create with default window sizes (100 baseline, 20 recent)
     pub fn default_windows() -> Self {
         Self::new(100, 20)
@@ -371,25 +411,36 @@ impl DriftTracker {
             .collect()
     }
 
-    /// Compute overall severity (worst of all features)
+    /// Compute overall severity (worst of all features), subject to the
+    /// halt confirmation window
     pub fn compute_overall(&mut self) -> DriftSeverity {
         let reports = self.reports();
 
-        self.overall_severity = reports
+        let raw = reports
             .iter()
             .map(|r| r.severity)
-            .max_by_key(|s| match s {
-                DriftSeverity::None => 0,
-                DriftSeverity::Low => 1,
-                DriftSeverity::Moderate => 2,
-                DriftSeverity::Severe => 3,
-                DriftSeverity::Critical => 4,
-            })
+            .max_by_key(|s| s.rank())
             .unwrap_or(DriftSeverity::None);
 
+        self.overall_severity = self.confirm(raw);
         self.overall_severity
     }
 
+    /// Track the halt streak and cap an unconfirmed halt at Elevated, which
+    /// still throttles entries while the reading proves itself.
+    fn confirm(&mut self, raw: DriftSeverity) -> DriftSeverity {
+        if !raw.should_halt() {
+            self.halt_streak = 0;
+            return raw;
+        }
+        self.halt_streak = self.halt_streak.saturating_add(1);
+        if self.halt_streak >= self.halt_confirm_bars {
+            raw
+        } else {
+            DriftSeverity::Elevated
+        }
+    }
+
     /// Get position multiplier based on drift
     pub fn position_multiplier(&self) -> f64 {
         self.overall_severity.position_multiplier()
@@ -417,8 +468,10 @@ impl DriftTracker {
                     msg: "Low drift detected. Monitoring.".to_string(),
                 });
             }
-            DriftSeverity::Moderate => {
-                actions.push(DriftAction::ReduceExposure { multiplier: 0.5 });
+            DriftSeverity::Moderate | DriftSeverity::Elevated => {
+                actions.push(DriftAction::ReduceExposure {
+                    multiplier: self.position_multiplier(),
+                });
                 actions.push(DriftAction::WidenNoTradeZone { factor: 1.5 });
             }
             DriftSeverity::Severe => {
@@ -550,6 +603,80 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_severity_position_multipliers() {
+        let cases = [
+            (DriftSeverity::None, 1.0, false),
+            (DriftSeverity::Low, 0.9, false),
+            (DriftSeverity::Moderate, 0.5, true),
+            (DriftSeverity::Elevated, 0.5, true),
+            (DriftSeverity::Severe, 0.0, false),
+            (DriftSeverity::Critical, 0.0, false),
+        ];
+        for (severity, mult, throttles) in cases {
+            assert_eq!(severity.position_multiplier(), mult, "{:?}", severity);
+            assert_eq!(severity.should_throttle(), throttles, "{:?}", severity);
+        }
+        // Multipliers never grow as severity worsens
+        for pair in cases.windows(2) {
+            assert!(pair[0].0.rank() < pair[1].0.rank());
+            assert!(pair[0].1 >= pair[1].1);
+        }
+    }
+
+    #[test]
+    fn test_elevated_band_between_moderate_and_severe() {
+        let mut tracker = FeatureTracker::new("test", 100, 10);
+        for i in 0..100 {
+            tracker.push(100.0 + (i % 2) as f64);
+        }
+        // Pin the score inside the elevated band via the thresholds themselves
+        let score = tracker.report().score;
+        tracker.thresholds = DriftThresholds {
+            low_z: score - 3.0,
+            moderate_z: score - 2.0,
+            elevated_z: score - 1.0,
+            severe_z: score + 1.0,
+            critical_z: score + 2.0,
+        };
+        let report = tracker.report();
+        assert_eq!(report.severity, DriftSeverity::Elevated);
+        assert!(!report.severity.should_halt());
+        assert_eq!(report.severity.position_multiplier(), 0.5);
+    }
+
+    #[test]
+    fn test_halt_needs_consecutive_confirmation() {
+        let mut tracker = DriftTracker::default_windows().with_halt_confirmation(3);
+
+        // One glitched bar then normal data never halts
+        assert_eq!(
+            tracker.confirm(DriftSeverity::Critical),
+            DriftSeverity::Elevated
+        );
+        assert_eq!(tracker.confirm(DriftSeverity::None), DriftSeverity::None);
+        assert_eq!(tracker.halt_streak, 0);
+
+        // Three in a row does, at whatever severity the third bar reads
+        assert!(!tracker.confirm(DriftSeverity::Severe).should_halt());
+        assert!(!tracker.confirm(DriftSeverity::Critical).should_halt());
+        assert_eq!(
+            tracker.confirm(DriftSeverity::Severe),
+            DriftSeverity::Severe
+        );
+        assert_eq!(
+            tracker.confirm(DriftSeverity::Critical),
+            DriftSeverity::Critical
+        );
+
+        // Default honors a halt on the first bar
+        let mut tracker = DriftTracker::default_windows();
+        assert_eq!(
+            tracker.confirm(DriftSeverity::Severe),
+            DriftSeverity::Severe
+        );
+    }
+
     /// Pseudo-random for deterministic tests
     fn rand_like() -> f64 {
         use std::time::{SystemTime, UNIX_EPOCH};
@@ -2,8 +2,11 @@ use anyhow::{anyhow, Result};
 use reqwest::Client;
 use serde::Deserialize;
 
+use crate::adapter::throttle;
+use crate::exchange::error::checked;
 use crate::exchange::signing::sign_binance;
-use crate::exchange::{Candle, Exchange};
+use crate::exchange::symbol::normalize;
+use crate::exchange::{BookTop, Candle, Exchange, ExchangeError, ExchangeKind};
 use crate::state::{now_ts, Config, Fill};
 use crate::strategy::{Action, MarketAux};
 
@@ -49,6 +52,13 @@ struct BinanceFundingRate {
     funding_time: u64,
 }
 
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct BinanceBookTicker {
+    bid_price: String,
+    ask_price: String,
+}
+
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)]
 struct BinanceMarkPrice {
@@ -94,30 +104,57 @@ struct BinanceError {
 #[async_trait::async_trait]
 impl Exchange for Binance {
     async fn fetch_latest_candle(&self, symbol: &str, granularity: u64) -> Result<Candle> {
+        self.fetch_recent_candles(symbol, granularity, 1)
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("empty kline"))
+    }
+
+    async fn fetch_recent_candles(
+        &self,
+        symbol: &str,
+        granularity: u64,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
         let interval = Self::as_kline_interval(granularity);
         let url = format!(
-            "{}/api/v3/klines?symbol={}&interval={}&limit=1",
-            self.base, symbol, interval
+            "{}/api/v3/klines?symbol={}&interval={}&limit={}",
+            self.base, symbol, interval, limit
         );
-        let resp = self.client.get(&url).send().await?;
-        let data: Vec<Vec<serde_json::Value>> = resp.json().await?;
-        let row = data.get(0).ok_or_else(|| anyhow!("empty kline"))?;
-        Ok(Candle {
-            ts: row[0].as_u64().unwrap_or(0) / 1000,
-            o: row[1].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            h: row[2].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            l: row[3].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            c: row[4].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            v: row[5].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-        })
+        throttle::binance().acquire(1.0).await;
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(ExchangeError::from)?;
+        let data: Vec<Vec<serde_json::Value>> = checked(resp)
+            .await?
+            .json()
+            .await
+            .map_err(ExchangeError::from)?;
+        Ok(data
+            .iter()
+            .map(|row| Candle {
+                ts: row[0].as_u64().unwrap_or(0) / 1000,
+                o: row[1].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                h: row[2].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                l: row[3].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                c: row[4].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                v: row[5].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+            })
+            .collect())
     }
 
     async fn fetch_aux(&self, symbol: &str) -> Result<MarketAux> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
         // Fetch funding rate from Binance Futures API
         let funding_url = format!(
             "https://fapi.binance.com/fapi/v1/fundingRate?symbol={}&limit=1",
             symbol
         );
+        throttle::binance().acquire(1.0).await;
         let funding_rate = match self.client.get(&funding_url).send().await {
             Ok(resp) => {
                 let data: Vec<BinanceFundingRate> = resp.json().await.unwrap_or_default();
@@ -133,6 +170,7 @@ impl Exchange for Binance {
             "https://fapi.binance.com/fapi/v1/premiumIndex?symbol={}",
             symbol
         );
+        throttle::binance().acquire(1.0).await;
         let (mark_price, index_price) = match self.client.get(&mark_url).send().await {
             Ok(resp) => {
                 let data: BinanceMarkPrice = resp.json().await.unwrap_or(BinanceMarkPrice {
@@ -176,6 +214,28 @@ impl Exchange for Binance {
             has_borrow: false,       // Not fetched from Binance
             has_liquidations: false, // Using proxy
             has_depeg: stable_depeg != 0.0,
+            next_funding_ts: 0,
+        })
+    }
+
+    async fn fetch_book_top(&self, symbol: &str) -> Result<BookTop> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
+        let url = format!("{}/api/v3/ticker/bookTicker?symbol={}", self.base, symbol);
+        throttle::binance().acquire(1.0).await;
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(ExchangeError::from)?;
+        let data: BinanceBookTicker = checked(resp)
+            .await?
+            .json()
+            .await
+            .map_err(ExchangeError::from)?;
+        Ok(BookTop {
+            bid: data.bid_price.parse()?,
+            ask: data.ask_price.parse()?,
         })
     }
 
@@ -185,6 +245,7 @@ impl Exchange for Binance {
         action: Action,
         state: &crate::strategy::StrategyState,
     ) -> Result<Fill> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
         let (side, qty) = match action {
             Action::Buy { qty } => ("BUY", qty),
             Action::Sell { qty } => ("SELL", qty),
@@ -215,12 +276,14 @@ impl Exchange for Binance {
         let api_key = self
             .api_key
             .as_ref()
-            .ok_or_else(|| anyhow!("missing API_KEY"))?;
+            .ok_or_else(|| ExchangeError::Auth("missing API_KEY".into()))?;
         let api_secret = self
             .api_secret
             .as_ref()
-            .ok_or_else(|| anyhow!("missing API_SECRET"))?;
+            .ok_or_else(|| ExchangeError::Auth("missing API_SECRET".into()))?;
 
+        // Wait before stamping, so a queued request can't outlive recvWindow
+        throttle::binance().acquire(1.0).await;
         let timestamp = Self::timestamp_ms();
         let recv_window = 5000u64;
 
@@ -240,7 +303,8 @@ impl Exchange for Binance {
             .post(&url)
             .header("X-MBX-APIKEY", api_key)
             .send()
-            .await?;
+            .await
+            .map_err(ExchangeError::from)?;
 
         let status = resp.status();
         let body = resp.text().await?;
@@ -250,7 +314,11 @@ impl Exchange for Binance {
                 code: -1,
                 msg: body.clone(),
             });
-            return Err(anyhow!("Binance order failed: {} - {}", err.code, err.msg));
+            return Err(ExchangeError::from_status(
+                status.as_u16(),
+                &format!("Binance order failed: {} - {}", err.code, err.msg),
+            )
+            .into());
         }
 
         let order: BinanceOrderResponse = serde_json::from_str(&body)?;
@@ -0,0 +1,259 @@
+//! Per-symbol contract specs: tick size, lot step and minimum notional.
+//!
+//! Orders are rounded to the venue's grid before they leave the process, so
+//! each traded symbol needs its own filters. The registry is built from
+//! Binance `exchangeInfo` (live or saved to a file) or a plain JSON map;
+//! symbols it doesn't know fall back to coarse defaults.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Exchange filters
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct ExchangeFilters {
+    /// Price tick size (e.g., 0.01)
+    pub tick_size: f64,
+    /// Quantity step size (e.g., 0.001)
+    pub step_size: f64,
+    /// Minimum notional value (e.g., 10.0)
+    pub min_notional: f64,
+}
+
+impl ExchangeFilters {
+    #[allow(dead_code)]
+    pub fn binance_btcusdt() -> Self {
+        Self {
+            tick_size: 0.01,
+            step_size: 0.00001,
+            min_notional: 10.0,
+        }
+    }
+
+    /// Fallback for symbols without a spec: a coarse lot step and a high
+    /// minimum notional, so an unknown contract errs toward smaller or
+    /// skipped orders rather than venue rejects.
+    pub fn conservative() -> Self {
+        Self {
+            tick_size: 0.01,
+            step_size: 0.001,
+            min_notional: 10.0,
+        }
+    }
+
+    /// Round quantity to step size
+    pub fn round_qty(&self, qty: f64) -> f64 {
+        // Nudge before flooring so 0.3 / 0.1 = 2.9999999999999996 stays 3 steps
+        (qty / self.step_size + 1e-9).floor() * self.step_size
+    }
+
+    /// Round price to tick size
+    pub fn round_price(&self, price: f64) -> f64 {
+        (price / self.tick_size).round() * self.tick_size
+    }
+
+    /// Check if order meets minimum notional
+    pub fn meets_min_notional(&self, qty: f64, price: f64) -> bool {
+        qty * price >= self.min_notional
+    }
+}
+
+/// Symbol → filters, with a fallback for anything unlisted.
+#[derive(Debug, Clone)]
+pub struct ContractSpecs {
+    specs: HashMap<String, ExchangeFilters>,
+    fallback: ExchangeFilters,
+}
+
+impl Default for ContractSpecs {
+    fn default() -> Self {
+        Self {
+            specs: HashMap::new(),
+            fallback: ExchangeFilters::conservative(),
+        }
+    }
+}
+
+impl ContractSpecs {
+    pub fn with_spec(mut self, symbol: &str, filters: ExchangeFilters) -> Self {
+        self.specs.insert(symbol.to_uppercase(), filters);
+        self
+    }
+
+    /// Filters for `symbol`, or the conservative fallback.
+    pub fn get(&self, symbol: &str) -> ExchangeFilters {
+        self.lookup(symbol).unwrap_or(self.fallback)
+    }
+
+    pub fn lookup(&self, symbol: &str) -> Option<ExchangeFilters> {
+        self.specs.get(&symbol.to_uppercase()).copied()
+    }
+
+    /// Of `symbols`, those that will trade on the fallback filters.
+    pub fn missing<'a>(&self, symbols: &'a [String]) -> Vec<&'a str> {
+        symbols
+            .iter()
+            .filter(|s| self.lookup(s).is_none())
+            .map(|s| s.as_str())
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    /// Parse either a Binance `exchangeInfo` body (`{"symbols": [...]}`) or a
+    /// map of `{"SYMBOL": {"tick_size", "step_size", "min_notional"}}`.
+    pub fn from_json(json: &Value) -> Result<Self> {
+        if let Some(symbols) = json.get("symbols").and_then(|v| v.as_array()) {
+            let mut specs = Self::default();
+            for sym in symbols {
+                if let Some((name, filters)) = parse_binance_symbol(sym) {
+                    specs.specs.insert(name, filters);
+                }
+            }
+            return Ok(specs);
+        }
+        let map: HashMap<String, ExchangeFilters> =
+            serde_json::from_value(json.clone()).map_err(|e| anyhow!("contract specs: {}", e))?;
+        Ok(map
+            .into_iter()
+            .fold(Self::default(), |acc, (s, f)| acc.with_spec(&s, f)))
+    }
+
+    pub fn from_file(path: &str) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Self::from_json(&serde_json::from_str(&text)?)
+    }
+
+    /// Load from Binance `GET /api/v3/exchangeInfo`.
+    pub async fn fetch_binance(base: &str) -> Result<Self> {
+        let url = format!("{}/api/v3/exchangeInfo", base);
+        let body: Value = reqwest::Client::new()
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Self::from_json(&body)
+    }
+}
+
+/// One `exchangeInfo` symbol entry; None unless it carries both a price and
+/// a lot filter. Binance sends the numbers as strings.
+fn parse_binance_symbol(sym: &Value) -> Option<(String, ExchangeFilters)> {
+    let name = sym.get("symbol")?.as_str()?.to_uppercase();
+    let num = |f: &Value, key: &str| -> Option<f64> {
+        let v = f.get(key)?;
+        v.as_str()
+            .and_then(|s| s.parse().ok())
+            .or_else(|| v.as_f64())
+    };
+    let (mut tick, mut step, mut min_notional) = (None, None, 0.0);
+    for f in sym.get("filters")?.as_array()? {
+        match f.get("filterType").and_then(|v| v.as_str()) {
+            Some("PRICE_FILTER") => tick = num(f, "tickSize"),
+            Some("LOT_SIZE") => step = num(f, "stepSize"),
+            // Spot renamed MIN_NOTIONAL to NOTIONAL; futures use `notional`
+            Some("MIN_NOTIONAL") | Some("NOTIONAL") => {
+                min_notional = num(f, "minNotional")
+                    .or_else(|| num(f, "notional"))
+                    .unwrap_or(0.0)
+            }
+            _ => {}
+        }
+    }
+    let (tick_size, step_size) = (tick?, step?);
+    (tick_size > 0.0 && step_size > 0.0).then_some((
+        name,
+        ExchangeFilters {
+            tick_size,
+            step_size,
+            min_notional,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn exchange_info() -> Value {
+        json!({
+            "timezone": "UTC",
+            "symbols": [
+                {
+                    "symbol": "BTCUSDT",
+                    "filters": [
+                        {"filterType": "PRICE_FILTER", "minPrice": "0.01", "tickSize": "0.01"},
+                        {"filterType": "LOT_SIZE", "minQty": "0.00001", "stepSize": "0.00001"},
+                        {"filterType": "NOTIONAL", "minNotional": "5.00"}
+                    ]
+                },
+                {
+                    "symbol": "DOGEUSDT",
+                    "filters": [
+                        {"filterType": "PRICE_FILTER", "tickSize": "0.00001"},
+                        {"filterType": "LOT_SIZE", "stepSize": "1.00000000"},
+                        {"filterType": "MIN_NOTIONAL", "minNotional": "1.0"}
+                    ]
+                },
+                {"symbol": "NOFILTERS", "filters": []}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_registry_returns_filters_per_symbol() {
+        let specs = ContractSpecs::from_json(&exchange_info()).unwrap();
+        assert_eq!(specs.len(), 2);
+
+        let btc = specs.get("BTCUSDT");
+        assert_eq!(btc.step_size, 0.00001);
+        assert_eq!(btc.min_notional, 5.0);
+        assert_eq!(btc.round_qty(0.123456), 0.12345);
+
+        let doge = specs.get("dogeusdt");
+        assert_eq!(doge.tick_size, 0.00001);
+        assert_eq!(doge.round_qty(123.9), 123.0);
+        assert!((doge.round_price(0.123456) - 0.12346).abs() < 1e-12);
+        assert!(!doge.meets_min_notional(5.0, 0.1));
+    }
+
+    #[test]
+    fn test_unknown_symbols_fall_back_to_defaults() {
+        let specs = ContractSpecs::from_json(&exchange_info()).unwrap();
+        assert_eq!(specs.get("NOFILTERS"), ExchangeFilters::conservative());
+        assert_eq!(specs.get("ETHUSDT"), ExchangeFilters::conservative());
+        let symbols = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()];
+        assert_eq!(specs.missing(&symbols), vec!["ETHUSDT"]);
+    }
+
+    #[test]
+    fn test_plain_json_map() {
+        let specs = ContractSpecs::from_json(&json!({
+            "ethusdt": {"tick_size": 0.01, "step_size": 0.0001, "min_notional": 5.0}
+        }))
+        .unwrap();
+        assert_eq!(specs.get("ETHUSDT").step_size, 0.0001);
+        assert!(ContractSpecs::from_json(&json!({"ETHUSDT": {"tick_size": 1}})).is_err());
+    }
+
+    #[test]
+    fn test_round_qty_survives_float_division() {
+        let f = ExchangeFilters {
+            tick_size: 0.1,
+            step_size: 0.1,
+            min_notional: 0.0,
+        };
+        assert!((f.round_qty(0.3) - 0.3).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,175 @@
+//! Typed venue failures.
+//!
+//! Adapters still return `anyhow::Result`, but wrap venue failures in
+//! `ExchangeError` so callers can downcast and decide: transient variants are
+//! worth retrying, auth failures should stop trading, the rest are permanent.
+
+use std::fmt;
+
+use super::retry::is_retryable_http_error;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExchangeError {
+    /// Request weight or order-rate limit hit; back off and retry.
+    RateLimited(String),
+    /// Missing, invalid or under-permissioned credentials.
+    Auth(String),
+    /// The venue does not know the symbol/pair.
+    BadSymbol(String),
+    /// Connect/timeout failure before a response arrived.
+    Network(String),
+    /// Venue-side failure (5xx, maintenance, malformed response).
+    Server(String),
+    /// Any other rejected request (bad params, insufficient balance, ...).
+    Rejected(String),
+}
+
+impl ExchangeError {
+    /// Map an HTTP status plus response body.
+    pub fn from_status(status: u16, body: &str) -> Self {
+        let msg = format!("HTTP {}: {}", status, body);
+        match status {
+            429 | 418 => ExchangeError::RateLimited(msg),
+            401 | 403 => ExchangeError::Auth(msg),
+            s if is_retryable_http_error(s) || (500..600).contains(&s) => {
+                ExchangeError::Server(msg)
+            }
+            // Binance: {"code":-1121,"msg":"Invalid symbol."}
+            400 | 404 if body.to_lowercase().contains("symbol") => ExchangeError::BadSymbol(msg),
+            _ => ExchangeError::Rejected(msg),
+        }
+    }
+
+    /// Map Kraken's `error` array (Kraken answers 200 with errors in the body).
+    pub fn from_kraken(errors: &[String]) -> Self {
+        let msg = errors.join("; ");
+        let has = |prefix: &str| errors.iter().any(|e| e.starts_with(prefix));
+        if has("EAPI:Rate limit") || has("EOrder:Rate limit") || has("EGeneral:Too many") {
+            ExchangeError::RateLimited(msg)
+        } else if has("EAPI:Invalid key")
+            || has("EAPI:Invalid signature")
+            || has("EAPI:Invalid nonce")
+            || has("EGeneral:Permission denied")
+        {
+            ExchangeError::Auth(msg)
+        } else if has("EQuery:Unknown asset pair") {
+            ExchangeError::BadSymbol(msg)
+        } else if has("EService:") || has("EGeneral:Temporary") {
+            ExchangeError::Server(msg)
+        } else {
+            ExchangeError::Rejected(msg)
+        }
+    }
+
+    /// Worth retrying with backoff.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ExchangeError::RateLimited(_) | ExchangeError::Network(_) | ExchangeError::Server(_)
+        )
+    }
+
+    pub fn is_auth(&self) -> bool {
+        matches!(self, ExchangeError::Auth(_))
+    }
+
+    /// The typed cause of an adapter error, if it has one.
+    pub fn classify(err: &anyhow::Error) -> Option<&ExchangeError> {
+        err.downcast_ref::<ExchangeError>()
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ExchangeError::RateLimited(_) => "rate_limited",
+            ExchangeError::Auth(_) => "auth",
+            ExchangeError::BadSymbol(_) => "bad_symbol",
+            ExchangeError::Network(_) => "network",
+            ExchangeError::Server(_) => "server",
+            ExchangeError::Rejected(_) => "rejected",
+        }
+    }
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let msg = match self {
+            ExchangeError::RateLimited(m)
+            | ExchangeError::Auth(m)
+            | ExchangeError::BadSymbol(m)
+            | ExchangeError::Network(m)
+            | ExchangeError::Server(m)
+            | ExchangeError::Rejected(m) => m,
+        };
+        write!(f, "{}: {}", self.kind(), msg)
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+impl From<reqwest::Error> for ExchangeError {
+    fn from(err: reqwest::Error) -> Self {
+        match err.status() {
+            Some(status) => ExchangeError::from_status(status.as_u16(), &err.to_string()),
+            None if err.is_decode() => ExchangeError::Server(err.to_string()),
+            None => ExchangeError::Network(err.to_string()),
+        }
+    }
+}
+
+/// Pass successful responses through; turn anything else into a typed error.
+pub(crate) async fn checked(resp: reqwest::Response) -> Result<reqwest::Response, ExchangeError> {
+    let status = resp.status();
+    if status.is_success() {
+        return Ok(resp);
+    }
+    let body = resp.text().await.unwrap_or_default();
+    Err(ExchangeError::from_status(status.as_u16(), &body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_status_mapping() {
+        assert!(matches!(
+            ExchangeError::from_status(429, ""),
+            ExchangeError::RateLimited(_)
+        ));
+        assert!(ExchangeError::from_status(401, "").is_auth());
+        assert!(matches!(
+            ExchangeError::from_status(400, r#"{"code":-1121,"msg":"Invalid symbol."}"#),
+            ExchangeError::BadSymbol(_)
+        ));
+        assert!(ExchangeError::from_status(503, "").is_transient());
+        let rejected = ExchangeError::from_status(400, r#"{"code":-2010,"msg":"balance"}"#);
+        assert!(matches!(rejected, ExchangeError::Rejected(_)));
+        assert!(!rejected.is_transient());
+    }
+
+    #[test]
+    fn test_kraken_error_mapping() {
+        let e = |s: &str| ExchangeError::from_kraken(&[s.to_string()]);
+        assert!(matches!(
+            e("EAPI:Rate limit exceeded"),
+            ExchangeError::RateLimited(_)
+        ));
+        assert!(e("EAPI:Invalid key").is_auth());
+        assert!(matches!(
+            e("EQuery:Unknown asset pair"),
+            ExchangeError::BadSymbol(_)
+        ));
+        assert!(e("EService:Unavailable").is_transient());
+        assert!(matches!(
+            e("EOrder:Insufficient funds"),
+            ExchangeError::Rejected(_)
+        ));
+    }
+
+    #[test]
+    fn test_classify_through_anyhow() {
+        let err: anyhow::Error = ExchangeError::Auth("bad key".into()).into();
+        assert!(ExchangeError::classify(&err).is_some_and(ExchangeError::is_auth));
+        assert!(ExchangeError::classify(&anyhow::anyhow!("plain")).is_none());
+    }
+}
@@ -4,8 +4,10 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use tokio::time::{sleep, Duration};
 
+use crate::exchange::error::checked;
 use crate::exchange::signing::sign_kraken;
-use crate::exchange::{Candle, Exchange};
+use crate::exchange::symbol::normalize;
+use crate::exchange::{BookTop, Candle, Exchange, ExchangeError, ExchangeKind};
 use crate::state::{now_ts, Config, Fill};
 use crate::strategy::{Action, MarketAux};
 
@@ -18,6 +20,11 @@ pub struct Kraken {
 
 impl Kraken {
     pub fn new(cfg: Config) -> Result<Self> {
+        if cfg.kraken_base.is_empty() {
+            return Err(anyhow!(
+                "Kraken has no spot testnet; set KRAKEN_BASE to use it with USE_TESTNET"
+            ));
+        }
         Ok(Self {
             client: Client::new(),
             base: cfg.kraken_base,
@@ -41,11 +48,7 @@ impl Kraken {
     }
 
     fn to_kraken_pair(symbol: &str) -> String {
-        if symbol.starts_with("BTC") {
-            symbol.replacen("BTC", "XBT", 1)
-        } else {
-            symbol.to_string()
-        }
+        normalize(symbol, ExchangeKind::Kraken)
     }
 
     /// Query order status to get real fill information
@@ -53,11 +56,11 @@ impl Kraken {
         let api_key = self
             .api_key
             .as_ref()
-            .ok_or_else(|| anyhow!("missing API_KEY"))?;
+            .ok_or_else(|| ExchangeError::Auth("missing API_KEY".into()))?;
         let api_secret = self
             .api_secret
             .as_ref()
-            .ok_or_else(|| anyhow!("missing API_SECRET"))?;
+            .ok_or_else(|| ExchangeError::Auth("missing API_SECRET".into()))?;
 
         let nonce = Self::nonce();
         let post_data = format!("nonce={}&txid={}", nonce, txid);
@@ -76,13 +79,14 @@ impl Kraken {
             .header("Content-Type", "application/x-www-form-urlencoded")
             .body(post_data)
             .send()
-            .await?;
+            .await
+            .map_err(ExchangeError::from)?;
 
         let body = resp.text().await?;
         let result: KrakenResp<HashMap<String, KrakenOrderInfo>> = serde_json::from_str(&body)?;
 
         if !result.error.is_empty() {
-            return Err(anyhow!("Kraken query error: {:?}", result.error));
+            return Err(ExchangeError::from_kraken(&result.error).into());
         }
 
         result
@@ -170,17 +174,38 @@ struct KrakenTickerInfo {
 #[async_trait::async_trait]
 impl Exchange for Kraken {
     async fn fetch_latest_candle(&self, symbol: &str, granularity: u64) -> Result<Candle> {
+        self.fetch_recent_candles(symbol, granularity, 1)
+            .await?
+            .pop()
+            .ok_or_else(|| anyhow!("empty"))
+    }
+
+    async fn fetch_recent_candles(
+        &self,
+        symbol: &str,
+        granularity: u64,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
         let pair = Self::to_kraken_pair(symbol);
         let interval = Self::as_kraken_interval(granularity);
         let url = format!(
             "{}/0/public/OHLC?pair={}&interval={}",
             self.base, pair, interval
         );
-        let resp = self.client.get(&url).send().await?;
-        let data: KrakenResp<serde_json::Value> = resp.json().await?;
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(ExchangeError::from)?;
+        let data: KrakenResp<serde_json::Value> = checked(resp)
+            .await?
+            .json()
+            .await
+            .map_err(ExchangeError::from)?;
 
         if !data.error.is_empty() {
-            return Err(anyhow!("Kraken error: {:?}", data.error));
+            return Err(ExchangeError::from_kraken(&data.error).into());
         }
 
         let result = data.result.ok_or_else(|| anyhow!("missing result"))?;
@@ -192,17 +217,21 @@ impl Exchange for Kraken {
             .find(|(k, _)| *k != "last")
             .ok_or_else(|| anyhow!("missing series"))?;
         let candles = series.as_array().ok_or_else(|| anyhow!("bad series"))?;
-        let row = candles.last().ok_or_else(|| anyhow!("empty"))?;
-        let row = row.as_array().ok_or_else(|| anyhow!("bad row"))?;
-
-        Ok(Candle {
-            ts: row[0].as_u64().unwrap_or(0),
-            o: row[1].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            h: row[2].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            l: row[3].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            c: row[4].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-            v: row[6].as_str().unwrap_or("0").parse().unwrap_or(0.0),
-        })
+        let start = candles.len().saturating_sub(limit);
+        candles[start..]
+            .iter()
+            .map(|row| {
+                let row = row.as_array().ok_or_else(|| anyhow!("bad row"))?;
+                Ok(Candle {
+                    ts: row[0].as_u64().unwrap_or(0),
+                    o: row[1].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                    h: row[2].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                    l: row[3].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                    c: row[4].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                    v: row[6].as_str().unwrap_or("0").parse().unwrap_or(0.0),
+                })
+            })
+            .collect()
     }
 
     async fn fetch_aux(&self, symbol: &str) -> Result<MarketAux> {
@@ -250,6 +279,43 @@ impl Exchange for Kraken {
             has_borrow: spread > 0.0, // Using spread as proxy
             has_liquidations: false,
             has_depeg: false,
+            next_funding_ts: 0,
+        })
+    }
+
+    async fn fetch_book_top(&self, symbol: &str) -> Result<BookTop> {
+        let url = format!(
+            "{}/0/public/Ticker?pair={}",
+            self.base,
+            Self::to_kraken_pair(symbol)
+        );
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(ExchangeError::from)?;
+        let data: KrakenResp<HashMap<String, KrakenTickerInfo>> = checked(resp)
+            .await?
+            .json()
+            .await
+            .map_err(ExchangeError::from)?;
+        if !data.error.is_empty() {
+            return Err(ExchangeError::from_kraken(&data.error).into());
+        }
+        let info = data
+            .result
+            .and_then(|r| r.into_values().next())
+            .ok_or_else(|| anyhow!("empty kraken ticker"))?;
+        let top = |side: &[String]| -> Result<f64> {
+            Ok(side
+                .first()
+                .ok_or_else(|| anyhow!("missing quote"))?
+                .parse()?)
+        };
+        Ok(BookTop {
+            bid: top(&info.b)?,
+            ask: top(&info.a)?,
         })
     }
 
@@ -289,11 +355,11 @@ impl Exchange for Kraken {
         let api_key = self
             .api_key
             .as_ref()
-            .ok_or_else(|| anyhow!("missing API_KEY"))?;
+            .ok_or_else(|| ExchangeError::Auth("missing API_KEY".into()))?;
         let api_secret = self
             .api_secret
             .as_ref()
-            .ok_or_else(|| anyhow!("missing API_SECRET"))?;
+            .ok_or_else(|| ExchangeError::Auth("missing API_SECRET".into()))?;
 
         let pair = Self::to_kraken_pair(symbol);
         let nonce = Self::nonce();
@@ -317,19 +383,20 @@ impl Exchange for Kraken {
             .header("Content-Type", "application/x-www-form-urlencoded")
             .body(post_data)
             .send()
-            .await?;
+            .await
+            .map_err(ExchangeError::from)?;
 
         let status = resp.status();
         let body = resp.text().await?;
 
         if !status.is_success() {
-            return Err(anyhow!("Kraken HTTP error: {} - {}", status, body));
+            return Err(ExchangeError::from_status(status.as_u16(), &body).into());
         }
 
         let result: KrakenResp<KrakenOrderResult> = serde_json::from_str(&body)?;
 
         if !result.error.is_empty() {
-            return Err(anyhow!("Kraken order failed: {:?}", result.error));
+            return Err(ExchangeError::from_kraken(&result.error).into());
         }
 
         let order = result
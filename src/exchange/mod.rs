@@ -1,14 +1,20 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use async_trait::async_trait;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::state::{Config, Fill};
 use crate::strategy::{Action, MarketAux};
 
 mod binance;
+pub mod contract_spec;
+pub mod error;
 mod kraken;
+pub mod paper;
 pub mod retry;
 pub mod signing;
+pub mod symbol;
+
+pub use error::ExchangeError;
 
 #[derive(Clone, Copy, Debug)]
 pub enum ExchangeKind {
@@ -18,12 +24,17 @@ pub enum ExchangeKind {
 
 impl ExchangeKind {
     pub fn from_env() -> Self {
-        match std::env::var("EXCHANGE")
-            .unwrap_or_else(|_| "binance".to_string())
-            .as_str()
-        {
-            "kraken" => ExchangeKind::Kraken,
-            _ => ExchangeKind::Binance,
+        std::env::var("EXCHANGE")
+            .ok()
+            .and_then(|v| Self::from_name(&v))
+            .unwrap_or(ExchangeKind::Binance)
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "binance" => Some(ExchangeKind::Binance),
+            "kraken" => Some(ExchangeKind::Kraken),
+            _ => None,
         }
     }
 
@@ -35,7 +46,7 @@ impl ExchangeKind {
     }
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Candle {
     pub ts: u64,
     pub o: f64,
@@ -45,10 +56,39 @@ pub struct Candle {
     pub v: f64,
 }
 
+/// Best bid/ask at the top of the book.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BookTop {
+    pub bid: f64,
+    pub ask: f64,
+}
+
+impl BookTop {
+    /// Quoted spread as a fraction of mid; None for an empty or crossed book.
+    pub fn spread_pct(&self) -> Option<f64> {
+        if !(self.bid > 0.0 && self.ask >= self.bid) {
+            return None;
+        }
+        let mid = (self.bid + self.ask) / 2.0;
+        Some((self.ask - self.bid) / mid)
+    }
+}
+
 #[async_trait]
 pub trait Exchange {
     async fn fetch_latest_candle(&self, symbol: &str, granularity: u64) -> Result<Candle>;
+    /// Up to `limit` most recent candles, oldest first.
+    async fn fetch_recent_candles(
+        &self,
+        symbol: &str,
+        granularity: u64,
+        limit: usize,
+    ) -> Result<Vec<Candle>>;
     async fn fetch_aux(&self, symbol: &str) -> Result<MarketAux>;
+    /// Current top of book. Venues without a book endpoint return an error.
+    async fn fetch_book_top(&self, symbol: &str) -> Result<BookTop> {
+        Err(anyhow!("book not available for {}", symbol))
+    }
     async fn execute(
         &self,
         symbol: &str,
@@ -0,0 +1,242 @@
+//! Paper execution: fills simulated against the last observed candle.
+//!
+//! Market data is delegated to a real venue; orders never leave the process.
+//! Slippage scales with bar volatility and with order size relative to the
+//! bar's traded notional, so large orders in thin bars pay more.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+
+use super::{BookTop, Candle, Exchange};
+use crate::state::{Config, Fill};
+use crate::strategy::{Action, MarketAux, StrategyState};
+
+/// Smoothing factor for the per-symbol baseline bar range.
+const RANGE_EMA_ALPHA: f64 = 0.05;
+
+/// Volatility- and size-adjusted slippage fraction:
+/// `base * vol_mult * (1 + impact_coeff * order_notional / bar_volume_notional)`.
+pub fn size_adjusted_slippage(
+    base_slippage: f64,
+    vol_mult: f64,
+    order_notional: f64,
+    bar_volume_notional: f64,
+    impact_coeff: f64,
+) -> f64 {
+    let participation = order_notional.abs() / bar_volume_notional.max(1.0);
+    base_slippage * vol_mult.max(1.0) * (1.0 + impact_coeff * participation)
+}
+
+/// Per-symbol view of the most recent bar plus a slow baseline of bar range.
+#[derive(Debug, Clone, Copy)]
+struct BarContext {
+    candle: Candle,
+    baseline_range: f64,
+}
+
+/// Parameters of the paper fill model.
+#[derive(Debug, Clone, Copy)]
+pub struct PaperFillModel {
+    pub base_slippage: f64,
+    pub fee_rate: f64,
+    pub impact_coeff: f64,
+}
+
+impl PaperFillModel {
+    pub fn from_config(cfg: &Config) -> Self {
+        Self {
+            base_slippage: cfg.paper_slippage,
+            fee_rate: cfg.paper_fee_rate,
+            impact_coeff: cfg.impact_coeff,
+        }
+    }
+
+    /// Slippage fraction for an order of `order_notional` against `candle`.
+    pub fn slippage(&self, candle: &Candle, baseline_range: f64, order_notional: f64) -> f64 {
+        let vol_mult = if baseline_range > 0.0 && candle.c > 0.0 {
+            ((candle.h - candle.l) / candle.c) / baseline_range
+        } else {
+            1.0
+        };
+        let bar_volume_notional = candle.v * candle.c;
+        size_adjusted_slippage(
+            self.base_slippage,
+            vol_mult,
+            order_notional,
+            bar_volume_notional,
+            self.impact_coeff,
+        )
+    }
+
+    /// Fill a signed quantity (buy > 0, sell < 0) at the bar close, adjusted for slippage.
+    pub fn simulate_fill(&self, candle: &Candle, baseline_range: f64, qty: f64) -> Fill {
+        if qty == 0.0 || candle.c <= 0.0 {
+            return Fill {
+                price: 0.0,
+                qty: 0.0,
+                fee: 0.0,
+                ts: candle.ts,
+            };
+        }
+        let slip = self.slippage(candle, baseline_range, qty.abs() * candle.c);
+        let price = candle.c * (1.0 + slip * qty.signum());
+        Fill {
+            price,
+            qty,
+            fee: qty.abs() * price * self.fee_rate,
+            ts: candle.ts,
+        }
+    }
+}
+
+/// Wraps a venue for market data and simulates execution locally.
+pub struct PaperExchange {
+    inner: Box<dyn Exchange + Send + Sync>,
+    model: PaperFillModel,
+    bars: Mutex<HashMap<String, BarContext>>,
+}
+
+impl PaperExchange {
+    pub fn new(inner: Box<dyn Exchange + Send + Sync>, cfg: &Config) -> Self {
+        Self {
+            inner,
+            model: PaperFillModel::from_config(cfg),
+            bars: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn observe(&self, symbol: &str, candle: Candle) {
+        let range = if candle.c > 0.0 {
+            (candle.h - candle.l) / candle.c
+        } else {
+            0.0
+        };
+        let mut bars = self.bars.lock().unwrap_or_else(|e| e.into_inner());
+        let ctx = bars.entry(symbol.to_string()).or_insert(BarContext {
+            candle,
+            baseline_range: range,
+        });
+        ctx.candle = candle;
+        ctx.baseline_range += RANGE_EMA_ALPHA * (range - ctx.baseline_range);
+    }
+}
+
+#[async_trait]
+impl Exchange for PaperExchange {
+    async fn fetch_latest_candle(&self, symbol: &str, granularity: u64) -> Result<Candle> {
+        let candle = self.inner.fetch_latest_candle(symbol, granularity).await?;
+        self.observe(symbol, candle);
+        Ok(candle)
+    }
+
+    async fn fetch_recent_candles(
+        &self,
+        symbol: &str,
+        granularity: u64,
+        limit: usize,
+    ) -> Result<Vec<Candle>> {
+        self.inner
+            .fetch_recent_candles(symbol, granularity, limit)
+            .await
+    }
+
+    async fn fetch_aux(&self, symbol: &str) -> Result<MarketAux> {
+        self.inner.fetch_aux(symbol).await
+    }
+
+    async fn fetch_book_top(&self, symbol: &str) -> Result<BookTop> {
+        self.inner.fetch_book_top(symbol).await
+    }
+
+    async fn execute(&self, symbol: &str, action: Action, state: &StrategyState) -> Result<Fill> {
+        let qty = match action {
+            Action::Buy { qty } => qty.abs(),
+            Action::Sell { qty } => -qty.abs(),
+            Action::Close => -state.portfolio.position,
+            Action::Hold => 0.0,
+        };
+        let ctx = {
+            let bars = self.bars.lock().unwrap_or_else(|e| e.into_inner());
+            bars.get(symbol).copied()
+        };
+        let ctx = ctx.ok_or_else(|| anyhow!("paper: no candle observed for {}", symbol))?;
+        Ok(self
+            .model
+            .simulate_fill(&ctx.candle, ctx.baseline_range, qty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model() -> PaperFillModel {
+        PaperFillModel {
+            base_slippage: 0.0005,
+            fee_rate: 0.001,
+            impact_coeff: 1.0,
+        }
+    }
+
+    fn bar(volume: f64) -> Candle {
+        Candle {
+            ts: 1_000,
+            o: 100.0,
+            h: 101.0,
+            l: 99.0,
+            c: 100.0,
+            v: volume,
+        }
+    }
+
+    #[test]
+    fn impact_term_matches_formula() {
+        assert!((size_adjusted_slippage(0.001, 1.0, 0.0, 1e6, 2.0) - 0.001).abs() < 1e-12);
+        // Half the bar's notional at coeff 2.0 doubles the base
+        assert!((size_adjusted_slippage(0.001, 1.0, 5e5, 1e6, 2.0) - 0.002).abs() < 1e-12);
+    }
+
+    #[test]
+    fn tiny_order_gets_near_base_slippage() {
+        let m = model();
+        let candle = bar(10_000.0);
+        let fill = m.simulate_fill(&candle, 0.02, 0.001);
+        let slip = fill.price / candle.c - 1.0;
+        assert!((slip - m.base_slippage).abs() < 1e-8, "slip={}", slip);
+        assert!(fill.qty > 0.0);
+    }
+
+    #[test]
+    fn large_order_in_thin_bar_fills_materially_worse() {
+        let m = model();
+        let thick = m.simulate_fill(&bar(10_000.0), 0.02, -5.0);
+        let thin = m.simulate_fill(&bar(10.0), 0.02, -5.0);
+        // Sells fill below the close; the thin bar costs far more
+        assert!(thin.price < thick.price);
+        let thick_slip = 1.0 - thick.price / 100.0;
+        let thin_slip = 1.0 - thin.price / 100.0;
+        assert!(
+            thin_slip > 1.4 * thick_slip,
+            "thin={} thick={}",
+            thin_slip,
+            thick_slip
+        );
+        assert!(thin_slip > 1.4 * m.base_slippage);
+    }
+
+    #[test]
+    fn volatile_bar_scales_slippage() {
+        let m = model();
+        let calm = m.slippage(&bar(1e9), 0.02, 1.0);
+        let wide = Candle {
+            h: 104.0,
+            l: 96.0,
+            ..bar(1e9)
+        };
+        let volatile = m.slippage(&wide, 0.02, 1.0);
+        assert!((volatile / calm - 4.0).abs() < 1e-6);
+    }
+}
@@ -3,6 +3,8 @@ use rand::Rng;
 use std::future::Future;
 use tokio::time::{sleep, Duration};
 
+use super::ExchangeError;
+
 /// Retry configuration
 #[derive(Clone, Debug)]
 pub struct RetryConfig {
@@ -38,7 +40,9 @@ impl RetryConfig {
     }
 }
 
-/// Retry a fallible async operation with exponential backoff
+/// Retry a fallible async operation with exponential backoff.
+/// Typed exchange errors are only retried when transient; untyped errors
+/// keep the old retry-everything behaviour.
 pub async fn retry_async<F, Fut, T>(
     config: &RetryConfig,
     operation_name: &str,
@@ -54,6 +58,15 @@ where
         match operation().await {
             Ok(result) => return Ok(result),
             Err(e) => {
+                if let Some(kind) = ExchangeError::classify(&e).filter(|k| !k.is_transient()) {
+                    eprintln!(
+                        "[retry] {} failed with non-retryable {} error: {}",
+                        operation_name,
+                        kind.kind(),
+                        e
+                    );
+                    return Err(e);
+                }
                 if attempt < config.max_retries {
                     let delay = config.delay_for_attempt(attempt);
                     eprintln!(
@@ -146,4 +159,41 @@ mod tests {
         assert_eq!(result.unwrap(), 42);
         assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 3);
     }
+
+    async fn attempts_until(err: ExchangeError) -> (Result<i32>, u32) {
+        let config = RetryConfig {
+            max_retries: 3,
+            base_delay_ms: 1,
+            ..Default::default()
+        };
+        let counter = std::sync::Arc::new(std::sync::atomic::AtomicU32::new(0));
+        let result = retry_async(&config, "test", || {
+            let c = counter.clone();
+            let err = err.clone();
+            async move {
+                if c.fetch_add(1, std::sync::atomic::Ordering::SeqCst) == 0 {
+                    Err(err.into())
+                } else {
+                    Ok(42)
+                }
+            }
+        })
+        .await;
+        (result, counter.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_retried() {
+        let (result, calls) = attempts_until(ExchangeError::from_status(429, "slow down")).await;
+        assert_eq!(result.unwrap(), 42);
+        assert_eq!(calls, 2);
+    }
+
+    #[tokio::test]
+    async fn test_auth_error_is_not_retried() {
+        let (result, calls) = attempts_until(ExchangeError::Auth("invalid key".into())).await;
+        let err = result.unwrap_err();
+        assert!(ExchangeError::classify(&err).is_some_and(ExchangeError::is_auth));
+        assert_eq!(calls, 1);
+    }
 }
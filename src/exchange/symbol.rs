@@ -0,0 +1,89 @@
+//! Venue-neutral symbols.
+//!
+//! Config names a market canonically as `BASE/QUOTE` (`BTC/USDT`); each venue
+//! wants its own spelling. Raw venue symbols (`BTCUSDT`, `XBTUSD`) are also
+//! accepted, so existing configs keep working.
+
+use crate::exchange::ExchangeKind;
+
+/// Quote assets recognised when splitting a symbol without a separator,
+/// longest first so `USDT` wins over `USD`.
+const QUOTES: [&str; 8] = ["USDT", "USDC", "BUSD", "EUR", "USD", "GBP", "BTC", "ETH"];
+
+/// Kraken spells a few assets differently: (canonical, kraken).
+const KRAKEN_ASSETS: [(&str, &str); 2] = [("BTC", "XBT"), ("DOGE", "XDG")];
+
+/// Split a canonical or raw symbol into canonical `(base, quote)`; None when
+/// no known quote asset ends it.
+pub fn split(symbol: &str) -> Option<(String, String)> {
+    let upper = symbol.trim().to_uppercase();
+    let (base, quote) = match upper.split_once(['/', '-', '_']) {
+        Some((b, q)) => (b.to_string(), q.to_string()),
+        None => {
+            let quote = QUOTES
+                .iter()
+                .find(|q| upper.len() > q.len() && upper.ends_with(*q))?;
+            (
+                upper[..upper.len() - quote.len()].to_string(),
+                quote.to_string(),
+            )
+        }
+    };
+    if base.is_empty() || quote.is_empty() {
+        return None;
+    }
+    Some((canonical_asset(&base), canonical_asset(&quote)))
+}
+
+/// Map `canonical` (`BTC/USDT`) to the symbol `kind` expects. Symbols that
+/// don't split pass through upper-cased.
+pub fn normalize(canonical: &str, kind: ExchangeKind) -> String {
+    let Some((base, quote)) = split(canonical) else {
+        return canonical.trim().to_uppercase();
+    };
+    match kind {
+        ExchangeKind::Binance => format!("{}{}", base, quote),
+        ExchangeKind::Kraken => format!("{}{}", kraken_asset(&base), kraken_asset(&quote)),
+    }
+}
+
+fn canonical_asset(asset: &str) -> String {
+    KRAKEN_ASSETS
+        .iter()
+        .find(|(_, k)| *k == asset)
+        .map_or(asset, |(c, _)| c)
+        .to_string()
+}
+
+fn kraken_asset(asset: &str) -> &str {
+    KRAKEN_ASSETS
+        .iter()
+        .find(|(c, _)| *c == asset)
+        .map_or(asset, |(_, k)| k)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_maps_to_each_venue() {
+        assert_eq!(normalize("BTC/USDT", ExchangeKind::Binance), "BTCUSDT");
+        assert_eq!(normalize("BTC/USDT", ExchangeKind::Kraken), "XBTUSDT");
+        assert_eq!(normalize("btc/usd", ExchangeKind::Kraken), "XBTUSD");
+        assert_eq!(normalize("ETH/BTC", ExchangeKind::Kraken), "ETHXBT");
+        assert_eq!(normalize("DOGE/USDT", ExchangeKind::Kraken), "XDGUSDT");
+    }
+
+    #[test]
+    fn test_raw_symbols_round_trip_between_venues() {
+        // Existing configs with venue symbols keep working
+        assert_eq!(normalize("BTCUSDT", ExchangeKind::Binance), "BTCUSDT");
+        assert_eq!(normalize("BTCUSDT", ExchangeKind::Kraken), "XBTUSDT");
+        assert_eq!(normalize("XBTUSD", ExchangeKind::Binance), "BTCUSD");
+        assert_eq!(split("ETHUSDC"), Some(("ETH".into(), "USDC".into())));
+        // Unknown quote passes through
+        assert_eq!(split("FOOBAR"), None);
+        assert_eq!(normalize("foobar", ExchangeKind::Kraken), "FOOBAR");
+    }
+}
@@ -0,0 +1,123 @@
+//! OHLCV candles built from a raw trade stream.
+//!
+//! For venues without a candle websocket: trades are bucketed by
+//! `candle_granularity` and a candle is finalized the moment a trade lands in
+//! a later bucket. Buckets with no trades produce no candle, and trades older
+//! than the open bucket are dropped because the candle they belong to has
+//! already been emitted.
+
+use crate::exchange::Candle;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trade {
+    /// Seconds since epoch.
+    pub ts: u64,
+    pub price: f64,
+    pub qty: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct CandleAggregator {
+    granularity: u64,
+    open: Option<Candle>,
+    last_closed: Option<Candle>,
+}
+
+impl CandleAggregator {
+    pub fn new(granularity: u64) -> Self {
+        Self {
+            granularity: granularity.max(1),
+            open: None,
+            last_closed: None,
+        }
+    }
+
+    fn bucket(&self, ts: u64) -> u64 {
+        ts - ts % self.granularity
+    }
+
+    /// Fold one trade in. Returns the previous candle when this trade rolls
+    /// the bucket over.
+    pub fn push(&mut self, trade: Trade) -> Option<Candle> {
+        if !trade.price.is_finite() || trade.price <= 0.0 || !trade.qty.is_finite() {
+            return None;
+        }
+        let bucket = self.bucket(trade.ts);
+        match self.open.as_mut() {
+            Some(c) if bucket == c.ts => {
+                c.h = c.h.max(trade.price);
+                c.l = c.l.min(trade.price);
+                c.c = trade.price;
+                c.v += trade.qty;
+                None
+            }
+            Some(c) if bucket < c.ts => None,
+            _ => {
+                let closed = self.open.replace(Candle {
+                    ts: bucket,
+                    o: trade.price,
+                    h: trade.price,
+                    l: trade.price,
+                    c: trade.price,
+                    v: trade.qty,
+                });
+                if closed.is_some() {
+                    self.last_closed = closed;
+                }
+                closed
+            }
+        }
+    }
+
+    /// Most recent finalized candle.
+    pub fn latest(&self) -> Option<Candle> {
+        self.last_closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(ts: u64, price: f64, qty: f64) -> Trade {
+        Trade { ts, price, qty }
+    }
+
+    #[test]
+    fn trades_across_bucket_boundary_produce_ohlcv() {
+        let mut agg = CandleAggregator::new(60);
+        assert_eq!(agg.push(t(120, 100.0, 1.0)), None);
+        assert_eq!(agg.push(t(130, 105.0, 0.5)), None);
+        assert_eq!(agg.push(t(150, 98.0, 2.0)), None);
+        assert_eq!(agg.push(t(179, 101.0, 0.25)), None);
+        assert!(agg.latest().is_none());
+
+        let first = agg.push(t(180, 102.0, 1.0)).expect("bucket rolled");
+        assert_eq!(first.ts, 120);
+        assert_eq!(
+            (first.o, first.h, first.l, first.c),
+            (100.0, 105.0, 98.0, 101.0)
+        );
+        assert!((first.v - 3.75).abs() < 1e-12);
+
+        // An empty bucket (240..300) is skipped, not synthesized
+        let second = agg.push(t(305, 99.0, 1.0)).expect("bucket rolled");
+        assert_eq!(second.ts, 180);
+        assert_eq!(
+            (second.o, second.h, second.l, second.c),
+            (102.0, 102.0, 102.0, 102.0)
+        );
+        assert_eq!(agg.latest().map(|c| c.ts), Some(180));
+    }
+
+    #[test]
+    fn late_and_invalid_trades_are_dropped() {
+        let mut agg = CandleAggregator::new(60);
+        agg.push(t(120, 100.0, 1.0));
+        assert_eq!(agg.push(t(60, 500.0, 1.0)), None);
+        assert_eq!(agg.push(t(125, f64::NAN, 1.0)), None);
+        assert_eq!(agg.push(t(126, 0.0, 1.0)), None);
+        let c = agg.push(t(180, 100.0, 1.0)).unwrap();
+        assert_eq!((c.h, c.l, c.v), (100.0, 100.0, 1.0));
+    }
+}
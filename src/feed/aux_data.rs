@@ -1,33 +1,163 @@
 use anyhow::Result;
 use reqwest::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::io::Write;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
+use crate::adapter::throttle;
+use crate::exchange::symbol::normalize;
+use crate::exchange::ExchangeKind;
+use crate::state::LiqScoreSource;
 use crate::strategy::MarketAux;
 
-/// Cached aux data with TTL and backoff
+/// Funding observations recorded by the live loop, one JSON object per line;
+/// the workbench renders them as per-symbol sparklines.
+pub const FUNDING_HISTORY_PATH: &str = "out/funding/history.jsonl";
+
+/// Points kept in the funding history file: a bit over a year of 8h
+/// settlements for a handful of symbols.
+pub const FUNDING_HISTORY_MAX_POINTS: usize = 5_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FundingPoint {
+    pub ts: u64,
+    pub symbol: String,
+    pub rate: f64,
+    /// Settlement the rate applies to; with `symbol`, the point's key.
+    /// Older lines without it are keyed by `ts`.
+    #[serde(default)]
+    pub funding_ts: u64,
+}
+
+impl FundingPoint {
+    fn key(&self) -> (&str, u64) {
+        let ts = if self.funding_ts > 0 {
+            self.funding_ts
+        } else {
+            self.ts
+        };
+        (&self.symbol, ts)
+    }
+}
+
+/// Parse funding history lines, skipping malformed ones, keeping the latest
+/// observation per `(symbol, funding_ts)` and returning them in time order.
+pub fn parse_funding_history(content: &str) -> Vec<FundingPoint> {
+    let mut points: Vec<FundingPoint> = Vec::new();
+    for point in content
+        .lines()
+        .filter_map(|l| serde_json::from_str::<FundingPoint>(l).ok())
+    {
+        match points.iter_mut().find(|p| p.key() == point.key()) {
+            Some(p) if p.ts <= point.ts => *p = point,
+            Some(_) => {}
+            None => points.push(point),
+        }
+    }
+    points.sort_by_key(|p| p.ts);
+    points
+}
+
+/// Record `point` in the history at `path`, replacing an earlier observation
+/// of the same settlement and dropping the oldest points beyond `max_points`.
+/// The file is rewritten through a temp file and rename.
+pub fn record_funding_point(
+    path: &str,
+    point: &FundingPoint,
+    max_points: usize,
+) -> std::io::Result<()> {
+    if let Some(dir) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut content = match std::fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    content.push_str(&serde_json::to_string(point)?);
+    let points = parse_funding_history(&content);
+    let skip = points.len().saturating_sub(max_points);
+    let tmp_path = format!("{}.tmp", path);
+    {
+        let mut file = std::fs::File::create(&tmp_path)?;
+        for p in &points[skip..] {
+            writeln!(file, "{}", serde_json::to_string(p)?)?;
+        }
+    }
+    std::fs::rename(&tmp_path, path)
+}
+
+/// Cache lifetime of each aux component. Funding settles every few hours
+/// and borrow rates move daily, so both can be held far longer than the
+/// liquidation poll, which is only worth anything while it is recent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AuxTtls {
+    pub funding_secs: u64,
+    pub borrow_secs: u64,
+    /// Stablecoin price and futures premium
+    pub depeg_secs: u64,
+    pub liquidations_secs: u64,
+}
+
+impl Default for AuxTtls {
+    fn default() -> Self {
+        Self {
+            funding_secs: 300,
+            borrow_secs: 600,
+            depeg_secs: 60,
+            liquidations_secs: 15,
+        }
+    }
+}
+
+impl AuxTtls {
+    /// `AUX_TTL_{FUNDING,BORROW,DEPEG,LIQUIDATIONS}_SECS`, each falling back
+    /// to its default.
+    pub fn from_env() -> Self {
+        let secs = |key: &str, default: u64| {
+            std::env::var(key)
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(default)
+        };
+        let d = Self::default();
+        Self {
+            funding_secs: secs("AUX_TTL_FUNDING_SECS", d.funding_secs),
+            borrow_secs: secs("AUX_TTL_BORROW_SECS", d.borrow_secs),
+            depeg_secs: secs("AUX_TTL_DEPEG_SECS", d.depeg_secs),
+            liquidations_secs: secs("AUX_TTL_LIQUIDATIONS_SECS", d.liquidations_secs),
+        }
+    }
+}
+
+/// One cached aux component with TTL and backoff. `fetch_ts` is the wall
+/// clock of the fetch that produced `data`, kept as-is while it is served
+/// from cache so staleness checks see its true age.
 #[derive(Debug, Clone)]
-struct CachedAux {
-    data: MarketAux,
+struct CachedAux<T> {
+    data: T,
+    fetch_ts: u64,
     fetched_at: Instant,
     fetch_failures: u32,
     last_failure: Option<Instant>,
 }
 
-impl CachedAux {
-    fn new(data: MarketAux) -> Self {
+impl<T: Copy> CachedAux<T> {
+    fn new(data: T, fetch_ts: u64, now: Instant) -> Self {
         Self {
             data,
-            fetched_at: Instant::now(),
+            fetch_ts,
+            fetched_at: now,
             fetch_failures: 0,
             last_failure: None,
         }
     }
 
-    fn is_fresh(&self, ttl_secs: u64) -> bool {
-        self.fetched_at.elapsed() < Duration::from_secs(ttl_secs)
+    fn is_fresh(&self, ttl_secs: u64, now: Instant) -> bool {
+        now.saturating_duration_since(self.fetched_at) < Duration::from_secs(ttl_secs)
     }
 
     fn backoff_secs(&self) -> u64 {
@@ -36,34 +166,98 @@ impl CachedAux {
         base.min(300)
     }
 
-    fn can_retry(&self) -> bool {
+    fn can_retry(&self, now: Instant) -> bool {
         match self.last_failure {
             None => true,
-            Some(last) => last.elapsed() >= Duration::from_secs(self.backoff_secs()),
+            Some(last) => {
+                now.saturating_duration_since(last) >= Duration::from_secs(self.backoff_secs())
+            }
         }
     }
 
-    fn record_failure(&mut self) {
+    fn record_failure(&mut self, now: Instant) {
         self.fetch_failures = self.fetch_failures.saturating_add(1);
-        self.last_failure = Some(Instant::now());
+        self.last_failure = Some(now);
     }
 
-    fn record_success(&mut self, data: MarketAux) {
+    fn record_success(&mut self, data: T, fetch_ts: u64, now: Instant) {
         self.data = data;
-        self.fetched_at = Instant::now();
+        self.fetch_ts = fetch_ts;
+        self.fetched_at = now;
         self.fetch_failures = 0;
         self.last_failure = None;
     }
 }
 
+/// Symbol -> cached value of one aux component.
+type ComponentCache<T> = Mutex<HashMap<String, CachedAux<T>>>;
+
+/// `symbol`'s cached component as `(value, fetch_ts)`, awaiting `fetch` only
+/// once the entry has outlived `ttl_secs`. A failed refetch serves the
+/// expired value (its `fetch_ts` still shows the age) and backs off before
+/// trying again; None when there has never been a successful fetch.
+async fn cached<T, F>(
+    cache: &ComponentCache<T>,
+    symbol: &str,
+    ttl_secs: u64,
+    now: Instant,
+    now_ts: u64,
+    fetch: F,
+) -> Option<(T, u64)>
+where
+    T: Copy,
+    F: Future<Output = Result<T>>,
+{
+    {
+        let cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(c) = cache.get(symbol) {
+            if c.is_fresh(ttl_secs, now) || !c.can_retry(now) {
+                return Some((c.data, c.fetch_ts));
+            }
+        }
+    }
+    let result = fetch.await;
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    match result {
+        Ok(data) => {
+            cache
+                .entry(symbol.to_string())
+                .and_modify(|c| c.record_success(data, now_ts, now))
+                .or_insert_with(|| CachedAux::new(data, now_ts, now));
+            Some((data, now_ts))
+        }
+        Err(_) => cache.get_mut(symbol).map(|c| {
+            c.record_failure(now);
+            (c.data, c.fetch_ts)
+        }),
+    }
+}
+
+/// Per-component caches, each keyed by symbol.
+#[derive(Default)]
+struct AuxCaches {
+    funding: ComponentCache<f64>,
+    borrow: ComponentCache<f64>,
+    stable: ComponentCache<f64>,
+    premium: ComponentCache<f64>,
+    /// Last REST liquidation poll; the events themselves live in the window
+    liquidations: ComponentCache<()>,
+}
+
 /// Aggregates auxiliary market data from multiple sources
 pub struct AuxDataFetcher {
     client: Client,
     liquidation_window: Arc<Mutex<LiquidationWindow>>,
-    /// Per-symbol cache with TTL and backoff
-    cache: Arc<Mutex<HashMap<String, CachedAux>>>,
-    /// TTL for cached data in seconds
-    cache_ttl_secs: u64,
+    /// Per-component, per-symbol caches with TTL and backoff
+    caches: AuxCaches,
+    ttls: AuxTtls,
+    /// Venue whose derivatives market supplies funding/borrow
+    venue: ExchangeKind,
+    /// Scale the liquidation score is reported on
+    liq_source: LiqScoreSource,
+    liq_baseline_bars: usize,
+    /// Per-symbol raw liquidation score history, for `Normalized`
+    liq_baselines: Mutex<HashMap<String, LiqBaseline>>,
 }
 
 /// Rolling window of recent liquidations for score calculation
@@ -124,6 +318,65 @@ impl LiquidationWindow {
     }
 }
 
+/// Fewest baseline samples before a normalized liquidation score is given.
+pub const LIQ_BASELINE_MIN: usize = 20;
+
+/// Normalized liquidation scores are clamped to this many standard
+/// deviations, so a burst after a perfectly quiet baseline stays finite.
+pub const LIQ_Z_CAP: f64 = 10.0;
+
+/// Rolling window of raw liquidation scores for one symbol, against which
+/// `LiqScoreSource::Normalized` measures each new score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiqBaseline {
+    window: VecDeque<f64>,
+    cap: usize,
+}
+
+impl LiqBaseline {
+    pub fn new(cap: usize) -> Self {
+        Self {
+            window: VecDeque::with_capacity(cap),
+            cap: cap.max(1),
+        }
+    }
+
+    /// `raw` on the scale `source` asks for, then add it to the baseline.
+    /// The z-score is taken against the scores before this one and is 0
+    /// until `LIQ_BASELINE_MIN` of them have been seen.
+    pub fn score(&mut self, source: LiqScoreSource, raw: f64) -> f64 {
+        let scored = match source {
+            LiqScoreSource::Raw => raw,
+            LiqScoreSource::Normalized => self.z(raw),
+        };
+        if raw.is_finite() {
+            if self.window.len() == self.cap {
+                self.window.pop_front();
+            }
+            self.window.push_back(raw);
+        }
+        scored
+    }
+
+    fn z(&self, raw: f64) -> f64 {
+        let n = self.window.len();
+        if n < LIQ_BASELINE_MIN || !raw.is_finite() {
+            return 0.0;
+        }
+        let mean = self.window.iter().sum::<f64>() / n as f64;
+        let var = self.window.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        let std = var.sqrt();
+        let z = if std > 0.0 {
+            (raw - mean) / std
+        } else if raw == mean {
+            0.0
+        } else {
+            (raw - mean).signum() * LIQ_Z_CAP
+        };
+        z.clamp(-LIQ_Z_CAP, LIQ_Z_CAP)
+    }
+}
+
 // Binance API response types
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)]
@@ -178,104 +431,222 @@ struct CoinGeckoPrice {
     usd: f64,
 }
 
+/// CoinGecko id of the stablecoin `symbol` is quoted in, or None when the
+/// quote asset is not a USD stablecoin (e.g. BTC or fiat USD). TUSD is left
+/// out: `XBTUSD` would read as XB/TUSD.
+fn quote_stablecoin(symbol: &str) -> Option<&'static str> {
+    const STABLES: [(&str, &str); 5] = [
+        ("FDUSD", "first-digital-usd"),
+        ("USDT", "tether"),
+        ("USDC", "usd-coin"),
+        ("BUSD", "binance-usd"),
+        ("DAI", "dai"),
+    ];
+    let symbol = symbol.to_uppercase();
+    STABLES
+        .iter()
+        .find(|(quote, _)| symbol.len() > quote.len() && symbol.ends_with(quote))
+        .map(|(_, id)| *id)
+}
+
+/// `(stable_depeg, has_depeg)` for a symbol. Only stable-quoted symbols carry a
+/// depeg measure; the futures premium widens it but never stands in for it.
+fn combine_depeg(symbol: &str, stable: Option<f64>, premium: Option<f64>) -> (f64, bool) {
+    if quote_stablecoin(symbol).is_none() {
+        return (0.0, false);
+    }
+    match stable {
+        Some(depeg) => {
+            let premium = premium.unwrap_or(0.0);
+            let combined = if depeg.abs() > premium.abs() {
+                depeg
+            } else {
+                premium
+            };
+            (combined, true)
+        }
+        None => (0.0, false),
+    }
+}
+
 #[derive(Deserialize, Debug)]
-struct CoinGeckoPrices {
-    tether: Option<CoinGeckoPrice>,
-    #[serde(rename = "usd-coin")]
-    usd_coin: Option<CoinGeckoPrice>,
+struct KrakenFuturesTickers {
+    tickers: Vec<KrakenFuturesTicker>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct KrakenFuturesTicker {
+    symbol: String,
+    funding_rate: Option<f64>,
+    mark_price: Option<f64>,
+}
+
+/// Kraken perpetual for a Binance-style symbol: BTCUSDT -> PF_XBTUSD.
+fn kraken_futures_symbol(symbol: &str) -> String {
+    let symbol = symbol.to_uppercase();
+    let base = ["USDT", "USDC", "USD"]
+        .iter()
+        .find_map(|q| symbol.strip_suffix(q))
+        .unwrap_or(&symbol);
+    let base = if base == "BTC" { "XBT" } else { base };
+    format!("PF_{}USD", base)
+}
+
+/// Funding for `symbol` as a fraction per 8h, matching Binance's convention.
+/// Kraken quotes an absolute hourly rate per contract, so it is divided by
+/// the mark price and scaled by eight hours.
+fn kraken_funding_rate(data: &KrakenFuturesTickers, symbol: &str) -> Option<f64> {
+    let wanted = kraken_futures_symbol(symbol);
+    let ticker = data
+        .tickers
+        .iter()
+        .find(|t| t.symbol.eq_ignore_ascii_case(&wanted))?;
+    let mark = ticker.mark_price.filter(|m| *m > 0.0)?;
+    ticker.funding_rate.map(|f| f / mark * 8.0)
+}
+
+/// `fetch_ts` of the oldest component present; `now_ts` when none is.
+fn oldest_fetch_ts(components: &[Option<(f64, u64)>], now_ts: u64) -> u64 {
+    components
+        .iter()
+        .flatten()
+        .map(|c| c.1)
+        .min()
+        .unwrap_or(now_ts)
+}
+
+/// Kraken aux snapshot. Kraken has no margin borrow or liquidation feed here,
+/// so those flags are always false.
+fn kraken_aux(funding: Option<f64>, depeg: (f64, bool), now_ts: u64) -> MarketAux {
+    MarketAux {
+        funding_rate: funding.unwrap_or(0.0),
+        borrow_rate: 0.0,
+        liquidation_score: 0.0,
+        stable_depeg: depeg.0,
+        fetch_ts: now_ts,
+        has_funding: funding.is_some(),
+        has_borrow: false,
+        has_liquidations: false,
+        has_depeg: depeg.1,
+        next_funding_ts: 0,
+    }
 }
 
 impl AuxDataFetcher {
     pub fn new() -> Self {
-        Self::with_ttl(60) // Default 60s TTL
+        Self::with_ttls(AuxTtls::default())
     }
 
-    pub fn with_ttl(cache_ttl_secs: u64) -> Self {
+    pub fn with_ttls(ttls: AuxTtls) -> Self {
         Self {
             client: Client::builder()
                 .timeout(Duration::from_secs(10))
                 .build()
                 .unwrap_or_else(|_| Client::new()),
             liquidation_window: Arc::new(Mutex::new(LiquidationWindow::new(300))), // 5 min window
-            cache: Arc::new(Mutex::new(HashMap::new())),
-            cache_ttl_secs,
+            caches: AuxCaches::default(),
+            ttls,
+            venue: ExchangeKind::Binance,
+            liq_source: LiqScoreSource::Raw,
+            liq_baseline_bars: 288,
+            liq_baselines: Mutex::new(HashMap::new()),
         }
     }
 
-    /// Fetch with caching and backoff
-    pub async fn fetch(&self, symbol: &str) -> Result<MarketAux> {
-        // Check cache first
-        let should_fetch = {
-            let cache = self
-                .cache
-                .lock()
-                .map_err(|_| anyhow::anyhow!("aux cache lock poisoned"))?;
-            match cache.get(symbol) {
-                Some(cached) if cached.is_fresh(self.cache_ttl_secs) => {
-                    return Ok(cached.data);
-                }
-                Some(cached) if !cached.can_retry() => {
-                    return Ok(cached.data);
-                }
-                _ => true,
-            }
-        };
-
-        if should_fetch {
-            match self.fetch_fresh(symbol).await {
-                Ok(data) => {
-                    let mut cache = self
-                        .cache
-                        .lock()
-                        .map_err(|_| anyhow::anyhow!("aux cache lock poisoned"))?;
-                    cache
-                        .entry(symbol.to_string())
-                        .and_modify(|c| c.record_success(data))
-                        .or_insert_with(|| CachedAux::new(data));
-                    return Ok(data);
-                }
-                Err(e) => {
-                    if let Ok(mut cache) = self.cache.lock() {
-                        if let Some(cached) = cache.get_mut(symbol) {
-                            cached.record_failure();
-                            return Ok(cached.data);
-                        }
-                    }
-                    return Err(e);
-                }
-            }
-        }
+    /// Report the liquidation score on `source`'s scale, normalizing against
+    /// the last `baseline_bars` raw scores per symbol.
+    pub fn with_liq_score_source(mut self, source: LiqScoreSource, baseline_bars: usize) -> Self {
+        self.liq_source = source;
+        self.liq_baseline_bars = baseline_bars;
+        self
+    }
 
-        Err(anyhow::anyhow!("cache miss with no retry allowed"))
+    /// Source funding/borrow from `venue` instead of Binance.
+    pub fn with_venue(mut self, venue: ExchangeKind) -> Self {
+        self.venue = venue;
+        self
     }
 
-    /// Fresh fetch without cache
-    async fn fetch_fresh(&self, symbol: &str) -> Result<MarketAux> {
+    /// Aux snapshot for `symbol`. Each component comes from its own cache
+    /// and is only refetched once its TTL lapses; the snapshot's `fetch_ts`
+    /// is that of the oldest component it carries.
+    pub async fn fetch(&self, symbol: &str) -> Result<MarketAux> {
+        let now = Instant::now();
         let now_ts = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .map(|d| d.as_secs())
             .unwrap_or(0);
+        let (c, t) = (&self.caches, &self.ttls);
+
+        if let ExchangeKind::Kraken = self.venue {
+            let (funding, stable) = tokio::join!(
+                cached(
+                    &c.funding,
+                    symbol,
+                    t.funding_secs,
+                    now,
+                    now_ts,
+                    self.fetch_kraken_funding_rate(symbol)
+                ),
+                cached(
+                    &c.stable,
+                    symbol,
+                    t.depeg_secs,
+                    now,
+                    now_ts,
+                    self.fetch_stablecoin_depeg(symbol)
+                ),
+            );
+            let fetch_ts = oldest_fetch_ts(&[funding, stable], now_ts);
+            return Ok(kraken_aux(
+                funding.map(|f| f.0),
+                combine_depeg(symbol, stable.map(|s| s.0), None),
+                fetch_ts,
+            ));
+        }
 
-        // Fetch all data concurrently
-        let (funding, borrow, premium, depeg) = tokio::join!(
-            self.fetch_funding_rate(symbol),
-            self.fetch_borrow_rate(symbol),
-            self.fetch_premium_index(symbol),
-            self.fetch_stablecoin_depeg(),
+        // Fetch whatever has expired, concurrently
+        let (funding, borrow, premium, stable) = tokio::join!(
+            cached(
+                &c.funding,
+                symbol,
+                t.funding_secs,
+                now,
+                now_ts,
+                self.fetch_funding_rate(symbol)
+            ),
+            cached(
+                &c.borrow,
+                symbol,
+                t.borrow_secs,
+                now,
+                now_ts,
+                self.fetch_borrow_rate(symbol)
+            ),
+            cached(
+                &c.premium,
+                symbol,
+                t.depeg_secs,
+                now,
+                now_ts,
+                self.fetch_premium_index(symbol)
+            ),
+            cached(
+                &c.stable,
+                symbol,
+                t.depeg_secs,
+                now,
+                now_ts,
+                self.fetch_stablecoin_depeg(symbol)
+            ),
         );
 
         // Track which fields have real data vs defaults
-        let (funding_rate, has_funding) = match funding {
-            Ok(v) => (v, true),
-            Err(_) => (0.0, false),
-        };
-        let (borrow_rate, has_borrow) = match borrow {
-            Ok(v) => (v, true),
-            Err(_) => (0.0, false),
-        };
-        let premium_opt = premium.ok();
-        let depeg_opt = depeg.ok();
-        let has_depeg = premium_opt.is_some() || depeg_opt.is_some();
+        let (funding_rate, has_funding) = funding.map_or((0.0, false), |f| (f.0, true));
+        let (borrow_rate, has_borrow) = borrow.map_or((0.0, false), |b| (b.0, true));
+        let (stable_depeg, has_depeg) =
+            combine_depeg(symbol, stable.map(|s| s.0), premium.map(|p| p.0));
 
         // Calculate liquidation score from window
         let (liquidation_score, has_liquidations) = self
@@ -287,38 +658,39 @@ impl AuxDataFetcher {
                 (score, has_events)
             })
             .unwrap_or((0.0, false));
-
-        // Use premium deviation as additional depeg signal for futures
-        let premium_depeg = premium_opt.unwrap_or(0.0);
-        let stable_depeg = depeg_opt.unwrap_or(0.0);
-
-        // Combine depeg signals
-        let combined_depeg = if stable_depeg.abs() > premium_depeg.abs() {
-            stable_depeg
-        } else {
-            premium_depeg
-        };
+        let liquidation_score = self
+            .liq_baselines
+            .lock()
+            .map(|mut b| {
+                b.entry(symbol.to_string())
+                    .or_insert_with(|| LiqBaseline::new(self.liq_baseline_bars))
+                    .score(self.liq_source, liquidation_score)
+            })
+            .unwrap_or(liquidation_score);
 
         Ok(MarketAux {
             funding_rate,
             borrow_rate,
             liquidation_score,
-            stable_depeg: combined_depeg,
-            fetch_ts: now_ts,
+            stable_depeg,
+            fetch_ts: oldest_fetch_ts(&[funding, borrow, stable], now_ts),
             has_funding,
             has_borrow,
             has_liquidations,
             has_depeg,
+            next_funding_ts: 0,
         })
     }
 
     /// Fetch funding rate from Binance Futures
     async fn fetch_funding_rate(&self, symbol: &str) -> Result<f64> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
         let url = format!(
             "https://fapi.binance.com/fapi/v1/fundingRate?symbol={}&limit=1",
             symbol
         );
 
+        throttle::binance().acquire(1.0).await;
         let resp = self.client.get(&url).send().await?;
         let data: Vec<BinanceFundingRate> = resp.json().await?;
 
@@ -327,6 +699,18 @@ impl AuxDataFetcher {
             .ok_or_else(|| anyhow::anyhow!("no funding rate"))
     }
 
+    /// Fetch funding rate from the Kraken Futures perpetual for `symbol`
+    async fn fetch_kraken_funding_rate(&self, symbol: &str) -> Result<f64> {
+        let url = "https://futures.kraken.com/derivatives/api/v3/tickers";
+        let resp = self.client.get(url).send().await?;
+        if !resp.status().is_success() {
+            return Err(anyhow::anyhow!("kraken tickers unavailable"));
+        }
+        let data: KrakenFuturesTickers = resp.json().await?;
+        kraken_funding_rate(&data, symbol)
+            .ok_or_else(|| anyhow::anyhow!("no kraken funding for {}", symbol))
+    }
+
     /// Fetch borrow rate from Binance Margin API
     async fn fetch_borrow_rate(&self, symbol: &str) -> Result<f64> {
         // Extract base asset from pair (BTCUSDT -> BTC)
@@ -339,6 +723,7 @@ impl AuxDataFetcher {
         // Cross margin data endpoint (public)
         let url = "https://api.binance.com/sapi/v1/margin/crossMarginData";
 
+        throttle::binance().acquire(1.0).await;
         let resp = self.client.get(url).send().await?;
 
         if !resp.status().is_success() {
@@ -365,11 +750,13 @@ impl AuxDataFetcher {
 
     /// Fetch premium index (mark - index price deviation)
     async fn fetch_premium_index(&self, symbol: &str) -> Result<f64> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
         let url = format!(
             "https://fapi.binance.com/fapi/v1/premiumIndex?symbol={}",
             symbol
         );
 
+        throttle::binance().acquire(1.0).await;
         let resp = self.client.get(&url).send().await?;
         let data: BinancePremiumIndex = resp.json().await?;
 
@@ -383,34 +770,26 @@ impl AuxDataFetcher {
         }
     }
 
-    /// Fetch stablecoin prices from CoinGecko to detect depeg
-    async fn fetch_stablecoin_depeg(&self) -> Result<f64> {
-        let url =
-            "https://api.coingecko.com/api/v3/simple/price?ids=tether,usd-coin&vs_currencies=usd";
+    /// Depeg of `symbol`'s quote stablecoin from $1, via CoinGecko
+    async fn fetch_stablecoin_depeg(&self, symbol: &str) -> Result<f64> {
+        let id = quote_stablecoin(symbol)
+            .ok_or_else(|| anyhow::anyhow!("{} is not stable-quoted", symbol))?;
+        let url = format!(
+            "https://api.coingecko.com/api/v3/simple/price?ids={}&vs_currencies=usd",
+            id
+        );
 
-        let resp = self.client.get(url).send().await?;
+        let resp = self.client.get(&url).send().await?;
 
         if !resp.status().is_success() {
             return Err(anyhow::anyhow!("depeg data unavailable"));
         }
 
-        let prices: CoinGeckoPrices = resp.json().await?;
-
-        // Calculate max depeg from either USDT or USDC
-        let usdt_depeg = prices.tether.map(|p| p.usd - 1.0);
-        let usdc_depeg = prices.usd_coin.map(|p| p.usd - 1.0);
-
-        match (usdt_depeg, usdc_depeg) {
-            (None, None) => Err(anyhow::anyhow!("missing stablecoin prices")),
-            (Some(v), None) | (None, Some(v)) => Ok(v),
-            (Some(a), Some(b)) => {
-                if a.abs() > b.abs() {
-                    Ok(a)
-                } else {
-                    Ok(b)
-                }
-            }
-        }
+        let prices: HashMap<String, CoinGeckoPrice> = resp.json().await?;
+        prices
+            .get(id)
+            .map(|p| p.usd - 1.0)
+            .ok_or_else(|| anyhow::anyhow!("missing {} price", id))
     }
 
     /// Process a liquidation event (call this from websocket handler)
@@ -420,13 +799,33 @@ impl AuxDataFetcher {
         }
     }
 
-    /// Fetch recent liquidations via REST (fallback when no websocket)
+    /// Fetch recent liquidations via REST (fallback when no websocket), at
+    /// most once per liquidations TTL.
     pub async fn fetch_recent_liquidations(&self, symbol: &str) -> Result<()> {
+        let now_ts = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        cached(
+            &self.caches.liquidations,
+            symbol,
+            self.ttls.liquidations_secs,
+            Instant::now(),
+            now_ts,
+            self.poll_liquidations(symbol),
+        )
+        .await;
+        Ok(())
+    }
+
+    async fn poll_liquidations(&self, symbol: &str) -> Result<()> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
         let url = format!(
             "https://fapi.binance.com/fapi/v1/allForceOrders?symbol={}&limit=100",
             symbol
         );
 
+        throttle::binance().acquire(1.0).await;
         let resp = self.client.get(&url).send().await?;
 
         if !resp.status().is_success() {
@@ -465,6 +864,127 @@ impl Default for AuxDataFetcher {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_funding_history_keyed_and_bounded() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("funding/history.jsonl");
+        let path = path.to_str().unwrap();
+        let point = |ts: u64, symbol: &str, rate: f64, funding_ts: u64| FundingPoint {
+            ts,
+            symbol: symbol.to_string(),
+            rate,
+            funding_ts,
+        };
+        // Re-fetching the same settlement updates it rather than adding a line
+        record_funding_point(path, &point(100, "BTCUSDT", 0.0001, 28_800), 10).unwrap();
+        record_funding_point(path, &point(400, "BTCUSDT", 0.0002, 28_800), 10).unwrap();
+        record_funding_point(path, &point(400, "ETHUSDT", 0.0003, 28_800), 10).unwrap();
+        let points = parse_funding_history(&std::fs::read_to_string(path).unwrap());
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].rate, 0.0002);
+        assert_eq!(points[1].symbol, "ETHUSDT");
+
+        // The file never holds more than the cap; the newest settlements win
+        for i in 2..20u64 {
+            record_funding_point(path, &point(i * 28_800, "BTCUSDT", 0.0, i * 28_800), 5).unwrap();
+        }
+        let content = std::fs::read_to_string(path).unwrap();
+        assert_eq!(content.lines().count(), 5);
+        let points = parse_funding_history(&content);
+        assert_eq!(points.last().unwrap().funding_ts, 19 * 28_800);
+    }
+
+    #[test]
+    fn test_normalized_liq_score_is_regime_relative() {
+        // Same shape of activity on two venues whose raw scores differ 10x
+        let run = |scale: f64, source: LiqScoreSource| {
+            let mut baseline = LiqBaseline::new(100);
+            for i in 0..40 {
+                let quiet = if i % 2 == 0 { 0.9 } else { 1.1 };
+                let warm = baseline.score(source, quiet * scale);
+                if source == LiqScoreSource::Normalized && i < LIQ_BASELINE_MIN {
+                    assert_eq!(warm, 0.0, "no score before the baseline fills");
+                }
+            }
+            baseline.score(source, 1.3 * scale)
+        };
+
+        // Raw is absolute: the busier venue reads 10x higher for the same burst
+        let (small, large) = (
+            run(1.0, LiqScoreSource::Raw),
+            run(10.0, LiqScoreSource::Raw),
+        );
+        assert!((small - 1.3).abs() < 1e-12);
+        assert!((large - 13.0).abs() < 1e-12);
+
+        // Normalized is relative to each venue's own regime: the same burst
+        // reads the same z on both
+        let (small, large) = (
+            run(1.0, LiqScoreSource::Normalized),
+            run(10.0, LiqScoreSource::Normalized),
+        );
+        assert!((small - large).abs() < 1e-9, "{} vs {}", small, large);
+        assert!(small > 2.5 && small < 3.5, "z {}", small);
+
+        // A burst after a perfectly quiet baseline is capped, not infinite
+        let mut baseline = LiqBaseline::new(100);
+        for _ in 0..LIQ_BASELINE_MIN {
+            baseline.score(LiqScoreSource::Normalized, 0.0);
+        }
+        assert_eq!(baseline.score(LiqScoreSource::Normalized, 4.0), LIQ_Z_CAP);
+    }
+
+    #[test]
+    fn test_depeg_only_for_stable_quotes() {
+        assert_eq!(quote_stablecoin("BTCUSDT"), Some("tether"));
+        assert_eq!(quote_stablecoin("ethusdc"), Some("usd-coin"));
+        assert_eq!(quote_stablecoin("BTCFDUSD"), Some("first-digital-usd"));
+        assert_eq!(quote_stablecoin("BTCBTC"), None);
+        assert_eq!(quote_stablecoin("XBTUSD"), None);
+        assert_eq!(quote_stablecoin("USDT"), None);
+
+        // USDT trading at 0.997 is a -0.3% depeg for BTCUSDT
+        let (depeg, has) = combine_depeg("BTCUSDT", Some(0.997 - 1.0), Some(0.0005));
+        assert!(has);
+        assert!((depeg + 0.003).abs() < 1e-12);
+
+        // A non-stable quote never reports depeg, whatever data came back
+        assert_eq!(
+            combine_depeg("BTCBTC", Some(-0.01), Some(0.002)),
+            (0.0, false)
+        );
+        // Premium alone is not a depeg measure
+        assert_eq!(combine_depeg("BTCUSDT", None, Some(0.002)), (0.0, false));
+    }
+
+    #[test]
+    fn test_kraken_funding_maps_to_aux() {
+        let body = r#"{"result":"success","tickers":[
+            {"symbol":"PF_ETHUSD","fundingRate":0.05,"markPrice":2500.0},
+            {"symbol":"PF_XBTUSD","fundingRate":0.625,"markPrice":50000.0,"indexPrice":49990.0},
+            {"symbol":"FI_XBTUSD_260925","markPrice":50500.0}
+        ]}"#;
+        let data: KrakenFuturesTickers = serde_json::from_str(body).unwrap();
+        assert_eq!(kraken_futures_symbol("BTCUSDT"), "PF_XBTUSD");
+        assert_eq!(kraken_futures_symbol("ethusd"), "PF_ETHUSD");
+
+        // 0.625 / 50000 per hour = 0.00125%/h -> 0.0001 per 8h
+        let funding = kraken_funding_rate(&data, "BTCUSDT");
+        assert!((funding.unwrap() - 0.0001).abs() < 1e-12);
+        let aux = kraken_aux(funding, (0.0, false), 1_000);
+        assert!(aux.has_funding);
+        assert!(!aux.has_borrow);
+        assert!(!aux.has_liquidations);
+        assert_eq!(aux.borrow_rate, 0.0);
+        assert_eq!(aux.fetch_ts, 1_000);
+
+        // No perpetual listed: funding is missing, not zero
+        let missing = kraken_funding_rate(&data, "SOLUSDT");
+        assert_eq!(missing, None);
+        let aux = kraken_aux(missing, (0.0, false), 1_000);
+        assert!(!aux.has_funding);
+    }
+
     #[test]
     fn test_liquidation_window() {
         let mut window = LiquidationWindow::new(60);
@@ -486,59 +1006,129 @@ mod tests {
 
     #[test]
     fn test_cached_aux_freshness() {
-        let data = MarketAux::default();
-        let cached = CachedAux::new(data);
+        let t0 = Instant::now();
+        let cached = CachedAux::new(MarketAux::default(), 1_000, t0);
 
-        // Immediately fresh
-        assert!(cached.is_fresh(60));
-
-        // Would be stale after TTL (can't easily test without sleep)
+        assert!(cached.is_fresh(60, t0));
+        assert!(cached.is_fresh(60, t0 + Duration::from_secs(59)));
+        assert!(!cached.is_fresh(60, t0 + Duration::from_secs(60)));
     }
 
     #[test]
     fn test_cached_aux_backoff() {
-        let data = MarketAux::default();
-        let mut cached = CachedAux::new(data);
+        let t0 = Instant::now();
+        let mut cached = CachedAux::new(MarketAux::default(), 1_000, t0);
 
         // Initially can retry
-        assert!(cached.can_retry());
+        assert!(cached.can_retry(t0));
 
         // After failure, backoff kicks in
-        cached.record_failure();
+        cached.record_failure(t0);
         assert_eq!(cached.backoff_secs(), 2); // 2^1
+        assert!(!cached.can_retry(t0 + Duration::from_secs(1)));
+        assert!(cached.can_retry(t0 + Duration::from_secs(2)));
 
-        cached.record_failure();
+        cached.record_failure(t0);
         assert_eq!(cached.backoff_secs(), 4); // 2^2
 
-        cached.record_failure();
+        cached.record_failure(t0);
         assert_eq!(cached.backoff_secs(), 8); // 2^3
 
         // Caps at 300s
         for _ in 0..10 {
-            cached.record_failure();
+            cached.record_failure(t0);
         }
         assert_eq!(cached.backoff_secs(), 256); // 2^8 = 256
     }
 
     #[test]
     fn test_cached_aux_success_resets() {
-        let data = MarketAux::default();
-        let mut cached = CachedAux::new(data);
+        let t0 = Instant::now();
+        let mut cached = CachedAux::new(MarketAux::default(), 1_000, t0);
 
         // Simulate failures
-        cached.record_failure();
-        cached.record_failure();
+        cached.record_failure(t0);
+        cached.record_failure(t0);
         assert_eq!(cached.fetch_failures, 2);
 
         // Success resets
-        cached.record_success(data);
+        cached.record_success(MarketAux::default(), 1_060, t0);
         assert_eq!(cached.fetch_failures, 0);
         assert!(cached.last_failure.is_none());
+        assert_eq!(cached.fetch_ts, 1_060);
     }
 
     #[test]
     fn test_fetcher_with_custom_ttl() {
-        let fetcher = AuxDataFetcher::with_ttl(120);
-        assert_eq!(fetcher.cache_ttl_secs, 120);
+        let ttls = AuxTtls {
+            funding_secs: 120,
+            ..AuxTtls::default()
+        };
+        let fetcher = AuxDataFetcher::with_ttls(ttls);
+        assert_eq!(fetcher.ttls.funding_secs, 120);
+        let defaults = AuxTtls::default();
+        assert!(defaults.funding_secs > defaults.liquidations_secs);
+    }
+
+    #[tokio::test]
+    async fn test_component_served_from_cache_until_ttl() {
+        let cache: ComponentCache<f64> = Mutex::new(HashMap::new());
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let fetch = |v: f64| {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move { Ok(v) }
+        };
+        let t0 = Instant::now();
+
+        let first = cached(&cache, "BTCUSDT", 300, t0, 1_000, fetch(0.0001)).await;
+        assert_eq!(first, Some((0.0001, 1_000)));
+
+        // Within the TTL: the cached value with its original fetch_ts, no call
+        let t1 = t0 + Duration::from_secs(299);
+        let hit = cached(&cache, "BTCUSDT", 300, t1, 1_299, async { Ok(9.9) }).await;
+        assert_eq!(hit, Some((0.0001, 1_000)));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        // Another symbol has its own entry
+        let eth = cached(&cache, "ETHUSDT", 300, t1, 1_299, fetch(0.0003)).await;
+        assert_eq!(eth, Some((0.0003, 1_299)));
+
+        // Past the TTL: refetched and restamped
+        let t2 = t0 + Duration::from_secs(300);
+        let refetched = cached(&cache, "BTCUSDT", 300, t2, 1_300, fetch(0.0002)).await;
+        assert_eq!(refetched, Some((0.0002, 1_300)));
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_failed_refetch_serves_expired_value_and_backs_off() {
+        let cache: ComponentCache<f64> = Mutex::new(HashMap::new());
+        let t0 = Instant::now();
+        let fail = || async { Err::<f64, _>(anyhow::anyhow!("down")) };
+
+        assert_eq!(cached(&cache, "BTCUSDT", 10, t0, 1_000, fail()).await, None);
+        cached(&cache, "BTCUSDT", 10, t0, 1_000, async { Ok(0.5) }).await;
+
+        // Expired and the venue is down: old value, old timestamp
+        let t1 = t0 + Duration::from_secs(20);
+        let stale = cached(&cache, "BTCUSDT", 10, t1, 1_020, fail()).await;
+        assert_eq!(stale, Some((0.5, 1_000)));
+
+        // Inside the 2s backoff a working fetch is not even tried
+        let t2 = t1 + Duration::from_secs(1);
+        let held = cached(&cache, "BTCUSDT", 10, t2, 1_021, async { Ok(0.7) }).await;
+        assert_eq!(held, Some((0.5, 1_000)));
+        let t3 = t1 + Duration::from_secs(2);
+        let fresh = cached(&cache, "BTCUSDT", 10, t3, 1_022, async { Ok(0.7) }).await;
+        assert_eq!(fresh, Some((0.7, 1_022)));
+    }
+
+    #[test]
+    fn test_snapshot_carries_oldest_component_ts() {
+        assert_eq!(
+            oldest_fetch_ts(&[Some((0.1, 900)), None, Some((0.0, 1_000))], 1_100),
+            900
+        );
+        assert_eq!(oldest_fetch_ts(&[None, None], 1_100), 1_100);
     }
 }
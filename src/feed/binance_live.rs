@@ -6,6 +6,9 @@ use tokio::sync::mpsc;
 use tokio::time::{sleep, Duration};
 
 use crate::exchange::signing::sign_binance;
+use crate::exchange::symbol::normalize;
+use crate::exchange::ExchangeKind;
+use crate::logging::{json_log_at, obj, v_num, v_str, Level};
 
 #[derive(Debug, Clone)]
 pub struct FillEvent {
@@ -75,15 +78,118 @@ struct Trade {
     is_buyer: bool,
 }
 
+#[derive(Debug, Deserialize)]
+struct WsPublicTrade {
+    #[serde(rename = "p")]
+    price: String,
+    #[serde(rename = "q")]
+    qty: String,
+    #[serde(rename = "T")]
+    trade_time: u64,
+}
+
+/// Stream public trades for `symbol` into `sender` for candle aggregation.
+/// Returns how many trades the session delivered once the venue closes it.
+pub async fn start_trade_stream(
+    ws_base: String,
+    symbol: String,
+    sender: mpsc::Sender<(String, crate::feed::aggregator::Trade)>,
+) -> Result<usize> {
+    let ws_url = format!("{}/ws/{}@trade", ws_base, symbol.to_lowercase());
+    let (ws, _) = tokio_tungstenite::connect_async(ws_url).await?;
+    let (mut _write, mut read) = ws.split();
+
+    let mut delivered = 0;
+    while let Some(msg) = read.next().await {
+        let Ok(msg) = msg else { continue };
+        let Ok(text) = msg.into_text() else { continue };
+        if let Ok(t) = serde_json::from_str::<WsPublicTrade>(&text) {
+            let trade = crate::feed::aggregator::Trade {
+                ts: t.trade_time / 1000,
+                price: t.price.parse().unwrap_or(0.0),
+                qty: t.qty.parse().unwrap_or(0.0),
+            };
+            if sender.send((symbol.clone(), trade)).await.is_err() {
+                break;
+            }
+            delivered += 1;
+        }
+    }
+
+    Ok(delivered)
+}
+
+/// Longest wait between trade-stream reconnects.
+pub const TRADE_STREAM_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Wait before the next connect after `failures` sessions in a row that
+/// delivered nothing: `base` doubling per failure, capped.
+pub fn trade_stream_backoff(base: Duration, failures: u32) -> Duration {
+    base.saturating_mul(1u32 << failures.min(16))
+        .min(TRADE_STREAM_MAX_BACKOFF)
+}
+
+/// Keep a trade stream up: run `connect` (one session, returning trades
+/// delivered) and reconnect after the backoff whenever the session errors
+/// or the venue closes it. A session that delivered trades resets the
+/// backoff. Returns the number of reconnects once `sender` is closed.
+pub async fn supervise_trade_stream<F, Fut>(
+    symbol: &str,
+    sender: &mpsc::Sender<(String, crate::feed::aggregator::Trade)>,
+    base: Duration,
+    mut connect: F,
+) -> u32
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<usize>>,
+{
+    let mut failures = 0;
+    let mut reconnects = 0;
+    loop {
+        let outcome = connect().await;
+        if sender.is_closed() {
+            return reconnects;
+        }
+        let error = match outcome {
+            Ok(delivered) if delivered > 0 => {
+                failures = 0;
+                "closed".to_string()
+            }
+            Ok(_) => {
+                failures += 1;
+                "closed".to_string()
+            }
+            Err(err) => {
+                failures += 1;
+                err.to_string()
+            }
+        };
+        let delay = trade_stream_backoff(base, failures);
+        json_log_at(
+            Level::Warn,
+            "trade_stream",
+            obj(&[
+                ("status", v_str("reconnect")),
+                ("symbol", v_str(symbol)),
+                ("error", v_str(&error)),
+                ("delay_ms", v_num(delay.as_millis() as f64)),
+            ]),
+        );
+        sleep(delay).await;
+        reconnects += 1;
+    }
+}
+
 pub async fn start_ws_listener(
     api_key: String,
     base: String,
+    ws_base: String,
     sender: mpsc::Sender<FillEvent>,
 ) -> Result<()> {
     let client = Client::new();
     let listen_key = get_listen_key(&client, &api_key, &base).await?;
 
-    let ws_url = format!("wss://stream.binance.com:9443/ws/{}", listen_key);
+    let ws_url = format!("{}/ws/{}", ws_base, listen_key);
     let (ws, _) = tokio_tungstenite::connect_async(ws_url).await?;
     let (mut _write, mut read) = ws.split();
 
@@ -206,6 +312,7 @@ async fn fetch_my_trades(
     symbol: &str,
     start_time_ms: u64,
 ) -> Result<Vec<Trade>> {
+    let symbol = normalize(symbol, ExchangeKind::Binance);
     let timestamp = chrono::Utc::now().timestamp_millis() as u64;
     let mut query = format!("symbol={}&timestamp={}&recvWindow=5000", symbol, timestamp);
     if start_time_ms > 0 {
@@ -229,3 +336,44 @@ async fn fetch_my_trades(
     let data: Vec<Trade> = resp.json().await?;
     Ok(data)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feed::aggregator::Trade;
+
+    #[test]
+    fn test_trade_stream_backoff_doubles_to_cap() {
+        let base = Duration::from_secs(1);
+        assert_eq!(trade_stream_backoff(base, 0), base);
+        assert_eq!(trade_stream_backoff(base, 3), Duration::from_secs(8));
+        assert_eq!(trade_stream_backoff(base, 10), TRADE_STREAM_MAX_BACKOFF);
+        assert_eq!(trade_stream_backoff(base, 40), TRADE_STREAM_MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn test_trade_stream_reconnects_until_receiver_drops() {
+        let (tx, rx) = mpsc::channel::<(String, Trade)>(8);
+        let mut rx = Some(rx);
+        let mut sessions = 0;
+        let reconnects = supervise_trade_stream("BTCUSDT", &tx, Duration::from_millis(1), || {
+            sessions += 1;
+            let n = sessions;
+            // The engine shuts down during the fifth session
+            if n == 5 {
+                rx.take();
+            }
+            async move {
+                match n {
+                    1 | 2 => Err(anyhow::anyhow!("connection refused")),
+                    3 => Ok(12),
+                    _ => Ok(0),
+                }
+            }
+        })
+        .await;
+        // Two failed connects, a dropped session and an idle one, each retried
+        assert_eq!(sessions, 5);
+        assert_eq!(reconnects, 4);
+    }
+}
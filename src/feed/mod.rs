@@ -1,3 +1,4 @@
+pub mod aggregator;
 pub mod aux_data;
 pub mod binance_live;
 pub mod events;
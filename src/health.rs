@@ -0,0 +1,187 @@
+//! Liveness/readiness report for orchestration.
+//!
+//! The loop mutates a shared [`HealthState`] once per iteration; with the
+//! `health` feature a small blocking HTTP server answers `/health` with the
+//! JSON [`HealthReport`], returning 503 when the bot should not be trusted.
+
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+
+use crate::reliability::circuit::CircuitState;
+
+/// What the loop last observed; read by the health server.
+#[derive(Debug, Clone)]
+pub struct HealthState {
+    /// Every strategy has `trading_halted` set.
+    pub halted: bool,
+    pub circuit: CircuitState,
+    /// Largest position drift seen by the most recent venue reconcile.
+    pub last_reconcile_drift: Option<f64>,
+    /// Wall-clock second at which the slowest symbol's candle last advanced.
+    pub last_candle_advance_ts: u64,
+    pub candle_granularity_ms: u64,
+    pub max_latency_ms: u64,
+    pub wal_path: String,
+}
+
+pub type SharedHealth = Arc<Mutex<HealthState>>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub halted: bool,
+    pub circuit_state: &'static str,
+    pub last_reconcile_drift: Option<f64>,
+    /// Milliseconds since the candle feed last advanced.
+    pub candle_age_ms: u64,
+    pub wal_bytes: u64,
+}
+
+impl HealthReport {
+    pub fn status_code(&self) -> u16 {
+        if self.healthy {
+            200
+        } else {
+            503
+        }
+    }
+}
+
+impl HealthState {
+    pub fn new(cfg: &crate::state::Config, now: u64) -> Self {
+        Self {
+            halted: false,
+            circuit: CircuitState::Closed,
+            last_reconcile_drift: None,
+            last_candle_advance_ts: now,
+            candle_granularity_ms: cfg.candle_granularity_ms,
+            max_latency_ms: cfg.max_latency_ms,
+            wal_path: cfg.wal_path.clone(),
+        }
+    }
+
+    pub fn shared(self) -> SharedHealth {
+        Arc::new(Mutex::new(self))
+    }
+
+    /// Unhealthy when halted or when the next candle is overdue by more than
+    /// `max_latency_ms` (same budget as the loop's staleness guard).
+    pub fn report(&self, now_ms: u64) -> HealthReport {
+        let candle_age_ms = now_ms.saturating_sub(self.last_candle_advance_ts * 1000);
+        let candle_stale = candle_age_ms > self.candle_granularity_ms + self.max_latency_ms;
+        let wal_bytes = std::fs::metadata(&self.wal_path)
+            .map(|m| m.len())
+            .unwrap_or(0);
+        HealthReport {
+            healthy: !self.halted && !candle_stale,
+            halted: self.halted,
+            circuit_state: match self.circuit {
+                CircuitState::Closed => "closed",
+                CircuitState::HalfOpen => "half_open",
+                CircuitState::Open => "open",
+            },
+            last_reconcile_drift: self.last_reconcile_drift,
+            candle_age_ms,
+            wal_bytes,
+        }
+    }
+}
+
+/// Serve `/health` on `addr` from a background thread.
+#[cfg(feature = "health")]
+pub fn serve(addr: &str, health: SharedHealth) -> std::io::Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind(addr)?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            let mut request_line = String::new();
+            if BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .is_err()
+            {
+                continue;
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+            let response = if path == "/health" {
+                let now_ms = std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0);
+                let report = health
+                    .lock()
+                    .map(|h| h.report(now_ms))
+                    .unwrap_or_else(|e| e.into_inner().report(now_ms));
+                let body = serde_json::to_string(&report).unwrap_or_default();
+                let status = match report.status_code() {
+                    200 => "200 OK",
+                    _ => "503 Service Unavailable",
+                };
+                format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state() -> HealthState {
+        HealthState {
+            halted: false,
+            circuit: CircuitState::Closed,
+            last_reconcile_drift: Some(0.0),
+            last_candle_advance_ts: 1_000,
+            candle_granularity_ms: 300_000,
+            max_latency_ms: 1_500,
+            wal_path: "/nonexistent/health-test.wal".to_string(),
+        }
+    }
+
+    #[test]
+    fn halted_reports_unhealthy() {
+        let now_ms = 1_010_000;
+        let ok = state().report(now_ms);
+        assert!(ok.healthy);
+        assert_eq!(ok.status_code(), 200);
+
+        let mut halted = state();
+        halted.halted = true;
+        let report = halted.report(now_ms);
+        assert!(!report.healthy);
+        assert!(report.halted);
+        assert_eq!(report.status_code(), 503);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["halted"], true);
+        assert_eq!(json["healthy"], false);
+        assert_eq!(json["circuit_state"], "closed");
+    }
+
+    #[test]
+    fn overdue_candle_reports_unhealthy() {
+        let s = state();
+        // One full bar plus the latency budget is still fine
+        assert!(s.report(1_000_000 + 300_000 + 1_500).healthy);
+        let late = s.report(1_000_000 + 300_000 + 1_501);
+        assert!(!late.healthy);
+        assert_eq!(late.candle_age_ms, 301_501);
+        assert_eq!(late.wal_bytes, 0);
+    }
+}
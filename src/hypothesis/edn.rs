@@ -0,0 +1,347 @@
+//! EDN writer and line-oriented readers for the hypothesis ledger.
+//!
+//! The writer emits one key per line in the same layout as the hand-edited
+//! ledger, so the line-oriented readers below recover every written value.
+
+use std::fmt::Write as _;
+
+use super::{Evidence, Hypothesis, HypothesisLedger, Stv, Supports};
+
+/// Serialize a ledger to the EDN shape `hypothesis_ledger.edn` uses.
+pub fn write_ledger(ledger: &HypothesisLedger) -> String {
+    let mut out = String::new();
+    out.push_str("{:meta\n");
+    let _ = writeln!(out, " {{:version {}", quote(&ledger.version));
+    let _ = writeln!(out, "  :updated {}}}", quote(&ledger.updated));
+    out.push('\n');
+    out.push_str(" :hypotheses\n\n");
+    if ledger.hypotheses.is_empty() {
+        out.push_str(" []}\n");
+        return out;
+    }
+    for (i, h) in ledger.hypotheses.iter().enumerate() {
+        let open = if i == 0 { " [" } else { "  " };
+        write_hypothesis(&mut out, open, h);
+        if i + 1 == ledger.hypotheses.len() {
+            out.push_str("]}\n");
+        } else {
+            out.push_str("\n\n");
+        }
+    }
+    out
+}
+
+fn write_hypothesis(out: &mut String, open: &str, h: &Hypothesis) {
+    let _ = writeln!(out, "{}{{:id {}", open, quote(&h.id));
+    let _ = writeln!(out, "   :name {}", quote(&h.name));
+    let _ = writeln!(out, "   :prior {}", stv(h.prior));
+    let _ = writeln!(out, "   :current {}", stv(h.current));
+    if h.evidence.is_empty() {
+        out.push_str("   :evidence []\n");
+    } else {
+        out.push_str("   :evidence\n");
+        for (i, e) in h.evidence.iter().enumerate() {
+            let open = if i == 0 { "   [" } else { "    " };
+            let close = if i + 1 == h.evidence.len() { "]" } else { "" };
+            write_evidence(out, open, e, close);
+        }
+    }
+    let _ = write!(out, "   :assessment {}}}", quote(&h.assessment));
+}
+
+fn write_evidence(out: &mut String, open: &str, e: &Evidence, close: &str) {
+    let _ = writeln!(
+        out,
+        "{}{{:dataset :{} :observation {}",
+        open,
+        e.dataset,
+        quote(&e.observation)
+    );
+    let p_value = e
+        .p_value
+        .map(|p| format!(" :p-value {:?}", p))
+        .unwrap_or_default();
+    let _ = writeln!(
+        out,
+        "     :supports? {} :weight {:?}{}}}{}",
+        supports(e.supports),
+        e.weight,
+        p_value,
+        close
+    );
+}
+
+fn stv(v: Stv) -> String {
+    format!("(stv {:?} {:?})", v.strength, v.confidence)
+}
+
+fn supports(s: Supports) -> &'static str {
+    match s {
+        Supports::Yes => "true",
+        Supports::No => "false",
+        Supports::Partial => ":partial",
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut q = String::with_capacity(s.len() + 2);
+    q.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => q.push_str("\\\""),
+            '\\' => q.push_str("\\\\"),
+            '\n' => q.push_str("\\n"),
+            c => q.push(c),
+        }
+    }
+    q.push('"');
+    q
+}
+
+/// Value of the first string literal after `key` on a line.
+pub fn extract_quoted(line: &str, key: &str) -> Option<String> {
+    let idx = line.find(key)?;
+    let rest = &line[idx + key.len()..];
+    let start = rest.find('"')? + 1;
+    let end = rest[start..].find('"')? + start;
+    Some(rest[start..end].to_string())
+}
+
+/// `(strength, confidence)` from the first `(stv s c)` form on a line.
+pub fn extract_stv(line: &str) -> Option<(f64, f64)> {
+    let idx = line.find("(stv")?;
+    let rest = &line[idx + 4..];
+    let end = rest.find(')')?;
+    let nums: Vec<f64> = rest[..end]
+        .split_whitespace()
+        .filter_map(|s| s.parse().ok())
+        .collect();
+    if nums.len() >= 2 {
+        Some((nums[0], nums[1]))
+    } else {
+        None
+    }
+}
+
+/// Whitespace-delimited token after `key` on a line, without closing
+/// brackets (`:weight 0.15}]` gives `0.15`).
+fn token_after<'a>(line: &'a str, key: &str) -> Option<&'a str> {
+    let idx = line.find(key)?;
+    let token = line[idx + key.len()..].split_whitespace().next()?;
+    Some(token.trim_end_matches(['}', ']']))
+}
+
+/// Read a ledger back with the line readers: ids, names, truth values,
+/// assessments and each evidence entry's dataset, observation, support,
+/// weight and p-value. Works on the hand-edited ledger as well as on
+/// `write_ledger` output; anything else (`:detail`, dataset metadata) is
+/// skipped.
+pub fn read_ledger(text: &str) -> HypothesisLedger {
+    let mut ledger = HypothesisLedger {
+        version: String::new(),
+        updated: String::new(),
+        hypotheses: Vec::new(),
+    };
+    for line in text.lines() {
+        let t = line.trim();
+        if t.starts_with(";;") {
+            continue;
+        }
+        if ledger.version.is_empty() {
+            if let Some(v) = extract_quoted(t, ":version") {
+                ledger.version = v;
+            }
+        }
+        if ledger.updated.is_empty() {
+            if let Some(v) = extract_quoted(t, ":updated") {
+                ledger.updated = v;
+            }
+        }
+        if t.contains("{:id \"H") {
+            ledger.hypotheses.push(Hypothesis {
+                id: extract_quoted(t, ":id").unwrap_or_default(),
+                name: String::new(),
+                prior: Stv::new(0.5, 0.0),
+                current: Stv::new(0.5, 0.0),
+                evidence: Vec::new(),
+                assessment: String::new(),
+            });
+            continue;
+        }
+        let Some(h) = ledger.hypotheses.last_mut() else {
+            continue;
+        };
+        if t.starts_with(":name") {
+            h.name = extract_quoted(t, ":name").unwrap_or_default();
+        } else if t.starts_with(":prior (stv") {
+            if let Some((s, c)) = extract_stv(t) {
+                h.prior = Stv::new(s, c);
+            }
+        } else if t.starts_with(":current (stv") {
+            if let Some((s, c)) = extract_stv(t) {
+                h.current = Stv::new(s, c);
+            }
+        } else if t.starts_with(":assessment") {
+            h.assessment = extract_quoted(t, ":assessment").unwrap_or_default();
+        }
+        if let Some(dataset) = token_after(t, ":dataset :") {
+            h.evidence.push(Evidence {
+                dataset: dataset.to_string(),
+                observation: extract_quoted(t, ":observation").unwrap_or_default(),
+                supports: Supports::Partial,
+                weight: 0.0,
+                p_value: None,
+            });
+        }
+        if let (Some(support), Some(e)) = (token_after(t, ":supports?"), h.evidence.last_mut()) {
+            e.supports = match support {
+                "true" => Supports::Yes,
+                "false" => Supports::No,
+                _ => Supports::Partial,
+            };
+            e.weight = token_after(t, ":weight")
+                .and_then(|w| w.parse().ok())
+                .unwrap_or(0.0);
+            e.p_value = token_after(t, ":p-value").and_then(|p| p.parse().ok());
+        }
+    }
+    ledger
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ledger() -> HypothesisLedger {
+        HypothesisLedger {
+            version: "1.2.0".to_string(),
+            updated: "2026-03-01".to_string(),
+            hypotheses: vec![
+                Hypothesis {
+                    id: "H001".to_string(),
+                    name: "Momentum strategies generate raw alpha".to_string(),
+                    prior: Stv::new(0.5, 0.0),
+                    current: Stv::new(0.42, 0.72),
+                    evidence: vec![
+                        Evidence {
+                            dataset: "markdown-1".to_string(),
+                            observation: "10/12 strategies negative raw PnL".to_string(),
+                            supports: Supports::Partial,
+                            weight: 0.2,
+                            p_value: None,
+                        },
+                        Evidence {
+                            dataset: "markup-1".to_string(),
+                            observation: "All 12 strategies negative raw PnL".to_string(),
+                            supports: Supports::No,
+                            weight: 0.15,
+                            p_value: Some(0.004),
+                        },
+                    ],
+                    assessment: "Improving but bull regimes still yield zero.".to_string(),
+                },
+                Hypothesis {
+                    id: "H003".to_string(),
+                    name: "Position sizing limits drawdown to <2%".to_string(),
+                    prior: Stv::new(0.5, 0.0),
+                    current: Stv::new(0.95, 0.84),
+                    evidence: Vec::new(),
+                    assessment: "Holds outside strong bull runs.".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn written_ledger_reads_back_through_line_parsers() {
+        let src = ledger();
+        let text = write_ledger(&src);
+
+        let mut ids = Vec::new();
+        let mut names = Vec::new();
+        let mut currents = Vec::new();
+        let mut priors = Vec::new();
+        let mut assessments = Vec::new();
+        for line in text.lines() {
+            let t = line.trim();
+            if t.contains("{:id \"H") {
+                ids.push(extract_quoted(t, ":id").unwrap());
+            }
+            if t.starts_with(":name") {
+                names.push(extract_quoted(t, ":name").unwrap());
+            }
+            if t.starts_with(":current (stv") {
+                currents.push(extract_stv(t).unwrap());
+            }
+            if t.starts_with(":prior (stv") {
+                priors.push(extract_stv(t).unwrap());
+            }
+            if t.starts_with(":assessment") {
+                assessments.push(extract_quoted(t, ":assessment").unwrap());
+            }
+        }
+
+        assert_eq!(ids, vec!["H001", "H003"]);
+        for (i, h) in src.hypotheses.iter().enumerate() {
+            assert_eq!(names[i], h.name);
+            assert_eq!(currents[i], (h.current.strength, h.current.confidence));
+            assert_eq!(priors[i], (h.prior.strength, h.prior.confidence));
+            assert_eq!(assessments[i], h.assessment);
+        }
+        assert!(text.contains(":supports? :partial :weight 0.2}"));
+        assert!(text.contains(":supports? false :weight 0.15 :p-value 0.004}]"));
+        assert!(text.contains(":evidence []"));
+    }
+
+    #[test]
+    fn written_ledger_reads_back_whole() {
+        let src = ledger();
+        assert_eq!(read_ledger(&write_ledger(&src)), src);
+    }
+
+    #[test]
+    fn hand_edited_ledger_reads_evidence() {
+        let text = r#"{:meta
+ {:version "1.1.0"
+  :updated "2026-02-19"
+  :datasets
+  [{:id :markdown-1   :file "btc_real_1h.csv"  :candles 1000}]}
+
+ :hypotheses
+
+ [{:id "H001"
+   :name "Momentum strategies generate raw alpha"
+   :prior (stv 0.5 0.0)
+   :current (stv 0.42 0.72)
+   :evidence
+   [{:dataset :markdown-1  :observation "churn-9 raw PnL +$4.29 (best)"
+     :supports? :partial  :weight 0.2
+     :detail {:best-raw-pnl 4.29 :total 12}}
+    {:dataset :markup-1    :observation "All 12 strategies negative raw PnL"
+     :supports? false      :weight 0.2 :p-value 0.01}]
+   :assessment "Improving."}]}
+"#;
+        let ledger = read_ledger(text);
+        assert_eq!(ledger.version, "1.1.0");
+        assert_eq!(ledger.hypotheses.len(), 1);
+        let h = &ledger.hypotheses[0];
+        assert_eq!(h.current, Stv::new(0.42, 0.72));
+        assert_eq!(h.evidence.len(), 2);
+        assert_eq!(h.evidence[0].dataset, "markdown-1");
+        assert_eq!(h.evidence[0].supports, Supports::Partial);
+        assert_eq!(h.evidence[0].p_value, None);
+        assert_eq!(h.evidence[1].supports, Supports::No);
+        assert_eq!(h.evidence[1].weight, 0.2);
+        assert_eq!(h.evidence[1].p_value, Some(0.01));
+        assert_eq!(h.assessment, "Improving.");
+    }
+
+    #[test]
+    fn brackets_balance() {
+        let text = write_ledger(&ledger());
+        let count = |c: char| text.chars().filter(|&x| x == c).count();
+        assert_eq!(count('{'), count('}'));
+        assert_eq!(count('['), count(']'));
+        assert_eq!(count('('), count(')'));
+    }
+}
@@ -0,0 +1,198 @@
+//! Structured form of `hypothesis_ledger.edn`.
+//!
+//! Truth values follow the ledger header: `(stv strength confidence)`, where
+//! strength is P(hypothesis | evidence) and confidence is the weight of evidence.
+
+pub mod edn;
+
+use std::collections::BTreeMap;
+
+use crate::backtest_traps::trap_12_multiple_testing::bonferroni_alpha;
+
+pub const DEFAULT_LEDGER_PATH: &str = "hypothesis_ledger.edn";
+
+/// Simple truth value: (strength, confidence).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stv {
+    pub strength: f64,
+    pub confidence: f64,
+}
+
+impl Stv {
+    pub fn new(strength: f64, confidence: f64) -> Self {
+        Self {
+            strength,
+            confidence,
+        }
+    }
+}
+
+/// How a piece of evidence bears on its hypothesis (`:supports?`).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Supports {
+    Yes,
+    No,
+    Partial,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evidence {
+    /// Dataset keyword without the leading colon (e.g. `markdown-1`, `all`).
+    pub dataset: String,
+    pub observation: String,
+    pub supports: Supports,
+    pub weight: f64,
+    /// p-value of the test behind the observation, when there was one.
+    pub p_value: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hypothesis {
+    pub id: String,
+    pub name: String,
+    pub prior: Stv,
+    pub current: Stv,
+    pub evidence: Vec<Evidence>,
+    pub assessment: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HypothesisLedger {
+    pub version: String,
+    pub updated: String,
+    pub hypotheses: Vec<Hypothesis>,
+}
+
+/// Where a hypothesis stands after multiple-testing correction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HypothesisStatus {
+    Supported,
+    Refuted,
+    Inconclusive,
+}
+
+impl Hypothesis {
+    /// Status from p-valued evidence, treating each dataset as one regime
+    /// test. The latest p-value per dataset stands for that regime, and each
+    /// is held to `bonferroni_alpha(alpha, regimes)`, so passing in one of
+    /// five regimes at p = 0.03 is not enough. Supported needs a significant
+    /// supporting regime and no significant contrary one; Refuted the
+    /// reverse. Evidence without a p-value only moves the truth value.
+    pub fn status(&self, alpha: f64) -> HypothesisStatus {
+        let mut latest: BTreeMap<&str, &Evidence> = BTreeMap::new();
+        for e in self.evidence.iter().filter(|e| e.p_value.is_some()) {
+            latest.insert(&e.dataset, e);
+        }
+        if latest.is_empty() {
+            return HypothesisStatus::Inconclusive;
+        }
+        let corrected = bonferroni_alpha(alpha, latest.len() as u32);
+        let significant = |want: Supports| {
+            latest
+                .values()
+                .any(|e| e.supports == want && e.p_value.is_some_and(|p| p <= corrected))
+        };
+        match (significant(Supports::Yes), significant(Supports::No)) {
+            (true, false) => HypothesisStatus::Supported,
+            (false, true) => HypothesisStatus::Refuted,
+            _ => HypothesisStatus::Inconclusive,
+        }
+    }
+}
+
+impl HypothesisLedger {
+    /// `Hypothesis::status` for every hypothesis, in ledger order.
+    pub fn update_hypothesis_status(&self, alpha: f64) -> Vec<(&str, HypothesisStatus)> {
+        self.hypotheses
+            .iter()
+            .map(|h| (h.id.as_str(), h.status(alpha)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ev(dataset: &str, supports: Supports, p_value: f64) -> Evidence {
+        Evidence {
+            dataset: dataset.to_string(),
+            observation: String::new(),
+            supports,
+            weight: 0.2,
+            p_value: Some(p_value),
+        }
+    }
+
+    fn hypothesis(evidence: Vec<Evidence>) -> Hypothesis {
+        Hypothesis {
+            id: "H001".to_string(),
+            name: "Momentum strategies generate raw alpha".to_string(),
+            prior: Stv::new(0.5, 0.0),
+            current: Stv::new(0.5, 0.0),
+            evidence,
+            assessment: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_single_regime_pass_among_many_is_not_supported() {
+        let regimes = [
+            "markdown-1",
+            "markup-1",
+            "accumulator-1",
+            "markdown-2",
+            "markdown-3",
+        ];
+        // p = 0.03 passes at 0.05 in one regime; the other four show nothing
+        let mut evidence = vec![ev(regimes[0], Supports::Yes, 0.03)];
+        for r in &regimes[1..] {
+            evidence.push(ev(r, Supports::Partial, 0.6));
+        }
+        let h = hypothesis(evidence.clone());
+        assert_eq!(h.status(0.05), HypothesisStatus::Inconclusive);
+
+        // Tested in that regime alone it would have passed
+        assert_eq!(
+            hypothesis(evidence[..1].to_vec()).status(0.05),
+            HypothesisStatus::Supported
+        );
+
+        // Strong enough to survive 0.05 / 5 = 0.01
+        evidence[0].p_value = Some(0.004);
+        assert_eq!(
+            hypothesis(evidence).status(0.05),
+            HypothesisStatus::Supported
+        );
+    }
+
+    #[test]
+    fn test_status_uses_latest_evidence_per_regime() {
+        let h = hypothesis(vec![
+            ev("markdown-1", Supports::Yes, 0.001),
+            ev("markup-1", Supports::No, 0.2),
+            // Retest in markdown-1 replaces the earlier pass
+            ev("markdown-1", Supports::No, 0.002),
+        ]);
+        assert_eq!(h.status(0.05), HypothesisStatus::Refuted);
+
+        let unvalued = Evidence {
+            p_value: None,
+            ..ev("markdown-1", Supports::Yes, 0.0)
+        };
+        assert_eq!(
+            hypothesis(vec![unvalued]).status(0.05),
+            HypothesisStatus::Inconclusive
+        );
+
+        let ledger = HypothesisLedger {
+            version: "1".to_string(),
+            updated: String::new(),
+            hypotheses: vec![h],
+        };
+        assert_eq!(
+            ledger.update_hypothesis_status(0.05),
+            vec![("H001", HypothesisStatus::Refuted)]
+        );
+    }
+}
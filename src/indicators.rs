@@ -2,6 +2,7 @@
 //!
 //! Each indicator maintains its own state and can be updated incrementally.
 
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
 // =============================================================================
@@ -9,7 +10,7 @@ use std::collections::VecDeque;
 // =============================================================================
 
 /// Exponential moving average with configurable alpha
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ema {
     pub value: f64,
     pub alpha: f64,
@@ -304,7 +305,7 @@ impl BollingerBands {
 }
 
 /// Average True Range (ATR)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Atr {
     period: usize,
     ema: Ema,
@@ -9,16 +9,21 @@ pub mod exchange;
 pub mod fault;
 pub mod features;
 pub mod feed;
+pub mod health;
+pub mod hypothesis;
 pub mod indicators;
 pub mod logging;
 pub mod metrics;
 pub mod narrative_detector;
+pub mod prometheus;
 pub mod regime;
 pub mod reliability;
 pub mod risk;
+pub mod signals;
 pub mod skeleton;
 pub mod state;
 pub mod storage;
+pub mod strategies;
 pub mod strategy;
 pub mod verify;
 pub mod walk_forward;
@@ -1,22 +1,52 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::time::SystemTime;
 
+use futures_util::stream::{FuturesUnordered, StreamExt};
+
+use crate::adapter::types::{OrderRequest, OrderResponse, OrderStatus, OrderType, Side};
 use crate::adapter::unified::UnifiedAdapter;
+use crate::drift_tracker::DriftSeverity;
+use crate::exchange::contract_spec::{ContractSpecs, ExchangeFilters};
+use crate::exchange::paper::PaperFillModel;
+use crate::exchange::{BookTop, Candle, Exchange, ExchangeKind};
 use crate::feed::binance_live::FillEvent;
-use crate::logging::{json_log, obj, params_hash, v_num, v_str};
+use crate::logging::{json_log, json_log_at, obj, params_hash, v_num, v_str, Level};
+use crate::metrics::{correlation_matrix, CORRELATION_FLAG};
 use crate::reconcile::binance::BinanceReconcileClient;
+use crate::reliability::audit::{AuditRecord, AuditStage, AuditTrail};
 use crate::reliability::circuit::CircuitBreaker;
-use crate::reliability::{state::OrderBook, wal::Wal};
+use crate::reliability::{
+    state::OrderBook,
+    wal::{RecoveryState, Wal},
+};
 use crate::state::MarketState;
-use crate::state::{Config, StrategyInstance};
+use crate::state::{
+    check_granularity, check_price_consistency, detect_interval, Config, MarketType,
+    ReconcileAction, StrategyInstance, RELOADABLE_THRESHOLDS,
+};
+use crate::storage::StateStore;
+use crate::strategy::{funding_settlements, Action, MarketView, Urgency};
+use crate::verify::invariants::check_portfolio;
 use crate::verify::order_sm::{Event, OrderState};
 use tokio::sync::mpsc;
 
 #[derive(Debug, Clone)]
 pub struct PendingMeta {
     pub strategy_id: String,
+    pub symbol: String,
     pub intent_id: String,
     pub placed_ts: u64,
     pub order_id: Option<String>,
+    /// Signed qty the strategy asked for before lot-step rounding (0 when
+    /// unknown, e.g. recovered from the WAL); see `track_rounding`.
+    pub intended_qty: f64,
+}
+
+/// Where an applied fill is recorded besides the strategy's own state.
+pub struct FillSinks<'a> {
+    pub wal: &'a mut Wal,
+    pub audit: &'a mut AuditTrail,
+    pub circuit: &'a mut CircuitBreaker,
 }
 
 pub fn process_fills(
@@ -24,16 +54,31 @@ pub fn process_fills(
     pending_by_client: &mut HashMap<String, PendingMeta>,
     strategies: &mut [StrategyInstance],
     order_book: &mut OrderBook,
-    wal: &mut Wal,
-    circuit: &mut CircuitBreaker,
+    sinks: FillSinks<'_>,
     market: &MarketState,
     cfg: &Config,
 ) -> bool {
+    let FillSinks {
+        wal,
+        audit,
+        circuit,
+    } = sinks;
     let mut halt_on_slip = false;
     while let Ok(fill) = fill_rx.try_recv() {
         if let Some(meta) = pending_by_client.get(&fill.client_id).cloned() {
             if let Some(inst) = strategies.iter_mut().find(|s| s.id == meta.strategy_id) {
-                let last_price = market.view(&cfg.symbol).last.c;
+                if !order_book.first_delivery(&fill.fill_id) {
+                    json_log(
+                        "order_state",
+                        obj(&[
+                            ("order_id", v_str(&fill.client_id)),
+                            ("fill_id", v_str(&fill.fill_id)),
+                            ("status", v_str("duplicate_fill")),
+                        ]),
+                    );
+                    continue;
+                }
+                let last_price = market.view(&inst.symbol).last.c;
                 if last_price > 0.0 {
                     let slip_pct = ((fill.price - last_price).abs()) / last_price;
                     if slip_pct > cfg.max_fill_slip_pct {
@@ -49,6 +94,10 @@ pub fn process_fills(
                         );
                     }
                 }
+                let first_fill = order_book
+                    .orders
+                    .get(&fill.client_id)
+                    .is_none_or(|o| o.filled_qty <= 0.0);
                 if let Ok((prev, next)) = order_book.apply(
                     &fill.client_id,
                     Event::Fill {
@@ -70,6 +119,9 @@ pub fn process_fills(
                     );
                     if next == OrderState::Filled {
                         pending_by_client.remove(&fill.client_id);
+                        let filled = order_book.orders[&fill.client_id].filled_qty;
+                        let filled = if fill.side == "BUY" { filled } else { -filled };
+                        track_rounding(cfg, inst, meta.intended_qty, filled, fill.price);
                     }
                 }
 
@@ -78,19 +130,34 @@ pub fn process_fills(
                 } else {
                     -fill.qty
                 };
-                let realized = inst.state.portfolio.apply_fill(crate::state::Fill {
+                let applied = crate::state::Fill {
                     price: fill.price,
                     qty: signed_qty,
                     fee: fill.fee,
                     ts: fill.ts,
-                });
-                inst.state.metrics.pnl += realized;
-                if realized > 0.0 {
-                    inst.state.metrics.wins += 1;
-                    circuit.record_success();
-                } else if realized < 0.0 {
-                    inst.state.metrics.losses += 1;
-                    inst.state.last_loss_ts = fill.ts;
+                };
+                let prev_portfolio = inst.state.portfolio;
+                let realized = inst.state.portfolio.apply_fill(applied);
+                inst.state
+                    .count_tranche(prev_portfolio.position, first_fill);
+                if let Err(v) =
+                    check_portfolio(&prev_portfolio, &inst.state.portfolio, &applied, realized)
+                {
+                    json_log(
+                        "audit",
+                        obj(&[
+                            ("event", v_str("invariant_violation")),
+                            ("strategy_id", v_str(&inst.id)),
+                            ("client_order_id", v_str(&fill.client_id)),
+                            ("msg", v_str(&v.msg)),
+                        ]),
+                    );
+                }
+                inst.state
+                    .metrics
+                    .record_fill(realized, &applied, inst.state.portfolio.position);
+                if realized < 0.0 {
+                    inst.state.record_loss(fill.ts, realized);
                     circuit.record_failure();
                 } else {
                     circuit.record_success();
@@ -102,6 +169,18 @@ pub fn process_fills(
                     inst.state.trades_today = 0;
                 }
                 inst.state.trades_today += 1;
+                let _ = audit.record(&AuditRecord {
+                    ts: crate::state::now_ts(),
+                    intent_id: meta.intent_id.clone(),
+                    stage: AuditStage::Fill,
+                    strategy_id: Some(meta.strategy_id.clone()),
+                    client_order_id: Some(fill.client_id.clone()),
+                    side: Some(fill.side.clone()),
+                    qty: Some(fill.qty),
+                    price: Some(fill.price),
+                    fee: Some(fill.fee),
+                    ..AuditRecord::default()
+                });
                 let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Fill {
                     ts: crate::state::now_ts(),
                     intent_id: meta.intent_id,
@@ -125,13 +204,156 @@ pub fn process_fills(
     halt_on_slip
 }
 
+/// Refuse to start when the venue's candle spacing differs from
+/// `candle_granularity` (unsupported granularities silently fall back to 1m
+/// on some venues). Symbols whose candles cannot be fetched are skipped.
+pub async fn check_feed_granularity(
+    exchange: &(dyn Exchange + Send + Sync),
+    cfg: &Config,
+) -> Result<(), String> {
+    for symbol in &cfg.symbols {
+        let candles = match exchange
+            .fetch_recent_candles(symbol, cfg.candle_granularity, 5)
+            .await
+        {
+            Ok(c) => c,
+            Err(err) => {
+                json_log(
+                    "risk_guard",
+                    obj(&[
+                        ("check", v_str("granularity")),
+                        ("result", v_str("skipped")),
+                        ("symbol", v_str(symbol)),
+                        ("error", v_str(&err.to_string())),
+                    ]),
+                );
+                continue;
+            }
+        };
+        let ts: Vec<u64> = candles.iter().map(|c| c.ts).collect();
+        if let Err(err) = check_granularity(cfg.candle_granularity, detect_interval(&ts)) {
+            json_log(
+                "risk_guard",
+                obj(&[
+                    ("check", v_str("granularity")),
+                    ("result", v_str("fail")),
+                    ("symbol", v_str(symbol)),
+                    ("error", v_str(&err)),
+                ]),
+            );
+            return Err(format!("{}: {}", symbol, err));
+        }
+    }
+    Ok(())
+}
+
+/// Feed closed historical candles through the market state, oldest first,
+/// so indicators start warm. The newest candle is dropped because it is
+/// still forming and the live loop will fetch it as its first bar.
+pub fn prefill_market(market: &mut MarketState, symbol: &str, mut candles: Vec<Candle>) -> usize {
+    candles.pop();
+    for candle in &candles {
+        market.on_candle_for(symbol, *candle);
+    }
+    candles.len()
+}
+
+/// Startup warmup: replay the last `min_history` candles per symbol without
+/// trading. A failed fetch is logged and leaves that symbol to warm up live.
+pub async fn prefill_history(
+    exchange: &(dyn Exchange + Send + Sync),
+    market: &mut MarketState,
+    cfg: &Config,
+) {
+    let limit = cfg.min_history() as usize + 1;
+    for symbol in &cfg.symbols {
+        match exchange
+            .fetch_recent_candles(symbol, cfg.candle_granularity, limit)
+            .await
+        {
+            Ok(candles) => {
+                let fed = prefill_market(market, symbol, candles);
+                let samples = market.view(symbol).indicators.samples;
+                json_log(
+                    "warmup",
+                    obj(&[
+                        ("symbol", v_str(symbol)),
+                        ("prefilled", v_num(fed as f64)),
+                        ("samples", v_num(samples as f64)),
+                        ("required", v_num(cfg.min_history() as f64)),
+                    ]),
+                );
+            }
+            Err(err) => json_log(
+                "warmup",
+                obj(&[
+                    ("symbol", v_str(symbol)),
+                    ("status", v_str("prefill_failed")),
+                    ("error", v_str(&err.to_string())),
+                ]),
+            ),
+        }
+    }
+}
+
+/// Compare the primary close against the reference venue's latest candle.
+/// Returns false (after logging) only on a confirmed divergence; a failed
+/// reference fetch is logged and does not block trading.
+pub async fn reference_price_ok(
+    reference: &(dyn Exchange + Send + Sync),
+    symbol: &str,
+    primary_close: f64,
+    cfg: &Config,
+) -> bool {
+    let reference_close = match reference
+        .fetch_latest_candle(symbol, cfg.candle_granularity)
+        .await
+    {
+        Ok(c) => c.c,
+        Err(err) => {
+            json_log(
+                "risk_guard",
+                obj(&[
+                    ("check", v_str("reference_price")),
+                    ("result", v_str("skipped")),
+                    ("symbol", v_str(symbol)),
+                    ("error", v_str(&err.to_string())),
+                ]),
+            );
+            return true;
+        }
+    };
+    match check_price_consistency(primary_close, reference_close, cfg.max_liquidity_spread) {
+        Ok(()) => true,
+        Err(err) => {
+            json_log(
+                "risk_guard",
+                obj(&[
+                    ("check", v_str("reference_price")),
+                    ("result", v_str("fail")),
+                    ("symbol", v_str(symbol)),
+                    ("primary", v_num(primary_close)),
+                    ("reference", v_num(reference_close)),
+                    ("error", v_str(&err)),
+                ]),
+            );
+            false
+        }
+    }
+}
+
+/// Reconcile open orders and balances against Binance; spot drift is handled
+/// by `apply_reconcile_drift`.
+/// Returns the largest spot position drift seen, if balances were fetched.
 pub async fn reconcile_binance(
     cfg: &Config,
     strategies: &mut [StrategyInstance],
     pending_by_client: &mut HashMap<String, PendingMeta>,
-) {
+    market: &MarketState,
+    wal: &mut Wal,
+) -> Option<f64> {
     let (Some(key), Some(secret)) = (&cfg.api_key, &cfg.api_secret) else {
-        return;
+        return None;
     };
     let client = BinanceReconcileClient::new(
         cfg.binance_base.clone(),
@@ -139,164 +361,2718 @@ pub async fn reconcile_binance(
         key.clone(),
         secret.clone(),
     );
+    let mut max_drift: Option<f64> = None;
 
-    match client.fetch_open_orders(&cfg.symbol).await {
-        Ok(orders) => {
-            let mut open_clients = HashMap::new();
-            for o in &orders {
-                open_clients.insert(o.client_order_id.clone(), o.order_id.clone());
+    for symbol in &cfg.symbols {
+        match client.fetch_open_orders(symbol).await {
+            Ok(orders) => {
+                let mut open_clients = HashMap::new();
+                for o in &orders {
+                    open_clients.insert(o.client_order_id.clone(), o.order_id.clone());
+                }
+                for (client_id, meta) in pending_by_client.iter_mut() {
+                    if let Some(order_id) = open_clients.get(client_id) {
+                        meta.order_id = Some(order_id.clone());
+                    }
+                }
+                json_log(
+                    "reconcile",
+                    obj(&[
+                        ("venue", v_str("binance")),
+                        ("symbol", v_str(symbol)),
+                        ("open_orders", v_num(orders.len() as f64)),
+                        ("status", v_str("ok")),
+                    ]),
+                );
+            }
+            Err(err) => {
+                json_log(
+                    "reconcile",
+                    obj(&[
+                        ("venue", v_str("binance")),
+                        ("symbol", v_str(symbol)),
+                        ("status", v_str("error")),
+                        ("error", v_str(&err.to_string())),
+                    ]),
+                );
             }
-            for (client_id, meta) in pending_by_client.iter_mut() {
-                if let Some(order_id) = open_clients.get(client_id) {
-                    meta.order_id = Some(order_id.clone());
+        }
+    }
+
+    match client.fetch_spot_balances().await {
+        Ok(balances) => {
+            for symbol in &cfg.symbols {
+                let mut quote_balance = None;
+                let mut base_balance = None;
+                for b in &balances {
+                    if symbol.ends_with(&b.asset) {
+                        quote_balance = Some(b.free + b.locked);
+                    }
+                    if symbol.starts_with(&b.asset) {
+                        base_balance = Some(b.free + b.locked);
+                    }
+                }
+                if let (Some(q), Some(b)) = (quote_balance, base_balance) {
+                    let local_pos: f64 = strategies
+                        .iter()
+                        .filter(|s| &s.symbol == symbol && !s.shadow)
+                        .map(|s| s.state.portfolio.position)
+                        .sum();
+                    let drift = (local_pos - b).abs();
+                    max_drift = Some(max_drift.map_or(drift, |m| m.max(drift)));
+                    let mark = market.view(symbol).last.c;
+                    apply_reconcile_drift(
+                        cfg,
+                        strategies,
+                        symbol,
+                        b,
+                        mark,
+                        crate::state::now_ts(),
+                        wal,
+                    );
+                    json_log(
+                        "reconcile",
+                        obj(&[
+                            ("venue", v_str("binance")),
+                            ("symbol", v_str(symbol)),
+                            ("base_balance", v_num(b)),
+                            ("quote_balance", v_num(q)),
+                            ("status", v_str("balances")),
+                        ]),
+                    );
                 }
             }
-            json_log(
-                "reconcile",
-                obj(&[
-                    ("venue", v_str("binance")),
-                    ("open_orders", v_num(orders.len() as f64)),
-                    ("status", v_str("ok")),
-                ]),
-            );
         }
         Err(err) => {
             json_log(
                 "reconcile",
                 obj(&[
                     ("venue", v_str("binance")),
-                    ("status", v_str("error")),
+                    ("status", v_str("balance_error")),
                     ("error", v_str(&err.to_string())),
                 ]),
             );
         }
     }
 
-    match client.fetch_spot_balances().await {
-        Ok(balances) => {
-            let mut quote_balance = None;
-            let mut base_balance = None;
-            for b in balances {
-                if cfg.symbol.ends_with(&b.asset) {
-                    quote_balance = Some(b.free + b.locked);
-                }
-                if cfg.symbol.starts_with(&b.asset) {
-                    base_balance = Some(b.free + b.locked);
-                }
-            }
-            if let (Some(q), Some(b)) = (quote_balance, base_balance) {
-                let local_pos: f64 = strategies.iter().map(|s| s.state.portfolio.position).sum();
-                let drift = (local_pos - b).abs();
-                let thresh =
-                    (local_pos.abs() * cfg.reconcile_drift_pct).max(cfg.reconcile_drift_abs);
-                if drift > thresh {
-                    for inst in strategies.iter_mut() {
-                        inst.state.trading_halted = true;
-                    }
+    for symbol in &cfg.symbols {
+        match client.fetch_futures_positions(symbol).await {
+            Ok(positions) => {
+                if let Some(pos) = positions.first() {
                     json_log(
                         "reconcile",
                         obj(&[
                             ("venue", v_str("binance")),
-                            ("status", v_str("drift_halt")),
-                            ("local_pos", v_num(local_pos)),
-                            ("exchange_pos", v_num(b)),
-                            ("drift", v_num(drift)),
-                            ("threshold", v_num(thresh)),
+                            ("perp_symbol", v_str(&pos.symbol)),
+                            ("perp_pos", v_num(pos.position_amt)),
+                            ("perp_entry", v_num(pos.entry_price)),
+                            ("perp_mark", v_num(pos.mark_price)),
+                            ("status", v_str("perp_position")),
                         ]),
                     );
                 }
+            }
+            Err(err) => {
                 json_log(
                     "reconcile",
                     obj(&[
                         ("venue", v_str("binance")),
-                        ("base_balance", v_num(b)),
-                        ("quote_balance", v_num(q)),
-                        ("status", v_str("balances")),
+                        ("status", v_str("perp_error")),
+                        ("error", v_str(&err.to_string())),
                     ]),
                 );
             }
         }
-        Err(err) => {
+    }
+    max_drift
+}
+
+/// Act on one symbol's local vs venue position per `cfg.reconcile_action`.
+/// The band is `max(|local| * reconcile_drift_pct, reconcile_drift_abs)`.
+/// AutoCorrect books the gap as a fee-free synthetic fill at `mark` on the
+/// instances holding the symbol, pro rata by size and logged to the WAL, so
+/// cash, entry price and the fill replay all follow the venue; fee dust and
+/// rounding never accumulate. A flat book has no owner to attach it to and
+/// is left alone, as is a book with no mark yet. A drift halt stops only the
+/// instances trading `symbol`. Returns the status logged.
+pub fn apply_reconcile_drift(
+    cfg: &Config,
+    strategies: &mut [StrategyInstance],
+    symbol: &str,
+    exchange_pos: f64,
+    mark: f64,
+    ts: u64,
+    wal: &mut Wal,
+) -> &'static str {
+    let local_pos: f64 = strategies
+        .iter()
+        .filter(|s| s.symbol == symbol && !s.shadow)
+        .map(|s| s.state.portfolio.position)
+        .sum();
+    let drift = (local_pos - exchange_pos).abs();
+    let thresh = (local_pos.abs() * cfg.reconcile_drift_pct).max(cfg.reconcile_drift_abs);
+    let within = drift <= thresh;
+    let status = match cfg.reconcile_action {
+        _ if drift <= 1e-12 => return "in_sync",
+        ReconcileAction::LogOnly | ReconcileAction::Halt if within => return "within_band",
+        ReconcileAction::LogOnly => "drift_logged",
+        ReconcileAction::Halt => "drift_halt",
+        ReconcileAction::AutoCorrect if !within => "drift_halt",
+        ReconcileAction::AutoCorrect => {
+            let held: f64 = strategies
+                .iter()
+                .filter(|s| s.symbol == symbol && !s.shadow)
+                .map(|s| s.state.portfolio.position.abs())
+                .sum();
+            if held <= 0.0 {
+                "correct_skipped_flat"
+            } else if mark <= 0.0 {
+                "correct_skipped_no_mark"
+            } else {
+                let gap = exchange_pos - local_pos;
+                for inst in strategies
+                    .iter_mut()
+                    .filter(|s| s.symbol == symbol && !s.shadow)
+                {
+                    let share = inst.state.portfolio.position.abs() / held;
+                    let fill = crate::state::Fill {
+                        price: mark,
+                        qty: gap * share,
+                        fee: 0.0,
+                        ts,
+                    };
+                    if fill.qty == 0.0 {
+                        continue;
+                    }
+                    inst.state.portfolio.apply_fill(fill);
+                    let intent_id = format!("I-{}-reconcile-{}", inst.id, ts);
+                    let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Fill {
+                        ts,
+                        params_hash: params_hash(&intent_id),
+                        intent_id,
+                        price: fill.price,
+                        qty: fill.qty,
+                        fee: fill.fee,
+                        fsync: true,
+                    });
+                }
+                "auto_corrected"
+            }
+        }
+    };
+    if status == "drift_halt" {
+        for inst in strategies.iter_mut().filter(|s| s.symbol == symbol) {
+            inst.state.trading_halted = true;
+        }
+    }
+    json_log(
+        "reconcile",
+        obj(&[
+            ("venue", v_str("binance")),
+            ("symbol", v_str(symbol)),
+            ("status", v_str(status)),
+            ("local_pos", v_num(local_pos)),
+            ("exchange_pos", v_num(exchange_pos)),
+            ("drift", v_num(drift)),
+            ("threshold", v_num(thresh)),
+        ]),
+    );
+    status
+}
+
+/// Credit funding to `symbol`'s open positions when a settlement boundary
+/// falls between the previous candle and this one. `rate` is the rate that
+/// was in force going into the settlement. Returns the total credited;
+/// always 0 on a spot market, where no funding is paid.
+pub fn settle_funding(
+    cfg: &Config,
+    strategies: &mut [StrategyInstance],
+    symbol: &str,
+    prev_candle_ts: u64,
+    candle_ts: u64,
+    rate: f64,
+    price: f64,
+) -> f64 {
+    let settlements = funding_settlements(prev_candle_ts, candle_ts, cfg.funding_interval_secs);
+    if settlements == 0 || rate == 0.0 || cfg.market_type == MarketType::Spot {
+        return 0.0;
+    }
+    let mut total = 0.0;
+    for inst in strategies.iter_mut().filter(|i| i.symbol == symbol) {
+        let paid = inst
+            .state
+            .portfolio
+            .apply_funding(rate * settlements as f64, price);
+        if paid != 0.0 {
+            total += paid;
             json_log(
-                "reconcile",
+                "funding",
                 obj(&[
-                    ("venue", v_str("binance")),
-                    ("status", v_str("balance_error")),
-                    ("error", v_str(&err.to_string())),
+                    ("strategy", v_str(&inst.id)),
+                    ("symbol", v_str(symbol)),
+                    ("rate", v_num(rate)),
+                    ("settlements", v_num(settlements as f64)),
+                    ("amount", v_num(paid)),
                 ]),
             );
         }
     }
+    total
+}
 
-    match client.fetch_futures_positions(&cfg.symbol).await {
-        Ok(positions) => {
-            if let Some(pos) = positions.first() {
-                json_log(
-                    "reconcile",
-                    obj(&[
-                        ("venue", v_str("binance")),
-                        ("perp_symbol", v_str(&pos.symbol)),
-                        ("perp_pos", v_num(pos.position_amt)),
-                        ("perp_entry", v_num(pos.entry_price)),
-                        ("perp_mark", v_num(pos.mark_price)),
-                        ("status", v_str("perp_position")),
-                    ]),
-                );
+/// Halt every strategy while the candle feed is stale or off the reference
+/// price, noting in `feed_halted` which halts this set, and lift exactly
+/// those once the data is good again. Halts from risk checks stay, as in
+/// the backtest's outage handling.
+pub fn apply_feed_halt(strategies: &mut [StrategyInstance], feed_halted: &mut [bool], bad: bool) {
+    for (inst, halted) in strategies.iter_mut().zip(feed_halted.iter_mut()) {
+        if bad {
+            if !inst.state.trading_halted {
+                inst.state.trading_halted = true;
+                *halted = true;
             }
+        } else if std::mem::take(halted) {
+            inst.state.trading_halted = false;
+            json_log(
+                "risk_guard",
+                obj(&[
+                    ("check", v_str("feed_health")),
+                    ("result", v_str("resumed")),
+                    ("strategy", v_str(&inst.id)),
+                ]),
+            );
         }
-        Err(err) => {
+    }
+}
+
+/// Re-enable disabled strategies named in `cfg.reenable_file` (all of them
+/// when the file is empty), then remove the file. Returns how many were
+/// re-enabled.
+pub fn apply_reenable_file(cfg: &Config, strategies: &mut [StrategyInstance]) -> usize {
+    let Ok(body) = std::fs::read_to_string(&cfg.reenable_file) else {
+        return 0;
+    };
+    let _ = std::fs::remove_file(&cfg.reenable_file);
+    let ids: Vec<&str> = body
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    let mut count = 0;
+    for inst in strategies.iter_mut() {
+        if inst.state.disabled && (ids.is_empty() || ids.contains(&inst.id.as_str())) {
+            inst.state.reenable();
+            count += 1;
             json_log(
-                "reconcile",
+                "risk_guard",
                 obj(&[
-                    ("venue", v_str("binance")),
-                    ("status", v_str("perp_error")),
-                    ("error", v_str(&err.to_string())),
+                    ("check", v_str("profit_factor")),
+                    ("result", v_str("reenabled")),
+                    ("strategy", v_str(&inst.id)),
                 ]),
             );
         }
     }
+    count
 }
 
-pub fn cancel_stale_orders(
-    start: u64,
+/// True when the operator has requested an immediate reconcile via
+/// `cfg.reconcile_now_file`; the file is removed so it fires once.
+pub fn take_reconcile_request(cfg: &Config) -> bool {
+    if cfg.reconcile_now_file.is_empty() || !std::path::Path::new(&cfg.reconcile_now_file).exists()
+    {
+        return false;
+    }
+    let _ = std::fs::remove_file(&cfg.reconcile_now_file);
+    json_log(
+        "reconcile",
+        obj(&[
+            ("event", v_str("on_demand")),
+            ("file", v_str(&cfg.reconcile_now_file)),
+        ]),
+    );
+    true
+}
+
+/// Hot-reload signal thresholds from `cfg.threshold_file` whenever its mtime
+/// moves past `last_mtime`, merging them into every strategy's own config.
+/// Each change is logged; keys outside `RELOADABLE_THRESHOLDS` are logged as
+/// ignored, and a malformed file changes nothing. Returns how many
+/// (strategy, field) changes were applied.
+pub fn reload_thresholds(
     cfg: &Config,
-    adapter: &mut dyn UnifiedAdapter,
-    pending_by_client: &mut HashMap<String, PendingMeta>,
-    order_book: &mut OrderBook,
-    wal: &mut Wal,
-) {
-    let cancel_after = cfg
-        .cancel_after_candles
-        .saturating_mul(cfg.candle_granularity);
-    if cancel_after == 0 {
-        return;
+    strategies: &mut [StrategyInstance],
+    last_mtime: &mut Option<SystemTime>,
+) -> usize {
+    if cfg.threshold_file.is_empty() {
+        return 0;
     }
-    let mut to_cancel: Vec<(String, String)> = Vec::new();
-    for (client_id, meta) in pending_by_client.iter() {
-        if start.saturating_sub(meta.placed_ts) >= cancel_after {
-            if let Some(order_id) = &meta.order_id {
-                to_cancel.push((client_id.clone(), order_id.clone()));
+    let Ok(mtime) = std::fs::metadata(&cfg.threshold_file).and_then(|m| m.modified()) else {
+        return 0;
+    };
+    if *last_mtime == Some(mtime) {
+        return 0;
+    }
+    *last_mtime = Some(mtime);
+    let reload_error = |err: &str| {
+        json_log(
+            "config_reload",
+            obj(&[
+                ("result", v_str("error")),
+                ("file", v_str(&cfg.threshold_file)),
+                ("error", v_str(err)),
+            ]),
+        )
+    };
+    let overrides: serde_json::Value = match std::fs::read_to_string(&cfg.threshold_file)
+        .map_err(|e| e.to_string())
+        .and_then(|body| serde_json::from_str(&body).map_err(|e| e.to_string()))
+    {
+        Ok(v) => v,
+        Err(e) => {
+            reload_error(&e);
+            return 0;
+        }
+    };
+    if let Some(map) = overrides.as_object() {
+        for key in map.keys() {
+            if !RELOADABLE_THRESHOLDS.contains(&key.as_str()) {
+                json_log(
+                    "config_reload",
+                    obj(&[("result", v_str("ignored")), ("field", v_str(key))]),
+                );
             }
         }
     }
-    for (client_id, order_id) in to_cancel {
-        if adapter.cancel_order(&order_id).is_ok() {
-            pending_by_client.remove(&client_id);
-            let _ = order_book.apply(&client_id, Event::CancelRequest);
-            let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Cancel {
-                ts: crate::logging::ts_epoch_ms(),
-                intent_id: format!("cancel-{}", client_id),
-                params_hash: params_hash(&client_id),
-                fsync: true,
-            });
+    let mut applied = 0;
+    for inst in strategies.iter_mut() {
+        let Some(strategy_cfg) = inst.strategy.config_mut() else {
+            continue;
+        };
+        let changes = match strategy_cfg.merge_thresholds(&overrides) {
+            Ok(changes) => changes,
+            Err(e) => {
+                // Same file for every strategy, so nothing else can apply either
+                reload_error(&e);
+                return applied;
+            }
+        };
+        for change in changes {
+            json_log(
+                "config_reload",
+                obj(&[
+                    ("result", v_str("applied")),
+                    ("strategy", v_str(&inst.id)),
+                    ("field", v_str(change.field)),
+                    ("old", v_num(change.old)),
+                    ("new", v_num(change.new)),
+                ]),
+            );
+            applied += 1;
+        }
+    }
+    applied
+}
+
+/// `CANDLE_SOURCE=trades` reads the Binance public trade stream; refuse to
+/// start on any other venue rather than wait forever for candles.
+pub fn check_candle_source(cfg: &Config, venue: ExchangeKind) -> Result<(), String> {
+    if cfg.trade_candles && !matches!(venue, ExchangeKind::Binance) {
+        return Err(format!(
+            "CANDLE_SOURCE=trades needs the Binance trade stream, not {:?}",
+            venue
+        ));
+    }
+    Ok(())
+}
+
+/// Contract specs from `contract_spec_file` if set, else Binance
+/// `exchangeInfo`. Any failure, or a venue without a spec endpoint, logs a
+/// warning and leaves the conservative defaults; every configured symbol
+/// still without a spec is warned about once here.
+pub async fn load_contract_specs(cfg: &Config, venue: ExchangeKind) -> ContractSpecs {
+    let (source, loaded) = if !cfg.contract_spec_file.is_empty() {
+        ("file", ContractSpecs::from_file(&cfg.contract_spec_file))
+    } else if matches!(venue, ExchangeKind::Binance) {
+        (
+            "exchange_info",
+            ContractSpecs::fetch_binance(&cfg.binance_base).await,
+        )
+    } else {
+        ("none", Ok(ContractSpecs::default()))
+    };
+    let specs = match loaded {
+        Ok(specs) => {
+            json_log(
+                "contract_specs",
+                obj(&[
+                    ("source", v_str(source)),
+                    (
+                        "status",
+                        v_str(if specs.is_empty() { "empty" } else { "ok" }),
+                    ),
+                    ("symbols", v_num(specs.len() as f64)),
+                ]),
+            );
+            specs
+        }
+        Err(e) => {
             json_log(
-                "exec_wrapper",
+                "contract_specs",
                 obj(&[
-                    ("client_order_id", v_str(&client_id)),
-                    ("status", v_str("cancelled_timeout")),
+                    ("source", v_str(source)),
+                    ("status", v_str("error")),
+                    ("error", v_str(&e.to_string())),
                 ]),
             );
-            let _ = order_book.apply(&client_id, Event::CancelAck);
+            ContractSpecs::default()
+        }
+    };
+    for symbol in specs.missing(&cfg.symbols) {
+        let fallback = specs.get(symbol);
+        json_log(
+            "contract_specs",
+            obj(&[
+                ("warning", v_str("unknown_symbol_using_defaults")),
+                ("symbol", v_str(symbol)),
+                ("tick_size", v_num(fallback.tick_size)),
+                ("step_size", v_num(fallback.step_size)),
+                ("min_notional", v_num(fallback.min_notional)),
+            ]),
+        );
+    }
+    specs
+}
+
+/// Round `qty` down to the contract's lot step; None (logged) if nothing is
+/// left, or if an entry falls under the minimum notional at `price`.
+/// Closes skip the notional check so a small residual can always be flattened.
+pub fn fit_to_contract(
+    filters: &ExchangeFilters,
+    action: Action,
+    qty: f64,
+    price: f64,
+    strategy_id: &str,
+) -> Option<f64> {
+    let rounded = filters.round_qty(qty);
+    let check = if rounded <= 0.0 {
+        "lot_size"
+    } else if !matches!(action, Action::Close) && !filters.meets_min_notional(rounded, price) {
+        "min_notional"
+    } else {
+        return Some(rounded);
+    };
+    json_log(
+        "risk_guard",
+        obj(&[
+            ("check", v_str(check)),
+            ("result", v_str("fail")),
+            ("strategy", v_str(strategy_id)),
+            ("qty", v_num(qty)),
+            ("rounded_qty", v_num(rounded)),
+            ("notional", v_num(rounded * price)),
+            ("min_notional", v_num(filters.min_notional)),
+        ]),
+    );
+    None
+}
+
+/// `market_view` record of one decision: the strategy's exact inputs and the
+/// action it returned, keyed by strategy id and bar timestamp.
+pub fn view_record(
+    strategy_id: &str,
+    view: &MarketView,
+    action: Action,
+) -> serde_json::Map<String, serde_json::Value> {
+    obj(&[
+        ("strategy_id", v_str(strategy_id)),
+        ("ts", v_num(view.last.ts as f64)),
+        ("action", v_str(&format!("{:?}", action))),
+        (
+            "view",
+            serde_json::to_value(view).unwrap_or(serde_json::Value::Null),
+        ),
+    ])
+}
+
+/// Log `view_record` when `dump_views` is on.
+pub fn dump_view(cfg: &Config, strategy_id: &str, view: &MarketView, action: Action) {
+    if cfg.dump_views {
+        json_log("market_view", view_record(strategy_id, view, action));
+    }
+}
+
+/// Accumulate the gap between what a strategy asked for (`intended_qty`,
+/// before lot-step rounding) and what the venue filled (`filled_qty`, the
+/// order's total once Filled), both signed, into the strategy's residual.
+/// Once the residual's notional at `price` reaches `rounding_drift_notional`,
+/// log a `rounding_drift` and start a fresh tally. Returns true when it
+/// logged. Orders with no recorded intent (`intended_qty == 0`) are skipped.
+pub fn track_rounding(
+    cfg: &Config,
+    inst: &mut StrategyInstance,
+    intended_qty: f64,
+    filled_qty: f64,
+    price: f64,
+) -> bool {
+    if intended_qty == 0.0 {
+        return false;
+    }
+    inst.state.rounding_residual += intended_qty - filled_qty;
+    let residual = inst.state.rounding_residual;
+    let notional = residual.abs() * price;
+    if cfg.rounding_drift_notional <= 0.0 || notional < cfg.rounding_drift_notional {
+        return false;
+    }
+    json_log(
+        "position_agg",
+        obj(&[
+            ("event", v_str("rounding_drift")),
+            ("strategy_id", v_str(&inst.id)),
+            ("asset", v_str(&inst.symbol)),
+            ("residual_qty", v_num(residual)),
+            ("residual_notional", v_num(notional)),
+            ("threshold", v_num(cfg.rounding_drift_notional)),
+        ]),
+    );
+    inst.state.rounding_residual = 0.0;
+    true
+}
+
+/// Shadow (observe-only) execution: fill `qty` against `inst`'s virtual
+/// portfolio with the paper fill model at `bar`'s close and log what
+/// would have been sent. Returns false for live instances, which go on to
+/// place a real order; the caller skips the order path on true. The
+/// `shadow_order` log lines are the evidence a hypothesis review reads
+/// before a strategy is promoted.
+pub fn shadow_execute(
+    cfg: &Config,
+    inst: &mut StrategyInstance,
+    action: Action,
+    qty: f64,
+    bar: &crate::strategy::Candle,
+) -> bool {
+    if !inst.shadow {
+        return false;
+    }
+    let candle = Candle {
+        ts: bar.ts,
+        o: bar.o,
+        h: bar.h,
+        l: bar.l,
+        c: bar.c,
+        v: bar.v,
+    };
+    let signed = match action {
+        Action::Buy { .. } => qty,
+        Action::Sell { .. } => -qty,
+        Action::Close => -qty * inst.state.portfolio.position.signum(),
+        Action::Hold => 0.0,
+    };
+    let fill = PaperFillModel::from_config(cfg).simulate_fill(&candle, 0.0, signed);
+    if fill.qty == 0.0 {
+        return true;
+    }
+    let position_before = inst.state.portfolio.position;
+    let realized = inst.state.portfolio.apply_fill(fill);
+    inst.state.count_tranche(position_before, true);
+    inst.state
+        .metrics
+        .record_fill(realized, &fill, inst.state.portfolio.position);
+    if realized < 0.0 {
+        inst.state.record_loss(fill.ts, realized);
+    }
+    inst.state.record_trade_ts(fill.ts);
+    inst.state.last_trade_ts = fill.ts;
+    json_log(
+        "shadow_order",
+        obj(&[
+            ("strategy", v_str(&inst.id)),
+            ("symbol", v_str(&inst.symbol)),
+            ("qty", v_num(fill.qty)),
+            ("price", v_num(fill.price)),
+            ("fee", v_num(fill.fee)),
+            ("realized", v_num(realized)),
+            ("position", v_num(inst.state.portfolio.position)),
+            ("equity", v_num(inst.state.portfolio.equity)),
+        ]),
+    );
+    true
+}
+
+/// Time of the last order sent on each symbol, from any strategy, for the
+/// `min_order_interval_secs` guard.
+#[derive(Debug, Default)]
+pub struct OrderSpacing {
+    last_order_ts: HashMap<String, u64>,
+}
+
+impl OrderSpacing {
+    /// Refuse an open or add on `symbol` within `min_order_interval_secs` of
+    /// its previous order. Orders that reduce `position` always pass, as
+    /// with the spread guard.
+    pub fn allows(
+        &self,
+        cfg: &Config,
+        symbol: &str,
+        action: Action,
+        position: f64,
+        now_ts: u64,
+        strategy_id: &str,
+    ) -> bool {
+        if cfg.min_order_interval_secs == 0 || action.reduces(position) {
+            return true;
         }
+        let Some(&last) = self.last_order_ts.get(symbol) else {
+            return true;
+        };
+        let elapsed = now_ts.saturating_sub(last);
+        if elapsed >= cfg.min_order_interval_secs {
+            return true;
+        }
+        json_log(
+            "risk_guard",
+            obj(&[
+                ("check", v_str("order_interval")),
+                ("result", v_str("fail")),
+                ("strategy", v_str(strategy_id)),
+                ("symbol", v_str(symbol)),
+                ("elapsed_secs", v_num(elapsed as f64)),
+                ("threshold", v_num(cfg.min_order_interval_secs as f64)),
+            ]),
+        );
+        false
+    }
+
+    /// Note an order on `symbol` that actually went out: an exchange ack or
+    /// a shadow fill. Orders dropped by sizing or rejected by the venue are
+    /// not recorded, so they never delay the next attempt.
+    pub fn record(&mut self, symbol: &str, now_ts: u64) {
+        self.last_order_ts.insert(symbol.to_string(), now_ts);
+    }
+}
+
+/// Spread guard: refuse to open or add while the quoted spread exceeds
+/// `max_liquidity_spread`. Orders that reduce `position` (closes and partial
+/// exits) always pass so a thin book never traps a position, and a missing
+/// book (fetch failed) does not block trading.
+pub fn spread_allows_order(
+    book: Option<&BookTop>,
+    action: Action,
+    position: f64,
+    cfg: &Config,
+    strategy_id: &str,
+) -> bool {
+    if matches!(action, Action::Hold) || action.reduces(position) {
+        return true;
+    }
+    let Some(book) = book else {
+        return true;
+    };
+    let spread = book.spread_pct().unwrap_or(f64::INFINITY);
+    if spread <= cfg.max_liquidity_spread {
+        return true;
+    }
+    json_log_at(
+        Level::Debug,
+        "risk_guard",
+        obj(&[
+            ("check", v_str("spread_too_wide")),
+            ("result", v_str("fail")),
+            ("strategy", v_str(strategy_id)),
+            ("bid", v_num(book.bid)),
+            ("ask", v_num(book.ask)),
+            ("spread_pct", v_num(spread * 100.0)),
+            ("threshold_pct", v_num(cfg.max_liquidity_spread * 100.0)),
+        ]),
+    );
+    false
+}
+
+/// Graduated drift de-risking: scale entry size by the drift severity's
+/// position multiplier. Closes, holds and orders that reduce `position` pass
+/// through, so drift never slows getting out; Severe and Critical are
+/// handled by the halt/close path, not here.
+pub fn drift_throttle(
+    action: Action,
+    position: f64,
+    severity: DriftSeverity,
+    strategy_id: &str,
+) -> Action {
+    if !severity.should_throttle() || action.reduces(position) {
+        return action;
+    }
+    let mult = severity.position_multiplier();
+    let throttled = match action {
+        Action::Buy { qty } => Action::Buy { qty: qty * mult },
+        Action::Sell { qty } => Action::Sell { qty: qty * mult },
+        other => return other,
+    };
+    json_log_at(
+        Level::Debug,
+        "risk_guard",
+        obj(&[
+            ("check", v_str("drift_throttle")),
+            ("result", v_str("shrink")),
+            ("strategy", v_str(strategy_id)),
+            ("severity", v_str(&format!("{:?}", severity))),
+            ("multiplier", v_num(mult)),
+        ]),
+    );
+    throttled
+}
+
+/// Limit price and post-only flag for an order. With `maker_offset_bps` set,
+/// patient actions rest post-only that far inside the close, rounded away
+/// from the book; urgent ones cross at market live and at the close on paper.
+pub fn execution_intent(
+    cfg: &Config,
+    filters: &ExchangeFilters,
+    action: Action,
+    urgency: Urgency,
+    close: f64,
+    live: bool,
+) -> (Option<f64>, bool) {
+    let patient = urgency == Urgency::Patient && cfg.maker_offset_bps > 0.0;
+    if !patient {
+        // Live venues take market orders; paper orders rest at the close
+        return ((!live).then(|| filters.round_price(close)), false);
+    }
+    let offset = cfg.maker_offset_bps / 10_000.0;
+    let ticks = |p: f64| p / filters.tick_size;
+    let price = if matches!(action, Action::Buy { .. }) {
+        ticks(close * (1.0 - offset)).floor() * filters.tick_size
+    } else {
+        ticks(close * (1.0 + offset)).ceil() * filters.tick_size
+    };
+    (Some(price), true)
+}
+
+/// `action` resized to the contract-rounded `qty` that was actually sent, for
+/// executors that take an `Action`. A close becomes the reducing side, so a
+/// position rounded down to the lot step is not flattened past what the WAL
+/// and order book recorded.
+pub fn sent_action(action: Action, position: f64, qty: f64) -> Action {
+    match action {
+        Action::Buy { .. } => Action::Buy { qty },
+        Action::Sell { .. } => Action::Sell { qty },
+        Action::Close if position < 0.0 => Action::Buy { qty },
+        Action::Close => Action::Sell { qty },
+        Action::Hold => Action::Hold,
+    }
+}
+
+/// Venue order for a guarded action. Closing flattens `position`, so the
+/// order is reduce-only; a `limit_price` makes it a limit order, which
+/// `post_only` keeps off the other side of the book.
+pub fn order_request(
+    symbol: &str,
+    action: Action,
+    position: f64,
+    qty: f64,
+    client_id: &str,
+    limit_price: Option<f64>,
+    post_only: bool,
+) -> OrderRequest {
+    let side = match action {
+        Action::Buy { .. } => Side::Buy,
+        Action::Close if position < 0.0 => Side::Buy,
+        _ => Side::Sell,
+    };
+    OrderRequest {
+        symbol: symbol.to_string(),
+        side,
+        order_type: if limit_price.is_some() {
+            OrderType::Limit
+        } else {
+            OrderType::Market
+        },
+        price: limit_price,
+        qty,
+        client_id: client_id.to_string(),
+        reduce_only: matches!(action, Action::Close),
+        post_only: post_only && limit_price.is_some(),
+    }
+}
+
+/// Submit an order unless its client id was already acknowledged by the venue
+/// (possibly before a crash, per the WAL). Returns `None` when suppressed.
+/// On success the ack is recorded in the order book and the WAL.
+pub fn place_order_once(
+    adapter: &mut dyn UnifiedAdapter,
+    order_book: &mut OrderBook,
+    wal: &mut Wal,
+    intent_id: &str,
+    req: OrderRequest,
+) -> Option<Result<OrderResponse, String>> {
+    if suppress_duplicate(order_book, intent_id, &req.client_id) {
+        return None;
+    }
+    let client_id = req.client_id.clone();
+    let resp = adapter.place_order(req);
+    if let Ok(ack) = &resp {
+        record_ack(order_book, wal, intent_id, &client_id, ack);
+    }
+    Some(resp)
+}
+
+/// True (logged) if the venue already acknowledged `client_id`.
+fn suppress_duplicate(order_book: &OrderBook, intent_id: &str, client_id: &str) -> bool {
+    if !order_book.is_acked(client_id) {
+        return false;
+    }
+    json_log(
+        "exec_wrapper",
+        obj(&[
+            ("intent_id", v_str(intent_id)),
+            ("client_order_id", v_str(client_id)),
+            ("status", v_str("duplicate_suppressed")),
+        ]),
+    );
+    true
+}
+
+fn record_ack(
+    order_book: &mut OrderBook,
+    wal: &mut Wal,
+    intent_id: &str,
+    client_id: &str,
+    ack: &OrderResponse,
+) {
+    order_book.mark_acked(client_id);
+    let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Ack {
+        ts: crate::state::now_ts(),
+        intent_id: intent_id.to_string(),
+        client_order_id: client_id.to_string(),
+        exchange_order_id: ack.order_id.clone(),
+        fsync: true,
+    });
+}
+
+/// An order fully decided and journaled, waiting to be sent.
+#[derive(Debug, Clone)]
+pub struct OrderJob {
+    /// Position of the placing instance in the strategy list
+    pub index: usize,
+    pub intent_id: String,
+    pub client_id: String,
+    pub action: Action,
+    pub req: OrderRequest,
+}
+
+#[derive(Debug)]
+pub struct OrderOutcome {
+    pub job: OrderJob,
+    /// None when suppressed as a duplicate
+    pub resp: Option<Result<OrderResponse, String>>,
+    pub sent_ms: u64,
+    pub recv_ms: u64,
+}
+
+/// Send `jobs` with at most `max_in_flight` outstanding and return the
+/// outcomes in job order. Only the wait on the venue overlaps: acks are
+/// marked and journaled afterwards, in job order, so the order book and WAL
+/// come out the same however the responses interleaved. Adapters without
+/// `submit_order` send one at a time.
+pub async fn place_orders(
+    adapter: &mut dyn UnifiedAdapter,
+    order_book: &mut OrderBook,
+    wal: &mut Wal,
+    jobs: Vec<OrderJob>,
+    max_in_flight: usize,
+) -> Vec<OrderOutcome> {
+    let mut done: Vec<(usize, OrderOutcome)> = Vec::with_capacity(jobs.len());
+    let mut sequential = Vec::new();
+    {
+        let shared: &dyn UnifiedAdapter = adapter;
+        let mut queue = jobs.into_iter().enumerate();
+        let mut in_flight = FuturesUnordered::new();
+        loop {
+            while in_flight.len() < max_in_flight.max(1) {
+                let Some((seq, job)) = queue.next() else {
+                    break;
+                };
+                if suppress_duplicate(order_book, &job.intent_id, &job.client_id) {
+                    let now = crate::logging::ts_epoch_ms();
+                    let outcome = OrderOutcome {
+                        job,
+                        resp: None,
+                        sent_ms: now,
+                        recv_ms: now,
+                    };
+                    done.push((seq, outcome));
+                    continue;
+                }
+                let Some(fut) = shared.submit_order(&job.req) else {
+                    sequential.push((seq, job));
+                    continue;
+                };
+                let sent_ms = crate::logging::ts_epoch_ms();
+                in_flight.push(async move {
+                    let resp = fut.await;
+                    let outcome = OrderOutcome {
+                        job,
+                        resp: Some(resp),
+                        sent_ms,
+                        recv_ms: crate::logging::ts_epoch_ms(),
+                    };
+                    (seq, outcome)
+                });
+            }
+            match in_flight.next().await {
+                Some(finished) => done.push(finished),
+                None => break,
+            }
+        }
+    }
+    done.sort_by_key(|(seq, _)| *seq);
+    for (_, outcome) in &done {
+        if let Some(Ok(ack)) = &outcome.resp {
+            let job = &outcome.job;
+            record_ack(order_book, wal, &job.intent_id, &job.client_id, ack);
+        }
+    }
+    for (seq, job) in sequential {
+        let sent_ms = crate::logging::ts_epoch_ms();
+        let resp = place_order_once(adapter, order_book, wal, &job.intent_id, job.req.clone());
+        let outcome = OrderOutcome {
+            job,
+            resp,
+            sent_ms,
+            recv_ms: crate::logging::ts_epoch_ms(),
+        };
+        done.push((seq, outcome));
+    }
+    done.sort_by_key(|(seq, _)| *seq);
+    done.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+pub fn cancel_stale_orders(
+    start: u64,
+    cfg: &Config,
+    adapter: &mut dyn UnifiedAdapter,
+    pending_by_client: &mut HashMap<String, PendingMeta>,
+    order_book: &mut OrderBook,
+    wal: &mut Wal,
+    audit: &mut AuditTrail,
+) {
+    let cancel_after = cfg.candles_to_secs(cfg.cancel_after_candles);
+    if cancel_after == 0 {
+        return;
+    }
+    let mut to_cancel: Vec<(String, String, String)> = Vec::new();
+    for (client_id, meta) in pending_by_client.iter() {
+        if start.saturating_sub(meta.placed_ts) >= cancel_after {
+            if let Some(order_id) = &meta.order_id {
+                to_cancel.push((client_id.clone(), order_id.clone(), meta.symbol.clone()));
+            }
+        }
+    }
+    for (client_id, order_id, symbol) in to_cancel {
+        // Confirm the order is still working; a failed query falls back to cancelling
+        let venue_status = adapter
+            .fetch_order_status(&symbol, &client_id)
+            .unwrap_or(OrderStatus::Unknown);
+        let (detail, status) = match venue_status {
+            OrderStatus::Filled => {
+                // Leave it pending: the fill stream settles it
+                json_log(
+                    "exec_wrapper",
+                    obj(&[
+                        ("client_order_id", v_str(&client_id)),
+                        ("status", v_str("stale_already_filled")),
+                    ]),
+                );
+                continue;
+            }
+            s if s.is_open() || s == OrderStatus::Unknown => {
+                if adapter.cancel_order(&order_id).is_err() {
+                    continue;
+                }
+                ("timeout", "cancelled_timeout")
+            }
+            // Cancelled, rejected or expired on the venue already
+            _ => ("venue_closed", "closed_on_venue"),
+        };
+        if let Some(meta) = pending_by_client.remove(&client_id) {
+            let _ = audit.record(&AuditRecord {
+                ts: crate::state::now_ts(),
+                intent_id: meta.intent_id,
+                stage: AuditStage::Cancel,
+                client_order_id: Some(client_id.clone()),
+                exchange_order_id: Some(order_id.clone()),
+                detail: Some(detail.to_string()),
+                ..AuditRecord::default()
+            });
+        }
+        let _ = order_book.apply(&client_id, Event::CancelRequest);
+        let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Cancel {
+            ts: crate::logging::ts_epoch_ms(),
+            intent_id: format!("cancel-{}", client_id),
+            params_hash: params_hash(&client_id),
+            fsync: true,
+        });
+        json_log(
+            "exec_wrapper",
+            obj(&[
+                ("client_order_id", v_str(&client_id)),
+                ("status", v_str(status)),
+                ("venue_status", v_str(venue_status.as_str())),
+            ]),
+        );
+        let _ = order_book.apply(&client_id, Event::CancelAck);
+    }
+}
+
+/// Orders kept in the rolling place-order latency window.
+const LATENCY_WINDOW: usize = 20;
+/// Samples required before the latency guard may trip.
+const LATENCY_MIN_SAMPLES: usize = 3;
+/// Samples older than this no longer count toward the rolling mean.
+const LATENCY_MAX_AGE_MS: u64 = 10 * 60 * 1000;
+
+/// Round-trip latency of one venue call from wall-clock send/receive times (ms).
+pub fn latency_ms(sent_ms: u64, recv_ms: u64) -> u64 {
+    recv_ms.saturating_sub(sent_ms)
+}
+
+/// Rolling place-order latency, checked against `max_latency_ms` alongside
+/// the candle dead-man's switch.
+#[derive(Debug, Default)]
+pub struct LatencyWindow {
+    /// (receive time, latency) in ms, oldest first
+    samples: VecDeque<(u64, u64)>,
+}
+
+impl LatencyWindow {
+    /// Record one round trip; returns its latency in ms.
+    pub fn record(&mut self, sent_ms: u64, recv_ms: u64) -> u64 {
+        let latency = latency_ms(sent_ms, recv_ms);
+        if self.samples.len() == LATENCY_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((recv_ms, latency));
+        latency
+    }
+
+    /// Forget samples older than `LATENCY_MAX_AGE_MS`. Slow latency pauses
+    /// entries, which stops new samples, so the pause has to age out.
+    pub fn expire(&mut self, now_ms: u64) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|(recv, _)| now_ms.saturating_sub(*recv) > LATENCY_MAX_AGE_MS)
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn mean_ms(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        Some(self.samples.iter().map(|(_, l)| l).sum::<u64>() as f64 / self.samples.len() as f64)
+    }
+
+    /// True once enough orders are sampled and their mean is over budget.
+    pub fn exceeded(&self, max_latency_ms: u64) -> bool {
+        self.samples.len() >= LATENCY_MIN_SAMPLES
+            && self.mean_ms().is_some_and(|m| m > max_latency_ms as f64)
+    }
+}
+
+/// Latency guard: while the rolling order latency is over budget, skip
+/// entries and adds but let position-reducing orders out.
+pub fn latency_allows_order(
+    latency_slow: bool,
+    action: Action,
+    position: f64,
+    strategy_id: &str,
+) -> bool {
+    if !latency_slow || action.reduces(position) {
+        return true;
+    }
+    json_log_at(
+        Level::Debug,
+        "risk_guard",
+        obj(&[
+            ("check", v_str("order_latency")),
+            ("result", v_str("skip_entry")),
+            ("strategy", v_str(strategy_id)),
+        ]),
+    );
+    false
+}
+
+/// Apply venue fills for orders the WAL shows as placed but never filled: they
+/// executed while the bot was down. Each applied fill is logged, written to
+/// the WAL and registered with `order_book` under the `trade-<id>` key the
+/// user stream uses, so a replay of the same trade after startup is dropped
+/// as a duplicate. Returns client ids now fully filled.
+pub fn reconcile_startup_fills(
+    adapter: &mut dyn UnifiedAdapter,
+    recovery: &RecoveryState,
+    strategies: &mut [StrategyInstance],
+    order_book: &mut OrderBook,
+    wal: &mut Wal,
+) -> Vec<String> {
+    let mut filled = Vec::new();
+    for pending in &recovery.pending_orders {
+        let (Some(client_id), Some(strategy_id)) = (&pending.client_order_id, &pending.strategy_id)
+        else {
+            continue;
+        };
+        let Some(inst) = strategies.iter_mut().find(|s| &s.id == strategy_id) else {
+            continue;
+        };
+        let venue_status = adapter
+            .fetch_order_status(&pending.symbol, client_id)
+            .unwrap_or(OrderStatus::Unknown);
+        json_log(
+            "reconcile",
+            obj(&[
+                ("status", v_str("startup_pending")),
+                ("strategy", v_str(strategy_id)),
+                ("client_order_id", v_str(client_id)),
+                ("venue_status", v_str(venue_status.as_str())),
+            ]),
+        );
+        let fills = match adapter.fetch_order_fills(&pending.symbol, client_id) {
+            Ok(f) => f,
+            Err(err) => {
+                json_log(
+                    "reconcile",
+                    obj(&[
+                        ("status", v_str("startup_fills_error")),
+                        ("client_order_id", v_str(client_id)),
+                        ("error", v_str(&err)),
+                    ]),
+                );
+                continue;
+            }
+        };
+        order_book.ensure(client_id, pending.qty.abs());
+        for fill in fills {
+            let fill_id = format!("trade-{}", fill.fill_id);
+            if !order_book.first_delivery(&fill_id) {
+                continue;
+            }
+            // The venue filled it, so it was accepted: recovered orders
+            // start out New and would ignore the fill otherwise
+            if order_book.orders[client_id].state == OrderState::New {
+                let _ = order_book.apply(client_id, Event::Submit);
+                let _ = order_book.apply(
+                    client_id,
+                    Event::Ack {
+                        order_id: fill.order_id.clone(),
+                    },
+                );
+            }
+            let first_fill = order_book.orders[client_id].filled_qty <= 0.0;
+            let _ = order_book.apply(
+                client_id,
+                Event::Fill {
+                    fill_id: fill_id.clone(),
+                    qty: fill.qty.abs(),
+                    price: fill.price,
+                },
+            );
+            let position_before = inst.state.portfolio.position;
+            let applied = crate::state::Fill {
+                price: fill.price,
+                qty: fill.qty,
+                fee: fill.fee,
+                ts: fill.ts,
+            };
+            let realized = inst.state.portfolio.apply_fill(applied);
+            inst.state.count_tranche(position_before, first_fill);
+            inst.state
+                .metrics
+                .record_fill(realized, &applied, inst.state.portfolio.position);
+            inst.state.last_trade_ts = fill.ts;
+            let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Fill {
+                ts: fill.ts,
+                intent_id: pending.intent_id.clone(),
+                params_hash: params_hash(client_id),
+                price: fill.price,
+                qty: fill.qty,
+                fee: fill.fee,
+                fsync: true,
+            });
+            json_log(
+                "reconcile",
+                obj(&[
+                    ("status", v_str("startup_fill")),
+                    ("strategy", v_str(strategy_id)),
+                    ("client_order_id", v_str(client_id)),
+                    ("fill_id", v_str(&fill_id)),
+                    ("qty", v_num(fill.qty)),
+                    ("price", v_num(fill.price)),
+                    ("position_before", v_num(position_before)),
+                    ("position_after", v_num(inst.state.portfolio.position)),
+                ]),
+            );
+        }
+        if order_book.orders[client_id].state == OrderState::Filled {
+            filled.push(client_id.clone());
+        }
+    }
+    filled
+}
+
+/// Flag ensemble members whose persisted equity curves move together.
+fn log_ensemble_correlation(store: &StateStore) {
+    let Ok(curves) = store.equity_curves() else {
+        return;
+    };
+    for (a, b, r) in correlation_matrix(&curves).correlated_pairs(CORRELATION_FLAG) {
+        json_log(
+            "metrics",
+            obj(&[
+                ("status", v_str("correlated_strategies")),
+                ("strategy_a", v_str(&a)),
+                ("strategy_b", v_str(&b)),
+                ("correlation", v_num(r)),
+            ]),
+        );
+    }
+}
+
+/// Final checkpoint on SIGINT/SIGTERM: WAL snapshot per strategy, SQLite persist,
+/// and a log line per outstanding order for manual review. Returns snapshots written.
+pub fn shutdown_snapshot(
+    ts: u64,
+    strategies: &[StrategyInstance],
+    pending_by_client: &HashMap<String, PendingMeta>,
+    wal: &mut Wal,
+    store: &mut StateStore,
+) -> anyhow::Result<usize> {
+    let mut written = 0;
+    for inst in strategies {
+        wal.write_snapshot(&inst.id, &inst.state)?;
+        written += 1;
+    }
+    store.persist_snapshot(ts, strategies)?;
+    log_ensemble_correlation(store);
+    for (client_id, meta) in pending_by_client {
+        json_log(
+            "shutdown",
+            obj(&[
+                ("status", v_str("pending_order_outstanding")),
+                ("client_order_id", v_str(client_id)),
+                ("strategy_id", v_str(&meta.strategy_id)),
+                ("intent_id", v_str(&meta.intent_id)),
+                ("order_id", v_str(meta.order_id.as_deref().unwrap_or(""))),
+                ("placed_ts", v_num(meta.placed_ts as f64)),
+            ]),
+        );
+    }
+    json_log(
+        "shutdown",
+        obj(&[
+            ("status", v_str("snapshot_written")),
+            ("strategies", v_num(written as f64)),
+            ("pending_orders", v_num(pending_by_client.len() as f64)),
+        ]),
+    );
+    Ok(written)
+}
+
+/// Resolves on the first SIGINT or SIGTERM.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Counts submissions so tests can assert nothing reached the venue.
+    struct CountingAdapter {
+        placed: usize,
+    }
+
+    impl UnifiedAdapter for CountingAdapter {
+        fn place_order(&mut self, req: OrderRequest) -> Result<OrderResponse, String> {
+            self.placed += 1;
+            Ok(OrderResponse {
+                order_id: format!("EX-{}", req.client_id),
+                status: "NEW".to_string(),
+            })
+        }
+
+        fn cancel_order(&mut self, _order_id: &str) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn cancel_all(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn order(client_id: &str) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side: crate::adapter::types::Side::Buy,
+            order_type: crate::adapter::types::OrderType::Market,
+            price: None,
+            qty: 0.01,
+            client_id: client_id.to_string(),
+            reduce_only: false,
+            post_only: false,
+        }
+    }
+
+    #[test]
+    fn test_fit_to_contract_rounds_and_enforces_min_notional() {
+        let doge = ExchangeFilters {
+            tick_size: 0.00001,
+            step_size: 1.0,
+            min_notional: 5.0,
+        };
+        let entry = Action::Buy { qty: 120.7 };
+        assert_eq!(fit_to_contract(&doge, entry, 120.7, 0.1, "s"), Some(120.0));
+        // 40 DOGE at 0.1 is $4: too small to open
+        assert_eq!(fit_to_contract(&doge, entry, 40.0, 0.1, "s"), None);
+        // ...but a close of the same size still goes out
+        assert_eq!(
+            fit_to_contract(&doge, Action::Close, 40.0, 0.1, "s"),
+            Some(40.0)
+        );
+        // Under one lot rounds to nothing
+        assert_eq!(fit_to_contract(&doge, Action::Close, 0.4, 0.1, "s"), None);
+    }
+
+    #[test]
+    fn test_sent_action_executes_the_rounded_qty() {
+        let lots = ExchangeFilters {
+            tick_size: 0.01,
+            step_size: 0.001,
+            min_notional: 5.0,
+        };
+        // Entry of 0.01537 goes out as 0.015
+        let entry = Action::Buy { qty: 0.01537 };
+        let qty = fit_to_contract(&lots, entry, 0.01537, 50_000.0, "s").unwrap();
+        assert!(matches!(sent_action(entry, 0.0, qty), Action::Buy { qty: q } if q == qty));
+        // Closing a 0.0153 long sends 0.015 and leaves the lot remainder
+        let qty = fit_to_contract(&lots, Action::Close, 0.0153, 50_000.0, "s").unwrap();
+        assert!(matches!(
+            sent_action(Action::Close, 0.0153, qty),
+            Action::Sell { qty: q } if q == qty
+        ));
+        assert!(matches!(
+            sent_action(Action::Close, -0.0153, qty),
+            Action::Buy { qty: q } if q == qty
+        ));
+    }
+
+    #[test]
+    fn test_close_orders_are_reduce_only() {
+        let close = order_request("BTCUSDT", Action::Close, 0.3, 0.3, "C-1", None, false);
+        assert!(close.reduce_only);
+        assert!(matches!(close.side, Side::Sell));
+        assert!(matches!(close.order_type, OrderType::Market));
+        let cover = order_request(
+            "BTCUSDT",
+            Action::Close,
+            -0.3,
+            0.3,
+            "C-2",
+            Some(100.0),
+            false,
+        );
+        assert!(cover.reduce_only);
+        assert!(matches!(cover.side, Side::Buy));
+        assert!(matches!(cover.order_type, OrderType::Limit));
+
+        let entry = order_request(
+            "BTCUSDT",
+            Action::Sell { qty: 0.1 },
+            0.0,
+            0.1,
+            "C-3",
+            None,
+            false,
+        );
+        assert!(!entry.reduce_only);
+        assert!(!entry.post_only);
+    }
+
+    #[test]
+    fn test_stop_loss_crosses_while_patient_entry_rests_as_maker() {
+        let mut cfg = Config::from_env();
+        cfg.maker_offset_bps = 5.0;
+        let filters = ExchangeFilters {
+            tick_size: 0.01,
+            step_size: 0.001,
+            min_notional: 5.0,
+        };
+        let order = |cfg: &Config, action: Action, position: f64| {
+            let urgency = action.urgency(position);
+            let (price, post_only) = execution_intent(cfg, &filters, action, urgency, 100.0, true);
+            order_request("BTCUSDT", action, position, 0.1, "C-1", price, post_only)
+        };
+
+        // A stop-loss exit is a Close: urgent, so taker
+        let stop = order(&cfg, Action::Close, 0.1);
+        assert!(matches!(stop.order_type, OrderType::Market));
+        assert!(!stop.post_only && stop.price.is_none());
+
+        // Patient entries rest 5 bps inside the close, post-only
+        let long = order(&cfg, Action::Buy { qty: 0.1 }, 0.0);
+        assert!(matches!(long.order_type, OrderType::Limit));
+        assert!(long.post_only);
+        assert!((long.price.unwrap() - 99.95).abs() < 1e-9);
+        let short = order(&cfg, Action::Sell { qty: 0.1 }, 0.0);
+        assert!(short.post_only);
+        assert!((short.price.unwrap() - 100.05).abs() < 1e-9);
+
+        // A partial scale-out reduces the long, so it crosses too
+        let trim = order(&cfg, Action::Sell { qty: 0.05 }, 0.1);
+        assert!(matches!(trim.order_type, OrderType::Market));
+        assert!(!trim.post_only);
+        // A strategy can mark an entry urgent
+        let (price, post_only) = execution_intent(
+            &cfg,
+            &filters,
+            Action::Buy { qty: 0.1 },
+            Urgency::Urgent,
+            100.0,
+            true,
+        );
+        assert!(price.is_none() && !post_only);
+
+        // Offset off: entries go out at market as before
+        cfg.maker_offset_bps = 0.0;
+        let plain = order(&cfg, Action::Buy { qty: 0.1 }, 0.0);
+        assert!(matches!(plain.order_type, OrderType::Market));
+        assert!(!plain.post_only);
+    }
+
+    #[test]
+    fn test_acked_order_not_resubmitted_after_wal_replay() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("bot.wal");
+        let wal_path = wal_path.to_str().unwrap();
+
+        // Session 1: order placed and acked, then the process dies before any fill
+        {
+            let mut wal = Wal::open(wal_path).unwrap();
+            let mut book = OrderBook::new();
+            let mut adapter = CountingAdapter { placed: 0 };
+            let resp = place_order_once(&mut adapter, &mut book, &mut wal, "I-1", order("CID-1"));
+            assert!(matches!(resp, Some(Ok(_))));
+            assert_eq!(adapter.placed, 1);
+        }
+
+        // Session 2: rebuild the order book from the WAL and retry the same client id
+        let recovery = Wal::recover(wal_path).unwrap();
+        let mut book = OrderBook::new();
+        for client_id in recovery.acked_orders.keys() {
+            book.mark_acked(client_id);
+        }
+        let mut wal = Wal::open(wal_path).unwrap();
+        let mut adapter = CountingAdapter { placed: 0 };
+        let resp = place_order_once(&mut adapter, &mut book, &mut wal, "I-1", order("CID-1"));
+        assert!(resp.is_none());
+        assert_eq!(adapter.placed, 0);
+
+        // A fresh client id still goes through
+        let resp = place_order_once(&mut adapter, &mut book, &mut wal, "I-2", order("CID-2"));
+        assert!(matches!(resp, Some(Ok(_))));
+        assert_eq!(adapter.placed, 1);
+    }
+
+    #[test]
+    fn test_dumped_view_round_trips() {
+        let view = MarketView {
+            symbol: "ETHUSDT",
+            last: crate::strategy::Candle {
+                ts: 1_700_000_100,
+                o: 2_000.5,
+                h: 2_010.25,
+                l: 1_990.0,
+                c: 2_005.125,
+                v: 42.0,
+            },
+            indicators: crate::strategy::IndicatorSnapshot {
+                ema_fast: 2_003.1,
+                ema_slow: 1_998.7,
+                z_momentum: -1.234_567_890_123,
+                atr: 12.5,
+                samples: 500,
+                ..Default::default()
+            },
+            aux: crate::strategy::MarketAux {
+                funding_rate: 0.000_125,
+                has_funding: true,
+                next_funding_ts: 1_700_028_800,
+                ..Default::default()
+            },
+        };
+        let record = view_record("mom-1", &view, Action::Buy { qty: 0.001 });
+        // Through text, as it would come back out of the log file
+        let line = serde_json::Value::Object(record).to_string();
+        let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(parsed["strategy_id"], "mom-1");
+        assert_eq!(parsed["ts"].as_f64(), Some(1_700_000_100.0));
+        assert_eq!(parsed["action"], "Buy { qty: 0.001 }");
+        let restored = <MarketView as serde::Deserialize>::deserialize(&parsed["view"]).unwrap();
+        assert_eq!(restored, view);
+    }
+
+    #[test]
+    fn test_trade_candles_need_binance() {
+        let mut cfg = Config::from_env();
+        cfg.trade_candles = false;
+        assert!(check_candle_source(&cfg, ExchangeKind::Kraken).is_ok());
+        cfg.trade_candles = true;
+        assert!(check_candle_source(&cfg, ExchangeKind::Binance).is_ok());
+        let err = check_candle_source(&cfg, ExchangeKind::Kraken).unwrap_err();
+        assert!(err.contains("CANDLE_SOURCE"), "{err}");
+    }
+
+    #[test]
+    fn test_rounding_residuals_accumulate_until_threshold() {
+        let mut cfg = Config::from_env();
+        cfg.rounding_drift_notional = 1.0;
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        let inst = &mut strategies[0];
+        let filters = ExchangeFilters {
+            tick_size: 0.01,
+            step_size: 0.001,
+            min_notional: 0.0,
+        };
+        let price = 100.0;
+        let buy = Action::Buy { qty: 0.0029 };
+        // Each entry loses 0.0009 to the 0.001 step: 0.09 notional a time
+        let mut tripped_at = None;
+        for i in 1..=20 {
+            let sent = fit_to_contract(&filters, buy, 0.0029, price, &inst.id).unwrap();
+            assert!((sent - 0.002).abs() < 1e-12);
+            if track_rounding(&cfg, inst, 0.0029, sent, price) {
+                tripped_at = Some(i);
+                break;
+            }
+        }
+        // 11 * 0.09 = 0.99 stays under 1.0; the 12th residual crosses it
+        assert_eq!(tripped_at, Some(12));
+        assert_eq!(inst.state.rounding_residual, 0.0);
+
+        // Sells count against buys: balanced rounding never trips
+        for _ in 0..50 {
+            assert!(!track_rounding(&cfg, inst, 0.0029, 0.002, price));
+            assert!(!track_rounding(&cfg, inst, -0.0029, -0.002, price));
+        }
+        assert!(inst.state.rounding_residual.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rounding_residual_measures_the_filled_qty() {
+        let dir = TempDir::new().unwrap();
+        let mut wal = Wal::open(dir.path().join("bot.wal").to_str().unwrap()).unwrap();
+        let mut audit = AuditTrail::open(dir.path().join("audit.jsonl").to_str().unwrap()).unwrap();
+        let cfg = Config::from_env();
+        let market = MarketState::new(cfg.clone());
+        let mut circuit = CircuitBreaker::new(5);
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        let mut book = OrderBook::new();
+        // Asked for 0.0029, rounded to 0.002 on the way out
+        book.ensure("CID-1", 0.002);
+        book.apply("CID-1", Event::Submit).unwrap();
+        book.apply(
+            "CID-1",
+            Event::Ack {
+                order_id: "EX-1".to_string(),
+            },
+        )
+        .unwrap();
+        let mut pending = HashMap::from([(
+            "CID-1".to_string(),
+            PendingMeta {
+                strategy_id: strategies[0].id.clone(),
+                symbol: strategies[0].symbol.clone(),
+                intent_id: "I-1".to_string(),
+                placed_ts: 0,
+                order_id: None,
+                intended_qty: -0.0029,
+            },
+        )]);
+        let (tx, mut rx) = mpsc::channel(4);
+        // A partial fill is not a rounding loss; the whole order is measured
+        // once it is Filled: intended minus filled, signed like the order
+        for (fill_id, qty, residual) in [("t-1", 0.0012, 0.0), ("t-2", 0.0008, -0.0009)] {
+            tx.try_send(FillEvent {
+                client_id: "CID-1".to_string(),
+                order_id: "EX-1".to_string(),
+                fill_id: fill_id.to_string(),
+                price: 100.0,
+                qty,
+                fee: 0.0,
+                ts: 1_000,
+                side: "SELL".to_string(),
+            })
+            .unwrap();
+            process_fills(
+                &mut rx,
+                &mut pending,
+                &mut strategies,
+                &mut book,
+                FillSinks {
+                    wal: &mut wal,
+                    audit: &mut audit,
+                    circuit: &mut circuit,
+                },
+                &market,
+                &cfg,
+            );
+            let got = strategies[0].state.rounding_residual;
+            assert!((got - residual).abs() < 1e-12, "{fill_id}: {got}");
+        }
+    }
+
+    #[test]
+    fn test_shadow_strategy_updates_virtual_equity_without_placing() {
+        let dir = TempDir::new().unwrap();
+        let mut wal = Wal::open(dir.path().join("bot.wal").to_str().unwrap()).unwrap();
+        let mut book = OrderBook::new();
+        let mut adapter = CountingAdapter { placed: 0 };
+        let mut cfg = Config::from_env();
+        cfg.shadow_strategies = vec!["mom-1".to_string()];
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        assert!(!strategies[0].shadow && strategies[1].shadow);
+
+        let bar = crate::strategy::Candle {
+            ts: 10_000,
+            o: 100.0,
+            h: 101.0,
+            l: 99.0,
+            c: 100.0,
+            v: 1_000.0,
+        };
+        let starting = strategies[1].state.portfolio.equity;
+        // Mirror the main order path: shadow instances stop before the venue
+        for inst in strategies.iter_mut().take(2) {
+            let buy = Action::Buy { qty: 0.5 };
+            if shadow_execute(&cfg, inst, buy, 0.5, &bar) {
+                continue;
+            }
+            let cid = format!("CID-{}", inst.id);
+            let _ = place_order_once(&mut adapter, &mut book, &mut wal, "I-1", order(&cid));
+        }
+        assert_eq!(adapter.placed, 1);
+
+        let shadow = &strategies[1].state;
+        assert_eq!(shadow.portfolio.position, 0.5);
+        assert!(
+            shadow.portfolio.entry_price > 100.0,
+            "paper slippage applies"
+        );
+        // Fee and slippage come straight off virtual equity
+        assert!(shadow.portfolio.equity < starting);
+        assert_eq!(shadow.last_trade_ts, 10_000);
+        assert_eq!(strategies[0].state.portfolio.position, 0.0);
+    }
+
+    /// Answers each submission after a delay, tracking how many overlap.
+    struct SlowVenue {
+        in_flight: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        peak: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl UnifiedAdapter for SlowVenue {
+        fn place_order(&mut self, _req: OrderRequest) -> Result<OrderResponse, String> {
+            Err("sequential path not expected".to_string())
+        }
+
+        fn submit_order(
+            &self,
+            req: &OrderRequest,
+        ) -> Option<crate::adapter::unified::OrderFuture<'_>> {
+            use std::sync::atomic::Ordering::SeqCst;
+            // Later orders answer first, so completion order is reversed
+            let n: u64 = req.client_id.trim_start_matches("CID-").parse().unwrap();
+            let delay = std::time::Duration::from_millis(80 - 10 * n);
+            let client_id = req.client_id.clone();
+            let (in_flight, peak) = (self.in_flight.clone(), self.peak.clone());
+            Some(Box::pin(async move {
+                let now = in_flight.fetch_add(1, SeqCst) + 1;
+                peak.fetch_max(now, SeqCst);
+                tokio::time::sleep(delay).await;
+                in_flight.fetch_sub(1, SeqCst);
+                Ok(OrderResponse {
+                    order_id: format!("EX-{}", client_id),
+                    status: "NEW".to_string(),
+                })
+            }))
+        }
+
+        fn cancel_order(&mut self, _order_id: &str) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn cancel_all(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+    }
+
+    fn job(index: usize) -> OrderJob {
+        let client_id = format!("CID-{}", index);
+        OrderJob {
+            index,
+            intent_id: format!("I-{}", index),
+            client_id: client_id.clone(),
+            action: Action::Buy { qty: 0.01 },
+            req: order(&client_id),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orders_placed_concurrently_apply_in_job_order() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("bot.wal");
+        let wal_path = wal_path.to_str().unwrap();
+        let mut wal = Wal::open(wal_path).unwrap();
+        let mut book = OrderBook::new();
+        let peak = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let mut venue = SlowVenue {
+            in_flight: Default::default(),
+            peak: peak.clone(),
+        };
+
+        let started = std::time::Instant::now();
+        let jobs: Vec<OrderJob> = (0..8).map(job).collect();
+        let outcomes = place_orders(&mut venue, &mut book, &mut wal, jobs, 4).await;
+        // Two waves of at most 80ms each, against 360ms one at a time
+        assert!(started.elapsed() < std::time::Duration::from_millis(300));
+        assert_eq!(peak.load(std::sync::atomic::Ordering::SeqCst), 4);
+
+        let indices: Vec<usize> = outcomes.iter().map(|o| o.job.index).collect();
+        assert_eq!(indices, (0..8).collect::<Vec<_>>());
+        assert!(outcomes.iter().all(|o| matches!(o.resp, Some(Ok(_)))));
+        assert!((0..8).all(|i| book.is_acked(&format!("CID-{}", i))));
+        drop(wal);
+        let recovery = Wal::recover(wal_path).unwrap();
+        assert_eq!(recovery.acked_orders.len(), 8);
+
+        // Acked ids are suppressed on a second pass, and nothing is sent
+        let mut wal = Wal::open(wal_path).unwrap();
+        let again = place_orders(&mut venue, &mut book, &mut wal, vec![job(3)], 4).await;
+        assert!(again[0].resp.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_blocking_adapter_places_one_at_a_time() {
+        let dir = TempDir::new().unwrap();
+        let mut wal = Wal::open(dir.path().join("bot.wal").to_str().unwrap()).unwrap();
+        let mut book = OrderBook::new();
+        let mut adapter = CountingAdapter { placed: 0 };
+        let jobs: Vec<OrderJob> = (0..3).map(job).collect();
+        let outcomes = place_orders(&mut adapter, &mut book, &mut wal, jobs, 4).await;
+        assert_eq!(adapter.placed, 3);
+        assert_eq!(outcomes.len(), 3);
+        assert!(book.is_acked("CID-2"));
+    }
+
+    /// Reports canned executions per client id, as the venue would after a restart.
+    struct FillsAdapter {
+        fills: HashMap<String, Vec<crate::adapter::types::FillEvent>>,
+    }
+
+    impl UnifiedAdapter for FillsAdapter {
+        fn place_order(&mut self, _req: OrderRequest) -> Result<OrderResponse, String> {
+            Err("not used".to_string())
+        }
+
+        fn cancel_order(&mut self, _order_id: &str) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn cancel_all(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn fetch_order_fills(
+            &mut self,
+            _symbol: &str,
+            client_id: &str,
+        ) -> Result<Vec<crate::adapter::types::FillEvent>, String> {
+            Ok(self.fills.get(client_id).cloned().unwrap_or_default())
+        }
+    }
+
+    #[test]
+    fn test_startup_reconcile_applies_fill_missed_while_down() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("bot.wal");
+        let wal_path = wal_path.to_str().unwrap();
+        let mut strategies = StrategyInstance::build_default_set(Config::from_env());
+        let strategy_id = strategies[0].id.clone();
+
+        // Session 1: order placed, then the process dies before the fill arrives
+        {
+            let mut wal = Wal::open(wal_path).unwrap();
+            wal.append_entry(&crate::reliability::wal::WalEntry::PlaceOrder {
+                ts: 1_000,
+                intent_id: "I-1".to_string(),
+                strategy_id: Some(strategy_id.clone()),
+                client_order_id: Some("CID-1".to_string()),
+                params_hash: params_hash("CID-1"),
+                symbol: "BTCUSDT".to_string(),
+                side: "buy".to_string(),
+                qty: 0.01,
+                reason: None,
+                fsync: true,
+            })
+            .unwrap();
+        }
+
+        // Session 2: the venue reports a partial execution the WAL never saw
+        let recovery = Wal::recover(wal_path).unwrap();
+        assert_eq!(recovery.pending_orders.len(), 1);
+        let mut adapter = FillsAdapter {
+            fills: HashMap::from([(
+                "CID-1".to_string(),
+                vec![crate::adapter::types::FillEvent {
+                    order_id: "EX-1".to_string(),
+                    fill_id: "T-1".to_string(),
+                    qty: 0.004,
+                    price: 100.0,
+                    fee: 0.001,
+                    ts: 1_005,
+                }],
+            )]),
+        };
+        let mut order_book = OrderBook::new();
+        let mut wal = Wal::open(wal_path).unwrap();
+        let filled = reconcile_startup_fills(
+            &mut adapter,
+            &recovery,
+            &mut strategies,
+            &mut order_book,
+            &mut wal,
+        );
+
+        // Still working, so the order stays pending
+        assert!(filled.is_empty());
+        assert!((strategies[0].state.portfolio.position - 0.004).abs() < 1e-12);
+        assert_eq!(strategies[0].state.portfolio.entry_price, 100.0);
+        assert!(strategies[1..]
+            .iter()
+            .all(|s| s.state.portfolio.position == 0.0));
+        assert_eq!(
+            order_book.orders["CID-1"].state,
+            OrderState::PartiallyFilled
+        );
+
+        // The poll fallback replays the same trade once the bot is up: it is
+        // dropped, while the rest of the order still applies
+        let cfg = Config::from_env();
+        let mut pending_by_client = HashMap::from([(
+            "CID-1".to_string(),
+            PendingMeta {
+                strategy_id: strategy_id.clone(),
+                symbol: "BTCUSDT".to_string(),
+                intent_id: "I-1".to_string(),
+                placed_ts: 1_000,
+                order_id: Some("EX-1".to_string()),
+                intended_qty: 0.0,
+            },
+        )]);
+        let (tx, mut fill_rx) = mpsc::channel(4);
+        for (trade, qty) in [("trade-T-1", 0.004), ("trade-T-2", 0.006)] {
+            tx.try_send(FillEvent {
+                client_id: "CID-1".to_string(),
+                order_id: "EX-1".to_string(),
+                fill_id: trade.to_string(),
+                price: 100.0,
+                qty,
+                fee: 0.0,
+                ts: 1_010,
+                side: "BUY".to_string(),
+            })
+            .unwrap();
+        }
+        let mut audit = AuditTrail::open(dir.path().join("audit.jsonl").to_str().unwrap()).unwrap();
+        let mut circuit = CircuitBreaker::new(3);
+        let market = MarketState::new(cfg.clone());
+        process_fills(
+            &mut fill_rx,
+            &mut pending_by_client,
+            &mut strategies,
+            &mut order_book,
+            FillSinks {
+                wal: &mut wal,
+                audit: &mut audit,
+                circuit: &mut circuit,
+            },
+            &market,
+            &cfg,
+        );
+        assert!((strategies[0].state.portfolio.position - 0.01).abs() < 1e-12);
+        assert!(pending_by_client.is_empty());
+
+        // Every fill is in the WAL, so the order is no longer pending on the next start
+        let again = Wal::recover(wal_path).unwrap();
+        assert!(again.pending_orders.is_empty());
+        assert_eq!(again.fills_since_snapshot.len(), 2);
+    }
+
+    /// Answers status queries from a canned table and records cancels.
+    struct StatusAdapter {
+        statuses: HashMap<String, OrderStatus>,
+        cancelled: Vec<String>,
+    }
+
+    impl UnifiedAdapter for StatusAdapter {
+        fn place_order(&mut self, _req: OrderRequest) -> Result<OrderResponse, String> {
+            Err("not used".to_string())
+        }
+
+        fn cancel_order(&mut self, order_id: &str) -> Result<(), String> {
+            self.cancelled.push(order_id.to_string());
+            Ok(())
+        }
+
+        fn cancel_all(&mut self) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn fetch_order_status(
+            &mut self,
+            _symbol: &str,
+            client_id: &str,
+        ) -> Result<OrderStatus, String> {
+            self.statuses
+                .get(client_id)
+                .copied()
+                .ok_or_else(|| "unknown order".to_string())
+        }
+    }
+
+    #[test]
+    fn test_redelivered_fill_after_timeout_applies_once() {
+        let dir = TempDir::new().unwrap();
+        let mut wal = Wal::open(dir.path().join("bot.wal").to_str().unwrap()).unwrap();
+        let mut audit = AuditTrail::open(dir.path().join("audit.jsonl").to_str().unwrap()).unwrap();
+        let cfg = Config::from_env();
+        let market = MarketState::new(cfg.clone());
+        let mut circuit = CircuitBreaker::new(5);
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        let mut book = OrderBook::new();
+        book.ensure("CID-1", 0.01);
+        book.apply("CID-1", Event::Submit).unwrap();
+        book.apply(
+            "CID-1",
+            Event::Ack {
+                order_id: "EX-1".to_string(),
+            },
+        )
+        .unwrap();
+        book.apply("CID-1", Event::Timeout).unwrap();
+        let mut pending = HashMap::from([(
+            "CID-1".to_string(),
+            PendingMeta {
+                strategy_id: strategies[0].id.clone(),
+                symbol: strategies[0].symbol.clone(),
+                intent_id: "I-1".to_string(),
+                placed_ts: 0,
+                order_id: Some("EX-1".to_string()),
+                intended_qty: 0.01,
+            },
+        )]);
+
+        // The venue delivers the same execution twice after our timeout
+        let (tx, mut rx) = mpsc::channel(4);
+        for _ in 0..2 {
+            tx.try_send(FillEvent {
+                client_id: "CID-1".to_string(),
+                order_id: "EX-1".to_string(),
+                fill_id: "trade-7".to_string(),
+                price: 100.0,
+                qty: 0.01,
+                fee: 0.001,
+                ts: 1_000,
+                side: "BUY".to_string(),
+            })
+            .unwrap();
+        }
+        process_fills(
+            &mut rx,
+            &mut pending,
+            &mut strategies,
+            &mut book,
+            FillSinks {
+                wal: &mut wal,
+                audit: &mut audit,
+                circuit: &mut circuit,
+            },
+            &market,
+            &cfg,
+        );
+
+        assert_eq!(book.orders["CID-1"].state, OrderState::Canceled);
+        assert!((strategies[0].state.portfolio.position - 0.01).abs() < 1e-12);
+        assert_eq!(strategies[0].state.trades_today, 1);
+    }
+
+    #[test]
+    fn test_stale_cancel_checks_venue_status_first() {
+        let dir = TempDir::new().unwrap();
+        let mut wal = Wal::open(dir.path().join("bot.wal").to_str().unwrap()).unwrap();
+        let mut audit = AuditTrail::open(dir.path().join("audit.jsonl").to_str().unwrap()).unwrap();
+        let mut book = OrderBook::new();
+        let mut cfg = Config::from_env();
+        cfg.cancel_after_candles = 1;
+        let start = cfg.candles_to_secs(1) + 10;
+
+        let mut pending = HashMap::new();
+        for client_id in ["CID-open", "CID-filled", "CID-canceled", "CID-lost"] {
+            book.ensure(client_id, 0.01);
+            pending.insert(
+                client_id.to_string(),
+                PendingMeta {
+                    strategy_id: "mom-0".to_string(),
+                    symbol: "BTCUSDT".to_string(),
+                    intent_id: format!("I-{}", client_id),
+                    placed_ts: 0,
+                    order_id: Some(format!("EX-{}", client_id)),
+                    intended_qty: 0.01,
+                },
+            );
+        }
+        let mut adapter = StatusAdapter {
+            statuses: HashMap::from([
+                ("CID-open".to_string(), OrderStatus::Open),
+                ("CID-filled".to_string(), OrderStatus::Filled),
+                ("CID-canceled".to_string(), OrderStatus::Canceled),
+            ]),
+            cancelled: Vec::new(),
+        };
+
+        cancel_stale_orders(
+            start,
+            &cfg,
+            &mut adapter,
+            &mut pending,
+            &mut book,
+            &mut wal,
+            &mut audit,
+        );
+
+        // Open, and unqueryable, orders are cancelled on the venue
+        adapter.cancelled.sort();
+        assert_eq!(adapter.cancelled, vec!["EX-CID-lost", "EX-CID-open"]);
+        // Already cancelled on the venue: dropped without a cancel request
+        assert!(!pending.contains_key("CID-canceled"));
+        // Filled: left for the fill stream to settle
+        assert_eq!(pending.keys().collect::<Vec<_>>(), vec!["CID-filled"]);
+    }
+
+    #[test]
+    fn test_order_latency_window() {
+        assert_eq!(latency_ms(1_000, 1_250), 250);
+        // Receive stamped before send (clock step) is zero, not a wraparound
+        assert_eq!(latency_ms(1_000, 900), 0);
+
+        let mut window = LatencyWindow::default();
+        assert_eq!(window.mean_ms(), None);
+        assert_eq!(window.record(10_000, 10_400), 400);
+        assert_eq!(window.record(20_000, 20_600), 600);
+        // Over budget, but too few samples to trip
+        assert!(!window.exceeded(300));
+        window.record(30_000, 30_500);
+        assert_eq!(window.mean_ms(), Some(500.0));
+        assert!(window.exceeded(300));
+        assert!(!window.exceeded(500));
+
+        // Fast orders roll the slow ones out of the window
+        for i in 0..LATENCY_WINDOW as u64 {
+            window.record(i * 1_000, i * 1_000 + 50);
+        }
+        assert_eq!(window.mean_ms(), Some(50.0));
+        assert!(!window.exceeded(300));
+    }
+
+    #[test]
+    fn test_slow_latency_pauses_entries_until_it_ages_out() {
+        let mut window = LatencyWindow::default();
+        for i in 0..3 {
+            window.record(i * 1_000, i * 1_000 + 900);
+        }
+        assert!(window.exceeded(300));
+
+        // Paused: no entries or adds, but exits still go out
+        let slow = window.exceeded(300);
+        assert!(!latency_allows_order(
+            slow,
+            Action::Buy { qty: 0.01 },
+            0.0,
+            "s"
+        ));
+        assert!(!latency_allows_order(
+            slow,
+            Action::Buy { qty: 0.01 },
+            0.02,
+            "s"
+        ));
+        assert!(!latency_allows_order(
+            slow,
+            Action::Sell { qty: 0.05 },
+            0.02,
+            "s"
+        ));
+        assert!(latency_allows_order(slow, Action::Close, 0.02, "s"));
+        assert!(latency_allows_order(
+            slow,
+            Action::Sell { qty: 0.01 },
+            0.02,
+            "s"
+        ));
+        assert!(latency_allows_order(
+            slow,
+            Action::Buy { qty: 0.02 },
+            -0.02,
+            "s"
+        ));
+
+        // Still within the age limit: stays paused
+        window.expire(900 + LATENCY_MAX_AGE_MS);
+        assert!(window.exceeded(300));
+        // Once the slow samples age out, entries resume
+        window.expire(2_901 + LATENCY_MAX_AGE_MS);
+        assert_eq!(window.mean_ms(), None);
+        let slow = window.exceeded(300);
+        assert!(latency_allows_order(
+            slow,
+            Action::Buy { qty: 0.01 },
+            0.0,
+            "s"
+        ));
+    }
+
+    #[test]
+    fn test_prefill_warms_indicators() {
+        let cfg = Config::from_env();
+        let need = cfg.min_history();
+        let candles: Vec<Candle> = (0..=need)
+            .map(|i| {
+                let px = 100.0 + (i as f64 * 0.3).sin() * 2.0;
+                Candle {
+                    ts: 1_000 + i * cfg.candle_granularity,
+                    o: px,
+                    h: px + 0.5,
+                    l: px - 0.5,
+                    c: px,
+                    v: 10.0 + i as f64,
+                }
+            })
+            .collect();
+        let mut market = MarketState::new(cfg.clone());
+        let fed = prefill_market(&mut market, "BTCUSDT", candles);
+        assert_eq!(fed as u64, need);
+
+        let view = market.view("BTCUSDT");
+        assert!(cfg.is_warmed_up(view.indicators.samples));
+        assert!(view.indicators.ema_slow > 0.0);
+        assert!(view.indicators.vol > 0.0);
+        // The forming candle was left for the live loop
+        assert_eq!(view.last.ts, 1_000 + (need - 1) * cfg.candle_granularity);
+    }
+
+    #[test]
+    fn test_drift_throttle_scales_entries_only() {
+        let buy = Action::Buy { qty: 0.2 };
+        assert!(matches!(
+            drift_throttle(buy, 0.0, DriftSeverity::Elevated, "s"),
+            Action::Buy { qty } if (qty - 0.1).abs() < 1e-12
+        ));
+        assert!(matches!(
+            drift_throttle(Action::Sell { qty: 0.2 }, 0.0, DriftSeverity::Moderate, "s"),
+            Action::Sell { qty } if (qty - 0.1).abs() < 1e-12
+        ));
+        // Low drift is only logged
+        for calm in [DriftSeverity::None, DriftSeverity::Low] {
+            assert!(matches!(
+                drift_throttle(buy, 0.0, calm, "s"),
+                Action::Buy { qty } if qty == 0.2
+            ));
+        }
+        assert!(matches!(
+            drift_throttle(Action::Close, 0.0, DriftSeverity::Elevated, "s"),
+            Action::Close
+        ));
+        // Halting severities are left to the halt/close path
+        assert!(matches!(
+            drift_throttle(buy, 0.0, DriftSeverity::Severe, "s"),
+            Action::Buy { qty } if qty == 0.2
+        ));
+        // Trimming a long or covering a short goes out at full size
+        assert!(matches!(
+            drift_throttle(Action::Sell { qty: 0.2 }, 0.5, DriftSeverity::Elevated, "s"),
+            Action::Sell { qty } if qty == 0.2
+        ));
+        assert!(matches!(
+            drift_throttle(buy, -0.5, DriftSeverity::Elevated, "s"),
+            Action::Buy { qty } if qty == 0.2
+        ));
+        // Adding to the long is still scaled
+        assert!(matches!(
+            drift_throttle(buy, 0.5, DriftSeverity::Elevated, "s"),
+            Action::Buy { qty } if (qty - 0.1).abs() < 1e-12
+        ));
+    }
+
+    #[test]
+    fn test_reconcile_action_side_effects() {
+        // Two holders of BTCUSDT, 0.3 + 0.1 = 0.4 local; band = max(0.4 * 2%, 0.0005) = 0.008
+        let setup = |action: ReconcileAction| {
+            let mut cfg = Config::from_env();
+            cfg.reconcile_drift_pct = 0.02;
+            cfg.reconcile_drift_abs = 0.0005;
+            cfg.reconcile_action = action;
+            let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+            strategies.retain(|s| s.symbol == "BTCUSDT");
+            strategies.truncate(2);
+            strategies[0].state.portfolio.position = 0.3;
+            strategies[0].state.portfolio.entry_price = 50_000.0;
+            strategies[1].state.portfolio.position = 0.1;
+            strategies[1].state.portfolio.entry_price = 50_000.0;
+            (cfg, strategies)
+        };
+        let small = 0.404;
+        let large = 0.5;
+        const MARK: f64 = 50_000.0;
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("wal.log");
+        let mut wal = Wal::open(wal_path.to_str().unwrap()).unwrap();
+
+        let (cfg, mut s) = setup(ReconcileAction::LogOnly);
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", large, MARK, 2_000, &mut wal),
+            "drift_logged"
+        );
+        assert!(s.iter().all(|i| !i.state.trading_halted));
+        assert_eq!(s[0].state.portfolio.position, 0.3);
+
+        let (cfg, mut s) = setup(ReconcileAction::Halt);
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", small, MARK, 2_000, &mut wal),
+            "within_band"
+        );
+        assert!(s.iter().all(|i| !i.state.trading_halted));
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", large, MARK, 2_000, &mut wal),
+            "drift_halt"
+        );
+        assert!(s.iter().all(|i| i.state.trading_halted));
+        assert_eq!(s[0].state.portfolio.position, 0.3);
+
+        let (cfg, mut s) = setup(ReconcileAction::AutoCorrect);
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", small, MARK, 2_000, &mut wal),
+            "auto_corrected"
+        );
+        assert!(s.iter().all(|i| !i.state.trading_halted));
+        // 0.004 gap split 3:1
+        assert!((s[0].state.portfolio.position - 0.303).abs() < 1e-12);
+        assert!((s[1].state.portfolio.position - 0.101).abs() < 1e-12);
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", small, MARK, 2_000, &mut wal),
+            "in_sync"
+        );
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", large, MARK, 2_000, &mut wal),
+            "drift_halt"
+        );
+        assert!(s.iter().all(|i| i.state.trading_halted));
+        assert!((s[0].state.portfolio.position - 0.303).abs() < 1e-12);
+
+        // Nothing held locally: no entry price to attach a correction to
+        let (cfg, mut s) = setup(ReconcileAction::AutoCorrect);
+        for inst in s.iter_mut() {
+            inst.state.portfolio.position = 0.0;
+        }
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", 0.0004, MARK, 2_000, &mut wal),
+            "correct_skipped_flat"
+        );
+        assert!(s.iter().all(|i| i.state.portfolio.position == 0.0));
+
+        // A halt on one symbol leaves strategies on other symbols trading
+        let (cfg, mut s) = setup(ReconcileAction::Halt);
+        let mut eth = StrategyInstance::build_default_set(cfg.clone());
+        for inst in eth.iter_mut() {
+            inst.symbol = "ETHUSDT".to_string();
+        }
+        s.extend(eth);
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", large, MARK, 2_000, &mut wal),
+            "drift_halt"
+        );
+        assert!(s
+            .iter()
+            .all(|i| i.state.trading_halted == (i.symbol == "BTCUSDT")));
+    }
+
+    #[test]
+    fn test_settle_funding_only_on_boundary_candles() {
+        let mut cfg = Config::from_env();
+        cfg.funding_interval_secs = 28_800;
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        let symbol = strategies[0].symbol.clone();
+        strategies[0].state.portfolio.position = 0.5;
+        let cash = strategies[0].state.portfolio.cash;
+
+        let settle = |s: &mut [StrategyInstance], prev, ts| {
+            settle_funding(&cfg, s, &symbol, prev, ts, 0.0002, 100.0)
+        };
+        assert_eq!(settle(&mut strategies, 28_200, 28_500), 0.0);
+        assert!((settle(&mut strategies, 28_500, 28_800) + 0.01).abs() < 1e-12);
+        assert_eq!(settle(&mut strategies, 28_800, 29_100), 0.0);
+        assert!((strategies[0].state.portfolio.cash - (cash - 0.01)).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_spot_positions_never_settle_funding() {
+        let mut cfg = Config::from_env();
+        cfg.funding_interval_secs = 28_800;
+        cfg.market_type = MarketType::Spot;
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        let symbol = strategies[0].symbol.clone();
+        strategies[0].state.portfolio.position = 0.5;
+        let before = strategies[0].state.portfolio;
+
+        assert_eq!(
+            settle_funding(
+                &cfg,
+                &mut strategies,
+                &symbol,
+                28_500,
+                28_800,
+                0.0002,
+                100.0
+            ),
+            0.0
+        );
+        assert_eq!(strategies[0].state.portfolio.cash, before.cash);
+        assert_eq!(strategies[0].state.portfolio.equity, before.equity);
+    }
+
+    #[test]
+    fn test_auto_correct_books_a_fill_at_the_mark() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("wal.log");
+        let wal_path = wal_path.to_str().unwrap();
+        let mut cfg = Config::from_env();
+        cfg.reconcile_drift_pct = 0.02;
+        cfg.reconcile_action = ReconcileAction::AutoCorrect;
+        let mut s = StrategyInstance::build_default_set(cfg.clone());
+        s.retain(|i| i.symbol == "BTCUSDT");
+        s.truncate(1);
+        s[0].state.portfolio.apply_fill(crate::state::Fill {
+            price: 50_000.0,
+            qty: 0.3,
+            fee: 0.0,
+            ts: 1_000,
+        });
+        let mut wal = Wal::open(wal_path).unwrap();
+        wal.write_snapshot(&s[0].id, &s[0].state).unwrap();
+        let before = s[0].state.portfolio;
+
+        // The venue holds 0.004 more, marked at 51k
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", 0.304, 51_000.0, 2_000, &mut wal),
+            "auto_corrected"
+        );
+        let after = s[0].state.portfolio;
+        assert!((after.position - 0.304).abs() < 1e-12);
+        assert!((after.cash - (before.cash - 0.004 * 51_000.0)).abs() < 1e-6);
+        let entry = (0.3 * 50_000.0 + 0.004 * 51_000.0) / 0.304;
+        assert!((after.entry_price - entry).abs() < 1e-6);
+        // Buying at the mark leaves marked equity where it was
+        assert!((after.mark_equity(51_000.0) - before.mark_equity(51_000.0)).abs() < 1e-6);
+        // Recovery replays the synthetic fill, so the rebuild agrees
+        let recovery = Wal::recover(wal_path).unwrap();
+        assert!(recovery.check_consistency(&s[0].id, &after));
+
+        // Without a mark there is no price to book the gap at
+        assert_eq!(
+            apply_reconcile_drift(&cfg, &mut s, "BTCUSDT", 0.31, 0.0, 3_000, &mut wal),
+            "correct_skipped_no_mark"
+        );
+        assert_eq!(s[0].state.portfolio.position, after.position);
+    }
+
+    #[test]
+    fn test_reconcile_request_fires_once() {
+        let dir = TempDir::new().unwrap();
+        let mut cfg = Config::from_env();
+        cfg.reconcile_now_file = dir.path().join("RECONCILE").display().to_string();
+        assert!(!take_reconcile_request(&cfg));
+
+        std::fs::write(&cfg.reconcile_now_file, "").unwrap();
+        assert!(take_reconcile_request(&cfg));
+        assert!(!std::path::Path::new(&cfg.reconcile_now_file).exists());
+        assert!(!take_reconcile_request(&cfg));
+
+        // Disabled when unset
+        cfg.reconcile_now_file = String::new();
+        assert!(!take_reconcile_request(&cfg));
+    }
+
+    #[test]
+    fn test_reenable_file_clears_disabled_strategies() {
+        let dir = TempDir::new().unwrap();
+        let mut cfg = Config::from_env();
+        cfg.reenable_file = dir.path().join("REENABLE").display().to_string();
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        for inst in strategies.iter_mut() {
+            inst.state.disabled = true;
+            inst.state.metrics.record_trade(-1.0);
+        }
+        // No file: nothing changes
+        assert_eq!(apply_reenable_file(&cfg, &mut strategies), 0);
+
+        let target = strategies[1].id.clone();
+        std::fs::write(&cfg.reenable_file, format!("{}\n", target)).unwrap();
+        assert_eq!(apply_reenable_file(&cfg, &mut strategies), 1);
+        assert!(!strategies[1].state.disabled);
+        assert_eq!(strategies[1].state.metrics.rolling_profit_factor(1), None);
+        assert!(strategies[0].state.disabled);
+        assert!(!std::path::Path::new(&cfg.reenable_file).exists());
+
+        std::fs::write(&cfg.reenable_file, "").unwrap();
+        assert_eq!(
+            apply_reenable_file(&cfg, &mut strategies),
+            strategies.len() - 1
+        );
+        assert!(strategies.iter().all(|s| !s.state.disabled));
+    }
+
+    #[test]
+    fn test_threshold_file_reloads_strategy_configs() {
+        let dir = TempDir::new().unwrap();
+        let mut cfg = Config::from_env();
+        cfg.threshold_file = dir.path().join("thresholds.json").display().to_string();
+        let mut strategies = StrategyInstance::build_default_set(cfg.clone());
+        let mut mtime = None;
+        // No file yet
+        assert_eq!(reload_thresholds(&cfg, &mut strategies, &mut mtime), 0);
+
+        std::fs::write(
+            &cfg.threshold_file,
+            r#"{"entry_threshold": 2.5, "symbol": "ETHUSDT"}"#,
+        )
+        .unwrap();
+        let n = strategies.len();
+        assert_eq!(reload_thresholds(&cfg, &mut strategies, &mut mtime), n);
+        for inst in strategies.iter_mut() {
+            let scfg = inst.strategy.config_mut().unwrap();
+            assert_eq!(scfg.entry_threshold, 2.5);
+            assert_eq!(scfg.symbol, cfg.symbol);
+        }
+        // Unchanged mtime: not re-read
+        assert_eq!(reload_thresholds(&cfg, &mut strategies, &mut mtime), 0);
+
+        // A malformed edit keeps the running values
+        std::fs::write(&cfg.threshold_file, r#"{"entry_threshold": "#).unwrap();
+        mtime = None;
+        assert_eq!(reload_thresholds(&cfg, &mut strategies, &mut mtime), 0);
+        assert_eq!(
+            strategies[0].strategy.config_mut().unwrap().entry_threshold,
+            2.5
+        );
+    }
+
+    #[test]
+    fn test_order_interval_blocks_back_to_back_orders_per_symbol() {
+        let mut cfg = Config::from_env();
+        cfg.min_order_interval_secs = 60;
+        let buy = Action::Buy { qty: 0.01 };
+        let mut spacing = OrderSpacing::default();
+
+        assert!(spacing.allows(&cfg, "BTCUSDT", buy, 0.0, 1_000, "mom-0"));
+        spacing.record("BTCUSDT", 1_000);
+        // Back to back on the same symbol, even from another strategy
+        assert!(!spacing.allows(&cfg, "BTCUSDT", buy, 0.0, 1_030, "mom-1"));
+        assert!(!spacing.allows(&cfg, "BTCUSDT", buy, 0.02, 1_059, "mom-0"));
+        // Exits, partial ones included, and other symbols are unaffected
+        assert!(spacing.allows(&cfg, "BTCUSDT", Action::Close, 0.02, 1_030, "mom-0"));
+        let trim = Action::Sell { qty: 0.01 };
+        assert!(spacing.allows(&cfg, "BTCUSDT", trim, 0.02, 1_030, "mom-0"));
+        assert!(!spacing.allows(&cfg, "BTCUSDT", trim, 0.0, 1_030, "mom-0"));
+        assert!(spacing.allows(&cfg, "ETHUSDT", buy, 0.0, 1_030, "mom-0"));
+        // Spaced out, the next order passes
+        assert!(spacing.allows(&cfg, "BTCUSDT", buy, 0.0, 1_060, "mom-1"));
+
+        cfg.min_order_interval_secs = 0;
+        assert!(spacing.allows(&cfg, "BTCUSDT", buy, 0.0, 1_001, "mom-1"));
+    }
+
+    #[test]
+    fn test_wide_spread_suppresses_order() {
+        let mut cfg = Config::from_env();
+        cfg.max_liquidity_spread = 0.001;
+        let buy = Action::Buy { qty: 0.1 };
+        let tight = BookTop {
+            bid: 50_000.0,
+            ask: 50_010.0,
+        };
+        let wide = BookTop {
+            bid: 50_000.0,
+            ask: 50_500.0,
+        };
+        assert!(spread_allows_order(Some(&tight), buy, 0.0, &cfg, "s"));
+        assert!(!spread_allows_order(Some(&wide), buy, 0.0, &cfg, "s"));
+        assert!(!spread_allows_order(Some(&wide), buy, 0.1, &cfg, "s"));
+        assert!(!spread_allows_order(
+            Some(&wide),
+            Action::Sell { qty: 0.1 },
+            0.0,
+            &cfg,
+            "s"
+        ));
+        // Exits, partial ones included, and a missing book are never blocked
+        assert!(spread_allows_order(
+            Some(&wide),
+            Action::Close,
+            0.1,
+            &cfg,
+            "s"
+        ));
+        assert!(spread_allows_order(
+            Some(&wide),
+            Action::Sell { qty: 0.05 },
+            0.1,
+            &cfg,
+            "s"
+        ));
+        assert!(spread_allows_order(None, buy, 0.0, &cfg, "s"));
+        // A crossed or empty book counts as untradeable
+        let crossed = BookTop {
+            bid: 50_100.0,
+            ask: 50_000.0,
+        };
+        assert!(!spread_allows_order(Some(&crossed), buy, 0.0, &cfg, "s"));
+    }
+
+    #[test]
+    fn test_shutdown_snapshot_covers_all_strategies() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("bot.wal");
+        let db_path = dir.path().join("bot.sqlite");
+        let mut wal = Wal::open(wal_path.to_str().unwrap()).unwrap();
+        let mut store = StateStore::new(db_path.to_str().unwrap()).unwrap();
+        store.init().unwrap();
+
+        let mut strategies = StrategyInstance::build_default_set(Config::from_env());
+        for (i, inst) in strategies.iter_mut().enumerate() {
+            inst.state.portfolio.position = i as f64 * 0.1;
+        }
+        let mut pending = HashMap::new();
+        pending.insert(
+            "CID-mom-0-1-1".to_string(),
+            PendingMeta {
+                strategy_id: "mom-0".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                intent_id: "I-mom-0-1-1".to_string(),
+                placed_ts: 1,
+                order_id: None,
+                intended_qty: 0.0,
+            },
+        );
+
+        let written = shutdown_snapshot(1000, &strategies, &pending, &mut wal, &mut store).unwrap();
+        assert_eq!(written, strategies.len());
+
+        let recovery = Wal::recover(wal_path.to_str().unwrap()).unwrap();
+        for inst in &strategies {
+            let snap = recovery
+                .snapshots_by_strategy
+                .get(&inst.id)
+                .unwrap_or_else(|| panic!("missing snapshot for {}", inst.id));
+            assert_eq!(snap.position, inst.state.portfolio.position);
+        }
+    }
+
+    #[test]
+    fn test_feed_halt_lifts_when_data_recovers() {
+        let mut strategies = StrategyInstance::build_default_set(Config::from_env());
+        strategies.truncate(2);
+        // Already halted by a risk check before the outage
+        strategies[1].state.trading_halted = true;
+        let mut feed_halted = vec![false; 2];
+
+        apply_feed_halt(&mut strategies, &mut feed_halted, true);
+        apply_feed_halt(&mut strategies, &mut feed_halted, true);
+        assert!(strategies.iter().all(|s| s.state.trading_halted));
+
+        apply_feed_halt(&mut strategies, &mut feed_halted, false);
+        assert!(!strategies[0].state.trading_halted);
+        assert!(strategies[1].state.trading_halted);
+        assert_eq!(feed_halted, vec![false, false]);
+    }
+
+    #[test]
+    fn test_max_hold_clock_survives_restart() {
+        let dir = TempDir::new().unwrap();
+        let wal_path = dir.path().join("bot.wal");
+        let wal_path = wal_path.to_str().unwrap();
+        let mut cfg = Config::from_env();
+        cfg.global_max_hold_secs = 3_600;
+        let mut before = StrategyInstance::build_default_set(cfg.clone());
+        before[0].state.portfolio.position = 0.1;
+        before[0].state.position_opened_ts = 1_000;
+        {
+            let mut wal = Wal::open(wal_path).unwrap();
+            wal.write_snapshot(&before[0].id, &before[0].state).unwrap();
+            // A second strategy opens after the snapshot; replay dates it
+            wal.write_snapshot(&before[1].id, &before[1].state).unwrap();
+            wal.append_entry(&crate::reliability::wal::WalEntry::Fill {
+                ts: 2_000,
+                intent_id: format!("I-{}-2000-1", before[1].id),
+                params_hash: String::new(),
+                price: 100.0,
+                qty: 0.5,
+                fee: 0.0,
+                fsync: false,
+            })
+            .unwrap();
+        }
+
+        let mut after = StrategyInstance::build_default_set(cfg.clone());
+        Wal::recover(wal_path)
+            .unwrap()
+            .apply_to(&mut after, &HashMap::new(), false);
+        assert_eq!(after[0].state.position_opened_ts, 1_000);
+        assert_eq!(after[1].state.position_opened_ts, 2_000);
+
+        // Held since 1000, so the limit still closes an hour later
+        let risk = crate::risk::RiskEngine::new(cfg);
+        let inst = &mut after[0];
+        assert!(matches!(
+            risk.enforce_max_hold(&mut inst.state, Action::Hold, &inst.id, 4_600),
+            Action::Close
+        ));
     }
 }
@@ -10,7 +10,7 @@
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Map, Value};
-use std::fs::{create_dir_all, File};
+use std::fs::{create_dir_all, File, OpenOptions};
 use std::io::{BufWriter, Write};
 use std::path::PathBuf;
 use std::process;
@@ -34,18 +34,30 @@ pub enum Level {
 }
 
 impl Level {
-    pub fn from_env() -> Self {
-        match std::env::var("LOG_LEVEL").as_deref() {
-            Ok("trace") => Level::Trace,
-            Ok("debug") => Level::Debug,
-            Ok("info") => Level::Info,
-            Ok("warn") => Level::Warn,
-            Ok("error") => Level::Error,
-            Ok("fatal") => Level::Fatal,
-            _ => Level::Info,
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_lowercase().as_str() {
+            "trace" => Some(Level::Trace),
+            "debug" => Some(Level::Debug),
+            "info" => Some(Level::Info),
+            "warn" | "warning" => Some(Level::Warn),
+            "error" => Some(Level::Error),
+            "fatal" => Some(Level::Fatal),
+            _ => None,
         }
     }
 
+    pub fn from_env() -> Self {
+        std::env::var("LOG_LEVEL")
+            .ok()
+            .and_then(|v| Self::parse(&v))
+            .unwrap_or(Level::Info)
+    }
+
+    /// True when a record at this level clears the `min` threshold.
+    pub fn passes(self, min: Level) -> bool {
+        self >= min
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Level::Trace => "trace",
@@ -109,6 +121,12 @@ impl Domain {
 static LOG_SEQ: AtomicU64 = AtomicU64::new(0);
 static PROFILE_SEQ: AtomicU64 = AtomicU64::new(0);
 static RUN_CONTEXT: OnceLock<RunContext> = OnceLock::new();
+static MIN_LEVEL: OnceLock<Level> = OnceLock::new();
+
+/// `LOG_LEVEL` threshold, read once per process.
+fn min_level() -> Level {
+    *MIN_LEVEL.get_or_init(Level::from_env)
+}
 
 fn next_seq() -> u64 {
     LOG_SEQ.fetch_add(1, Ordering::SeqCst)
@@ -202,6 +220,154 @@ fn write_line(writer: &Mutex<BufWriter<File>>, line: &str) {
     }
 }
 
+// =============================================================================
+// Output sink (stdout and/or a size-rotated file)
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SinkKind {
+    Stdout,
+    File,
+    Both,
+}
+
+impl SinkKind {
+    pub fn from_env() -> Self {
+        match std::env::var("LOG_SINK").as_deref() {
+            Ok("file") => SinkKind::File,
+            Ok("both") => SinkKind::Both,
+            _ => SinkKind::Stdout,
+        }
+    }
+
+    fn stdout(&self) -> bool {
+        matches!(self, SinkKind::Stdout | SinkKind::Both)
+    }
+}
+
+/// True when appending `incoming` bytes would push a non-empty file past
+/// `max_bytes`. A zero cap disables rotation.
+pub fn should_rotate(written: u64, incoming: u64, max_bytes: u64) -> bool {
+    max_bytes > 0 && written > 0 && written + incoming > max_bytes
+}
+
+/// Append-only JSONL file rotated by size: `path` is live, `path.1` is the
+/// most recent rotation, up to `path.<keep>`. Disk use stays within roughly
+/// `max_bytes * (keep + 1)`. Rotation only happens between whole lines.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: BufWriter<File>,
+    written: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, keep: usize) -> std::io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            max_bytes,
+            keep,
+            file: BufWriter::new(file),
+            written,
+        })
+    }
+
+    pub fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let incoming = line.len() as u64 + 1;
+        if should_rotate(self.written, incoming, self.max_bytes) {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.file.flush()?;
+        self.written += incoming;
+        Ok(())
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        if self.keep > 0 {
+            let _ = std::fs::remove_file(self.rotated(self.keep));
+            for n in (1..self.keep).rev() {
+                let from = self.rotated(n);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated(1))?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(&self.path)?;
+        self.file = BufWriter::new(file);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+static SINK_KIND: OnceLock<SinkKind> = OnceLock::new();
+static FILE_SINK: OnceLock<Option<Mutex<RotatingFile>>> = OnceLock::new();
+
+fn sink_kind() -> SinkKind {
+    *SINK_KIND.get_or_init(SinkKind::from_env)
+}
+
+/// Rotating file sink configured by `LOG_FILE`, `LOG_MAX_BYTES` and
+/// `LOG_KEEP`; only opened when `LOG_SINK` is `file` or `both`.
+fn file_sink() -> Option<&'static Mutex<RotatingFile>> {
+    FILE_SINK
+        .get_or_init(|| {
+            if sink_kind() == SinkKind::Stdout {
+                return None;
+            }
+            let path = std::env::var("LOG_FILE")
+                .unwrap_or_else(|_| "out/logs/arbitragefx.jsonl".to_string());
+            let max_bytes = std::env::var("LOG_MAX_BYTES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(50 * 1024 * 1024);
+            let keep = std::env::var("LOG_KEEP")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(5);
+            match RotatingFile::open(&path, max_bytes, keep) {
+                Ok(f) => Some(Mutex::new(f)),
+                Err(err) => {
+                    eprintln!("[log] failed to open log sink {}: {}", path, err);
+                    None
+                }
+            }
+        })
+        .as_ref()
+}
+
+fn emit_line(line: &str) {
+    if let Some(sink) = file_sink() {
+        if let Ok(mut f) = sink.lock() {
+            if let Err(err) = f.write_line(line) {
+                eprintln!("[log] sink write failed: {}", err);
+            }
+        }
+    }
+    if sink_kind().stdout() {
+        println!("{}", line);
+    }
+}
+
 // =============================================================================
 // Core logging functions
 // =============================================================================
@@ -218,19 +384,28 @@ pub fn ts_epoch_ms() -> u64 {
 
 /// Emit a structured log entry
 pub fn log(level: Level, domain: Domain, event: &str, fields: Map<String, Value>) {
-    let min_level = Level::from_env();
-    if level < min_level || !domain.is_enabled() {
+    if !level.passes(min_level()) || !domain.is_enabled() {
         return;
     }
 
     emit_record(level, domain.as_str(), event, fields);
 }
 
-/// Legacy compatibility: json_log with module name
-pub fn json_log(module: &str, mut fields: Map<String, Value>) {
+/// Legacy compatibility: json_log with module name, at info level
+pub fn json_log(module: &str, fields: Map<String, Value>) {
+    json_log_at(Level::Info, module, fields);
+}
+
+/// `json_log` at an explicit level, dropped below the `LOG_LEVEL` threshold.
+/// Per-bar guard and decision chatter goes out at debug; order, fill and
+/// audit events stay at info or above.
+pub fn json_log_at(level: Level, module: &str, mut fields: Map<String, Value>) {
+    if !level.passes(min_level()) {
+        return;
+    }
     fields.insert("ts".to_string(), Value::String(ts_now()));
     fields.insert("module".to_string(), Value::String(module.to_string()));
-    emit_record(Level::Info, module, module, fields);
+    emit_record(level, module, module, fields);
 }
 
 fn emit_record(level: Level, component: &str, event: &str, fields: Map<String, Value>) {
@@ -260,7 +435,7 @@ fn emit_record(level: Level, component: &str, event: &str, fields: Map<String, V
         Level::Trace | Level::Debug => write_line(&ctx.trace, &line),
         _ => write_line(&ctx.events, &line),
     }
-    println!("{}", line);
+    emit_line(&line);
 }
 
 // =============================================================================
@@ -660,7 +835,7 @@ impl Drop for ProfileScope {
         let mut fields = self.context.take().unwrap_or_default();
         fields.insert("label".to_string(), v_str(self.label));
         fields.insert("elapsed_ms".to_string(), v_num(elapsed_ms));
-        log(Level::Trace, self.domain, "profile", fields);
+        log(Level::Debug, self.domain, "profile", fields);
     }
 }
 
@@ -766,6 +941,20 @@ mod tests {
         assert!(Level::Error < Level::Fatal);
     }
 
+    #[test]
+    fn test_level_threshold_filters_below_and_passes_at_or_above() {
+        let min = Level::parse("WARN").unwrap();
+        assert_eq!(min, Level::Warn);
+        assert!(!Level::Debug.passes(min));
+        assert!(!Level::Info.passes(min));
+        assert!(Level::Warn.passes(min));
+        assert!(Level::Error.passes(min));
+        // Default threshold keeps info events and drops debug chatter
+        assert!(Level::Info.passes(Level::Info));
+        assert!(!Level::Debug.passes(Level::Info));
+        assert_eq!(Level::parse("verbose"), None);
+    }
+
     #[test]
     fn test_params_hash_deterministic() {
         let h1 = params_hash("test-input");
@@ -787,6 +976,56 @@ mod tests {
         assert_eq!(m.get("num").unwrap(), 42.0);
     }
 
+    #[test]
+    fn test_rotation_predicate_fires_at_threshold() {
+        assert!(
+            !should_rotate(0, 200, 100),
+            "an empty file always takes the line"
+        );
+        assert!(!should_rotate(60, 40, 100), "exactly at the cap still fits");
+        assert!(should_rotate(60, 41, 100));
+        assert!(!should_rotate(10_000, 1, 0), "zero cap disables rotation");
+    }
+
+    #[test]
+    fn test_rotated_files_hold_whole_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bot.jsonl");
+        let mut sink = RotatingFile::open(&path, 256, 2).unwrap();
+        for i in 0..40 {
+            let line = Value::Object(obj(&[("seq", v_num(i as f64)), ("msg", v_str("tick"))]));
+            sink.write_line(&line.to_string()).unwrap();
+        }
+
+        let mut files: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        files.sort();
+        assert_eq!(
+            files.len(),
+            3,
+            "live file plus `keep` rotations: {:?}",
+            files
+        );
+        assert!(!sink.rotated(3).exists());
+
+        let mut seqs = Vec::new();
+        for f in &files {
+            let text = std::fs::read_to_string(f).unwrap();
+            assert!(text.len() as u64 <= 256);
+            for line in text.lines() {
+                let v: Value = serde_json::from_str(line).expect("valid json line");
+                seqs.push(v["seq"].as_f64().unwrap() as u64);
+            }
+        }
+        // Oldest lines were dropped; the newest survive in order
+        seqs.sort();
+        assert_eq!(*seqs.last().unwrap(), 39);
+        assert!(seqs.windows(2).all(|w| w[1] == w[0] + 1));
+        assert!(seqs[0] > 0);
+    }
+
     #[test]
     fn test_seq_increments() {
         let s1 = next_seq();
@@ -2,15 +2,20 @@ mod adapter;
 mod drift_tracker;
 mod exchange;
 mod feed;
+#[cfg(feature = "health")]
+mod health;
 mod indicators;
 mod live_ops;
 mod logging;
 mod metrics;
+#[cfg(feature = "prometheus")]
+mod prometheus;
 mod reconcile;
 mod reliability;
 mod risk;
 mod state;
 mod storage;
+mod strategies;
 mod strategy;
 mod verify;
 
@@ -20,18 +25,23 @@ use adapter::types;
 use adapter::unified::UnifiedAdapter;
 use anyhow::Result;
 use chrono::Utc;
+use exchange::paper::PaperExchange;
 use exchange::retry::{retry_async, RetryConfig};
-use exchange::{Exchange, ExchangeKind};
+use exchange::{Exchange, ExchangeError, ExchangeKind};
+use feed::aggregator::CandleAggregator;
 use feed::aux_data::AuxDataFetcher;
 use live_ops::PendingMeta;
-use logging::{json_log, obj, params_hash, v_num, v_str, ProfileScope};
+use logging::{
+    json_log, json_log_at, obj, params_hash, ts_epoch_ms, v_num, v_str, Level, ProfileScope,
+};
 use metrics::MetricsEngine;
+use reliability::audit::{AuditRecord, AuditStage, AuditTrail};
 use reliability::{circuit::CircuitBreaker, state::OrderBook, wal::Wal};
 use risk::RiskEngine;
 use state::{MarketState, StrategyInstance};
 use std::collections::HashMap;
 use storage::StateStore;
-use strategy::{Action, Strategy};
+use strategy::{Action, RegimeRouter, Strategy};
 use tokio::sync::mpsc;
 use tokio::time::{sleep, Duration};
 
@@ -41,26 +51,83 @@ fn now_ts() -> u64 {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cfg = state::Config::from_env();
+    let cfg = state::Config::from_env_checked().map_err(anyhow::Error::msg)?;
+    json_log(
+        "startup",
+        obj(&[
+            ("seed", v_num(cfg.seed as f64)),
+            ("config_hash", v_str(&cfg.config_hash())),
+        ]),
+    );
+    // Use real adapter if API keys provided, otherwise stub
+    let live_adapter = matches!((&cfg.api_key, &cfg.api_secret), (Some(_), Some(_)));
     let exchange = ExchangeKind::from_env().build(cfg.clone())?;
+    // Without keys, orders are filled locally against the venue's candles
+    let exchange: Box<dyn Exchange + Send + Sync> = if live_adapter {
+        exchange
+    } else {
+        Box::new(PaperExchange::new(exchange, &cfg))
+    };
+    live_ops::check_candle_source(&cfg, ExchangeKind::from_env()).map_err(anyhow::Error::msg)?;
+    live_ops::check_feed_granularity(exchange.as_ref(), &cfg)
+        .await
+        .map_err(anyhow::Error::msg)?;
+    // Optional second venue used only to sanity-check the primary's prices
+    let reference = match cfg.reference_exchange.as_deref() {
+        Some(name) => {
+            let kind = ExchangeKind::from_name(name)
+                .ok_or_else(|| anyhow::anyhow!("unknown REFERENCE_EXCHANGE {}", name))?;
+            Some(kind.build(cfg.clone())?)
+        }
+        None => None,
+    };
     let mut market = MarketState::new(cfg.clone());
+    if cfg.warmup_prefill {
+        live_ops::prefill_history(exchange.as_ref(), &mut market, &cfg).await;
+    }
     let mut store = StateStore::new(&cfg.sqlite_path)?;
     store.init()?;
     let mut wal = Wal::open(&cfg.wal_path)?;
+    let audit_path = AuditTrail::path_for_wal(&cfg.wal_path);
+    let mut audit = AuditTrail::open(&audit_path)?;
     let mut order_book = OrderBook::new();
     let mut pending_by_client: HashMap<String, PendingMeta> = HashMap::new();
     let mut circuit = CircuitBreaker::new(5);
-    let aux_fetcher = AuxDataFetcher::new();
+    #[cfg(feature = "prometheus")]
+    let prom = {
+        let registry = prometheus::Registry::shared();
+        prometheus::serve(&cfg.metrics_addr, registry.clone())?;
+        json_log("metrics_http", obj(&[("addr", v_str(&cfg.metrics_addr))]));
+        registry
+    };
+    #[cfg(feature = "health")]
+    let health_state = {
+        let shared = health::HealthState::new(&cfg, now_ts()).shared();
+        health::serve(&cfg.health_addr, shared.clone())?;
+        json_log("health_http", obj(&[("addr", v_str(&cfg.health_addr))]));
+        shared
+    };
+    let aux_fetcher = AuxDataFetcher::with_ttls(feed::aux_data::AuxTtls::from_env())
+        .with_venue(ExchangeKind::from_env())
+        .with_liq_score_source(cfg.liq_score_source, cfg.liq_baseline_bars);
+    let contract_specs = live_ops::load_contract_specs(&cfg, ExchangeKind::from_env()).await;
 
-    // Use real adapter if API keys provided, otherwise stub
-    let live_adapter = matches!((&cfg.api_key, &cfg.api_secret), (Some(_), Some(_)));
     let mut adapter: Box<dyn UnifiedAdapter> = match (&cfg.api_key, &cfg.api_secret) {
         (Some(key), Some(secret)) => {
             json_log(
                 "adapter",
-                obj(&[("type", v_str("binance")), ("status", v_str("live"))]),
+                obj(&[
+                    ("type", v_str("binance")),
+                    ("status", v_str("live")),
+                    ("testnet", v_str(&cfg.use_testnet.to_string())),
+                    ("base", v_str(&cfg.binance_base)),
+                ]),
             );
-            Box::new(BinanceAdapter::new(key.clone(), secret.clone()))
+            Box::new(BinanceAdapter::new(
+                cfg.binance_base.clone(),
+                key.clone(),
+                secret.clone(),
+            ))
         }
         _ => {
             json_log(
@@ -92,6 +159,19 @@ async fn main() -> Result<()> {
         );
     }
     for pending in &recovery.pending_orders {
+        let history = AuditTrail::for_intent(&audit_path, &pending.intent_id).unwrap_or_default();
+        json_log(
+            "wal_recovery",
+            obj(&[
+                ("intent_id", v_str(&pending.intent_id)),
+                (
+                    "last_audit_stage",
+                    v_str(&history.last().map_or("none".to_string(), |r| {
+                        format!("{:?}", r.stage).to_lowercase()
+                    })),
+                ),
+            ]),
+        );
         if let (Some(client_id), Some(strategy_id)) =
             (&pending.client_order_id, &pending.strategy_id)
         {
@@ -99,91 +179,158 @@ async fn main() -> Result<()> {
                 client_id.clone(),
                 PendingMeta {
                     strategy_id: strategy_id.clone(),
+                    symbol: pending.symbol.clone(),
                     intent_id: pending.intent_id.clone(),
                     placed_ts: pending.ts,
-                    order_id: None,
+                    order_id: recovery.acked_orders.get(client_id).cloned(),
+                    intended_qty: 0.0,
                 },
             );
             order_book.ensure(client_id, pending.qty);
         }
     }
+    for client_id in recovery.acked_orders.keys() {
+        order_book.mark_acked(client_id);
+    }
 
-    let mut strategies = StrategyInstance::build_default_set(cfg.clone());
-
-    // Apply recovered state per-strategy (FIXED: no longer overwrites)
+    let mut strategies = StrategyInstance::build_default_set_per_symbol(cfg.clone());
     for inst in strategies.iter_mut() {
-        if let Some(snap) = recovery.snapshots_by_strategy.get(&inst.id) {
-            inst.state.portfolio.cash = snap.cash;
-            inst.state.portfolio.position = snap.position;
-            inst.state.portfolio.entry_price = snap.entry_price;
-            inst.state.portfolio.equity = snap.equity;
-            inst.state.metrics.pnl = snap.pnl;
-            json_log(
-                "wal_recovery",
-                obj(&[
-                    ("status", v_str("recovered")),
-                    ("strategy_id", v_str(&inst.id)),
-                    ("snapshot_ts", v_num(snap.ts as f64)),
-                    ("position", v_num(snap.position)),
-                    ("equity", v_num(snap.equity)),
-                ]),
-            );
-        }
+        inst.regimes = cfg.regime_tags_for(&inst.id);
     }
-    // Replay fills since snapshot (per-strategy based on intent_id prefix)
-    for fill in &recovery.fills_since_snapshot {
-        for inst in strategies.iter_mut() {
-            // Only apply fills that belong to this strategy (by intent_id prefix)
-            if fill.intent_id.starts_with(&format!("I-{}-", inst.id)) {
-                let f = state::Fill {
-                    price: fill.price,
-                    qty: fill.qty,
-                    fee: fill.fee,
-                    ts: fill.ts,
-                };
-                let _ = inst.state.portfolio.apply_fill(f);
-            }
-        }
+    let disabled: Vec<&str> = strategies
+        .iter()
+        .filter(|inst| !cfg.strategy_enabled(&inst.id))
+        .map(|inst| inst.id.as_str())
+        .collect();
+    if !disabled.is_empty() {
+        json_log(
+            "strategy_control",
+            obj(&[
+                ("status", v_str("disabled")),
+                ("strategies", v_str(&disabled.join(","))),
+            ]),
+        );
+    }
+
+    let stored_hwm = if cfg.persist_hwm {
+        store.high_water_marks()?
+    } else {
+        HashMap::new()
+    };
+
+    // Apply recovered state per-strategy, then fills logged since each snapshot
+    recovery.apply_to(&mut strategies, &stored_hwm, cfg.persist_hwm);
+    for inst in &strategies {
+        recovery.check_consistency(&inst.id, &inst.state.portfolio);
+    }
+    // Orders still pending in the WAL may have filled while we were down
+    for client_id in live_ops::reconcile_startup_fills(
+        adapter.as_mut(),
+        &recovery,
+        &mut strategies,
+        &mut order_book,
+        &mut wal,
+    ) {
+        pending_by_client.remove(&client_id);
     }
 
     let mut risk = RiskEngine::new(cfg.clone());
     let mut metrics = MetricsEngine::new();
     let retry_cfg = RetryConfig::default();
-    let mut drift_tracker = DriftTracker::default_windows();
-    let mut prev_price: Option<f64> = None;
+    let mut drift_trackers: HashMap<String, DriftTracker> = cfg
+        .symbols
+        .iter()
+        .map(|s| {
+            let tracker =
+                DriftTracker::default_windows().with_halt_confirmation(cfg.drift_halt_confirm_bars);
+            (s.clone(), tracker)
+        })
+        .collect();
+    let mut prev_prices: HashMap<String, f64> = HashMap::new();
+    let mut regime_router = RegimeRouter::new(cfg.regime_lookback);
+    // symbol -> (latest candle ts, wall-clock ts when it last advanced)
+    let mut candle_advance: HashMap<String, (u64, u64)> = HashMap::new();
+    // Per strategy: halted by a stale or diverged feed, so the halt lifts
+    // when the data recovers
+    let mut feed_halted = vec![false; strategies.len()];
+    let mut order_spacing = live_ops::OrderSpacing::default();
     let (fill_tx, mut fill_rx) = mpsc::channel(cfg.fill_channel_capacity);
     if live_adapter {
         if let (Some(key), Some(secret)) = (&cfg.api_key, &cfg.api_secret) {
             let base = cfg.binance_base.clone();
-            let symbol = cfg.symbol.clone();
             let ws_key = key.clone();
-            let ws_base = base.clone();
+            let rest_base = base.clone();
+            let ws_base = cfg.binance_ws_base.clone();
             let ws_tx = fill_tx.clone();
             tokio::spawn(async move {
-                let _ = feed::binance_live::start_ws_listener(ws_key, ws_base, ws_tx).await;
+                let _ =
+                    feed::binance_live::start_ws_listener(ws_key, rest_base, ws_base, ws_tx).await;
             });
 
-            let poll_tx = fill_tx.clone();
-            let poll_key = key.clone();
-            let poll_secret = secret.clone();
+            for symbol in cfg.symbols.iter().cloned() {
+                let poll_tx = fill_tx.clone();
+                let poll_key = key.clone();
+                let poll_secret = secret.clone();
+                let poll_base = base.clone();
+                tokio::spawn(async move {
+                    let _ = feed::binance_live::start_poll_fallback(
+                        poll_key,
+                        poll_secret,
+                        poll_base,
+                        symbol,
+                        poll_tx,
+                        std::env::var("POLL_SECS")
+                            .ok()
+                            .and_then(|v| v.parse().ok())
+                            .unwrap_or(15),
+                    )
+                    .await;
+                });
+            }
+        }
+    }
+
+    // Optional candle source: aggregate public trades instead of polling candles
+    let (trade_tx, mut trade_rx) = mpsc::channel(cfg.fill_channel_capacity.max(1024));
+    let mut aggregators: HashMap<String, CandleAggregator> = HashMap::new();
+    if cfg.trade_candles {
+        for symbol in &cfg.symbols {
+            aggregators.insert(
+                symbol.clone(),
+                CandleAggregator::new(cfg.candle_granularity),
+            );
+            let symbol = symbol.clone();
+            let tx = trade_tx.clone();
+            let ws_base = cfg.binance_ws_base.clone();
             tokio::spawn(async move {
-                let _ = feed::binance_live::start_poll_fallback(
-                    poll_key,
-                    poll_secret,
-                    base,
-                    symbol,
-                    poll_tx,
-                    std::env::var("POLL_SECS")
-                        .ok()
-                        .and_then(|v| v.parse().ok())
-                        .unwrap_or(15),
+                feed::binance_live::supervise_trade_stream(
+                    &symbol,
+                    &tx,
+                    Duration::from_secs(1),
+                    || {
+                        feed::binance_live::start_trade_stream(
+                            ws_base.clone(),
+                            symbol.clone(),
+                            tx.clone(),
+                        )
+                    },
                 )
                 .await;
             });
         }
     }
+    drop(trade_tx);
 
     let mut last_reconcile_ts: u64 = 0;
+    let mut order_latency = live_ops::LatencyWindow::default();
+    let mut threshold_mtime = None;
+
+    // Graceful shutdown: SIGINT/SIGTERM flips this once; the loop checkpoints and exits
+    let (shutdown_tx, mut shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+    tokio::spawn(async move {
+        live_ops::shutdown_signal().await;
+        let _ = shutdown_tx.send(());
+    });
 
     loop {
         let start = now_ts();
@@ -195,8 +342,11 @@ async fn main() -> Result<()> {
             &mut pending_by_client,
             &mut strategies,
             &mut order_book,
-            &mut wal,
-            &mut circuit,
+            live_ops::FillSinks {
+                wal: &mut wal,
+                audit: &mut audit,
+                circuit: &mut circuit,
+            },
             &market,
             &cfg,
         );
@@ -206,72 +356,232 @@ async fn main() -> Result<()> {
             }
         }
 
-        // Fetch candle with retry
-        let _candle_prof = ProfileScope::new("profile", "fetch_candle");
-        let candle = retry_async(&retry_cfg, "fetch_candle", || {
-            exchange.fetch_latest_candle(&cfg.symbol, cfg.candle_granularity)
-        })
-        .await?;
+        while let Ok((symbol, trade)) = trade_rx.try_recv() {
+            if let Some(agg) = aggregators.get_mut(&symbol) {
+                agg.push(trade);
+            }
+        }
 
-        market.on_candle(candle);
+        let mut drift_by_symbol = HashMap::new();
+        let mut feed_stale = false;
+        let mut price_diverged = false;
+        let mut books = HashMap::new();
+        for symbol in &cfg.symbols {
+            // Fetch candle with retry
+            let _candle_prof = ProfileScope::new("profile", "fetch_candle");
+            let candle = if cfg.trade_candles {
+                match aggregators.get(symbol).and_then(|a| a.latest()) {
+                    Some(c) => c,
+                    // No completed bucket yet
+                    None => continue,
+                }
+            } else {
+                match retry_async(&retry_cfg, "fetch_candle", || {
+                    exchange.fetch_latest_candle(symbol, cfg.candle_granularity)
+                })
+                .await
+                {
+                    Ok(c) => c,
+                    // Bad credentials won't heal by retrying: stop trading, keep reporting
+                    Err(err)
+                        if ExchangeError::classify(&err).is_some_and(ExchangeError::is_auth) =>
+                    {
+                        circuit.trip();
+                        json_log(
+                            "circuit_breaker",
+                            obj(&[
+                                ("trigger", v_str("auth_error")),
+                                ("action", v_str("trading_halted")),
+                                ("error", v_str(&err.to_string())),
+                            ]),
+                        );
+                        continue;
+                    }
+                    Err(err) => return Err(err),
+                }
+            };
 
-        // Fetch comprehensive auxiliary data (funding, borrow, liquidations, depeg)
-        let _aux_prof = ProfileScope::new("profile", "fetch_aux");
-        match aux_fetcher.fetch(&cfg.symbol).await {
-            Ok(aux) => {
-                market.update_aux(&cfg.symbol, aux);
+            let advance = candle_advance
+                .entry(symbol.clone())
+                .or_insert((candle.ts, start));
+            let prev_candle_ts = advance.0;
+            if candle.ts > advance.0 {
+                *advance = (candle.ts, start);
+            } else if state::candle_feed_stale(advance.1, start, &cfg) {
+                json_log_at(
+                    Level::Warn,
+                    "risk_guard",
+                    obj(&[
+                        ("check", v_str("candle_staleness")),
+                        ("result", v_str("fail")),
+                        ("symbol", v_str(symbol)),
+                        ("candle_ts", v_num(advance.0 as f64)),
+                        ("stale_secs", v_num(start.saturating_sub(advance.1) as f64)),
+                    ]),
+                );
+                feed_stale = true;
+            }
+
+            if let Some(reference) = reference.as_deref() {
+                if !live_ops::reference_price_ok(reference, symbol, candle.c, &cfg).await {
+                    price_diverged = true;
+                }
             }
-            Err(err) => {
+
+            match exchange.fetch_book_top(symbol).await {
+                Ok(book) => {
+                    books.insert(symbol.clone(), book);
+                }
+                Err(err) => {
+                    json_log_at(
+                        Level::Debug,
+                        "risk_guard",
+                        obj(&[
+                            ("check", v_str("spread_too_wide")),
+                            ("result", v_str("skipped")),
+                            ("symbol", v_str(symbol)),
+                            ("error", v_str(&err.to_string())),
+                        ]),
+                    );
+                }
+            }
+
+            let funding_rate = market.view(symbol).aux.funding_rate;
+            live_ops::settle_funding(
+                &cfg,
+                &mut strategies,
+                symbol,
+                prev_candle_ts,
+                candle.ts,
+                funding_rate,
+                candle.c,
+            );
+            market.on_candle_for(symbol, candle);
+            if let Some(regime) = regime_router.on_close(symbol, candle.c) {
                 json_log(
-                    "aux_fetch",
+                    "regime",
+                    obj(&[("symbol", v_str(symbol)), ("trend", v_str(regime.as_str()))]),
+                );
+                for inst in strategies.iter_mut().filter(|s| &s.symbol == symbol) {
+                    risk.on_regime_change(&mut inst.state, &inst.id, regime, start);
+                }
+            }
+
+            // Fetch comprehensive auxiliary data (funding, borrow, liquidations, depeg)
+            let _aux_prof = ProfileScope::new("profile", "fetch_aux");
+            match aux_fetcher.fetch(symbol).await {
+                Ok(aux) => {
+                    if aux.has_funding {
+                        let point = feed::aux_data::FundingPoint {
+                            ts: start,
+                            symbol: symbol.clone(),
+                            rate: aux.funding_rate,
+                            funding_ts: strategy::next_funding_ts(start, cfg.funding_interval_secs),
+                        };
+                        if let Err(err) = feed::aux_data::record_funding_point(
+                            feed::aux_data::FUNDING_HISTORY_PATH,
+                            &point,
+                            feed::aux_data::FUNDING_HISTORY_MAX_POINTS,
+                        ) {
+                            json_log(
+                                "funding_history",
+                                obj(&[
+                                    ("status", v_str("write_failed")),
+                                    ("error", v_str(&err.to_string())),
+                                ]),
+                            );
+                        }
+                    }
+                    market.update_aux(symbol, aux);
+                }
+                Err(err) => {
+                    json_log(
+                        "aux_fetch",
+                        obj(&[
+                            ("status", v_str("error")),
+                            ("symbol", v_str(symbol)),
+                            ("error", v_str(&err.to_string())),
+                        ]),
+                    );
+                }
+            }
+
+            // Update liquidation rolling window
+            let _liq_prof = ProfileScope::new("profile", "fetch_liquidations");
+            let _ = aux_fetcher.fetch_recent_liquidations(symbol).await;
+
+            let view = market.view(symbol);
+            let returns = match prev_prices.get(symbol) {
+                Some(&prev) if prev > 0.0 => (view.last.c / prev) - 1.0,
+                _ => 0.0,
+            };
+            let drift_tracker = drift_trackers.entry(symbol.clone()).or_insert_with(|| {
+                DriftTracker::default_windows().with_halt_confirmation(cfg.drift_halt_confirm_bars)
+            });
+            drift_tracker.update_from_market(
+                view.indicators.vol,
+                returns,
+                0.0,
+                view.aux.funding_rate,
+                view.indicators.z_momentum,
+                start,
+            );
+            let drift_severity = drift_tracker.compute_overall();
+            prev_prices.insert(symbol.clone(), view.last.c);
+            json_log(
+                "drift",
+                obj(&[
+                    ("symbol", v_str(symbol)),
+                    ("severity", v_str(&format!("{:?}", drift_severity))),
+                    ("halt_streak", v_num(drift_tracker.halt_streak as f64)),
+                    ("returns", v_num(returns)),
+                ]),
+            );
+            for evt in feed::monitor::scan(view) {
+                json_log(
+                    "flow_feed",
                     obj(&[
-                        ("status", v_str("error")),
-                        ("error", v_str(&err.to_string())),
+                        ("event", v_str(&format!("{:?}", evt))),
+                        ("symbol", v_str(symbol)),
                     ]),
                 );
             }
+            drift_by_symbol.insert(symbol.clone(), drift_severity);
         }
-
-        // Update liquidation rolling window
-        let _liq_prof = ProfileScope::new("profile", "fetch_liquidations");
-        let _ = aux_fetcher.fetch_recent_liquidations(&cfg.symbol).await;
-
-        let view = market.view(&cfg.symbol);
-        let returns = match prev_price {
-            Some(prev) if prev > 0.0 => (view.last.c / prev) - 1.0,
-            _ => 0.0,
-        };
-        drift_tracker.update_from_market(
-            view.indicators.vol,
-            returns,
-            0.0,
-            view.aux.funding_rate,
-            view.indicators.z_momentum,
-            start,
-        );
-        let drift_severity = drift_tracker.compute_overall();
-        prev_price = Some(view.last.c);
-        json_log(
-            "drift",
-            obj(&[
-                ("severity", v_str(&format!("{:?}", drift_severity))),
-                ("returns", v_num(returns)),
-            ]),
-        );
-        for evt in feed::monitor::scan(view) {
-            json_log(
-                "flow_feed",
+        order_latency.expire(crate::logging::ts_epoch_ms());
+        let latency_slow = order_latency.exceeded(cfg.max_latency_ms);
+        if latency_slow {
+            json_log_at(
+                Level::Warn,
+                "risk_guard",
                 obj(&[
-                    ("event", v_str(&format!("{:?}", evt))),
-                    ("symbol", v_str(&cfg.symbol)),
+                    ("check", v_str("order_latency")),
+                    ("result", v_str("fail")),
+                    ("mean_ms", v_num(order_latency.mean_ms().unwrap_or(0.0))),
+                    ("threshold", v_num(cfg.max_latency_ms as f64)),
                 ]),
             );
         }
+        live_ops::apply_feed_halt(
+            &mut strategies,
+            &mut feed_halted,
+            feed_stale || price_diverged,
+        );
+        live_ops::apply_reenable_file(&cfg, &mut strategies);
+        live_ops::reload_thresholds(&cfg, &mut strategies, &mut threshold_mtime);
 
-        for inst in strategies.iter_mut() {
-            let view = market.view(&cfg.symbol);
+        let mut jobs = Vec::new();
+        let mut report = Vec::new();
+        for (index, inst) in strategies.iter_mut().enumerate() {
+            // Owned so the order path can hand `inst` to `shadow_execute`
+            let symbol = inst.symbol.clone();
+            let view = market.view(&symbol);
+            let Some(&drift_severity) = drift_by_symbol.get(&inst.symbol) else {
+                continue;
+            };
             if view.last.ts == 0 {
-                json_log(
+                json_log_at(
+                    Level::Debug,
                     "risk_guard",
                     obj(&[
                         ("check", v_str("market_data_missing")),
@@ -281,15 +591,28 @@ async fn main() -> Result<()> {
                 );
                 continue;
             }
+            if !cfg.is_warmed_up(view.indicators.samples) {
+                json_log_at(
+                    Level::Debug,
+                    "risk_guard",
+                    obj(&[
+                        ("check", v_str("warmup")),
+                        ("result", v_str("fail")),
+                        ("strategy", v_str(&inst.id)),
+                        ("samples", v_num(view.indicators.samples as f64)),
+                        ("required", v_num(cfg.min_history() as f64)),
+                    ]),
+                );
+                continue;
+            }
             let reqs = inst.strategy.aux_requirements();
             if !reqs.is_empty()
-                && !view.aux.is_valid_for_strategy(
-                    start,
-                    cfg.candle_granularity.saturating_mul(2),
-                    &reqs,
-                )
+                && !view
+                    .aux
+                    .is_valid_for_strategy(start, cfg.candles_to_secs(2), &reqs)
             {
-                json_log(
+                json_log_at(
+                    Level::Warn,
                     "risk_guard",
                     obj(&[
                         ("check", v_str("aux_data_staleness")),
@@ -305,12 +628,21 @@ async fn main() -> Result<()> {
                 &[("strategy", v_str(&inst.id))],
             );
             let mut action = inst.strategy.update(view, &mut inst.state);
+            live_ops::dump_view(&cfg, &inst.id, &view, action);
             if drift_severity.should_halt() {
                 inst.state.trading_halted = true;
             }
+            risk.enforce_drawdown_kill(&mut inst.state, &inst.id);
+            risk.enforce_profit_factor(&mut inst.state, &inst.id);
             if drift_severity.should_close() && inst.state.portfolio.position.abs() > 1e-9 {
                 action = Action::Close;
             }
+            action = risk.enforce_max_hold(&mut inst.state, action, &inst.id, start);
+            // Operator-disabled: stops, max hold and signal exits still run
+            // on an open position, entries are dropped
+            if !cfg.strategy_enabled(&inst.id) {
+                action = action.exits_only(inst.state.portfolio.position);
+            }
             // FIXED: Use current price for MTM risk calculations
             let _risk_prof = ProfileScope::with_context(
                 "profile",
@@ -318,28 +650,55 @@ async fn main() -> Result<()> {
                 &[("strategy", v_str(&inst.id))],
             );
             let guarded = risk.apply_with_price(&inst.state, action, start, view.last.c);
-            json_log(
+            let guarded = regime_router.gate(&inst.symbol, &inst.regimes, guarded);
+            if let (
+                Action::Buy { qty: want } | Action::Sell { qty: want },
+                Action::Buy { qty: got } | Action::Sell { qty: got },
+            ) = (action, guarded)
+            {
+                if got < want {
+                    json_log_at(
+                        Level::Debug,
+                        "risk_guard",
+                        obj(&[
+                            ("check", v_str("position_clamp")),
+                            ("result", v_str("shrink")),
+                            ("strategy", v_str(&inst.id)),
+                            ("requested_qty", v_num(want)),
+                            ("qty", v_num(got)),
+                        ]),
+                    );
+                }
+            }
+            let guarded = live_ops::drift_throttle(
+                guarded,
+                inst.state.portfolio.position,
+                drift_severity,
+                &inst.id,
+            );
+            let guarded = risk.enforce_notional_cap(&inst.state, guarded, view.last.c, &inst.id);
+            json_log_at(
+                Level::Debug,
                 "strategy",
                 obj(&[
                     ("strategy", v_str(&inst.id)),
                     ("score", v_num(view.indicators.z_momentum)),
                     ("action", v_str(&format!("{:?}", action))),
+                    ("reason", v_str(inst.state.signal_reason)),
                 ]),
             );
 
             if let Action::Hold = guarded {
-                json_log(
+                json_log_at(
+                    Level::Debug,
                     "risk_guard",
                     obj(&[("check", v_str("guarded")), ("result", v_str("fail"))]),
                 );
             } else {
-                let exposure = if inst.state.portfolio.equity.abs() > 0.0 {
-                    (inst.state.portfolio.position * view.last.c).abs()
-                        / inst.state.portfolio.equity.abs()
-                } else {
-                    0.0
-                };
-                json_log(
+                // Branches on contract kind: linear pos*price, inverse pos/price
+                let exposure = inst.state.portfolio.exposure(view.last.c);
+                json_log_at(
+                    Level::Debug,
                     "risk_guard",
                     obj(&[
                         ("check", v_str("position_limit")),
@@ -357,19 +716,74 @@ async fn main() -> Result<()> {
                     );
                     continue;
                 }
+                if !live_ops::spread_allows_order(
+                    books.get(&inst.symbol),
+                    guarded,
+                    inst.state.portfolio.position,
+                    &cfg,
+                    &inst.id,
+                ) {
+                    continue;
+                }
+                if !live_ops::latency_allows_order(
+                    latency_slow,
+                    guarded,
+                    inst.state.portfolio.position,
+                    &inst.id,
+                ) {
+                    continue;
+                }
+                if !matches!(guarded, Action::Close)
+                    && inst
+                        .state
+                        .trade_rate_exceeded(start, cfg.max_trades_per_hour)
+                {
+                    json_log_at(
+                        Level::Debug,
+                        "risk_guard",
+                        obj(&[
+                            ("check", v_str("trade_rate")),
+                            ("result", v_str("fail")),
+                            ("strategy", v_str(&inst.id)),
+                            (
+                                "trades_last_hour",
+                                v_num(inst.state.trades_last_hour(start) as f64),
+                            ),
+                            ("threshold", v_num(cfg.max_trades_per_hour as f64)),
+                        ]),
+                    );
+                    continue;
+                }
+                if !order_spacing.allows(
+                    &cfg,
+                    &inst.symbol,
+                    guarded,
+                    inst.state.portfolio.position,
+                    start,
+                    &inst.id,
+                ) {
+                    continue;
+                }
                 let _order_prof = ProfileScope::new("profile", "place_order");
                 inst.state.order_seq = inst.state.order_seq.saturating_add(1);
                 // FIXED: Include strategy_id + sequence to avoid collisions across strategies
                 let intent_id = format!("I-{}-{}-{}", inst.id, start, inst.state.order_seq);
                 let client_id = format!("CID-{}-{}-{}", inst.id, start, inst.state.order_seq);
-                let order_qty = match guarded {
+                let raw_qty = match guarded {
                     Action::Buy { qty } => qty,
                     Action::Sell { qty } => qty,
                     Action::Close => inst.state.portfolio.position.abs(),
                     Action::Hold => 0.0,
                 };
+                let filters = contract_specs.get(&inst.symbol);
+                let Some(order_qty) =
+                    live_ops::fit_to_contract(&filters, guarded, raw_qty, view.last.c, &inst.id)
+                else {
+                    continue;
+                };
                 if order_qty <= 0.0 {
-                    json_log(
+                    json_log_at(
+                        Level::Debug,
                         "risk_guard",
                         obj(&[
                             ("check", v_str("order_qty_zero")),
@@ -379,14 +793,33 @@ async fn main() -> Result<()> {
                     );
                     continue;
                 }
+                let dir = match guarded {
+                    Action::Buy { .. } => 1.0,
+                    Action::Sell { .. } => -1.0,
+                    _ => -inst.state.portfolio.position.signum(),
+                };
+                if live_ops::shadow_execute(&cfg, inst, guarded, order_qty, &view.last) {
+                    // The paper fill takes the whole rounded qty
+                    live_ops::track_rounding(
+                        &cfg,
+                        inst,
+                        raw_qty * dir,
+                        order_qty * dir,
+                        view.last.c,
+                    );
+                    order_spacing.record(&inst.symbol, start);
+                    continue;
+                }
                 order_book.ensure(&client_id, order_qty);
                 pending_by_client.insert(
                     client_id.clone(),
                     PendingMeta {
                         strategy_id: inst.id.clone(),
+                        symbol: symbol.clone(),
                         intent_id: intent_id.clone(),
                         placed_ts: start,
                         order_id: None,
+                        intended_qty: raw_qty * dir,
                     },
                 );
                 if let Ok((prev, next)) =
@@ -411,29 +844,63 @@ async fn main() -> Result<()> {
                         ("fsync", v_str("true")),
                     ]),
                 );
+                let urgency = inst.strategy.urgency(guarded, &inst.state);
+                let (limit_price, post_only) = live_ops::execution_intent(
+                    &cfg,
+                    &filters,
+                    guarded,
+                    urgency,
+                    view.last.c,
+                    live_adapter,
+                );
+                let side = match guarded {
+                    Action::Buy { .. } => "BUY",
+                    Action::Sell { .. } => "SELL",
+                    Action::Close => {
+                        if inst.state.portfolio.position >= 0.0 {
+                            "SELL"
+                        } else {
+                            "BUY"
+                        }
+                    }
+                    Action::Hold => "HOLD",
+                };
+                let reason = Some(inst.state.signal_reason.to_string()).filter(|r| !r.is_empty());
                 let _ = wal.append_entry(&crate::reliability::wal::WalEntry::PlaceOrder {
                     ts: state::now_ts(),
                     intent_id: intent_id.clone(),
                     strategy_id: Some(inst.id.clone()),
                     client_order_id: Some(client_id.clone()),
                     params_hash: params_hash(&client_id),
-                    symbol: cfg.symbol.clone(),
-                    side: match guarded {
-                        Action::Buy { .. } => "BUY".to_string(),
-                        Action::Sell { .. } => "SELL".to_string(),
-                        Action::Close => {
-                            if inst.state.portfolio.position >= 0.0 {
-                                "SELL".to_string()
-                            } else {
-                                "BUY".to_string()
-                            }
-                        }
-                        Action::Hold => "HOLD".to_string(),
-                    },
+                    symbol: inst.symbol.clone(),
+                    side: side.to_string(),
                     qty: order_qty,
+                    reason: reason.clone(),
                     fsync: true,
                 });
+                let _ = audit.record(&AuditRecord {
+                    ts: state::now_ts(),
+                    intent_id: intent_id.clone(),
+                    stage: AuditStage::Intent,
+                    strategy_id: Some(inst.id.clone()),
+                    client_order_id: Some(client_id.clone()),
+                    symbol: Some(inst.symbol.clone()),
+                    side: Some(side.to_string()),
+                    qty: Some(order_qty),
+                    price: limit_price,
+                    detail: reason,
+                    ..AuditRecord::default()
+                });
 
+                let order_req = live_ops::order_request(
+                    &inst.symbol,
+                    guarded,
+                    inst.state.portfolio.position,
+                    order_qty,
+                    &client_id,
+                    limit_price,
+                    post_only,
+                );
                 json_log(
                     "exec_wrapper",
                     obj(&[
@@ -446,204 +913,339 @@ async fn main() -> Result<()> {
                         ("client_order_id", v_str(&client_id)),
                         ("attempt", v_num(1.0)),
                         ("status", v_str("request_sent")),
+                        (
+                            "reduce_only",
+                            serde_json::Value::Bool(order_req.reduce_only),
+                        ),
+                        ("post_only", serde_json::Value::Bool(order_req.post_only)),
                     ]),
                 );
-                let side = match guarded {
-                    Action::Buy { .. } => types::Side::Buy,
-                    Action::Sell { .. } => types::Side::Sell,
-                    Action::Close => {
-                        if inst.state.portfolio.position >= 0.0 {
-                            types::Side::Sell
-                        } else {
-                            types::Side::Buy
-                        }
-                    }
-                    Action::Hold => types::Side::Sell,
-                };
-                let order_type = if live_adapter {
-                    types::OrderType::Market
-                } else {
-                    types::OrderType::Limit
-                };
-                let price = if live_adapter {
-                    None
-                } else {
-                    Some(view.last.c)
-                };
-                let resp = adapter.place_order(types::OrderRequest {
-                    symbol: cfg.symbol.clone(),
-                    side,
-                    order_type,
-                    price,
-                    qty: order_qty,
-                    client_id: client_id.clone(),
+                let _ = audit.record(&AuditRecord {
+                    ts: state::now_ts(),
+                    intent_id: intent_id.clone(),
+                    stage: AuditStage::Submit,
+                    client_order_id: Some(client_id.clone()),
+                    ..AuditRecord::default()
                 });
-                match resp {
-                    Ok(resp) => {
-                        if let Some(meta) = pending_by_client.get_mut(&client_id) {
-                            meta.order_id = Some(resp.order_id.clone());
-                        }
-                        if let Ok((prev, next)) = order_book.apply(
-                            &client_id,
-                            crate::verify::order_sm::Event::Ack {
-                                order_id: resp.order_id.clone(),
-                            },
-                        ) {
-                            json_log(
-                                "order_state",
-                                obj(&[
-                                    ("order_id", v_str(&client_id)),
-                                    ("prev_state", v_str(&format!("{:?}", prev))),
-                                    ("new_state", v_str(&format!("{:?}", next))),
-                                    ("evidence", v_str("api_ack")),
-                                ]),
-                            );
-                        }
-                        json_log(
-                            "exec_wrapper",
-                            obj(&[
-                                ("intent_id", v_str(&intent_id)),
-                                ("client_order_id", v_str(&client_id)),
-                                ("status", v_str("response")),
-                                ("order_id", v_str(&resp.order_id)),
-                            ]),
-                        );
+                jobs.push(live_ops::OrderJob {
+                    index,
+                    intent_id,
+                    client_id,
+                    action: guarded,
+                    req: order_req,
+                });
+                continue;
+            }
+
+            report.push(index);
+        }
+        // Orders go out together, bounded by MAX_ORDERS_IN_FLIGHT; responses
+        // and fills are applied afterwards in strategy order
+        let outcomes = live_ops::place_orders(
+            adapter.as_mut(),
+            &mut order_book,
+            &mut wal,
+            jobs,
+            cfg.max_orders_in_flight,
+        )
+        .await;
+        for outcome in outcomes {
+            let live_ops::OrderOutcome {
+                job,
+                resp,
+                sent_ms,
+                recv_ms,
+            } = outcome;
+            let live_ops::OrderJob {
+                index,
+                intent_id,
+                client_id,
+                action: guarded,
+                req,
+            } = job;
+            let inst = &mut strategies[index];
+            let symbol = inst.symbol.clone();
+            let view = market.view(&symbol);
+            let Some(resp) = resp else {
+                continue;
+            };
+            let latency = order_latency.record(sent_ms, recv_ms);
+            match resp {
+                Ok(resp) => {
+                    inst.state.record_trade_ts(start);
+                    // Only accepted orders count toward spacing; a rejected
+                    // submit must not hold back the retry
+                    order_spacing.record(&symbol, start);
+                    if let Some(meta) = pending_by_client.get_mut(&client_id) {
+                        meta.order_id = Some(resp.order_id.clone());
                     }
-                    Err(err) => {
-                        let _ = order_book.apply(
-                            &client_id,
-                            crate::verify::order_sm::Event::Reject {
-                                reason: err.clone(),
-                            },
-                        );
-                        pending_by_client.remove(&client_id);
-                        circuit.record_failure();
+                    if let Ok((prev, next)) = order_book.apply(
+                        &client_id,
+                        crate::verify::order_sm::Event::Ack {
+                            order_id: resp.order_id.clone(),
+                        },
+                    ) {
                         json_log(
-                            "exec_wrapper",
+                            "order_state",
                             obj(&[
-                                ("intent_id", v_str(&intent_id)),
-                                ("client_order_id", v_str(&client_id)),
-                                ("status", v_str("error")),
-                                ("error", v_str(&err)),
+                                ("order_id", v_str(&client_id)),
+                                ("prev_state", v_str(&format!("{:?}", prev))),
+                                ("new_state", v_str(&format!("{:?}", next))),
+                                ("evidence", v_str("api_ack")),
                             ]),
                         );
-                        continue;
                     }
-                }
-
-                if live_adapter {
+                    let _ = audit.record(&AuditRecord {
+                        ts: state::now_ts(),
+                        intent_id: intent_id.clone(),
+                        stage: AuditStage::Ack,
+                        client_order_id: Some(client_id.clone()),
+                        exchange_order_id: Some(resp.order_id.clone()),
+                        ..AuditRecord::default()
+                    });
                     json_log(
                         "exec_wrapper",
                         obj(&[
                             ("intent_id", v_str(&intent_id)),
                             ("client_order_id", v_str(&client_id)),
-                            ("status", v_str("pending_fill")),
+                            ("status", v_str("response")),
+                            ("order_id", v_str(&resp.order_id)),
+                            ("latency_ms", v_num(latency as f64)),
                         ]),
                     );
                 }
-
-                // Execute with retry (paper execution path only)
-                if !live_adapter {
-                    let _exec_prof = ProfileScope::new("profile", "execute_order");
-                    let fill = retry_async(&retry_cfg, "execute_order", || {
-                        exchange.execute(&cfg.symbol, guarded, &inst.state)
-                    })
-                    .await?;
-
-                    if view.last.c > 0.0 {
-                        let slip_pct = ((fill.price - view.last.c).abs()) / view.last.c;
-                        if slip_pct > cfg.max_fill_slip_pct {
-                            halt_on_slip = true;
-                            json_log(
-                                "risk_guard",
-                                obj(&[
-                                    ("check", v_str("fill_slippage")),
-                                    ("result", v_str("halt")),
-                                    ("slip_pct", v_num(slip_pct)),
-                                    ("threshold", v_num(cfg.max_fill_slip_pct)),
-                                ]),
-                            );
-                        }
-                    }
-                    if let Ok((prev, next)) = order_book.apply(
+                Err(err) => {
+                    let _ = order_book.apply(
                         &client_id,
-                        crate::verify::order_sm::Event::Fill {
-                            fill_id: format!("fill-{}", client_id),
-                            qty: fill.qty,
-                            price: fill.price,
+                        crate::verify::order_sm::Event::Reject {
+                            reason: err.clone(),
                         },
-                    ) {
-                        json_log(
-                            "order_state",
-                            obj(&[
-                                ("order_id", v_str(&client_id)),
-                                ("prev_state", v_str(&format!("{:?}", prev))),
-                                ("new_state", v_str(&format!("{:?}", next))),
-                                ("fill_qty", v_num(fill.qty)),
-                                ("price", v_num(fill.price)),
-                                ("source", v_str("trade_stream")),
-                            ]),
-                        );
-                    }
-                    let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Fill {
+                    );
+                    pending_by_client.remove(&client_id);
+                    let _ = audit.record(&AuditRecord {
                         ts: state::now_ts(),
                         intent_id: intent_id.clone(),
-                        params_hash: params_hash(&client_id),
-                        price: fill.price,
-                        qty: fill.qty,
-                        fee: fill.fee,
-                        fsync: true,
+                        stage: AuditStage::Reject,
+                        client_order_id: Some(client_id.clone()),
+                        detail: Some(err.clone()),
+                        ..AuditRecord::default()
                     });
-                    let realized = inst.state.portfolio.apply_fill(fill);
-                    inst.state.metrics.pnl += realized;
-                    if realized > 0.0 {
-                        inst.state.metrics.wins += 1;
-                        circuit.record_success();
-                    } else if realized < 0.0 {
-                        inst.state.metrics.losses += 1;
-                        inst.state.last_loss_ts = fill.ts;
+                    // A would-cross post-only reject is a normal outcome, not a venue fault;
+                    // the strategy may simply retry on a later bar
+                    let post_only_reject =
+                        types::classify_reject(&err) == types::RejectKind::PostOnlyWouldCross;
+                    if !post_only_reject {
                         circuit.record_failure();
-                    } else {
-                        circuit.record_success();
-                    }
-                    inst.state.last_trade_ts = fill.ts;
-                    let day = fill.ts / 86_400;
-                    if inst.state.trade_day != day {
-                        inst.state.trade_day = day;
-                        inst.state.trades_today = 0;
                     }
-                    inst.state.trades_today += 1;
                     json_log(
-                        "position_agg",
+                        "exec_wrapper",
                         obj(&[
-                            ("asset", v_str(&cfg.symbol)),
-                            ("spot", v_num(inst.state.portfolio.position)),
-                            ("perp", v_num(0.0)),
-                            ("net", v_num(inst.state.portfolio.position)),
+                            ("intent_id", v_str(&intent_id)),
+                            ("client_order_id", v_str(&client_id)),
+                            (
+                                "status",
+                                v_str(if post_only_reject {
+                                    "post_only_rejected"
+                                } else {
+                                    "error"
+                                }),
+                            ),
+                            ("error", v_str(&err)),
+                            ("latency_ms", v_num(latency as f64)),
                         ]),
                     );
+                    continue;
+                }
+            }
+
+            if live_adapter {
+                json_log(
+                    "exec_wrapper",
+                    obj(&[
+                        ("intent_id", v_str(&intent_id)),
+                        ("client_order_id", v_str(&client_id)),
+                        ("status", v_str("pending_fill")),
+                    ]),
+                );
+            }
+
+            // Execute with retry (paper execution path only), at the rounded
+            // qty the WAL and order book recorded
+            if !live_adapter {
+                let _exec_prof = ProfileScope::new("profile", "execute_order");
+                let sent = live_ops::sent_action(guarded, inst.state.portfolio.position, req.qty);
+                let fill = retry_async(&retry_cfg, "execute_order", || {
+                    exchange.execute(&inst.symbol, sent, &inst.state)
+                })
+                .await?;
+
+                if view.last.c > 0.0 {
+                    let slip_pct = ((fill.price - view.last.c).abs()) / view.last.c;
+                    if slip_pct > cfg.max_fill_slip_pct {
+                        halt_on_slip = true;
+                        json_log(
+                            "risk_guard",
+                            obj(&[
+                                ("check", v_str("fill_slippage")),
+                                ("result", v_str("halt")),
+                                ("slip_pct", v_num(slip_pct)),
+                                ("threshold", v_num(cfg.max_fill_slip_pct)),
+                            ]),
+                        );
+                    }
+                }
+                match order_book.apply(
+                    &client_id,
+                    crate::verify::order_sm::Event::Fill {
+                        fill_id: format!("fill-{}", client_id),
+                        qty: fill.qty,
+                        price: fill.price,
+                    },
+                ) {
+                    Ok((prev, next)) => json_log(
+                        "order_state",
+                        obj(&[
+                            ("order_id", v_str(&client_id)),
+                            ("prev_state", v_str(&format!("{:?}", prev))),
+                            ("new_state", v_str(&format!("{:?}", next))),
+                            ("fill_qty", v_num(fill.qty)),
+                            ("price", v_num(fill.price)),
+                            ("source", v_str("trade_stream")),
+                        ]),
+                    ),
+                    Err(err) => json_log_at(
+                        Level::Warn,
+                        "order_state",
+                        obj(&[
+                            ("order_id", v_str(&client_id)),
+                            ("status", v_str("fill_rejected")),
+                            ("fill_qty", v_num(fill.qty)),
+                            ("order_qty", v_num(req.qty)),
+                            ("error", v_str(&err)),
+                        ]),
+                    ),
+                }
+                let _ = wal.append_entry(&crate::reliability::wal::WalEntry::Fill {
+                    ts: state::now_ts(),
+                    intent_id: intent_id.clone(),
+                    params_hash: params_hash(&client_id),
+                    price: fill.price,
+                    qty: fill.qty,
+                    fee: fill.fee,
+                    fsync: true,
+                });
+                let _ = audit.record(&AuditRecord {
+                    ts: state::now_ts(),
+                    intent_id: intent_id.clone(),
+                    stage: AuditStage::Fill,
+                    client_order_id: Some(client_id.clone()),
+                    qty: Some(fill.qty),
+                    price: Some(fill.price),
+                    fee: Some(fill.fee),
+                    ..AuditRecord::default()
+                });
+                let prev_portfolio = inst.state.portfolio;
+                let realized = inst.state.portfolio.apply_fill(fill);
+                inst.state.count_tranche(prev_portfolio.position, true);
+                if let Err(v) = verify::invariants::check_portfolio(
+                    &prev_portfolio,
+                    &inst.state.portfolio,
+                    &fill,
+                    realized,
+                ) {
                     json_log(
                         "audit",
                         obj(&[
+                            ("event", v_str("invariant_violation")),
+                            ("strategy_id", v_str(&inst.id)),
                             ("intent_id", v_str(&intent_id)),
-                            ("client_order_id", v_str(&client_id)),
-                            ("exchange_order_id", v_str("stub")),
-                            ("state", v_str("FILLED")),
-                            ("source_of_truth", v_str("trade_stream")),
+                            ("msg", v_str(&v.msg)),
                         ]),
                     );
                 }
+                inst.state
+                    .metrics
+                    .record_fill(realized, &fill, inst.state.portfolio.position);
+                if realized < 0.0 {
+                    inst.state.record_loss(fill.ts, realized);
+                    circuit.record_failure();
+                } else {
+                    circuit.record_success();
+                }
+                inst.state.last_trade_ts = fill.ts;
+                let day = fill.ts / 86_400;
+                if inst.state.trade_day != day {
+                    inst.state.trade_day = day;
+                    inst.state.trades_today = 0;
+                }
+                inst.state.trades_today += 1;
+                json_log(
+                    "position_agg",
+                    obj(&[
+                        ("asset", v_str(&inst.symbol)),
+                        ("spot", v_num(inst.state.portfolio.position)),
+                        ("perp", v_num(0.0)),
+                        ("net", v_num(inst.state.portfolio.position)),
+                    ]),
+                );
+                json_log(
+                    "audit",
+                    obj(&[
+                        ("intent_id", v_str(&intent_id)),
+                        ("client_order_id", v_str(&client_id)),
+                        ("exchange_order_id", v_str("stub")),
+                        ("state", v_str("FILLED")),
+                        ("source_of_truth", v_str("trade_stream")),
+                    ]),
+                );
             }
+            report.push(index);
+        }
 
-            inst.state.portfolio.equity =
-                inst.state.portfolio.cash + inst.state.portfolio.position * view.last.c;
+        report.sort_unstable();
+        for index in report {
+            let inst = &mut strategies[index];
+            let price = market.view(&inst.symbol).last.c;
+            inst.state.portfolio.equity = inst.state.portfolio.mark_equity(price);
+            if let Some(day) = metrics.roll_day(&mut inst.state, start) {
+                store.archive_day(&inst.id, &day)?;
+                json_log(
+                    "metrics_daily",
+                    obj(&[
+                        ("strategy_id", v_str(&inst.id)),
+                        ("day", v_num(day.day as f64)),
+                        ("pnl", v_num(day.pnl)),
+                        ("trades", v_num(day.trades as f64)),
+                        ("win_rate", v_num(day.win_rate)),
+                        ("drawdown", v_num(day.max_drawdown)),
+                    ]),
+                );
+            }
             metrics.update(&mut inst.state);
+            let throttle = adapter.throttle_stats().unwrap_or_default();
+            #[cfg(feature = "prometheus")]
+            if let Ok(mut reg) = prom.lock() {
+                reg.publish(&inst.id, &inst.symbol, &inst.state);
+                reg.set_circuit(circuit.state);
+                reg.set_throttle(throttle);
+            }
             json_log(
                 "metrics",
                 obj(&[
                     ("equity", v_num(inst.state.portfolio.equity)),
                     ("pnl", v_num(inst.state.metrics.pnl)),
                     ("drawdown", v_num(inst.state.metrics.max_drawdown)),
+                    ("win_rate", v_num(inst.state.metrics.win_rate())),
+                    ("avg_win", v_num(inst.state.metrics.avg_win())),
+                    ("avg_loss", v_num(inst.state.metrics.avg_loss())),
+                    ("expectancy", v_num(inst.state.metrics.expectancy())),
+                    ("payoff_ratio", v_num(inst.state.metrics.payoff_ratio())),
+                    ("loss_p99", v_num(inst.state.metrics.loss_p99())),
+                    ("sharpe", v_num(inst.state.metrics.sharpe())),
+                    ("sortino", v_num(inst.state.metrics.sortino())),
+                    ("api_requests", v_num(throttle.requests as f64)),
+                    ("throttle_waits", v_num(throttle.waits as f64)),
+                    ("throttle_wait_secs", v_num(throttle.waited_secs)),
                 ]),
             );
         }
@@ -653,9 +1255,30 @@ async fn main() -> Result<()> {
             }
         }
 
-        if live_adapter && start.saturating_sub(last_reconcile_ts) >= cfg.reconcile_secs {
+        let reconcile_due = start.saturating_sub(last_reconcile_ts) >= cfg.reconcile_secs;
+        if live_adapter && (live_ops::take_reconcile_request(&cfg) || reconcile_due) {
             last_reconcile_ts = start;
-            live_ops::reconcile_binance(&cfg, &mut strategies, &mut pending_by_client).await;
+            let _drift = live_ops::reconcile_binance(
+                &cfg,
+                &mut strategies,
+                &mut pending_by_client,
+                &market,
+                &mut wal,
+            )
+            .await;
+            #[cfg(feature = "health")]
+            if let (Some(drift), Ok(mut h)) = (_drift, health_state.lock()) {
+                h.last_reconcile_drift = Some(drift);
+            }
+        }
+
+        #[cfg(feature = "health")]
+        if let Ok(mut h) = health_state.lock() {
+            h.halted = strategies.iter().all(|s| s.state.trading_halted);
+            h.circuit = circuit.state;
+            if let Some(oldest) = candle_advance.values().map(|&(_, wall)| wall).min() {
+                h.last_candle_advance_ts = oldest;
+            }
         }
 
         live_ops::cancel_stale_orders(
@@ -665,6 +1288,7 @@ async fn main() -> Result<()> {
             &mut pending_by_client,
             &mut order_book,
             &mut wal,
+            &mut audit,
         );
 
         if start % (cfg.persist_every_secs) == 0 {
@@ -672,7 +1296,25 @@ async fn main() -> Result<()> {
             store.persist_snapshot(start, &strategies)?;
             // Write WAL snapshot for each strategy
             for inst in strategies.iter() {
-                let _ = wal.write_snapshot(&inst.id, &inst.state.portfolio, inst.state.metrics.pnl);
+                let _ = wal.write_snapshot(&inst.id, &inst.state);
+            }
+            // Everything recovery needs now sits after these snapshots
+            match wal.compact() {
+                Ok((kept, total)) => json_log(
+                    "wal",
+                    obj(&[
+                        ("status", v_str("compacted")),
+                        ("kept", v_num(kept as f64)),
+                        ("total", v_num(total as f64)),
+                    ]),
+                ),
+                Err(err) => json_log(
+                    "wal",
+                    obj(&[
+                        ("status", v_str("compact_failed")),
+                        ("error", v_str(&err.to_string())),
+                    ]),
+                ),
             }
             json_log(
                 "reconcile",
@@ -694,7 +1336,19 @@ async fn main() -> Result<()> {
             );
         }
 
-        let sleep_for = cfg.sleep_until_next_candle(start);
-        sleep(Duration::from_secs(sleep_for)).await;
+        let sleep_for = cfg.sleep_until_next_candle_ms(ts_epoch_ms());
+        tokio::select! {
+            _ = sleep(Duration::from_millis(sleep_for)) => {}
+            _ = &mut shutdown_rx => {
+                live_ops::shutdown_snapshot(
+                    now_ts(),
+                    &strategies,
+                    &pending_by_client,
+                    &mut wal,
+                    &mut store,
+                )?;
+                return Ok(());
+            }
+        }
     }
 }
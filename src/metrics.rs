@@ -1,5 +1,307 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::strategy::StrategyState;
 
+/// Streaming quantile estimate (Jain & Chlamtac P² algorithm).
+///
+/// Keeps five markers whose heights track the minimum, p/2, p, (1+p)/2 and
+/// maximum quantiles, so memory stays constant however long the run is.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct P2Quantile {
+    p: f64,
+    count: u64,
+    heights: [f64; 5],
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    pub fn new(p: f64) -> Self {
+        let p = p.clamp(0.0, 1.0);
+        Self {
+            p,
+            count: 0,
+            heights: [0.0; 5],
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    pub fn observe(&mut self, x: f64) {
+        if !x.is_finite() {
+            return;
+        }
+        if self.count < 5 {
+            self.heights[self.count as usize] = x;
+            self.count += 1;
+            if self.count == 5 {
+                self.heights.sort_by(|a, b| a.total_cmp(b));
+            }
+            return;
+        }
+        self.count += 1;
+
+        let q = &mut self.heights;
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (0..4).find(|&i| x < q[i + 1]).unwrap_or(3)
+        };
+        for n in &mut self.positions[k + 1..] {
+            *n += 1.0;
+        }
+        for (d, inc) in self.desired.iter_mut().zip(self.increments) {
+            *d += inc;
+        }
+
+        for i in 1..4 {
+            let n = self.positions;
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let s = d.signum();
+                let parabolic = q[i]
+                    + s / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + s) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - s) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    let j = if s > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + s * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                self.positions[i] += s;
+            }
+        }
+    }
+
+    /// Current estimate; exact (nearest rank) until five samples are seen.
+    pub fn value(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            c if c < 5 => {
+                let mut seen = self.heights[..c as usize].to_vec();
+                seen.sort_by(|a, b| a.total_cmp(b));
+                let idx = (self.p * (c - 1) as f64).round() as usize;
+                Some(seen[idx])
+            }
+            _ => Some(self.heights[2]),
+        }
+    }
+}
+
+/// Defaults to the 99th percentile, the tail the risk report tracks.
+impl Default for P2Quantile {
+    fn default() -> Self {
+        Self::new(0.99)
+    }
+}
+
+/// Most trades `TradeWindow` remembers.
+pub const TRADE_WINDOW_CAP: usize = 64;
+
+/// Fixed-size ring of the latest round-trip PnLs (kept `Copy` like the rest
+/// of `MetricsState`).
+#[derive(Debug, Clone, Copy)]
+pub struct TradeWindow {
+    pnls: [f64; TRADE_WINDOW_CAP],
+    len: usize,
+    next: usize,
+}
+
+impl TradeWindow {
+    pub fn push(&mut self, pnl: f64) {
+        self.pnls[self.next] = pnl;
+        self.next = (self.next + 1) % TRADE_WINDOW_CAP;
+        self.len = (self.len + 1).min(TRADE_WINDOW_CAP);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+
+    /// Gross profit / gross loss over the newest `k` trades (capped at
+    /// `TRADE_WINDOW_CAP`). Infinite with no losses; None until `k` trades.
+    pub fn profit_factor(&self, k: usize) -> Option<f64> {
+        let k = k.min(TRADE_WINDOW_CAP);
+        if k == 0 || self.len < k {
+            return None;
+        }
+        let (mut won, mut lost) = (0.0, 0.0);
+        for i in 1..=k {
+            let pnl = self.pnls[(self.next + TRADE_WINDOW_CAP - i) % TRADE_WINDOW_CAP];
+            if pnl > 0.0 {
+                won += pnl;
+            } else {
+                lost -= pnl;
+            }
+        }
+        Some(if lost > 0.0 {
+            won / lost
+        } else {
+            f64::INFINITY
+        })
+    }
+
+    /// Full Kelly fraction `p - (1 - p) / b` over the newest `k` trades, with
+    /// `p` the win rate and `b` the average win over the average loss.
+    /// Negative when the window has no edge; `p` itself with no losses.
+    /// None until `k` trades.
+    pub fn kelly_fraction(&self, k: usize) -> Option<f64> {
+        let k = k.min(TRADE_WINDOW_CAP);
+        if k == 0 || self.len < k {
+            return None;
+        }
+        let (mut wins, mut won, mut losses, mut lost) = (0u32, 0.0, 0u32, 0.0);
+        for i in 1..=k {
+            let pnl = self.pnls[(self.next + TRADE_WINDOW_CAP - i) % TRADE_WINDOW_CAP];
+            if pnl > 0.0 {
+                wins += 1;
+                won += pnl;
+            } else if pnl < 0.0 {
+                losses += 1;
+                lost -= pnl;
+            }
+        }
+        let p = wins as f64 / k as f64;
+        if losses == 0 {
+            return Some(p);
+        }
+        if wins == 0 {
+            return Some(-1.0);
+        }
+        let payoff = (won / wins as f64) / (lost / losses as f64);
+        Some(p - (1.0 - p) / payoff)
+    }
+}
+
+/// The `len` values of a ring ending just before `next`, oldest first.
+fn ring_oldest_first(buf: &[f64], len: usize, next: usize) -> impl Iterator<Item = &f64> {
+    let cap = buf.len();
+    (0..len)
+        .rev()
+        .map(move |i| &buf[(next + cap - 1 - i) % cap])
+}
+
+/// Serialized as the remembered PnLs, oldest first.
+impl Serialize for TradeWindow {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(ring_oldest_first(&self.pnls, self.len, self.next))
+    }
+}
+
+impl<'de> Deserialize<'de> for TradeWindow {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let mut window = Self::default();
+        for pnl in Vec::<f64>::deserialize(d)? {
+            window.push(pnl);
+        }
+        Ok(window)
+    }
+}
+
+impl Default for TradeWindow {
+    fn default() -> Self {
+        Self {
+            pnls: [0.0; TRADE_WINDOW_CAP],
+            len: 0,
+            next: 0,
+        }
+    }
+}
+
+/// Most per-unit bar losses `TailWindow` remembers.
+pub const TAIL_WINDOW_CAP: usize = 128;
+
+/// Fewest samples before `TailWindow::cvar` returns an estimate.
+pub const TAIL_MIN_SAMPLES: usize = 20;
+
+/// Rolling per-bar equity loss per unit of position held, the input to
+/// CVaR-budget sizing. Fixed-size like `TradeWindow` so `MetricsState`
+/// stays `Copy`.
+#[derive(Debug, Clone, Copy)]
+pub struct TailWindow {
+    losses: [f64; TAIL_WINDOW_CAP],
+    len: usize,
+    next: usize,
+}
+
+impl TailWindow {
+    pub fn push(&mut self, loss: f64) {
+        self.losses[self.next] = loss;
+        self.next = (self.next + 1) % TAIL_WINDOW_CAP;
+        self.len = (self.len + 1).min(TAIL_WINDOW_CAP);
+    }
+
+    /// Expected shortfall at `alpha`: the mean of the worst `1 - alpha` of
+    /// losses (at least one). None until `TAIL_MIN_SAMPLES` bars.
+    pub fn cvar(&self, alpha: f64) -> Option<f64> {
+        if self.len < TAIL_MIN_SAMPLES {
+            return None;
+        }
+        let mut seen = self.losses[..self.len].to_vec();
+        seen.sort_by(|a, b| b.total_cmp(a));
+        let k = (((1.0 - alpha.clamp(0.0, 1.0)) * self.len as f64).ceil() as usize).max(1);
+        Some(seen[..k].iter().sum::<f64>() / k as f64)
+    }
+}
+
+/// Serialized as the remembered losses, oldest first.
+impl Serialize for TailWindow {
+    fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        s.collect_seq(ring_oldest_first(&self.losses, self.len, self.next))
+    }
+}
+
+impl<'de> Deserialize<'de> for TailWindow {
+    fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        let mut window = Self::default();
+        for loss in Vec::<f64>::deserialize(d)? {
+            window.push(loss);
+        }
+        Ok(window)
+    }
+}
+
+impl Default for TailWindow {
+    fn default() -> Self {
+        Self {
+            losses: [0.0; TAIL_WINDOW_CAP],
+            len: 0,
+            next: 0,
+        }
+    }
+}
+
+fn welford(x: f64, n: &mut u64, mean: &mut f64, m2: &mut f64) {
+    *n += 1;
+    let delta = x - *mean;
+    *mean += delta / *n as f64;
+    *m2 += delta * (x - *mean);
+}
+
+/// One strategy's figures for a single UTC day, archived at the boundary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DailyMetrics {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    pub pnl: f64,
+    /// Round trips closed during the day.
+    pub trades: u64,
+    pub wins: u64,
+    pub win_rate: f64,
+    /// Worst drawdown from the intraday equity peak (negative fraction).
+    pub max_drawdown: f64,
+}
+
 pub struct MetricsEngine;
 
 impl MetricsEngine {
@@ -7,37 +309,415 @@ impl MetricsEngine {
         Self
     }
 
-    /// Update metrics with mark-to-market price
-    pub fn update_with_price(&mut self, state: &mut StrategyState, mark_price: f64) {
-        // Mark to market: equity = cash + position * current_price
-        state.portfolio.equity = state.portfolio.cash + state.portfolio.position * mark_price;
+    /// Feed the bar-over-bar equity return into the running stats and tail
+    /// estimators.
+    fn record_return(state: &mut StrategyState) {
+        let equity = state.portfolio.equity;
+        let prev = state.metrics.last_equity;
+        if prev > 0.0 && equity.is_finite() {
+            let ret = equity / prev - 1.0;
+            let m = &mut state.metrics;
+            welford(ret, &mut m.n, &mut m.mean, &mut m.m2);
+            if ret < 0.0 {
+                m.down_n += 1;
+                m.down_sq += ret * ret;
+            }
+            state.metrics.loss_quantile.observe(-ret);
+            let held = state.metrics.last_position.abs();
+            if held > 0.0 {
+                state.metrics.unit_losses.push((prev - equity) / held);
+            }
+        }
+        state.metrics.last_equity = equity;
+        state.metrics.last_position = state.portfolio.position;
+    }
 
+    /// Raise the lifetime and intraday equity peaks and record the worst
+    /// drawdown from each.
+    fn track_drawdown(state: &mut StrategyState) {
         let equity = state.portfolio.equity;
-        if equity > state.metrics.equity_peak {
-            state.metrics.equity_peak = equity;
+        let m = &mut state.metrics;
+        for (peak, max_dd) in [
+            (&mut m.equity_peak, &mut m.max_drawdown),
+            (&mut m.day_peak, &mut m.day_max_drawdown),
+        ] {
+            if equity > *peak {
+                *peak = equity;
+            }
+            let drawdown = if *peak > 0.0 {
+                (equity - *peak) / *peak
+            } else {
+                0.0
+            };
+            if drawdown < *max_dd {
+                *max_dd = drawdown;
+            }
         }
-        let drawdown = if state.metrics.equity_peak > 0.0 {
-            (equity - state.metrics.equity_peak) / state.metrics.equity_peak
-        } else {
-            0.0
-        };
-        if drawdown < state.metrics.max_drawdown {
-            state.metrics.max_drawdown = drawdown;
+    }
+
+    /// At the first update of a new UTC day, close out the previous day's
+    /// figures and restart the daily accumulators from the current lifetime
+    /// totals. Lifetime metrics are untouched. Returns the finished day, or
+    /// None mid-day and on the very first call (no day to close yet).
+    pub fn roll_day(&mut self, state: &mut StrategyState, now_ts: u64) -> Option<DailyMetrics> {
+        let day = now_ts / 86_400;
+        let m = &mut state.metrics;
+        if m.day == day {
+            return None;
         }
+        let archived = (m.day != 0).then(|| {
+            let wins = m.wins - m.day_start_wins;
+            let trades = wins + (m.losses - m.day_start_losses);
+            DailyMetrics {
+                day: m.day,
+                pnl: m.pnl - m.day_start_pnl,
+                trades,
+                wins,
+                win_rate: if trades > 0 {
+                    wins as f64 / trades as f64
+                } else {
+                    0.0
+                },
+                max_drawdown: m.day_max_drawdown,
+            }
+        });
+        m.day = day;
+        m.day_start_pnl = m.pnl;
+        m.day_start_wins = m.wins;
+        m.day_start_losses = m.losses;
+        m.day_peak = state.portfolio.equity;
+        m.day_max_drawdown = 0.0;
+        archived
+    }
+
+    /// Update metrics with mark-to-market price
+    pub fn update_with_price(&mut self, state: &mut StrategyState, mark_price: f64) {
+        // Mark to market: equity = cash + position value (position * price for linear)
+        state.portfolio.equity = state.portfolio.mark_equity(mark_price);
+        Self::record_return(state);
+
+        Self::track_drawdown(state);
     }
 
     pub fn update(&mut self, state: &mut StrategyState) {
-        let equity = state.portfolio.equity;
-        if equity > state.metrics.equity_peak {
-            state.metrics.equity_peak = equity;
+        Self::record_return(state);
+        Self::track_drawdown(state);
+    }
+}
+
+/// Return correlation at or above which two ensemble members are flagged as
+/// the same bet.
+pub const CORRELATION_FLAG: f64 = 0.9;
+
+/// Persisted `(ts, equity)` snapshots of one strategy, oldest first.
+pub type EquityCurve = Vec<(u64, f64)>;
+
+/// Pairwise Pearson correlation of strategy equity returns. `values[i][j]` is
+/// NaN when the pair shares under three snapshots or either curve is flat.
+#[derive(Debug, Clone, Serialize)]
+pub struct CorrelationMatrix {
+    pub ids: Vec<String>,
+    pub values: Vec<Vec<f64>>,
+}
+
+impl CorrelationMatrix {
+    /// Distinct pairs correlated at or above `threshold`, most correlated first.
+    pub fn correlated_pairs(&self, threshold: f64) -> Vec<(String, String, f64)> {
+        let mut pairs = Vec::new();
+        for i in 0..self.ids.len() {
+            for j in i + 1..self.ids.len() {
+                let r = self.values[i][j];
+                if r >= threshold {
+                    pairs.push((self.ids[i].clone(), self.ids[j].clone(), r));
+                }
+            }
         }
-        let drawdown = if state.metrics.equity_peak > 0.0 {
-            (equity - state.metrics.equity_peak) / state.metrics.equity_peak
-        } else {
-            0.0
-        };
-        if drawdown < state.metrics.max_drawdown {
-            state.metrics.max_drawdown = drawdown;
+        pairs.sort_by(|a, b| b.2.total_cmp(&a.2));
+        pairs
+    }
+}
+
+/// Correlate bar-over-bar returns of persisted equity curves, `(ts, equity)`
+/// per strategy. Curves are joined on snapshot timestamp, so returns line up.
+/// Levels are not compared: any two trending curves would look correlated.
+pub fn correlation_matrix(curves: &[(String, EquityCurve)]) -> CorrelationMatrix {
+    let n = curves.len();
+    let mut values = vec![vec![f64::NAN; n]; n];
+    for i in 0..n {
+        values[i][i] = 1.0;
+        let by_ts: HashMap<u64, f64> = curves[i].1.iter().copied().collect();
+        for j in i + 1..n {
+            let joined: Vec<(f64, f64)> = curves[j]
+                .1
+                .iter()
+                .filter_map(|&(ts, b)| by_ts.get(&ts).map(|&a| (a, b)))
+                .collect();
+            let returns: Vec<(f64, f64)> = joined
+                .windows(2)
+                .filter(|w| w[0].0 > 0.0 && w[0].1 > 0.0)
+                .map(|w| (w[1].0 / w[0].0 - 1.0, w[1].1 / w[0].1 - 1.0))
+                .collect();
+            let r = pearson(&returns);
+            values[i][j] = r;
+            values[j][i] = r;
         }
     }
+    CorrelationMatrix {
+        ids: curves.iter().map(|(id, _)| id.clone()).collect(),
+        values,
+    }
+}
+
+fn pearson(pairs: &[(f64, f64)]) -> f64 {
+    if pairs.len() < 2 {
+        return f64::NAN;
+    }
+    let n = pairs.len() as f64;
+    let (mx, my) = pairs
+        .iter()
+        .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mx, my) = (mx / n, my / n);
+    let (mut sxy, mut sxx, mut syy) = (0.0, 0.0, 0.0);
+    for (x, y) in pairs {
+        sxy += (x - mx) * (y - my);
+        sxx += (x - mx) * (x - mx);
+        syy += (y - my) * (y - my);
+    }
+    if sxx <= 0.0 || syy <= 0.0 {
+        return f64::NAN;
+    }
+    sxy / (sxx * syy).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::ContractKind;
+
+    /// Deterministic uniform samples in [0, 1).
+    fn uniforms(seed: u64, n: usize) -> Vec<f64> {
+        let mut s = seed;
+        (0..n)
+            .map(|_| {
+                s = s
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (s >> 11) as f64 / (1u64 << 53) as f64
+            })
+            .collect()
+    }
+
+    fn exact(samples: &[f64], p: f64) -> f64 {
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        sorted[(p * (sorted.len() - 1) as f64).round() as usize]
+    }
+
+    #[test]
+    fn trade_window_profit_factor_uses_newest_trades() {
+        let mut w = TradeWindow::default();
+        assert_eq!(w.profit_factor(3), None);
+        for pnl in [-100.0, 2.0, -1.0, 3.0] {
+            w.push(pnl);
+        }
+        // Last three: +2 -1 +3 -> 5 / 1; the old -100 has rolled out of view
+        assert_eq!(w.profit_factor(3), Some(5.0));
+        assert_eq!(w.profit_factor(4), Some(5.0 / 101.0));
+        assert_eq!(w.profit_factor(5), None);
+        for _ in 0..TRADE_WINDOW_CAP {
+            w.push(1.0);
+        }
+        assert_eq!(w.profit_factor(TRADE_WINDOW_CAP * 2), Some(f64::INFINITY));
+        w.clear();
+        assert_eq!(w.profit_factor(1), None);
+    }
+
+    #[test]
+    fn p2_tracks_exact_percentile_on_uniform() {
+        let xs = uniforms(7, 20_000);
+        let mut est = P2Quantile::new(0.99);
+        for &x in &xs {
+            est.observe(x);
+        }
+        let got = est.value().unwrap();
+        assert!((got - exact(&xs, 0.99)).abs() < 0.005, "got {}", got);
+    }
+
+    #[test]
+    fn p2_tracks_exact_percentile_on_fat_tailed_losses() {
+        // Box-Muller normals scaled into returns, with occasional 5x shocks
+        let u = uniforms(42, 40_000);
+        let xs: Vec<f64> = u
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| {
+                let z = (-2.0 * (1.0 - pair[0]).ln()).sqrt()
+                    * (2.0 * std::f64::consts::PI * pair[1]).cos();
+                let shock = if i % 50 == 0 { 5.0 } else { 1.0 };
+                z * 0.01 * shock
+            })
+            .collect();
+        let mut est = P2Quantile::default();
+        for &x in &xs {
+            est.observe(x);
+        }
+        let want = exact(&xs, 0.99);
+        let got = est.value().unwrap();
+        assert!((got - want).abs() / want < 0.1, "got {} want {}", got, want);
+    }
+
+    #[test]
+    fn p2_is_exact_for_few_samples() {
+        let mut est = P2Quantile::new(0.5);
+        assert_eq!(est.value(), None);
+        for x in [3.0, 1.0, 2.0] {
+            est.observe(x);
+        }
+        assert_eq!(est.value(), Some(2.0));
+        est.observe(f64::NAN);
+        assert_eq!(est.value(), Some(2.0));
+    }
+
+    #[test]
+    fn update_records_bar_losses() {
+        let mut state = StrategyState::with_capital(1_000.0, ContractKind::Linear);
+        let mut engine = MetricsEngine::new();
+        for equity in [1_000.0, 990.0, 1_000.0, 950.0] {
+            state.portfolio.equity = equity;
+            engine.update(&mut state);
+        }
+        // Three returns seen, so the estimate is still the exact worst bar: 1000 -> 950
+        assert!((state.metrics.loss_p99() - 0.05).abs() < 1e-12);
+    }
+
+    #[test]
+    fn sortino_exceeds_sharpe_with_upside_skew() {
+        let mut state = StrategyState::with_capital(1_000.0, ContractKind::Linear);
+        let mut engine = MetricsEngine::new();
+        engine.update(&mut state);
+        // Big up bars, small down bars: volatile, but mostly to the upside
+        for i in 0..200 {
+            let ret = match i % 4 {
+                0 => 0.03,
+                1 => -0.002,
+                2 => -0.003,
+                _ => 0.001,
+            };
+            state.portfolio.equity *= 1.0 + ret;
+            engine.update(&mut state);
+        }
+        let m = &state.metrics;
+        assert_eq!(m.n, 200);
+        assert_eq!(m.down_n, 100);
+        assert!(m.sharpe() > 0.0);
+        assert!(
+            m.sortino() > 2.0 * m.sharpe(),
+            "sortino {} sharpe {}",
+            m.sortino(),
+            m.sharpe()
+        );
+    }
+
+    #[test]
+    fn sortino_counts_equal_losses() {
+        let mut state = StrategyState::with_capital(1_000.0, ContractKind::Linear);
+        let mut engine = MetricsEngine::new();
+        engine.update(&mut state);
+        // Every other bar loses exactly 1%: zero spread among the losses,
+        // but a real downside deviation of sqrt(0.5 * 0.01^2)
+        for i in 0..100 {
+            let ret = if i % 2 == 0 { 0.02 } else { -0.01 };
+            state.portfolio.equity *= 1.0 + ret;
+            engine.update(&mut state);
+        }
+        let m = &state.metrics;
+        assert_eq!(m.down_n, 50);
+        let downside = (0.5f64 * 0.01 * 0.01).sqrt();
+        assert!((m.sortino() - m.mean / downside).abs() < 1e-9);
+        assert!(m.sortino().is_finite() && m.sortino() > 0.0);
+    }
+
+    #[test]
+    fn roll_day_archives_and_resets_daily_figures() {
+        let mut state = StrategyState::with_capital(1_000.0, ContractKind::Linear);
+        let mut engine = MetricsEngine::new();
+        let day1 = 19_000 * 86_400;
+        // First sight of a day only starts the accumulators
+        assert_eq!(engine.roll_day(&mut state, day1 + 10), None);
+
+        state.metrics.record_trade(30.0);
+        state.metrics.record_trade(-10.0);
+        state.metrics.record_trade(5.0);
+        for equity in [1_000.0, 1_100.0, 990.0, 1_025.0] {
+            state.portfolio.equity = equity;
+            engine.update(&mut state);
+        }
+        assert_eq!(engine.roll_day(&mut state, day1 + 86_399), None);
+
+        let archived = engine.roll_day(&mut state, day1 + 86_400).unwrap();
+        assert_eq!(archived.day, 19_000);
+        assert!((archived.pnl - 25.0).abs() < 1e-12);
+        assert_eq!(archived.trades, 3);
+        assert_eq!(archived.wins, 2);
+        assert!((archived.win_rate - 2.0 / 3.0).abs() < 1e-12);
+        assert!((archived.max_drawdown - (990.0 / 1_100.0 - 1.0)).abs() < 1e-12);
+
+        // Daily figures restart; lifetime totals carry on
+        let m = &state.metrics;
+        assert_eq!(m.day, 19_001);
+        assert_eq!(m.day_max_drawdown, 0.0);
+        assert_eq!(m.day_peak, 1_025.0);
+        assert_eq!((m.wins, m.losses), (2, 1));
+        assert!((m.pnl - 25.0).abs() < 1e-12);
+        assert!((m.max_drawdown - archived.max_drawdown).abs() < 1e-12);
+
+        state.metrics.record_trade(-4.0);
+        let next = engine.roll_day(&mut state, day1 + 3 * 86_400).unwrap();
+        assert_eq!(next.day, 19_001);
+        assert!((next.pnl + 4.0).abs() < 1e-12);
+        assert_eq!((next.trades, next.wins, next.win_rate), (1, 0, 0.0));
+    }
+
+    #[test]
+    fn test_correlation_matrix_flags_duplicate_bets() {
+        let curve = |seed: u64| -> EquityCurve {
+            let mut equity = 1_000.0;
+            uniforms(seed, 2_000)
+                .into_iter()
+                .enumerate()
+                .map(|(i, u)| {
+                    equity *= 1.0 + (u - 0.5) * 0.01;
+                    (i as u64 * 300, equity)
+                })
+                .collect()
+        };
+        let twin = curve(7);
+        // A missing snapshot in one curve must not shift the join
+        let mut gappy = twin.clone();
+        gappy.remove(500);
+        let curves = vec![
+            ("mom-0".to_string(), twin),
+            ("mom-1".to_string(), gappy),
+            ("carry-0".to_string(), curve(99)),
+            (
+                "flat-0".to_string(),
+                vec![(0, 1_000.0), (300, 1_000.0), (600, 1_000.0)],
+            ),
+        ];
+
+        let m = correlation_matrix(&curves);
+        assert_eq!(m.ids.len(), 4);
+        assert!((m.values[0][1] - 1.0).abs() < 1e-3, "{}", m.values[0][1]);
+        assert_eq!(m.values[0][1], m.values[1][0]);
+        assert!(m.values[0][2].abs() < 0.1, "{}", m.values[0][2]);
+        assert!(m.values[0][3].is_nan(), "flat curve has no correlation");
+        assert_eq!(m.values[3][3], 1.0);
+
+        let flagged = m.correlated_pairs(CORRELATION_FLAG);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(
+            (flagged[0].0.as_str(), flagged[0].1.as_str()),
+            ("mom-0", "mom-1")
+        );
+    }
 }
@@ -0,0 +1,266 @@
+//! Prometheus text exposition of live-loop metrics.
+//!
+//! The loop publishes each strategy's `MetricsState` and portfolio into a
+//! shared [`Registry`] after every update; with the `prometheus` feature a
+//! small blocking HTTP server renders it at `/metrics`.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+use crate::adapter::throttle::ThrottleStats;
+use crate::reliability::circuit::CircuitState;
+use crate::strategy::StrategyState;
+
+/// Point-in-time gauges for one strategy instance.
+#[derive(Debug, Clone, Default)]
+pub struct StrategyGauges {
+    pub symbol: String,
+    pub equity: f64,
+    pub pnl: f64,
+    pub max_drawdown: f64,
+    pub position: f64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    strategies: BTreeMap<String, StrategyGauges>,
+    circuit_state: u8,
+    throttle: ThrottleStats,
+}
+
+pub type SharedRegistry = Arc<Mutex<Registry>>;
+
+/// (metric name, HELP text, accessor) for a per-strategy gauge.
+type GaugeSpec = (&'static str, &'static str, fn(&StrategyGauges) -> f64);
+
+impl Registry {
+    pub fn shared() -> SharedRegistry {
+        Arc::new(Mutex::new(Self::default()))
+    }
+
+    /// Copy the loop's current view of a strategy into the registry.
+    pub fn publish(&mut self, id: &str, symbol: &str, state: &StrategyState) {
+        self.strategies.insert(
+            id.to_string(),
+            StrategyGauges {
+                symbol: symbol.to_string(),
+                equity: state.portfolio.equity,
+                pnl: state.metrics.pnl,
+                max_drawdown: state.metrics.max_drawdown,
+                position: state.portfolio.position,
+            },
+        );
+    }
+
+    /// 0 = closed, 1 = half-open, 2 = open.
+    pub fn set_circuit(&mut self, state: CircuitState) {
+        self.circuit_state = match state {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        };
+    }
+
+    /// Adapter request-throttle counters (cumulative).
+    pub fn set_throttle(&mut self, stats: ThrottleStats) {
+        self.throttle = stats;
+    }
+
+    /// Render in Prometheus text exposition format (version 0.0.4).
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let per_strategy: [GaugeSpec; 4] = [
+            ("arbitragefx_equity", "Marked equity per strategy", |g| {
+                g.equity
+            }),
+            (
+                "arbitragefx_pnl",
+                "Cumulative realized PnL per strategy",
+                |g| g.pnl,
+            ),
+            (
+                "arbitragefx_max_drawdown",
+                "Worst peak-to-trough drawdown (fraction, <= 0)",
+                |g| g.max_drawdown,
+            ),
+            (
+                "arbitragefx_position",
+                "Open position in contracts per strategy",
+                |g| g.position,
+            ),
+        ];
+        for (name, help, value) in per_strategy {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} gauge", name);
+            for (id, g) in &self.strategies {
+                let _ = writeln!(
+                    out,
+                    "{}{{strategy=\"{}\",symbol=\"{}\"}} {}",
+                    name,
+                    escape_label(id),
+                    escape_label(&g.symbol),
+                    format_value(value(g))
+                );
+            }
+        }
+        let _ = writeln!(
+            out,
+            "# HELP arbitragefx_circuit_state Circuit breaker state (0=closed, 1=half_open, 2=open)"
+        );
+        let _ = writeln!(out, "# TYPE arbitragefx_circuit_state gauge");
+        let _ = writeln!(out, "arbitragefx_circuit_state {}", self.circuit_state);
+        let counters: [(&str, &str, f64); 3] = [
+            (
+                "arbitragefx_api_requests_total",
+                "Venue API requests sent through the rate limiter",
+                self.throttle.requests as f64,
+            ),
+            (
+                "arbitragefx_throttle_waits_total",
+                "Requests delayed by the client-side rate limiter",
+                self.throttle.waits as f64,
+            ),
+            (
+                "arbitragefx_throttle_wait_seconds_total",
+                "Time spent waiting for rate-limit capacity",
+                self.throttle.waited_secs,
+            ),
+        ];
+        for (name, help, value) in counters {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} counter", name);
+            let _ = writeln!(out, "{} {}", name, format_value(value));
+        }
+        out
+    }
+}
+
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn format_value(v: f64) -> String {
+    if v.is_nan() {
+        "NaN".to_string()
+    } else if v.is_infinite() {
+        if v > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+/// Serve `/metrics` on `addr` from a background thread.
+#[cfg(feature = "prometheus")]
+pub fn serve(addr: &str, registry: SharedRegistry) -> std::io::Result<()> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+
+    let listener = TcpListener::bind(addr)?;
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = match stream {
+                Ok(s) => s,
+                Err(_) => continue,
+            };
+            let mut request_line = String::new();
+            if BufReader::new(&stream)
+                .read_line(&mut request_line)
+                .is_err()
+            {
+                continue;
+            }
+            let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+            let response = if path == "/metrics" {
+                let body = registry
+                    .lock()
+                    .map(|r| r.render())
+                    .unwrap_or_else(|e| e.into_inner().render());
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                )
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                    .to_string()
+            };
+            let _ = stream.write_all(response.as_bytes());
+        }
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{ContractKind, MetricsState, PortfolioState};
+    use std::collections::HashSet;
+
+    #[test]
+    fn exposition_parses_with_help_and_type() {
+        let mut reg = Registry::default();
+        let state = StrategyState {
+            portfolio: PortfolioState {
+                cash: 1_075.0,
+                position: -0.25,
+                entry_price: 100.0,
+                equity: 1_050.0,
+                contract: ContractKind::Linear,
+            },
+            metrics: MetricsState {
+                pnl: 50.0,
+                max_drawdown: -0.02,
+                ..MetricsState::default()
+            },
+            ..StrategyState::with_capital(1_050.0, ContractKind::Linear)
+        };
+        reg.publish("carry:BTCUSDT", "BTCUSDT", &state);
+        reg.set_circuit(CircuitState::Open);
+        reg.set_throttle(ThrottleStats {
+            requests: 40,
+            waits: 3,
+            waited_secs: 0.75,
+        });
+        let text = reg.render();
+
+        let mut help = HashSet::new();
+        let mut typed = HashSet::new();
+        let mut samples = HashSet::new();
+        for line in text.lines() {
+            if let Some(rest) = line.strip_prefix("# HELP ") {
+                help.insert(rest.split_whitespace().next().unwrap().to_string());
+            } else if let Some(rest) = line.strip_prefix("# TYPE ") {
+                let mut parts = rest.split_whitespace();
+                typed.insert(parts.next().unwrap().to_string());
+                let kind = parts.next().unwrap();
+                assert!(kind == "gauge" || kind == "counter", "{}", kind);
+            } else {
+                let (series, value) = line.rsplit_once(' ').expect("sample line");
+                value.parse::<f64>().expect("numeric sample");
+                let name = series.split('{').next().unwrap();
+                samples.insert(name.to_string());
+            }
+        }
+        for name in [
+            "arbitragefx_equity",
+            "arbitragefx_pnl",
+            "arbitragefx_max_drawdown",
+            "arbitragefx_position",
+            "arbitragefx_circuit_state",
+            "arbitragefx_api_requests_total",
+            "arbitragefx_throttle_waits_total",
+            "arbitragefx_throttle_wait_seconds_total",
+        ] {
+            assert!(help.contains(name), "missing HELP for {}", name);
+            assert!(typed.contains(name), "missing TYPE for {}", name);
+            assert!(samples.contains(name), "missing sample for {}", name);
+        }
+        assert!(text.contains("arbitragefx_circuit_state 2"));
+        assert!(text.contains("# TYPE arbitragefx_throttle_waits_total counter"));
+        assert!(text.contains("arbitragefx_throttle_wait_seconds_total 0.75"));
+        assert!(text.contains("strategy=\"carry:BTCUSDT\",symbol=\"BTCUSDT\"} 1050"));
+    }
+}
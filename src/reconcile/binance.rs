@@ -3,6 +3,8 @@ use reqwest::Client;
 use serde::Deserialize;
 
 use crate::exchange::signing::sign_binance;
+use crate::exchange::symbol::normalize;
+use crate::exchange::ExchangeKind;
 
 #[derive(Debug, Clone)]
 pub struct SpotBalance {
@@ -54,6 +56,7 @@ impl BinanceReconcileClient {
     }
 
     pub async fn fetch_open_orders(&self, symbol: &str) -> Result<Vec<SpotOrder>> {
+        let symbol = &normalize(symbol, ExchangeKind::Binance);
         let timestamp = Self::timestamp_ms();
         let query = format!("symbol={}&timestamp={}&recvWindow=5000", symbol, timestamp);
         let signature = sign_binance(&query, &self.api_secret).map_err(|e| anyhow!(e))?;
@@ -139,6 +142,7 @@ impl BinanceReconcileClient {
     }
 
     pub async fn fetch_futures_positions(&self, symbol: &str) -> Result<Vec<FuturesPosition>> {
+        let symbol = normalize(symbol, ExchangeKind::Binance);
         let timestamp = Self::timestamp_ms();
         let query = format!("timestamp={}&recvWindow=5000", timestamp);
         let signature = sign_binance(&query, &self.api_secret).map_err(|e| anyhow!(e))?;
@@ -5,6 +5,7 @@
 
 use crate::backtest::CsvRow;
 use crate::narrative_detector::{NarrativeIndicators, NarrativeRegime};
+use crate::strategy::TrendRegime;
 use serde::Serialize;
 
 /// Summary of regime classification for a dataset.
@@ -27,28 +28,12 @@ pub struct RegimeSummary {
     pub mean_volatility_ratio: f64,
 }
 
-/// Classify a dataset into its dominant market regime.
+/// Narrative indicators at each candle, `None` during the warmup window.
 ///
-/// Processes candle data in rolling windows to compute narrative indicators
-/// at each step, then aggregates into a single regime classification.
-pub fn classify_dataset(rows: &[CsvRow]) -> RegimeSummary {
-    if rows.len() < 20 {
-        return RegimeSummary {
-            dominant_regime: "insufficient_data".into(),
-            mean_narrative_score: 0.0,
-            grounded_frac: 1.0,
-            uncertain_frac: 0.0,
-            narrative_frac: 0.0,
-            reflexive_frac: 0.0,
-            price_trend: "unknown".into(),
-            price_change_pct: 0.0,
-            mean_volatility_ratio: 1.0,
-        };
-    }
-
-    let mut scores = Vec::new();
-    let mut regimes = Vec::new();
-    let mut vol_ratios = Vec::new();
+/// Statistics are cumulative up to and including each row, so the value at
+/// index `i` only uses data available at that candle's close.
+pub fn bar_indicators(rows: &[CsvRow]) -> Vec<Option<NarrativeIndicators>> {
+    let mut out = vec![None; rows.len()];
 
     // Rolling statistics for z-scores
     let mut funding_sum = 0.0;
@@ -139,9 +124,46 @@ pub fn classify_dataset(rows: &[CsvRow]) -> RegimeSummary {
             retail_flow_proxy: 0.0,
         };
 
+        out[i] = Some(indicators);
+    }
+
+    out
+}
+
+/// Narrative regime at each candle, `None` during the warmup window.
+pub fn bar_regimes(rows: &[CsvRow]) -> Vec<Option<NarrativeRegime>> {
+    bar_indicators(rows)
+        .iter()
+        .map(|ind| ind.as_ref().map(|i| i.regime()))
+        .collect()
+}
+
+/// Classify a dataset into its dominant market regime.
+///
+/// Processes candle data in rolling windows to compute narrative indicators
+/// at each step, then aggregates into a single regime classification.
+pub fn classify_dataset(rows: &[CsvRow]) -> RegimeSummary {
+    if rows.len() < 20 {
+        return RegimeSummary {
+            dominant_regime: "insufficient_data".into(),
+            mean_narrative_score: 0.0,
+            grounded_frac: 1.0,
+            uncertain_frac: 0.0,
+            narrative_frac: 0.0,
+            reflexive_frac: 0.0,
+            price_trend: "unknown".into(),
+            price_change_pct: 0.0,
+            mean_volatility_ratio: 1.0,
+        };
+    }
+
+    let mut scores = Vec::new();
+    let mut regimes = Vec::new();
+    let mut vol_ratios = Vec::new();
+    for indicators in bar_indicators(rows).into_iter().flatten() {
         scores.push(indicators.narrative_score());
         regimes.push(indicators.regime());
-        vol_ratios.push(volatility_ratio);
+        vol_ratios.push(indicators.volatility_ratio);
     }
 
     if scores.is_empty() {
@@ -191,17 +213,7 @@ pub fn classify_dataset(rows: &[CsvRow]) -> RegimeSummary {
     } else {
         0.0
     };
-    let price_trend = if price_change_pct > 10.0 {
-        "strong_bull"
-    } else if price_change_pct > 2.0 {
-        "mild_bull"
-    } else if price_change_pct < -10.0 {
-        "strong_bear"
-    } else if price_change_pct < -2.0 {
-        "mild_bear"
-    } else {
-        "ranging"
-    };
+    let price_trend = TrendRegime::from_change_pct(price_change_pct).as_str();
 
     // Dominant regime: most common
     let dominant = if grounded >= uncertain && grounded >= narrative && grounded >= reflexive {
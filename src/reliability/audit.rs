@@ -0,0 +1,195 @@
+//! Append-only order audit trail.
+//!
+//! One JSONL record per lifecycle step (intent -> submit -> ack -> fill, or
+//! reject/cancel), keyed by `intent_id`. Unlike the WAL this is never replayed
+//! into state; it exists so an order's history can be reconstructed after
+//! the fact without grepping stdout.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditStage {
+    /// Risk-approved order recorded before anything is sent
+    #[default]
+    Intent,
+    Submit,
+    Ack,
+    Fill,
+    Reject,
+    Cancel,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub ts: u64,
+    pub intent_id: String,
+    pub stage: AuditStage,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strategy_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exchange_order_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub side: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qty: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub price: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fee: Option<f64>,
+    /// Signal reason on intents, error text on rejects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AuditTrail {
+    file: File,
+}
+
+impl AuditTrail {
+    /// Audit file kept next to the WAL (`<wal>.audit.jsonl`).
+    pub fn path_for_wal(wal_path: &str) -> String {
+        format!("{}.audit.jsonl", wal_path)
+    }
+
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    pub fn record(&mut self, rec: &AuditRecord) -> std::io::Result<()> {
+        let line = serde_json::to_string(rec).unwrap_or_else(|_| "{}".to_string());
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.file.flush()
+    }
+
+    /// Every parseable record in file order.
+    pub fn read(path: &str) -> std::io::Result<Vec<AuditRecord>> {
+        if !Path::new(path).exists() {
+            return Ok(Vec::new());
+        }
+        let reader = BufReader::new(File::open(path)?);
+        Ok(reader
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|l| serde_json::from_str(&l).ok())
+            .collect())
+    }
+
+    /// The lifecycle of one intent, in the order it was written.
+    pub fn for_intent(path: &str, intent_id: &str) -> std::io::Result<Vec<AuditRecord>> {
+        Ok(Self::read(path)?
+            .into_iter()
+            .filter(|r| r.intent_id == intent_id)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn rec(ts: u64, intent: &str, stage: AuditStage) -> AuditRecord {
+        AuditRecord {
+            ts,
+            intent_id: intent.to_string(),
+            stage,
+            client_order_id: Some(format!("CID-{}", intent)),
+            ..AuditRecord::default()
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_is_reconstructed_per_intent() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("bot.wal.audit.jsonl");
+        let path = path.to_str().unwrap();
+        {
+            let mut audit = AuditTrail::open(path).unwrap();
+            // Two orders interleaved; the second is rejected
+            audit
+                .record(&AuditRecord {
+                    symbol: Some("BTCUSDT".to_string()),
+                    side: Some("BUY".to_string()),
+                    qty: Some(0.01),
+                    detail: Some("score_entry".to_string()),
+                    ..rec(1, "I-1", AuditStage::Intent)
+                })
+                .unwrap();
+            audit.record(&rec(2, "I-1", AuditStage::Submit)).unwrap();
+            audit.record(&rec(3, "I-2", AuditStage::Intent)).unwrap();
+            audit
+                .record(&AuditRecord {
+                    exchange_order_id: Some("EX-1".to_string()),
+                    ..rec(4, "I-1", AuditStage::Ack)
+                })
+                .unwrap();
+            audit.record(&rec(5, "I-2", AuditStage::Submit)).unwrap();
+            audit
+                .record(&AuditRecord {
+                    detail: Some("insufficient balance".to_string()),
+                    ..rec(6, "I-2", AuditStage::Reject)
+                })
+                .unwrap();
+            audit
+                .record(&AuditRecord {
+                    price: Some(50_000.0),
+                    qty: Some(0.01),
+                    fee: Some(0.5),
+                    ..rec(7, "I-1", AuditStage::Fill)
+                })
+                .unwrap();
+        }
+        // Reopening appends rather than truncating
+        AuditTrail::open(path)
+            .unwrap()
+            .record(&rec(8, "I-3", AuditStage::Intent))
+            .unwrap();
+
+        let one = AuditTrail::for_intent(path, "I-1").unwrap();
+        let stages: Vec<AuditStage> = one.iter().map(|r| r.stage).collect();
+        assert_eq!(
+            stages,
+            vec![
+                AuditStage::Intent,
+                AuditStage::Submit,
+                AuditStage::Ack,
+                AuditStage::Fill
+            ]
+        );
+        assert!(one.windows(2).all(|w| w[0].ts < w[1].ts));
+        assert_eq!(one[0].detail.as_deref(), Some("score_entry"));
+        assert_eq!(one[2].exchange_order_id.as_deref(), Some("EX-1"));
+        assert_eq!(one[3].price, Some(50_000.0));
+
+        let two: Vec<AuditStage> = AuditTrail::for_intent(path, "I-2")
+            .unwrap()
+            .iter()
+            .map(|r| r.stage)
+            .collect();
+        assert_eq!(
+            two,
+            vec![AuditStage::Intent, AuditStage::Submit, AuditStage::Reject]
+        );
+        assert_eq!(AuditTrail::read(path).unwrap().len(), 8);
+    }
+
+    #[test]
+    fn test_records_are_flat_jsonl() {
+        let line = serde_json::to_string(&rec(1, "I-1", AuditStage::Ack)).unwrap();
+        assert_eq!(
+            line,
+            r#"{"ts":1,"intent_id":"I-1","stage":"ack","client_order_id":"CID-I-1"}"#
+        );
+    }
+}
@@ -33,6 +33,12 @@ impl CircuitBreaker {
         }
     }
 
+    /// Open immediately, e.g. on a credential failure that retries can't fix.
+    pub fn trip(&mut self) {
+        self.failures = self.failures.max(self.threshold);
+        self.state = CircuitState::Open;
+    }
+
     pub fn allow(&self) -> bool {
         matches!(self.state, CircuitState::Closed | CircuitState::HalfOpen)
     }
@@ -54,6 +60,15 @@ mod tests {
         assert!(matches!(cb.state, CircuitState::Open));
     }
 
+    #[test]
+    fn test_circuit_trip_opens_immediately() {
+        let mut cb = CircuitBreaker::new(5);
+        cb.trip();
+        assert!(!cb.allow());
+        cb.record_success();
+        assert!(cb.allow());
+    }
+
     #[test]
     fn test_circuit_resets_on_success() {
         let mut cb = CircuitBreaker::new(2);
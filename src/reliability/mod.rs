@@ -1,3 +1,4 @@
+pub mod audit;
 pub mod circuit;
 pub mod state;
 pub mod wal;
@@ -1,19 +1,40 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use crate::verify::order_sm::{apply_event, Event, Order, OrderState};
 
 #[derive(Debug, Clone)]
 pub struct OrderBook {
     pub orders: HashMap<String, Order>,
+    /// Client ids the venue has acknowledged (seeded from the WAL on recovery)
+    acked: HashSet<String>,
+    /// Venue fill ids already applied to a portfolio, across all orders
+    applied_fills: HashSet<String>,
 }
 
 impl OrderBook {
     pub fn new() -> Self {
         Self {
             orders: HashMap::new(),
+            acked: HashSet::new(),
+            applied_fills: HashSet::new(),
         }
     }
 
+    pub fn mark_acked(&mut self, client_id: &str) {
+        self.acked.insert(client_id.to_string());
+    }
+
+    pub fn is_acked(&self, client_id: &str) -> bool {
+        self.acked.contains(client_id)
+    }
+
+    /// Record a venue fill id; false when it was applied before. The order
+    /// state machine ignores fills on terminal orders, so a redelivery after
+    /// a timeout or cancel is only caught here.
+    pub fn first_delivery(&mut self, fill_id: &str) -> bool {
+        self.applied_fills.insert(fill_id.to_string())
+    }
+
     pub fn ensure(&mut self, client_id: &str, qty: f64) {
         self.orders
             .entry(client_id.to_string())
@@ -26,6 +26,9 @@ pub enum WalEntry {
         symbol: String,
         side: String,
         qty: f64,
+        /// Strategy branch that triggered the order, when known.
+        #[serde(default)]
+        reason: Option<String>,
         #[serde(default)]
         fsync: bool,
     },
@@ -40,6 +43,16 @@ pub enum WalEntry {
         #[serde(default)]
         fsync: bool,
     },
+    /// Exchange accepted the order; its client id must never be submitted again.
+    #[serde(rename = "ack")]
+    Ack {
+        ts: u64,
+        intent_id: String,
+        client_order_id: String,
+        exchange_order_id: String,
+        #[serde(default)]
+        fsync: bool,
+    },
     #[serde(rename = "cancel")]
     Cancel {
         ts: u64,
@@ -57,7 +70,21 @@ pub enum WalEntry {
         entry_price: f64,
         equity: f64,
         pnl: f64,
+        /// Equity high-water mark, so drawdown survives a restart.
+        #[serde(default)]
+        equity_peak: f64,
+        #[serde(default)]
+        max_drawdown: f64,
+        /// When the open position was first seen (0 while flat), so the
+        /// holding-period limit keeps counting across a restart.
+        #[serde(default)]
+        position_opened_ts: u64,
     },
+    /// Backtest carry-over state at the end of a chunk, for warm-starting
+    /// the next one (see `backtest::BacktestCheckpoint`). Live recovery
+    /// ignores it.
+    #[serde(rename = "backtest_checkpoint")]
+    BacktestCheckpoint { ts: u64, state: Value },
 }
 
 /// Recovery state from WAL replay
@@ -70,6 +97,153 @@ pub struct RecoveryState {
     pub last_snapshot: Option<SnapshotData>,
     /// Fills since the oldest snapshot (per-strategy filtering needed in caller)
     pub fills_since_snapshot: Vec<FillData>,
+    /// client_order_id -> exchange order id for every order the venue acknowledged
+    pub acked_orders: std::collections::HashMap<String, String>,
+    /// Lines skipped because they failed their checksum or were not JSON
+    /// (e.g. torn by a crash mid-write).
+    pub corrupt_entries: usize,
+    /// Every fill in the log, in log order (not cleared by snapshots)
+    pub fill_history: Vec<FillData>,
+    /// Earliest snapshot per strategy still in the log, with how many of
+    /// `fill_history` precede it
+    pub first_snapshots_by_strategy: std::collections::HashMap<String, (usize, SnapshotData)>,
+}
+
+/// Relative equity gap `check_consistency` tolerates: funding settles into
+/// cash without a fill, so equity can move where position can't.
+const EQUITY_TOLERANCE: f64 = 0.01;
+
+impl RecoveryState {
+    /// Restore each strategy from its latest snapshot (including when its
+    /// position was opened), then replay the fills logged after it. Fills are routed by the `I-<strategy_id>-` intent id
+    /// prefix. `stored_hwm` seeds high-water marks persisted outside the WAL;
+    /// snapshot marks only override them when `persist_hwm` is set.
+    pub fn apply_to(
+        &self,
+        strategies: &mut [crate::state::StrategyInstance],
+        stored_hwm: &std::collections::HashMap<String, (f64, f64)>,
+        persist_hwm: bool,
+    ) {
+        use crate::logging::{json_log, obj, v_num, v_str};
+
+        for inst in strategies.iter_mut() {
+            if let Some(&(peak, max_dd)) = stored_hwm.get(&inst.id) {
+                inst.state.metrics.restore_high_water(peak, max_dd);
+            }
+            if let Some(snap) = self.snapshots_by_strategy.get(&inst.id) {
+                inst.state.portfolio.cash = snap.cash;
+                inst.state.portfolio.position = snap.position;
+                inst.state.portfolio.entry_price = snap.entry_price;
+                inst.state.portfolio.equity = snap.equity;
+                inst.state.metrics.pnl = snap.pnl;
+                inst.state.position_opened_ts = snap.position_opened_ts;
+                if persist_hwm {
+                    inst.state
+                        .metrics
+                        .restore_high_water(snap.equity_peak, snap.max_drawdown);
+                }
+                json_log(
+                    "wal_recovery",
+                    obj(&[
+                        ("status", v_str("recovered")),
+                        ("strategy_id", v_str(&inst.id)),
+                        ("snapshot_ts", v_num(snap.ts as f64)),
+                        ("position", v_num(snap.position)),
+                        ("equity", v_num(snap.equity)),
+                        ("equity_peak", v_num(inst.state.metrics.equity_peak)),
+                    ]),
+                );
+            }
+        }
+        for fill in &self.fills_since_snapshot {
+            for inst in strategies.iter_mut() {
+                if fill.intent_id.starts_with(&format!("I-{}-", inst.id)) {
+                    let f = crate::state::Fill {
+                        price: fill.price,
+                        qty: fill.qty,
+                        fee: fill.fee,
+                        ts: fill.ts,
+                    };
+                    let was_flat = inst.state.portfolio.position.abs() <= 1e-9;
+                    let _ = inst.state.portfolio.apply_fill(f);
+                    // Keep the holding clock in step with replayed opens and closes
+                    if inst.state.portfolio.position.abs() <= 1e-9 {
+                        inst.state.position_opened_ts = 0;
+                    } else if was_flat {
+                        inst.state.position_opened_ts = fill.ts;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Recompute `strategy_id`'s portfolio from its earliest snapshot in the
+    /// log and every fill after it, ignoring later snapshots. `base` supplies
+    /// the contract. None when the log holds no snapshot for the strategy.
+    pub fn rebuild_from_fills(
+        &self,
+        strategy_id: &str,
+        base: &crate::strategy::PortfolioState,
+    ) -> Option<(crate::strategy::PortfolioState, Option<f64>)> {
+        let (start, snap) = self.first_snapshots_by_strategy.get(strategy_id)?;
+        let mut portfolio = *base;
+        portfolio.cash = snap.cash;
+        portfolio.position = snap.position;
+        portfolio.entry_price = snap.entry_price;
+        portfolio.equity = snap.equity;
+        let prefix = format!("I-{}-", strategy_id);
+        let mut last_price = None;
+        for fill in self.fill_history[*start..]
+            .iter()
+            .filter(|f| f.intent_id.starts_with(&prefix))
+        {
+            let _ = portfolio.apply_fill(crate::state::Fill {
+                price: fill.price,
+                qty: fill.qty,
+                fee: fill.fee,
+                ts: fill.ts,
+            });
+            last_price = Some(fill.price);
+        }
+        Some((portfolio, last_price))
+    }
+
+    /// Check the snapshot+replay result in `recovered` against
+    /// `rebuild_from_fills`: positions must match and equity, marked at the
+    /// last fill price, must agree within `EQUITY_TOLERANCE`. Logs
+    /// `wal_recovery` / `inconsistent` and returns false on a mismatch.
+    pub fn check_consistency(
+        &self,
+        strategy_id: &str,
+        recovered: &crate::strategy::PortfolioState,
+    ) -> bool {
+        use crate::logging::{json_log_at, obj, v_num, v_str, Level};
+
+        let Some((rebuilt, last_price)) = self.rebuild_from_fills(strategy_id, recovered) else {
+            return true;
+        };
+        let price = last_price.unwrap_or(rebuilt.entry_price);
+        let (equity, rebuilt_equity) = (recovered.mark_equity(price), rebuilt.mark_equity(price));
+        let position_ok = (recovered.position - rebuilt.position).abs() <= 1e-9;
+        let equity_ok =
+            (equity - rebuilt_equity).abs() <= EQUITY_TOLERANCE * rebuilt_equity.abs().max(1.0);
+        if position_ok && equity_ok {
+            return true;
+        }
+        json_log_at(
+            Level::Warn,
+            "wal_recovery",
+            obj(&[
+                ("status", v_str("inconsistent")),
+                ("strategy_id", v_str(strategy_id)),
+                ("position", v_num(recovered.position)),
+                ("fills_position", v_num(rebuilt.position)),
+                ("equity", v_num(equity)),
+                ("fills_equity", v_num(rebuilt_equity)),
+            ]),
+        );
+        false
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -92,6 +266,9 @@ pub struct SnapshotData {
     pub entry_price: f64,
     pub equity: f64,
     pub pnl: f64,
+    pub equity_peak: f64,
+    pub max_drawdown: f64,
+    pub position_opened_ts: u64,
 }
 
 #[derive(Debug, Clone)]
@@ -103,6 +280,58 @@ pub struct FillData {
     pub fee: f64,
 }
 
+/// Key of the checksum every appended JSON line ends with.
+const CRC_FIELD: &str = ",\"crc\":\"";
+
+/// CRC-32 (IEEE 802.3, reflected), as used by zlib and gzip.
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Append a CRC-32 of a JSON object line as its trailing `crc` field.
+/// Anything that isn't a non-empty object is written as is.
+fn seal(line: &str) -> String {
+    match line.strip_suffix('}') {
+        Some(body) if line.starts_with('{') && body.len() > 1 => {
+            format!("{}{}{:08x}\"}}", body, CRC_FIELD, crc32(line.as_bytes()))
+        }
+        _ => line.to_string(),
+    }
+}
+
+/// Verify a sealed line and return it without its `crc` field. Lines written
+/// before checksums existed carry none and pass through unchecked.
+fn unseal(line: &str) -> Result<String, String> {
+    let Some(at) = line.rfind(CRC_FIELD) else {
+        return Ok(line.to_string());
+    };
+    let (body, tail) = line.split_at(at);
+    let hex = tail[CRC_FIELD.len()..]
+        .strip_suffix("\"}")
+        .ok_or_else(|| "malformed crc field".to_string())?;
+    let expected = u32::from_str_radix(hex, 16).map_err(|_| format!("malformed crc {:?}", hex))?;
+    let original = format!("{}}}", body);
+    let actual = crc32(original.as_bytes());
+    if actual != expected {
+        return Err(format!(
+            "crc mismatch: stored {:08x}, computed {:08x}",
+            expected, actual
+        ));
+    }
+    Ok(original)
+}
+
 impl Wal {
     pub fn open(path: &str) -> std::io::Result<Self> {
         let file = OpenOptions::new().create(true).append(true).open(path)?;
@@ -112,7 +341,9 @@ impl Wal {
         })
     }
 
+    /// Append one line, sealed with a checksum when it is a JSON object.
     pub fn append(&mut self, line: &str) -> std::io::Result<()> {
+        let line = seal(line);
         self.file.write_all(line.as_bytes())?;
         self.file.write_all(b"\n")?;
         self.file.flush()
@@ -138,6 +369,94 @@ impl Wal {
         Ok(reader.lines().flatten().collect())
     }
 
+    /// Verify and parse every line. Checksum failures and non-JSON lines come
+    /// back as `Err` and are logged; recognised-but-unknown JSON is `Ok(None)`.
+    fn checked_entries(lines: &[String]) -> Vec<Result<Option<WalEntry>, String>> {
+        use crate::logging::{json_log_at, obj, v_num, v_str, Level};
+
+        lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let checked = unseal(line).and_then(|json| {
+                    serde_json::from_str::<Value>(&json).map_err(|e| format!("not json: {}", e))?;
+                    Ok(Self::parse_line(&json))
+                });
+                if let Err(reason) = &checked {
+                    json_log_at(
+                        Level::Warn,
+                        "wal_recovery",
+                        obj(&[
+                            ("status", v_str("corrupt_entry")),
+                            ("line", v_num((i + 1) as f64)),
+                            ("reason", v_str(reason)),
+                        ]),
+                    );
+                }
+                checked
+            })
+            .collect()
+    }
+
+    /// Parse one WAL line, accepting the typed form and older hand-built JSON
+    /// lines that omit optional fields. Unrecognised lines yield None.
+    fn parse_line(line: &str) -> Option<WalEntry> {
+        if let Ok(entry) = serde_json::from_str::<WalEntry>(line) {
+            return Some(entry);
+        }
+
+        let json: Value = serde_json::from_str(line).ok()?;
+        let str_field = |k: &str| json.get(k).and_then(|v| v.as_str()).map(|v| v.to_string());
+        let f64_field = |k: &str| json.get(k).and_then(|v| v.as_f64());
+        let ts = json.get("ts").and_then(|v| v.as_u64());
+
+        match json.get("operation").and_then(|v| v.as_str())? {
+            "place_order" => Some(WalEntry::PlaceOrder {
+                ts: ts?,
+                intent_id: str_field("intent_id")?,
+                strategy_id: str_field("strategy_id"),
+                client_order_id: str_field("client_order_id"),
+                params_hash: str_field("params_hash").unwrap_or_default(),
+                symbol: str_field("symbol")?,
+                side: str_field("side")?,
+                qty: f64_field("qty")?,
+                reason: str_field("reason"),
+                fsync: false,
+            }),
+            "fill" => Some(WalEntry::Fill {
+                ts: ts?,
+                intent_id: str_field("intent_id")?,
+                params_hash: str_field("params_hash").unwrap_or_default(),
+                price: f64_field("price")?,
+                qty: f64_field("qty")?,
+                fee: f64_field("fee")?,
+                fsync: false,
+            }),
+            "cancel" => Some(WalEntry::Cancel {
+                ts: ts.unwrap_or(0),
+                intent_id: str_field("intent_id")?,
+                params_hash: str_field("params_hash").unwrap_or_default(),
+                fsync: false,
+            }),
+            "snapshot" => Some(WalEntry::Snapshot {
+                ts: ts?,
+                strategy_id: str_field("strategy_id")?,
+                cash: f64_field("cash")?,
+                position: f64_field("position")?,
+                entry_price: f64_field("entry_price")?,
+                equity: f64_field("equity")?,
+                pnl: f64_field("pnl")?,
+                equity_peak: f64_field("equity_peak").unwrap_or(0.0),
+                max_drawdown: f64_field("max_drawdown").unwrap_or(0.0),
+                position_opened_ts: json
+                    .get("position_opened_ts")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0),
+            }),
+            _ => None,
+        }
+    }
+
     /// Parse WAL entries and build recovery state
     pub fn recover(path: &str) -> std::io::Result<RecoveryState> {
         let lines = Self::replay(path)?;
@@ -145,168 +464,96 @@ impl Wal {
         let mut completed_intents: std::collections::HashSet<String> =
             std::collections::HashSet::new();
 
-        for line in lines {
-            if let Ok(entry) = serde_json::from_str::<WalEntry>(&line) {
-                match entry {
-                    WalEntry::PlaceOrder {
-                        ts,
+        let checked = Self::checked_entries(&lines);
+        state.corrupt_entries = checked.iter().filter(|c| c.is_err()).count();
+        for entry in checked.into_iter().filter_map(|c| c.ok().flatten()) {
+            match entry {
+                WalEntry::PlaceOrder {
+                    ts,
+                    intent_id,
+                    strategy_id,
+                    client_order_id,
+                    symbol,
+                    side,
+                    qty,
+                    ..
+                } => {
+                    state.pending_orders.push(PendingOrder {
                         intent_id,
                         strategy_id,
                         client_order_id,
                         symbol,
                         side,
                         qty,
-                        ..
-                    } => {
-                        state.pending_orders.push(PendingOrder {
-                            intent_id,
-                            strategy_id,
-                            client_order_id,
-                            symbol,
-                            side,
-                            qty,
-                            ts,
-                        });
-                    }
-                    WalEntry::Fill {
+                        ts,
+                    });
+                }
+                WalEntry::Fill {
+                    ts,
+                    intent_id,
+                    price,
+                    qty,
+                    fee,
+                    ..
+                } => {
+                    completed_intents.insert(intent_id.clone());
+                    let fill = FillData {
                         ts,
                         intent_id,
                         price,
                         qty,
                         fee,
-                        ..
-                    } => {
-                        completed_intents.insert(intent_id.clone());
-                        state.fills_since_snapshot.push(FillData {
-                            ts,
-                            intent_id,
-                            price,
-                            qty,
-                            fee,
-                        });
-                    }
-                    WalEntry::Cancel { intent_id, .. } => {
-                        completed_intents.insert(intent_id);
-                    }
-                    WalEntry::Snapshot {
+                    };
+                    state.fill_history.push(fill.clone());
+                    state.fills_since_snapshot.push(fill);
+                }
+                WalEntry::Ack {
+                    client_order_id,
+                    exchange_order_id,
+                    ..
+                } => {
+                    state
+                        .acked_orders
+                        .insert(client_order_id, exchange_order_id);
+                }
+                WalEntry::Cancel { intent_id, .. } => {
+                    completed_intents.insert(intent_id);
+                }
+                WalEntry::Snapshot {
+                    ts,
+                    strategy_id,
+                    cash,
+                    position,
+                    entry_price,
+                    equity,
+                    pnl,
+                    equity_peak,
+                    max_drawdown,
+                    position_opened_ts,
+                } => {
+                    let snap = SnapshotData {
                         ts,
-                        strategy_id,
+                        strategy_id: strategy_id.clone(),
                         cash,
                         position,
                         entry_price,
                         equity,
                         pnl,
-                    } => {
-                        let snap = SnapshotData {
-                            ts,
-                            strategy_id: strategy_id.clone(),
-                            cash,
-                            position,
-                            entry_price,
-                            equity,
-                            pnl,
-                        };
-                        state
-                            .snapshots_by_strategy
-                            .insert(strategy_id, snap.clone());
-                        state.last_snapshot = Some(snap);
-                        state.fills_since_snapshot.clear();
-                    }
-                }
-                continue;
-            }
-
-            let parsed: Result<Value, _> = serde_json::from_str(&line);
-            if let Ok(json) = parsed {
-                let operation = json.get("operation").and_then(|v| v.as_str());
-
-                match operation {
-                    Some("place_order") => {
-                        if let (Some(intent_id), Some(symbol), Some(side), Some(qty), Some(ts)) = (
-                            json.get("intent_id").and_then(|v| v.as_str()),
-                            json.get("symbol").and_then(|v| v.as_str()),
-                            json.get("side").and_then(|v| v.as_str()),
-                            json.get("qty").and_then(|v| v.as_f64()),
-                            json.get("ts").and_then(|v| v.as_u64()),
-                        ) {
-                            let strategy_id = json
-                                .get("strategy_id")
-                                .and_then(|v| v.as_str())
-                                .map(|v| v.to_string());
-                            let client_order_id = json
-                                .get("client_order_id")
-                                .and_then(|v| v.as_str())
-                                .map(|v| v.to_string());
-                            state.pending_orders.push(PendingOrder {
-                                intent_id: intent_id.to_string(),
-                                strategy_id,
-                                client_order_id,
-                                symbol: symbol.to_string(),
-                                side: side.to_string(),
-                                qty,
-                                ts,
-                            });
-                        }
-                    }
-                    Some("fill") => {
-                        if let (Some(intent_id), Some(price), Some(qty), Some(fee), Some(ts)) = (
-                            json.get("intent_id").and_then(|v| v.as_str()),
-                            json.get("price").and_then(|v| v.as_f64()),
-                            json.get("qty").and_then(|v| v.as_f64()),
-                            json.get("fee").and_then(|v| v.as_f64()),
-                            json.get("ts").and_then(|v| v.as_u64()),
-                        ) {
-                            completed_intents.insert(intent_id.to_string());
-                            state.fills_since_snapshot.push(FillData {
-                                ts,
-                                intent_id: intent_id.to_string(),
-                                price,
-                                qty,
-                                fee,
-                            });
-                        }
-                    }
-                    Some("cancel") => {
-                        if let Some(intent_id) = json.get("intent_id").and_then(|v| v.as_str()) {
-                            completed_intents.insert(intent_id.to_string());
-                        }
-                    }
-                    Some("snapshot") => {
-                        if let (
-                            Some(ts),
-                            Some(strategy_id),
-                            Some(cash),
-                            Some(position),
-                            Some(entry_price),
-                            Some(equity),
-                            Some(pnl),
-                        ) = (
-                            json.get("ts").and_then(|v| v.as_u64()),
-                            json.get("strategy_id").and_then(|v| v.as_str()),
-                            json.get("cash").and_then(|v| v.as_f64()),
-                            json.get("position").and_then(|v| v.as_f64()),
-                            json.get("entry_price").and_then(|v| v.as_f64()),
-                            json.get("equity").and_then(|v| v.as_f64()),
-                            json.get("pnl").and_then(|v| v.as_f64()),
-                        ) {
-                            let snap = SnapshotData {
-                                ts,
-                                strategy_id: strategy_id.to_string(),
-                                cash,
-                                position,
-                                entry_price,
-                                equity,
-                                pnl,
-                            };
-                            state
-                                .snapshots_by_strategy
-                                .insert(strategy_id.to_string(), snap.clone());
-                            state.last_snapshot = Some(snap);
-                            state.fills_since_snapshot.clear();
-                        }
-                    }
-                    _ => {}
+                        equity_peak,
+                        max_drawdown,
+                        position_opened_ts,
+                    };
+                    state
+                        .first_snapshots_by_strategy
+                        .entry(strategy_id.clone())
+                        .or_insert_with(|| (state.fill_history.len(), snap.clone()));
+                    state
+                        .snapshots_by_strategy
+                        .insert(strategy_id, snap.clone());
+                    state.last_snapshot = Some(snap);
+                    state.fills_since_snapshot.clear();
                 }
+                WalEntry::BacktestCheckpoint { .. } => {}
             }
         }
 
@@ -318,13 +565,124 @@ impl Wal {
         Ok(state)
     }
 
+    /// Rewrite the WAL keeping only what `recover` needs: the latest snapshot
+    /// per strategy, fills after the last snapshot, still-pending orders and
+    /// the last ack per client id of orders not yet terminal as of that
+    /// snapshot (plus the latest backtest checkpoint). Kept lines are copied
+    /// verbatim in their original order. The new file is written to a temp path, fsynced and
+    /// renamed over the WAL, so a crash leaves either the old or the new
+    /// file intact. Returns `(kept, total)` line counts.
+    pub fn compact(&mut self) -> std::io::Result<(usize, usize)> {
+        let lines = Self::replay(&self.path)?;
+        // Corrupt lines are dropped: recovery already ignores them
+        let entries: Vec<Option<WalEntry>> = Self::checked_entries(&lines)
+            .into_iter()
+            .map(|c| c.ok().flatten())
+            .collect();
+
+        let mut completed = std::collections::HashSet::new();
+        let mut filled_at = std::collections::HashMap::new();
+        let mut last_snapshot_for = std::collections::HashMap::new();
+        let mut last_ack_for = std::collections::HashMap::new();
+        let mut last_snapshot = None;
+        let mut last_checkpoint = None;
+        for (i, entry) in entries.iter().enumerate() {
+            match entry {
+                Some(WalEntry::Fill { intent_id, .. }) => {
+                    completed.insert(intent_id.as_str());
+                    filled_at.insert(intent_id.as_str(), i);
+                }
+                Some(WalEntry::Cancel { intent_id, .. }) => {
+                    completed.insert(intent_id.as_str());
+                }
+                Some(WalEntry::Snapshot { strategy_id, .. }) => {
+                    last_snapshot_for.insert(strategy_id.as_str(), i);
+                    last_snapshot = Some(i);
+                }
+                Some(WalEntry::Ack {
+                    client_order_id, ..
+                }) => {
+                    last_ack_for.insert(client_order_id.as_str(), i);
+                }
+                Some(WalEntry::BacktestCheckpoint { .. }) => last_checkpoint = Some(i),
+                _ => {}
+            }
+        }
+
+        // A fill kept after the snapshot still needs its order's ack; a
+        // cancelled order leaves nothing behind to attach one to
+        let fill_kept = |intent_id: &str| {
+            filled_at
+                .get(intent_id)
+                .is_some_and(|&f| last_snapshot.is_none_or(|s| f > s))
+        };
+        let keep = |i: usize, entry: &WalEntry| match entry {
+            WalEntry::Snapshot { strategy_id, .. } => {
+                last_snapshot_for.get(strategy_id.as_str()) == Some(&i)
+            }
+            WalEntry::Fill { .. } => last_snapshot.is_none_or(|s| i > s),
+            WalEntry::PlaceOrder { intent_id, .. } => !completed.contains(intent_id.as_str()),
+            WalEntry::Ack {
+                intent_id,
+                client_order_id,
+                ..
+            } => {
+                last_ack_for.get(client_order_id.as_str()) == Some(&i)
+                    && (!completed.contains(intent_id.as_str()) || fill_kept(intent_id))
+            }
+            WalEntry::Cancel { .. } => false,
+            WalEntry::BacktestCheckpoint { .. } => last_checkpoint == Some(i),
+        };
+
+        let tmp_path = format!("{}.compact.tmp", self.path);
+        let mut kept = 0;
+        {
+            let mut tmp = File::create(&tmp_path)?;
+            for (i, (line, entry)) in lines.iter().zip(&entries).enumerate() {
+                if entry.as_ref().is_some_and(|e| keep(i, e)) {
+                    tmp.write_all(line.as_bytes())?;
+                    tmp.write_all(b"\n")?;
+                    kept += 1;
+                }
+            }
+            tmp.sync_all()?;
+        }
+        std::fs::rename(&tmp_path, &self.path)?;
+        if let Some(dir) = Path::new(&self.path).parent() {
+            let dir = if dir.as_os_str().is_empty() {
+                Path::new(".")
+            } else {
+                dir
+            };
+            if let Ok(d) = File::open(dir) {
+                let _ = d.sync_all();
+            }
+        }
+        // The old handle points at the replaced inode
+        self.file = OpenOptions::new().append(true).open(&self.path)?;
+        Ok((kept, lines.len()))
+    }
+
+    /// State of the newest intact `BacktestCheckpoint` entry in the WAL at
+    /// `path`, if any.
+    pub fn last_backtest_checkpoint(path: &str) -> std::io::Result<Option<Value>> {
+        let lines = Self::replay(path)?;
+        Ok(Self::checked_entries(&lines)
+            .into_iter()
+            .filter_map(|c| match c {
+                Ok(Some(WalEntry::BacktestCheckpoint { state, .. })) => Some(state),
+                _ => None,
+            })
+            .next_back())
+    }
+
     /// Write a snapshot entry for state persistence
     pub fn write_snapshot(
         &mut self,
         strategy_id: &str,
-        portfolio: &crate::strategy::PortfolioState,
-        pnl: f64,
+        state: &crate::strategy::StrategyState,
     ) -> std::io::Result<()> {
+        let (portfolio, metrics) = (&state.portfolio, &state.metrics);
         let entry = WalEntry::Snapshot {
             ts: crate::state::now_ts(),
             strategy_id: strategy_id.to_string(),
@@ -332,7 +690,10 @@ impl Wal {
             position: portfolio.position,
             entry_price: portfolio.entry_price,
             equity: portfolio.equity,
-            pnl,
+            pnl: metrics.pnl,
+            equity_peak: metrics.equity_peak,
+            max_drawdown: metrics.max_drawdown,
+            position_opened_ts: state.position_opened_ts,
         };
         self.append_entry(&entry)
     }
@@ -377,6 +738,9 @@ mod tests {
             ((s.entry_price * 1e8) as i64).hash(&mut h);
             ((s.equity * 1e8) as i64).hash(&mut h);
             ((s.pnl * 1e8) as i64).hash(&mut h);
+            ((s.equity_peak * 1e8) as i64).hash(&mut h);
+            ((s.max_drawdown * 1e8) as i64).hash(&mut h);
+            s.position_opened_ts.hash(&mut h);
         }
         let mut fills = state.fills_since_snapshot.clone();
         fills.sort_by(|a, b| a.ts.cmp(&b.ts));
@@ -406,6 +770,7 @@ mod tests {
                 symbol: "BTCUSDT".to_string(),
                 side: "BUY".to_string(),
                 qty: 0.001,
+                reason: None,
                 fsync: true,
             })
             .unwrap();
@@ -448,6 +813,9 @@ mod tests {
                 entry_price: 50000.0,
                 equity: 10500.0,
                 pnl: 500.0,
+                equity_peak: 0.0,
+                max_drawdown: 0.0,
+                position_opened_ts: 0,
             })
             .unwrap();
 
@@ -460,6 +828,9 @@ mod tests {
                 entry_price: 51000.0,
                 equity: 7800.0,
                 pnl: -200.0,
+                equity_peak: 0.0,
+                max_drawdown: 0.0,
+                position_opened_ts: 0,
             })
             .unwrap();
         }
@@ -486,6 +857,51 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_drawdown_after_restart_uses_pre_restart_peak() {
+        use crate::metrics::MetricsEngine;
+        use crate::strategy::{ContractKind, StrategyState};
+
+        let path = "/tmp/test_wal_hwm.log";
+        let _ = fs::remove_file(path);
+
+        // Run up to 1200, give back to 1100, snapshot and "crash"
+        let mut engine = MetricsEngine::new();
+        let mut before = StrategyState::with_capital(1_000.0, ContractKind::Linear);
+        for equity in [1_000.0, 1_200.0, 1_100.0] {
+            before.portfolio.cash = equity;
+            before.portfolio.equity = equity;
+            engine.update(&mut before);
+        }
+        {
+            let mut wal = Wal::open(path).unwrap();
+            wal.write_snapshot("s-1", &before).unwrap();
+        }
+
+        let recovery = Wal::recover(path).unwrap();
+        let snap = &recovery.snapshots_by_strategy["s-1"];
+        assert_eq!(snap.equity_peak, 1_200.0);
+
+        let mut after = StrategyState::with_capital(snap.cash, ContractKind::Linear);
+        after
+            .metrics
+            .restore_high_water(snap.equity_peak, snap.max_drawdown);
+        let mut engine = MetricsEngine::new();
+        after.portfolio.equity = 1_080.0;
+        engine.update(&mut after);
+        // Measured from the 1200 peak, not the 1100 post-recovery equity
+        assert!((after.metrics.max_drawdown - (-0.1)).abs() < 1e-12);
+
+        // Snapshots written before the fields existed recover with no peak
+        let legacy = r#"{"operation":"snapshot","ts":1,"strategy_id":"s-0","cash":1.0,"position":0.0,"entry_price":0.0,"equity":1.0,"pnl":0.0}"#;
+        match Wal::parse_line(legacy) {
+            Some(WalEntry::Snapshot { equity_peak, .. }) => assert_eq!(equity_peak, 0.0),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_fills_after_snapshot_preserved() {
         let path = "/tmp/test_wal_fills_after_snap.log";
@@ -515,6 +931,9 @@ mod tests {
                 entry_price: 0.0,
                 equity: 10000.0,
                 pnl: 0.0,
+                equity_peak: 0.0,
+                max_drawdown: 0.0,
+                position_opened_ts: 0,
             })
             .unwrap();
 
@@ -570,6 +989,7 @@ mod tests {
                 symbol: "BTCUSDT".to_string(),
                 side: "BUY".to_string(),
                 qty: 0.1,
+                reason: None,
                 fsync: true,
             })
             .unwrap();
@@ -584,6 +1004,7 @@ mod tests {
                 symbol: "BTCUSDT".to_string(),
                 side: "SELL".to_string(),
                 qty: 0.05,
+                reason: None,
                 fsync: true,
             })
             .unwrap();
@@ -609,6 +1030,47 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_acked_orders_recovered() {
+        let path = "/tmp/test_wal_acked.log";
+        let _ = fs::remove_file(path);
+
+        {
+            let mut wal = Wal::open(path).unwrap();
+            wal.append_entry(&WalEntry::PlaceOrder {
+                ts: 1000,
+                intent_id: "I-a".to_string(),
+                strategy_id: Some("s1".to_string()),
+                client_order_id: Some("CID-a".to_string()),
+                params_hash: "a".to_string(),
+                symbol: "BTCUSDT".to_string(),
+                side: "BUY".to_string(),
+                qty: 0.1,
+                reason: None,
+                fsync: true,
+            })
+            .unwrap();
+            wal.append_entry(&WalEntry::Ack {
+                ts: 1001,
+                intent_id: "I-a".to_string(),
+                client_order_id: "CID-a".to_string(),
+                exchange_order_id: "EX-1".to_string(),
+                fsync: true,
+            })
+            .unwrap();
+        }
+
+        let state = Wal::recover(path).unwrap();
+        // Acked but unfilled: still pending, and known to the venue
+        assert_eq!(state.pending_orders.len(), 1);
+        assert_eq!(
+            state.acked_orders.get("CID-a").map(String::as_str),
+            Some("EX-1")
+        );
+
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_recovery_hash_determinism() {
         let path = "/tmp/test_wal_hash.log";
@@ -625,6 +1087,7 @@ mod tests {
                 symbol: "BTCUSDT".to_string(),
                 side: "BUY".to_string(),
                 qty: 0.1,
+                reason: None,
                 fsync: true,
             })
             .unwrap();
@@ -636,6 +1099,9 @@ mod tests {
                 entry_price: 50000.0,
                 equity: 1005.0,
                 pnl: 5.0,
+                equity_peak: 0.0,
+                max_drawdown: 0.0,
+                position_opened_ts: 0,
             })
             .unwrap();
             wal.append_entry(&WalEntry::Fill {
@@ -656,4 +1122,286 @@ mod tests {
 
         let _ = fs::remove_file(path);
     }
+
+    #[test]
+    fn test_place_order_reason_roundtrip() {
+        let line = serde_json::to_string(&place(1000, "I-1")).unwrap();
+        match Wal::parse_line(&line) {
+            Some(WalEntry::PlaceOrder { reason, .. }) => {
+                assert_eq!(reason.as_deref(), Some("score_entry"))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // Lines written before the field existed still parse
+        let legacy = r#"{"operation":"place_order","ts":1,"intent_id":"I-0","params_hash":"h","symbol":"BTCUSDT","side":"BUY","qty":0.1}"#;
+        match Wal::parse_line(legacy) {
+            Some(WalEntry::PlaceOrder { reason, .. }) => assert!(reason.is_none()),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    fn place(ts: u64, intent: &str) -> WalEntry {
+        WalEntry::PlaceOrder {
+            ts,
+            intent_id: intent.to_string(),
+            strategy_id: Some("s-1".to_string()),
+            client_order_id: Some(format!("CID-{}", intent)),
+            params_hash: "h".to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side: "BUY".to_string(),
+            qty: 0.1,
+            reason: Some("score_entry".to_string()),
+            fsync: true,
+        }
+    }
+
+    fn fill(ts: u64, intent: &str, price: f64) -> WalEntry {
+        WalEntry::Fill {
+            ts,
+            intent_id: intent.to_string(),
+            params_hash: "h".to_string(),
+            price,
+            qty: 0.1,
+            fee: 0.01,
+            fsync: true,
+        }
+    }
+
+    fn snapshot(ts: u64, strategy: &str, cash: f64) -> WalEntry {
+        WalEntry::Snapshot {
+            ts,
+            strategy_id: strategy.to_string(),
+            cash,
+            position: 0.1,
+            entry_price: 50000.0,
+            equity: cash + 5000.0,
+            pnl: cash - 1000.0,
+            equity_peak: 0.0,
+            max_drawdown: 0.0,
+            position_opened_ts: 0,
+        }
+    }
+
+    fn ack(ts: u64, intent: &str, order_id: &str) -> WalEntry {
+        WalEntry::Ack {
+            ts,
+            intent_id: intent.to_string(),
+            client_order_id: format!("CID-{}", intent),
+            exchange_order_id: order_id.to_string(),
+            fsync: true,
+        }
+    }
+
+    fn assert_same_recovery(a: &RecoveryState, b: &RecoveryState) {
+        assert_eq!(recovery_hash(a), recovery_hash(b));
+        // Compaction may only forget acks of orders that are done
+        for (cid, oid) in &b.acked_orders {
+            assert_eq!(a.acked_orders.get(cid), Some(oid));
+        }
+        for p in &a.pending_orders {
+            let cid = p.client_order_id.as_ref();
+            assert_eq!(
+                cid.and_then(|c| a.acked_orders.get(c)),
+                cid.and_then(|c| b.acked_orders.get(c))
+            );
+        }
+        let last = |s: &RecoveryState| {
+            s.last_snapshot
+                .as_ref()
+                .map(|x| (x.strategy_id.clone(), x.ts))
+        };
+        assert_eq!(last(a), last(b));
+        let pending = |s: &RecoveryState| -> Vec<String> {
+            s.pending_orders
+                .iter()
+                .map(|p| p.intent_id.clone())
+                .collect()
+        };
+        assert_eq!(pending(a), pending(b));
+    }
+
+    #[test]
+    fn test_corrupt_entries_skipped_while_valid_ones_recover() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("crc.wal");
+        let path = path.to_str().unwrap();
+        {
+            let mut wal = Wal::open(path).unwrap();
+            for e in [
+                place(1000, "I-1"),
+                fill(1001, "I-1", 50_000.0),
+                place(1002, "I-2"),
+                fill(1003, "I-2", 51_000.0),
+            ] {
+                wal.append_entry(&e).unwrap();
+            }
+        }
+        let text = fs::read_to_string(path).unwrap();
+        assert!(text
+            .lines()
+            .all(|l| unseal(l).is_ok() && l.contains(CRC_FIELD)));
+
+        // Flip the first fill's price on disk, then tear a write mid-line
+        let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+        lines[1] = lines[1].replace("50000.0", "5000.0");
+        assert!(unseal(&lines[1]).is_err());
+        lines.push(r#"{"operation":"fill","ts":1004,"intent_id":"I-"#.to_string());
+        fs::write(path, lines.join("\n") + "\n").unwrap();
+
+        let state = Wal::recover(path).unwrap();
+        assert_eq!(state.corrupt_entries, 2);
+        assert_eq!(state.fills_since_snapshot.len(), 1);
+        assert_eq!(state.fills_since_snapshot[0].intent_id, "I-2");
+        assert_eq!(state.fills_since_snapshot[0].price, 51_000.0);
+        // The corrupted fill never happened as far as recovery knows
+        assert_eq!(state.pending_orders.len(), 1);
+        assert_eq!(state.pending_orders[0].intent_id, "I-1");
+
+        // Lines from before checksums still parse
+        let legacy = serde_json::to_string(&fill(1005, "I-3", 1.0)).unwrap();
+        assert_eq!(unseal(&legacy), Ok(legacy.clone()));
+    }
+
+    #[test]
+    fn test_snapshot_contradicting_fills_is_inconsistent() {
+        use crate::strategy::{ContractKind, PortfolioState};
+
+        let snap = |ts: u64, cash: f64, position: f64| WalEntry::Snapshot {
+            ts,
+            strategy_id: "s-1".to_string(),
+            cash,
+            position,
+            entry_price: 50000.0,
+            equity: cash + position * 50000.0,
+            pnl: 0.0,
+            equity_peak: 0.0,
+            max_drawdown: 0.0,
+            position_opened_ts: 0,
+        };
+        // Snapshot + replay, the way `apply_to` restores a strategy
+        let restore = |state: &RecoveryState| {
+            let last = &state.snapshots_by_strategy["s-1"];
+            let mut p = PortfolioState {
+                cash: last.cash,
+                position: last.position,
+                entry_price: last.entry_price,
+                equity: last.equity,
+                contract: ContractKind::Linear,
+            };
+            for f in &state.fills_since_snapshot {
+                p.apply_fill(crate::state::Fill {
+                    price: f.price,
+                    qty: f.qty,
+                    fee: f.fee,
+                    ts: f.ts,
+                });
+            }
+            p
+        };
+        let recover = |second: WalEntry| {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("consistency.wal");
+            let path = path.to_str().unwrap();
+            let mut wal = Wal::open(path).unwrap();
+            for entry in [
+                snap(1, 10_000.0, 0.0),
+                fill(2, "I-s-1-1", 50000.0),
+                second,
+                fill(4, "I-s-1-2", 50000.0),
+            ] {
+                wal.append_entry(&entry).unwrap();
+            }
+            Wal::recover(path).unwrap()
+        };
+
+        // Second snapshot agrees with the fill before it
+        let state = recover(snap(3, 10_000.0 - 5000.01, 0.1));
+        assert!(state.check_consistency("s-1", &restore(&state)));
+
+        // Second snapshot claims a short the fills never opened
+        let state = recover(snap(3, 10_000.0, -0.3));
+        let recovered = restore(&state);
+        assert!((recovered.position - (-0.2)).abs() < 1e-12);
+        let (rebuilt, _) = state.rebuild_from_fills("s-1", &recovered).unwrap();
+        assert!((rebuilt.position - 0.2).abs() < 1e-12);
+        assert!(!state.check_consistency("s-1", &recovered));
+
+        // No snapshot to rebuild from: nothing to contradict
+        assert!(state.check_consistency("s-2", &recovered));
+    }
+
+    #[test]
+    fn test_compaction_preserves_recovery() {
+        let path = "/tmp/test_wal_compact.log";
+        let _ = fs::remove_file(path);
+        let mut wal = Wal::open(path).unwrap();
+        let entries = [
+            place(1000, "I-1"),
+            ack(1000, "I-1", "E-1"),
+            fill(1001, "I-1", 50000.0),
+            snapshot(1002, "s-1", 1000.0),
+            snapshot(1002, "s-2", 2000.0),
+            place(1003, "I-2"),
+            place(1004, "I-3"),
+            ack(1004, "I-3", "E-3"),
+            ack(1005, "I-3", "E-3b"),
+            fill(1006, "I-2", 50100.0),
+            snapshot(1010, "s-1", 1100.0),
+            place(1011, "I-4"),
+            ack(1011, "I-4", "E-4"),
+            WalEntry::Cancel {
+                ts: 1012,
+                intent_id: "I-4".to_string(),
+                params_hash: "h".to_string(),
+                fsync: true,
+            },
+            snapshot(1020, "s-2", 2100.0),
+            place(1021, "I-5"),
+            ack(1021, "I-5", "E-5"),
+            fill(1022, "I-5", 50200.0),
+            fill(1023, "I-6", 50300.0),
+            place(1024, "I-7"),
+        ];
+        for e in &entries {
+            wal.append_entry(e).unwrap();
+        }
+        // Hand-written line without optional fields, and a torn write
+        wal.append(r#"{"operation":"place_order","ts":1025,"intent_id":"I-8","symbol":"BTCUSDT","side":"SELL","qty":0.2}"#)
+            .unwrap();
+        wal.append(r#"{"operation":"fill","ts":10"#).unwrap();
+
+        let before = Wal::recover(path).unwrap();
+        let (kept, total) = wal.compact().unwrap();
+        let after = Wal::recover(path).unwrap();
+
+        assert_eq!(total, entries.len() + 2);
+        assert!(kept < total);
+        assert_same_recovery(&before, &after);
+        assert_eq!(after.fills_since_snapshot.len(), 2);
+        let pending: Vec<_> = after
+            .pending_orders
+            .iter()
+            .map(|p| p.intent_id.as_str())
+            .collect();
+        assert_eq!(pending, vec!["I-3", "I-7", "I-8"]);
+        assert_eq!(after.acked_orders["CID-I-3"], "E-3b");
+        // Filled before the snapshot or cancelled: the ack goes; I-5's fill
+        // is still replayed, so its ack stays
+        let mut acked: Vec<_> = after.acked_orders.keys().map(String::as_str).collect();
+        acked.sort_unstable();
+        assert_eq!(acked, vec!["CID-I-3", "CID-I-5"]);
+        assert_eq!(before.acked_orders.len(), 4);
+        assert!(!Path::new("/tmp/test_wal_compact.log.compact.tmp").exists());
+
+        // Compaction is idempotent and the handle keeps appending to the new file
+        let (kept_again, total_again) = wal.compact().unwrap();
+        assert_eq!((kept_again, total_again), (kept, kept));
+        wal.append_entry(&fill(1030, "I-7", 50400.0)).unwrap();
+        let appended = Wal::recover(path).unwrap();
+        assert_eq!(appended.fills_since_snapshot.len(), 3);
+        assert!(appended.pending_orders.iter().all(|p| p.intent_id != "I-7"));
+
+        let _ = fs::remove_file(path);
+    }
 }
@@ -1,5 +1,6 @@
-use crate::state::Config;
-use crate::strategy::{Action, StrategyState};
+use crate::logging::{json_log, json_log_at, obj, v_num, v_str, Level};
+use crate::state::{Config, DeleverCurve, SizingMode};
+use crate::strategy::{Action, StrategyState, TrendRegime};
 
 /// Kelly criterion position sizing
 pub fn kelly_size(win_rate: f64, avg_win: f64, avg_loss: f64, equity: f64, fraction: f64) -> f64 {
@@ -68,7 +69,8 @@ pub struct RiskEngine {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::strategy::{MetricsState, PortfolioState};
+    use crate::state::Fill;
+    use crate::strategy::{ContractKind, MetricsState, PortfolioState};
 
     #[test]
     fn test_kelly_size_positive_edge() {
@@ -163,17 +165,13 @@ mod tests {
                 position,
                 entry_price,
                 equity,
+                contract: ContractKind::Linear,
             },
             metrics: MetricsState {
                 pnl: realized_pnl,
                 ..Default::default()
             },
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
+            ..StrategyState::with_capital(equity, ContractKind::Linear)
         }
     }
 
@@ -323,6 +321,235 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_drawdown_kill_flattens_then_blocks_reentry() {
+        let cfg = make_config(); // 5% daily loss -> 10% drawdown kill
+        let mut engine = RiskEngine::new(cfg);
+
+        let mut state = make_state(0.1, 50000.0, 10000.0, 0.0);
+        state.metrics.max_drawdown = -0.099;
+        assert!(!engine.enforce_drawdown_kill(&mut state, "s"));
+        assert!(matches!(
+            engine.apply_with_price(&state, Action::Hold, 1000, 50000.0),
+            Action::Hold
+        ));
+
+        // At the limit the engine forces a close regardless of the signal
+        state.metrics.max_drawdown = -0.10;
+        let action = engine.apply_with_price(&state, Action::Buy { qty: 0.1 }, 1000, 50000.0);
+        assert!(matches!(action, Action::Close), "got {:?}", action);
+        assert!(engine.enforce_drawdown_kill(&mut state, "s"));
+        assert!(state.trading_halted);
+
+        // Flat afterwards: no re-entry, even once the daily loss is fine
+        state.portfolio.position = 0.0;
+        state.portfolio.cash = 10000.0;
+        for action in [Action::Buy { qty: 0.1 }, Action::Sell { qty: 0.1 }] {
+            let guarded = engine.apply_with_price(&state, action, 100_000, 50000.0);
+            assert!(matches!(guarded, Action::Hold), "got {:?}", guarded);
+        }
+    }
+
+    #[test]
+    fn test_drawdown_kill_limit_is_configurable() {
+        let mut cfg = make_config();
+        cfg.max_drawdown_mult = 4.0; // 20% kill
+        let engine = RiskEngine::new(cfg);
+        let mut state = make_state(0.0, 0.0, 10000.0, 0.0);
+        state.metrics.max_drawdown = -0.15;
+        assert!(!engine.enforce_drawdown_kill(&mut state, "s"));
+        assert!(!state.trading_halted);
+    }
+
+    #[test]
+    fn test_losing_profit_factor_disables_strategy() {
+        let mut cfg = make_config();
+        cfg.pf_disable_trades = 4;
+        cfg.pf_disable_min = 1.0;
+        let mut engine = RiskEngine::new(cfg);
+
+        // Wins 3, losses 4 over the last four trades: PF 0.75
+        let mut losing = make_state(0.0, 0.0, 10000.0, 0.0);
+        for pnl in [50.0, -2.0, 1.0, 2.0, -2.0] {
+            losing.metrics.record_trade(pnl);
+        }
+        assert!(engine.enforce_profit_factor(&mut losing, "loser"));
+        assert!(losing.disabled);
+        let guarded = engine.apply_with_price(&losing, Action::Buy { qty: 0.1 }, 1000, 50000.0);
+        assert!(matches!(guarded, Action::Hold), "got {:?}", guarded);
+        assert!(matches!(
+            engine.apply_with_price(&losing, Action::Close, 1000, 50000.0),
+            Action::Close
+        ));
+        // Sticky even if later trades would lift the ratio
+        losing.metrics.record_trade(100.0);
+        assert!(engine.enforce_profit_factor(&mut losing, "loser"));
+
+        // Operator re-enable starts a fresh window
+        losing.reenable();
+        assert!(!losing.disabled);
+        assert!(!engine.enforce_profit_factor(&mut losing, "loser"));
+
+        // PF 1.5 over four trades stays active
+        let mut winning = make_state(0.0, 0.0, 10000.0, 0.0);
+        for pnl in [2.0, -1.0, -1.0, 1.0] {
+            winning.metrics.record_trade(pnl);
+        }
+        assert!(!engine.enforce_profit_factor(&mut winning, "winner"));
+        assert!(!winning.disabled);
+
+        // Too few trades to judge
+        let mut fresh = make_state(0.0, 0.0, 10000.0, 0.0);
+        fresh.metrics.record_trade(-5.0);
+        assert!(!engine.enforce_profit_factor(&mut fresh, "fresh"));
+    }
+
+    #[test]
+    fn test_daily_trade_cap_resets_on_new_day_without_fills() {
+        let mut engine = RiskEngine::new(make_config());
+        let mut state = make_state(0.0, 0.0, 10000.0, 0.0);
+        state.trade_day = 19_000;
+        state.trades_today = 20;
+
+        // Same day: capped
+        let same_day = 19_000 * 86_400 + 3_600;
+        let guarded = engine.apply_with_price(&state, Action::Buy { qty: 0.01 }, same_day, 50000.0);
+        assert!(matches!(guarded, Action::Hold), "got {:?}", guarded);
+
+        // Next day the stale count no longer blocks, though no fill reset it
+        let next_day = same_day + 86_400;
+        let guarded = engine.apply_with_price(&state, Action::Buy { qty: 0.01 }, next_day, 50000.0);
+        assert!(matches!(guarded, Action::Buy { .. }), "got {:?}", guarded);
+    }
+
+    #[test]
+    fn test_notional_cap_rejects_oversized_orders() {
+        let mut cfg = make_config();
+        cfg.max_order_notional = 1000.0;
+        let engine = RiskEngine::new(cfg);
+        let state = make_state(0.0, 0.0, 10000.0, 0.0);
+
+        // 0.1 @ 50k = 5000 > 1000
+        let big = engine.enforce_notional_cap(&state, Action::Buy { qty: 0.1 }, 50000.0, "s");
+        assert!(matches!(big, Action::Hold), "got {:?}", big);
+        let big = engine.enforce_notional_cap(&state, Action::Sell { qty: 0.1 }, 50000.0, "s");
+        assert!(matches!(big, Action::Hold), "got {:?}", big);
+
+        // 0.01 @ 50k = 500 passes untouched, as does exactly the cap
+        let small = engine.enforce_notional_cap(&state, Action::Buy { qty: 0.01 }, 50000.0, "s");
+        assert!(matches!(small, Action::Buy { qty } if qty == 0.01));
+        let edge = engine.enforce_notional_cap(&state, Action::Sell { qty: 0.02 }, 50000.0, "s");
+        assert!(matches!(edge, Action::Sell { qty } if qty == 0.02));
+
+        // Never blocks flattening
+        let long = make_state(1.0, 50000.0, 10000.0, 0.0);
+        assert!(matches!(
+            engine.enforce_notional_cap(&long, Action::Close, 50000.0, "s"),
+            Action::Close
+        ));
+
+        // 0 disables the cap
+        let off = RiskEngine::new(make_config());
+        assert!(matches!(
+            off.enforce_notional_cap(&state, Action::Buy { qty: 100.0 }, 50000.0, "s"),
+            Action::Buy { .. }
+        ));
+    }
+
+    #[test]
+    fn test_global_max_hold_closes_even_when_strategy_holds() {
+        let mut cfg = make_config();
+        cfg.global_max_hold_secs = 3_600;
+        let mut engine = RiskEngine::new(cfg);
+        let mut state = make_state(0.1, 50000.0, 10000.0, 0.0);
+        let opened = 1_000_000;
+
+        // First sight starts the clock; within the limit the strategy decides
+        let held = engine.enforce_max_hold(&mut state, Action::Hold, "s", opened);
+        assert!(matches!(held, Action::Hold));
+        assert_eq!(state.position_opened_ts, opened);
+        let held = engine.enforce_max_hold(&mut state, Action::Hold, "s", opened + 3_599);
+        assert!(matches!(held, Action::Hold));
+
+        // Past the limit the Hold becomes a Close that the risk engine passes
+        let forced = engine.enforce_max_hold(&mut state, Action::Hold, "s", opened + 3_600);
+        assert!(matches!(forced, Action::Close), "got {:?}", forced);
+        let guarded = engine.apply_with_price(&state, forced, opened + 3_600, 50000.0);
+        assert!(matches!(guarded, Action::Close), "got {:?}", guarded);
+        // Adds are overridden too
+        let forced =
+            engine.enforce_max_hold(&mut state, Action::Buy { qty: 0.01 }, "s", opened + 7_200);
+        assert!(matches!(forced, Action::Close));
+
+        // Flat resets the clock
+        state.portfolio.position = 0.0;
+        engine.enforce_max_hold(&mut state, Action::Hold, "s", opened + 7_300);
+        assert_eq!(state.position_opened_ts, 0);
+
+        // 0 disables the limit
+        let off = RiskEngine::new(make_config());
+        let mut long = make_state(0.1, 50000.0, 10000.0, 0.0);
+        long.position_opened_ts = 1;
+        assert!(matches!(
+            off.enforce_max_hold(&mut long, Action::Hold, "s", opened),
+            Action::Hold
+        ));
+    }
+
+    #[test]
+    fn test_regime_change_resets_cooldown_only_when_enabled() {
+        let mut cfg = make_config();
+        cfg.cooldown_secs = 600;
+        cfg.cooldown_ref_loss_pct = 0.0;
+        let mut router = crate::strategy::RegimeRouter::new(2);
+        router.on_close("BTC", 100.0);
+        let first = router.on_close("BTC", 90.0).unwrap();
+        let regime = router.on_close("BTC", 120.0).unwrap();
+        assert_ne!(first, regime);
+
+        let cooling = || {
+            let mut state = make_state(0.0, 0.0, 10000.0, -50.0);
+            state.record_loss(1_000, -50.0);
+            state
+        };
+        let buy = Action::Buy { qty: 0.01 };
+        for (enabled, expect_cleared) in [(false, false), (true, true)] {
+            cfg.reset_cooldown_on_regime = enabled;
+            let mut engine = RiskEngine::new(cfg.clone());
+            let mut state = cooling();
+            assert!(matches!(
+                engine.apply_with_price(&state, buy, 1_100, 50000.0),
+                Action::Hold
+            ));
+            let cleared = engine.on_regime_change(&mut state, "s", regime, 1_100);
+            assert_eq!(cleared, expect_cleared);
+            let after = engine.apply_with_price(&state, buy, 1_100, 50000.0);
+            assert_eq!(
+                matches!(after, Action::Buy { .. }),
+                expect_cleared,
+                "enabled={} got {:?}",
+                enabled,
+                after
+            );
+        }
+
+        // An expired cooldown is left alone
+        let engine = RiskEngine::new(cfg);
+        let mut state = cooling();
+        assert!(!engine.on_regime_change(&mut state, "s", regime, 1_600));
+        assert_eq!(state.last_loss_ts, 1_000);
+    }
+
+    #[test]
+    fn test_profit_factor_gate_off_by_default() {
+        let engine = RiskEngine::new(make_config());
+        let mut state = make_state(0.0, 0.0, 10000.0, 0.0);
+        for _ in 0..30 {
+            state.metrics.record_trade(-1.0);
+        }
+        assert!(!engine.enforce_profit_factor(&mut state, "s"));
+    }
+
     #[test]
     fn test_cooldown_after_loss() {
         let cfg = make_config();
@@ -341,6 +568,307 @@ mod tests {
             action
         );
     }
+
+    #[test]
+    fn test_position_cap_shrinks_oversized_orders() {
+        let mut cfg = make_config();
+        cfg.max_position_pct = 0.10;
+        let engine = RiskEngine::new(cfg);
+        // 10% of 100k equity at 50k = 0.2 BTC cap
+        let flat = make_state(0.0, 0.0, 100_000.0, 0.0);
+
+        match engine.clamp_to_position_cap(&flat, Action::Buy { qty: 1.0 }, 50_000.0) {
+            Action::Buy { qty } => assert!((qty - 0.2).abs() < 1e-12, "qty={}", qty),
+            other => panic!("expected shrunk buy, got {:?}", other),
+        }
+        match engine.clamp_to_position_cap(&flat, Action::Buy { qty: 0.05 }, 50_000.0) {
+            Action::Buy { qty } => assert_eq!(qty, 0.05),
+            other => panic!("expected unchanged buy, got {:?}", other),
+        }
+
+        // Long 0.15: only 0.05 more fits; selling may flip up to the cap short
+        let long = make_state(0.15, 50_000.0, 100_000.0, 0.0);
+        match engine.clamp_to_position_cap(&long, Action::Buy { qty: 0.1 }, 50_000.0) {
+            Action::Buy { qty } => assert!((qty - 0.05).abs() < 1e-12),
+            other => panic!("expected shrunk buy, got {:?}", other),
+        }
+        match engine.clamp_to_position_cap(&long, Action::Sell { qty: 1.0 }, 50_000.0) {
+            Action::Sell { qty } => assert!((qty - 0.35).abs() < 1e-12),
+            other => panic!("expected shrunk sell, got {:?}", other),
+        }
+
+        // At the cap, a further add is a Hold
+        let full = make_state(0.2, 50_000.0, 100_000.0, 0.0);
+        assert!(matches!(
+            engine.clamp_to_position_cap(&full, Action::Buy { qty: 0.01 }, 50_000.0),
+            Action::Hold
+        ));
+        assert!(matches!(
+            engine.clamp_to_position_cap(&full, Action::Close, 50_000.0),
+            Action::Close
+        ));
+    }
+
+    #[test]
+    fn test_cooldown_scales_with_loss_size() {
+        let mut cfg = make_config();
+        cfg.cooldown_secs = 600;
+        cfg.cooldown_ref_loss_pct = 0.005;
+        cfg.cooldown_max_secs = 3600;
+        let mut engine = RiskEngine::new(cfg);
+
+        assert_eq!(engine.loss_cooldown_secs(0.001), 600);
+        assert_eq!(engine.loss_cooldown_secs(0.02), 2400);
+        assert_eq!(engine.loss_cooldown_secs(0.10), 3600);
+
+        // 2% loss 1000s ago is still cooling down; a 0.1% loss is not
+        let mut state = make_state(0.0, 0.0, 10000.0, -200.0);
+        state.last_loss_ts = 1000;
+        state.last_loss_pct = 0.02;
+        let action = engine.apply_with_price(&state, Action::Buy { qty: 0.01 }, 2000, 50000.0);
+        assert!(matches!(action, Action::Hold), "got {:?}", action);
+        state.last_loss_pct = 0.001;
+        let action = engine.apply_with_price(&state, Action::Buy { qty: 0.01 }, 2000, 50000.0);
+        assert!(matches!(action, Action::Buy { .. }), "got {:?}", action);
+    }
+
+    /// Hold one unit through `moves` bar price changes, then go flat.
+    fn state_after_path(moves: &[f64]) -> StrategyState {
+        let mut engine = crate::metrics::MetricsEngine::new();
+        let mut state = make_state(1.0, 100.0, 10_000.0, 0.0);
+        let mut price = 100.0;
+        for dp in moves {
+            price += dp;
+            engine.update_with_price(&mut state, price);
+        }
+        state.portfolio.position = 0.0;
+        state.portfolio.equity = 10_000.0;
+        state
+    }
+
+    #[test]
+    fn test_cvar_budget_sizes_fat_tail_smaller() {
+        let mut cfg = make_config();
+        cfg.sizing_mode = SizingMode::CvarBudget;
+        cfg.cvar_budget_pct = 0.002;
+        let engine = RiskEngine::new(cfg);
+
+        // Same ±1 chop; the fat-tailed path also gaps down 15 every 20 bars
+        let thin: Vec<f64> = (0..100)
+            .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+            .collect();
+        let fat: Vec<f64> = (0..100)
+            .map(|i| match i % 20 {
+                19 => -15.0,
+                _ if i % 2 == 0 => 1.0,
+                _ => -1.0,
+            })
+            .collect();
+        let thin = state_after_path(&thin);
+        let fat = state_after_path(&fat);
+        assert!(fat.metrics.cvar_per_unit().unwrap() > thin.metrics.cvar_per_unit().unwrap());
+
+        let qty =
+            |state: &StrategyState| match engine.size_for_cvar(state, Action::Buy { qty: 0.001 }) {
+                Action::Buy { qty } => qty,
+                other => panic!("expected Buy, got {:?}", other),
+            };
+        let (thin_qty, fat_qty) = (qty(&thin), qty(&fat));
+        assert!(fat_qty < thin_qty, "fat {} vs thin {}", fat_qty, thin_qty);
+        // Budget of 20 over a CVaR of 1 per unit
+        assert!((thin_qty - 20.0).abs() < 1e-6, "thin qty {}", thin_qty);
+
+        // Reductions pass through; a full budget holds further adds
+        let mut long = thin;
+        long.portfolio.position = 25.0;
+        assert!(matches!(
+            engine.size_for_cvar(&long, Action::Buy { qty: 1.0 }),
+            Action::Hold
+        ));
+        assert!(matches!(
+            engine.size_for_cvar(&long, Action::Sell { qty: 1.0 }),
+            Action::Sell { qty } if qty == 1.0
+        ));
+    }
+
+    #[test]
+    fn test_cvar_budget_keeps_strategy_qty_without_history() {
+        let mut cfg = make_config();
+        cfg.sizing_mode = SizingMode::CvarBudget;
+        let engine = RiskEngine::new(cfg);
+        let state = make_state(0.0, 0.0, 10_000.0, 0.0);
+        assert!(matches!(
+            engine.size_for_cvar(&state, Action::Buy { qty: 0.01 }),
+            Action::Buy { qty } if qty == 0.01
+        ));
+    }
+
+    fn state_with_trades(pnls: &[f64]) -> StrategyState {
+        let mut state = make_state(0.0, 0.0, 10_000.0, 0.0);
+        for &pnl in pnls {
+            state.metrics.record_trade(pnl);
+        }
+        state
+    }
+
+    #[test]
+    fn test_kelly_sizes_high_edge_larger_within_cap() {
+        let mut cfg = make_config();
+        cfg.sizing_mode = SizingMode::Kelly;
+        cfg.kelly_fraction = 0.25;
+        cfg.kelly_window = 20;
+        cfg.max_position_pct = 0.10;
+        let engine = RiskEngine::new(cfg);
+        let qty = |state: &StrategyState| match engine.size_for_kelly(
+            state,
+            Action::Buy { qty: 0.001 },
+            100.0,
+        ) {
+            Action::Buy { qty } => qty,
+            other => panic!("expected Buy, got {:?}", other),
+        };
+
+        // 55% winners at 1.1:1 -> full Kelly ~0.141, quarter Kelly ~3.5% of equity
+        let marginal: Vec<f64> = (0..20).map(|i| if i < 11 { 11.0 } else { -10.0 }).collect();
+        // 70% winners at 2:1 -> full Kelly 0.55, quarter Kelly 13.75%, capped at 10%
+        let strong: Vec<f64> = (0..20).map(|i| if i < 14 { 20.0 } else { -10.0 }).collect();
+        let (marginal_qty, strong_qty) = (
+            qty(&state_with_trades(&marginal)),
+            qty(&state_with_trades(&strong)),
+        );
+        assert!(
+            strong_qty > marginal_qty,
+            "strong {} vs marginal {}",
+            strong_qty,
+            marginal_qty
+        );
+        let expected = (0.55 - 0.45 / 1.1) * 0.25 * 10_000.0 / 100.0;
+        assert!(
+            (marginal_qty - expected).abs() < 1e-9,
+            "marginal qty {}",
+            marginal_qty
+        );
+        assert!(
+            (strong_qty - 10.0).abs() < 1e-9,
+            "capped qty {}",
+            strong_qty
+        );
+
+        // Losing window holds; a short history keeps the strategy's quantity
+        let losing: Vec<f64> = (0..20).map(|i| if i < 8 { 10.0 } else { -10.0 }).collect();
+        assert!(matches!(
+            engine.size_for_kelly(
+                &state_with_trades(&losing),
+                Action::Buy { qty: 0.001 },
+                100.0
+            ),
+            Action::Hold
+        ));
+        assert!(matches!(
+            engine.size_for_kelly(&state_with_trades(&strong[..5]), Action::Buy { qty: 0.001 }, 100.0),
+            Action::Buy { qty } if qty == 0.001
+        ));
+    }
+
+    #[test]
+    fn test_kelly_from_backtest_fill_sequence_keeps_trading_an_edge() {
+        let mut cfg = make_config();
+        cfg.sizing_mode = SizingMode::Kelly;
+        cfg.kelly_window = 20;
+        let engine = RiskEngine::new(cfg);
+
+        // Round trips as the backtest fills them: entry in two partial fills,
+        // exit in two, 10 bps fees on each. 60% win +2%, 40% lose -1%.
+        let mut state = make_state(0.0, 0.0, 10_000.0, 0.0);
+        state.portfolio.cash = 10_000.0;
+        for i in 0..20 {
+            let exit = if i % 5 < 3 { 102.0 } else { 99.0 };
+            for (qty, price) in [(0.5, 100.0), (0.5, 100.0), (-0.5, exit), (-0.5, exit)] {
+                let fill = Fill {
+                    price,
+                    qty,
+                    fee: price * f64::abs(qty) * 0.001,
+                    ts: i,
+                };
+                let realized = state.portfolio.apply_fill(fill);
+                state
+                    .metrics
+                    .record_fill(realized, &fill, state.portfolio.position);
+            }
+        }
+        assert_eq!(state.portfolio.position, 0.0);
+
+        // Net of fees the window holds 12 wins of 1.798 and 8 losses of 1.199
+        let kelly = state.metrics.recent_trades.kelly_fraction(20).unwrap();
+        let payoff = 1.798 / 1.199;
+        assert!(
+            (kelly - (0.6 - 0.4 / payoff)).abs() < 1e-9,
+            "kelly {}",
+            kelly
+        );
+        assert!(matches!(
+            engine.size_for_kelly(&state, Action::Buy { qty: 0.001 }, 100.0),
+            Action::Buy { qty } if qty > 0.001
+        ));
+    }
+
+    #[test]
+    fn test_delever_curve_maps_drawdown_to_multiplier() {
+        let mut cfg = make_config();
+        // Kill limit at 5% x 2 = 10% drawdown
+        cfg.max_drawdown_mult = 2.0;
+        cfg.delever_curve = DeleverCurve::Linear;
+        let engine = RiskEngine::new(cfg.clone());
+        let at_drawdown = |dd: f64| {
+            let mut state = make_state(0.0, 0.0, 10_000.0 * (1.0 - dd), 0.0);
+            state.metrics.equity_peak = 10_000.0;
+            state
+        };
+        let cases = [(0.0, 1.0), (0.025, 0.75), (0.05, 0.5), (0.075, 0.25)];
+        for (dd, want) in cases {
+            let got = engine.delever_multiplier(&at_drawdown(dd));
+            assert!((got - want).abs() < 1e-9, "dd {}: {} vs {}", dd, got, want);
+        }
+        assert_eq!(engine.delever_multiplier(&at_drawdown(0.10)), 0.0);
+        assert_eq!(engine.delever_multiplier(&at_drawdown(0.15)), 0.0);
+
+        // Halfway to the limit under the other shapes
+        let half = at_drawdown(0.05);
+        let shaped = |curve| {
+            let mut cfg = cfg.clone();
+            cfg.delever_curve = curve;
+            RiskEngine::new(cfg).delever_multiplier(&half)
+        };
+        assert!((shaped(DeleverCurve::Convex) - 0.25).abs() < 1e-9);
+        assert!((shaped(DeleverCurve::Concave) - 0.75).abs() < 1e-9);
+        assert_eq!(shaped(DeleverCurve::Off), 1.0);
+        assert_eq!(DeleverCurve::parse("CONVEX"), Some(DeleverCurve::Convex));
+        assert_eq!(DeleverCurve::parse("nope"), None);
+    }
+
+    #[test]
+    fn test_delever_shrinks_entries_but_not_exits() {
+        let mut cfg = make_config();
+        cfg.delever_curve = DeleverCurve::Linear;
+        cfg.clamp_position_qty = false;
+        let mut engine = RiskEngine::new(cfg);
+        let mut state = make_state(0.0, 0.0, 9_500.0, 0.0);
+        state.metrics.equity_peak = 10_000.0;
+
+        let sized = engine.apply_with_price(&state, Action::Buy { qty: 1.0 }, 1_000_000, 100.0);
+        assert!(matches!(sized, Action::Buy { qty } if (qty - 0.5).abs() < 1e-9));
+
+        state.portfolio.position = 1.0;
+        assert!(matches!(
+            engine.delever(&state, Action::Sell { qty: 1.0 }),
+            Action::Sell { qty } if qty == 1.0
+        ));
+        state.portfolio.equity = 9_000.0;
+        assert!(matches!(
+            engine.delever(&state, Action::Buy { qty: 1.0 }),
+            Action::Hold
+        ));
+    }
 }
 
 impl RiskEngine {
@@ -408,19 +936,240 @@ impl RiskEngine {
 
     /// Calculate unrealized PnL for current position
     fn unrealized_pnl(state: &StrategyState, current_price: f64) -> f64 {
-        if state.portfolio.position == 0.0 || state.portfolio.entry_price == 0.0 {
-            return 0.0;
-        }
-        let price_delta = current_price - state.portfolio.entry_price;
-        state.portfolio.position * price_delta
+        state.portfolio.unrealized_pnl(current_price)
     }
 
     /// Calculate total exposure as fraction of equity
     fn exposure_pct(state: &StrategyState, current_price: f64) -> f64 {
-        let notional = state.portfolio.position.abs() * current_price;
+        let notional = state
+            .portfolio
+            .contract
+            .notional(state.portfolio.position, current_price);
         notional / state.portfolio.equity.max(1.0)
     }
 
+    /// Cooldown after a loss of `loss_pct` of equity: the base `cooldown_secs`
+    /// up to `cooldown_ref_loss_pct`, proportionally longer beyond it, capped
+    /// at `cooldown_max_secs`.
+    pub fn loss_cooldown_secs(&self, loss_pct: f64) -> u64 {
+        let base = self.cfg.cooldown_secs;
+        let reference = self.cfg.cooldown_ref_loss_pct;
+        if reference <= 0.0 || !loss_pct.is_finite() || loss_pct <= reference {
+            return base;
+        }
+        let scaled = base as f64 * loss_pct / reference;
+        (scaled as u64).min(self.cfg.cooldown_max_secs.max(base))
+    }
+
+    /// Entry size multiplier from `delever_curve`, given the current
+    /// drawdown from the equity peak against the kill limit. 1 with the
+    /// curve off, 0 at or past the limit.
+    pub fn delever_multiplier(&self, state: &StrategyState) -> f64 {
+        let limit = self.cfg.max_daily_loss_pct * self.cfg.max_drawdown_mult;
+        let peak = state.metrics.equity_peak;
+        if self.cfg.delever_curve == DeleverCurve::Off || limit <= 0.0 || peak <= 0.0 {
+            return 1.0;
+        }
+        let drawdown = ((peak - state.portfolio.equity) / peak).max(0.0);
+        self.cfg.delever_curve.multiplier(drawdown / limit)
+    }
+
+    /// Scale an entry (or add) by `delever_multiplier`; Hold once it reaches
+    /// zero. Reductions pass through.
+    pub fn delever(&self, state: &StrategyState, action: Action) -> Action {
+        let position = state.portfolio.position;
+        let (qty, adds) = match action {
+            Action::Buy { qty } => (qty, position >= 0.0),
+            Action::Sell { qty } => (qty, position <= 0.0),
+            _ => return action,
+        };
+        if !adds {
+            return action;
+        }
+        let qty = qty * self.delever_multiplier(state);
+        if qty <= 0.0 {
+            return Action::Hold;
+        }
+        match action {
+            Action::Buy { .. } => Action::Buy { qty },
+            _ => Action::Sell { qty },
+        }
+    }
+
+    /// On a trend-regime change for the strategy's symbol, clear a loss
+    /// cooldown still running at `now_ts` when `reset_cooldown_on_regime` is
+    /// set. Returns true when a cooldown was cleared.
+    pub fn on_regime_change(
+        &self,
+        state: &mut StrategyState,
+        strategy_id: &str,
+        regime: TrendRegime,
+        now_ts: u64,
+    ) -> bool {
+        if !self.cfg.reset_cooldown_on_regime {
+            return false;
+        }
+        let cooldown = self.loss_cooldown_secs(state.last_loss_pct);
+        let elapsed = now_ts.saturating_sub(state.last_loss_ts);
+        if state.last_loss_ts == 0 || elapsed >= cooldown {
+            return false;
+        }
+        state.last_loss_ts = 0;
+        state.last_loss_pct = 0.0;
+        json_log_at(
+            Level::Debug,
+            "risk_guard",
+            obj(&[
+                ("check", v_str("cooldown")),
+                ("result", v_str("reset")),
+                ("strategy", v_str(strategy_id)),
+                ("regime", v_str(regime.as_str())),
+                ("remaining_secs", v_num((cooldown - elapsed) as f64)),
+            ]),
+        );
+        true
+    }
+
+    /// Whether peak-to-trough drawdown has reached the hard kill limit
+    /// (`max_daily_loss_pct * max_drawdown_mult`).
+    pub fn drawdown_breached(&self, state: &StrategyState) -> bool {
+        let limit = self.cfg.max_daily_loss_pct * self.cfg.max_drawdown_mult;
+        limit > 0.0 && -state.metrics.max_drawdown >= limit
+    }
+
+    /// Halt a strategy whose drawdown breached the kill limit. The halt is
+    /// sticky: nothing here clears `trading_halted`, so the strategy stays
+    /// flat until an operator resets it. Returns true while breached.
+    pub fn enforce_drawdown_kill(&self, state: &mut StrategyState, strategy_id: &str) -> bool {
+        if !self.drawdown_breached(state) {
+            return false;
+        }
+        if !state.trading_halted {
+            state.trading_halted = true;
+            json_log_at(
+                Level::Warn,
+                "risk_guard",
+                obj(&[
+                    ("check", v_str("max_drawdown")),
+                    ("result", v_str("halt")),
+                    ("strategy", v_str(strategy_id)),
+                    ("drawdown", v_num(state.metrics.max_drawdown)),
+                    (
+                        "limit",
+                        v_num(self.cfg.max_daily_loss_pct * self.cfg.max_drawdown_mult),
+                    ),
+                ]),
+            );
+        }
+        true
+    }
+
+    /// Disable a strategy whose profit factor over its last
+    /// `pf_disable_trades` round trips is below `pf_disable_min`. Like the
+    /// drawdown kill it is sticky until `StrategyState::reenable`. Returns
+    /// true while disabled.
+    pub fn enforce_profit_factor(&self, state: &mut StrategyState, strategy_id: &str) -> bool {
+        if state.disabled {
+            return true;
+        }
+        let k = self.cfg.pf_disable_trades;
+        if k == 0 {
+            return false;
+        }
+        let Some(pf) = state.metrics.rolling_profit_factor(k) else {
+            return false;
+        };
+        if pf >= self.cfg.pf_disable_min {
+            return false;
+        }
+        state.disabled = true;
+        json_log(
+            "risk_guard",
+            obj(&[
+                ("check", v_str("profit_factor")),
+                ("result", v_str("disable")),
+                ("strategy", v_str(strategy_id)),
+                ("profit_factor", v_num(pf)),
+                ("trades", v_num(k as f64)),
+                ("threshold", v_num(self.cfg.pf_disable_min)),
+            ]),
+        );
+        true
+    }
+
+    /// Safety net over every strategy's own exits: a position open for
+    /// `global_max_hold_secs` or longer is force-closed, whatever `action` the
+    /// strategy chose. The clock starts on the first bar the position is seen
+    /// open, so it has bar granularity and restarts after a process restart.
+    pub fn enforce_max_hold(
+        &self,
+        state: &mut StrategyState,
+        action: Action,
+        strategy_id: &str,
+        now_ts: u64,
+    ) -> Action {
+        if state.portfolio.position.abs() <= 1e-9 {
+            state.position_opened_ts = 0;
+            return action;
+        }
+        if state.position_opened_ts == 0 {
+            state.position_opened_ts = now_ts;
+        }
+        let limit = self.cfg.global_max_hold_secs;
+        let held = now_ts.saturating_sub(state.position_opened_ts);
+        if limit == 0 || held < limit || matches!(action, Action::Close) {
+            return action;
+        }
+        json_log(
+            "risk_guard",
+            obj(&[
+                ("check", v_str("global_max_hold")),
+                ("result", v_str("close")),
+                ("strategy", v_str(strategy_id)),
+                ("held_secs", v_num(held as f64)),
+                ("limit_secs", v_num(limit as f64)),
+                ("position", v_num(state.portfolio.position)),
+            ]),
+        );
+        Action::Close
+    }
+
+    /// Last-line guard against sizing bugs: a Buy/Sell whose notional at
+    /// `price` exceeds `max_order_notional` becomes Hold. Closes always pass.
+    pub fn enforce_notional_cap(
+        &self,
+        state: &StrategyState,
+        action: Action,
+        price: f64,
+        strategy_id: &str,
+    ) -> Action {
+        let cap = self.cfg.max_order_notional;
+        let qty = match action {
+            Action::Buy { qty } | Action::Sell { qty } => qty,
+            _ => return action,
+        };
+        if cap <= 0.0 {
+            return action;
+        }
+        let notional = state.portfolio.contract.notional(qty.abs(), price);
+        if notional <= cap {
+            return action;
+        }
+        json_log(
+            "risk_guard",
+            obj(&[
+                ("check", v_str("notional_cap")),
+                ("result", v_str("reject")),
+                ("strategy", v_str(strategy_id)),
+                ("qty", v_num(qty)),
+                ("price", v_num(price)),
+                ("notional", v_num(notional)),
+                ("cap", v_num(cap)),
+            ]),
+        );
+        Action::Hold
+    }
+
     pub fn apply(&mut self, state: &StrategyState, action: Action, now_ts: u64) -> Action {
         self.apply_with_price(state, action, now_ts, state.portfolio.entry_price)
     }
@@ -433,7 +1182,15 @@ impl RiskEngine {
         now_ts: u64,
         current_price: f64,
     ) -> Action {
-        if state.trading_halted {
+        // Drawdown kill switch: flatten whatever the strategy wants
+        if self.drawdown_breached(state) {
+            return if state.portfolio.position != 0.0 {
+                Action::Close
+            } else {
+                Action::Hold
+            };
+        }
+        if state.trading_halted || state.disabled {
             return match action {
                 Action::Close => Action::Close,
                 _ => Action::Hold,
@@ -445,13 +1202,21 @@ impl RiskEngine {
                 _ => Action::Hold,
             };
         }
-        if now_ts.saturating_sub(state.last_loss_ts) < self.cfg.cooldown_secs {
+        if now_ts.saturating_sub(state.last_loss_ts) < self.loss_cooldown_secs(state.last_loss_pct)
+        {
             return match action {
                 Action::Close => Action::Close,
                 _ => Action::Hold,
             };
         }
-        if state.trades_today >= self.cfg.max_trades_per_day {
+        // `trades_today` only rolls over on the next fill, so a count from an
+        // earlier day must not keep blocking entries
+        let trades_today = if state.trade_day == now_ts / 86_400 {
+            state.trades_today
+        } else {
+            0
+        };
+        if trades_today >= self.cfg.max_trades_per_day {
             return match action {
                 Action::Close => Action::Close,
                 Action::Sell { qty } if state.portfolio.position > 0.0 => Action::Sell { qty },
@@ -491,7 +1256,7 @@ impl RiskEngine {
             }
         }
 
-        if state.portfolio.position != 0.0 {
+        let action = if state.portfolio.position != 0.0 {
             match action {
                 Action::Close => action,
                 Action::Sell { .. } => action,
@@ -500,6 +1265,119 @@ impl RiskEngine {
             }
         } else {
             action
+        };
+        let action = match self.cfg.sizing_mode {
+            SizingMode::CvarBudget => self.size_for_cvar(state, action),
+            SizingMode::Kelly => self.size_for_kelly(state, action, current_price),
+            SizingMode::Fixed => action,
+        };
+        let action = self.delever(state, action);
+        if self.cfg.clamp_position_qty {
+            self.clamp_to_position_cap(state, action, current_price)
+        } else {
+            action
+        }
+    }
+
+    /// Resize an entry (or add) so the whole position's 95% CVaR per bar is
+    /// `cvar_budget_pct` of equity, using the strategy's rolling per-unit
+    /// tail. Reductions pass through; without a tail estimate yet the
+    /// strategy's own quantity stands.
+    pub fn size_for_cvar(&self, state: &StrategyState, action: Action) -> Action {
+        let position = state.portfolio.position;
+        let adds = match action {
+            Action::Buy { .. } => position >= 0.0,
+            Action::Sell { .. } => position <= 0.0,
+            _ => false,
+        };
+        if !adds {
+            return action;
+        }
+        let cvar = match state.metrics.cvar_per_unit() {
+            Some(c) if c > 0.0 => c,
+            _ => return action,
+        };
+        let target = self.cfg.cvar_budget_pct * state.portfolio.equity.max(0.0) / cvar;
+        let qty = target - position.abs();
+        if qty <= 0.0 {
+            return Action::Hold;
+        }
+        match action {
+            Action::Buy { .. } => Action::Buy { qty },
+            _ => Action::Sell { qty },
+        }
+    }
+
+    /// Resize an entry (or add) so the whole position's notional is
+    /// `kelly_fraction` of the Kelly fraction measured over the newest
+    /// `kelly_window` net-of-fee round trips, never more than
+    /// `max_position_pct` of equity.
+    /// No measured edge holds; too short a history keeps the strategy's own
+    /// quantity. Reductions pass through.
+    pub fn size_for_kelly(
+        &self,
+        state: &StrategyState,
+        action: Action,
+        current_price: f64,
+    ) -> Action {
+        let position = state.portfolio.position;
+        let adds = match action {
+            Action::Buy { .. } => position >= 0.0,
+            Action::Sell { .. } => position <= 0.0,
+            _ => false,
+        };
+        if !adds {
+            return action;
+        }
+        let kelly = match state
+            .metrics
+            .recent_trades
+            .kelly_fraction(self.cfg.kelly_window)
+        {
+            Some(k) => k,
+            None => return action,
+        };
+        let unit = state.portfolio.contract.notional(1.0, current_price);
+        if kelly <= 0.0 || unit <= 0.0 {
+            return Action::Hold;
+        }
+        let pct = (kelly * self.cfg.kelly_fraction).min(self.cfg.max_position_pct);
+        let qty = pct * state.portfolio.equity.max(0.0) / unit - position.abs();
+        if qty <= 0.0 {
+            return Action::Hold;
+        }
+        match action {
+            Action::Buy { .. } => Action::Buy { qty },
+            _ => Action::Sell { qty },
+        }
+    }
+
+    /// Shrink a Buy/Sell so the resulting position stays within
+    /// `max_position_pct` of current equity; Hold when nothing fits.
+    pub fn clamp_to_position_cap(
+        &self,
+        state: &StrategyState,
+        action: Action,
+        current_price: f64,
+    ) -> Action {
+        let unit = state.portfolio.contract.notional(1.0, current_price);
+        if unit <= 0.0 {
+            return action;
+        }
+        let max_abs = self.cfg.max_position_pct * state.portfolio.equity.max(1.0) / unit;
+        let position = state.portfolio.position;
+        let (qty, room) = match action {
+            Action::Buy { qty } => (qty, max_abs - position),
+            Action::Sell { qty } => (qty, max_abs + position),
+            _ => return action,
+        };
+        let fitted = qty.min(room);
+        if fitted <= 0.0 {
+            return Action::Hold;
+        }
+        match action {
+            Action::Buy { .. } => Action::Buy { qty: fitted },
+            _ => Action::Sell { qty: fitted },
         }
     }
 }
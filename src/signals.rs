@@ -5,31 +5,63 @@
 
 use crate::strategy::{IndicatorSnapshot, MarketAux};
 
-/// Signal output with strength and direction
+/// Signal output with strength and direction.
+///
+/// Every constructor guarantees a finite `direction` in [-1, 1] and a
+/// `strength` in [0, 1]; NaN inputs collapse to neutral. `is_bullish` and
+/// `is_bearish` are therefore mutually exclusive.
 #[derive(Debug, Clone, Copy, Default)]
 pub struct Signal {
     /// Direction: positive = bullish, negative = bearish, zero = neutral
     pub direction: f64,
-    /// Strength/confidence (0.0 - 1.0)
+    /// Strength/confidence, clamped to [0.0, 1.0]
     pub strength: f64,
     /// Name for logging
     pub source: &'static str,
 }
 
+/// Clamp to `[lo, hi]`, mapping NaN to 0 (`f64::clamp` passes NaN through).
+fn clamp_finite(x: f64, lo: f64, hi: f64) -> f64 {
+    if x.is_nan() {
+        0.0
+    } else {
+        x.clamp(lo, hi)
+    }
+}
+
 impl Signal {
     pub fn neutral() -> Self {
-        Self { direction: 0.0, strength: 0.0, source: "neutral" }
+        Self {
+            direction: 0.0,
+            strength: 0.0,
+            source: "neutral",
+        }
     }
 
     pub fn bullish(strength: f64, source: &'static str) -> Self {
-        Self { direction: 1.0, strength: strength.clamp(0.0, 1.0), source }
+        Self {
+            direction: 1.0,
+            strength: clamp_finite(strength, 0.0, 1.0),
+            source,
+        }
     }
 
     pub fn bearish(strength: f64, source: &'static str) -> Self {
-        Self { direction: -1.0, strength: strength.clamp(0.0, 1.0), source }
+        Self {
+            direction: -1.0,
+            strength: clamp_finite(strength, 0.0, 1.0),
+            source,
+        }
     }
 
     pub fn from_score(score: f64, source: &'static str) -> Self {
+        // signum(0.0) is 1.0; a zero (or NaN) score has no direction
+        if score == 0.0 || score.is_nan() {
+            return Self {
+                source,
+                ..Self::neutral()
+            };
+        }
         Self {
             direction: score.signum(),
             strength: score.abs().min(3.0) / 3.0, // normalize to 0-1
@@ -57,8 +89,8 @@ impl Signal {
             + other.direction * other.strength * other_weight)
             / total_strength.max(0.001);
         Signal {
-            direction: weighted_dir,
-            strength: total_strength,
+            direction: clamp_finite(weighted_dir, -1.0, 1.0),
+            strength: clamp_finite(total_strength, 0.0, 1.0),
             source: "combined",
         }
     }
@@ -187,7 +219,11 @@ pub enum VolRegime {
 }
 
 pub fn volatility_regime(ind: &IndicatorSnapshot) -> VolRegime {
-    let vol_ratio = if ind.vol_mean > 0.0 { ind.vol / ind.vol_mean } else { 1.0 };
+    let vol_ratio = if ind.vol_mean > 0.0 {
+        ind.vol / ind.vol_mean
+    } else {
+        1.0
+    };
     if vol_ratio < 0.5 {
         VolRegime::Low
     } else if vol_ratio < 1.5 {
@@ -286,8 +322,13 @@ pub fn funding_extreme_signal(aux: &MarketAux, extreme_threshold: f64) -> Signal
 // =============================================================================
 
 /// Liquidation cascade: trade with the cascade momentum
-pub fn liquidation_cascade_signal(aux: &MarketAux, ind: &IndicatorSnapshot, threshold: f64) -> Signal {
-    if !aux.has_liquidations || aux.liquidation_score < threshold {
+pub fn liquidation_cascade_signal(
+    aux: &MarketAux,
+    ind: &IndicatorSnapshot,
+    threshold: f64,
+) -> Signal {
+    let score = aux.liquidation_score;
+    if !aux.has_liquidations || score.is_nan() || score < threshold {
         return Signal::neutral();
     }
 
@@ -355,11 +396,7 @@ pub fn trend_aligned_reversion(ind: &IndicatorSnapshot, stretch_th: f64) -> Sign
 }
 
 /// Multi-factor signal: combine multiple signals with weights
-pub fn multi_factor(
-    ind: &IndicatorSnapshot,
-    aux: &MarketAux,
-    weights: &SignalWeights,
-) -> Signal {
+pub fn multi_factor(ind: &IndicatorSnapshot, aux: &MarketAux, weights: &SignalWeights) -> Signal {
     let mut total_dir = 0.0;
     let mut total_weight = 0.0;
 
@@ -430,19 +467,43 @@ impl Default for SignalWeights {
 
 impl SignalWeights {
     pub fn momentum_only() -> Self {
-        Self { momentum: 1.0, trend: 0.0, reversion: 0.0, volume: 0.0, funding: 0.0 }
+        Self {
+            momentum: 1.0,
+            trend: 0.0,
+            reversion: 0.0,
+            volume: 0.0,
+            funding: 0.0,
+        }
     }
 
     pub fn trend_following() -> Self {
-        Self { momentum: 0.6, trend: 1.0, reversion: 0.0, volume: 0.3, funding: 0.0 }
+        Self {
+            momentum: 0.6,
+            trend: 1.0,
+            reversion: 0.0,
+            volume: 0.3,
+            funding: 0.0,
+        }
     }
 
     pub fn mean_reversion() -> Self {
-        Self { momentum: 0.0, trend: 0.3, reversion: 1.0, volume: 0.2, funding: 0.0 }
+        Self {
+            momentum: 0.0,
+            trend: 0.3,
+            reversion: 1.0,
+            volume: 0.2,
+            funding: 0.0,
+        }
     }
 
     pub fn carry() -> Self {
-        Self { momentum: 0.2, trend: 0.0, reversion: 0.0, volume: 0.0, funding: 1.0 }
+        Self {
+            momentum: 0.2,
+            trend: 0.0,
+            reversion: 0.0,
+            volume: 0.0,
+            funding: 1.0,
+        }
     }
 }
 
@@ -454,7 +515,13 @@ impl SignalWeights {
 mod tests {
     use super::*;
 
-    fn make_ind(z_mom: f64, z_stretch: f64, z_vol: f64, ema_fast: f64, ema_slow: f64) -> IndicatorSnapshot {
+    fn make_ind(
+        z_mom: f64,
+        z_stretch: f64,
+        z_vol: f64,
+        ema_fast: f64,
+        ema_slow: f64,
+    ) -> IndicatorSnapshot {
         IndicatorSnapshot {
             ema_fast,
             ema_slow,
@@ -504,6 +571,228 @@ mod tests {
         assert!(sig2.is_neutral());
     }
 
+    /// The `Signal` contract every builder must honour.
+    fn assert_contract(sig: Signal) {
+        assert!((0.0..=1.0).contains(&sig.strength), "{:?}", sig);
+        assert!((-1.0..=1.0).contains(&sig.direction), "{:?}", sig);
+        assert!(!(sig.is_bullish() && sig.is_bearish()), "{:?}", sig);
+    }
+
+    fn nan_ind() -> IndicatorSnapshot {
+        IndicatorSnapshot {
+            ema_fast: f64::NAN,
+            ema_slow: f64::NAN,
+            vwap: f64::NAN,
+            vol: f64::NAN,
+            vol_mean: f64::NAN,
+            z_momentum: f64::NAN,
+            z_vol: f64::NAN,
+            z_volume_spike: f64::NAN,
+            z_stretch: f64::NAN,
+            ..Default::default()
+        }
+    }
+
+    fn nan_aux() -> MarketAux {
+        MarketAux {
+            funding_rate: f64::NAN,
+            borrow_rate: f64::NAN,
+            liquidation_score: f64::NAN,
+            stable_depeg: f64::NAN,
+            has_funding: true,
+            has_borrow: true,
+            has_liquidations: true,
+            has_depeg: true,
+            ..Default::default()
+        }
+    }
+
+    /// Every signal builder evaluated on one input pair.
+    fn all_signals(ind: &IndicatorSnapshot, aux: &MarketAux) -> Vec<Signal> {
+        vec![
+            momentum_signal(ind, 0.5),
+            momentum_acceleration(ind, 0.0),
+            mean_reversion_signal(ind, 1.5),
+            bollinger_reversion(ind, 2.0),
+            trend_signal(ind),
+            vwap_signal(ind, 100.0),
+            volatility_breakout(ind, 1.0),
+            volume_spike_signal(ind, 1.0),
+            climax_signal(ind),
+            funding_carry_signal(aux, 0.0003, 0.0001),
+            funding_extreme_signal(aux, 0.001),
+            liquidation_cascade_signal(aux, ind, 2.0),
+            depeg_signal(aux, 0.005),
+            trend_aligned_momentum(ind, 0.5),
+            trend_aligned_reversion(ind, 1.5),
+            multi_factor(ind, aux, &SignalWeights::default()),
+        ]
+    }
+
+    #[test]
+    fn test_constructors_clamp_and_reject_nan() {
+        assert_eq!(Signal::bullish(5.0, "x").strength, 1.0);
+        assert_eq!(Signal::bearish(-2.0, "x").strength, 0.0);
+        assert_eq!(Signal::bullish(f64::NAN, "x").strength, 0.0);
+        assert!(Signal::from_score(0.0, "x").is_neutral());
+        assert_eq!(Signal::from_score(0.0, "x").direction, 0.0);
+        let nan = Signal::from_score(f64::NAN, "x");
+        assert_eq!((nan.direction, nan.strength), (0.0, 0.0));
+        assert_eq!(Signal::from_score(-100.0, "x").strength, 1.0);
+        // Weights outside [0, 1] can't push a combination out of range
+        let wild = Signal::bullish(1.0, "a").combine(&Signal::bullish(1.0, "b"), 3.0);
+        assert_contract(wild);
+        let nan_mix = Signal::bullish(1.0, "a").combine(&Signal::bearish(1.0, "b"), f64::NAN);
+        assert_contract(nan_mix);
+    }
+
+    #[test]
+    fn test_every_builder_is_neutral_on_zero_and_nan_inputs() {
+        for sig in all_signals(&IndicatorSnapshot::default(), &MarketAux::default()) {
+            assert_contract(sig);
+            assert!(sig.is_neutral(), "zero input: {:?}", sig);
+        }
+        for sig in all_signals(&nan_ind(), &nan_aux()) {
+            assert_contract(sig);
+            assert!(sig.is_neutral(), "NaN input: {:?}", sig);
+        }
+        assert_eq!(trend_strength(&IndicatorSnapshot::default()), 0.0);
+        assert_eq!(
+            volatility_regime(&IndicatorSnapshot::default()),
+            VolRegime::Normal
+        );
+        // No usable vol baseline reads as a normal regime
+        assert_eq!(volatility_regime(&nan_ind()), VolRegime::Normal);
+    }
+
+    #[test]
+    fn test_every_builder_clamps_extreme_inputs() {
+        let huge = make_ind(1e9, 1e9, 1e9, 1e9, 1.0);
+        let aux = MarketAux {
+            funding_rate: 1e9,
+            borrow_rate: 0.0,
+            liquidation_score: 1e9,
+            stable_depeg: 1e9,
+            has_funding: true,
+            has_borrow: true,
+            has_liquidations: true,
+            has_depeg: true,
+            ..Default::default()
+        };
+        for ind in [huge, make_ind(-1e9, -1e9, 1e9, 1.0, 1e9)] {
+            let aux = MarketAux {
+                funding_rate: aux.funding_rate * ind.z_momentum.signum(),
+                ..aux
+            };
+            for sig in all_signals(&ind, &aux) {
+                assert_contract(sig);
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_threshold_is_neutral() {
+        assert!(momentum_signal(&make_ind(0.5, 0.0, 0.0, 100.0, 100.0), 0.5).is_neutral());
+        assert!(mean_reversion_signal(&make_ind(0.0, 1.5, 0.0, 100.0, 100.0), 1.5).is_neutral());
+        assert!(bollinger_reversion(&make_ind(0.0, -2.0, 0.0, 100.0, 100.0), 2.0).is_neutral());
+        // 0.5% EMA divergence is the trend threshold
+        assert!(trend_signal(&make_ind(0.0, 0.0, 0.0, 100.5, 100.0)).is_neutral());
+        assert!(volatility_breakout(&make_ind(3.0, 0.0, 1.0, 100.0, 100.0), 1.0).is_neutral());
+        let aux = MarketAux {
+            liquidation_score: 2.0,
+            stable_depeg: -0.005,
+            funding_rate: 0.001,
+            has_liquidations: true,
+            has_depeg: true,
+            has_funding: true,
+            ..Default::default()
+        };
+        let ind = make_ind(1.0, 0.0, 0.0, 100.0, 100.0);
+        assert!(liquidation_cascade_signal(&aux, &ind, 2.0).is_neutral());
+        assert!(depeg_signal(&aux, 0.005).is_neutral());
+        assert!(funding_extreme_signal(&aux, 0.001).is_neutral());
+    }
+
+    #[test]
+    fn test_direction_flips_mirror_the_signal() {
+        let mirror = |a: Signal, b: Signal| {
+            assert_contract(a);
+            assert_contract(b);
+            assert!(a.is_bullish() && b.is_bearish(), "{:?} vs {:?}", a, b);
+            assert!((a.strength - b.strength).abs() < 1e-12);
+        };
+        let up = make_ind(2.0, -2.5, 2.0, 102.0, 100.0);
+        let down = make_ind(-2.0, 2.5, 2.0, 98.0, 100.0);
+        mirror(momentum_signal(&up, 0.5), momentum_signal(&down, 0.5));
+        mirror(
+            mean_reversion_signal(&up, 1.5),
+            mean_reversion_signal(&down, 1.5),
+        );
+        mirror(
+            bollinger_reversion(&up, 2.0),
+            bollinger_reversion(&down, 2.0),
+        );
+        mirror(
+            volatility_breakout(&up, 1.0),
+            volatility_breakout(&down, 1.0),
+        );
+        mirror(
+            trend_aligned_momentum(&up, 0.5),
+            trend_aligned_momentum(&down, 0.5),
+        );
+        mirror(
+            trend_aligned_reversion(&up, 1.5),
+            trend_aligned_reversion(&down, 1.5),
+        );
+        assert!(trend_signal(&up).is_bullish());
+        assert!(trend_signal(&down).is_bearish());
+
+        let vwap = IndicatorSnapshot {
+            vwap: 100.0,
+            ..Default::default()
+        };
+        mirror(vwap_signal(&vwap, 101.0), vwap_signal(&vwap, 99.0));
+
+        let carry = |funding_rate: f64| MarketAux {
+            funding_rate,
+            borrow_rate: 0.0001,
+            has_funding: true,
+            has_borrow: true,
+            ..Default::default()
+        };
+        mirror(
+            funding_carry_signal(&carry(-0.003), 0.0003, 0.0001),
+            funding_carry_signal(&carry(0.003), 0.0003, 0.0001),
+        );
+        mirror(
+            funding_extreme_signal(&carry(-0.01), 0.005),
+            funding_extreme_signal(&carry(0.01), 0.005),
+        );
+        let depeg = |stable_depeg: f64| MarketAux {
+            stable_depeg,
+            has_depeg: true,
+            ..Default::default()
+        };
+        mirror(
+            depeg_signal(&depeg(-0.01), 0.005),
+            depeg_signal(&depeg(0.01), 0.005),
+        );
+        let liq = MarketAux {
+            liquidation_score: 3.0,
+            has_liquidations: true,
+            ..Default::default()
+        };
+        mirror(
+            liquidation_cascade_signal(&liq, &up, 2.0),
+            liquidation_cascade_signal(&liq, &down, 2.0),
+        );
+        let weights = SignalWeights::momentum_only();
+        mirror(
+            multi_factor(&up, &MarketAux::default(), &weights),
+            multi_factor(&down, &MarketAux::default(), &weights),
+        );
+    }
+
     #[test]
     fn test_signal_combine() {
         let bull = Signal::bullish(0.8, "a");
@@ -1,236 +1,812 @@
 use std::collections::HashMap;
 
 use crate::exchange::Candle as ExCandle;
+use crate::logging::{json_log, obj, v_num, v_str};
 use crate::strategy::{
-    IndicatorSnapshot, MarketAux, MarketView, MetricsState, PortfolioState, Strategy, StrategyState,
+    next_funding_ts, ContractKind, ExitReason, IndicatorSnapshot, MarketAux, MarketView, StopMode,
+    Strategy, StrategyState, TrendRegime,
 };
 use serde::{Deserialize, Serialize};
 
+/// What periodic reconciliation does when local and venue positions disagree.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconcileAction {
+    /// Log drift beyond the threshold, keep trading.
+    LogOnly,
+    /// Halt every strategy when drift exceeds the threshold.
+    #[default]
+    Halt,
+    /// Snap local positions to the venue while drift is within the threshold;
+    /// halt beyond it.
+    AutoCorrect,
+}
+
+impl ReconcileAction {
+    /// Parse from env-style strings ("log", "halt", "auto_correct"); None otherwise.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "log" | "log_only" | "logonly" => Some(ReconcileAction::LogOnly),
+            "halt" => Some(ReconcileAction::Halt),
+            "auto" | "auto_correct" | "autocorrect" => Some(ReconcileAction::AutoCorrect),
+            _ => None,
+        }
+    }
+}
+
+/// How the risk layer sizes entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizingMode {
+    /// Trade whatever quantity the strategy asks for.
+    #[default]
+    Fixed,
+    /// Size so the position's estimated CVaR stays within
+    /// `cvar_budget_pct` of equity.
+    CvarBudget,
+    /// Fractional Kelly from the strategy's recent win rate and payoff
+    /// ratio, capped at `max_position_pct` of equity.
+    Kelly,
+}
+
+impl SizingMode {
+    /// Parse from env-style strings ("fixed", "cvar_budget", "kelly"); None otherwise.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "fixed" => Some(SizingMode::Fixed),
+            "cvar" | "cvar_budget" | "cvarbudget" => Some(SizingMode::CvarBudget),
+            "kelly" => Some(SizingMode::Kelly),
+            _ => None,
+        }
+    }
+}
+
+/// Scale `MarketAux.liquidation_score` is read on, and so the scale
+/// `liq_score_th` is set in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiqScoreSource {
+    /// The venue-specific score as computed from its liquidation feed.
+    #[default]
+    Raw,
+    /// Z-score of the raw score against its own rolling baseline, so the
+    /// threshold means the same on every venue.
+    Normalized,
+}
+
+impl LiqScoreSource {
+    /// Parse from env-style strings ("raw", "normalized"); None otherwise.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "raw" => Some(LiqScoreSource::Raw),
+            "normalized" | "normalised" | "z" | "zscore" | "computed" => {
+                Some(LiqScoreSource::Normalized)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// What the traded instrument is. Perpetuals settle funding on open
+/// positions; spot holdings never pay or receive it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketType {
+    #[default]
+    Perp,
+    Spot,
+}
+
+impl MarketType {
+    /// Parse from env-style strings ("perp", "spot"); None otherwise.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "perp" | "perpetual" | "futures" => Some(MarketType::Perp),
+            "spot" => Some(MarketType::Spot),
+            _ => None,
+        }
+    }
+}
+
+/// How position size shrinks as drawdown approaches the kill limit. With
+/// `x` the current drawdown as a fraction of the limit, the multiplier on
+/// new entries is:
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleverCurve {
+    /// 1 until the kill switch fires.
+    #[default]
+    Off,
+    /// `1 - x`
+    Linear,
+    /// `(1 - x)^2`: cuts hard early, little left to cut near the limit.
+    Convex,
+    /// `1 - x^2`: keeps most of the size until the limit is close.
+    Concave,
+}
+
+impl DeleverCurve {
+    /// Parse from env-style strings ("off", "linear", "convex", "concave"); None otherwise.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "off" => Some(DeleverCurve::Off),
+            "linear" => Some(DeleverCurve::Linear),
+            "convex" => Some(DeleverCurve::Convex),
+            "concave" => Some(DeleverCurve::Concave),
+            _ => None,
+        }
+    }
+
+    /// Multiplier at `x` = drawdown / limit, clamped to [0, 1].
+    pub fn multiplier(self, x: f64) -> f64 {
+        let x = x.clamp(0.0, 1.0);
+        match self {
+            DeleverCurve::Off => 1.0,
+            DeleverCurve::Linear => 1.0 - x,
+            DeleverCurve::Convex => (1.0 - x).powi(2),
+            DeleverCurve::Concave => 1.0 - x * x,
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Primary symbol (first entry of `symbols`)
     pub symbol: String,
+    /// All symbols traded by the live loop; strategies are instantiated per symbol
+    pub symbols: Vec<String>,
+    /// Candle length in seconds, as venue candle endpoints take it.
     pub candle_granularity: u64,
+    /// Candle length in milliseconds. The loop clock, hold/time-stop and
+    /// staleness budgets use this, so sub-second venues (e.g. 100ms) work.
+    pub candle_granularity_ms: u64,
     pub window: usize,
     pub api_key: Option<String>,
     pub api_secret: Option<String>,
+    /// Route every venue to its sandbox (USE_TESTNET); see `TESTNET_HOSTS`.
+    pub use_testnet: bool,
     pub binance_base: String,
     pub binance_fapi_base: String,
+    /// Websocket host for the user-data and public trade streams.
+    pub binance_ws_base: String,
+    /// Empty under `use_testnet`: Kraken has no spot sandbox.
     pub kraken_base: String,
+    /// JSON contract specs (exchangeInfo body or symbol -> filters map);
+    /// empty fetches them from the venue at startup.
+    pub contract_spec_file: String,
     pub sqlite_path: String,
     pub persist_every_secs: u64,
+    /// Restore each strategy's equity high-water mark on restart, so drawdown
+    /// keeps measuring from the pre-restart peak.
+    pub persist_hwm: bool,
     pub max_position_pct: f64,
+    /// Cash each strategy instance starts with, in the settlement currency.
+    pub starting_capital: f64,
     pub max_daily_loss_pct: f64,
+    /// Drawdown kill switch at `max_daily_loss_pct * max_drawdown_mult`.
+    pub max_drawdown_mult: f64,
+    /// Shrink entries along this curve as drawdown from the equity peak
+    /// nears the kill limit.
+    pub delever_curve: DeleverCurve,
+    /// Trades in the rolling profit-factor window (0 disables the gate).
+    pub pf_disable_trades: usize,
+    /// Disable a strategy whose rolling profit factor falls below this.
+    pub pf_disable_min: f64,
+    /// Absolute ceiling on a single order's notional, in the settlement
+    /// currency, checked after every other guard (0 disables).
+    pub max_order_notional: f64,
+    /// Notional of accumulated lot-step rounding residual that triggers a
+    /// `rounding_drift` log (0 = disabled).
+    pub rounding_drift_notional: f64,
     pub max_trades_per_day: u32,
+    /// Shrink entries to fit `max_position_pct` of current equity instead of
+    /// letting them overshoot the exposure cap.
+    pub clamp_position_qty: bool,
+    pub sizing_mode: SizingMode,
+    /// Per-bar CVaR budget as a fraction of equity under `CvarBudget` sizing.
+    pub cvar_budget_pct: f64,
+    /// Multiplier on the full Kelly fraction under `Kelly` sizing.
+    pub kelly_fraction: f64,
+    /// Newest round trips the Kelly estimate is taken over; entries keep the
+    /// strategy's own quantity until this many have closed.
+    pub kelly_window: usize,
+    /// Maximum orders per strategy in any rolling hour (0 = disabled)
+    pub max_trades_per_hour: u32,
+    /// Minimum seconds between orders on one symbol, across all strategies
+    /// trading it (0 = disabled). Closes are exempt.
+    pub min_order_interval_secs: u64,
+    /// Consecutive bars drift must stay at halt level before trading halts
+    /// (1 = halt on the first severe bar).
+    pub drift_halt_confirm_bars: u32,
     pub cooldown_secs: u64,
+    /// Build positions in up to this many entry tranches while the entry
+    /// signal persists, bounded by `max_position_pct` (1 = single entry).
+    pub scale_in_tranches: u32,
+    /// Fraction of the open position to sell off when its signal weakens
+    /// below the entry threshold without reversing (0 = never scale out).
+    pub scale_out_fraction: f64,
+    /// Loss (fraction of equity) that earns exactly one `cooldown_secs`;
+    /// larger losses scale the cooldown up proportionally (0 = fixed cooldown).
+    pub cooldown_ref_loss_pct: f64,
+    /// Upper bound on the loss-scaled cooldown.
+    pub cooldown_max_secs: u64,
+    /// Clear a symbol's loss cooldowns when its trend regime changes, so a
+    /// loss taken in the old regime doesn't block entries in the new one.
+    pub reset_cooldown_on_regime: bool,
     pub vol_pause_mult: f64,
     pub entry_threshold: f64,
     pub exit_threshold: f64,
+    /// EMA weight on the newest composite score (1 = no smoothing).
+    pub score_smoothing_alpha: f64,
+    /// Hysteresis around the score thresholds: enter above
+    /// `entry_threshold + band`, exit below `exit_threshold - band`.
+    pub threshold_band: f64,
     pub breakout_threshold: f64,
     pub edge_hurdle: f64,
     pub edge_scale: f64,
+    /// Assumed fee per side (bps) subtracted from expected edge
+    pub edge_fee_bps: f64,
+    /// Assumed slippage per unit of relative volatility, per side
+    pub edge_slip_vol_mult: f64,
     pub ema_fast: u32,
     pub ema_slow: u32,
     pub vol_window: u32,
     pub volume_window: u32,
     pub take_profit: f64,
     pub stop_loss: f64,
+    /// `take_profit` as a fixed fraction or an ATR multiple (env TAKE_PROFIT_ATR)
+    pub take_profit_mode: StopMode,
+    /// `stop_loss` as a fixed fraction or an ATR multiple (env STOP_LOSS_ATR)
+    pub stop_loss_mode: StopMode,
     pub time_stop: u32,
+    /// Force-close any position held this long, whatever the strategy says
+    /// (0 = disabled). A safety net under the per-strategy `time_stop`.
+    pub global_max_hold_secs: u64,
     pub funding_high: f64,
     pub funding_spread: f64,
     pub liq_score_th: f64,
+    pub liq_score_source: LiqScoreSource,
+    /// Bars of raw liquidation score the normalized score is measured against.
+    pub liq_baseline_bars: usize,
     pub depeg_th: f64,
     pub vol_low: f64,
     pub vol_high: f64,
     pub mom_th: f64,
     pub stretch_th: f64,
     pub kill_file: String,
+    /// Operator trigger to re-enable profit-factor-disabled strategies: one
+    /// strategy id per line, or empty for all. Consumed when read.
+    pub reenable_file: String,
+    /// JSON object of `RELOADABLE_THRESHOLDS` values, re-read whenever its
+    /// mtime changes (empty = hot reload off).
+    pub threshold_file: String,
     pub wal_path: String,
     pub reconcile_secs: u64,
+    /// Operator trigger: when this file exists the loop reconciles right
+    /// away, ignoring `reconcile_secs`, and removes it.
+    pub reconcile_now_file: String,
     pub cancel_after_candles: u64,
     pub reconcile_drift_pct: f64,
     pub reconcile_drift_abs: f64,
+    pub reconcile_action: ReconcileAction,
     pub max_fill_slip_pct: f64,
     pub fill_channel_capacity: usize,
+    /// Orders sent to the venue concurrently per loop iteration
+    pub max_orders_in_flight: usize,
     pub allow_unknown_regime: bool,
+    /// Build candles from the public trade stream (`CANDLE_SOURCE=trades`)
+    /// instead of polling the exchange candle endpoint.
+    pub trade_candles: bool,
+    /// Seed indicators from recent exchange history at startup so live
+    /// strategies are eligible on the first bar instead of after warmup.
+    pub warmup_prefill: bool,
+    /// Root seed for every randomized subsystem (latency jitter, chaos,
+    /// scrambling); a run is reproducible from this one number.
+    pub seed: u64,
     pub max_latency_ms: u64,
+    /// Maximum quoted bid/ask spread (fraction of mid) before new orders are
+    /// skipped; also the tolerated divergence from `reference_exchange`.
     pub max_liquidity_spread: f64,
+    /// Patient entries post a post-only limit this many bps inside the close
+    /// instead of crossing the spread (0 = always market live). Urgent exits
+    /// still go out at market.
+    pub maker_offset_bps: f64,
+    /// Secondary venue (`binance`/`kraken`) whose price must agree with the
+    /// primary within `max_liquidity_spread` before trading; None disables.
+    pub reference_exchange: Option<String>,
     /// Minimum candles to hold a position before allowing exit (reduces overtrading)
     pub min_hold_candles: u32,
+    /// Decay on history for indicator z-score stats, in (0, 1); 0 = cumulative Welford
+    pub zscore_decay: f64,
+    /// Settlement model for the traded contract (linear USDT vs inverse coin-margined)
+    pub contract_kind: ContractKind,
+    /// Seconds between funding settlements (8h on Binance perps); carry is
+    /// credited only when a settlement boundary passes. 0 = no funding.
+    pub funding_interval_secs: u64,
+    /// Perpetuals settle funding locally; spot positions never do. Defaults
+    /// to spot when API keys are set, since live orders go to the spot
+    /// order endpoint.
+    pub market_type: MarketType,
+    /// Paper execution: base slippage fraction before volatility/size adjustment
+    pub paper_slippage: f64,
+    /// Paper execution: taker fee rate on fill notional
+    pub paper_fee_rate: f64,
+    /// Paper execution: slippage growth per unit of order notional / bar volume notional
+    pub impact_coeff: f64,
+    /// Bind address for the Prometheus `/metrics` endpoint (`prometheus` feature)
+    pub metrics_addr: String,
+    /// Bind address for the `/health` endpoint (`health` feature)
+    pub health_addr: String,
+    /// Run one `ConfluenceStrategy` per symbol next to the default set
+    pub confluence_strategy: bool,
+    /// Confluence strategy: aligned signals required before entering
+    pub min_confluence: u32,
+    /// Closes per symbol used to classify the trend regime at runtime
+    pub regime_lookback: usize,
+    /// Strategy id prefix -> regimes it may trade in (REGIME_TAGS)
+    pub regime_tags: Vec<(String, Vec<TrendRegime>)>,
+    /// Operator-disabled strategy ids (DISABLED_STRATEGIES); these open
+    /// nothing new but still exit an open position, and their state is
+    /// still persisted.
+    pub disabled_strategies: Vec<String>,
+    /// Observe-only strategy ids (SHADOW_STRATEGIES); these trade a virtual
+    /// portfolio at paper fill prices and never place real orders.
+    pub shadow_strategies: Vec<String>,
+    /// Log every strategy's `MarketView` and resulting action as a
+    /// `market_view` record (DUMP_VIEWS), for reproducing a decision.
+    pub dump_views: bool,
+}
+
+/// Default REST/websocket hosts per venue.
+struct VenueHosts {
+    binance: &'static str,
+    binance_fapi: &'static str,
+    binance_ws: &'static str,
+    kraken: &'static str,
+}
+
+const MAINNET_HOSTS: VenueHosts = VenueHosts {
+    binance: "https://api.binance.com",
+    binance_fapi: "https://fapi.binance.com",
+    binance_ws: "wss://stream.binance.com:9443",
+    kraken: "https://api.kraken.com",
+};
+
+/// Sandbox hosts selected by USE_TESTNET. Binance signs testnet requests
+/// exactly like mainnet, only the hosts (and testnet API keys) differ. Kraken
+/// offers no spot sandbox, so its base is left empty and building the Kraken
+/// client fails unless KRAKEN_BASE is set explicitly.
+const TESTNET_HOSTS: VenueHosts = VenueHosts {
+    binance: "https://testnet.binance.vision",
+    binance_fapi: "https://testnet.binancefuture.com",
+    binance_ws: "wss://stream.testnet.binance.vision",
+    kraken: "",
+};
+
+/// Signal thresholds a running bot may retune via `threshold_file`.
+/// Everything else (symbols, paths, sizing, risk limits) needs a restart.
+pub const RELOADABLE_THRESHOLDS: &[&str] = &[
+    "entry_threshold",
+    "exit_threshold",
+    "threshold_band",
+    "edge_hurdle",
+    "edge_scale",
+    "stop_loss",
+    "take_profit",
+    "mom_th",
+    "stretch_th",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThresholdChange {
+    pub field: &'static str,
+    pub old: f64,
+    pub new: f64,
+}
+
+/// Config values looked up by env-var name, recording ones that are set
+/// but don't parse so strict loading can reject them.
+struct EnvReader<F: Fn(&str) -> Option<String>> {
+    get: F,
+    errors: Vec<String>,
+}
+
+impl<F: Fn(&str) -> Option<String>> EnvReader<F> {
+    fn raw(&self, key: &str) -> Option<String> {
+        (self.get)(key)
+    }
+
+    fn opt<T: std::str::FromStr>(&mut self, key: &str) -> Option<T> {
+        let raw = (self.get)(key)?;
+        match raw.parse() {
+            Ok(v) => Some(v),
+            Err(_) => {
+                self.errors
+                    .push(format!("{}={:?} does not parse as a number", key, raw));
+                None
+            }
+        }
+    }
+
+    fn num<T: std::str::FromStr>(&mut self, key: &str, default: T) -> T {
+        self.opt(key).unwrap_or(default)
+    }
+
+    /// Parse `key` with `parse`; a set value it doesn't recognise is an
+    /// error and reads as unset.
+    fn choice<T>(&mut self, key: &str, parse: impl Fn(&str) -> Option<T>) -> Option<T> {
+        let raw = (self.get)(key)?;
+        let value = parse(&raw);
+        if value.is_none() {
+            self.errors
+                .push(format!("{}={:?} is not a recognised value", key, raw));
+        }
+        value
+    }
+
+    /// On/off switch: 1/true/yes/on or 0/false/no/off, any case.
+    fn flag(&mut self, key: &str, default: bool) -> bool {
+        self.choice(key, |v| match v.trim().to_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => Some(true),
+            "0" | "false" | "no" | "off" => Some(false),
+            _ => None,
+        })
+        .unwrap_or(default)
+    }
 }
 
 impl Config {
+    /// Load from the environment, falling back to defaults for anything
+    /// missing or malformed. Tools and tests use this; the live bot uses
+    /// `from_env_checked`.
     pub fn from_env() -> Self {
+        Self::load(|key| std::env::var(key).ok()).0
+    }
+
+    /// Load from the environment and `validate`, failing on any set value
+    /// that doesn't parse instead of quietly using its default.
+    pub fn from_env_checked() -> Result<Self, String> {
+        Self::from_lookup_checked(|key| std::env::var(key).ok())
+    }
+
+    fn from_lookup_checked(get: impl Fn(&str) -> Option<String>) -> Result<Self, String> {
+        let (cfg, errors) = Self::load(get);
+        if !errors.is_empty() {
+            return Err(format!("invalid config: {}", errors.join("; ")));
+        }
+        cfg.validate()?;
+        Ok(cfg)
+    }
+
+    /// Range-check settings that would otherwise misbehave at runtime
+    /// (zero windows, fractions outside [0, 1], crossed EMA or vol bands).
+    /// Every problem is reported, not just the first.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        let fractions = [
+            ("MAX_POS_PCT", self.max_position_pct),
+            ("MAX_DAILY_LOSS_PCT", self.max_daily_loss_pct),
+            ("CVAR_BUDGET_PCT", self.cvar_budget_pct),
+            ("KELLY_FRACTION", self.kelly_fraction),
+            ("SCALE_OUT_FRACTION", self.scale_out_fraction),
+            ("SCORE_SMOOTHING_ALPHA", self.score_smoothing_alpha),
+            ("COOLDOWN_REF_LOSS_PCT", self.cooldown_ref_loss_pct),
+            ("TAKE_PROFIT", self.take_profit),
+            ("STOP_LOSS", self.stop_loss),
+            ("RECONCILE_DRIFT_PCT", self.reconcile_drift_pct),
+            ("MAX_FILL_SLIP_PCT", self.max_fill_slip_pct),
+            ("MAX_LIQ_SPREAD", self.max_liquidity_spread),
+            ("PAPER_SLIPPAGE", self.paper_slippage),
+            ("PAPER_FEE_RATE", self.paper_fee_rate),
+            ("ZSCORE_DECAY", self.zscore_decay),
+        ];
+        for (name, v) in fractions {
+            if !(0.0..=1.0).contains(&v) {
+                errors.push(format!("{} must be in [0, 1], got {}", name, v));
+            }
+        }
+        let counts = [
+            ("WINDOW", self.window as u64),
+            ("CANDLE_MS", self.candle_granularity_ms),
+            ("PERSIST_SECS", self.persist_every_secs),
+            ("EMA_FAST", self.ema_fast as u64),
+            ("EMA_SLOW", self.ema_slow as u64),
+            ("VOL_WINDOW", self.vol_window as u64),
+            ("VOL_MEAN_WINDOW", self.volume_window as u64),
+            ("SCALE_IN_TRANCHES", self.scale_in_tranches as u64),
+            ("FILL_CHANNEL_CAP", self.fill_channel_capacity as u64),
+            ("MAX_ORDERS_IN_FLIGHT", self.max_orders_in_flight as u64),
+            ("REGIME_LOOKBACK", self.regime_lookback as u64),
+            (
+                "DRIFT_HALT_CONFIRM_BARS",
+                self.drift_halt_confirm_bars as u64,
+            ),
+        ];
+        for (name, v) in counts {
+            if v == 0 {
+                errors.push(format!("{} must be positive", name));
+            }
+        }
+        if self.ema_fast >= self.ema_slow {
+            errors.push(format!(
+                "EMA_FAST ({}) must be shorter than EMA_SLOW ({})",
+                self.ema_fast, self.ema_slow
+            ));
+        }
+        if self.vol_low >= self.vol_high {
+            errors.push(format!(
+                "VOL_LOW ({}) must be below VOL_HIGH ({})",
+                self.vol_low, self.vol_high
+            ));
+        }
+        if self.score_smoothing_alpha <= 0.0 {
+            errors.push(format!(
+                "SCORE_SMOOTHING_ALPHA must be in (0, 1], got {}",
+                self.score_smoothing_alpha
+            ));
+        }
+        if self.maker_offset_bps < 0.0 {
+            errors.push(format!(
+                "MAKER_OFFSET_BPS must be non-negative, got {}",
+                self.maker_offset_bps
+            ));
+        }
+        if self.threshold_band < 0.0 {
+            errors.push(format!(
+                "THRESHOLD_BAND must be non-negative, got {}",
+                self.threshold_band
+            ));
+        }
+        // A window shorter than the warm-up would never produce a score
+        if self.liq_baseline_bars < crate::feed::aux_data::LIQ_BASELINE_MIN {
+            errors.push(format!(
+                "LIQ_BASELINE_BARS must be at least {}, got {}",
+                crate::feed::aux_data::LIQ_BASELINE_MIN,
+                self.liq_baseline_bars
+            ));
+        }
+        if self.max_drawdown_mult <= 0.0 {
+            errors.push(format!(
+                "MAX_DRAWDOWN_MULT must be positive, got {}",
+                self.max_drawdown_mult
+            ));
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("invalid config: {}", errors.join("; ")))
+        }
+    }
+
+    fn load(get: impl Fn(&str) -> Option<String>) -> (Self, Vec<String>) {
+        let mut env = EnvReader {
+            get,
+            errors: Vec::new(),
+        };
+        let cfg = Self::read(&mut env);
+        (cfg, env.errors)
+    }
+
+    fn read<F: Fn(&str) -> Option<String>>(env: &mut EnvReader<F>) -> Self {
+        let symbol = env.raw("SYMBOL").unwrap_or_else(|| "BTCUSDT".to_string());
+        let symbols = parse_symbols(env.raw("SYMBOLS").as_deref(), &symbol);
+        // CANDLE_MS wins for sub-second venues; otherwise it follows CANDLE_SECS
+        let candle_granularity_ms: Option<u64> = env.opt("CANDLE_MS").filter(|&ms| ms > 0);
+        let candle_granularity = env
+            .opt("CANDLE_SECS")
+            .unwrap_or_else(|| candle_granularity_ms.map_or(300, |ms| ms.div_ceil(1000)));
+        // Explicit *_BASE overrides still win over the testnet defaults
+        let use_testnet = env.flag("USE_TESTNET", false);
+        let hosts = if use_testnet {
+            &TESTNET_HOSTS
+        } else {
+            &MAINNET_HOSTS
+        };
+        // The edge filter prices the same fee and volatility slippage that
+        // fills are charged (PAPER_FEE_RATE live, the backtest's VOL_SLIP_MULT)
+        let paper_fee_rate = env.num("PAPER_FEE_RATE", 0.001);
+        let vol_slip_mult = env.num("VOL_SLIP_MULT", 2.0);
         Self {
-            symbol: std::env::var("SYMBOL").unwrap_or_else(|_| "BTCUSDT".to_string()),
-            candle_granularity: std::env::var("CANDLE_SECS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(300),
-            window: std::env::var("WINDOW")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(500),
-            api_key: std::env::var("API_KEY").ok(),
-            api_secret: std::env::var("API_SECRET").ok(),
-            binance_base: std::env::var("BINANCE_BASE")
-                .unwrap_or_else(|_| "https://api.binance.com".to_string()),
-            binance_fapi_base: std::env::var("BINANCE_FAPI_BASE")
-                .unwrap_or_else(|_| "https://fapi.binance.com".to_string()),
-            kraken_base: std::env::var("KRAKEN_BASE")
-                .unwrap_or_else(|_| "https://api.kraken.com".to_string()),
-            sqlite_path: std::env::var("SQLITE_PATH")
-                .unwrap_or_else(|_| "./bot.sqlite".to_string()),
-            persist_every_secs: std::env::var("PERSIST_SECS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(300),
-            max_position_pct: std::env::var("MAX_POS_PCT")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.05),
-            max_daily_loss_pct: std::env::var("MAX_DAILY_LOSS_PCT")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.02),
-            max_trades_per_day: std::env::var("MAX_TRADES_DAY")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(20),
-            cooldown_secs: std::env::var("COOLDOWN_SECS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(600),
-            vol_pause_mult: std::env::var("VOL_PAUSE_MULT")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(2.5),
-            entry_threshold: std::env::var("ENTRY_TH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(1.2),
-            exit_threshold: std::env::var("EXIT_TH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.4),
-            breakout_threshold: std::env::var("BREAKOUT_TH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(2.0),
-            edge_hurdle: std::env::var("EDGE_HURDLE")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.003),
-            edge_scale: std::env::var("EDGE_SCALE")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.0025),
-            ema_fast: std::env::var("EMA_FAST")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(6),
-            ema_slow: std::env::var("EMA_SLOW")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(24),
-            vol_window: std::env::var("VOL_WINDOW")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(30),
-            volume_window: std::env::var("VOL_MEAN_WINDOW")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(30),
-            take_profit: std::env::var("TAKE_PROFIT")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.006),
-            stop_loss: std::env::var("STOP_LOSS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.004),
-            time_stop: std::env::var("TIME_STOP")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(12),
-            funding_high: std::env::var("FUNDING_HIGH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.0001),
-            funding_spread: std::env::var("FUNDING_SPREAD")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.00005),
-            liq_score_th: std::env::var("LIQ_SCORE_TH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(3.0),
-            depeg_th: std::env::var("DEPEG_TH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.002),
-            vol_low: std::env::var("VOL_LOW")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.6),
-            vol_high: std::env::var("VOL_HIGH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(1.6),
-            mom_th: std::env::var("MOM_TH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.4),
-            stretch_th: std::env::var("STRETCH_TH")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.8),
-            kill_file: std::env::var("KILL_FILE").unwrap_or_else(|_| "/tmp/STOP".to_string()),
-            wal_path: std::env::var("WAL_PATH").unwrap_or_else(|_| "./bot.wal".to_string()),
-            reconcile_secs: std::env::var("RECONCILE_SECS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(60),
-            cancel_after_candles: std::env::var("CANCEL_AFTER_CANDLES")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(3),
-            reconcile_drift_pct: std::env::var("RECONCILE_DRIFT_PCT")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.02),
-            reconcile_drift_abs: std::env::var("RECONCILE_DRIFT_ABS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.0005),
-            max_fill_slip_pct: std::env::var("MAX_FILL_SLIP_PCT")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.02),
-            fill_channel_capacity: std::env::var("FILL_CHANNEL_CAP")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(256),
-            allow_unknown_regime: std::env::var("ALLOW_UNKNOWN_REGIME")
-                .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true" | "yes"))
+            symbol: symbols[0].clone(),
+            symbols,
+            candle_granularity,
+            candle_granularity_ms: candle_granularity_ms.unwrap_or(candle_granularity * 1000),
+            window: env.num("WINDOW", 500),
+            api_key: env.raw("API_KEY"),
+            api_secret: env.raw("API_SECRET"),
+            use_testnet,
+            binance_base: env
+                .raw("BINANCE_BASE")
+                .unwrap_or_else(|| hosts.binance.to_string()),
+            binance_fapi_base: env
+                .raw("BINANCE_FAPI_BASE")
+                .unwrap_or_else(|| hosts.binance_fapi.to_string()),
+            binance_ws_base: env
+                .raw("BINANCE_WS_BASE")
+                .unwrap_or_else(|| hosts.binance_ws.to_string()),
+            kraken_base: env
+                .raw("KRAKEN_BASE")
+                .unwrap_or_else(|| hosts.kraken.to_string()),
+            contract_spec_file: env.raw("CONTRACT_SPEC_FILE").unwrap_or_default(),
+            sqlite_path: env
+                .raw("SQLITE_PATH")
+                .unwrap_or_else(|| "./bot.sqlite".to_string()),
+            persist_every_secs: env.num("PERSIST_SECS", 300),
+            persist_hwm: env.flag("PERSIST_HWM", true),
+            max_position_pct: env.num("MAX_POS_PCT", 0.05),
+            starting_capital: env
+                .opt("STARTING_CAPITAL")
+                .filter(|c: &f64| *c > 0.0)
+                .unwrap_or(1000.0),
+            max_daily_loss_pct: env.num("MAX_DAILY_LOSS_PCT", 0.02),
+            max_drawdown_mult: env.num("MAX_DRAWDOWN_MULT", 2.0),
+            delever_curve: env
+                .choice("DELEVER_CURVE", DeleverCurve::parse)
+                .unwrap_or_default(),
+            pf_disable_trades: env.num("PF_DISABLE_TRADES", 0),
+            pf_disable_min: env.num("PF_DISABLE_MIN", 1.0),
+            max_order_notional: env.num("MAX_ORDER_NOTIONAL", 0.0),
+            rounding_drift_notional: env.num("ROUNDING_DRIFT_NOTIONAL", 5.0),
+            max_trades_per_day: env.num("MAX_TRADES_DAY", 20),
+            clamp_position_qty: env.flag("CLAMP_POSITION_QTY", true),
+            sizing_mode: env
+                .choice("SIZING_MODE", SizingMode::parse)
+                .unwrap_or_default(),
+            cvar_budget_pct: env.num("CVAR_BUDGET_PCT", 0.002),
+            kelly_fraction: env.num("KELLY_FRACTION", 0.25),
+            kelly_window: env.num("KELLY_WINDOW", 30),
+            max_trades_per_hour: env.num("MAX_TRADES_HOUR", 6),
+            min_order_interval_secs: env.num("MIN_ORDER_INTERVAL_SECS", 0),
+            drift_halt_confirm_bars: env.num("DRIFT_HALT_CONFIRM_BARS", 1),
+            cooldown_secs: env.num("COOLDOWN_SECS", 600),
+            scale_in_tranches: env.num("SCALE_IN_TRANCHES", 1),
+            scale_out_fraction: env.num("SCALE_OUT_FRACTION", 0.0),
+            cooldown_ref_loss_pct: env.num("COOLDOWN_REF_LOSS_PCT", 0.005),
+            cooldown_max_secs: env.num("COOLDOWN_MAX_SECS", 3600),
+            reset_cooldown_on_regime: env.flag("RESET_COOLDOWN_ON_REGIME", false),
+            vol_pause_mult: env.num("VOL_PAUSE_MULT", 2.5),
+            entry_threshold: env.num("ENTRY_TH", 1.2),
+            exit_threshold: env.num("EXIT_TH", 0.4),
+            score_smoothing_alpha: env.num("SCORE_SMOOTHING_ALPHA", 1.0),
+            threshold_band: env.num("THRESHOLD_BAND", 0.0),
+            breakout_threshold: env.num("BREAKOUT_TH", 2.0),
+            edge_hurdle: env.num("EDGE_HURDLE", 0.003),
+            edge_scale: env.num("EDGE_SCALE", 0.0025),
+            edge_fee_bps: env.num("EDGE_FEE_BPS", paper_fee_rate * 10_000.0),
+            edge_slip_vol_mult: env.num("EDGE_SLIP_VOL_MULT", vol_slip_mult),
+            ema_fast: env.num("EMA_FAST", 6),
+            ema_slow: env.num("EMA_SLOW", 24),
+            vol_window: env.num("VOL_WINDOW", 30),
+            volume_window: env.num("VOL_MEAN_WINDOW", 30),
+            take_profit: env.num("TAKE_PROFIT", 0.006),
+            stop_loss: env.num("STOP_LOSS", 0.004),
+            take_profit_mode: StopMode::from_multiple(env.raw("TAKE_PROFIT_ATR").as_deref()),
+            stop_loss_mode: StopMode::from_multiple(env.raw("STOP_LOSS_ATR").as_deref()),
+            time_stop: env.num("TIME_STOP", 12),
+            global_max_hold_secs: env.num("GLOBAL_MAX_HOLD_SECS", 0),
+            funding_high: env.num("FUNDING_HIGH", 0.0001),
+            funding_spread: env.num("FUNDING_SPREAD", 0.00005),
+            liq_score_th: env.num("LIQ_SCORE_TH", 3.0),
+            liq_score_source: env
+                .choice("LIQ_SCORE_SOURCE", LiqScoreSource::parse)
+                .unwrap_or_default(),
+            liq_baseline_bars: env.num("LIQ_BASELINE_BARS", 288),
+            depeg_th: env.num("DEPEG_TH", 0.002),
+            vol_low: env.num("VOL_LOW", 0.6),
+            vol_high: env.num("VOL_HIGH", 1.6),
+            mom_th: env.num("MOM_TH", 0.4),
+            stretch_th: env.num("STRETCH_TH", 0.8),
+            kill_file: env
+                .raw("KILL_FILE")
+                .unwrap_or_else(|| "/tmp/STOP".to_string()),
+            reenable_file: env
+                .raw("REENABLE_FILE")
+                .unwrap_or_else(|| "/tmp/REENABLE".to_string()),
+            threshold_file: env.raw("THRESHOLD_FILE").unwrap_or_default(),
+            wal_path: env
+                .raw("WAL_PATH")
+                .unwrap_or_else(|| "./bot.wal".to_string()),
+            reconcile_secs: env.num("RECONCILE_SECS", 60),
+            reconcile_now_file: env
+                .raw("RECONCILE_NOW_FILE")
+                .unwrap_or_else(|| "/tmp/RECONCILE".to_string()),
+            cancel_after_candles: env.num("CANCEL_AFTER_CANDLES", 3),
+            reconcile_drift_pct: env.num("RECONCILE_DRIFT_PCT", 0.02),
+            reconcile_drift_abs: env.num("RECONCILE_DRIFT_ABS", 0.0005),
+            reconcile_action: env
+                .choice("RECONCILE_ACTION", ReconcileAction::parse)
+                .unwrap_or_default(),
+            max_fill_slip_pct: env.num("MAX_FILL_SLIP_PCT", 0.02),
+            fill_channel_capacity: env.num("FILL_CHANNEL_CAP", 256),
+            max_orders_in_flight: env.num("MAX_ORDERS_IN_FLIGHT", 4),
+            allow_unknown_regime: env.flag("ALLOW_UNKNOWN_REGIME", false),
+            trade_candles: env
+                .choice("CANDLE_SOURCE", |v| match v.to_lowercase().as_str() {
+                    "trades" => Some(true),
+                    "exchange" | "klines" | "candles" => Some(false),
+                    _ => None,
+                })
                 .unwrap_or(false),
-            max_latency_ms: std::env::var("MAX_LATENCY_MS")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(300000),
-            max_liquidity_spread: std::env::var("MAX_LIQ_SPREAD")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0.05),
-            min_hold_candles: std::env::var("MIN_HOLD_CANDLES")
-                .ok()
-                .and_then(|v| v.parse().ok())
-                .unwrap_or(0),
+            warmup_prefill: env.flag("WARMUP_PREFILL", true),
+            seed: env.num("SEED", 0),
+            max_latency_ms: env.num("MAX_LATENCY_MS", 300000),
+            max_liquidity_spread: env.num("MAX_LIQ_SPREAD", 0.05),
+            maker_offset_bps: env.num("MAKER_OFFSET_BPS", 0.0),
+            reference_exchange: env.raw("REFERENCE_EXCHANGE").filter(|v| !v.is_empty()),
+            min_hold_candles: env.num("MIN_HOLD_CANDLES", 0),
+            zscore_decay: env.num("ZSCORE_DECAY", 0.0),
+            contract_kind: env
+                .choice("CONTRACT_KIND", ContractKind::parse)
+                .unwrap_or_default(),
+            funding_interval_secs: env.num("FUNDING_INTERVAL_SECS", 28_800),
+            market_type: env.choice("MARKET_TYPE", MarketType::parse).unwrap_or(
+                if env.raw("API_KEY").is_some() && env.raw("API_SECRET").is_some() {
+                    MarketType::Spot
+                } else {
+                    MarketType::Perp
+                },
+            ),
+            paper_slippage: env.num("PAPER_SLIPPAGE", 0.0005),
+            paper_fee_rate,
+            impact_coeff: env.num("IMPACT_COEFF", 1.0),
+            metrics_addr: env
+                .raw("METRICS_ADDR")
+                .unwrap_or_else(|| "127.0.0.1:9184".to_string()),
+            health_addr: env
+                .raw("HEALTH_ADDR")
+                .unwrap_or_else(|| "127.0.0.1:9185".to_string()),
+            confluence_strategy: env.flag("CONFLUENCE_STRATEGY", false),
+            min_confluence: env.num("MIN_CONFLUENCE", 3),
+            regime_lookback: env.num("REGIME_LOOKBACK", 288),
+            regime_tags: env.raw("REGIME_TAGS").map_or_else(Vec::new, |raw| {
+                parse_regime_tags(&raw).unwrap_or_else(|entry| {
+                    env.errors.push(format!(
+                        "REGIME_TAGS entry {:?} is not a recognised value",
+                        entry
+                    ));
+                    Vec::new()
+                })
+            }),
+            disabled_strategies: parse_strategy_ids(env.raw("DISABLED_STRATEGIES").as_deref()),
+            shadow_strategies: parse_strategy_ids(env.raw("SHADOW_STRATEGIES").as_deref()),
+            dump_views: env.flag("DUMP_VIEWS", false),
         }
     }
 
-    pub fn sleep_until_next_candle(&self, now_ts: u64) -> u64 {
-        let next = ((now_ts / self.candle_granularity) + 1) * self.candle_granularity;
-        next.saturating_sub(now_ts)
+    /// False for ids listed in `DISABLED_STRATEGIES`. A bare id also covers
+    /// its per-symbol instances (`churn-3` matches `churn-3:ETHUSDT`).
+    pub fn strategy_enabled(&self, id: &str) -> bool {
+        !matches_strategy_id(&self.disabled_strategies, id)
+    }
+
+    /// True for ids listed in `SHADOW_STRATEGIES`, matched like
+    /// `strategy_enabled`.
+    pub fn is_shadow(&self, id: &str) -> bool {
+        matches_strategy_id(&self.shadow_strategies, id)
+    }
+
+    /// Regimes the strategy `id` is tagged for; empty means every regime.
+    /// The first `REGIME_TAGS` entry whose prefix matches wins.
+    pub fn regime_tags_for(&self, id: &str) -> Vec<TrendRegime> {
+        self.regime_tags
+            .iter()
+            .find(|(prefix, _)| id.starts_with(prefix.as_str()))
+            .map(|(_, tags)| tags.clone())
+            .unwrap_or_default()
+    }
+
+    /// Milliseconds from `now_ms` to the next candle boundary on the
+    /// `candle_granularity_ms` grid (a full candle when exactly on one).
+    pub fn sleep_until_next_candle_ms(&self, now_ms: u64) -> u64 {
+        let step = self.candle_granularity_ms.max(1);
+        let next = ((now_ms / step) + 1) * step;
+        next.saturating_sub(now_ms)
+    }
+
+    /// `n` candles in whole seconds, rounded up so holds and stops measured
+    /// on second-resolution candle timestamps never end early.
+    pub fn candles_to_secs(&self, n: u64) -> u64 {
+        n.saturating_mul(self.candle_granularity_ms).div_ceil(1000)
     }
 
     /// Serialize config to JSON string.
@@ -245,6 +821,210 @@ impl Config {
         let hash = Sha256::digest(json.as_bytes());
         hex::encode(hash)
     }
+
+    /// Merge whitelisted thresholds from a JSON object (see
+    /// `RELOADABLE_THRESHOLDS`). Unknown and structural keys are skipped and
+    /// left for the caller to report; returns what actually changed. A file
+    /// that would fail `validate` is rejected whole.
+    pub fn merge_thresholds(
+        &mut self,
+        overrides: &serde_json::Value,
+    ) -> Result<Vec<ThresholdChange>, String> {
+        let map = overrides
+            .as_object()
+            .ok_or_else(|| "threshold overrides must be a JSON object".to_string())?;
+        // Validate everything first so a bad value never half-applies a file
+        let mut updates = Vec::new();
+        for &field in RELOADABLE_THRESHOLDS {
+            if let Some(value) = map.get(field) {
+                let new = value
+                    .as_f64()
+                    .filter(|v| v.is_finite())
+                    .ok_or_else(|| format!("{} must be a finite number", field))?;
+                updates.push((field, new));
+            }
+        }
+        let mut merged = self.clone();
+        let mut changes = Vec::new();
+        for (field, new) in updates {
+            let slot = match field {
+                "entry_threshold" => &mut merged.entry_threshold,
+                "exit_threshold" => &mut merged.exit_threshold,
+                "threshold_band" => &mut merged.threshold_band,
+                "edge_hurdle" => &mut merged.edge_hurdle,
+                "edge_scale" => &mut merged.edge_scale,
+                "stop_loss" => &mut merged.stop_loss,
+                "take_profit" => &mut merged.take_profit,
+                "mom_th" => &mut merged.mom_th,
+                "stretch_th" => &mut merged.stretch_th,
+                _ => unreachable!("{} is listed but not mapped", field),
+            };
+            if *slot != new {
+                changes.push(ThresholdChange {
+                    field,
+                    old: *slot,
+                    new,
+                });
+                *slot = new;
+            }
+        }
+        // The merged thresholds must pass the same range checks as startup
+        merged.validate()?;
+        *self = merged;
+        Ok(changes)
+    }
+
+    /// Candles the indicators need before live strategies may act (trap #2).
+    pub fn min_history(&self) -> u64 {
+        self.ema_slow.max(self.vol_window) as u64
+    }
+
+    pub fn is_warmed_up(&self, samples: u64) -> bool {
+        samples >= self.min_history()
+    }
+}
+
+/// True when an open position has moved against entry by at least the stop distance.
+pub fn check_stop_loss(move_pct: f64, atr: f64, entry: f64, cfg: &Config) -> bool {
+    move_pct <= -cfg.stop_loss_mode.threshold(cfg.stop_loss, atr, entry)
+}
+
+/// True when an open position has moved in favour by at least the take-profit distance.
+pub fn check_take_profit(move_pct: f64, atr: f64, entry: f64, cfg: &Config) -> bool {
+    move_pct >= cfg.take_profit_mode.threshold(cfg.take_profit, atr, entry)
+}
+
+/// Modal positive spacing between consecutive timestamps (ties go to the
+/// smaller spacing, so a few gaps cannot outvote the true bar size).
+pub fn detect_interval(timestamps: &[u64]) -> Option<u64> {
+    let mut counts: std::collections::BTreeMap<u64, usize> = std::collections::BTreeMap::new();
+    for w in timestamps.windows(2) {
+        if w[1] > w[0] {
+            *counts.entry(w[1] - w[0]).or_insert(0) += 1;
+        }
+    }
+    let mut best: Option<(u64, usize)> = None;
+    for (&delta, &n) in &counts {
+        if best.map(|(_, m)| n > m).unwrap_or(true) {
+            best = Some((delta, n));
+        }
+    }
+    best.map(|(delta, _)| delta)
+}
+
+/// Err when the configured bar size disagrees with the data's spacing.
+/// Time stops, cooldowns and min-hold are counted in configured bars, so a
+/// mismatch silently rescales all of them. Unknown spacing passes.
+pub fn check_granularity(configured_secs: u64, detected_secs: Option<u64>) -> Result<(), String> {
+    match detected_secs {
+        Some(detected) if detected != configured_secs => Err(format!(
+            "interval_mismatch: configured={}s detected={}s",
+            configured_secs, detected
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Err when the primary venue's price strays from a reference venue's by
+/// more than `max_spread` (fraction of the reference price); a bad feed on
+/// either side looks the same, so the caller should halt rather than pick.
+pub fn check_price_consistency(
+    primary: f64,
+    reference: f64,
+    max_spread: f64,
+) -> Result<(), String> {
+    if !(primary > 0.0 && reference > 0.0) {
+        return Err(format!(
+            "price_invalid: primary={} reference={}",
+            primary, reference
+        ));
+    }
+    let divergence = (primary - reference).abs() / reference;
+    if divergence > max_spread {
+        return Err(format!(
+            "price_divergence: primary={} reference={} divergence={:.4} max={:.4}",
+            primary, reference, divergence, max_spread
+        ));
+    }
+    Ok(())
+}
+
+/// Dead-man's switch for a frozen feed. A new candle is due every
+/// `candle_granularity_ms`; the feed is stale once the last timestamp
+/// advance (wall clock, seconds) is more than `max_latency_ms` overdue.
+pub fn candle_feed_stale(last_advance_ts: u64, now: u64, cfg: &Config) -> bool {
+    let allowed_ms = cfg.candle_granularity_ms.saturating_add(cfg.max_latency_ms);
+    now.saturating_sub(last_advance_ts).saturating_mul(1000) > allowed_ms
+}
+
+/// Expected slippage fraction from volatility alone; the same term the
+/// backtest's `calc_slippage` charges.
+pub fn vol_slippage(vol_frac: f64, vol_slip_mult: f64) -> f64 {
+    vol_frac * vol_slip_mult * 0.1
+}
+
+/// Estimated round-trip cost as a fraction of price: a fee and a
+/// volatility-driven slippage on both entry and exit. `vol` is in price
+/// units (as in `IndicatorSnapshot::vol`).
+pub fn round_trip_cost(fee_bps: f64, vol_slip_mult: f64, vol: f64, price: f64) -> f64 {
+    let vol_frac = if price > 0.0 { vol / price } else { 0.0 };
+    2.0 * (fee_bps / 10_000.0 + vol_slippage(vol_frac, vol_slip_mult))
+}
+
+/// Parse `REGIME_TAGS`, e.g. `carry=strong_bear|mild_bear,mom=ranging`.
+/// Err names the first entry without a prefix or with an unknown regime.
+fn parse_regime_tags(raw: &str) -> Result<Vec<(String, Vec<TrendRegime>)>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let parsed = entry.split_once('=').and_then(|(prefix, regimes)| {
+                let tags = regimes
+                    .split('|')
+                    .map(TrendRegime::parse)
+                    .collect::<Option<Vec<_>>>()?;
+                let prefix = prefix.trim();
+                (!prefix.is_empty()).then(|| (prefix.to_string(), tags))
+            });
+            parsed.ok_or_else(|| entry.to_string())
+        })
+        .collect()
+}
+
+/// Parse a comma-separated list of strategy ids, e.g. `churn-3,carry-1`.
+pub fn parse_strategy_ids(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// True if `id` is listed, or is a per-symbol instance (`id:SYMBOL`) of a
+/// listed bare id.
+fn matches_strategy_id(list: &[String], id: &str) -> bool {
+    list.iter().any(|d| {
+        id == d
+            || id
+                .strip_prefix(d.as_str())
+                .is_some_and(|rest| rest.starts_with(':'))
+    })
+}
+
+/// Parse a comma-separated SYMBOLS list, falling back to the single SYMBOL.
+fn parse_symbols(raw: Option<&str>, fallback: &str) -> Vec<String> {
+    let symbols: Vec<String> = raw
+        .unwrap_or("")
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if symbols.is_empty() {
+        vec![fallback.to_string()]
+    } else {
+        symbols
+    }
 }
 
 pub fn now_ts() -> u64 {
@@ -259,7 +1039,7 @@ pub struct Fill {
     pub ts: u64,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 struct RingBuffer<T: Copy> {
     buf: Vec<T>,
     idx: usize,
@@ -299,10 +1079,11 @@ impl<T: Copy> RingBuffer<T> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 struct IndicatorState {
     ema_fast: crate::indicators::Ema,
     ema_slow: crate::indicators::Ema,
+    atr: crate::indicators::Atr,
     price_n: u64,
     price_mean: f64,
     price_m2: f64,
@@ -323,9 +1104,43 @@ struct IndicatorState {
     vspike_n: u64,
     sum_px_vol: f64,
     sum_vol: f64,
+    /// (price * volume, volume) for the rolling VWAP window
+    vwap_window: RingBuffer<(f64, f64)>,
     last_vol: f64,
     last_volume_spike: f64,
     last_stretch: f64,
+    /// EW decay for the z-score stats; 0 keeps cumulative Welford
+    decay: f64,
+}
+
+/// Lookback for the true-range average exposed as `IndicatorSnapshot::atr`.
+const ATR_PERIOD: usize = 14;
+
+/// Rolling VWAP bars when no `volume_window` is configured.
+const DEFAULT_VWAP_WINDOW: usize = 30;
+
+/// Fold `x` into a running `(mean, m2, n)`. With `decay` in (0, 1) mean and
+/// variance are exponentially weighted and `m2` holds the variance itself;
+/// otherwise this is cumulative Welford.
+fn push_stat(x: f64, mean: &mut f64, m2: &mut f64, n: &mut u64, decay: f64) {
+    *n += 1;
+    if decay > 0.0 && decay < 1.0 {
+        if *n == 1 {
+            *mean = x;
+            *m2 = 0.0;
+            return;
+        }
+        let alpha = 1.0 - decay;
+        let delta = x - *mean;
+        let incr = alpha * delta;
+        *mean += incr;
+        *m2 = decay * (*m2 + delta * incr);
+    } else {
+        let delta = x - *mean;
+        *mean += delta / *n as f64;
+        let delta2 = x - *mean;
+        *m2 += delta * delta2;
+    }
 }
 
 impl IndicatorState {
@@ -333,6 +1148,7 @@ impl IndicatorState {
         Self {
             ema_fast: crate::indicators::Ema::new(ema_fast_period as usize),
             ema_slow: crate::indicators::Ema::new(ema_slow_period as usize),
+            atr: crate::indicators::Atr::new(ATR_PERIOD),
             price_n: 0,
             price_mean: 0.0,
             price_m2: 0.0,
@@ -353,12 +1169,42 @@ impl IndicatorState {
             vspike_n: 0,
             sum_px_vol: 0.0,
             sum_vol: 0.0,
+            vwap_window: RingBuffer::new(DEFAULT_VWAP_WINDOW, (0.0, 0.0)),
             last_vol: 0.0,
             last_volume_spike: 0.0,
             last_stretch: 0.0,
+            decay: 0.0,
         }
     }
 
+    fn with_decay(mut self, decay: f64) -> Self {
+        self.decay = decay;
+        self
+    }
+
+    fn with_vwap_window(mut self, bars: usize) -> Self {
+        self.vwap_window = RingBuffer::new(bars.max(1), (0.0, 0.0));
+        self
+    }
+
+    /// VWAP over the window; unfilled slots are (0, 0) and drop out.
+    fn rolling_vwap(&self) -> Option<f64> {
+        let (px_vol, vol) = self
+            .vwap_window
+            .buf
+            .iter()
+            .fold((0.0, 0.0), |(pv, v), (x, y)| (pv + x, v + y));
+        (vol > 0.0).then(|| px_vol / vol)
+    }
+
+    fn session_vwap(&self) -> Option<f64> {
+        (self.sum_vol > 0.0).then(|| self.sum_px_vol / self.sum_vol)
+    }
+
+    fn update_range(&mut self, high: f64, low: f64, close: f64) {
+        self.atr.update(high, low, close);
+    }
+
     fn update(&mut self, price: f64, volume: f64) {
         self.ema_fast.update(price);
         self.ema_slow.update(price);
@@ -377,6 +1223,7 @@ impl IndicatorState {
 
         self.sum_px_vol += price * volume;
         self.sum_vol += volume;
+        self.vwap_window.push((price * volume, volume));
 
         let var = if self.price_n > 1 {
             self.price_m2 / (self.price_n as f64 - 1.0)
@@ -385,17 +1232,15 @@ impl IndicatorState {
         };
         let vol = var.sqrt();
         self.last_vol = vol;
-        self.vol_n += 1;
-        let voldelta = vol - self.vol_mean;
-        self.vol_mean += voldelta / self.vol_n as f64;
-        let voldelta2 = vol - self.vol_mean;
-        self.vol_m2 += voldelta * voldelta2;
-
-        let vwap = if self.sum_vol > 0.0 {
-            self.sum_px_vol / self.sum_vol
-        } else {
-            price
-        };
+        push_stat(
+            vol,
+            &mut self.vol_mean,
+            &mut self.vol_m2,
+            &mut self.vol_n,
+            self.decay,
+        );
+
+        let vwap = self.rolling_vwap().unwrap_or(price);
         let momentum = self.ema_fast.get() - self.ema_slow.get();
         let stretch = if vwap > 0.0 {
             (price - vwap) / vwap
@@ -410,28 +1255,36 @@ impl IndicatorState {
         self.last_stretch = stretch;
         self.last_volume_spike = vspike;
 
-        self.mom_n += 1;
-        let mdelta = momentum - self.mom_mean;
-        self.mom_mean += mdelta / self.mom_n as f64;
-        let mdelta2 = momentum - self.mom_mean;
-        self.mom_m2 += mdelta * mdelta2;
-
-        self.stretch_n += 1;
-        let sdelta = stretch - self.stretch_mean;
-        self.stretch_mean += sdelta / self.stretch_n as f64;
-        let sdelta2 = stretch - self.stretch_mean;
-        self.stretch_m2 += sdelta * sdelta2;
-
-        self.vspike_n += 1;
-        let vsdelta = vspike - self.vspike_mean;
-        self.vspike_mean += vsdelta / self.vspike_n as f64;
-        let vsdelta2 = vspike - self.vspike_mean;
-        self.vspike_m2 += vsdelta * vsdelta2;
+        push_stat(
+            momentum,
+            &mut self.mom_mean,
+            &mut self.mom_m2,
+            &mut self.mom_n,
+            self.decay,
+        );
+        push_stat(
+            stretch,
+            &mut self.stretch_mean,
+            &mut self.stretch_m2,
+            &mut self.stretch_n,
+            self.decay,
+        );
+        push_stat(
+            vspike,
+            &mut self.vspike_mean,
+            &mut self.vspike_m2,
+            &mut self.vspike_n,
+            self.decay,
+        );
     }
 
-    fn zscore(val: f64, mean: f64, m2: f64, n: u64) -> f64 {
+    fn zscore(&self, val: f64, mean: f64, m2: f64, n: u64) -> f64 {
         if n > 1 {
-            let var = m2 / (n as f64 - 1.0);
+            let var = if self.decay > 0.0 && self.decay < 1.0 {
+                m2
+            } else {
+                m2 / (n as f64 - 1.0)
+            };
             if var > 0.0 {
                 (val - mean) / var.sqrt()
             } else {
@@ -449,34 +1302,48 @@ impl IndicatorState {
         IndicatorSnapshot {
             ema_fast: ema_fast_val,
             ema_slow: ema_slow_val,
-            vwap: if self.sum_vol > 0.0 {
-                self.sum_px_vol / self.sum_vol
-            } else {
-                0.0
-            },
+            vwap: self.rolling_vwap().unwrap_or(0.0),
+            session_vwap: self.session_vwap().unwrap_or(0.0),
             vol: self.last_vol,
             vol_mean: self.vol_mean,
             momentum,
             volume_spike: self.last_volume_spike,
             stretch: self.last_stretch,
-            z_momentum: Self::zscore(momentum, self.mom_mean, self.mom_m2, self.mom_n),
-            z_vol: Self::zscore(self.last_vol, self.vol_mean, self.vol_m2, self.vol_n),
-            z_volume_spike: Self::zscore(
+            z_momentum: self.zscore(momentum, self.mom_mean, self.mom_m2, self.mom_n),
+            z_vol: self.zscore(self.last_vol, self.vol_mean, self.vol_m2, self.vol_n),
+            z_volume_spike: self.zscore(
                 self.last_volume_spike,
                 self.vspike_mean,
                 self.vspike_m2,
                 self.vspike_n,
             ),
-            z_stretch: Self::zscore(
+            z_stretch: self.zscore(
                 self.last_stretch,
                 self.stretch_mean,
                 self.stretch_m2,
                 self.stretch_n,
             ),
+            samples: self.price_n,
+            atr: self.atr.get(),
         }
     }
 }
 
+/// Why a candle is unfit for the indicators, if it is: every field must be
+/// finite, prices strictly positive and volume non-negative.
+pub fn candle_defect(candle: &ExCandle) -> Option<&'static str> {
+    let prices = [candle.o, candle.h, candle.l, candle.c];
+    if !prices.iter().chain([&candle.v]).all(|x| x.is_finite()) {
+        Some("non_finite")
+    } else if prices.iter().any(|&p| p <= 0.0) {
+        Some("non_positive_price")
+    } else if candle.v < 0.0 {
+        Some("negative_volume")
+    } else {
+        None
+    }
+}
+
 pub struct MarketState {
     cfg: Config,
     buffers: HashMap<String, RingBuffer<ExCandle>>,
@@ -494,8 +1361,58 @@ impl MarketState {
         }
     }
 
-    pub fn on_candle(&mut self, candle: ExCandle) {
+    pub fn on_candle(&mut self, candle: ExCandle) -> bool {
         let sym = self.cfg.symbol.clone();
+        self.on_candle_for(&sym, candle)
+    }
+
+    /// Candle buffer, indicator state and aux for `symbol` as JSON, for
+    /// carrying a backtest across chunks; None before its first candle.
+    #[allow(dead_code)]
+    pub fn export_symbol(&self, symbol: &str) -> Option<serde_json::Value> {
+        Some(serde_json::json!({
+            "buffer": self.buffers.get(symbol)?,
+            "indicators": self.indicators.get(symbol)?,
+            "aux": self.aux.get(symbol),
+        }))
+    }
+
+    /// Restore what `export_symbol` produced, replacing `symbol`'s state.
+    #[allow(dead_code)]
+    pub fn import_symbol(&mut self, symbol: &str, value: &serde_json::Value) -> Result<(), String> {
+        let field = |name: &str| value.get(name).cloned().unwrap_or_default();
+        let buffer = serde_json::from_value(field("buffer")).map_err(|e| e.to_string())?;
+        let indicators = serde_json::from_value(field("indicators")).map_err(|e| e.to_string())?;
+        let aux: Option<MarketAux> =
+            serde_json::from_value(field("aux")).map_err(|e| e.to_string())?;
+        self.buffers.insert(symbol.to_string(), buffer);
+        self.indicators.insert(symbol.to_string(), indicators);
+        match aux {
+            Some(aux) => self.aux.insert(symbol.to_string(), aux),
+            None => self.aux.remove(symbol),
+        };
+        Ok(())
+    }
+
+    /// Feed a candle for a specific symbol; buffers and indicators are keyed per symbol.
+    ///
+    /// Returns false (and logs) when the candle fails `candle_defect`; it is
+    /// dropped before it can reach the buffer or put NaN/inf into indicators.
+    pub fn on_candle_for(&mut self, symbol: &str, candle: ExCandle) -> bool {
+        if let Some(defect) = candle_defect(&candle) {
+            json_log(
+                "risk_guard",
+                obj(&[
+                    ("check", v_str("candle_invalid")),
+                    ("result", v_str("skip")),
+                    ("symbol", v_str(symbol)),
+                    ("defect", v_str(defect)),
+                    ("candle_ts", v_num(candle.ts as f64)),
+                ]),
+            );
+            return false;
+        }
+        let sym = symbol.to_string();
         let zero = ExCandle {
             ts: 0,
             o: 0.0,
@@ -511,11 +1428,16 @@ impl MarketState {
         let _old = buf.push(candle);
         let ema_fast_period = self.cfg.ema_fast;
         let ema_slow_period = self.cfg.ema_slow;
-        let ind = self
-            .indicators
-            .entry(sym)
-            .or_insert_with(|| IndicatorState::new(ema_fast_period, ema_slow_period));
+        let decay = self.cfg.zscore_decay;
+        let vwap_bars = self.cfg.volume_window as usize;
+        let ind = self.indicators.entry(sym).or_insert_with(|| {
+            IndicatorState::new(ema_fast_period, ema_slow_period)
+                .with_decay(decay)
+                .with_vwap_window(vwap_bars)
+        });
+        ind.update_range(candle.h, candle.l, candle.c);
         ind.update(candle.c, candle.v);
+        true
     }
 
     pub fn view<'a>(&self, symbol: &'a str) -> MarketView<'a> {
@@ -550,45 +1472,69 @@ impl MarketState {
         }
     }
 
-    pub fn update_aux(&mut self, symbol: &str, aux: MarketAux) {
+    pub fn update_aux(&mut self, symbol: &str, mut aux: MarketAux) {
+        // Feeds that don't report the settlement time get the configured schedule
+        if aux.next_funding_ts == 0 && aux.fetch_ts > 0 {
+            aux.next_funding_ts = next_funding_ts(aux.fetch_ts, self.cfg.funding_interval_secs);
+        }
         self.aux.insert(symbol.to_string(), aux);
     }
 }
 
 pub struct StrategyInstance {
     pub id: String,
+    pub symbol: String,
     pub strategy: Box<dyn Strategy + Send + Sync>,
     pub state: StrategyState,
+    /// Regimes this instance trades in (see `RegimeRouter`); empty = all.
+    pub regimes: Vec<TrendRegime>,
+    /// Observe-only: signals and a virtual portfolio, no real orders.
+    pub shadow: bool,
 }
 
 impl StrategyInstance {
+    /// Build the default set once per `cfg.symbols` entry.
+    ///
+    /// With a single symbol ids are unchanged (`mom-0`), keeping WAL/intent ids stable;
+    /// with several, ids get a `:SYMBOL` suffix so intent prefixes never collide.
+    /// `confluence_strategy` adds a `confluence:SYMBOL` instance per symbol.
+    pub fn build_default_set_per_symbol(cfg: Config) -> Vec<Self> {
+        let multi = cfg.symbols.len() > 1;
+        let mut list = Vec::new();
+        for symbol in &cfg.symbols {
+            let mut cfg_s = cfg.clone();
+            cfg_s.symbol = symbol.clone();
+            for mut inst in Self::build_default_set(cfg_s) {
+                if multi {
+                    inst.id = format!("{}:{}", inst.id, symbol);
+                    inst.shadow = cfg.is_shadow(&inst.id);
+                }
+                list.push(inst);
+            }
+        }
+        if cfg.confluence_strategy {
+            list.extend(crate::strategies::build_confluence_set(cfg));
+        }
+        list
+    }
+
     pub fn build_default_set(cfg: Config) -> Vec<Self> {
         let mut list = Vec::new();
         for i in 0..3 {
             let offset = (i as u64) * 300;
             let id = format!("mom-{}", i);
+            let shadow = cfg.is_shadow(&id);
             list.push(Self {
                 id: id.clone(),
+                symbol: cfg.symbol.clone(),
                 strategy: Box::new(SimpleMomentum {
                     id,
                     start_delay: offset,
                     cfg: cfg.clone(),
                 }),
-                state: StrategyState {
-                    portfolio: PortfolioState {
-                        cash: 1000.0,
-                        position: 0.0,
-                        entry_price: 0.0,
-                        equity: 1000.0,
-                    },
-                    metrics: MetricsState::default(),
-                    last_trade_ts: 0,
-                    last_loss_ts: 0,
-                    trading_halted: false,
-                    trades_today: 0,
-                    trade_day: 0,
-                    order_seq: 0,
-                },
+                state: StrategyState::with_capital(cfg.starting_capital, cfg.contract_kind),
+                regimes: Vec::new(),
+                shadow,
             });
         }
         list
@@ -618,28 +1564,18 @@ impl StrategyInstance {
             cfg_i.take_profit = *tp;
             cfg_i.stop_loss = *sl;
             let id = format!("churn-{}", i);
+            let shadow = cfg.is_shadow(&id);
             list.push(Self {
                 id: id.clone(),
+                symbol: cfg.symbol.clone(),
                 strategy: Box::new(SimpleMomentum {
                     id,
                     start_delay: (i as u64) * 300,
                     cfg: cfg_i,
                 }),
-                state: StrategyState {
-                    portfolio: PortfolioState {
-                        cash: 1000.0,
-                        position: 0.0,
-                        entry_price: 0.0,
-                        equity: 1000.0,
-                    },
-                    metrics: MetricsState::default(),
-                    last_trade_ts: 0,
-                    last_loss_ts: 0,
-                    trading_halted: false,
-                    trades_today: 0,
-                    trade_day: 0,
-                    order_seq: 0,
-                },
+                state: StrategyState::with_capital(cfg.starting_capital, cfg.contract_kind),
+                regimes: Vec::new(),
+                shadow,
             });
         }
         list
@@ -649,50 +1585,269 @@ impl StrategyInstance {
         let mut list = Vec::new();
         for i in 0..3 {
             let id = format!("carry-{}", i);
+            let shadow = cfg.is_shadow(&id);
             list.push(Self {
                 id: id.clone(),
+                symbol: cfg.symbol.clone(),
                 strategy: Box::new(CarryOpportunistic {
                     id,
                     cfg: cfg.clone(),
                 }),
-                state: StrategyState {
-                    portfolio: PortfolioState {
-                        cash: 1000.0,
-                        position: 0.0,
-                        entry_price: 0.0,
-                        equity: 1000.0,
-                    },
-                    metrics: MetricsState::default(),
-                    last_trade_ts: 0,
-                    last_loss_ts: 0,
-                    trading_halted: false,
-                    trades_today: 0,
-                    trade_day: 0,
-                    order_seq: 0,
-                },
+                state: StrategyState::with_capital(cfg.starting_capital, cfg.contract_kind),
+                regimes: Vec::new(),
+                shadow,
             });
         }
         list
     }
 }
 
+/// Funding rich relative to borrow. Both readings must be live: a defaulted
+/// zero borrow makes any funding look like carry, so an otherwise actionable
+/// signal with either flag missing abstains and logs `aux_incomplete`.
+pub fn carry_signal(strategy: &str, aux: &MarketAux, cfg: &Config) -> bool {
+    let actionable = aux.funding_rate.abs() > cfg.funding_high
+        && aux.borrow_rate < aux.funding_rate.abs() - cfg.funding_spread;
+    if actionable && !(aux.has_funding && aux.has_borrow) {
+        let missing = match (aux.has_funding, aux.has_borrow) {
+            (false, false) => "funding,borrow",
+            (false, true) => "funding",
+            _ => "borrow",
+        };
+        json_log(
+            "risk_guard",
+            obj(&[
+                ("check", v_str("aux_incomplete")),
+                ("result", v_str("abstain")),
+                ("strategy", v_str(strategy)),
+                ("missing", v_str(missing)),
+                ("funding_rate", v_num(aux.funding_rate)),
+                ("borrow_rate", v_num(aux.borrow_rate)),
+            ]),
+        );
+        return false;
+    }
+    actionable
+}
+
 struct SimpleMomentum {
-    #[allow(dead_code)]
     id: String,
     start_delay: u64,
     cfg: Config,
 }
 
+impl SimpleMomentum {
+    fn min_hold_secs(&self) -> u64 {
+        self.cfg.candles_to_secs(self.cfg.min_hold_candles as u64)
+    }
+
+    /// Entry the flat strategy would take on this bar (Hold if none).
+    fn entry_signal(
+        &self,
+        market: &MarketView,
+        score: f64,
+        in_uptrend: bool,
+        in_downtrend: bool,
+        strong_trend: bool,
+    ) -> (crate::strategy::Action, &'static str) {
+        // Volatility regime switch: low vol => follow momentum; high vol => trend-aligned mean reversion.
+        let vol_ratio = if market.indicators.vol_mean > 0.0 {
+            market.indicators.vol / market.indicators.vol_mean
+        } else {
+            1.0
+        };
+
+        // Low volatility: follow momentum
+        if vol_ratio < self.cfg.vol_low {
+            if market.indicators.z_momentum > self.cfg.mom_th {
+                return (
+                    crate::strategy::Action::Buy { qty: 0.001 },
+                    "low_vol_momentum",
+                );
+            }
+            if market.indicators.z_momentum < -self.cfg.mom_th {
+                return (
+                    crate::strategy::Action::Sell { qty: 0.001 },
+                    "low_vol_momentum",
+                );
+            }
+        }
+        // High volatility: only mean-revert if aligned with trend OR trend is weak
+        else if vol_ratio > self.cfg.vol_high {
+            // Stretched above in uptrend or weak trend: sell expecting reversion
+            if market.indicators.z_stretch > self.cfg.stretch_th && (in_uptrend || !strong_trend) {
+                return (
+                    crate::strategy::Action::Sell { qty: 0.001 },
+                    "high_vol_reversion",
+                );
+            }
+            // Stretched below in downtrend or weak trend: buy expecting reversion
+            if market.indicators.z_stretch < -self.cfg.stretch_th && (in_downtrend || !strong_trend)
+            {
+                return (
+                    crate::strategy::Action::Buy { qty: 0.001 },
+                    "high_vol_reversion",
+                );
+            }
+            // In strong opposite trend, don't mean-revert - follow trend instead
+            if strong_trend && in_downtrend && market.indicators.z_momentum < -self.cfg.mom_th {
+                return (
+                    crate::strategy::Action::Sell { qty: 0.001 },
+                    "high_vol_trend",
+                );
+            }
+            if strong_trend && in_uptrend && market.indicators.z_momentum > self.cfg.mom_th {
+                return (
+                    crate::strategy::Action::Buy { qty: 0.001 },
+                    "high_vol_trend",
+                );
+            }
+        }
+
+        // Score-based entry with trend confirmation, clearing the hysteresis band
+        let entry_threshold = self.cfg.entry_threshold + self.cfg.threshold_band;
+        if score > entry_threshold && !in_downtrend {
+            return (crate::strategy::Action::Buy { qty: 0.001 }, "score_entry");
+        }
+        if score < -entry_threshold && !in_uptrend {
+            return (crate::strategy::Action::Sell { qty: 0.001 }, "score_entry");
+        }
+        // Strong trend override: follow momentum regardless of score
+        if strong_trend && in_downtrend && market.indicators.z_momentum < -0.5 {
+            return (
+                crate::strategy::Action::Sell { qty: 0.001 },
+                "trend_override",
+            );
+        }
+        if strong_trend && in_uptrend && market.indicators.z_momentum > 0.5 {
+            return (
+                crate::strategy::Action::Buy { qty: 0.001 },
+                "trend_override",
+            );
+        }
+        (crate::strategy::Action::Hold, "")
+    }
+
+    /// Blend `raw` into the running score EMA when smoothing is on; the first
+    /// scored bar seeds it.
+    fn smooth_score(&self, raw: f64, state: &mut StrategyState) -> f64 {
+        let alpha = self.cfg.score_smoothing_alpha;
+        let score = match state.smoothed_score {
+            Some(prev) if alpha < 1.0 => prev + alpha * (raw - prev),
+            _ => raw,
+        };
+        state.smoothed_score = Some(score);
+        score
+    }
+
+    /// Add one more tranche while the entry signal still points the same way
+    /// as the open position, the ladder has room and the next tranche stays
+    /// within `max_position_pct` of equity. Otherwise Hold.
+    fn scale_in(
+        &self,
+        entry: crate::strategy::Action,
+        price: f64,
+        state: &mut StrategyState,
+    ) -> crate::strategy::Action {
+        let qty = match entry {
+            crate::strategy::Action::Buy { qty } if state.portfolio.position > 0.0 => qty,
+            crate::strategy::Action::Sell { qty } if state.portfolio.position < 0.0 => qty,
+            _ => return crate::strategy::Action::Hold,
+        };
+        // An open position is at least one tranche, even if it predates the ladder
+        let tranches = state.tranches.max(1);
+        if tranches >= self.cfg.scale_in_tranches {
+            return crate::strategy::Action::Hold;
+        }
+        let next_exposure = (state.portfolio.position.abs() + qty) * price;
+        if next_exposure > self.cfg.max_position_pct * state.portfolio.equity.max(0.0) {
+            return crate::strategy::Action::Hold;
+        }
+        entry
+    }
+
+    /// Partial exit as the open position's signal fades, when
+    /// `scale_out_fraction` is set: a score still on the position's side but
+    /// under the entry threshold trims that fraction once per position, and
+    /// only while it is in profit; one past the exit threshold on the other
+    /// side closes outright. None leaves the position to the score exit and
+    /// scale-in checks.
+    fn scale_out(
+        &self,
+        score: f64,
+        price: f64,
+        state: &mut StrategyState,
+    ) -> Option<(crate::strategy::Action, &'static str)> {
+        if self.cfg.scale_out_fraction <= 0.0 {
+            return None;
+        }
+        let position = state.portfolio.position;
+        let aligned = score * position.signum();
+        if aligned <= -self.cfg.exit_threshold {
+            return Some((crate::strategy::Action::Close, "signal_reversal"));
+        }
+        let in_profit = (price - state.portfolio.entry_price) * position.signum() > 0.0;
+        if !state.scaled_out
+            && in_profit
+            && aligned >= self.cfg.exit_threshold
+            && aligned < self.cfg.entry_threshold
+        {
+            let qty = position.abs() * self.cfg.scale_out_fraction;
+            let action = if position > 0.0 {
+                crate::strategy::Action::Sell { qty }
+            } else {
+                crate::strategy::Action::Buy { qty }
+            };
+            state.scaled_out = true;
+            return Some((action, "scale_out"));
+        }
+        None
+    }
+
+    /// Price/time exit that fires for the open position, checked in priority order.
+    fn rule_exit(&self, market: &MarketView, state: &StrategyState) -> Option<ExitReason> {
+        let price = market.last.c;
+        let entry = state.portfolio.entry_price.max(1e-9);
+        let move_pct = (price - entry) / entry;
+        let elapsed = market.last.ts.saturating_sub(state.last_trade_ts);
+
+        // Stop loss always fires regardless of min hold (capital preservation)
+        if check_stop_loss(move_pct, market.indicators.atr, entry, &self.cfg) {
+            return Some(ExitReason::Stop);
+        }
+
+        // Other exits respect min hold period to reduce overtrading
+        if elapsed >= self.min_hold_secs() {
+            if check_take_profit(move_pct, market.indicators.atr, entry, &self.cfg) {
+                return Some(ExitReason::Take);
+            }
+            if elapsed >= self.cfg.candles_to_secs(self.cfg.time_stop as u64) {
+                return Some(ExitReason::Time);
+            }
+        }
+        None
+    }
+}
+
 impl Strategy for SimpleMomentum {
     fn id(&self) -> &'static str {
         "simple-momentum"
     }
 
+    fn config_mut(&mut self) -> Option<&mut Config> {
+        Some(&mut self.cfg)
+    }
+
+    fn exit_reason(&self, market: MarketView, state: &StrategyState) -> ExitReason {
+        self.rule_exit(&market, state).unwrap_or(ExitReason::Signal)
+    }
+
     fn aux_requirements(&self) -> crate::strategy::AuxRequirements {
         crate::strategy::AuxRequirements::full()
     }
 
     fn update(&mut self, market: MarketView, state: &mut StrategyState) -> crate::strategy::Action {
+        state.signal_reason = "";
         let now = market.last.ts;
         if now < self.start_delay {
             return crate::strategy::Action::Hold;
@@ -726,20 +1881,26 @@ impl Strategy for SimpleMomentum {
             0.0
         };
 
-        let score = 1.0 * market.indicators.z_momentum
+        let raw_score = 1.0 * market.indicators.z_momentum
             + 0.3 * market.indicators.z_vol
             + 0.5 * market.indicators.z_volume_spike
             + stretch_contrib;
+        let score = self.smooth_score(raw_score, state);
 
-        let expected_edge = score.abs() * self.cfg.edge_scale;
+        let cost = round_trip_cost(
+            self.cfg.edge_fee_bps,
+            self.cfg.edge_slip_vol_mult,
+            market.indicators.vol,
+            market.last.c,
+        );
+        let expected_edge = score.abs() * self.cfg.edge_scale - cost;
         if expected_edge < self.cfg.edge_hurdle {
             return crate::strategy::Action::Hold;
         }
 
         // Funding carry: prefer direction opposite funding pressure.
-        if market.aux.funding_rate.abs() > self.cfg.funding_high
-            && market.aux.borrow_rate < market.aux.funding_rate.abs() - self.cfg.funding_spread
-        {
+        if carry_signal(&self.id, &market.aux, &self.cfg) {
+            state.signal_reason = "funding_carry";
             if market.aux.funding_rate > 0.0 {
                 return crate::strategy::Action::Sell { qty: 0.001 };
             } else {
@@ -749,105 +1910,61 @@ impl Strategy for SimpleMomentum {
 
         // Liquidation cascade: trade with impulse.
         if market.aux.has_liquidations && market.aux.liquidation_score > self.cfg.liq_score_th {
+            state.signal_reason = "liquidation_cascade";
             if market.indicators.z_momentum > 0.0 {
                 return crate::strategy::Action::Buy { qty: 0.001 };
-            } else {
-                return crate::strategy::Action::Sell { qty: 0.001 };
-            }
-        }
-
-        // Stablecoin depeg snapback: if symbol is stable-quoted, fade depeg.
-        if market.aux.has_depeg && market.aux.stable_depeg.abs() > self.cfg.depeg_th {
-            if market.aux.stable_depeg < 0.0 {
-                return crate::strategy::Action::Buy { qty: 0.001 };
-            } else {
-                return crate::strategy::Action::Sell { qty: 0.001 };
-            }
-        }
-
-        if state.portfolio.position != 0.0 {
-            let price = market.last.c;
-            let entry = state.portfolio.entry_price.max(1e-9);
-            let move_pct = (price - entry) / entry;
-            let elapsed = now.saturating_sub(state.last_trade_ts);
-            let min_hold_secs = self.cfg.min_hold_candles as u64 * self.cfg.candle_granularity;
-
-            // Stop loss always fires regardless of min hold (capital preservation)
-            if move_pct <= -self.cfg.stop_loss {
-                return crate::strategy::Action::Close;
-            }
-
-            // Other exits respect min hold period to reduce overtrading
-            if elapsed >= min_hold_secs {
-                if move_pct >= self.cfg.take_profit {
-                    return crate::strategy::Action::Close;
-                }
-                if elapsed >= self.cfg.time_stop as u64 * self.cfg.candle_granularity {
-                    return crate::strategy::Action::Close;
-                }
-                if score.abs() < self.cfg.exit_threshold {
-                    return crate::strategy::Action::Close;
-                }
+            } else {
+                return crate::strategy::Action::Sell { qty: 0.001 };
             }
-            return crate::strategy::Action::Hold;
         }
 
-        // Volatility regime switch: low vol => follow momentum; high vol => trend-aligned mean reversion.
-        let vol_ratio = if market.indicators.vol_mean > 0.0 {
-            market.indicators.vol / market.indicators.vol_mean
-        } else {
-            1.0
-        };
-
-        // Low volatility: follow momentum
-        if vol_ratio < self.cfg.vol_low {
-            if market.indicators.z_momentum > self.cfg.mom_th {
+        // Stablecoin depeg snapback: if symbol is stable-quoted, fade depeg.
+        if market.aux.has_depeg && market.aux.stable_depeg.abs() > self.cfg.depeg_th {
+            state.signal_reason = "depeg_snapback";
+            if market.aux.stable_depeg < 0.0 {
                 return crate::strategy::Action::Buy { qty: 0.001 };
-            }
-            if market.indicators.z_momentum < -self.cfg.mom_th {
+            } else {
                 return crate::strategy::Action::Sell { qty: 0.001 };
             }
         }
-        // High volatility: only mean-revert if aligned with trend OR trend is weak
-        else if vol_ratio > self.cfg.vol_high {
-            // Stretched above in uptrend or weak trend: sell expecting reversion
-            if market.indicators.z_stretch > self.cfg.stretch_th && (in_uptrend || !strong_trend) {
-                return crate::strategy::Action::Sell { qty: 0.001 };
+
+        if state.portfolio.position != 0.0 {
+            if self.rule_exit(&market, state).is_some() {
+                state.signal_reason = "rule_exit";
+                return crate::strategy::Action::Close;
             }
-            // Stretched below in downtrend or weak trend: buy expecting reversion
-            if market.indicators.z_stretch < -self.cfg.stretch_th && (in_downtrend || !strong_trend)
-            {
-                return crate::strategy::Action::Buy { qty: 0.001 };
+            let elapsed = now.saturating_sub(state.last_trade_ts);
+            let exit_threshold = self.cfg.exit_threshold - self.cfg.threshold_band;
+            if elapsed >= self.min_hold_secs() && score.abs() < exit_threshold {
+                state.signal_reason = "score_exit";
+                return crate::strategy::Action::Close;
             }
-            // In strong opposite trend, don't mean-revert - follow trend instead
-            if strong_trend && in_downtrend && market.indicators.z_momentum < -self.cfg.mom_th {
-                return crate::strategy::Action::Sell { qty: 0.001 };
+            if elapsed >= self.min_hold_secs() {
+                if let Some((action, reason)) = self.scale_out(score, market.last.c, state) {
+                    state.signal_reason = reason;
+                    return action;
+                }
             }
-            if strong_trend && in_uptrend && market.indicators.z_momentum > self.cfg.mom_th {
-                return crate::strategy::Action::Buy { qty: 0.001 };
+            let (entry, _) =
+                self.entry_signal(&market, score, in_uptrend, in_downtrend, strong_trend);
+            let action = self.scale_in(entry, market.last.c, state);
+            if !matches!(action, crate::strategy::Action::Hold) {
+                state.signal_reason = "scale_in";
             }
+            return action;
         }
 
-        // Score-based entry with trend confirmation
-        if score > self.cfg.entry_threshold && !in_downtrend {
-            return crate::strategy::Action::Buy { qty: 0.001 };
-        }
-        if score < -self.cfg.entry_threshold && !in_uptrend {
-            return crate::strategy::Action::Sell { qty: 0.001 };
+        state.scaled_out = false;
+        let (entry, reason) =
+            self.entry_signal(&market, score, in_uptrend, in_downtrend, strong_trend);
+        if !matches!(entry, crate::strategy::Action::Hold) {
+            state.signal_reason = reason;
         }
-        // Strong trend override: follow momentum regardless of score
-        if strong_trend && in_downtrend && market.indicators.z_momentum < -0.5 {
-            return crate::strategy::Action::Sell { qty: 0.001 };
-        }
-        if strong_trend && in_uptrend && market.indicators.z_momentum > 0.5 {
-            return crate::strategy::Action::Buy { qty: 0.001 };
-        }
-        crate::strategy::Action::Hold
+        entry
     }
 }
 
 struct CarryOpportunistic {
-    #[allow(dead_code)]
     id: String,
     cfg: Config,
 }
@@ -857,15 +1974,17 @@ impl Strategy for CarryOpportunistic {
         "carry-opportunistic"
     }
 
+    fn config_mut(&mut self) -> Option<&mut Config> {
+        Some(&mut self.cfg)
+    }
+
     fn aux_requirements(&self) -> crate::strategy::AuxRequirements {
         crate::strategy::AuxRequirements::full()
     }
 
     fn update(&mut self, market: MarketView, state: &mut StrategyState) -> crate::strategy::Action {
         // Funding carry: hold a small delta-hedged bias (modeled here as a single leg).
-        if market.aux.funding_rate.abs() > self.cfg.funding_high
-            && market.aux.borrow_rate < market.aux.funding_rate.abs() - self.cfg.funding_spread
-        {
+        if carry_signal(&self.id, &market.aux, &self.cfg) {
             if market.aux.funding_rate > 0.0 {
                 return crate::strategy::Action::Sell { qty: 0.001 };
             } else {
@@ -896,12 +2015,12 @@ impl Strategy for CarryOpportunistic {
             let move_pct = (price - entry) / entry;
 
             // Stop loss always fires (capital preservation overrides hold period)
-            if move_pct <= -self.cfg.stop_loss {
+            if check_stop_loss(move_pct, market.indicators.atr, entry, &self.cfg) {
                 return crate::strategy::Action::Close;
             }
 
             let elapsed = market.last.ts.saturating_sub(state.last_trade_ts);
-            let min_hold_secs = self.cfg.min_hold_candles as u64 * self.cfg.candle_granularity;
+            let min_hold_secs = self.cfg.candles_to_secs(self.cfg.min_hold_candles as u64);
             if elapsed >= min_hold_secs {
                 let vol_ratio = if market.indicators.vol_mean > 0.0 {
                     market.indicators.vol / market.indicators.vol_mean
@@ -911,7 +2030,7 @@ impl Strategy for CarryOpportunistic {
                 if vol_ratio > self.cfg.vol_pause_mult {
                     return crate::strategy::Action::Close;
                 }
-                if move_pct >= self.cfg.take_profit {
+                if check_take_profit(move_pct, market.indicators.atr, entry, &self.cfg) {
                     return crate::strategy::Action::Close;
                 }
             }
@@ -919,62 +2038,139 @@ impl Strategy for CarryOpportunistic {
 
         crate::strategy::Action::Hold
     }
+
+    fn exit_reason(&self, market: MarketView, state: &StrategyState) -> ExitReason {
+        let entry = state.portfolio.entry_price.max(1e-9);
+        let move_pct = (market.last.c - entry) / entry;
+        let vol_spike = market.indicators.vol_mean > 0.0
+            && market.indicators.vol / market.indicators.vol_mean > self.cfg.vol_pause_mult;
+        // Same precedence as `update`: stop, then vol spike, then take-profit.
+        if check_stop_loss(move_pct, market.indicators.atr, entry, &self.cfg) {
+            ExitReason::Stop
+        } else if !vol_spike && check_take_profit(move_pct, market.indicators.atr, entry, &self.cfg)
+        {
+            ExitReason::Take
+        } else {
+            ExitReason::Signal
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::strategy::{Action, MarketAux};
+    use crate::strategy::{Action, MarketAux, PortfolioState};
 
     fn test_config() -> Config {
         Config {
             symbol: "BTCUSDT".to_string(),
+            symbols: vec!["BTCUSDT".to_string()],
             candle_granularity: 300,
+            candle_granularity_ms: 300_000,
             window: 100,
             api_key: None,
             api_secret: None,
+            use_testnet: false,
             binance_base: String::new(),
             binance_fapi_base: String::new(),
+            binance_ws_base: String::new(),
             kraken_base: String::new(),
+            contract_spec_file: String::new(),
             sqlite_path: String::new(),
             persist_every_secs: 300,
+            persist_hwm: true,
             max_position_pct: 0.05,
+            starting_capital: 1000.0,
             max_daily_loss_pct: 0.02,
+            max_drawdown_mult: 2.0,
+            delever_curve: DeleverCurve::Off,
+            pf_disable_trades: 0,
+            pf_disable_min: 1.0,
+            max_order_notional: 0.0,
+            rounding_drift_notional: 0.0,
             max_trades_per_day: 20,
+            clamp_position_qty: true,
+            sizing_mode: SizingMode::Fixed,
+            cvar_budget_pct: 0.002,
+            kelly_fraction: 0.25,
+            kelly_window: 30,
+            max_trades_per_hour: 6,
+            min_order_interval_secs: 0,
+            drift_halt_confirm_bars: 1,
             cooldown_secs: 600,
+            scale_in_tranches: 1,
+            scale_out_fraction: 0.0,
+            cooldown_ref_loss_pct: 0.005,
+            cooldown_max_secs: 3600,
+            reset_cooldown_on_regime: false,
             vol_pause_mult: 2.5,
             entry_threshold: 1.2,
             exit_threshold: 0.4,
+            score_smoothing_alpha: 1.0,
+            threshold_band: 0.0,
             breakout_threshold: 2.0,
             edge_hurdle: 0.003,
             edge_scale: 0.0025,
+            edge_fee_bps: 0.0,
+            edge_slip_vol_mult: 0.0,
             ema_fast: 6,
             ema_slow: 24,
             vol_window: 30,
             volume_window: 30,
             take_profit: 0.006,
             stop_loss: 0.004,
+            take_profit_mode: StopMode::Fixed,
+            stop_loss_mode: StopMode::Fixed,
             time_stop: 12,
+            global_max_hold_secs: 0,
             funding_high: 0.0001,
             funding_spread: 0.00005,
             liq_score_th: 3.0,
+            liq_score_source: LiqScoreSource::Raw,
+            liq_baseline_bars: 288,
             depeg_th: 0.002,
             vol_low: 0.6,
             vol_high: 1.6,
             mom_th: 0.4,
             stretch_th: 0.8,
             kill_file: String::new(),
+            reenable_file: String::new(),
+            threshold_file: String::new(),
             wal_path: String::new(),
             reconcile_secs: 60,
+            reconcile_now_file: String::new(),
             cancel_after_candles: 3,
             reconcile_drift_pct: 0.02,
             reconcile_drift_abs: 0.0005,
+            reconcile_action: ReconcileAction::Halt,
             max_fill_slip_pct: 0.02,
             fill_channel_capacity: 256,
+            max_orders_in_flight: 4,
             allow_unknown_regime: false,
+            trade_candles: false,
+            warmup_prefill: true,
+            seed: 0,
             max_latency_ms: 300000,
             max_liquidity_spread: 0.01,
+            maker_offset_bps: 0.0,
+            reference_exchange: None,
             min_hold_candles: 0,
+            zscore_decay: 0.0,
+            contract_kind: ContractKind::Linear,
+            funding_interval_secs: 28_800,
+            market_type: MarketType::Perp,
+            paper_slippage: 0.0005,
+            paper_fee_rate: 0.001,
+            impact_coeff: 1.0,
+            metrics_addr: "127.0.0.1:9184".to_string(),
+            health_addr: "127.0.0.1:9185".to_string(),
+            confluence_strategy: false,
+            min_confluence: 3,
+            regime_lookback: 288,
+            regime_tags: Vec::new(),
+            disabled_strategies: Vec::new(),
+            shadow_strategies: Vec::new(),
+            dump_views: false,
         }
     }
 
@@ -982,32 +2178,335 @@ mod tests {
     // Config tests
     // ==========================================================================
 
+    #[test]
+    fn test_regime_tags_parse_and_match_by_prefix() {
+        let cfg = Config {
+            regime_tags: parse_regime_tags("carry=strong_bear|mild_bear, mom-1=ranging,").unwrap(),
+            ..test_config()
+        };
+        assert_eq!(cfg.regime_tags.len(), 2);
+        assert_eq!(
+            cfg.regime_tags_for("carry-2"),
+            vec![TrendRegime::StrongBear, TrendRegime::MildBear]
+        );
+        assert_eq!(
+            cfg.regime_tags_for("mom-1:ETHUSDT"),
+            vec![TrendRegime::Ranging]
+        );
+        assert!(cfg.regime_tags_for("mom-0").is_empty());
+
+        // A misspelt regime or a missing prefix fails the checked load
+        // rather than leaving the strategy untagged
+        for bad in [
+            "carry=strong_baer",
+            "carry=mild_bear|sideways",
+            "=ranging",
+            "carry",
+        ] {
+            let e = Config::from_lookup_checked(lookup(&[("REGIME_TAGS", bad)]))
+                .err()
+                .unwrap();
+            assert!(e.contains("REGIME_TAGS entry"), "{}", e);
+        }
+        let loose = Config::load(lookup(&[("REGIME_TAGS", "mom=ranging,carry=strong_baer")])).0;
+        assert!(loose.regime_tags.is_empty());
+    }
+
+    fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_candle_feed_stale_boundary() {
+        let mut cfg = Config::from_env();
+        cfg.candle_granularity = 300;
+        cfg.max_latency_ms = 60_000;
+        let last = 10_000;
+        // Due at +300s, tolerated until +360s
+        assert!(!candle_feed_stale(last, last, &cfg));
+        assert!(!candle_feed_stale(last, last + 300, &cfg));
+        assert!(!candle_feed_stale(last, last + 360, &cfg));
+        assert!(candle_feed_stale(last, last + 361, &cfg));
+        // Clock skew (now before last advance) is never stale
+        assert!(!candle_feed_stale(last, last - 5, &cfg));
+    }
+
+    #[test]
+    fn test_checked_config_accepts_defaults() {
+        let cfg = Config::from_lookup_checked(lookup(&[])).unwrap();
+        assert_eq!(cfg.max_position_pct, 0.05);
+        assert!(test_config().validate().is_ok());
+    }
+
+    #[test]
+    fn test_use_testnet_selects_sandbox_hosts() {
+        let live = Config::from_lookup_checked(lookup(&[])).unwrap();
+        assert!(!live.use_testnet);
+        assert_eq!(live.binance_base, "https://api.binance.com");
+        assert_eq!(live.binance_fapi_base, "https://fapi.binance.com");
+        assert_eq!(live.binance_ws_base, "wss://stream.binance.com:9443");
+        assert_eq!(live.kraken_base, "https://api.kraken.com");
+
+        let test = Config::from_lookup_checked(lookup(&[("USE_TESTNET", "true")])).unwrap();
+        assert!(test.use_testnet);
+        assert_eq!(test.binance_base, "https://testnet.binance.vision");
+        assert_eq!(test.binance_fapi_base, "https://testnet.binancefuture.com");
+        assert_eq!(test.binance_ws_base, "wss://stream.testnet.binance.vision");
+        // No Kraken spot sandbox: never fall through to the live host
+        assert_eq!(test.kraken_base, "");
+
+        // An explicit base still overrides the testnet default
+        let custom = Config::from_lookup_checked(lookup(&[
+            ("USE_TESTNET", "1"),
+            ("KRAKEN_BASE", "http://localhost:8080"),
+        ]))
+        .unwrap();
+        assert_eq!(custom.kraken_base, "http://localhost:8080");
+        assert_eq!(custom.binance_base, TESTNET_HOSTS.binance);
+    }
+
+    #[test]
+    fn test_live_keys_default_market_to_spot() {
+        let paper = Config::from_lookup_checked(lookup(&[])).unwrap();
+        assert_eq!(paper.market_type, MarketType::Perp);
+        let keys = [("API_KEY", "k"), ("API_SECRET", "s")];
+        let live = Config::from_lookup_checked(lookup(&keys)).unwrap();
+        assert_eq!(live.market_type, MarketType::Spot);
+        // An explicit setting wins
+        let perp =
+            Config::from_lookup_checked(lookup(&[keys[0], keys[1], ("MARKET_TYPE", "perp")]));
+        assert_eq!(perp.unwrap().market_type, MarketType::Perp);
+    }
+
+    #[test]
+    fn test_malformed_value_errors_instead_of_defaulting() {
+        let bad = lookup(&[("MAX_POS_PCT", "5%"), ("WINDOW", "-3")]);
+        // The lenient loader still falls back
+        assert_eq!(Config::load(&bad).0.max_position_pct, 0.05);
+        let err = Config::from_lookup_checked(&bad).err().unwrap();
+        assert!(err.contains("MAX_POS_PCT=\"5%\""), "{}", err);
+        assert!(err.contains("WINDOW=\"-3\""), "{}", err);
+    }
+
+    #[test]
+    fn test_edge_costs_default_to_the_fill_model() {
+        let cfg = Config::from_lookup_checked(lookup(&[
+            ("PAPER_FEE_RATE", "0.0004"),
+            ("VOL_SLIP_MULT", "1.5"),
+        ]))
+        .unwrap();
+        assert!((cfg.edge_fee_bps - 4.0).abs() < 1e-9);
+        assert_eq!(cfg.edge_slip_vol_mult, 1.5);
+
+        // Explicit edge assumptions still win
+        let cfg = Config::from_lookup_checked(lookup(&[
+            ("PAPER_FEE_RATE", "0.0004"),
+            ("EDGE_FEE_BPS", "0"),
+            ("EDGE_SLIP_VOL_MULT", "0"),
+        ]))
+        .unwrap();
+        assert_eq!((cfg.edge_fee_bps, cfg.edge_slip_vol_mult), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_unrecognised_enum_or_flag_errors_instead_of_defaulting() {
+        let bad = lookup(&[
+            ("SIZING_MODE", "kely"),
+            ("RECONCILE_ACTION", "autocorect"),
+            ("CONTRACT_KIND", "inverted"),
+            ("DELEVER_CURVE", "cubic"),
+            ("LIQ_SCORE_SOURCE", "zz"),
+            ("MARKET_TYPE", "margin"),
+            ("PERSIST_HWM", "nope"),
+            ("USE_TESTNET", "ture"),
+            ("DUMP_VIEWS", "2"),
+        ]);
+        let err = Config::from_lookup_checked(&bad).err().unwrap();
+        for key in [
+            "SIZING_MODE",
+            "RECONCILE_ACTION",
+            "CONTRACT_KIND",
+            "DELEVER_CURVE",
+            "LIQ_SCORE_SOURCE",
+            "MARKET_TYPE",
+            "PERSIST_HWM",
+            "USE_TESTNET",
+            "DUMP_VIEWS",
+        ] {
+            assert!(err.contains(&format!("{}=", key)), "{}: {}", key, err);
+        }
+        // The lenient loader still falls back to each default
+        let (cfg, _) = Config::load(&bad);
+        assert_eq!(cfg.sizing_mode, SizingMode::Fixed);
+        assert_eq!(cfg.reconcile_action, ReconcileAction::Halt);
+        assert!(cfg.persist_hwm);
+        assert!(!cfg.use_testnet);
+
+        let good = Config::from_lookup_checked(lookup(&[
+            ("SIZING_MODE", "Kelly"),
+            ("RECONCILE_ACTION", "halt"),
+            ("CONTRACT_KIND", "linear"),
+            ("PERSIST_HWM", "off"),
+            ("USE_TESTNET", "YES"),
+        ]))
+        .unwrap();
+        assert_eq!(good.sizing_mode, SizingMode::Kelly);
+        assert_eq!(good.contract_kind, ContractKind::Linear);
+        assert!(!good.persist_hwm);
+        assert!(good.use_testnet);
+    }
+
+    #[test]
+    fn test_validate_reports_each_range_error() {
+        let err = |vars: &[(&str, &str)]| Config::from_lookup_checked(lookup(vars)).err().unwrap();
+
+        let e = err(&[("MAX_POS_PCT", "1.5")]);
+        assert!(
+            e.contains("MAX_POS_PCT must be in [0, 1], got 1.5"),
+            "{}",
+            e
+        );
+
+        let e = err(&[("EMA_FAST", "30"), ("EMA_SLOW", "20")]);
+        assert!(
+            e.contains("EMA_FAST (30) must be shorter than EMA_SLOW (20)"),
+            "{}",
+            e
+        );
+
+        let e = err(&[("VOL_WINDOW", "0"), ("PERSIST_SECS", "0")]);
+        assert!(e.contains("VOL_WINDOW must be positive"), "{}", e);
+        assert!(e.contains("PERSIST_SECS must be positive"), "{}", e);
+
+        let e = err(&[("VOL_LOW", "2.0"), ("STOP_LOSS", "-0.01")]);
+        assert!(
+            e.contains("VOL_LOW (2) must be below VOL_HIGH (1.6)"),
+            "{}",
+            e
+        );
+        assert!(e.contains("STOP_LOSS must be in [0, 1]"), "{}", e);
+
+        let e = err(&[("LIQ_BASELINE_BARS", "5")]);
+        assert!(
+            e.contains("LIQ_BASELINE_BARS must be at least 20, got 5"),
+            "{}",
+            e
+        );
+    }
+
+    #[test]
+    fn test_disabled_strategies_parse_and_match() {
+        assert_eq!(
+            parse_strategy_ids(Some(" churn-3, carry-1:ETHUSDT,,")),
+            vec!["churn-3", "carry-1:ETHUSDT"]
+        );
+        assert!(parse_strategy_ids(None).is_empty());
+
+        let cfg = Config {
+            disabled_strategies: parse_strategy_ids(Some("churn-3,carry-1:ETHUSDT")),
+            ..test_config()
+        };
+        assert!(!cfg.strategy_enabled("churn-3"));
+        assert!(!cfg.strategy_enabled("churn-3:BTCUSDT"));
+        assert!(!cfg.strategy_enabled("carry-1:ETHUSDT"));
+        // Neither a longer id sharing the prefix nor another symbol
+        assert!(cfg.strategy_enabled("churn-30"));
+        assert!(cfg.strategy_enabled("carry-1:BTCUSDT"));
+        assert!(test_config().strategy_enabled("churn-3"));
+
+        let cfg = Config {
+            shadow_strategies: parse_strategy_ids(Some("carry-2")),
+            ..test_config()
+        };
+        assert!(cfg.is_shadow("carry-2:ETHUSDT"));
+        assert!(!cfg.is_shadow("carry-1"));
+    }
+
+    #[test]
+    fn test_reference_price_divergence_trips_guard() {
+        let cfg = test_config(); // max_liquidity_spread = 1%
+        assert!(check_price_consistency(50_050.0, 50_000.0, cfg.max_liquidity_spread).is_ok());
+        let err =
+            check_price_consistency(52_500.0, 50_000.0, cfg.max_liquidity_spread).unwrap_err();
+        assert!(err.starts_with("price_divergence"), "{}", err);
+        assert!(check_price_consistency(47_500.0, 50_000.0, cfg.max_liquidity_spread).is_err());
+        assert!(check_price_consistency(0.0, 50_000.0, cfg.max_liquidity_spread).is_err());
+    }
+
     #[test]
     fn test_sleep_until_next_candle_boundary() {
         let cfg = Config {
             candle_granularity: 300,
+            candle_granularity_ms: 300_000,
             ..test_config()
         };
 
         // Exactly at boundary
-        assert_eq!(cfg.sleep_until_next_candle(300), 300);
-        assert_eq!(cfg.sleep_until_next_candle(600), 300);
+        assert_eq!(cfg.sleep_until_next_candle_ms(300_000), 300_000);
+        assert_eq!(cfg.sleep_until_next_candle_ms(600_000), 300_000);
 
         // Just after boundary
-        assert_eq!(cfg.sleep_until_next_candle(301), 299);
-        assert_eq!(cfg.sleep_until_next_candle(599), 1);
+        assert_eq!(cfg.sleep_until_next_candle_ms(301_000), 299_000);
+        assert_eq!(cfg.sleep_until_next_candle_ms(599_000), 1_000);
 
         // Middle of candle
-        assert_eq!(cfg.sleep_until_next_candle(450), 150);
+        assert_eq!(cfg.sleep_until_next_candle_ms(450_000), 150_000);
     }
 
     #[test]
     fn test_sleep_until_next_candle_zero() {
         let cfg = Config {
             candle_granularity: 300,
+            candle_granularity_ms: 300_000,
+            ..test_config()
+        };
+        assert_eq!(cfg.sleep_until_next_candle_ms(0), 300_000);
+    }
+
+    #[test]
+    fn test_sleep_until_next_candle_ms_boundary() {
+        let cfg = Config {
+            candle_granularity: 1,
+            candle_granularity_ms: 100,
+            ..test_config()
+        };
+
+        // Exactly at boundary
+        assert_eq!(cfg.sleep_until_next_candle_ms(1_700_000_000_000), 100);
+        assert_eq!(cfg.sleep_until_next_candle_ms(1_700_000_000_100), 100);
+
+        // Just after boundary / just before the next
+        assert_eq!(cfg.sleep_until_next_candle_ms(1_700_000_000_101), 99);
+        assert_eq!(cfg.sleep_until_next_candle_ms(1_700_000_000_199), 1);
+
+        // Middle of candle
+        assert_eq!(cfg.sleep_until_next_candle_ms(1_700_000_000_050), 50);
+        assert_eq!(cfg.sleep_until_next_candle_ms(0), 100);
+    }
+
+    #[test]
+    fn test_sub_second_candles_round_holds_up() {
+        let cfg = Config {
+            candle_granularity: 1,
+            candle_granularity_ms: 100,
             ..test_config()
         };
-        assert_eq!(cfg.sleep_until_next_candle(0), 300);
+        // 25 x 100ms = 2.5s: never treat a hold as over early
+        assert_eq!(cfg.candles_to_secs(25), 3);
+        assert_eq!(cfg.candles_to_secs(10), 1);
+        assert_eq!(cfg.candles_to_secs(0), 0);
+        // Two-candle staleness budget still allows a second of clock resolution
+        assert_eq!(cfg.candles_to_secs(2), 1);
+
+        let minutes = Config {
+            candle_granularity_ms: 300_000,
+            ..cfg
+        };
+        assert_eq!(minutes.candles_to_secs(3), 900);
     }
 
     // ==========================================================================
@@ -1095,6 +2594,53 @@ mod tests {
         assert!(snapshot.z_vol.abs() < 1.0);
     }
 
+    /// Alternating +/-1 noise around 0 for `n` bars, then the same noise around 5.
+    fn regime_shift_zscores(decay: f64, bars_after: usize) -> f64 {
+        let (mut mean, mut m2, mut n) = (0.0, 0.0, 0u64);
+        let noise = |i: usize| [1.0, -1.0][i % 2];
+        for i in 0..2000 {
+            push_stat(noise(i), &mut mean, &mut m2, &mut n, decay);
+        }
+        let mut last = 0.0;
+        for i in 0..bars_after {
+            last = 5.0 + noise(i);
+            push_stat(last, &mut mean, &mut m2, &mut n, decay);
+        }
+        let ind = IndicatorState::new(4, 4).with_decay(decay);
+        ind.zscore(last, mean, m2, n)
+    }
+
+    #[test]
+    fn test_ew_zscore_adapts_after_regime_shift() {
+        let cumulative = regime_shift_zscores(0.0, 10);
+        let ew = regime_shift_zscores(0.8, 10);
+        // Cumulative baseline still treats the new level as a >3 sigma outlier
+        assert!(cumulative > 3.0, "cumulative z={}", cumulative);
+        // EW baseline has absorbed the new regime within ten bars
+        assert!(ew.abs() < 2.0, "ew z={}", ew);
+    }
+
+    #[test]
+    fn test_ew_zscore_flags_shift_on_first_bar() {
+        // Both variants must still flag the very first bar of the new regime
+        let cumulative = regime_shift_zscores(0.0, 1);
+        let ew = regime_shift_zscores(0.8, 1);
+        assert!(cumulative > 4.0);
+        assert!(ew > 1.0, "ew z={}", ew);
+    }
+
+    #[test]
+    fn test_zero_decay_matches_cumulative() {
+        let mut a = IndicatorState::new(6, 24);
+        let mut b = IndicatorState::new(6, 24).with_decay(0.0);
+        for i in 0..200 {
+            let px = 100.0 + (i as f64 * 0.3).sin() * 2.0;
+            a.update(px, 1000.0);
+            b.update(px, 1000.0);
+        }
+        assert_eq!(a.snapshot().z_momentum, b.snapshot().z_momentum);
+    }
+
     #[test]
     fn test_indicator_vwap_calculation() {
         let mut ind = IndicatorState::new(4, 9);
@@ -1106,6 +2652,32 @@ mod tests {
 
         let vwap = ind.sum_px_vol / ind.sum_vol;
         assert!((vwap - 106.666).abs() < 0.01);
+        // Both bars fit in the rolling window, so the two agree
+        assert!((ind.snapshot().vwap - vwap).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_rolling_vwap_tracks_recent_price_while_session_lags() {
+        let mut ind = IndicatorState::new(4, 9).with_vwap_window(10);
+        for _ in 0..100 {
+            ind.update(100.0, 1000.0);
+        }
+        for _ in 0..10 {
+            ind.update(120.0, 1000.0);
+        }
+        let snap = ind.snapshot();
+        // Window holds only the new level
+        assert!((snap.vwap - 120.0).abs() < 1e-9, "rolling {}", snap.vwap);
+        // 100 bars at 100 and 10 at 120
+        assert!((snap.session_vwap - 101.818).abs() < 0.01);
+        // Stretch is measured against the rolling VWAP, not the stale session one
+        assert!(snap.stretch.abs() < 1e-9, "stretch {}", snap.stretch);
+
+        // Half the window rolled over: equal volume at 120 and 130
+        for _ in 0..5 {
+            ind.update(130.0, 1000.0);
+        }
+        assert!((ind.snapshot().vwap - 125.0).abs() < 1e-9);
     }
 
     // ==========================================================================
@@ -1133,6 +2705,258 @@ mod tests {
         assert_eq!(view.indicators.ema_fast, 100.0); // First candle initializes EMA
     }
 
+    #[test]
+    fn test_merge_thresholds_applies_only_whitelisted_fields() {
+        let mut cfg = test_config();
+        let before = cfg.clone();
+        let overrides = serde_json::json!({
+            "entry_threshold": 1.7,
+            "stop_loss": 0.01,
+            "take_profit": before.take_profit,
+            "symbol": "ETHUSDT",
+            "wal_path": "/tmp/other.wal",
+            "max_position_pct": 0.9,
+        });
+        let changes = cfg.merge_thresholds(&overrides).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                ThresholdChange {
+                    field: "entry_threshold",
+                    old: before.entry_threshold,
+                    new: 1.7
+                },
+                ThresholdChange {
+                    field: "stop_loss",
+                    old: before.stop_loss,
+                    new: 0.01
+                },
+            ]
+        );
+        assert_eq!(cfg.entry_threshold, 1.7);
+        assert_eq!(cfg.stop_loss, 0.01);
+        // Structural and risk fields are untouched
+        assert_eq!(cfg.symbol, before.symbol);
+        assert_eq!(cfg.wal_path, before.wal_path);
+        assert_eq!(cfg.max_position_pct, before.max_position_pct);
+
+        // One bad value rejects the whole file
+        let bad = serde_json::json!({ "exit_threshold": 0.1, "mom_th": "high" });
+        assert!(cfg.merge_thresholds(&bad).is_err());
+        assert_eq!(cfg.exit_threshold, before.exit_threshold);
+        assert!(cfg.merge_thresholds(&serde_json::json!([1, 2])).is_err());
+
+        // Numbers that startup validation would reject are refused as well
+        let out_of_range = serde_json::json!({ "exit_threshold": 0.1, "stop_loss": 1.5 });
+        let err = cfg.merge_thresholds(&out_of_range).unwrap_err();
+        assert!(err.contains("STOP_LOSS must be in [0, 1]"), "{}", err);
+        assert_eq!(cfg.exit_threshold, before.exit_threshold);
+        assert_eq!(cfg.stop_loss, 0.01);
+        assert!(cfg
+            .merge_thresholds(&serde_json::json!({ "threshold_band": -0.2 }))
+            .is_err());
+    }
+
+    #[test]
+    fn test_invalid_candles_are_rejected_and_indicators_stay_finite() {
+        let cfg = test_config();
+        let mut market = MarketState::new(cfg.clone());
+        let good = |ts: u64, px: f64| ExCandle {
+            ts,
+            o: px,
+            h: px + 1.0,
+            l: px - 1.0,
+            c: px,
+            v: 1000.0,
+        };
+        for i in 0..10 {
+            assert!(market.on_candle(good(1000 + i * 300, 100.0 + i as f64)));
+        }
+        let before = market.view(&cfg.symbol).indicators;
+
+        let bad = [
+            (
+                "non_finite",
+                ExCandle {
+                    c: f64::NAN,
+                    ..good(4000, 110.0)
+                },
+            ),
+            (
+                "non_finite",
+                ExCandle {
+                    h: f64::INFINITY,
+                    ..good(4300, 110.0)
+                },
+            ),
+            (
+                "non_positive_price",
+                ExCandle {
+                    c: 0.0,
+                    ..good(4600, 110.0)
+                },
+            ),
+            (
+                "non_positive_price",
+                ExCandle {
+                    l: -1.0,
+                    ..good(4900, 110.0)
+                },
+            ),
+            (
+                "negative_volume",
+                ExCandle {
+                    v: -5.0,
+                    ..good(5200, 110.0)
+                },
+            ),
+        ];
+        for (defect, candle) in bad {
+            assert_eq!(candle_defect(&candle), Some(defect));
+            assert!(!market.on_candle(candle));
+        }
+
+        // Nothing from the bad candles reached the buffer or indicators
+        let view = market.view(&cfg.symbol);
+        assert_eq!(view.last.ts, 1000 + 9 * 300);
+        assert_eq!(view.indicators.samples, before.samples);
+        assert_eq!(view.indicators.vwap, before.vwap);
+
+        assert!(market.on_candle(good(5500, 111.0)));
+        let ind = market.view(&cfg.symbol).indicators;
+        for x in [
+            ind.ema_fast,
+            ind.vwap,
+            ind.vol,
+            ind.stretch,
+            ind.z_momentum,
+            ind.z_vol,
+            ind.z_stretch,
+            ind.z_volume_spike,
+            ind.atr,
+        ] {
+            assert!(x.is_finite());
+        }
+    }
+
+    #[test]
+    fn test_warmup_holds_strategy_until_min_history() {
+        let cfg = test_config();
+        let need = cfg.min_history();
+        assert_eq!(need, cfg.ema_slow.max(cfg.vol_window) as u64);
+        let mut market = MarketState::new(cfg.clone());
+        let mut first_eligible = None;
+        for i in 0..need + 5 {
+            let px = 100.0 + (i as f64 * 0.7).sin();
+            market.on_candle(ExCandle {
+                ts: 1000 + i * 300,
+                o: px,
+                h: px,
+                l: px,
+                c: px,
+                v: 1000.0,
+            });
+            let view = market.view(&cfg.symbol);
+            assert_eq!(view.indicators.samples, i + 1);
+            if first_eligible.is_none() && cfg.is_warmed_up(view.indicators.samples) {
+                first_eligible = Some(i + 1);
+            }
+        }
+        // Strategy is held for the first `need - 1` candles and released on the `need`th
+        assert_eq!(first_eligible, Some(need));
+    }
+
+    #[test]
+    fn test_market_state_two_symbols_independent() {
+        let cfg = test_config();
+        let mut market = MarketState::new(cfg);
+
+        for i in 0..20u64 {
+            let btc = 100.0 + i as f64;
+            let eth = 50.0 - i as f64 * 0.5;
+            market.on_candle_for(
+                "BTCUSDT",
+                ExCandle {
+                    ts: 1000 + i * 300,
+                    o: btc,
+                    h: btc,
+                    l: btc,
+                    c: btc,
+                    v: 1000.0,
+                },
+            );
+            market.on_candle_for(
+                "ETHUSDT",
+                ExCandle {
+                    ts: 1000 + i * 300,
+                    o: eth,
+                    h: eth,
+                    l: eth,
+                    c: eth,
+                    v: 3000.0,
+                },
+            );
+        }
+        market.update_aux(
+            "ETHUSDT",
+            MarketAux {
+                funding_rate: 0.0005,
+                ..Default::default()
+            },
+        );
+
+        let btc = market.view("BTCUSDT");
+        let eth = market.view("ETHUSDT");
+        assert_eq!(btc.last.c, 119.0);
+        assert_eq!(eth.last.c, 40.5);
+        // Uptrend vs downtrend: momentum has opposite sign per symbol
+        assert!(btc.indicators.momentum > 0.0);
+        assert!(eth.indicators.momentum < 0.0);
+        assert!((btc.indicators.vwap - eth.indicators.vwap).abs() > 1.0);
+        assert_eq!(btc.aux.funding_rate, 0.0);
+        assert_eq!(eth.aux.funding_rate, 0.0005);
+    }
+
+    #[test]
+    fn test_parse_symbols() {
+        assert_eq!(parse_symbols(None, "BTCUSDT"), vec!["BTCUSDT"]);
+        assert_eq!(parse_symbols(Some(" "), "BTCUSDT"), vec!["BTCUSDT"]);
+        assert_eq!(
+            parse_symbols(Some("btcusdt, ETHUSDT,"), "SOLUSDT"),
+            vec!["BTCUSDT", "ETHUSDT"]
+        );
+    }
+
+    #[test]
+    fn test_default_set_per_symbol() {
+        let single = StrategyInstance::build_default_set_per_symbol(test_config());
+        assert_eq!(single.len(), 3);
+        assert_eq!(single[0].id, "mom-0");
+
+        let cfg = Config {
+            symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            ..test_config()
+        };
+        let multi = StrategyInstance::build_default_set_per_symbol(cfg);
+        assert_eq!(multi.len(), 6);
+        assert_eq!(multi[3].id, "mom-0:ETHUSDT");
+        assert_eq!(multi[3].symbol, "ETHUSDT");
+        let eth = multi.iter().filter(|s| s.symbol == "ETHUSDT").count();
+        assert_eq!(eth, 3);
+
+        let cfg = Config {
+            symbols: vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            confluence_strategy: true,
+            ..test_config()
+        };
+        let with_confluence = StrategyInstance::build_default_set_per_symbol(cfg);
+        assert_eq!(with_confluence.len(), 8);
+        let ids: Vec<&str> = with_confluence[6..].iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["confluence:BTCUSDT", "confluence:ETHUSDT"]);
+        assert_eq!(with_confluence[7].symbol, "ETHUSDT");
+        assert_eq!(with_confluence[7].strategy.id(), "confluence");
+    }
+
     #[test]
     fn test_market_state_view_missing_symbol() {
         let cfg = test_config();
@@ -1159,6 +2983,7 @@ mod tests {
             has_borrow: true,
             has_liquidations: true,
             has_depeg: true,
+            next_funding_ts: 0,
         };
         market.update_aux(&cfg.symbol, aux);
 
@@ -1179,21 +3004,7 @@ mod tests {
             start_delay: 1000,
             cfg: cfg.clone(),
         };
-        let mut state = StrategyState {
-            portfolio: PortfolioState {
-                cash: 1000.0,
-                position: 0.0,
-                entry_price: 0.0,
-                equity: 1000.0,
-            },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
-        };
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
 
         // Create a view with ts < start_delay
         let view = MarketView {
@@ -1226,21 +3037,7 @@ mod tests {
             start_delay: 0,
             cfg: cfg.clone(),
         };
-        let mut state = StrategyState {
-            portfolio: PortfolioState {
-                cash: 1000.0,
-                position: 0.0,
-                entry_price: 0.0,
-                equity: 1000.0,
-            },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
-        };
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
 
         // High volatility ratio triggers pause
         let view = MarketView {
@@ -1278,21 +3075,7 @@ mod tests {
             start_delay: 0,
             cfg: cfg.clone(),
         };
-        let mut state = StrategyState {
-            portfolio: PortfolioState {
-                cash: 1000.0,
-                position: 0.0,
-                entry_price: 0.0,
-                equity: 1000.0,
-            },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
-        };
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
 
         // High positive funding + low borrow = short opportunity
         let view = MarketView {
@@ -1330,6 +3113,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_simple_momentum_carry_abstains_without_aux_flags() {
+        let mut cfg = test_config();
+        cfg.funding_high = 0.0001;
+        cfg.funding_spread = 0.00005;
+        let mut strategy = SimpleMomentum {
+            id: "test".to_string(),
+            start_delay: 0,
+            cfg: cfg.clone(),
+        };
+        let flat = || StrategyState::with_capital(1000.0, ContractKind::Linear);
+
+        // Same actionable numbers as the carry-short case, but the flags say
+        // at least one reading is a defaulted zero rather than a fetch
+        for (has_funding, has_borrow) in [(true, false), (false, true), (false, false)] {
+            let view = MarketView {
+                symbol: "BTCUSDT",
+                last: crate::strategy::Candle {
+                    ts: 1000,
+                    o: 100.0,
+                    h: 101.0,
+                    l: 99.0,
+                    c: 100.0,
+                    v: 1000.0,
+                },
+                indicators: IndicatorSnapshot {
+                    z_momentum: 2.0,
+                    z_vol: 1.0,
+                    z_volume_spike: 1.0,
+                    vol: 1.0,
+                    vol_mean: 1.0,
+                    ..Default::default()
+                },
+                aux: MarketAux {
+                    funding_rate: 0.0005,
+                    borrow_rate: 0.0,
+                    has_funding,
+                    has_borrow,
+                    ..Default::default()
+                },
+            };
+            let action = strategy.update(view, &mut flat());
+            assert!(
+                !matches!(action, Action::Sell { .. }),
+                "carry short taken with has_funding={} has_borrow={}",
+                has_funding,
+                has_borrow
+            );
+        }
+    }
+
     #[test]
     fn test_simple_momentum_liquidation_cascade() {
         let mut cfg = test_config();
@@ -1339,21 +3173,7 @@ mod tests {
             start_delay: 0,
             cfg: cfg.clone(),
         };
-        let mut state = StrategyState {
-            portfolio: PortfolioState {
-                cash: 1000.0,
-                position: 0.0,
-                entry_price: 0.0,
-                equity: 1000.0,
-            },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
-        };
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
 
         // High liquidation score + positive momentum = buy with cascade
         let view = MarketView {
@@ -1405,14 +3225,12 @@ mod tests {
                 position: 0.1,
                 entry_price: 100.0,
                 equity: 1000.0,
+                contract: ContractKind::Linear,
             },
-            metrics: MetricsState::default(),
             last_trade_ts: 500,
-            last_loss_ts: 0,
-            trading_halted: false,
             trades_today: 1,
-            trade_day: 0,
             order_seq: 1,
+            ..StrategyState::with_capital(1000.0, ContractKind::Linear)
         };
 
         // Price moved up 1% (above take_profit 0.6%)
@@ -1458,14 +3276,12 @@ mod tests {
                 position: 0.1,
                 entry_price: 100.0,
                 equity: 1000.0,
+                contract: ContractKind::Linear,
             },
-            metrics: MetricsState::default(),
             last_trade_ts: 500,
-            last_loss_ts: 0,
-            trading_halted: false,
             trades_today: 1,
-            trade_day: 0,
             order_seq: 1,
+            ..StrategyState::with_capital(1000.0, ContractKind::Linear)
         };
 
         // Price moved down 0.5% (above stop_loss 0.4%)
@@ -1492,6 +3308,61 @@ mod tests {
         assert!(matches!(action, Action::Close), "Should close on stop loss");
     }
 
+    #[test]
+    fn test_atr_stop_distance_widens_with_atr() {
+        let mut cfg = test_config();
+        cfg.stop_loss_mode = StopMode::AtrMultiple(2.0);
+        cfg.take_profit_mode = StopMode::AtrMultiple(3.0);
+        let entry = 100.0;
+        let (calm_atr, wild_atr) = (1.0, 3.0);
+
+        // Absolute stop distance is k * ATR, so 3x the ATR means 3x the distance
+        let calm = cfg.stop_loss_mode.threshold(cfg.stop_loss, calm_atr, entry) * entry;
+        let wild = cfg.stop_loss_mode.threshold(cfg.stop_loss, wild_atr, entry) * entry;
+        assert!((calm - 2.0).abs() < 1e-9);
+        assert!((wild / calm - 3.0).abs() < 1e-9);
+
+        // A -3% move stops out in the calm regime but not in the volatile one
+        assert!(check_stop_loss(-0.03, calm_atr, entry, &cfg));
+        assert!(!check_stop_loss(-0.03, wild_atr, entry, &cfg));
+        assert!(check_take_profit(0.03, calm_atr, entry, &cfg));
+        assert!(!check_take_profit(0.03, wild_atr, entry, &cfg));
+    }
+
+    #[test]
+    fn test_fixed_stop_ignores_atr_and_atr_mode_falls_back() {
+        let mut cfg = test_config();
+        cfg.stop_loss = 0.004;
+        assert!(check_stop_loss(-0.005, 50.0, 100.0, &cfg));
+        // ATR mode before any ATR is available uses the fixed fraction
+        cfg.stop_loss_mode = StopMode::AtrMultiple(2.0);
+        assert!(check_stop_loss(-0.005, 0.0, 100.0, &cfg));
+        assert_eq!(
+            StopMode::from_multiple(Some("1.5")),
+            StopMode::AtrMultiple(1.5)
+        );
+        assert_eq!(StopMode::from_multiple(Some("0")), StopMode::Fixed);
+        assert_eq!(StopMode::from_multiple(None), StopMode::Fixed);
+    }
+
+    #[test]
+    fn test_market_view_exposes_atr() {
+        let cfg = test_config();
+        let mut market = MarketState::new(cfg.clone());
+        for i in 0..50u64 {
+            market.on_candle(ExCandle {
+                ts: 1000 + i * 300,
+                o: 100.0,
+                h: 101.0,
+                l: 99.0,
+                c: 100.0,
+                v: 1000.0,
+            });
+        }
+        let atr = market.view(&cfg.symbol).indicators.atr;
+        assert!((atr - 2.0).abs() < 1e-6, "atr={}", atr);
+    }
+
     #[test]
     fn test_simple_momentum_time_stop() {
         let mut cfg = test_config();
@@ -1509,14 +3380,11 @@ mod tests {
                 position: 0.1,
                 entry_price: 100.0,
                 equity: 1000.0,
+                contract: ContractKind::Linear,
             },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
             trades_today: 1,
-            trade_day: 0,
             order_seq: 1,
+            ..StrategyState::with_capital(1000.0, ContractKind::Linear)
         };
 
         // 12 candles * 300 seconds = 3600 seconds elapsed
@@ -1556,21 +3424,7 @@ mod tests {
             id: "carry-test".to_string(),
             cfg: cfg.clone(),
         };
-        let mut state = StrategyState {
-            portfolio: PortfolioState {
-                cash: 1000.0,
-                position: 0.0,
-                entry_price: 0.0,
-                equity: 1000.0,
-            },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
-        };
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
 
         // High negative funding + low borrow = long opportunity
         let view = MarketView {
@@ -1600,6 +3454,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_carry_opportunistic_abstains_without_aux_flags() {
+        let mut cfg = test_config();
+        cfg.funding_high = 0.0001;
+        cfg.funding_spread = 0.00005;
+        let mut strategy = CarryOpportunistic {
+            id: "carry-test".to_string(),
+            cfg: cfg.clone(),
+        };
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
+
+        // Funding is live but borrow defaulted to zero: not evidence of carry
+        let view = MarketView {
+            symbol: "BTCUSDT",
+            last: crate::strategy::Candle {
+                ts: 1000,
+                o: 100.0,
+                h: 101.0,
+                l: 99.0,
+                c: 100.0,
+                v: 1000.0,
+            },
+            indicators: IndicatorSnapshot::default(),
+            aux: MarketAux {
+                funding_rate: -0.0005,
+                borrow_rate: 0.0,
+                has_funding: true,
+                has_borrow: false,
+                ..Default::default()
+            },
+        };
+
+        let action = strategy.update(view, &mut state);
+        assert!(
+            matches!(action, Action::Hold),
+            "Should abstain when borrow is not a real reading"
+        );
+    }
+
     #[test]
     fn test_carry_opportunistic_vol_exit() {
         let mut cfg = test_config();
@@ -1614,14 +3507,12 @@ mod tests {
                 position: 0.1,
                 entry_price: 100.0,
                 equity: 1000.0,
+                contract: ContractKind::Linear,
             },
-            metrics: MetricsState::default(),
             last_trade_ts: 500,
-            last_loss_ts: 0,
-            trading_halted: false,
             trades_today: 1,
-            trade_day: 0,
             order_seq: 1,
+            ..StrategyState::with_capital(1000.0, ContractKind::Linear)
         };
 
         // Vol spike while in position = close
@@ -1655,21 +3546,7 @@ mod tests {
             id: "carry-test".to_string(),
             cfg: cfg.clone(),
         };
-        let mut state = StrategyState {
-            portfolio: PortfolioState {
-                cash: 1000.0,
-                position: 0.0,
-                entry_price: 0.0,
-                equity: 1000.0,
-            },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
-        };
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
 
         // Negative depeg (stablecoin below peg) = buy expecting snapback
         let view = MarketView {
@@ -1742,6 +3619,43 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_starting_capital_sets_sizing_base() {
+        let mut cfg = test_config();
+        cfg.starting_capital = 10_000.0;
+        let risk = crate::risk::RiskEngine::new(cfg.clone());
+        let sets = [
+            StrategyInstance::build_default_set(cfg.clone()),
+            StrategyInstance::build_churn_set(cfg.clone()),
+            StrategyInstance::build_carry_event_set(cfg.clone()),
+        ];
+        for s in sets.iter().flatten() {
+            assert_eq!(s.state.portfolio.cash, 10_000.0);
+            assert_eq!(s.state.portfolio.equity, 10_000.0);
+            // 5% of $10k at $100 caps the position at 5 units, not 0.5
+            let sized = risk.clamp_to_position_cap(&s.state, Action::Buy { qty: 50.0 }, 100.0);
+            assert!(matches!(sized, Action::Buy { qty } if (qty - 5.0).abs() < 1e-9));
+        }
+
+        let mut inst = StrategyInstance::build_default_set(cfg).remove(0);
+        inst.state.portfolio.apply_fill(Fill {
+            price: 100.0,
+            qty: 1.0,
+            fee: 0.0,
+            ts: 1,
+        });
+        // One unit at $100 is 1% of a $10k book
+        assert!((inst.state.portfolio.exposure(100.0) - 0.01).abs() < 1e-12);
+        inst.state.portfolio.apply_fill(Fill {
+            price: 110.0,
+            qty: -1.0,
+            fee: 0.0,
+            ts: 2,
+        });
+        // PnL is reported against the $10k base
+        assert!((inst.state.portfolio.equity - 10_010.0).abs() < 1e-9);
+    }
+
     fn make_view(
         ts: u64,
         price: f64,
@@ -1765,21 +3679,7 @@ mod tests {
     }
 
     fn default_state() -> StrategyState {
-        StrategyState {
-            portfolio: PortfolioState {
-                cash: 1000.0,
-                position: 0.0,
-                entry_price: 0.0,
-                equity: 1000.0,
-            },
-            metrics: MetricsState::default(),
-            last_trade_ts: 0,
-            last_loss_ts: 0,
-            trading_halted: false,
-            trades_today: 0,
-            trade_day: 0,
-            order_seq: 0,
-        }
+        StrategyState::with_capital(1000.0, ContractKind::Linear)
     }
 
     #[test]
@@ -1805,12 +3705,335 @@ mod tests {
             has_borrow: true,
             has_liquidations: false,
             has_depeg: false,
+            next_funding_ts: 0,
         };
         let view = make_view(1000, 30000.0, indicators, aux);
         let action = strat.update(view, &mut state);
         assert!(matches!(action, Action::Sell { .. }));
     }
 
+    /// Run one flat SimpleMomentum update and return (action, recorded reason).
+    fn entry_reason(indicators: IndicatorSnapshot, aux: MarketAux) -> (Action, &'static str) {
+        let mut strat = SimpleMomentum {
+            id: "mom".to_string(),
+            start_delay: 0,
+            cfg: test_config(),
+        };
+        let mut state = default_state();
+        let action = strat.update(make_view(1000, 100.0, indicators, aux), &mut state);
+        (action, state.signal_reason)
+    }
+
+    #[test]
+    fn test_entry_branches_record_signal_reason() {
+        let snap = |z_momentum: f64, vol: f64, ema_fast: f64| IndicatorSnapshot {
+            z_momentum,
+            vol,
+            vol_mean: 1.0,
+            ema_fast,
+            ema_slow: 100.0,
+            ..IndicatorSnapshot::default()
+        };
+
+        let carry = MarketAux {
+            funding_rate: 0.001,
+            has_funding: true,
+            has_borrow: true,
+            ..MarketAux::default()
+        };
+        let (action, reason) = entry_reason(snap(2.0, 1.0, 100.0), carry);
+        assert!(matches!(action, Action::Sell { .. }));
+        assert_eq!(reason, "funding_carry");
+
+        let cascade = MarketAux {
+            liquidation_score: 5.0,
+            has_liquidations: true,
+            ..MarketAux::default()
+        };
+        let (action, reason) = entry_reason(snap(2.0, 1.0, 100.0), cascade);
+        assert!(matches!(action, Action::Buy { .. }));
+        assert_eq!(reason, "liquidation_cascade");
+
+        let depeg = MarketAux {
+            stable_depeg: -0.01,
+            has_depeg: true,
+            ..MarketAux::default()
+        };
+        let (action, reason) = entry_reason(snap(2.0, 1.0, 100.0), depeg);
+        assert!(matches!(action, Action::Buy { .. }));
+        assert_eq!(reason, "depeg_snapback");
+
+        // Low vol regime follows momentum
+        let (action, reason) = entry_reason(snap(2.0, 0.5, 100.0), MarketAux::default());
+        assert!(matches!(action, Action::Buy { .. }));
+        assert_eq!(reason, "low_vol_momentum");
+
+        // High vol, stretched above in an uptrend: fade it
+        let stretched = IndicatorSnapshot {
+            z_stretch: 1.0,
+            ..snap(1.5, 2.0, 100.5)
+        };
+        let (action, reason) = entry_reason(stretched, MarketAux::default());
+        assert!(matches!(action, Action::Sell { .. }));
+        assert_eq!(reason, "high_vol_reversion");
+
+        // High vol, strong downtrend, no stretch: follow it
+        let (action, reason) = entry_reason(snap(-1.5, 2.0, 97.0), MarketAux::default());
+        assert!(matches!(action, Action::Sell { .. }));
+        assert_eq!(reason, "high_vol_trend");
+
+        // Normal vol, no trend: plain score threshold
+        let (action, reason) = entry_reason(snap(1.5, 1.0, 100.0), MarketAux::default());
+        assert!(matches!(action, Action::Buy { .. }));
+        assert_eq!(reason, "score_entry");
+
+        // Negative score in a strong uptrend with positive momentum: trend wins
+        let against = IndicatorSnapshot {
+            z_volume_spike: -3.8,
+            ..snap(0.6, 1.0, 103.0)
+        };
+        let (action, reason) = entry_reason(against, MarketAux::default());
+        assert!(matches!(action, Action::Buy { .. }));
+        assert_eq!(reason, "trend_override");
+
+        // Below the edge hurdle nothing fires and no reason is left behind
+        let (action, reason) = entry_reason(snap(0.5, 1.0, 100.0), MarketAux::default());
+        assert!(matches!(action, Action::Hold));
+        assert_eq!(reason, "");
+    }
+
+    #[test]
+    fn test_exit_and_scale_in_record_signal_reason() {
+        let mut cfg = test_config();
+        cfg.edge_hurdle = 0.0;
+        cfg.time_stop = 1_000_000;
+        cfg.scale_in_tranches = 3;
+        cfg.max_position_pct = 1.0;
+        let mut strat = SimpleMomentum {
+            id: "mom".to_string(),
+            start_delay: 0,
+            cfg,
+        };
+        let open = || {
+            let mut state = default_state();
+            state.portfolio.position = 0.01;
+            state.portfolio.entry_price = 100.0;
+            state.tranches = 1;
+            state
+        };
+        let snap = |z_momentum: f64| IndicatorSnapshot {
+            z_momentum,
+            vol: 0.5,
+            vol_mean: 1.0,
+            ema_fast: 100.0,
+            ema_slow: 100.0,
+            ..IndicatorSnapshot::default()
+        };
+        let late = 1_000_000;
+
+        // Price halved: stop loss
+        let mut state = open();
+        let view = make_view(late, 50.0, snap(2.0), MarketAux::default());
+        assert!(matches!(strat.update(view, &mut state), Action::Close));
+        assert_eq!(state.signal_reason, "rule_exit");
+
+        // Past min hold with a faded score
+        let mut state = open();
+        let view = make_view(late, 100.0, snap(0.1), MarketAux::default());
+        assert!(matches!(strat.update(view, &mut state), Action::Close));
+        assert_eq!(state.signal_reason, "score_exit");
+
+        // Entry signal still long while long: add a tranche
+        let mut state = open();
+        let view = make_view(late, 100.0, snap(2.0), MarketAux::default());
+        assert!(matches!(strat.update(view, &mut state), Action::Buy { .. }));
+        assert_eq!(state.signal_reason, "scale_in");
+    }
+
+    #[test]
+    fn test_scale_out_trims_on_decay_and_closes_on_reversal() {
+        let mut cfg = test_config();
+        cfg.edge_hurdle = 0.0;
+        cfg.time_stop = 1_000_000;
+        cfg.scale_out_fraction = 0.5;
+        let mut strat = SimpleMomentum {
+            id: "mom".to_string(),
+            start_delay: 0,
+            cfg,
+        };
+        let open = |position: f64| {
+            let mut state = default_state();
+            state.portfolio.position = position;
+            state.portfolio.entry_price = 100.0;
+            state.tranches = 1;
+            state
+        };
+        let snap = |z_momentum: f64| IndicatorSnapshot {
+            z_momentum,
+            vol: 0.5,
+            vol_mean: 1.0,
+            ema_fast: 100.0,
+            ema_slow: 100.0,
+            ..IndicatorSnapshot::default()
+        };
+        let late = 1_000_000;
+
+        // Long signal decayed to 0.8: between exit (0.4) and entry (1.2)
+        let mut state = open(0.02);
+        let view = make_view(late, 100.2, snap(0.8), MarketAux::default());
+        match strat.update(view, &mut state) {
+            Action::Sell { qty } => assert!((qty - 0.01).abs() < 1e-12),
+            other => panic!("expected a partial sell, got {:?}", other),
+        }
+        assert_eq!(state.signal_reason, "scale_out");
+
+        // Same decay on a short buys back half
+        let mut state = open(-0.02);
+        let view = make_view(late, 99.8, snap(-0.8), MarketAux::default());
+        assert!(
+            matches!(strat.update(view, &mut state), Action::Buy { qty } if (qty - 0.01).abs() < 1e-12)
+        );
+
+        // Never trims a position that is flat or under water
+        let mut state = open(0.02);
+        let view = make_view(late, 99.8, snap(0.8), MarketAux::default());
+        assert!(matches!(strat.update(view, &mut state), Action::Hold));
+        assert!(!state.scaled_out);
+
+        // Signal flipped against the long: close everything
+        let mut state = open(0.02);
+        let view = make_view(late, 100.0, snap(-0.8), MarketAux::default());
+        assert!(matches!(strat.update(view, &mut state), Action::Close));
+        assert_eq!(state.signal_reason, "signal_reversal");
+
+        // Still strong: keep the whole position
+        let mut state = open(0.02);
+        let view = make_view(late, 100.0, snap(2.0), MarketAux::default());
+        assert!(!matches!(
+            strat.update(view, &mut state),
+            Action::Sell { .. } | Action::Close
+        ));
+
+        // Disabled by default: a decayed signal just holds
+        strat.cfg.scale_out_fraction = 0.0;
+        let mut state = open(0.02);
+        let view = make_view(late, 100.2, snap(0.8), MarketAux::default());
+        assert!(matches!(strat.update(view, &mut state), Action::Hold));
+    }
+
+    #[test]
+    fn test_scale_out_trims_once_per_position() {
+        let mut cfg = test_config();
+        cfg.edge_hurdle = 0.0;
+        cfg.time_stop = 1_000_000;
+        cfg.scale_out_fraction = 0.5;
+        let mut strat = SimpleMomentum {
+            id: "mom".to_string(),
+            start_delay: 0,
+            cfg,
+        };
+        let snap = IndicatorSnapshot {
+            z_momentum: 0.8,
+            vol: 0.5,
+            vol_mean: 1.0,
+            ema_fast: 100.0,
+            ema_slow: 100.0,
+            ..IndicatorSnapshot::default()
+        };
+        let mut state = default_state();
+        state.portfolio.position = 0.02;
+        state.portfolio.entry_price = 100.0;
+        state.tranches = 1;
+
+        // A score parked in the trim band for many bars trims exactly once
+        let mut trims = 0;
+        for i in 0..10 {
+            let view = make_view(1_000_000 + i * 60, 100.2, snap, MarketAux::default());
+            if let Action::Sell { qty } = strat.update(view, &mut state) {
+                trims += 1;
+                state.portfolio.position -= qty;
+            }
+        }
+        assert_eq!(trims, 1);
+        assert!((state.portfolio.position - 0.01).abs() < 1e-12);
+
+        // Going flat re-arms it for the next position
+        state.portfolio.position = 0.0;
+        let view = make_view(2_000_000, 100.2, snap, MarketAux::default());
+        strat.update(view, &mut state);
+        assert!(!state.scaled_out);
+        state.portfolio.position = 0.02;
+        let view = make_view(3_000_000, 100.2, snap, MarketAux::default());
+        assert!(matches!(
+            strat.update(view, &mut state),
+            Action::Sell { .. }
+        ));
+    }
+
+    /// Entries taken from flat while feeding `scores` one bar at a time,
+    /// filling every Buy/Close immediately.
+    fn count_entries(cfg: Config, scores: &[f64]) -> usize {
+        let mut strat = SimpleMomentum {
+            id: "mom".to_string(),
+            start_delay: 0,
+            cfg,
+        };
+        let mut state = default_state();
+        let mut entries = 0;
+        for (i, &z_momentum) in scores.iter().enumerate() {
+            let ts = 1_000_000 + i as u64 * 3_600;
+            let snap = IndicatorSnapshot {
+                z_momentum,
+                vol: 1.0,
+                vol_mean: 1.0,
+                ema_fast: 100.0,
+                ema_slow: 100.0,
+                ..IndicatorSnapshot::default()
+            };
+            match strat.update(make_view(ts, 100.0, snap, MarketAux::default()), &mut state) {
+                Action::Buy { qty } if state.portfolio.position == 0.0 => {
+                    entries += 1;
+                    state.portfolio.position = qty;
+                    state.portfolio.entry_price = 100.0;
+                    state.last_trade_ts = ts;
+                }
+                Action::Close => {
+                    state.portfolio.position = 0.0;
+                    state.last_trade_ts = ts;
+                }
+                _ => {}
+            }
+        }
+        entries
+    }
+
+    #[test]
+    fn test_smoothing_and_band_suppress_threshold_whipsaw() {
+        let mut cfg = test_config();
+        cfg.edge_hurdle = 0.0;
+        cfg.time_stop = 1_000_000;
+
+        // Score flickers across both thresholds (entry 1.2, exit 0.4)
+        let noisy: Vec<f64> = (0..20)
+            .map(|i| if i % 2 == 0 { 1.3 } else { 0.3 })
+            .collect();
+        assert_eq!(count_entries(cfg.clone(), &noisy), 10);
+
+        // EMA settles near the mean, above exit: one entry, held throughout
+        let mut smoothed = cfg.clone();
+        smoothed.score_smoothing_alpha = 0.2;
+        assert_eq!(count_entries(smoothed, &noisy), 1);
+
+        // Hovering just around the thresholds never clears the band
+        let hovering: Vec<f64> = (0..20)
+            .map(|i| if i % 2 == 0 { 1.25 } else { 0.35 })
+            .collect();
+        assert_eq!(count_entries(cfg.clone(), &hovering), 10);
+        let mut banded = cfg;
+        banded.threshold_band = 0.1;
+        assert_eq!(count_entries(banded, &hovering), 0);
+    }
+
     #[test]
     fn test_carry_opportunistic_depeg_signal() {
         let cfg = test_config();
@@ -1830,6 +4053,7 @@ mod tests {
             has_borrow: false,
             has_liquidations: false,
             has_depeg: true,
+            next_funding_ts: 0,
         };
         let view = make_view(1000, 30000.0, indicators, aux);
         let action = strat.update(view, &mut state);
@@ -1906,6 +4130,114 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_simple_momentum_scale_in_ladder() {
+        let mut cfg = test_config();
+        cfg.entry_threshold = 1.2;
+        cfg.edge_hurdle = 0.0001;
+        cfg.edge_scale = 0.01;
+        cfg.scale_in_tranches = 3;
+        cfg.max_position_pct = 0.5;
+        let mut strat = SimpleMomentum {
+            id: "test".to_string(),
+            start_delay: 0,
+            cfg,
+        };
+        let bullish = IndicatorSnapshot {
+            z_momentum: 2.0,
+            z_vol: 1.0,
+            z_volume_spike: 1.0,
+            ema_fast: 101.0,
+            ema_slow: 100.0,
+            vol: 1.0,
+            vol_mean: 1.0,
+            ..Default::default()
+        };
+        let mut state = default_state();
+        let mut actions = Vec::new();
+        for bar in 0..5u64 {
+            let ts = 1000 + bar * 300;
+            let action = strat.update(
+                make_view(ts, 100.0, bullish, MarketAux::default()),
+                &mut state,
+            );
+            if let Action::Buy { qty } = action {
+                let before = state.portfolio.position;
+                state.portfolio.position += qty;
+                state.portfolio.entry_price = 100.0;
+                state.last_trade_ts = ts;
+                state.count_tranche(before, true);
+            }
+            actions.push(action);
+        }
+        let buys = actions
+            .iter()
+            .filter(|a| matches!(a, Action::Buy { .. }))
+            .count();
+        assert_eq!(buys, 3, "persistent signal fills the ladder: {:?}", actions);
+        assert_eq!(state.tranches, 3);
+        assert!(matches!(actions[3], Action::Hold));
+
+        // A reversed signal never adds to the long
+        let mut state = default_state();
+        strat.update(
+            make_view(1000, 100.0, bullish, MarketAux::default()),
+            &mut state,
+        );
+        state.portfolio.position = 0.001;
+        state.portfolio.entry_price = 100.0;
+        state.last_trade_ts = 1000;
+        state.count_tranche(0.0, true);
+        let bearish = IndicatorSnapshot {
+            z_momentum: -2.0,
+            z_vol: -1.0,
+            z_volume_spike: -1.0,
+            ema_fast: 99.0,
+            ema_slow: 100.0,
+            ..bullish
+        };
+        let action = strat.update(
+            make_view(1300, 100.0, bearish, MarketAux::default()),
+            &mut state,
+        );
+        assert!(!matches!(action, Action::Buy { .. }), "got {:?}", action);
+        assert_eq!(state.tranches, 1);
+
+        // The exposure cap stops the ladder before the tranche count does
+        strat.cfg.max_position_pct = 0.00015; // $0.15 of $1000 equity
+        let action = strat.update(
+            make_view(1300, 100.0, bullish, MarketAux::default()),
+            &mut state,
+        );
+        assert!(matches!(action, Action::Hold), "got {:?}", action);
+    }
+
+    #[test]
+    fn test_tranches_count_filled_orders() {
+        let mut state = default_state();
+        let buy = |state: &mut StrategyState, qty: f64, first_fill: bool| {
+            let before = state.portfolio.position;
+            state.portfolio.position += qty;
+            state.count_tranche(before, first_fill);
+        };
+        // An issued order that never fills leaves the ladder alone
+        assert_eq!(state.tranches, 0);
+        // One order filled in two pieces is one tranche
+        buy(&mut state, 0.5, true);
+        buy(&mut state, 0.5, false);
+        assert_eq!(state.tranches, 1);
+        buy(&mut state, 0.3, true);
+        buy(&mut state, 0.2, false);
+        assert_eq!(state.tranches, 2);
+        // Reducing keeps the count; flattening and flipping restart it
+        buy(&mut state, -0.5, true);
+        assert_eq!(state.tranches, 2);
+        buy(&mut state, -2.0, true);
+        assert_eq!(state.tranches, 1);
+        buy(&mut state, 1.0, true);
+        assert_eq!(state.tranches, 0);
+    }
+
     #[test]
     fn test_simple_momentum_edge_hurdle_blocks() {
         let mut cfg = test_config();
@@ -1936,6 +4268,51 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_round_trip_cost_fees_and_vol_slippage() {
+        assert_eq!(round_trip_cost(0.0, 0.0, 5.0, 100.0), 0.0);
+        // 10 bps each way
+        assert!((round_trip_cost(10.0, 0.0, 5.0, 100.0) - 0.002).abs() < 1e-12);
+        // 5% relative vol * mult 1 * 0.1 = 50 bps each way
+        assert!((round_trip_cost(0.0, 1.0, 5.0, 100.0) - 0.01).abs() < 1e-12);
+        assert_eq!(round_trip_cost(0.0, 1.0, 5.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_simple_momentum_fee_assumption_suppresses_borderline_entry() {
+        let mut cfg = test_config();
+        cfg.entry_threshold = 1.2;
+        cfg.edge_hurdle = 0.02;
+        cfg.edge_scale = 0.01;
+        let indicators = IndicatorSnapshot {
+            z_momentum: 2.0,
+            z_vol: 1.0,
+            z_volume_spike: 1.0,
+            ema_fast: 101.0,
+            ema_slow: 100.0,
+            vol: 1.0,
+            vol_mean: 1.0,
+            ..Default::default()
+        };
+        // score 2.8 -> gross edge 0.028, 80 bps above the hurdle
+        let decide = |fee_bps: f64| {
+            let mut strat = SimpleMomentum {
+                id: "test".to_string(),
+                start_delay: 0,
+                cfg: Config {
+                    edge_fee_bps: fee_bps,
+                    ..cfg.clone()
+                },
+            };
+            let view = make_view(1000, 100.0, indicators, MarketAux::default());
+            strat.update(view, &mut default_state())
+        };
+        assert!(matches!(decide(0.0), Action::Buy { .. }));
+        assert!(matches!(decide(30.0), Action::Buy { .. }));
+        // 50 bps a side is 100 bps round trip: no longer worth it
+        assert!(matches!(decide(50.0), Action::Hold));
+    }
+
     #[test]
     fn test_simple_momentum_low_vol_momentum_follow() {
         let mut cfg = test_config();
@@ -2058,14 +4435,12 @@ mod tests {
                 position: 0.1,
                 entry_price: 100.0,
                 equity: 1000.0,
+                contract: ContractKind::Linear,
             },
-            metrics: MetricsState::default(),
             last_trade_ts: 500,
-            last_loss_ts: 0,
-            trading_halted: false,
             trades_today: 1,
-            trade_day: 0,
             order_seq: 1,
+            ..StrategyState::with_capital(1000.0, ContractKind::Linear)
         };
         // Price up 1% (should trigger TP) but only 1 candle elapsed (need 3)
         let view = MarketView {
@@ -2112,14 +4487,12 @@ mod tests {
                 position: 0.1,
                 entry_price: 100.0,
                 equity: 1000.0,
+                contract: ContractKind::Linear,
             },
-            metrics: MetricsState::default(),
             last_trade_ts: 500,
-            last_loss_ts: 0,
-            trading_halted: false,
             trades_today: 1,
-            trade_day: 0,
             order_seq: 1,
+            ..StrategyState::with_capital(1000.0, ContractKind::Linear)
         };
         // Price down 0.5% (triggers stop loss) with only 1 candle elapsed
         let view = MarketView {
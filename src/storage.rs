@@ -1,6 +1,9 @@
+use std::collections::HashMap;
+
 use anyhow::Result;
 use rusqlite::{params, Connection};
 
+use crate::metrics::{DailyMetrics, EquityCurve};
 use crate::state::StrategyInstance;
 
 pub struct StateStore {
@@ -24,10 +27,32 @@ impl StateStore {
                 pnl REAL NOT NULL,
                 wins INTEGER NOT NULL,
                 losses INTEGER NOT NULL,
-                max_drawdown REAL NOT NULL
+                max_drawdown REAL NOT NULL,
+                equity_peak REAL NOT NULL DEFAULT 0
+            );
+            CREATE TABLE IF NOT EXISTS daily_metrics (
+                day INTEGER NOT NULL,
+                strategy_id TEXT NOT NULL,
+                pnl REAL NOT NULL,
+                trades INTEGER NOT NULL,
+                wins INTEGER NOT NULL,
+                win_rate REAL NOT NULL,
+                max_drawdown REAL NOT NULL,
+                PRIMARY KEY (day, strategy_id)
             );
             COMMIT;",
         )?;
+        // Databases created before the high-water mark was persisted
+        let has_peak = self
+            .conn
+            .prepare("SELECT 1 FROM pragma_table_info('metrics') WHERE name = 'equity_peak'")?
+            .exists([])?;
+        if !has_peak {
+            self.conn.execute(
+                "ALTER TABLE metrics ADD COLUMN equity_peak REAL NOT NULL DEFAULT 0",
+                [],
+            )?;
+        }
         Ok(())
     }
 
@@ -36,8 +61,8 @@ impl StateStore {
         for inst in strategies {
             let s = &inst.state;
             tx.execute(
-                "INSERT INTO metrics (ts, strategy_id, equity, pnl, wins, losses, max_drawdown)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT INTO metrics (ts, strategy_id, equity, pnl, wins, losses, max_drawdown, equity_peak)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
                 params![
                     ts as i64,
                     inst.id,
@@ -45,11 +70,150 @@ impl StateStore {
                     s.metrics.pnl,
                     s.metrics.wins as i64,
                     s.metrics.losses as i64,
-                    s.metrics.max_drawdown
+                    s.metrics.max_drawdown,
+                    s.metrics.equity_peak
                 ],
             )?;
         }
         tx.commit()?;
         Ok(())
     }
+
+    /// Store one strategy's closed-out day; re-archiving a day replaces it.
+    pub fn archive_day(&mut self, strategy_id: &str, day: &DailyMetrics) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO daily_metrics (day, strategy_id, pnl, trades, wins, win_rate, max_drawdown)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                day.day as i64,
+                strategy_id,
+                day.pnl,
+                day.trades as i64,
+                day.wins as i64,
+                day.win_rate,
+                day.max_drawdown
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Every persisted `(ts, equity)` snapshot, per strategy id in id order.
+    pub fn equity_curves(&self) -> Result<Vec<(String, EquityCurve)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT strategy_id, ts, equity FROM metrics ORDER BY strategy_id, ts")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)? as u64,
+                row.get::<_, f64>(2)?,
+            ))
+        })?;
+        let mut curves: Vec<(String, EquityCurve)> = Vec::new();
+        for row in rows {
+            let (id, ts, equity) = row?;
+            match curves.last_mut() {
+                Some((last, curve)) if *last == id => curve.push((ts, equity)),
+                _ => curves.push((id, vec![(ts, equity)])),
+            }
+        }
+        Ok(curves)
+    }
+
+    /// Highest equity peak and worst drawdown ever persisted, per strategy id.
+    pub fn high_water_marks(&self) -> Result<HashMap<String, (f64, f64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT strategy_id, MAX(equity_peak), MIN(max_drawdown)
+             FROM metrics GROUP BY strategy_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, (row.get(1)?, row.get(2)?)))
+        })?;
+        Ok(rows.collect::<rusqlite::Result<_>>()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{Config, StrategyInstance};
+
+    #[test]
+    fn test_high_water_marks_survive_reopen() {
+        let path = "/tmp/test_storage_hwm.sqlite";
+        let _ = std::fs::remove_file(path);
+        let mut strategies = StrategyInstance::build_default_set(Config::from_env());
+        strategies.truncate(1);
+        {
+            let mut store = StateStore::new(path).unwrap();
+            store.init().unwrap();
+            let m = &mut strategies[0].state.metrics;
+            m.equity_peak = 1_200.0;
+            m.max_drawdown = -0.05;
+            store.persist_snapshot(1, &strategies).unwrap();
+            // Later, lower snapshot: the peak must not regress
+            let m = &mut strategies[0].state.metrics;
+            m.equity_peak = 1_100.0;
+            m.max_drawdown = -0.01;
+            store.persist_snapshot(2, &strategies).unwrap();
+        }
+        let mut store = StateStore::new(path).unwrap();
+        store.init().unwrap();
+        let hwm = store.high_water_marks().unwrap();
+        assert_eq!(hwm.get(&strategies[0].id), Some(&(1_200.0, -0.05)));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_equity_curves_grouped_per_strategy_in_time_order() {
+        let mut store = StateStore::new(":memory:").unwrap();
+        store.init().unwrap();
+        let mut strategies = StrategyInstance::build_default_set(Config::from_env());
+        strategies.truncate(2);
+        for ts in [300, 0, 600] {
+            strategies[0].state.portfolio.equity = 1_000.0 + ts as f64;
+            store.persist_snapshot(ts, &strategies).unwrap();
+        }
+        let curves = store.equity_curves().unwrap();
+        assert_eq!(curves.len(), 2);
+        let (id, curve) = curves
+            .iter()
+            .find(|(id, _)| id == &strategies[0].id)
+            .unwrap();
+        assert_eq!(id, &strategies[0].id);
+        assert_eq!(curve, &vec![(0, 1_000.0), (300, 1_300.0), (600, 1_600.0)]);
+    }
+
+    #[test]
+    fn test_archive_day_replaces_same_day() {
+        let mut store = StateStore::new(":memory:").unwrap();
+        store.init().unwrap();
+        let mut day = DailyMetrics {
+            day: 19_000,
+            pnl: 12.5,
+            trades: 4,
+            wins: 3,
+            win_rate: 0.75,
+            max_drawdown: -0.01,
+        };
+        store.archive_day("mom-0", &day).unwrap();
+        day.pnl = 15.0;
+        store.archive_day("mom-0", &day).unwrap();
+        store.archive_day("mom-1", &day).unwrap();
+        let rows: Vec<(String, f64, i64)> = store
+            .conn
+            .prepare("SELECT strategy_id, pnl, trades FROM daily_metrics ORDER BY strategy_id")
+            .unwrap()
+            .query_map([], |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("mom-0".to_string(), 15.0, 4),
+                ("mom-1".to_string(), 15.0, 4)
+            ]
+        );
+    }
 }
@@ -0,0 +1,311 @@
+//! Composable strategies built from single-source signal votes.
+//!
+//! Each vote reads one input family and answers +1 (long), -1 (short) or 0.
+//! [`ConfluenceStrategy`] tests H007: it only enters when at least
+//! `min_confluence` votes agree and sizes up with the agreement count.
+
+use crate::state::{carry_signal, check_stop_loss, check_take_profit, Config, StrategyInstance};
+use crate::strategy::{Action, AuxRequirements, ExitReason, MarketView, Strategy, StrategyState};
+
+/// Size of one unit of agreement; each vote beyond the minimum adds a unit.
+const BASE_QTY: f64 = 0.001;
+
+/// Follow strong momentum.
+pub fn momentum_vote(market: &MarketView, cfg: &Config) -> i8 {
+    let z = market.indicators.z_momentum;
+    if z > cfg.mom_th {
+        1
+    } else if z < -cfg.mom_th {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Fade a stretched price.
+pub fn mean_reversion_vote(market: &MarketView, cfg: &Config) -> i8 {
+    let z = market.indicators.z_stretch;
+    if z > cfg.stretch_th {
+        -1
+    } else if z < -cfg.stretch_th {
+        1
+    } else {
+        0
+    }
+}
+
+/// Lean against funding pressure when carry is actionable.
+pub fn funding_vote(id: &str, market: &MarketView, cfg: &Config) -> i8 {
+    if !carry_signal(id, &market.aux, cfg) {
+        0
+    } else if market.aux.funding_rate > 0.0 {
+        -1
+    } else {
+        1
+    }
+}
+
+/// Trade with the impulse of a liquidation cascade.
+pub fn liquidation_vote(market: &MarketView, cfg: &Config) -> i8 {
+    let score = market.aux.liquidation_score;
+    if !market.aux.has_liquidations || score.is_nan() || score <= cfg.liq_score_th {
+        0
+    } else if market.indicators.z_momentum > 0.0 {
+        1
+    } else if market.indicators.z_momentum < 0.0 {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Votes per direction: `(long, short)`.
+pub fn tally(votes: &[i8]) -> (u32, u32) {
+    votes.iter().fold((0, 0), |(l, s), &v| match v {
+        1 => (l + 1, s),
+        -1 => (l, s + 1),
+        _ => (l, s),
+    })
+}
+
+pub struct ConfluenceStrategy {
+    id: String,
+    cfg: Config,
+}
+
+impl ConfluenceStrategy {
+    pub fn new(id: impl Into<String>, cfg: Config) -> Self {
+        Self { id: id.into(), cfg }
+    }
+
+    fn votes(&self, market: &MarketView) -> [i8; 4] {
+        [
+            momentum_vote(market, &self.cfg),
+            mean_reversion_vote(market, &self.cfg),
+            funding_vote(&self.id, market, &self.cfg),
+            liquidation_vote(market, &self.cfg),
+        ]
+    }
+
+    fn rule_exit(&self, market: &MarketView, state: &StrategyState) -> Option<ExitReason> {
+        let entry = state.portfolio.entry_price.max(1e-9);
+        let move_pct = (market.last.c - entry) / entry;
+        let elapsed = market.last.ts.saturating_sub(state.last_trade_ts);
+        if check_stop_loss(move_pct, market.indicators.atr, entry, &self.cfg) {
+            return Some(ExitReason::Stop);
+        }
+        if elapsed >= self.cfg.candles_to_secs(self.cfg.min_hold_candles as u64) {
+            if check_take_profit(move_pct, market.indicators.atr, entry, &self.cfg) {
+                return Some(ExitReason::Take);
+            }
+            if elapsed >= self.cfg.candles_to_secs(self.cfg.time_stop as u64) {
+                return Some(ExitReason::Time);
+            }
+        }
+        None
+    }
+}
+
+impl Strategy for ConfluenceStrategy {
+    fn id(&self) -> &'static str {
+        "confluence"
+    }
+
+    fn config_mut(&mut self) -> Option<&mut Config> {
+        Some(&mut self.cfg)
+    }
+
+    fn aux_requirements(&self) -> AuxRequirements {
+        AuxRequirements::full()
+    }
+
+    fn update(&mut self, market: MarketView, state: &mut StrategyState) -> Action {
+        let min = self.cfg.min_confluence.max(1);
+        let (long, short) = tally(&self.votes(&market));
+
+        if state.portfolio.position != 0.0 {
+            if self.rule_exit(&market, state).is_some() {
+                return Action::Close;
+            }
+            // Confluence has turned against the open position
+            let against = if state.portfolio.position > 0.0 {
+                short
+            } else {
+                long
+            };
+            if against >= min {
+                return Action::Close;
+            }
+            return Action::Hold;
+        }
+
+        // Mixed votes are not confluence
+        if long >= min && short == 0 {
+            Action::Buy {
+                qty: BASE_QTY * (long - min + 1) as f64,
+            }
+        } else if short >= min && long == 0 {
+            Action::Sell {
+                qty: BASE_QTY * (short - min + 1) as f64,
+            }
+        } else {
+            Action::Hold
+        }
+    }
+
+    fn exit_reason(&self, market: MarketView, state: &StrategyState) -> ExitReason {
+        self.rule_exit(&market, state).unwrap_or(ExitReason::Signal)
+    }
+}
+
+/// One confluence instance per configured symbol.
+pub fn build_confluence_set(cfg: Config) -> Vec<StrategyInstance> {
+    cfg.symbols
+        .iter()
+        .map(|symbol| {
+            let mut cfg_sym = cfg.clone();
+            cfg_sym.symbol = symbol.clone();
+            let id = format!("confluence:{}", symbol);
+            let shadow = cfg.is_shadow(&id);
+            StrategyInstance {
+                id: id.clone(),
+                symbol: symbol.clone(),
+                strategy: Box::new(ConfluenceStrategy::new(id, cfg_sym)),
+                state: StrategyState::with_capital(cfg.starting_capital, cfg.contract_kind),
+                regimes: Vec::new(),
+                shadow,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{Candle, IndicatorSnapshot, MarketAux};
+
+    fn cfg() -> Config {
+        let mut cfg = Config::from_env();
+        cfg.mom_th = 0.4;
+        cfg.stretch_th = 0.8;
+        cfg.funding_high = 0.0001;
+        cfg.funding_spread = 0.00005;
+        cfg.liq_score_th = 3.0;
+        cfg.min_confluence = 3;
+        cfg
+    }
+
+    fn flat() -> StrategyState {
+        StrategyState::with_capital(1000.0, crate::strategy::ContractKind::Linear)
+    }
+
+    /// A view where the first `n` of {momentum, mean reversion, funding,
+    /// liquidation} vote long and the rest abstain.
+    fn view(n: usize) -> MarketView<'static> {
+        let mut indicators = IndicatorSnapshot::default();
+        let mut aux = MarketAux::default();
+        if n >= 1 {
+            indicators.z_momentum = 1.5;
+        }
+        if n >= 2 {
+            indicators.z_stretch = -1.2;
+        }
+        if n >= 3 {
+            aux.funding_rate = -0.0005;
+            aux.borrow_rate = 0.0001;
+            aux.has_funding = true;
+            aux.has_borrow = true;
+        }
+        if n >= 4 {
+            aux.liquidation_score = 5.0;
+            aux.has_liquidations = true;
+        }
+        MarketView {
+            symbol: "BTCUSDT",
+            last: Candle {
+                ts: 1_000,
+                o: 100.0,
+                h: 101.0,
+                l: 99.0,
+                c: 100.0,
+                v: 1_000.0,
+            },
+            indicators,
+            aux,
+        }
+    }
+
+    #[test]
+    fn one_or_two_agreeing_signals_hold() {
+        let mut s = ConfluenceStrategy::new("c", cfg());
+        for n in [0, 1, 2] {
+            let action = s.update(view(n), &mut flat());
+            assert!(matches!(action, Action::Hold), "n={} -> {:?}", n, action);
+        }
+    }
+
+    #[test]
+    fn three_or_more_agreeing_signals_enter_and_size_up() {
+        let mut s = ConfluenceStrategy::new("c", cfg());
+        let three = s.update(view(3), &mut flat());
+        let four = s.update(view(4), &mut flat());
+        match (three, four) {
+            (Action::Buy { qty: q3 }, Action::Buy { qty: q4 }) => {
+                assert!((q3 - BASE_QTY).abs() < 1e-12);
+                assert!((q4 - 2.0 * BASE_QTY).abs() < 1e-12);
+            }
+            other => panic!("expected two buys, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn conflicting_votes_are_not_confluence() {
+        let mut s = ConfluenceStrategy::new("c", cfg());
+        let mut v = view(3);
+        // Positive funding now votes short against momentum and mean reversion
+        v.aux.funding_rate = 0.0005;
+        v.aux.liquidation_score = 5.0;
+        v.aux.has_liquidations = true;
+        assert_eq!(tally(&s.votes(&v)), (3, 1));
+        assert!(matches!(s.update(v, &mut flat()), Action::Hold));
+    }
+
+    #[test]
+    fn votes_abstain_on_nan_and_exact_threshold_and_mirror_on_flip() {
+        let cfg = cfg();
+        let votes = |v: &MarketView| {
+            [
+                momentum_vote(v, &cfg),
+                mean_reversion_vote(v, &cfg),
+                funding_vote("c", v, &cfg),
+                liquidation_vote(v, &cfg),
+            ]
+        };
+
+        let mut nan = view(4);
+        nan.indicators.z_momentum = f64::NAN;
+        nan.indicators.z_stretch = f64::NAN;
+        nan.aux.funding_rate = f64::NAN;
+        nan.aux.liquidation_score = f64::NAN;
+        assert_eq!(votes(&nan), [0, 0, 0, 0]);
+        assert_eq!(votes(&view(0)), [0, 0, 0, 0]);
+
+        // Thresholds are strict
+        let mut edge = view(4);
+        edge.indicators.z_momentum = cfg.mom_th;
+        edge.indicators.z_stretch = -cfg.stretch_th;
+        edge.aux.liquidation_score = cfg.liq_score_th;
+        assert_eq!(votes(&edge)[..2], [0, 0]);
+        assert_eq!(votes(&edge)[3], 0);
+
+        // Negating every input flips every vote
+        let long = view(4);
+        let mut short = view(4);
+        short.indicators.z_momentum = -long.indicators.z_momentum;
+        short.indicators.z_stretch = -long.indicators.z_stretch;
+        short.aux.funding_rate = -long.aux.funding_rate;
+        assert_eq!(votes(&long), [1, 1, 1, 1]);
+        assert_eq!(votes(&short), [-1, -1, -1, -1]);
+        assert_eq!(tally(&votes(&short)), (0, 4));
+    }
+}
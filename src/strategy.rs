@@ -1,6 +1,11 @@
 // Strategy API + state layout for rolling backtests.
 
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+use crate::metrics::{P2Quantile, TailWindow, TradeWindow};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Candle {
     pub ts: u64,
     pub o: f64,
@@ -10,7 +15,7 @@ pub struct Candle {
     pub v: f64,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct MarketAux {
     pub funding_rate: f64,
     pub borrow_rate: f64,
@@ -23,6 +28,8 @@ pub struct MarketAux {
     pub has_borrow: bool,
     pub has_liquidations: bool,
     pub has_depeg: bool,
+    /// Next funding settlement (unix secs); 0 = unknown.
+    pub next_funding_ts: u64,
 }
 
 /// Requirements for aux data - different strategies need different fields
@@ -73,7 +80,30 @@ impl AuxRequirements {
     }
 }
 
+/// First funding settlement strictly after `ts` on an `interval`-second
+/// schedule anchored at the epoch (Binance settles 00:00/08:00/16:00 UTC).
+/// 0 when `interval` is 0.
+pub fn next_funding_ts(ts: u64, interval: u64) -> u64 {
+    if interval == 0 {
+        return 0;
+    }
+    (ts / interval + 1) * interval
+}
+
+/// Funding settlements falling in `(prev_ts, ts]`.
+pub fn funding_settlements(prev_ts: u64, ts: u64, interval: u64) -> u64 {
+    if interval == 0 || ts <= prev_ts {
+        return 0;
+    }
+    ts / interval - prev_ts / interval
+}
+
 impl MarketAux {
+    /// Seconds until the next funding settlement; None when unknown.
+    pub fn secs_to_funding(&self, now_ts: u64) -> Option<u64> {
+        (self.next_funding_ts > 0).then(|| self.next_funding_ts.saturating_sub(now_ts))
+    }
+
     /// Check if aux data is stale (older than max_age_secs)
     pub fn is_stale(&self, now_ts: u64, max_age_secs: u64) -> bool {
         if self.fetch_ts == 0 {
@@ -120,7 +150,10 @@ impl MarketAux {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Everything a strategy sees for one decision. Serializable so the exact
+/// inputs can be captured (`DUMP_VIEWS`) and replayed; non-finite floats
+/// serialize as null.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct MarketView<'a> {
     // Read-only pointers into shared state.
     pub symbol: &'a str,
@@ -129,11 +162,14 @@ pub struct MarketView<'a> {
     pub aux: MarketAux,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
 pub struct IndicatorSnapshot {
     pub ema_fast: f64,
     pub ema_slow: f64,
+    /// VWAP over the last `volume_window` bars; `stretch` is measured against it
     pub vwap: f64,
+    /// Cumulative VWAP since the first candle
+    pub session_vwap: f64,
     pub vol: f64,
     pub vol_mean: f64,
     pub momentum: f64,
@@ -143,17 +179,146 @@ pub struct IndicatorSnapshot {
     pub z_vol: f64,
     pub z_volume_spike: f64,
     pub z_stretch: f64,
+    /// Candles the indicator state has consumed for this symbol
+    pub samples: u64,
+    /// Average true range in price units
+    pub atr: f64,
 }
 
-#[derive(Debug, Clone, Copy)]
+/// Contract settlement model.
+///
+/// Linear contracts are quoted and settled in the quote currency (USDT):
+/// `position` is in base units and PnL = qty * (exit - entry).
+/// Inverse (coin-margined) contracts are sized in quote units (USD) and
+/// settled in the base coin: PnL = qty * (1/entry - 1/exit), notional = qty / price.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContractKind {
+    #[default]
+    Linear,
+    Inverse,
+}
+
+impl ContractKind {
+    /// Parse from env-style strings ("linear", "inverse", "coin"); None otherwise.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "linear" | "usdt" | "usd_margined" => Some(ContractKind::Linear),
+            "inverse" | "coin" | "coin_margined" => Some(ContractKind::Inverse),
+            _ => None,
+        }
+    }
+
+    /// Settlement-currency value of `qty` contracts marked at `price`.
+    pub fn mark_value(&self, qty: f64, price: f64) -> f64 {
+        match self {
+            ContractKind::Linear => qty * price,
+            ContractKind::Inverse => {
+                if price > 0.0 {
+                    -qty / price
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    /// Absolute notional of `qty` contracts in the settlement currency.
+    pub fn notional(&self, qty: f64, price: f64) -> f64 {
+        match self {
+            ContractKind::Linear => (qty * price).abs(),
+            ContractKind::Inverse => {
+                if price > 0.0 {
+                    (qty / price).abs()
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// How a stop-loss / take-profit distance is expressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub enum StopMode {
+    /// Use the configured fraction of entry price as-is.
+    #[default]
+    Fixed,
+    /// Distance is `k * ATR`, converted to a fraction of entry price.
+    AtrMultiple(f64),
+}
+
+impl StopMode {
+    /// Env-style parse: a positive multiple selects `AtrMultiple`, anything else `Fixed`.
+    pub fn from_multiple(raw: Option<&str>) -> Self {
+        match raw.and_then(|v| v.parse::<f64>().ok()) {
+            Some(k) if k > 0.0 => StopMode::AtrMultiple(k),
+            _ => StopMode::Fixed,
+        }
+    }
+
+    /// Effective threshold as a fraction of `entry`. Falls back to `fixed`
+    /// while ATR is not yet available.
+    pub fn threshold(&self, fixed: f64, atr: f64, entry: f64) -> f64 {
+        match self {
+            StopMode::Fixed => fixed,
+            StopMode::AtrMultiple(k) => {
+                if atr > 0.0 && entry > 0.0 {
+                    k * atr / entry
+                } else {
+                    fixed
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PortfolioState {
     pub cash: f64,
     pub position: f64,
     pub entry_price: f64,
     pub equity: f64,
+    pub contract: ContractKind,
 }
 
 impl PortfolioState {
+    /// Equity marked at `price`: cash + settlement value of the open position.
+    pub fn mark_equity(&self, price: f64) -> f64 {
+        self.cash + self.contract.mark_value(self.position, price)
+    }
+
+    /// Unrealized PnL of the open position at `price`, in the settlement currency.
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        if self.position == 0.0 || self.entry_price == 0.0 {
+            return 0.0;
+        }
+        self.contract.mark_value(self.position, price)
+            - self.contract.mark_value(self.position, self.entry_price)
+    }
+
+    /// Open notional as a fraction of equity.
+    pub fn exposure(&self, price: f64) -> f64 {
+        if self.equity.abs() > 0.0 {
+            self.contract.notional(self.position, price) / self.equity.abs()
+        } else {
+            0.0
+        }
+    }
+
+    /// Settle one funding payment at `rate` on the open position, marked at
+    /// `price`: longs pay positive funding, shorts receive it. Returns the
+    /// cash credited (negative when paid).
+    pub fn apply_funding(&mut self, rate: f64, price: f64) -> f64 {
+        if self.position == 0.0 || rate == 0.0 {
+            return 0.0;
+        }
+        let payment = -self.position.signum() * self.contract.notional(self.position, price) * rate;
+        self.cash += payment;
+        self.equity = self.mark_equity(price);
+        payment
+    }
+
     pub fn apply_fill(&mut self, fill: crate::state::Fill) -> f64 {
         if fill.qty == 0.0 {
             return 0.0;
@@ -166,13 +331,15 @@ impl PortfolioState {
         if prev_pos != 0.0 && prev_pos.signum() != fill.qty.signum() {
             let close_qty = prev_pos.abs().min(fill.qty.abs());
             let dir = if prev_pos > 0.0 { 1.0 } else { -1.0 };
-            realized = (fill.price - self.entry_price) * close_qty * dir;
+            realized = (self.contract.mark_value(close_qty, fill.price)
+                - self.contract.mark_value(close_qty, self.entry_price))
+                * dir;
         }
 
         // Cash flow: buy (qty>0) costs money, sell (qty<0) receives money
-        // cash -= price * qty for the position change
+        // cash -= settlement value of the position change (price * qty for linear)
         // Always subtract fees
-        self.cash -= fill.price * fill.qty + fill.fee;
+        self.cash -= self.contract.mark_value(fill.qty, fill.price) + fill.fee;
         self.position = new_pos;
 
         // Update entry price based on add/reduce/flip
@@ -180,11 +347,20 @@ impl PortfolioState {
             self.entry_price = fill.price;
         } else if prev_pos.signum() == new_pos.signum() {
             if new_pos.abs() > prev_pos.abs() {
-                // Increasing position: use weighted average entry
+                // Increasing position: use weighted average entry. Inverse
+                // PnL is linear in 1/price, so its average is harmonic.
                 let total = prev_pos.abs() + fill.qty.abs();
                 if total > 0.0 {
-                    self.entry_price =
-                        (self.entry_price * prev_pos.abs() + fill.price * fill.qty.abs()) / total;
+                    self.entry_price = match self.contract {
+                        ContractKind::Linear => {
+                            (self.entry_price * prev_pos.abs() + fill.price * fill.qty.abs())
+                                / total
+                        }
+                        ContractKind::Inverse => {
+                            total
+                                / (prev_pos.abs() / self.entry_price + fill.qty.abs() / fill.price)
+                        }
+                    };
                 }
             }
             // Reducing position: keep entry price
@@ -193,7 +369,7 @@ impl PortfolioState {
             self.entry_price = fill.price;
         }
 
-        self.equity = self.cash + (self.position * fill.price);
+        self.equity = self.mark_equity(fill.price);
         realized
     }
 }
@@ -203,6 +379,102 @@ mod tests {
     use super::*;
     use crate::state::Fill;
 
+    #[test]
+    fn funding_schedule_counts_boundaries() {
+        assert_eq!(next_funding_ts(28_799, 28_800), 28_800);
+        assert_eq!(next_funding_ts(28_800, 28_800), 57_600);
+        assert_eq!(next_funding_ts(1_000, 0), 0);
+        assert_eq!(funding_settlements(28_500, 28_799, 28_800), 0);
+        assert_eq!(funding_settlements(28_799, 28_800, 28_800), 1);
+        assert_eq!(funding_settlements(28_800, 28_801, 28_800), 0);
+        assert_eq!(funding_settlements(28_799, 57_600, 28_800), 2);
+
+        let aux = MarketAux {
+            next_funding_ts: 28_800,
+            ..Default::default()
+        };
+        assert_eq!(aux.secs_to_funding(27_000), Some(1_800));
+        assert_eq!(MarketAux::default().secs_to_funding(27_000), None);
+    }
+
+    #[test]
+    fn live_metrics_track_expectancy_and_payoff() {
+        let mut m = MetricsState::default();
+        assert_eq!(m.expectancy(), 0.0);
+        assert_eq!(m.payoff_ratio(), 0.0);
+
+        // 4 wins of 20, 2 losses of 10, one scratch
+        for pnl in [20.0, -10.0, 20.0, 0.0, 20.0, -10.0, 20.0] {
+            m.record_trade(pnl);
+        }
+        assert_eq!((m.wins, m.losses), (4, 2));
+        assert!((m.pnl - 60.0).abs() < 1e-12);
+        assert!((m.win_rate() - 4.0 / 6.0).abs() < 1e-12);
+        assert!((m.avg_win() - 20.0).abs() < 1e-12);
+        assert!((m.avg_loss() - 10.0).abs() < 1e-12);
+        assert!((m.payoff_ratio() - 2.0).abs() < 1e-12);
+        // 2/3 * 20 - 1/3 * 10 = 10 per trade
+        assert!((m.expectancy() - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn round_trips_are_recorded_once_net_of_fees() {
+        let mut p = PortfolioState {
+            cash: 1000.0,
+            position: 0.0,
+            entry_price: 0.0,
+            equity: 1000.0,
+            contract: ContractKind::Linear,
+        };
+        let mut m = MetricsState::default();
+        let mut fill = |m: &mut MetricsState, qty: f64, price: f64, fee: f64| {
+            let fill = Fill {
+                price,
+                qty,
+                fee,
+                ts: 0,
+            };
+            let realized = p.apply_fill(fill);
+            m.record_fill(realized, &fill, p.position);
+        };
+
+        // Entry, scale-in, partial exit: nothing closed yet
+        fill(&mut m, 1.0, 100.0, 0.1);
+        fill(&mut m, 1.0, 100.0, 0.1);
+        fill(&mut m, -1.0, 101.0, 0.1);
+        assert_eq!((m.wins, m.losses), (0, 0));
+        assert_eq!(m.recent_trades.profit_factor(1), None);
+        // Final exit books a single trade: 2 realized less 0.4 fees
+        fill(&mut m, -1.0, 101.0, 0.1);
+        assert_eq!((m.wins, m.losses), (1, 0));
+        assert!((m.total_win_amount - 1.6).abs() < 1e-9);
+        assert!((m.pnl - 2.0).abs() < 1e-9);
+
+        // A gross-flat round trip that only pays fees is a loss
+        fill(&mut m, 1.0, 100.0, 0.1);
+        fill(&mut m, -1.0, 100.0, 0.1);
+        assert_eq!((m.wins, m.losses), (1, 1));
+        assert!((m.avg_loss() - 0.2).abs() < 1e-9);
+
+        // A flip closes the long and opens a short in one fill
+        fill(&mut m, 1.0, 100.0, 0.0);
+        fill(&mut m, -2.0, 105.0, 0.0);
+        assert_eq!((m.wins, m.losses), (2, 1));
+        fill(&mut m, 1.0, 104.0, 0.0);
+        assert_eq!((m.wins, m.losses), (3, 1));
+    }
+
+    #[test]
+    fn live_metrics_losing_sequence_has_negative_expectancy() {
+        let mut m = MetricsState::default();
+        for pnl in [5.0, -15.0, -15.0, 5.0] {
+            m.record_trade(pnl);
+        }
+        assert!((m.win_rate() - 0.5).abs() < 1e-12);
+        assert!((m.payoff_ratio() - 1.0 / 3.0).abs() < 1e-12);
+        assert!((m.expectancy() + 5.0).abs() < 1e-9);
+    }
+
     #[test]
     fn apply_fill_charges_fee() {
         let mut p = PortfolioState {
@@ -210,6 +482,7 @@ mod tests {
             position: 0.0,
             entry_price: 0.0,
             equity: 100.0,
+            contract: ContractKind::Linear,
         };
         let realized = p.apply_fill(Fill {
             price: 10.0,
@@ -243,6 +516,7 @@ mod tests {
             position: 0.0,
             entry_price: 0.0,
             equity: 1000.0,
+            contract: ContractKind::Linear,
         };
         p.apply_fill(Fill {
             price: 100.0,
@@ -262,6 +536,7 @@ mod tests {
             position: 0.0,
             entry_price: 0.0,
             equity: 1000.0,
+            contract: ContractKind::Linear,
         };
         p.apply_fill(Fill {
             price: 100.0,
@@ -280,6 +555,7 @@ mod tests {
             position: 1.0,
             entry_price: 100.0,
             equity: 1000.0,
+            contract: ContractKind::Linear,
         };
         let realized = p.apply_fill(Fill {
             price: 110.0,
@@ -300,6 +576,7 @@ mod tests {
             position: -1.0,
             entry_price: 100.0,
             equity: 1000.0,
+            contract: ContractKind::Linear,
         };
         let realized = p.apply_fill(Fill {
             price: 90.0,
@@ -320,6 +597,7 @@ mod tests {
             position: 1.0,
             entry_price: 100.0,
             equity: 1000.0,
+            contract: ContractKind::Linear,
         };
         let realized = p.apply_fill(Fill {
             price: 105.0,
@@ -341,6 +619,7 @@ mod tests {
             position: 1.0,
             entry_price: 100.0,
             equity: 1000.0,
+            contract: ContractKind::Linear,
         };
         p.apply_fill(Fill {
             price: 120.0,
@@ -365,6 +644,7 @@ mod tests {
             position: 2.0,
             entry_price: 100.0,
             equity: 1000.0,
+            contract: ContractKind::Linear,
         };
         let realized = p.apply_fill(Fill {
             price: 110.0,
@@ -386,6 +666,7 @@ mod tests {
             position: 1.0,
             entry_price: 100.0,
             equity: 1100.0,
+            contract: ContractKind::Linear,
         };
         let realized = p.apply_fill(Fill {
             price: 200.0,
@@ -406,6 +687,7 @@ mod tests {
             position: 0.0,
             entry_price: 0.0,
             equity: 10000.0,
+            contract: ContractKind::Linear,
         };
         let fills = vec![
             (100.0, 1.0),
@@ -427,22 +709,286 @@ mod tests {
             assert_equity_invariant(&p, price);
         }
     }
+
+    // ======================================================================
+    // Linear vs inverse contracts
+    // ======================================================================
+
+    fn flat(contract: ContractKind, cash: f64) -> PortfolioState {
+        PortfolioState {
+            cash,
+            position: 0.0,
+            entry_price: 0.0,
+            equity: cash,
+            contract,
+        }
+    }
+
+    fn round_trip(p: &mut PortfolioState, qty: f64, entry: f64, exit: f64) -> f64 {
+        p.apply_fill(Fill {
+            price: entry,
+            qty,
+            fee: 0.0,
+            ts: 0,
+        });
+        p.apply_fill(Fill {
+            price: exit,
+            qty: -qty,
+            fee: 0.0,
+            ts: 1,
+        })
+    }
+
+    #[test]
+    fn linear_vs_inverse_round_trip_long() {
+        // Linear: 1 BTC long, 100 -> 110 earns 10 USDT
+        let mut lin = flat(ContractKind::Linear, 1000.0);
+        let lin_pnl = round_trip(&mut lin, 1.0, 100.0, 110.0);
+        assert!((lin_pnl - 10.0).abs() < 1e-9, "linear pnl={}", lin_pnl);
+        assert!((lin.equity - 1010.0).abs() < 1e-9);
+
+        // Inverse: 100 USD of contracts, 100 -> 110 earns 100*(1/100 - 1/110) coin
+        let mut inv = flat(ContractKind::Inverse, 10.0);
+        let inv_pnl = round_trip(&mut inv, 100.0, 100.0, 110.0);
+        let expected = 100.0 * (1.0 / 100.0 - 1.0 / 110.0);
+        assert!(
+            (inv_pnl - expected).abs() < 1e-12,
+            "inverse pnl={}",
+            inv_pnl
+        );
+        assert!((inv.equity - (10.0 + expected)).abs() < 1e-12);
+        assert_eq!(inv.position, 0.0);
+
+        // Same USD exposure at entry: coin payoff valued at exit equals the linear payoff,
+        // but in coin terms it is smaller than the naive lin_pnl / entry_price.
+        assert!((inv_pnl * 110.0 - lin_pnl).abs() < 1e-9);
+        assert!(inv_pnl < lin_pnl / 100.0);
+    }
+
+    #[test]
+    fn linear_vs_inverse_round_trip_short() {
+        let mut lin = flat(ContractKind::Linear, 1000.0);
+        let lin_pnl = round_trip(&mut lin, -1.0, 100.0, 90.0);
+        assert!((lin_pnl - 10.0).abs() < 1e-9);
+
+        let mut inv = flat(ContractKind::Inverse, 10.0);
+        let inv_pnl = round_trip(&mut inv, -100.0, 100.0, 90.0);
+        let expected = -100.0 * (1.0 / 100.0 - 1.0 / 90.0);
+        assert!(
+            (inv_pnl - expected).abs() < 1e-12,
+            "inverse pnl={}",
+            inv_pnl
+        );
+        assert!(inv_pnl > 0.0);
+    }
+
+    #[test]
+    fn inverse_scale_in_then_close_realizes_exact_pnl() {
+        let mut inv = flat(ContractKind::Inverse, 10.0);
+        for (qty, price) in [(100.0, 100.0), (100.0, 50.0), (-200.0, 80.0)] {
+            inv.apply_fill(Fill {
+                price,
+                qty,
+                fee: 0.0,
+                ts: 0,
+            });
+        }
+        // Leg by leg: 100*(1/100 - 1/80) + 100*(1/50 - 1/80) = -0.25 + 0.75 = 0.5 coin
+        let expected: f64 = 100.0 * (1.0 / 100.0 - 1.0 / 80.0) + 100.0 * (1.0 / 50.0 - 1.0 / 80.0);
+        assert!((expected - 0.5).abs() < 1e-12);
+        assert_eq!(inv.position, 0.0);
+        assert!(
+            (inv.equity - (10.0 + expected)).abs() < 1e-12,
+            "equity={}",
+            inv.equity
+        );
+
+        // The realized figure on the close agrees with the cash movement
+        let mut inv = flat(ContractKind::Inverse, 10.0);
+        for (qty, price) in [(100.0, 100.0), (100.0, 50.0)] {
+            inv.apply_fill(Fill {
+                price,
+                qty,
+                fee: 0.0,
+                ts: 0,
+            });
+        }
+        // Harmonic mean of 100 and 50, weighted by USD size
+        assert!(
+            (inv.entry_price - 200.0 / 3.0).abs() < 1e-9,
+            "entry={}",
+            inv.entry_price
+        );
+        let realized = inv.apply_fill(Fill {
+            price: 80.0,
+            qty: -200.0,
+            fee: 0.0,
+            ts: 1,
+        });
+        assert!((realized - expected).abs() < 1e-12, "realized={}", realized);
+    }
+
+    #[test]
+    fn inverse_equity_and_exposure_mark_in_coin() {
+        let mut inv = flat(ContractKind::Inverse, 1.0);
+        inv.apply_fill(Fill {
+            price: 50_000.0,
+            qty: 10_000.0,
+            fee: 0.0,
+            ts: 0,
+        });
+        // 10k USD at 50k = 0.2 coin notional on 1 coin equity
+        assert!((inv.exposure(50_000.0) - 0.2).abs() < 1e-12);
+        // Unrealized at 40k: 10k * (1/50k - 1/40k) = -0.05 coin
+        let upnl = inv.unrealized_pnl(40_000.0);
+        assert!((upnl + 0.05).abs() < 1e-12, "upnl={}", upnl);
+        assert!((inv.mark_equity(40_000.0) - 0.95).abs() < 1e-12);
+
+        let mut lin = flat(ContractKind::Linear, 100_000.0);
+        lin.apply_fill(Fill {
+            price: 50_000.0,
+            qty: 0.2,
+            fee: 0.0,
+            ts: 0,
+        });
+        assert!((lin.exposure(50_000.0) - 0.1).abs() < 1e-12);
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyState {
     // Per-instance mutable state owned by the strategy.
     pub portfolio: PortfolioState,
     pub metrics: MetricsState,
     pub last_trade_ts: u64,
     pub last_loss_ts: u64,
+    /// Size of the most recent realized loss as a fraction of equity.
+    pub last_loss_pct: f64,
+    /// Entry tranches filled into the current position (scale-in ladder);
+    /// see `count_tranche`.
+    pub tranches: u32,
+    /// The current position has already been trimmed by a scale-out.
+    #[serde(default)]
+    pub scaled_out: bool,
+    /// EMA of the composite entry score when `score_smoothing_alpha` < 1
+    /// (None until the first scored bar).
+    pub smoothed_score: Option<f64>,
+    /// Signed qty lost to lot-step rounding (intended minus sent) since the
+    /// last `rounding_drift` report.
+    pub rounding_residual: f64,
+    /// First bar the current position was seen open (0 while flat), for the
+    /// global holding-period limit.
+    pub position_opened_ts: u64,
+    /// Branch that produced the latest non-Hold action ("" when none fired).
+    #[serde(skip)]
+    pub signal_reason: &'static str,
     pub trading_halted: bool,
+    /// Auto-disabled for a rolling profit factor below `pf_disable_min`.
+    /// Only closes pass; cleared by `reenable` (or a restart).
+    pub disabled: bool,
     pub trades_today: u32,
     pub trade_day: u64,
     pub order_seq: u64,
+    /// Order timestamps within the last hour (oldest first) for the trade-rate guard
+    pub recent_trade_ts: VecDeque<u64>,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
+const TRADE_RATE_WINDOW_SECS: u64 = 3600;
+
+impl StrategyState {
+    /// Flat, fresh state funded with `capital` in the settlement currency.
+    pub fn with_capital(capital: f64, contract: ContractKind) -> Self {
+        Self {
+            portfolio: PortfolioState {
+                cash: capital,
+                position: 0.0,
+                entry_price: 0.0,
+                equity: capital,
+                contract,
+            },
+            metrics: MetricsState::default(),
+            last_trade_ts: 0,
+            last_loss_ts: 0,
+            last_loss_pct: 0.0,
+            tranches: 0,
+            scaled_out: false,
+            smoothed_score: None,
+            rounding_residual: 0.0,
+            position_opened_ts: 0,
+            signal_reason: "",
+            trading_halted: false,
+            disabled: false,
+            trades_today: 0,
+            trade_day: 0,
+            order_seq: 0,
+            recent_trade_ts: VecDeque::new(),
+        }
+    }
+
+    /// Keep the scale-in ladder in step with what actually filled. Called
+    /// after each fill with the position it started from; `first_fill` is
+    /// true for an order's first fill, so partial fills of one tranche count
+    /// once. Opening (or flipping) starts the ladder at one tranche, adding
+    /// to the position is the next tranche, flat resets it and reducing
+    /// leaves it alone.
+    pub fn count_tranche(&mut self, position_before: f64, first_fill: bool) {
+        let after = self.portfolio.position;
+        if after.abs() <= 1e-9 {
+            self.tranches = 0;
+        } else if position_before.abs() <= 1e-9 || after.signum() != position_before.signum() {
+            self.tranches = 1;
+        } else if first_fill && after.abs() > position_before.abs() {
+            self.tranches = self.tranches.max(1) + 1;
+        }
+    }
+
+    /// Remember a realized loss at `ts` for the loss cooldown, sized against
+    /// current equity.
+    pub fn record_loss(&mut self, ts: u64, realized: f64) {
+        self.last_loss_ts = ts;
+        self.last_loss_pct = if self.portfolio.equity > 0.0 {
+            -realized / self.portfolio.equity
+        } else {
+            0.0
+        };
+    }
+
+    /// Record an order placed at `ts` in the rolling trade-rate window.
+    pub fn record_trade_ts(&mut self, ts: u64) {
+        self.prune_trade_window(ts);
+        self.recent_trade_ts.push_back(ts);
+    }
+
+    /// Number of orders placed in the hour ending at `now`.
+    pub fn trades_last_hour(&mut self, now: u64) -> u32 {
+        self.prune_trade_window(now);
+        self.recent_trade_ts.len() as u32
+    }
+
+    /// True when another order at `now` would exceed `max_per_hour` (0 disables).
+    pub fn trade_rate_exceeded(&mut self, now: u64, max_per_hour: u32) -> bool {
+        max_per_hour > 0 && self.trades_last_hour(now) >= max_per_hour
+    }
+
+    /// Operator re-enable after a profit-factor disable. The trade window is
+    /// cleared so the strategy gets a fresh K trades before being judged again.
+    pub fn reenable(&mut self) {
+        self.disabled = false;
+        self.metrics.recent_trades.clear();
+    }
+
+    fn prune_trade_window(&mut self, now: u64) {
+        while let Some(&ts) = self.recent_trade_ts.front() {
+            if now.saturating_sub(ts) >= TRADE_RATE_WINDOW_SECS {
+                self.recent_trade_ts.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
 pub struct MetricsState {
     pub wins: u64,
     pub losses: u64,
@@ -453,34 +999,147 @@ pub struct MetricsState {
     pub n: u64,
     pub mean: f64,
     pub m2: f64,
+    // Losing bars and their summed squared return, for Sortino's downside
+    // deviation.
+    pub down_n: u64,
+    #[serde(default)]
+    pub down_sq: f64,
     // Expectancy tracking
     pub total_win_amount: f64,
     pub total_loss_amount: f64,
+    /// Equity at the previous metrics update, for bar-over-bar returns.
+    pub last_equity: f64,
+    /// Streaming 99th percentile of per-bar equity loss (fraction).
+    pub loss_quantile: P2Quantile,
+    /// Net-of-fee PnL of the most recent round trips.
+    pub recent_trades: TradeWindow,
+    /// Realized PnL less fees of the round trip still open.
+    #[serde(default)]
+    pub open_trip_pnl: f64,
+    /// Position held at the previous metrics update.
+    pub last_position: f64,
+    /// Per-bar equity loss per unit of position, for CVaR sizing.
+    pub unit_losses: TailWindow,
+    /// UTC day (days since epoch) the daily figures below cover; 0 until
+    /// `MetricsEngine::roll_day` first runs.
+    pub day: u64,
+    /// Lifetime totals when `day` began; daily figures are the difference.
+    pub day_start_pnl: f64,
+    pub day_start_wins: u64,
+    pub day_start_losses: u64,
+    /// Intraday equity high-water mark and worst drawdown from it.
+    pub day_peak: f64,
+    pub day_max_drawdown: f64,
 }
 
 impl MetricsState {
-    /// Calculate expectancy per trade
-    pub fn expectancy(&self) -> f64 {
+    /// Carry a persisted high-water mark and worst drawdown into this state.
+    /// Keeps whichever is more extreme, so restoring never loosens either.
+    pub fn restore_high_water(&mut self, equity_peak: f64, max_drawdown: f64) {
+        if equity_peak.is_finite() && equity_peak > self.equity_peak {
+            self.equity_peak = equity_peak;
+        }
+        if max_drawdown.is_finite() && max_drawdown < self.max_drawdown {
+            self.max_drawdown = max_drawdown;
+        }
+    }
+
+    /// 99th percentile per-bar loss as a fraction of equity; 0 before any bars.
+    pub fn loss_p99(&self) -> f64 {
+        self.loss_quantile.value().unwrap_or(0.0)
+    }
+
+    /// 95% CVaR of per-bar loss per unit of position; None until enough
+    /// bars have been spent holding a position.
+    pub fn cvar_per_unit(&self) -> Option<f64> {
+        self.unit_losses.cvar(0.95)
+    }
+
+    /// Per-bar Sharpe: mean / sample std of bar returns (not annualized);
+    /// 0 until two bars.
+    pub fn sharpe(&self) -> f64 {
+        if self.n < 2 || self.m2 <= 0.0 {
+            return 0.0;
+        }
+        self.mean / (self.m2 / (self.n - 1) as f64).sqrt()
+    }
+
+    /// Per-bar Sortino: mean return over the downside deviation
+    /// `sqrt(mean(min(r, 0)^2))` taken across every bar, so upside swings
+    /// don't count against the strategy and steady equal losses still do.
+    /// 0 until there is a losing bar.
+    pub fn sortino(&self) -> f64 {
+        if self.n == 0 || self.down_sq <= 0.0 {
+            return 0.0;
+        }
+        self.mean / (self.down_sq / self.n as f64).sqrt()
+    }
+
+    pub fn win_rate(&self) -> f64 {
         let total = self.wins + self.losses;
         if total == 0 {
             return 0.0;
         }
-        let win_rate = self.wins as f64 / total as f64;
-        let avg_win = if self.wins > 0 {
+        self.wins as f64 / total as f64
+    }
+
+    pub fn avg_win(&self) -> f64 {
+        if self.wins > 0 {
             self.total_win_amount / self.wins as f64
         } else {
             0.0
-        };
-        let avg_loss = if self.losses > 0 {
+        }
+    }
+
+    /// Average losing trade as a positive amount.
+    pub fn avg_loss(&self) -> f64 {
+        if self.losses > 0 {
             self.total_loss_amount / self.losses as f64
         } else {
             0.0
-        };
-        (win_rate * avg_win) - ((1.0 - win_rate) * avg_loss)
+        }
+    }
+
+    /// Calculate expectancy per trade
+    pub fn expectancy(&self) -> f64 {
+        if self.wins + self.losses == 0 {
+            return 0.0;
+        }
+        let win_rate = self.win_rate();
+        (win_rate * self.avg_win()) - ((1.0 - win_rate) * self.avg_loss())
+    }
+
+    /// Average win over average loss; 0 until both sides have a trade.
+    pub fn payoff_ratio(&self) -> f64 {
+        if self.wins == 0 || self.losses == 0 {
+            return 0.0;
+        }
+        self.avg_win() / self.avg_loss()
     }
 
-    /// Record a trade outcome
+    /// Record a closed round trip's outcome
+    #[allow(dead_code)]
     pub fn record_trade(&mut self, pnl: f64) {
+        self.pnl += pnl;
+        self.book_trip(pnl);
+    }
+
+    /// Record one fill. `realized` counts toward `pnl` as it lands; wins,
+    /// losses and `recent_trades` get one net-of-fee figure per round trip,
+    /// booked by the fill that leaves the position flat or flips it.
+    pub fn record_fill(&mut self, realized: f64, fill: &crate::state::Fill, position_after: f64) {
+        let before = position_after - fill.qty;
+        self.pnl += realized;
+        self.open_trip_pnl += realized - fill.fee;
+        let closed = before.abs() > 1e-9
+            && (position_after.abs() <= 1e-9 || position_after.signum() != before.signum());
+        if closed {
+            let net = std::mem::take(&mut self.open_trip_pnl);
+            self.book_trip(net);
+        }
+    }
+
+    fn book_trip(&mut self, pnl: f64) {
         if pnl > 0.0 {
             self.wins += 1;
             self.total_win_amount += pnl;
@@ -488,7 +1147,13 @@ impl MetricsState {
             self.losses += 1;
             self.total_loss_amount += pnl.abs();
         }
-        self.pnl += pnl;
+        self.recent_trades.push(pnl);
+    }
+
+    /// Gross profit over gross loss across the last `k` trades; None until
+    /// `k` trades have closed.
+    pub fn rolling_profit_factor(&self, k: usize) -> Option<f64> {
+        self.recent_trades.profit_factor(k)
     }
 }
 
@@ -500,6 +1165,166 @@ pub enum Action {
     Close,
 }
 
+/// How an order should meet the book: patient orders may rest as a maker
+/// limit, urgent ones cross the spread as a taker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Patient,
+    Urgent,
+}
+
+impl Action {
+    /// True when the order only shrinks `position` toward flat: a close, or
+    /// the opposite side for no more than the open size. Flips and entries
+    /// are not reducing.
+    pub fn reduces(self, position: f64) -> bool {
+        match self {
+            Action::Close => position != 0.0,
+            Action::Buy { qty } => position < 0.0 && qty <= position.abs() + 1e-9,
+            Action::Sell { qty } => position > 0.0 && qty <= position + 1e-9,
+            Action::Hold => false,
+        }
+    }
+
+    /// `self` when it reduces `position`, otherwise Hold: what a strategy
+    /// blocked from entering may still send.
+    pub fn exits_only(self, position: f64) -> Action {
+        if self.reduces(position) {
+            self
+        } else {
+            Action::Hold
+        }
+    }
+
+    /// Entries and adds can wait for a better price; anything that reduces
+    /// `position` (stops, kill switches, max hold, signal closes, partial
+    /// scale-outs) must get out now.
+    pub fn urgency(self, position: f64) -> Urgency {
+        if self.reduces(position) {
+            Urgency::Urgent
+        } else {
+            Urgency::Patient
+        }
+    }
+}
+
+/// Which exit path closed a position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExitReason {
+    Stop,
+    Take,
+    Time,
+    Signal,
+    /// Force-closed when the data ran out.
+    EndOfData,
+}
+
+/// Price-trend bucket over a lookback window; labels match the
+/// `price_trend` field reported by `regime::classify_dataset`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrendRegime {
+    StrongBull,
+    MildBull,
+    Ranging,
+    MildBear,
+    StrongBear,
+}
+
+impl TrendRegime {
+    pub fn from_change_pct(change_pct: f64) -> Self {
+        if change_pct > 10.0 {
+            TrendRegime::StrongBull
+        } else if change_pct > 2.0 {
+            TrendRegime::MildBull
+        } else if change_pct < -10.0 {
+            TrendRegime::StrongBear
+        } else if change_pct < -2.0 {
+            TrendRegime::MildBear
+        } else {
+            TrendRegime::Ranging
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrendRegime::StrongBull => "strong_bull",
+            TrendRegime::MildBull => "mild_bull",
+            TrendRegime::Ranging => "ranging",
+            TrendRegime::MildBear => "mild_bear",
+            TrendRegime::StrongBear => "strong_bear",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "strong_bull" => Some(TrendRegime::StrongBull),
+            "mild_bull" => Some(TrendRegime::MildBull),
+            "ranging" => Some(TrendRegime::Ranging),
+            "mild_bear" => Some(TrendRegime::MildBear),
+            "strong_bear" => Some(TrendRegime::StrongBear),
+            _ => None,
+        }
+    }
+}
+
+/// Runtime regime switcher: classifies each symbol's trend over the last
+/// `lookback` closes and gates strategies to the regimes they are tagged
+/// for. Untagged strategies always run; tagged ones idle until the window
+/// fills. Idle strategies may still close positions, as when halted.
+#[derive(Debug, Clone)]
+pub struct RegimeRouter {
+    lookback: usize,
+    closes: HashMap<String, VecDeque<f64>>,
+    current: HashMap<String, TrendRegime>,
+}
+
+impl RegimeRouter {
+    pub fn new(lookback: usize) -> Self {
+        Self {
+            lookback: lookback.max(2),
+            closes: HashMap::new(),
+            current: HashMap::new(),
+        }
+    }
+
+    /// Feed a close; returns the regime when it changes.
+    pub fn on_close(&mut self, symbol: &str, close: f64) -> Option<TrendRegime> {
+        let window = self.closes.entry(symbol.to_string()).or_default();
+        window.push_back(close);
+        while window.len() > self.lookback {
+            window.pop_front();
+        }
+        let first = *window.front()?;
+        if window.len() < self.lookback || first <= 0.0 {
+            return None;
+        }
+        let regime = TrendRegime::from_change_pct((close - first) / first * 100.0);
+        match self.current.insert(symbol.to_string(), regime) {
+            Some(prev) if prev == regime => None,
+            _ => Some(regime),
+        }
+    }
+
+    pub fn regime(&self, symbol: &str) -> Option<TrendRegime> {
+        self.current.get(symbol).copied()
+    }
+
+    pub fn is_active(&self, symbol: &str, tags: &[TrendRegime]) -> bool {
+        tags.is_empty() || self.regime(symbol).is_some_and(|r| tags.contains(&r))
+    }
+
+    /// Pass `action` through for an active strategy; otherwise only a close.
+    pub fn gate(&self, symbol: &str, tags: &[TrendRegime], action: Action) -> Action {
+        match action {
+            Action::Close => Action::Close,
+            _ if self.is_active(symbol, tags) => action,
+            _ => Action::Hold,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Decision {
     pub action: Action,
@@ -515,12 +1340,117 @@ pub trait Strategy {
     fn aux_requirements(&self) -> AuxRequirements {
         AuxRequirements::default()
     }
+
+    /// Why `update` just returned `Close` for an open position.
+    fn exit_reason(&self, _market: MarketView, _state: &StrategyState) -> ExitReason {
+        ExitReason::Signal
+    }
+
+    /// The config this strategy reads its thresholds from, for hot reload;
+    /// None if it has nothing tunable.
+    fn config_mut(&mut self) -> Option<&mut crate::state::Config> {
+        None
+    }
+
+    /// How `action` should meet the book. Override to mark entries urgent
+    /// (e.g. a breakout that must not miss the move).
+    fn urgency(&self, action: Action, state: &StrategyState) -> Urgency {
+        action.urgency(state.portfolio.position)
+    }
 }
 
 #[cfg(test)]
 mod strategy_tests {
     use super::*;
 
+    fn router_in(change_pct: f64) -> RegimeRouter {
+        let mut router = RegimeRouter::new(3);
+        router.on_close("BTCUSDT", 100.0);
+        router.on_close("BTCUSDT", 100.0 + change_pct / 2.0);
+        router.on_close("BTCUSDT", 100.0 + change_pct);
+        router
+    }
+
+    #[test]
+    fn bear_tagged_strategy_active_in_strong_bear_idle_in_ranging() {
+        let tags = [TrendRegime::StrongBear];
+        let bear = router_in(-15.0);
+        assert_eq!(bear.regime("BTCUSDT"), Some(TrendRegime::StrongBear));
+        assert!(bear.is_active("BTCUSDT", &tags));
+        assert!(matches!(
+            bear.gate("BTCUSDT", &tags, Action::Sell { qty: 0.01 }),
+            Action::Sell { .. }
+        ));
+
+        let ranging = router_in(0.5);
+        assert_eq!(ranging.regime("BTCUSDT"), Some(TrendRegime::Ranging));
+        assert!(!ranging.is_active("BTCUSDT", &tags));
+        assert!(matches!(
+            ranging.gate("BTCUSDT", &tags, Action::Sell { qty: 0.01 }),
+            Action::Hold
+        ));
+        // Idle strategies can still flatten
+        assert!(matches!(
+            ranging.gate("BTCUSDT", &tags, Action::Close),
+            Action::Close
+        ));
+        // Untagged strategies run in every regime
+        assert!(ranging.is_active("BTCUSDT", &[]));
+    }
+
+    #[test]
+    fn regime_router_idles_tagged_strategies_until_warm_and_reports_changes() {
+        let mut router = RegimeRouter::new(3);
+        assert_eq!(router.on_close("BTCUSDT", 100.0), None);
+        assert!(!router.is_active("BTCUSDT", &[TrendRegime::Ranging]));
+        assert_eq!(router.on_close("BTCUSDT", 100.0), None);
+        assert_eq!(
+            router.on_close("BTCUSDT", 100.0),
+            Some(TrendRegime::Ranging)
+        );
+        assert_eq!(router.on_close("BTCUSDT", 100.5), None);
+        assert_eq!(
+            router.on_close("BTCUSDT", 80.0),
+            Some(TrendRegime::StrongBear)
+        );
+        assert_eq!(router.regime("ETHUSDT"), None);
+    }
+
+    #[test]
+    fn test_trade_rate_blocks_burst_within_hour() {
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
+        let max = 3;
+        for i in 0..max {
+            let ts = 10_000 + i as u64 * 60;
+            assert!(!state.trade_rate_exceeded(ts, max));
+            state.record_trade_ts(ts);
+        }
+        // N+1th order inside the same hour is blocked
+        assert!(state.trade_rate_exceeded(10_000 + 600, max));
+    }
+
+    #[test]
+    fn test_trade_rate_allows_spaced_trades() {
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
+        let max = 3;
+        // One order every 25 minutes: never more than 3 in a rolling hour
+        for i in 0..10 {
+            let ts = 10_000 + i as u64 * 1500;
+            assert!(!state.trade_rate_exceeded(ts, max), "blocked at i={}", i);
+            state.record_trade_ts(ts);
+        }
+        assert!(state.recent_trade_ts.len() <= max as usize);
+    }
+
+    #[test]
+    fn test_trade_rate_zero_disables() {
+        let mut state = StrategyState::with_capital(1000.0, ContractKind::Linear);
+        for i in 0..50 {
+            state.record_trade_ts(10_000 + i);
+        }
+        assert!(!state.trade_rate_exceeded(10_050, 0));
+    }
+
     fn make_aux(
         fetch_ts: u64,
         has_funding: bool,
@@ -538,6 +1468,7 @@ mod strategy_tests {
             has_borrow,
             has_liquidations,
             has_depeg,
+            next_funding_ts: 0,
         }
     }
 
@@ -47,7 +47,7 @@ pub fn assert_equity_consistency(
     mark_price: f64,
     tolerance: f64,
 ) -> Result<(), InvariantViolation> {
-    let expected = portfolio.cash + portfolio.position * mark_price;
+    let expected = portfolio.mark_equity(mark_price);
     if (portfolio.equity - expected).abs() > tolerance {
         return Err(InvariantViolation {
             msg: "equity not consistent with cash + position".to_string(),
@@ -55,3 +55,142 @@ pub fn assert_equity_consistency(
     }
     Ok(())
 }
+
+/// Conservation check across a single fill.
+///
+/// Marked at the fill price, equity after the fill must equal equity before it
+/// minus the fee, and the position must change by exactly the fill quantity.
+/// Marked at the entry price instead (cost basis), equity must move by the
+/// fill's `realized` PnL net of the fee, so a mis-booked realized PnL or entry
+/// price is caught as well.
+pub fn check_portfolio(
+    prev: &PortfolioState,
+    next: &PortfolioState,
+    fill: &Fill,
+    realized: f64,
+) -> Result<(), InvariantViolation> {
+    let eps = 1e-9 * prev.mark_equity(fill.price).abs().max(1.0);
+    let expected_pos = prev.position + fill.qty;
+    if (next.position - expected_pos).abs() > 1e-9 * expected_pos.abs().max(1.0) {
+        return Err(InvariantViolation {
+            msg: format!(
+                "position {} != prev {} + fill {}",
+                next.position, prev.position, fill.qty
+            ),
+        });
+    }
+    let expected = prev.mark_equity(fill.price) - fill.fee;
+    let actual = next.mark_equity(fill.price);
+    if (actual - expected).abs() > eps {
+        return Err(InvariantViolation {
+            msg: format!(
+                "equity not conserved across fill: expected {} got {}",
+                expected, actual
+            ),
+        });
+    }
+    let basis = |p: &PortfolioState| p.cash + p.contract.mark_value(p.position, p.entry_price);
+    let expected = basis(prev) + realized - fill.fee;
+    let actual = basis(next);
+    if (actual - expected).abs() > eps {
+        return Err(InvariantViolation {
+            msg: format!(
+                "cost basis moved by {} but realized {} net of fee {}",
+                actual - basis(prev),
+                realized,
+                fill.fee
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::ContractKind;
+
+    fn flat(cash: f64, contract: ContractKind) -> PortfolioState {
+        PortfolioState {
+            cash,
+            position: 0.0,
+            entry_price: 0.0,
+            equity: cash,
+            contract,
+        }
+    }
+
+    fn fill(price: f64, qty: f64, fee: f64) -> Fill {
+        Fill {
+            price,
+            qty,
+            fee,
+            ts: 0,
+        }
+    }
+
+    #[test]
+    fn correct_fills_conserve_equity() {
+        for contract in [ContractKind::Linear, ContractKind::Inverse] {
+            let mut p = flat(1_000.0, contract);
+            for f in [
+                fill(100.0, 2.0, 0.2),
+                fill(110.0, -3.0, 0.33),
+                fill(90.0, 1.0, 0.09),
+            ] {
+                let prev = p;
+                let realized = p.apply_fill(f);
+                assert!(
+                    check_portfolio(&prev, &p, &f, realized).is_ok(),
+                    "{:?}",
+                    contract
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn corrupted_fill_is_flagged() {
+        let prev = flat(1_000.0, ContractKind::Linear);
+        let f = fill(100.0, 2.0, 0.2);
+        let mut next = prev;
+        let realized = next.apply_fill(f);
+
+        let mut leaked_cash = next;
+        leaked_cash.cash += 5.0;
+        assert!(check_portfolio(&prev, &leaked_cash, &f, realized).is_err());
+
+        let mut wrong_pos = next;
+        wrong_pos.position = 1.0;
+        assert!(check_portfolio(&prev, &wrong_pos, &f, realized).is_err());
+    }
+
+    #[test]
+    fn close_with_profit_books_realized_net_of_fee() {
+        for contract in [ContractKind::Linear, ContractKind::Inverse] {
+            let mut open = flat(1_000.0, contract);
+            open.apply_fill(fill(100.0, 2.0, 0.2));
+
+            let close = fill(110.0, -2.0, 0.22);
+            let mut closed = open;
+            let realized = closed.apply_fill(close);
+            assert!(realized > 0.0, "{:?}", contract);
+            assert!(
+                check_portfolio(&open, &closed, &close, realized).is_ok(),
+                "{:?}",
+                contract
+            );
+            // Flat again: cash is the start plus the profit, less both fees
+            assert!((closed.cash - (1_000.0 + realized - 0.42)).abs() < 1e-9);
+
+            // A realized figure that drops the profit is flagged
+            assert!(check_portfolio(&open, &closed, &close, 0.0).is_err());
+            // So is a reduce that also moved the entry price
+            let mut half = open;
+            let partial = fill(110.0, -1.0, 0.11);
+            let realized = half.apply_fill(partial);
+            half.entry_price = 110.0;
+            assert!(check_portfolio(&open, &half, &partial, realized).is_err());
+        }
+    }
+}
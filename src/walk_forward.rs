@@ -31,6 +31,9 @@ pub struct StrategySummary {
     pub test_positive_windows: usize,
     pub total_windows: usize,
     pub test_mean_drawdown: f64,
+    /// Mean per-bar Sharpe / Sortino over the test windows.
+    pub test_mean_sharpe: f64,
+    pub test_mean_sortino: f64,
     /// Raw p-value: fraction of windows where test PnL <= 0
     pub p_value: f64,
     /// Bonferroni-corrected significance (p_value * num_comparisons < alpha)
@@ -149,6 +152,7 @@ pub fn walk_forward(
         let mut train_pnls = Vec::new();
         let mut test_pnls = Vec::new();
         let mut test_dds = Vec::new();
+        let (mut test_sharpe, mut test_sortino) = (0.0, 0.0);
         let mut test_positive = 0usize;
 
         for w in &windows {
@@ -158,6 +162,8 @@ pub fn walk_forward(
             if let Some(test_s) = w.test.strategies.get(s_idx) {
                 test_pnls.push(test_s.pnl);
                 test_dds.push(test_s.max_drawdown);
+                test_sharpe += test_s.sharpe;
+                test_sortino += test_s.sortino;
                 if test_s.pnl > 0.0 {
                     test_positive += 1;
                 }
@@ -191,6 +197,8 @@ pub fn walk_forward(
             test_positive_windows: test_positive,
             total_windows: test_pnls.len(),
             test_mean_drawdown: test_mean_dd,
+            test_mean_sharpe: test_sharpe / n,
+            test_mean_sortino: test_sortino / n,
             p_value,
             survives_correction: survives,
         });
@@ -1,4 +1,6 @@
-use arbitragefx::data::{analyze_csv, validate_schema, EXPECTED_COLUMNS};
+use arbitragefx::data::{analyze_csv, migrate_csv, resample, validate_schema, EXPECTED_COLUMNS};
+use arbitragefx::exchange::Candle;
+use arbitragefx::state::{check_granularity, detect_interval};
 use std::fs;
 use std::path::Path;
 use tempfile::TempDir;
@@ -36,6 +38,48 @@ fn schema_rejects_bad_header() {
     assert!(!report.ok);
 }
 
+#[test]
+fn migrates_legacy_ten_column_file() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("legacy.csv");
+    write_csv(
+        &path,
+        &EXPECTED_COLUMNS[..10],
+        &[
+            "1000,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0",
+            "1060,1,2,0.5,1.5,10,0.0001,0.0,0.0,0.0",
+        ],
+    );
+    assert!(!validate_schema(&path).unwrap().ok);
+
+    let report = migrate_csv(&path).unwrap();
+    assert!(report.migrated);
+    assert_eq!(report.rows, 2);
+    assert!(report
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("oi_backfilled")));
+    assert!(validate_schema(&path).unwrap().ok);
+    let body = fs::read_to_string(&path).unwrap();
+    assert!(body.contains("1060,1,2,0.5,1.5,10,0.0001,0.0,0.0,0.0,0.0\n"));
+    let (manifest, _) = analyze_csv(&path, 60, 3600, 1060).unwrap();
+    assert_eq!(manifest.row_count, 2);
+    assert_eq!(manifest.bad_rows, 0);
+
+    // Second run is a no-op and leaves the bytes alone
+    let again = migrate_csv(&path).unwrap();
+    assert!(!again.migrated);
+    assert_eq!(fs::read_to_string(&path).unwrap(), body);
+}
+
+#[test]
+fn migration_refuses_unknown_header() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("odd.csv");
+    write_csv(&path, &["ts", "open", "high", "low"], &["1000,1,2,0.5"]);
+    assert!(migrate_csv(&path).is_err());
+}
+
 #[test]
 fn detects_gaps_and_staleness() {
     let dir = TempDir::new().unwrap();
@@ -52,3 +96,118 @@ fn detects_gaps_and_staleness() {
     assert_eq!(manifest.gaps.len(), 1);
     assert!(report.stale);
 }
+
+#[test]
+fn flags_config_granularity_that_disagrees_with_dataset() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("15m.csv");
+    write_csv(
+        &path,
+        &EXPECTED_COLUMNS,
+        &[
+            "0,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0",
+            "900,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0",
+            "1800,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0",
+            // One missing bar must not change the detected spacing
+            "3600,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0",
+            "4500,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0",
+        ],
+    );
+    let (manifest, report) = analyze_csv(&path, 300, 3600, 5000).unwrap();
+    assert_eq!(manifest.detected_interval_secs, Some(900));
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|w| w == "interval_mismatch: configured=300s detected=900s"),
+        "{:?}",
+        report.warnings
+    );
+
+    assert!(check_granularity(300, Some(900)).is_err());
+    assert!(check_granularity(900, detect_interval(&[0, 900, 1800, 3600])).is_ok());
+    assert!(check_granularity(300, None).is_ok());
+}
+
+#[test]
+fn warns_on_jittery_timestamps() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("jitter.csv");
+    // Nominal 300s bars stamped a few seconds early or late about a third of the time
+    let jitter = [0i64, 0, 5, 0, -2, 0, 0, 3, 0, -4];
+    let rows: Vec<String> = (0..60)
+        .map(|i| {
+            let ts = 1_000 + i * 300 + jitter[i as usize % jitter.len()];
+            format!("{},1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0", ts)
+        })
+        .collect();
+    let rows: Vec<&str> = rows.iter().map(|r| r.as_str()).collect();
+    write_csv(&path, &EXPECTED_COLUMNS, &rows);
+
+    let (manifest, report) = analyze_csv(&path, 300, 3600, 20_000).unwrap();
+    assert_eq!(manifest.detected_interval_secs, Some(300));
+    assert!(manifest.irregular_intervals > 0);
+    // Jitter is not missing data
+    assert!(manifest.gaps.is_empty(), "{:?}", manifest.gaps);
+    assert!(
+        report
+            .warnings
+            .iter()
+            .any(|w| w.starts_with("irregular_intervals:")),
+        "{:?}",
+        report.warnings
+    );
+
+    // A clean series with one real gap does not trip it
+    let clean = dir.path().join("clean.csv");
+    let rows: Vec<String> = (0..60)
+        .filter(|&i| i != 30)
+        .map(|i| format!("{},1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0", 1_000 + i * 300))
+        .collect();
+    let rows: Vec<&str> = rows.iter().map(|r| r.as_str()).collect();
+    write_csv(&clean, &EXPECTED_COLUMNS, &rows);
+    let (manifest, report) = analyze_csv(&clean, 300, 3600, 20_000).unwrap();
+    assert_eq!(manifest.irregular_intervals, 0);
+    assert_eq!(manifest.gaps.len(), 1);
+    assert!(!report
+        .warnings
+        .iter()
+        .any(|w| w.starts_with("irregular_intervals:")));
+}
+
+fn minute_bar(ts: u64, o: f64, h: f64, l: f64, c: f64, v: f64) -> Candle {
+    Candle { ts, o, h, l, c, v }
+}
+
+#[test]
+fn five_one_minute_bars_make_one_five_minute_bar() {
+    let rows = vec![
+        // Partial bucket before the 5m boundary: dropped
+        minute_bar(540, 9.0, 9.5, 8.5, 9.2, 1.0),
+        minute_bar(600, 10.0, 10.5, 9.8, 10.2, 1.0),
+        minute_bar(660, 10.2, 11.0, 10.1, 10.9, 2.0),
+        minute_bar(720, 10.9, 10.9, 9.5, 9.7, 3.0),
+        minute_bar(780, 9.7, 10.0, 9.6, 9.9, 4.0),
+        minute_bar(840, 9.9, 10.3, 9.9, 10.1, 5.0),
+        // ...and after it
+        minute_bar(900, 10.1, 10.2, 10.0, 10.0, 6.0),
+    ];
+    let bars = resample(&rows, 60, 300).unwrap();
+    assert_eq!(bars, vec![minute_bar(600, 10.0, 11.0, 9.5, 10.1, 15.0)]);
+
+    // Same size is a copy
+    assert_eq!(resample(&rows, 60, 60).unwrap(), rows);
+}
+
+#[test]
+fn resample_refuses_upsampling_and_mismatched_input() {
+    let rows: Vec<Candle> = (0..10)
+        .map(|i| minute_bar(i * 300, 1.0, 1.0, 1.0, 1.0, 1.0))
+        .collect();
+    let err = resample(&rows, 300, 60).unwrap_err();
+    assert!(err.contains("upsample"), "{}", err);
+    // 5m bars claimed to be 1m
+    let err = resample(&rows, 60, 900).unwrap_err();
+    assert!(err.starts_with("interval_mismatch"), "{}", err);
+    assert!(resample(&rows, 300, 450).is_err());
+}
@@ -0,0 +1,72 @@
+use arbitragefx::data::{default_manifest_path, file_sha256, EXPECTED_COLUMNS};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn write_csv(path: &Path, rows: &[&str]) {
+    let mut out = EXPECTED_COLUMNS.join(",");
+    out.push('\n');
+    for row in rows {
+        out.push_str(row);
+        out.push('\n');
+    }
+    fs::write(path, out).unwrap();
+}
+
+fn run(path: &Path, extra: &[&str]) -> std::process::Output {
+    Command::new(env!("CARGO_BIN_EXE_manifest"))
+        .arg(path)
+        .args(["60", "3600"])
+        .args(extra)
+        .env_remove("DATA_MAX_BAD_ROWS")
+        .env_remove("DATA_MAX_GAPS")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn writes_manifest_for_good_csv() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("good.csv");
+    write_csv(
+        &path,
+        &[
+            "1000,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0",
+            "1060,1.5,2,1,1.8,12,0.0,0.0,0.0,0.0,0.0",
+            "1120,1.8,2.2,1.7,2.0,9,0.0,0.0,0.0,0.0,0.0",
+        ],
+    );
+    let out = run(&path, &[]);
+    assert!(out.status.success(), "{:?}", out);
+
+    let manifest_path = default_manifest_path(&path);
+    let raw = fs::read_to_string(&manifest_path).unwrap();
+    let payload: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    assert_eq!(payload["manifest"]["row_count"], 3);
+    assert_eq!(
+        payload["manifest"]["hash_sha256"],
+        file_sha256(&path).unwrap()
+    );
+    assert_eq!(payload["report"]["gaps"], 0);
+}
+
+#[test]
+fn exits_nonzero_when_gaps_exceed_threshold() {
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("gappy.csv");
+    write_csv(
+        &path,
+        &[
+            "1000,1,2,0.5,1.5,10,0.0,0.0,0.0,0.0,0.0",
+            "1300,1.5,2,1,1.8,12,0.0,0.0,0.0,0.0,0.0",
+        ],
+    );
+    let out = run(&path, &[]);
+    assert_eq!(out.status.code(), Some(5));
+    // Manifest is still written for inspection
+    assert!(default_manifest_path(&path).exists());
+
+    let out = run(&path, &["--max-gaps", "1"]);
+    assert!(out.status.success());
+}
@@ -0,0 +1,421 @@
+//! Adversarial fuzzing of the order/fill/risk reducer path.
+//!
+//! Generates seeded, well-typed event sequences (candles, submits, acks,
+//! fills, rejects, timers, halts) and folds them through the same pieces the
+//! live loop uses: `OrderBook` + order state machine, `PortfolioState::apply_fill`,
+//! `MetricsEngine` and the `RiskEngine` drawdown kill switch. After every
+//! event the invariants below must hold; a failing seed reproduces exactly.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use arbitragefx::metrics::MetricsEngine;
+use arbitragefx::reliability::state::OrderBook;
+use arbitragefx::risk::RiskEngine;
+use arbitragefx::state::{Config, Fill};
+use arbitragefx::strategy::{ContractKind, MetricsState, PortfolioState, StrategyState};
+use arbitragefx::verify::invariants::{assert_order_invariants, assert_portfolio_invariants};
+use arbitragefx::verify::order_sm::{Event as OrderEvent, OrderState};
+
+const MAX_OPEN: usize = 4;
+const ORDER_TIMEOUT_SECS: u64 = 600;
+const FUZZ_SEEDS: u64 = 200;
+const EVENTS_PER_SEED: usize = 400;
+
+/// xorshift64*: tiny, dependency-free and stable across toolchains.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n.max(1)
+    }
+
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Event {
+    Candle {
+        ts: u64,
+        close: f64,
+    },
+    Submit {
+        client: String,
+        qty: f64,
+    },
+    Ack {
+        client: String,
+    },
+    Fill {
+        client: String,
+        fill_id: String,
+        qty: f64,
+        price: f64,
+        fee: f64,
+    },
+    Reject {
+        client: String,
+    },
+    Timer {
+        ts: u64,
+    },
+    Halt,
+    Reset,
+}
+
+fn generate(seed: u64, len: usize) -> Vec<Event> {
+    let mut rng = Rng::new(seed);
+    let mut ts = 1_700_000_000u64;
+    let mut close = 50_000.0f64;
+    let mut clients: Vec<String> = Vec::new();
+    let mut events = Vec::with_capacity(len);
+    for _ in 0..len {
+        let client = |rng: &mut Rng, clients: &[String]| match clients.len() {
+            0 => "C-none".to_string(),
+            n => clients[rng.below(n as u64) as usize].clone(),
+        };
+        let event = match rng.below(100) {
+            0..=29 => {
+                ts += 1 + rng.below(600);
+                // Mostly a random walk, occasionally a violent gap
+                let step = if rng.below(20) == 0 {
+                    rng.unit() * 1.8 - 0.9
+                } else {
+                    (rng.unit() - 0.5) * 0.02
+                };
+                close = (close * (1.0 + step)).max(1e-6);
+                Event::Candle { ts, close }
+            }
+            30..=44 => {
+                let id = format!("C-{}", clients.len());
+                clients.push(id.clone());
+                Event::Submit {
+                    client: id,
+                    qty: [0.001, 0.01, 0.5, 25.0][rng.below(4) as usize],
+                }
+            }
+            45..=57 => Event::Ack {
+                client: client(&mut rng, &clients),
+            },
+            58..=79 => {
+                let c = client(&mut rng, &clients);
+                // Reuse a small fill-id space so redeliveries happen
+                let fill_id = format!("{}-F{}", c, rng.below(3));
+                Event::Fill {
+                    client: c,
+                    fill_id,
+                    qty: [0.0, 0.0005, 0.001, 0.01, 0.5][rng.below(5) as usize],
+                    price: close * (1.0 + (rng.unit() - 0.5) * 0.1),
+                    fee: rng.unit() * 0.5,
+                }
+            }
+            80..=85 => Event::Reject {
+                client: client(&mut rng, &clients),
+            },
+            86..=94 => {
+                ts += rng.below(2 * ORDER_TIMEOUT_SECS);
+                Event::Timer { ts }
+            }
+            95..=97 => Event::Halt,
+            _ => Event::Reset,
+        };
+        events.push(event);
+    }
+    events
+}
+
+struct Model {
+    state: StrategyState,
+    book: OrderBook,
+    metrics: MetricsEngine,
+    risk: RiskEngine,
+    open: Vec<(String, u64)>,
+    submitted: usize,
+    now: u64,
+}
+
+impl Model {
+    fn new() -> Self {
+        let mut cfg = Config::from_env();
+        cfg.max_daily_loss_pct = 0.05;
+        cfg.max_drawdown_mult = 2.0;
+        cfg.kill_file = "/nonexistent/reducer-fuzz.kill".to_string();
+        Self {
+            state: StrategyState {
+                portfolio: PortfolioState {
+                    cash: 10_000.0,
+                    position: 0.0,
+                    entry_price: 0.0,
+                    equity: 10_000.0,
+                    contract: ContractKind::Linear,
+                },
+                metrics: MetricsState {
+                    equity_peak: 10_000.0,
+                    ..Default::default()
+                },
+                last_trade_ts: 0,
+                last_loss_ts: 0,
+                last_loss_pct: 0.0,
+                tranches: 0,
+                scaled_out: false,
+                smoothed_score: None,
+                rounding_residual: 0.0,
+                position_opened_ts: 0,
+                signal_reason: "",
+                trading_halted: false,
+                disabled: false,
+                trades_today: 0,
+                trade_day: 0,
+                order_seq: 0,
+                recent_trade_ts: Default::default(),
+            },
+            book: OrderBook::new(),
+            metrics: MetricsEngine::new(),
+            risk: RiskEngine::new(cfg),
+            open: Vec::new(),
+            submitted: 0,
+            now: 0,
+        }
+    }
+
+    fn close_order(&mut self, client: &str) {
+        let done = self
+            .book
+            .orders
+            .get(client)
+            .map(|o| o.state)
+            .is_some_and(|s| {
+                matches!(
+                    s,
+                    OrderState::Filled | OrderState::Canceled | OrderState::Rejected
+                )
+            });
+        if done {
+            self.open.retain(|(c, _)| c != client);
+        }
+    }
+
+    fn reduce(&mut self, event: &Event) {
+        match event {
+            Event::Candle { ts, close } => {
+                self.now = self.now.max(*ts);
+                self.metrics.update_with_price(&mut self.state, *close);
+                self.risk.enforce_drawdown_kill(&mut self.state, "fuzz");
+            }
+            Event::Submit { client, qty } => {
+                if self.state.trading_halted || self.open.len() >= MAX_OPEN {
+                    return;
+                }
+                self.book.ensure(client, *qty);
+                if self.book.apply(client, OrderEvent::Submit).is_ok() {
+                    self.open.push((client.clone(), self.now));
+                    self.submitted += 1;
+                }
+            }
+            Event::Ack { client } => {
+                let _ = self.book.apply(
+                    client,
+                    OrderEvent::Ack {
+                        order_id: format!("X-{}", client),
+                    },
+                );
+            }
+            Event::Fill {
+                client,
+                fill_id,
+                qty,
+                price,
+                fee,
+            } => {
+                if !self.book.orders.contains_key(client) {
+                    return;
+                }
+                let _ = self.book.apply(
+                    client,
+                    OrderEvent::Fill {
+                        fill_id: fill_id.clone(),
+                        qty: *qty,
+                        price: *price,
+                    },
+                );
+                // The venue's fill is real even if our order already timed out,
+                // but a redelivered fill id must never move the portfolio twice;
+                // this is the same gate `live_ops::process_fills` applies
+                if self.book.first_delivery(fill_id) {
+                    let fill = Fill {
+                        price: *price,
+                        qty: *qty,
+                        fee: *fee,
+                        ts: self.now,
+                    };
+                    let realized = self.state.portfolio.apply_fill(fill);
+                    self.state
+                        .metrics
+                        .record_fill(realized, &fill, self.state.portfolio.position);
+                    self.metrics.update_with_price(&mut self.state, *price);
+                }
+                self.close_order(client);
+            }
+            Event::Reject { client } => {
+                let _ = self.book.apply(
+                    client,
+                    OrderEvent::Reject {
+                        reason: "fuzz".to_string(),
+                    },
+                );
+                self.close_order(client);
+            }
+            Event::Timer { ts } => {
+                self.now = self.now.max(*ts);
+                let expired: Vec<String> = self
+                    .open
+                    .iter()
+                    .filter(|(_, placed)| self.now.saturating_sub(*placed) >= ORDER_TIMEOUT_SECS)
+                    .map(|(c, _)| c.clone())
+                    .collect();
+                for client in expired {
+                    let _ = self.book.apply(&client, OrderEvent::Timeout);
+                    self.close_order(&client);
+                }
+            }
+            Event::Halt => self.state.trading_halted = true,
+            Event::Reset => self.state.trading_halted = false,
+        }
+    }
+
+    fn state_hash(&self) -> u64 {
+        let mut h = DefaultHasher::new();
+        let p = &self.state.portfolio;
+        for v in [p.cash, p.position, p.entry_price, p.equity] {
+            v.to_bits().hash(&mut h);
+        }
+        let m = &self.state.metrics;
+        for v in [m.pnl, m.equity_peak, m.max_drawdown, m.total_win_amount] {
+            v.to_bits().hash(&mut h);
+        }
+        (m.wins, m.losses, self.state.trading_halted, self.now).hash(&mut h);
+        let mut orders: Vec<_> = self.book.orders.values().collect();
+        orders.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+        for o in orders {
+            o.client_id.hash(&mut h);
+            format!("{:?}", o.state).hash(&mut h);
+            o.filled_qty.to_bits().hash(&mut h);
+        }
+        h.finish()
+    }
+}
+
+/// Fold `events`, checking invariants after each one. Err names the first
+/// violated invariant and the event index.
+fn run(events: &[Event]) -> Result<u64, String> {
+    let mut model = Model::new();
+    for (i, event) in events.iter().enumerate() {
+        let was_halted = model.state.trading_halted;
+        model.reduce(event);
+        let fail = |msg: String| Err(format!("event {} {:?}: {}", i, event, msg));
+
+        if let Err(v) = assert_portfolio_invariants(&model.state.portfolio) {
+            return fail(v.msg);
+        }
+        if !model.state.portfolio.position.is_finite() || model.state.metrics.pnl.is_nan() {
+            return fail("non-finite position or pnl".to_string());
+        }
+        if was_halted && !model.state.trading_halted && !matches!(event, Event::Reset) {
+            return fail("halt cleared without reset".to_string());
+        }
+        if model.open.len() > MAX_OPEN || model.book.orders.len() > model.submitted {
+            return fail(format!(
+                "order count unbounded: open={} book={} submitted={}",
+                model.open.len(),
+                model.book.orders.len(),
+                model.submitted
+            ));
+        }
+        for order in model.book.orders.values() {
+            // A single fill may overshoot (venue rounding); nothing after it may
+            if order.state != OrderState::Filled {
+                if let Err(v) = assert_order_invariants(order.qty, order.filled_qty) {
+                    return fail(format!("{}: {}", order.client_id, v.msg));
+                }
+            }
+        }
+    }
+    Ok(model.state_hash())
+}
+
+#[test]
+fn fuzz_reducer_invariants_hold_across_seeds() {
+    for seed in 0..FUZZ_SEEDS {
+        let events = generate(seed, EVENTS_PER_SEED);
+        if let Err(err) = run(&events) {
+            panic!("seed {} violated an invariant: {}", seed, err);
+        }
+    }
+}
+
+#[test]
+fn fuzz_state_hash_is_a_pure_function_of_events() {
+    for seed in 0..20 {
+        let events = generate(seed, EVENTS_PER_SEED);
+        assert_eq!(generate(seed, EVENTS_PER_SEED).len(), events.len());
+        let a = run(&events).unwrap();
+        let b = run(&generate(seed, EVENTS_PER_SEED)).unwrap();
+        assert_eq!(a, b, "seed {} is not reproducible", seed);
+    }
+    // Different sequences should not collide in practice
+    assert_ne!(
+        run(&generate(1, EVENTS_PER_SEED)).unwrap(),
+        run(&generate(2, EVENTS_PER_SEED)).unwrap()
+    );
+}
+
+/// Regression: a fill redelivered after the order timed out. The order state
+/// machine ignores fills on a canceled order, so deduplication has to happen
+/// by fill id; applying the redelivery moved the position twice.
+#[test]
+fn regression_redelivered_fill_after_timeout_applies_once() {
+    let client = "C-0".to_string();
+    let fill = Event::Fill {
+        client: client.clone(),
+        fill_id: "C-0-F0".to_string(),
+        qty: 0.01,
+        price: 50_000.0,
+        fee: 0.1,
+    };
+    let events = vec![
+        Event::Candle {
+            ts: 1_000,
+            close: 50_000.0,
+        },
+        Event::Submit {
+            client: client.clone(),
+            qty: 0.01,
+        },
+        Event::Ack {
+            client: client.clone(),
+        },
+        Event::Timer { ts: 2_000 },
+        fill.clone(),
+        fill,
+    ];
+    let mut model = Model::new();
+    for e in &events {
+        model.reduce(e);
+    }
+    assert_eq!(model.book.orders[&client].state, OrderState::Canceled);
+    assert!((model.state.portfolio.position - 0.01).abs() < 1e-12);
+    assert!(run(&events).is_ok());
+}
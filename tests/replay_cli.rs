@@ -0,0 +1,92 @@
+use arbitragefx::reliability::wal::{Wal, WalEntry};
+use arbitragefx::state::{Config, StrategyInstance};
+use arbitragefx::storage::StateStore;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+fn write_wal(path: &Path) {
+    let mut wal = Wal::open(path.to_str().unwrap()).unwrap();
+    let fill = |ts, intent: &str, price, qty| WalEntry::Fill {
+        ts,
+        intent_id: intent.to_string(),
+        params_hash: "h".to_string(),
+        price,
+        qty,
+        fee: 0.0,
+        fsync: false,
+    };
+    // Superseded by the snapshot below
+    wal.append_entry(&fill(1, "I-mom-0-old", 90.0, 5.0))
+        .unwrap();
+    wal.append_entry(&WalEntry::Snapshot {
+        ts: 2,
+        strategy_id: "mom-0".to_string(),
+        cash: 900.0,
+        position: 1.0,
+        entry_price: 100.0,
+        equity: 1_050.0,
+        pnl: 50.0,
+        equity_peak: 1_050.0,
+        max_drawdown: 0.0,
+        position_opened_ts: 0,
+    })
+    .unwrap();
+    wal.append_entry(&fill(3, "I-mom-0-a", 110.0, 1.0)).unwrap();
+    wal.append_entry(&fill(4, "I-mom-1-b", 105.0, -0.5))
+        .unwrap();
+}
+
+fn run(dir: &TempDir, extra: &[&str]) -> (i32, String) {
+    let out = Command::new(env!("CARGO_BIN_EXE_replay"))
+        .arg(dir.path().join("wal.log"))
+        .args(extra)
+        .env_remove("SYMBOLS")
+        .env_remove("SYMBOL")
+        .env("LOG_DIR", dir.path().join("logs"))
+        .output()
+        .unwrap();
+    (
+        out.status.code().unwrap(),
+        String::from_utf8_lossy(&out.stdout).to_string(),
+    )
+}
+
+fn line_for<'a>(stdout: &'a str, id: &str) -> &'a str {
+    let prefix = format!("strategy={} ", id);
+    stdout.lines().find(|l| l.starts_with(&prefix)).unwrap()
+}
+
+#[test]
+fn replay_reproduces_final_positions() {
+    let dir = TempDir::new().unwrap();
+    write_wal(&dir.path().join("wal.log"));
+    let (code, stdout) = run(&dir, &[]);
+    assert_eq!(code, 0, "{}", stdout);
+    // Snapshot position 1 @ 100 plus 1 @ 110
+    let mom0 = line_for(&stdout, "mom-0");
+    assert!(mom0.contains("position=2 "), "{}", mom0);
+    assert!(mom0.contains("entry_price=105 "), "{}", mom0);
+    assert!(mom0.contains("pnl=50"), "{}", mom0);
+    let mom1 = line_for(&stdout, "mom-1");
+    assert!(mom1.contains("position=-0.5 "), "{}", mom1);
+    let mom2 = line_for(&stdout, "mom-2");
+    assert!(mom2.contains("position=0 "), "{}", mom2);
+}
+
+#[test]
+fn replay_flags_divergence_from_sqlite_snapshot() {
+    let dir = TempDir::new().unwrap();
+    write_wal(&dir.path().join("wal.log"));
+    let db = dir.path().join("state.sqlite");
+    {
+        let mut store = StateStore::new(db.to_str().unwrap()).unwrap();
+        store.init().unwrap();
+        let strategies = StrategyInstance::build_default_set(Config::from_env());
+        store.persist_snapshot(10, &strategies).unwrap();
+    }
+    let (code, stdout) = run(&dir, &["--db", db.to_str().unwrap()]);
+    assert_eq!(code, 3, "{}", stdout);
+    assert!(stdout.contains("compare strategy=mom-0 status=mismatch"));
+    assert!(stdout.contains("compare strategy=mom-2 status=match"));
+}